@@ -57,7 +57,8 @@ impl EventHandler<Self, ContractEventNotification> for AccountEventService {
         }
 
         for i in item.0 .1.as_ref() {
-            if watched_keys.contains(i.contract_event.key()) {
+            // the accepted tokens are kept, even if the accept event is retracted.
+            if !i.removed && watched_keys.contains(i.contract_event.key()) {
                 if let Err(e) = self.handle_contract_event(&i.contract_event) {
                     error!(
                         "fail to save accept token event: {:?}, err: {}",
@@ -4,23 +4,30 @@
 use crate::metrics::BlockRelayerMetrics;
 use anyhow::{ensure, format_err, Result};
 use futures::FutureExt;
-use network_api::messages::{CompactBlockMessage, NotificationMessage, PeerCompactBlockMessage};
-use network_api::{NetworkService, PeerId, PeerProvider, PeerSelector, PeerStrategy};
+use network_api::messages::{
+    CompactBlockMessage, NotificationMessage, PeerBlockAnnouncementMessage, PeerCompactBlockMessage,
+};
+use network_api::{NetworkService, PeerId, PeerInfo, PeerProvider, PeerSelector, PeerStrategy};
+use parking_lot::Mutex;
 use starcoin_chain::verifier::StaticVerifier;
 use starcoin_config::NodeConfig;
 use starcoin_config::G_CRATE_VERSION;
+use starcoin_consensus::Consensus;
 use starcoin_crypto::HashValue;
 use starcoin_logger::prelude::*;
+use starcoin_logger::tracer::{Span, TraceContext};
 use starcoin_network::NetworkServiceRef;
 use starcoin_network_rpc_api::GetTxnsWithHash;
 use starcoin_service_registry::{ActorService, EventHandler, ServiceContext, ServiceFactory};
+use starcoin_storage::{BlockStore, Storage};
 use starcoin_sync::block_connector::BlockConnectorService;
 use starcoin_sync::verified_rpc_client::VerifiedRpcClient;
 use starcoin_sync_api::PeerNewBlock;
 use starcoin_time_service::TimeService;
 use starcoin_txpool::TxPoolService;
 use starcoin_txpool_api::TxPoolSyncService;
-use starcoin_types::block::ExecutedBlock;
+use starcoin_types::block::{BlockHeader, ExecutedBlock};
+use starcoin_types::genesis_config::ConsensusStrategy;
 use starcoin_types::sync_status::SyncStatus;
 use starcoin_types::system_events::{NewBranch, SyncStatusChangeEvent};
 use starcoin_types::{
@@ -29,15 +36,60 @@ use starcoin_types::{
     system_events::NewHeadBlock,
     transaction::SignedUserTransaction,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::sync::Arc;
 
+/// A new block relayed by a peer.
+enum RelayedBlock {
+    Compact(CompactBlock),
+    /// The announced block header, the compact block is fetched from the peer.
+    Announced(BlockHeader),
+}
+
+impl RelayedBlock {
+    fn header(&self) -> &BlockHeader {
+        match self {
+            Self::Compact(compact_block) => &compact_block.header,
+            Self::Announced(header) => header,
+        }
+    }
+}
+
+/// The peers which announce a block being fetched.
+struct AnnouncedBlock {
+    pending: VecDeque<PeerId>,
+    tried: HashSet<PeerId>,
+}
+
+impl AnnouncedBlock {
+    fn new(peer_id: PeerId) -> Self {
+        Self {
+            pending: VecDeque::from(vec![peer_id]),
+            tried: HashSet::new(),
+        }
+    }
+
+    fn add_peer(&mut self, peer_id: PeerId) {
+        if !self.tried.contains(&peer_id) && !self.pending.contains(&peer_id) {
+            self.pending.push_back(peer_id);
+        }
+    }
+
+    fn next_peer(&mut self) -> Option<PeerId> {
+        let peer_id = self.pending.pop_front()?;
+        self.tried.insert(peer_id.clone());
+        Some(peer_id)
+    }
+}
+
 pub struct BlockRelayer {
     txpool: TxPoolService,
     sync_status: Option<SyncStatus>,
     time_service: Arc<dyn TimeService>,
     metrics: Option<BlockRelayerMetrics>,
+    consensus: ConsensusStrategy,
+    announced_blocks: Arc<Mutex<HashMap<HashValue, AnnouncedBlock>>>,
 }
 
 impl ServiceFactory<Self> for BlockRelayer {
@@ -49,7 +101,8 @@ impl ServiceFactory<Self> for BlockRelayer {
             .metrics
             .registry()
             .and_then(|registry| BlockRelayerMetrics::register(registry).ok());
-        Ok(Self::new(txpool, time_service, metrics))
+        let consensus = node_config.net().genesis_config().consensus();
+        Ok(Self::new(txpool, time_service, metrics, consensus))
     }
 }
 
@@ -58,12 +111,15 @@ impl BlockRelayer {
         txpool: TxPoolService,
         time_service: Arc<dyn TimeService>,
         metrics: Option<BlockRelayerMetrics>,
+        consensus: ConsensusStrategy,
     ) -> Self {
         Self {
             txpool,
             sync_status: None,
             time_service,
             metrics,
+            consensus,
+            announced_blocks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -197,48 +253,204 @@ impl BlockRelayer {
         Ok(block)
     }
 
+    fn observe_relay_time(&self, header: &BlockHeader) {
+        let block_timestamp = header.timestamp();
+        let current_timestamp = self.time_service.now_millis();
+        let time = current_timestamp.saturating_sub(block_timestamp);
+        let time_sec: f64 = (time as f64) / 1000_f64;
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.block_relay_time.observe(time_sec);
+        }
+        sl_info!(
+            "{action} {hash} {time_sec}",
+            time_sec = time_sec,
+            hash = header.id().to_hex(),
+            action = "block_relay_time",
+        );
+    }
+
+    /// Fill the compact block with the help of the peer which relays it.
+    async fn fill_block_from_peer(
+        txpool: TxPoolService,
+        network: NetworkServiceRef,
+        peer: PeerInfo,
+        compact_block: CompactBlock,
+        metrics: Option<BlockRelayerMetrics>,
+        span_context: Option<TraceContext>,
+    ) -> Result<Block> {
+        let peer_id = peer.peer_id();
+        let peer_selector = PeerSelector::new(vec![peer], PeerStrategy::default(), None);
+        let rpc_client = VerifiedRpcClient::new(peer_selector, network);
+        BlockRelayer::fill_compact_block_with_metrics(
+            txpool,
+            rpc_client,
+            compact_block,
+            peer_id,
+            metrics,
+            span_context,
+        )
+        .await
+    }
+
+    async fn fill_compact_block_with_metrics(
+        txpool: TxPoolService,
+        rpc_client: VerifiedRpcClient,
+        compact_block: CompactBlock,
+        peer_id: PeerId,
+        metrics: Option<BlockRelayerMetrics>,
+        span_context: Option<TraceContext>,
+    ) -> Result<Block> {
+        let _timer = metrics
+            .as_ref()
+            .map(|metrics| metrics.txns_filled_time.start_timer());
+        let _fill_span = Span::new("block_relayer.fill_compact_block", span_context);
+        BlockRelayer::fill_compact_block(txpool, rpc_client, compact_block, peer_id, metrics).await
+    }
+
+    /// Get the announced block from the announcers in turn, until a peer serves a block which
+    /// is filled correctly. The peers which announce the block while it is being fetched are
+    /// queued by `handle_block_announcement`, so they are tried too.
+    async fn fetch_announced_block(
+        txpool: TxPoolService,
+        network: NetworkServiceRef,
+        announced_blocks: Arc<Mutex<HashMap<HashValue, AnnouncedBlock>>>,
+        block_id: HashValue,
+        metrics: Option<BlockRelayerMetrics>,
+        span_context: Option<TraceContext>,
+    ) -> Result<(PeerId, Block)> {
+        // the peers have the block as head can serve it too.
+        let head_peers = network
+            .peer_set()
+            .await?
+            .into_iter()
+            .filter(|peer| peer.block_id() == block_id)
+            .map(|peer| peer.peer_id())
+            .collect::<Vec<_>>();
+        if let Some(announced_block) = announced_blocks.lock().get_mut(&block_id) {
+            for peer_id in head_peers {
+                announced_block.add_peer(peer_id);
+            }
+        }
+        loop {
+            let peer_id = announced_blocks
+                .lock()
+                .get_mut(&block_id)
+                .and_then(|announced_block| announced_block.next_peer())
+                .ok_or_else(|| format_err!("No peer serves the announced block {:?}", block_id))?;
+            let peer = match network.get_peer(peer_id.clone()).await? {
+                Some(peer) => peer,
+                None => {
+                    debug!(
+                        "[block-relay] Announcer {} of block {:?} is not connected",
+                        peer_id, block_id
+                    );
+                    continue;
+                }
+            };
+            let peer_selector = PeerSelector::new(vec![peer], PeerStrategy::default(), None);
+            let rpc_client = VerifiedRpcClient::new(peer_selector, network.clone());
+            let compact_block = match rpc_client
+                .get_compact_block(peer_id.clone(), block_id)
+                .await
+            {
+                Ok(Some(compact_block)) => compact_block,
+                Ok(None) => {
+                    debug!(
+                        "[block-relay] Peer {} has no announced block {:?}",
+                        peer_id, block_id
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        "[block-relay] Get announced block {:?} from peer {} error: {:?}",
+                        block_id, peer_id, e
+                    );
+                    continue;
+                }
+            };
+            match BlockRelayer::fill_compact_block_with_metrics(
+                txpool.clone(),
+                rpc_client,
+                compact_block,
+                peer_id.clone(),
+                metrics.clone(),
+                span_context,
+            )
+            .await
+            {
+                Ok(block) => return Ok((peer_id, block)),
+                Err(e) => warn!(
+                    "[block-relay] Fill announced block {:?} from peer {} error: {:?}",
+                    block_id, peer_id, e
+                ),
+            }
+        }
+    }
+
     fn handle_block_event(
         &self,
-        compact_block_msg: PeerCompactBlockMessage,
+        peer_id: PeerId,
+        relayed_block: RelayedBlock,
         ctx: &mut ServiceContext<BlockRelayer>,
     ) -> Result<()> {
         let network = ctx.get_shared::<NetworkServiceRef>()?;
         let block_connector_service = ctx.service_ref::<BlockConnectorService>()?.clone();
         let txpool = self.txpool.clone();
         let metrics = self.metrics.clone();
+        let announced_blocks = self.announced_blocks.clone();
         let fut = async move {
-            let compact_block = compact_block_msg.message.compact_block;
-            let peer_id = compact_block_msg.peer_id;
-            debug!("Receive peer compact block event from peer id:{}", peer_id);
-            let block_id = compact_block.header.id();
+            debug!("Receive peer new block event from peer id:{}", peer_id);
+            let block_id = relayed_block.header().id();
+            // the span is held across the awaits, so it is not entered.
+            let span = Span::new("block_relayer.receive_compact_block", None)
+                .with("block_hash", block_id)
+                .with("block_number", relayed_block.header().number())
+                .with("peer_id", &peer_id);
             if let Ok(Some((_, _, _, version))) =
                 txpool.get_store().get_failed_block_by_id(block_id)
             {
                 if version == *G_CRATE_VERSION {
                     warn!("Block is failed block : {:?}", block_id);
                 }
+                announced_blocks.lock().remove(&block_id);
             } else {
-                let peer = network.get_peer(peer_id.clone()).await?.ok_or_else(|| {
-                    format_err!(
-                        "CompatBlockMessage's peer {} is not connected",
-                        peer_id.clone()
-                    )
-                })?;
-                let peer_selector = PeerSelector::new(vec![peer], PeerStrategy::default(), None);
-                let rpc_client = VerifiedRpcClient::new(peer_selector, network);
-                let _timer = metrics
-                    .as_ref()
-                    .map(|metrics| metrics.txns_filled_time.start_timer());
-                let block = BlockRelayer::fill_compact_block(
-                    txpool.clone(),
-                    rpc_client,
-                    compact_block,
-                    peer_id.clone(),
-                    metrics,
-                )
-                .await?;
-
-                block_connector_service.notify(PeerNewBlock::new(peer_id, block))?;
+                let (peer_id, block) = match relayed_block {
+                    RelayedBlock::Compact(compact_block) => {
+                        let peer = network.get_peer(peer_id.clone()).await?.ok_or_else(|| {
+                            format_err!(
+                                "CompatBlockMessage's peer {} is not connected",
+                                peer_id.clone()
+                            )
+                        })?;
+                        let block = BlockRelayer::fill_block_from_peer(
+                            txpool.clone(),
+                            network,
+                            peer,
+                            compact_block,
+                            metrics,
+                            span.context(),
+                        )
+                        .await?;
+                        (peer_id, block)
+                    }
+                    RelayedBlock::Announced(_) => {
+                        let result = BlockRelayer::fetch_announced_block(
+                            txpool.clone(),
+                            network,
+                            announced_blocks.clone(),
+                            block_id,
+                            metrics,
+                            span.context(),
+                        )
+                        .await;
+                        announced_blocks.lock().remove(&block_id);
+                        result?
+                    }
+                };
+
+                block_connector_service
+                    .notify(PeerNewBlock::new(peer_id, block).with_trace_context(span.context()))?;
             }
             Ok(())
         };
@@ -249,6 +461,53 @@ impl BlockRelayer {
         }));
         Ok(())
     }
+
+    /// Check the proof of work of the announced header before fetching the block body, the
+    /// header's own difficulty is checked as the parent is not at hand.
+    fn verify_announced_header(&self, header: &BlockHeader) -> Result<()> {
+        // the dummy consensus of the test networks does not verify the header, as the chain.
+        if self.consensus == ConsensusStrategy::Dummy {
+            return Ok(());
+        }
+        self.consensus
+            .verify_header_difficulty(header.difficulty(), header)
+    }
+
+    fn handle_block_announcement(
+        &self,
+        announcement_msg: PeerBlockAnnouncementMessage,
+        ctx: &mut ServiceContext<BlockRelayer>,
+    ) -> Result<()> {
+        let storage = ctx.get_shared::<Arc<Storage>>()?;
+        let peer_id = announcement_msg.peer_id;
+        let header = announcement_msg.message.header;
+        let block_id = header.id();
+        if storage.get_block_header_by_hash(block_id)?.is_some() {
+            debug!(
+                "[block-relay] Ignore block announcement {:?}, the block already exists.",
+                block_id
+            );
+            return Ok(());
+        }
+        // The block is being fetched, the announcer is tried if the earlier ones fail to
+        // serve it.
+        if let Some(announced_block) = self.announced_blocks.lock().get_mut(&block_id) {
+            announced_block.add_peer(peer_id);
+            return Ok(());
+        }
+        self.verify_announced_header(&header).map_err(|e| {
+            format_err!(
+                "Announced block {:?} from peer {} has an invalid header: {:?}",
+                block_id,
+                peer_id,
+                e
+            )
+        })?;
+        self.announced_blocks
+            .lock()
+            .insert(block_id, AnnouncedBlock::new(peer_id.clone()));
+        self.handle_block_event(peer_id, RelayedBlock::Announced(header), ctx)
+    }
 }
 
 impl ActorService for BlockRelayer {
@@ -313,22 +572,14 @@ impl EventHandler<Self, PeerCompactBlockMessage> for BlockRelayer {
         compact_block_msg: PeerCompactBlockMessage,
         ctx: &mut ServiceContext<BlockRelayer>,
     ) {
-        let block_timestamp = compact_block_msg.message.compact_block.header.timestamp();
-        let current_timestamp = self.time_service.now_millis();
-        let time = current_timestamp.saturating_sub(block_timestamp);
-        let time_sec: f64 = (time as f64) / 1000_f64;
-        if let Some(metrics) = self.metrics.as_ref() {
-            metrics.block_relay_time.observe(time_sec);
-        }
-        sl_info!(
-            "{action} {hash} {time_sec}",
-            time_sec = time_sec,
-            hash = compact_block_msg.message.compact_block.header.id().to_hex(),
-            action = "block_relay_time",
-        );
+        self.observe_relay_time(&compact_block_msg.message.compact_block.header);
         //TODO should filter too old block?
 
-        if let Err(e) = self.handle_block_event(compact_block_msg, ctx) {
+        if let Err(e) = self.handle_block_event(
+            compact_block_msg.peer_id,
+            RelayedBlock::Compact(compact_block_msg.message.compact_block),
+            ctx,
+        ) {
             if let Some(metrics) = self.metrics.as_ref() {
                 metrics.txns_filled_failed_total.inc();
             }
@@ -339,3 +590,22 @@ impl EventHandler<Self, PeerCompactBlockMessage> for BlockRelayer {
         }
     }
 }
+
+impl EventHandler<Self, PeerBlockAnnouncementMessage> for BlockRelayer {
+    fn handle_event(
+        &mut self,
+        announcement_msg: PeerBlockAnnouncementMessage,
+        ctx: &mut ServiceContext<BlockRelayer>,
+    ) {
+        self.observe_relay_time(&announcement_msg.message.header);
+        if let Err(e) = self.handle_block_announcement(announcement_msg, ctx) {
+            if let Some(metrics) = self.metrics.as_ref() {
+                metrics.txns_filled_failed_total.inc();
+            }
+            error!(
+                "[block-relay] handle PeerBlockAnnouncementMessage error: {:?}",
+                e
+            );
+        }
+    }
+}
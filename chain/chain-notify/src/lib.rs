@@ -9,12 +9,15 @@ use starcoin_logger::prelude::*;
 use starcoin_service_registry::{ActorService, EventHandler, ServiceContext, ServiceFactory};
 use starcoin_storage::{Storage, Store};
 use starcoin_types::block::Block;
-use starcoin_types::system_events::NewHeadBlock;
+use starcoin_types::system_events::{ChainReorg, NewHeadBlock};
 use std::sync::Arc;
 
 /// ChainNotify watch `NewHeadBlock` message from bus,
 /// and then reproduce `Notification<ThinBlock>` and `Notification<Arc<[Event]>>` message to bus.
 /// User can subscribe the two notification to watch onchain events.
+/// It also reproduces `ChainReorg` as `Notification<Arc<ChainReorg>>`,
+/// the events of the retracted blocks as removed events,
+/// and the blocks and events of the enacted blocks before the new head.
 pub struct ChainNotifyHandlerService {
     store: Arc<dyn Store>,
 }
@@ -37,11 +40,13 @@ impl ServiceFactory<Self> for ChainNotifyHandlerService {
 impl ActorService for ChainNotifyHandlerService {
     fn started(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
         ctx.subscribe::<NewHeadBlock>();
+        ctx.subscribe::<ChainReorg>();
         Ok(())
     }
 
     fn stopped(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
         ctx.unsubscribe::<NewHeadBlock>();
+        ctx.unsubscribe::<ChainReorg>();
         Ok(())
     }
 }
@@ -58,12 +63,49 @@ impl EventHandler<Self, NewHeadBlock> for ChainNotifyHandlerService {
         self.notify_new_block(block, ctx);
 
         // notify events
-        if let Err(e) = self.notify_events(block, self.store.clone(), ctx) {
+        if let Err(e) = self.notify_events(block, self.store.clone(), false, ctx) {
             error!(target: "pubsub", "fail to notify events to client, err: {}", &e);
         }
     }
 }
 
+impl EventHandler<Self, ChainReorg> for ChainNotifyHandlerService {
+    fn handle_event(
+        &mut self,
+        item: ChainReorg,
+        ctx: &mut ServiceContext<ChainNotifyHandlerService>,
+    ) {
+        let reorg = Arc::new(item);
+        ctx.broadcast(Notification(reorg.clone()));
+
+        // notify the events of the retracted blocks as removed, from the old head to the ancestor.
+        for block_id in reorg.retracted.iter().rev() {
+            let result = self.store.get_block(*block_id).and_then(|block| {
+                let block =
+                    block.ok_or_else(|| format_err!("cannot find retracted block {}", block_id))?;
+                self.notify_events(&block, self.store.clone(), true, ctx)
+            });
+            if let Err(e) = result {
+                error!(target: "pubsub", "fail to notify removed events to client, err: {}", &e);
+            }
+        }
+
+        // notify the enacted blocks before the new head, the head is notified by `NewHeadBlock`.
+        let enacted_before_head = reorg.enacted.len().saturating_sub(1);
+        for block_id in reorg.enacted.iter().take(enacted_before_head) {
+            let result = self.store.get_block(*block_id).and_then(|block| {
+                let block =
+                    block.ok_or_else(|| format_err!("cannot find enacted block {}", block_id))?;
+                self.notify_new_block(&block, ctx);
+                self.notify_events(&block, self.store.clone(), false, ctx)
+            });
+            if let Err(e) = result {
+                error!(target: "pubsub", "fail to notify enacted block to client, err: {}", &e);
+            }
+        }
+    }
+}
+
 impl ChainNotifyHandlerService {
     pub fn notify_new_block(&self, block: &Block, ctx: &mut ServiceContext<Self>) {
         let thin_block = ThinBlock::new(
@@ -73,10 +115,12 @@ impl ChainNotifyHandlerService {
         ctx.broadcast(Notification(thin_block));
     }
 
+    /// Notify the events of `block`, `removed` if the block is retracted from the main chain.
     pub fn notify_events(
         &self,
         block: &Block,
         store: Arc<dyn Store>,
+        removed: bool,
         ctx: &mut ServiceContext<Self>,
     ) -> Result<()> {
         let block_number = block.header().number();
@@ -90,7 +134,7 @@ impl ChainNotifyHandlerService {
             // get events directly by txn_info_id
             let events = store.get_contract_events(txn_info_id)?.unwrap_or_default();
             all_events.extend(events.into_iter().enumerate().map(|(idx, evt)| {
-                Event::new(
+                let event = Event::new(
                     block_id,
                     block_number,
                     txn_info.transaction_hash(),
@@ -98,7 +142,12 @@ impl ChainNotifyHandlerService {
                     Some(txn_info.transaction_global_index),
                     Some(idx as u32),
                     evt,
-                )
+                );
+                if removed {
+                    event.into_removed()
+                } else {
+                    event
+                }
             }));
         }
         let events_notification: ContractEventNotification =
@@ -3,6 +3,7 @@
 
 use starcoin_crypto::HashValue;
 use starcoin_types::block::BlockHeader;
+use starcoin_types::system_events::ChainReorg;
 use starcoin_types::{block::BlockNumber, contract_event::ContractEvent};
 use std::sync::Arc;
 
@@ -11,6 +12,7 @@ pub struct Notification<T>(pub T);
 
 pub type ContractEventNotification = Notification<(HashValue, Arc<[Event]>)>;
 pub type NewHeadEventNotification = Notification<ThinBlock>;
+pub type ChainReorgNotification = Notification<Arc<ChainReorg>>;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Event {
@@ -22,6 +24,8 @@ pub struct Event {
     pub transaction_global_index: Option<u64>,
     pub event_index: Option<u32>,
     pub contract_event: ContractEvent,
+    /// The event is from a block retracted from the main chain.
+    pub removed: bool,
 }
 
 impl Event {
@@ -42,8 +46,14 @@ impl Event {
             transaction_global_index,
             event_index,
             contract_event,
+            removed: false,
         }
     }
+
+    pub fn into_removed(mut self) -> Self {
+        self.removed = true;
+        self
+    }
 }
 
 /// Block with only txn hashes.
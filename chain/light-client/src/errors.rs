@@ -0,0 +1,25 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use starcoin_crypto::HashValue;
+use starcoin_types::block::BlockNumber;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LightClientError {
+    #[error("Invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+    #[error("Invalid header {number}: {reason}")]
+    InvalidHeader { number: BlockNumber, reason: String },
+    #[error(
+        "Epoch ends at block {end_block_number}, the epoch should be updated by the state of head {head_number}"
+    )]
+    EpochOutdated {
+        head_number: BlockNumber,
+        end_block_number: BlockNumber,
+    },
+    #[error("Not enough headers to calculate the difficulty, expect: {expect}, got: {real}")]
+    NotEnoughHeaders { expect: usize, real: usize },
+    #[error("Block {0} is not a verified header of the light client")]
+    UnknownBlock(HashValue),
+}
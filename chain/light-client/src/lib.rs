@@ -0,0 +1,12 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The light client tracks the header chain from a trusted checkpoint without storage, and
+//! verifies the state, transaction and event proofs from an untrusted node against it.
+#![deny(clippy::integer_arithmetic)]
+
+mod errors;
+mod light_client;
+
+pub use errors::LightClientError;
+pub use light_client::{Checkpoint, LightClient, MAX_RETAINED_HEADERS};
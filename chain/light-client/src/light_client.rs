@@ -0,0 +1,393 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::errors::LightClientError;
+use anyhow::{format_err, Result};
+use starcoin_accumulator::inmemory::InMemoryAccumulator;
+use starcoin_chain_api::TransactionInfoWithProof;
+use starcoin_consensus::difficulty::{get_next_target_helper, BlockDiffInfo};
+use starcoin_consensus::{difficult_to_target, target_to_difficulty, Consensus};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_state_api::StateWithProof;
+use starcoin_types::access_path::AccessPath;
+use starcoin_types::account_config::genesis_address;
+use starcoin_types::block::{BlockHeader, BlockInfo, BlockNumber};
+use starcoin_types::contract_event::ContractEvent;
+use starcoin_types::genesis_config::ConsensusStrategy;
+use starcoin_types::U256;
+use starcoin_vm_types::on_chain_resource::Epoch;
+use std::collections::VecDeque;
+
+/// The light client keeps at least this many latest headers, the proofs are verified against
+/// them.
+pub const MAX_RETAINED_HEADERS: usize = 1024;
+
+macro_rules! verify_header {
+    ($header:expr, $cond:expr, $fmt:expr, $($arg:tt)*) => {
+        if !$cond {
+            return Err(LightClientError::InvalidHeader {
+                number: $header.number(),
+                reason: format!($fmt, $($arg)*),
+            }
+            .into());
+        }
+    };
+}
+
+/// The trusted start of the header chain, fetched from an untrusted node and verified by the id
+/// of the trusted block.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// The trusted header and its ancestors, the trusted header last. The ancestors provide the
+    /// difficulty window to verify the headers after the trusted header.
+    pub headers: Vec<BlockHeader>,
+    /// The block info of the trusted header. It is not committed by the header, but a wrong block
+    /// accumulator is rejected by the block accumulator root of the next header. The total
+    /// difficulty is not verified.
+    pub block_info: BlockInfo,
+    /// The Epoch resource with proof at the state root of the trusted header.
+    pub epoch: StateWithProof,
+}
+
+/// A header chain from a trusted checkpoint without storage. Every header applied is verified by
+/// the parent, the block accumulator, the difficulty adjustment and the PoW, so the state,
+/// transaction and event proofs from an untrusted node can be verified against the headers.
+///
+/// The light client only follows one chain, it should be rebuilt from a checkpoint if the node
+/// switches to a fork.
+pub struct LightClient {
+    /// The latest verified headers, the head last.
+    headers: VecDeque<BlockHeader>,
+    /// The block accumulator of the head, the head included.
+    block_accumulator: InMemoryAccumulator,
+    total_difficulty: U256,
+    /// The epoch of the header after the head.
+    epoch: Epoch,
+}
+
+impl LightClient {
+    pub fn new(trusted_block_id: HashValue, checkpoint: Checkpoint) -> Result<Self> {
+        let Checkpoint {
+            headers,
+            block_info,
+            epoch,
+        } = checkpoint;
+        let trusted_header = headers
+            .last()
+            .ok_or_else(|| LightClientError::InvalidCheckpoint("headers is empty".to_string()))?;
+        if trusted_header.id() != trusted_block_id {
+            return Err(LightClientError::InvalidCheckpoint(format!(
+                "trusted block id mismatch, expect: {}, got: {}",
+                trusted_block_id,
+                trusted_header.id()
+            ))
+            .into());
+        }
+        for pair in headers.windows(2) {
+            if pair[1].parent_hash() != pair[0].id()
+                || pair[1].number() != pair[0].number().saturating_add(1)
+            {
+                return Err(LightClientError::InvalidCheckpoint(format!(
+                    "header {} is not the parent of header {}",
+                    pair[0].id(),
+                    pair[1].id()
+                ))
+                .into());
+            }
+        }
+        if block_info.block_id != trusted_block_id {
+            return Err(LightClientError::InvalidCheckpoint(format!(
+                "block info id mismatch, expect: {}, got: {}",
+                trusted_block_id, block_info.block_id
+            ))
+            .into());
+        }
+        let accumulator_info = block_info.get_block_accumulator_info();
+        let block_accumulator = InMemoryAccumulator::new(
+            accumulator_info.get_frozen_subtree_roots().clone(),
+            accumulator_info.get_num_leaves(),
+        )?;
+        if &block_accumulator.root_hash() != accumulator_info.get_accumulator_root()
+            || block_accumulator.num_leaves() != trusted_header.number().saturating_add(1)
+        {
+            return Err(LightClientError::InvalidCheckpoint(format!(
+                "block accumulator mismatch, root: {}, leaves: {}",
+                accumulator_info.get_accumulator_root(),
+                accumulator_info.get_num_leaves()
+            ))
+            .into());
+        }
+        let epoch = Self::verify_epoch(trusted_header, &epoch)?;
+        info!(
+            "Light client start from checkpoint {}: {}",
+            trusted_header.number(),
+            trusted_block_id
+        );
+        Ok(Self {
+            headers: headers.into(),
+            block_accumulator,
+            total_difficulty: block_info.get_total_difficulty(),
+            epoch,
+        })
+    }
+
+    /// The access path of the on-chain Epoch resource.
+    pub fn epoch_access_path() -> AccessPath {
+        AccessPath::new(genesis_address(), Epoch::data_path_for())
+    }
+
+    fn verify_epoch(header: &BlockHeader, epoch_proof: &StateWithProof) -> Result<Epoch> {
+        epoch_proof
+            .verify(header.state_root(), Self::epoch_access_path())
+            .map_err(|e| format_err!("epoch proof verify failed: {}", e))?;
+        let epoch: Epoch = bcs_ext::from_bytes(
+            epoch_proof
+                .state
+                .as_deref()
+                .ok_or_else(|| format_err!("Epoch is none."))?,
+        )?;
+        // the epoch at the state of a header is the epoch of the next header.
+        let next_number = header.number().saturating_add(1);
+        if next_number <= epoch.start_block_number() || next_number > epoch.end_block_number() {
+            return Err(format_err!(
+                "Epoch [{}, {}] does not cover the block {}",
+                epoch.start_block_number(),
+                epoch.end_block_number(),
+                next_number
+            ));
+        }
+        Ok(epoch)
+    }
+
+    pub fn head(&self) -> &BlockHeader {
+        self.headers
+            .back()
+            .expect("light client has the checkpoint header")
+    }
+
+    /// The total difficulty of the checkpoint block info plus the difficulties of the applied
+    /// headers. The total difficulty of the checkpoint is not committed by the headers, so it is
+    /// as trusted as the node served the checkpoint.
+    pub fn total_difficulty(&self) -> U256 {
+        self.total_difficulty
+    }
+
+    pub fn epoch(&self) -> &Epoch {
+        &self.epoch
+    }
+
+    pub fn block_accumulator_root(&self) -> HashValue {
+        self.block_accumulator.root_hash()
+    }
+
+    /// Whether the epoch should be updated by the state of the head before apply the next header.
+    pub fn epoch_outdated(&self) -> bool {
+        self.head().number() >= self.epoch.end_block_number()
+    }
+
+    /// Update the epoch by the Epoch resource with proof at the state root of the head.
+    pub fn update_epoch(&mut self, epoch_proof: &StateWithProof) -> Result<()> {
+        let epoch = Self::verify_epoch(self.head(), epoch_proof)?;
+        info!(
+            "Light client update epoch to {} at block {}",
+            epoch.number(),
+            self.head().number()
+        );
+        self.epoch = epoch;
+        Ok(())
+    }
+
+    pub fn get_header(&self, block_id: HashValue) -> Option<&BlockHeader> {
+        self.headers
+            .iter()
+            .rev()
+            .find(|header| header.id() == block_id)
+    }
+
+    pub fn get_header_by_number(&self, number: BlockNumber) -> Option<&BlockHeader> {
+        let first_number = self.headers.front()?.number();
+        let index = number.checked_sub(first_number)?;
+        self.headers.get(usize::try_from(index).ok()?)
+    }
+
+    fn verified_header(&self, block_id: HashValue) -> Result<&BlockHeader> {
+        self.get_header(block_id)
+            .ok_or_else(|| LightClientError::UnknownBlock(block_id).into())
+    }
+
+    /// Verify the header as the next header of the head, and append it to the header chain.
+    pub fn apply_header(&mut self, header: BlockHeader) -> Result<()> {
+        let head = self.head();
+        let expect_number = head.number().saturating_add(1);
+        verify_header!(
+            header,
+            header.number() == expect_number,
+            "unexpect block number, expect: {}, got: {}",
+            expect_number,
+            header.number()
+        );
+        verify_header!(
+            header,
+            header.parent_hash() == head.id(),
+            "parent id mismatch, expect: {}, got: {}",
+            head.id(),
+            header.parent_hash()
+        );
+        verify_header!(
+            header,
+            header.timestamp() > head.timestamp(),
+            "block timestamp too old, parent time: {}, block time: {}",
+            head.timestamp(),
+            header.timestamp()
+        );
+        verify_header!(
+            header,
+            header.chain_id() == head.chain_id(),
+            "chain id mismatch, expect: {}, got: {}",
+            head.chain_id(),
+            header.chain_id()
+        );
+        if header.number() > self.epoch.end_block_number() {
+            return Err(LightClientError::EpochOutdated {
+                head_number: head.number(),
+                end_block_number: self.epoch.end_block_number(),
+            }
+            .into());
+        }
+        verify_header!(
+            header,
+            header.gas_used() <= self.epoch.block_gas_limit(),
+            "gas used {} is greater than block gas limit {}",
+            header.gas_used(),
+            self.epoch.block_gas_limit()
+        );
+        verify_header!(
+            header,
+            header.block_accumulator_root() == self.block_accumulator.root_hash(),
+            "block accumulator root mismatch, expect: {}, got: {}",
+            self.block_accumulator.root_hash(),
+            header.block_accumulator_root()
+        );
+        self.verify_consensus(&header)?;
+
+        self.block_accumulator = self.block_accumulator.append(&[header.id()]);
+        self.total_difficulty = self.total_difficulty.saturating_add(header.difficulty());
+        self.headers.push_back(header);
+        let retained_headers =
+            MAX_RETAINED_HEADERS.max(self.epoch.block_difficulty_window() as usize);
+        while self.headers.len() > retained_headers {
+            self.headers.pop_front();
+        }
+        Ok(())
+    }
+
+    fn verify_consensus(&self, header: &BlockHeader) -> Result<()> {
+        let strategy = self.epoch.strategy();
+        // the dummy consensus of the test networks does not verify the header, as the chain.
+        if strategy == ConsensusStrategy::Dummy {
+            return Ok(());
+        }
+        let difficulty = target_to_difficulty(self.next_target()?);
+        strategy
+            .verify_header_difficulty(difficulty, header)
+            .map_err(|e| {
+                LightClientError::InvalidHeader {
+                    number: header.number(),
+                    reason: e.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// The target of the header after the head, as `get_next_work_required`.
+    fn next_target(&self) -> Result<U256> {
+        let head = self.head();
+        if head.number() <= 1 {
+            return Ok(difficult_to_target(head.difficulty()));
+        }
+        let window = self
+            .epoch
+            .block_difficulty_window()
+            .min(head.number().saturating_add(1)) as usize;
+        if self.headers.len() < window {
+            return Err(LightClientError::NotEnoughHeaders {
+                expect: window,
+                real: self.headers.len(),
+            }
+            .into());
+        }
+        let blocks = self
+            .headers
+            .iter()
+            .rev()
+            .take(window)
+            .map(|header| {
+                BlockDiffInfo::new(header.timestamp(), difficult_to_target(header.difficulty()))
+            })
+            .collect();
+        get_next_target_helper(blocks, self.epoch.block_time_target())
+    }
+
+    /// Verify the state with proof against the state root of the verified block.
+    pub fn verify_state(
+        &self,
+        block_id: HashValue,
+        access_path: AccessPath,
+        state_proof: &StateWithProof,
+    ) -> Result<()> {
+        let header = self.verified_header(block_id)?;
+        state_proof
+            .verify(header.state_root(), access_path)
+            .map_err(|e| format_err!("state proof verify failed: {}", e))
+    }
+
+    /// Verify the transaction info with proof against the transaction accumulator root of the
+    /// verified block, the event and the state in the proof are verified if the index and the
+    /// access path are given.
+    pub fn verify_transaction(
+        &self,
+        block_id: HashValue,
+        txn_proof: &TransactionInfoWithProof,
+        transaction_global_index: u64,
+        event_index: Option<u64>,
+        access_path: Option<AccessPath>,
+    ) -> Result<()> {
+        let header = self.verified_header(block_id)?;
+        if txn_proof.transaction_info.transaction_global_index != transaction_global_index {
+            return Err(format_err!(
+                "transaction global index mismatch, expect: {}, got: {}",
+                transaction_global_index,
+                txn_proof.transaction_info.transaction_global_index
+            ));
+        }
+        txn_proof.verify(
+            header.txn_accumulator_root(),
+            transaction_global_index,
+            event_index,
+            access_path,
+        )
+    }
+
+    /// Verify the event in the transaction info with proof, return the verified event.
+    pub fn verify_event<'a>(
+        &self,
+        block_id: HashValue,
+        txn_proof: &'a TransactionInfoWithProof,
+        transaction_global_index: u64,
+        event_index: u64,
+    ) -> Result<&'a ContractEvent> {
+        self.verify_transaction(
+            block_id,
+            txn_proof,
+            transaction_global_index,
+            Some(event_index),
+            None,
+        )?;
+        txn_proof
+            .event_proof
+            .as_ref()
+            .map(|event_proof| &event_proof.event)
+            .ok_or_else(|| format_err!("event proof is none"))
+    }
+}
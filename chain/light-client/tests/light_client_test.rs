@@ -0,0 +1,217 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use starcoin_account_api::AccountInfo;
+use starcoin_accumulator::inmemory::InMemoryAccumulator;
+use starcoin_chain::BlockChain;
+use starcoin_chain_api::{ChainReader, ChainWriter};
+use starcoin_config::{
+    BuiltinNetworkID, ChainNetwork, GenesisBlockParameter, GenesisBlockParameterConfig,
+};
+use starcoin_consensus::Consensus;
+use starcoin_crypto::HashValue;
+use starcoin_light_client::{Checkpoint, LightClient, LightClientError};
+use starcoin_state_api::ChainStateReader;
+use starcoin_time_service::{duration_since_epoch, TimeServiceType};
+use starcoin_types::block::{AccumulatorInfo, BlockHeader, BlockNumber};
+use starcoin_types::genesis_config::{ChainId, ConsensusStrategy};
+use starcoin_types::U256;
+use std::ops::RangeInclusive;
+
+/// A test network with the PoW consensus, the headers are verified by the difficulty and nonce.
+fn pow_network() -> Result<ChainNetwork> {
+    let mut genesis_config = BuiltinNetworkID::Test.genesis_config().clone();
+    genesis_config.genesis_block_parameter =
+        GenesisBlockParameterConfig::Static(GenesisBlockParameter {
+            parent_hash: Default::default(),
+            timestamp: duration_since_epoch().as_millis() as u64,
+            difficulty: 10.into(),
+        });
+    genesis_config.time_service_type = TimeServiceType::RealTimeService;
+    genesis_config.consensus_config.strategy = ConsensusStrategy::CryptoNight.value();
+    ChainNetwork::new_custom(
+        "light_client_test".to_string(),
+        ChainId::new(100),
+        genesis_config,
+    )
+}
+
+fn mine_blocks(block_chain: &mut BlockChain, net: &ChainNetwork, count: u64) -> Result<()> {
+    let miner_account = AccountInfo::random();
+    for _i in 0..count {
+        let (block_template, _) = block_chain.create_block_template(
+            *miner_account.address(),
+            None,
+            vec![],
+            vec![],
+            None,
+        )?;
+        let block = block_chain
+            .consensus()
+            .create_block(block_template, net.time_service().as_ref())?;
+        block_chain.apply(block)?;
+    }
+    Ok(())
+}
+
+fn get_headers(
+    block_chain: &BlockChain,
+    numbers: RangeInclusive<BlockNumber>,
+) -> Result<Vec<BlockHeader>> {
+    numbers
+        .map(|number| {
+            block_chain
+                .get_header_by_number(number)?
+                .ok_or_else(|| format_err!("Can not find header by number {}", number))
+        })
+        .collect()
+}
+
+/// The checkpoint of the head, with all the ancestors for the difficulty window.
+fn head_checkpoint(block_chain: &BlockChain) -> Result<Checkpoint> {
+    Ok(Checkpoint {
+        headers: get_headers(block_chain, 0..=block_chain.current_header().number())?,
+        block_info: block_chain
+            .get_block_info(None)?
+            .ok_or_else(|| format_err!("Can not find head block info"))?,
+        epoch: block_chain
+            .chain_state_reader()
+            .get_with_proof(&LightClient::epoch_access_path())?,
+    })
+}
+
+#[stest::test]
+fn test_light_client() -> Result<()> {
+    let net = ChainNetwork::new_test();
+    let mut block_chain = test_helper::gen_blockchain_with_blocks_for_test(3, &net)?;
+    let trusted_header = block_chain.current_header();
+    let checkpoint = head_checkpoint(&block_chain)?;
+    // the checkpoint does not match the trusted block.
+    assert!(LightClient::new(trusted_header.parent_hash(), checkpoint.clone()).is_err());
+    let mut light_client = LightClient::new(trusted_header.id(), checkpoint)?;
+
+    mine_blocks(&mut block_chain, &net, 3)?;
+    let head = block_chain.current_header();
+    let headers = get_headers(&block_chain, trusted_header.number() + 1..=head.number())?;
+    // the header does not follow the head.
+    assert!(light_client.apply_header(headers[1].clone()).is_err());
+    for header in headers {
+        light_client.apply_header(header)?;
+    }
+    assert_eq!(light_client.head().id(), head.id());
+    let block_info = block_chain
+        .get_block_info(None)?
+        .ok_or_else(|| format_err!("Can not find head block info"))?;
+    assert_eq!(
+        &light_client.block_accumulator_root(),
+        block_info
+            .get_block_accumulator_info()
+            .get_accumulator_root()
+    );
+    assert_eq!(
+        light_client.total_difficulty(),
+        block_info.get_total_difficulty()
+    );
+
+    let access_path = LightClient::epoch_access_path();
+    let state_proof = block_chain
+        .chain_state_reader()
+        .get_with_proof(&access_path)?;
+    light_client.verify_state(head.id(), access_path.clone(), &state_proof)?;
+    assert!(light_client
+        .verify_state(trusted_header.id(), access_path, &state_proof)
+        .is_err());
+
+    // the block metadata transaction of the head.
+    let txn_global_index = block_info.get_txn_accumulator_info().get_num_leaves() - 1;
+    let txn_proof = block_chain
+        .get_transaction_proof(head.id(), txn_global_index, Some(0), None)?
+        .ok_or_else(|| format_err!("Can not find transaction proof"))?;
+    light_client.verify_event(head.id(), &txn_proof, txn_global_index, 0)?;
+    assert!(light_client
+        .verify_transaction(head.id(), &txn_proof, txn_global_index - 1, None, None)
+        .is_err());
+    assert!(light_client
+        .verify_transaction(
+            HashValue::random(),
+            &txn_proof,
+            txn_global_index,
+            None,
+            None
+        )
+        .is_err());
+    Ok(())
+}
+
+#[stest::test(timeout = 120)]
+fn test_light_client_pow() -> Result<()> {
+    let net = pow_network()?;
+    let mut block_chain = test_helper::gen_blockchain_with_blocks_for_test(3, &net)?;
+    let trusted_header = block_chain.current_header();
+    let mut light_client = LightClient::new(trusted_header.id(), head_checkpoint(&block_chain)?)?;
+
+    mine_blocks(&mut block_chain, &net, 3)?;
+    let head = block_chain.current_header();
+    let strategy = net.genesis_config().consensus();
+    for header in get_headers(&block_chain, trusted_header.number() + 1..=head.number())? {
+        // the difficulty is not the one adjusted by the parents.
+        let tampered = header
+            .as_builder()
+            .with_difficulty(header.difficulty() + U256::one())
+            .build();
+        assert!(light_client.apply_header(tampered).is_err());
+        // the nonce does not meet the difficulty.
+        let tampered = (1u32..)
+            .map(|i| {
+                header
+                    .as_builder()
+                    .with_nonce(header.nonce().wrapping_add(i))
+                    .build()
+            })
+            .find(|tampered| {
+                strategy
+                    .verify_header_difficulty(header.difficulty(), tampered)
+                    .is_err()
+            })
+            .ok_or_else(|| format_err!("Can not find a nonce not meet the difficulty"))?;
+        assert!(light_client.apply_header(tampered).is_err());
+
+        light_client.apply_header(header)?;
+    }
+    assert_eq!(light_client.head().id(), head.id());
+    Ok(())
+}
+
+#[stest::test]
+fn test_light_client_wrong_checkpoint_accumulator() -> Result<()> {
+    let net = ChainNetwork::new_test();
+    let mut block_chain = test_helper::gen_blockchain_with_blocks_for_test(3, &net)?;
+    let trusted_header = block_chain.current_header();
+    let mut checkpoint = head_checkpoint(&block_chain)?;
+    // a block accumulator with the leaves of the chain, but not the blocks of the chain.
+    let accumulator = InMemoryAccumulator::from_leaves(
+        &(0..=trusted_header.number())
+            .map(|_| HashValue::random())
+            .collect::<Vec<_>>(),
+    );
+    let num_nodes = checkpoint.block_info.block_accumulator_info.num_nodes;
+    checkpoint.block_info.block_accumulator_info = AccumulatorInfo::new(
+        accumulator.root_hash(),
+        accumulator.frozen_subtree_roots().clone(),
+        accumulator.num_leaves(),
+        num_nodes,
+    );
+    // the checkpoint accumulator is consistent by itself.
+    let mut light_client = LightClient::new(trusted_header.id(), checkpoint)?;
+
+    mine_blocks(&mut block_chain, &net, 1)?;
+    let err = light_client
+        .apply_header(block_chain.current_header())
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LightClientError>(),
+        Some(LightClientError::InvalidHeader { .. })
+    ));
+    Ok(())
+}
@@ -216,9 +216,6 @@ impl OpenedBlock {
             };
         }
 
-        self.execute_extra_txn()
-            .expect("Extra txn must be executed successfully");
-
         Ok(ExcludedTxns {
             discarded_txns: discard_txns,
             untouched_txns: untouched_user_txns,
@@ -312,7 +309,10 @@ impl OpenedBlock {
     }
 
     /// Construct a block template for mining.
-    pub fn finalize(self) -> Result<BlockTemplate> {
+    /// The extra txn is executed here, after all the pushed user txns.
+    pub fn finalize(mut self) -> Result<BlockTemplate> {
+        self.execute_extra_txn()
+            .expect("Extra txn must be executed successfully");
         let accumulator_root = self.txn_accumulator.root_hash();
         let state_root = self.state.state_root();
         let uncles = if !self.uncles.is_empty() {
@@ -18,6 +18,7 @@ use starcoin_crypto::hash::PlainCryptoHash;
 use starcoin_crypto::HashValue;
 use starcoin_executor::{BlockExecutedData, VMMetrics};
 use starcoin_logger::prelude::*;
+use starcoin_logger::tracer::Span;
 use starcoin_open_block::OpenedBlock;
 use starcoin_state_api::{AccountStateReader, ChainStateReader, ChainStateWriter};
 use starcoin_statedb::ChainStateDB;
@@ -46,7 +47,7 @@ use std::iter::Extend;
 use std::option::Option::{None, Some};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::Arc,
 };
 
@@ -621,7 +622,8 @@ impl BlockChain {
         let state_root = head_block.header().state_root();
         let txn_accumulator_info = block_info.get_txn_accumulator_info();
         let block_accumulator_info = block_info.get_block_accumulator_info();
-        let chain_state = ChainStateDB::new(storage.clone().into_super_arc(), Some(state_root));
+        let chain_state = ChainStateDB::new(storage.clone().into_super_arc(), Some(state_root))
+            .enable_stale_nodes(storage.is_state_prune_enabled());
         let epoch = get_epoch_from_statedb(&chain_state)?;
         let genesis = storage
             .get_genesis()?
@@ -673,7 +675,8 @@ impl BlockChain {
             storage.get_accumulator_store(AccumulatorStoreType::Block),
         );
         let chain_id = genesis_block.header().chain_id();
-        let statedb = ChainStateDB::new(storage.clone().into_super_arc(), None);
+        let statedb = ChainStateDB::new(storage.clone().into_super_arc(), None)
+            .enable_stale_nodes(storage.is_state_prune_enabled());
         let executed_block = Self::execute_block_and_save(
             storage.as_ref(),
             statedb,
@@ -858,6 +861,9 @@ impl BlockChain {
     where
         V: BlockVerifier,
     {
+        let _span = Span::enter("chain.verify")
+            .with("block_hash", block.id())
+            .with("block_number", block.header().number());
         V::verify_block(self, block)
     }
 
@@ -978,9 +984,13 @@ impl BlockChain {
         );
 
         watch(CHAIN_WATCH_NAME, "n23");
+        let _commit_span = Span::enter("storage.commit_block")
+            .with("block_hash", block_id)
+            .with("txn_count", transactions.len());
         statedb
             .flush()
             .map_err(BlockExecutorError::BlockChainStateErr)?;
+        storage.save_stale_state_nodes(block_id, header.number(), statedb.take_stale_nodes())?;
         // If chain state is matched, and accumulator is matched,
         // then, we save flush states, and save block data.
         watch(CHAIN_WATCH_NAME, "n24");
@@ -1023,6 +1033,12 @@ impl BlockChain {
             "events' length should be equal to txn infos' length"
         );
         let txn_info_ids: Vec<_> = txn_infos.iter().map(|info| info.id()).collect();
+        storage.save_block_event_index(
+            block_id,
+            block.header().number(),
+            txn_info_ids.as_slice(),
+            txn_events.as_slice(),
+        )?;
         for (info_id, events) in txn_info_ids.iter().zip(txn_events.into_iter()) {
             storage.save_contract_events(*info_id, events)?;
         }
@@ -1136,6 +1152,7 @@ impl BlockChain {
         statedb
             .flush()
             .map_err(BlockExecutorError::BlockChainStateErr)?;
+        storage.save_stale_state_nodes(block_id, header.number(), statedb.take_stale_nodes())?;
         // If chain state is matched, and accumulator is matched,
         // then, we save flush states, and save block data.
         txn_accumulator
@@ -1168,6 +1185,12 @@ impl BlockChain {
 
         // save block's transaction relationship and save transaction
         let txn_info_ids: Vec<_> = txn_infos.iter().map(|info| info.id()).collect();
+        storage.save_block_event_index(
+            block_id,
+            block.header().number(),
+            txn_info_ids.as_slice(),
+            txn_events.as_slice(),
+        )?;
         for (info_id, events) in txn_info_ids.iter().zip(txn_events.into_iter()) {
             storage.save_contract_events(*info_id, events)?;
         }
@@ -1564,10 +1587,16 @@ impl ChainReader for BlockChain {
     }
 
     fn verify(&self, block: Block) -> Result<VerifiedBlock> {
+        let _span = Span::enter("chain.verify")
+            .with("block_hash", block.id())
+            .with("block_number", block.header().number());
         FullVerifier::verify_block(self, block)
     }
 
     fn execute(&self, verified_block: VerifiedBlock) -> Result<ExecutedBlock> {
+        let _span = Span::enter("chain.execute")
+            .with("block_hash", verified_block.0.id())
+            .with("block_number", verified_block.0.header().number());
         if let Some((executed_data, block_info)) =
             MAIN_DIRECT_SAVE_BLOCK_HASH_MAP.get(&verified_block.0.header.id())
         {
@@ -1725,16 +1754,16 @@ impl BlockChain {
         };
         let mut event_with_infos = vec![];
         'outer: loop {
-            let block = self.get_block_by_number(cur_block_number)?.ok_or_else(|| {
+            let block_id = self.get_hash_by_number(cur_block_number)?.ok_or_else(|| {
                 anyhow::anyhow!(format!(
                     "cannot find block({}) on main chain(head: {})",
                     cur_block_number,
                     chain_header.id()
                 ))
             })?;
-            let block_id = block.id();
-            let block_number = block.header().number();
-            let mut txn_info_ids = self.storage.get_block_txn_info_ids(block_id)?;
+            let block_number = cur_block_number;
+            let mut txn_info_ids =
+                self.filter_block_txn_info_ids(&filter, block_id, block_number)?;
             if reverse {
                 txn_info_ids.reverse();
             }
@@ -1766,7 +1795,7 @@ impl BlockChain {
                 let filtered_event_with_info =
                     filtered_events.map(|(idx, evt)| ContractEventInfo {
                         block_hash: block_id,
-                        block_number: block.header().number(),
+                        block_number,
                         transaction_hash: txn_info.transaction_hash(),
                         transaction_index: txn_info.transaction_index,
                         transaction_global_index: txn_info.transaction_global_index,
@@ -1808,6 +1837,38 @@ impl BlockChain {
         }
         Ok(event_with_infos)
     }
+
+    /// Get txn info ids of the block which may have events matching the `filter`.
+    /// The event bloom and event index are used to skip blocks and transactions,
+    /// blocks without a bloom are scanned fully.
+    fn filter_block_txn_info_ids(
+        &self,
+        filter: &Filter,
+        block_id: HashValue,
+        block_number: BlockNumber,
+    ) -> Result<Vec<HashValue>> {
+        let event_bloom = match self.storage.get_block_event_bloom(block_id)? {
+            Some(event_bloom) => event_bloom,
+            None => return self.storage.get_block_txn_info_ids(block_id),
+        };
+        if !filter.bloom_possible(&event_bloom.bloom) {
+            return Ok(vec![]);
+        }
+        let mut txn_info_ids = self.storage.get_block_txn_info_ids(block_id)?;
+        if event_bloom.indexed {
+            if let Some(topics) = filter.index_topics() {
+                let mut indexed_ids = HashSet::new();
+                for topic in topics {
+                    if let Some(ids) = self.storage.get_event_index(topic, block_number)? {
+                        indexed_ids.extend(ids);
+                    }
+                }
+                // the index is shared by all branches at the block number.
+                txn_info_ids.retain(|id| indexed_ids.contains(id));
+            }
+        }
+        Ok(txn_info_ids)
+    }
 }
 
 impl ChainWriter for BlockChain {
@@ -1833,7 +1894,8 @@ impl ChainWriter for BlockChain {
             self.storage.as_ref(),
         );
 
-        self.statedb = ChainStateDB::new(self.storage.clone().into_super_arc(), Some(state_root));
+        self.statedb = ChainStateDB::new(self.storage.clone().into_super_arc(), Some(state_root))
+            .enable_stale_nodes(self.storage.is_state_prune_enabled());
         self.status = ChainStatusWithBlock {
             status: ChainStatus::new(block.header().clone(), block_info.clone()),
             head: block.clone(),
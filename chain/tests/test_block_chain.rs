@@ -126,6 +126,39 @@ fn test_chain_filter_events() {
     }
 }
 
+#[stest::test(timeout = 120)]
+fn test_chain_filter_events_with_bloom() {
+    let mut mock_chain = MockChain::new(ChainNetwork::new_test()).unwrap();
+    mock_chain.produce_and_apply_times(3).unwrap();
+
+    let not_exist_type_tag = TypeTag::Struct(Box::new(StructTag {
+        address: genesis_address(),
+        module: Identifier::from_str("NotExist").unwrap(),
+        name: Identifier::from_str("NotExistEvent").unwrap(),
+        type_params: vec![],
+    }));
+    let event_filter = Filter {
+        from_block: 0,
+        to_block: 3,
+        event_keys: vec![],
+        addrs: vec![],
+        type_tags: vec![not_exist_type_tag],
+        limit: None,
+        reverse: false,
+    };
+    let head_id = mock_chain.head().current_header().id();
+    let event_bloom = mock_chain
+        .head()
+        .get_storage()
+        .get_block_event_bloom(head_id)
+        .unwrap()
+        .unwrap();
+    assert!(!event_bloom.bloom.is_empty());
+    assert!(!event_filter.bloom_possible(&event_bloom.bloom));
+    let evts = mock_chain.head().filter_events(event_filter).unwrap();
+    assert!(evts.is_empty());
+}
+
 #[stest::test]
 fn test_block_chain() -> Result<()> {
     let mut mock_chain = MockChain::new(ChainNetwork::new_test())?;
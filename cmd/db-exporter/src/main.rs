@@ -27,14 +27,17 @@ use starcoin_rpc_api::types::StrView;
 use starcoin_state_tree::StateTree;
 use starcoin_statedb::{ChainStateDB, ChainStateReader, ChainStateWriter};
 use starcoin_storage::{
+    backup,
     block::FailedBlock,
     block_info::BlockInfoStore,
     cache_storage::CacheStorage,
     db_storage::DBStorage,
+    event_index::EventIndexStore,
     storage::{ColumnFamilyName, InnerStore, StorageInstance, ValueCodec},
-    BlockStore, Storage, StorageVersion, Store, BLOCK_ACCUMULATOR_NODE_PREFIX_NAME,
-    BLOCK_HEADER_PREFIX_NAME, BLOCK_INFO_PREFIX_NAME, BLOCK_PREFIX_NAME, FAILED_BLOCK_PREFIX_NAME,
-    STATE_NODE_PREFIX_NAME, STATE_NODE_PREFIX_NAME_PREV, TRANSACTION_ACCUMULATOR_NODE_PREFIX_NAME,
+    BlockStore, ContractEventStore, Storage, StorageVersion, Store,
+    BLOCK_ACCUMULATOR_NODE_PREFIX_NAME, BLOCK_HEADER_PREFIX_NAME, BLOCK_INFO_PREFIX_NAME,
+    BLOCK_PREFIX_NAME, FAILED_BLOCK_PREFIX_NAME, STATE_NODE_PREFIX_NAME,
+    STATE_NODE_PREFIX_NAME_PREV, TRANSACTION_ACCUMULATOR_NODE_PREFIX_NAME,
 };
 use starcoin_transaction_builder::{
     build_signed_empty_txn, create_signed_txn_with_association_account, DEFAULT_MAX_GAS_AMOUNT,
@@ -246,6 +249,8 @@ enum Cmd {
     SaveStartupInfo(SaveStartupInfoOptions),
     TokenSupply(TokenSupplyOptions),
     ForceDeploy(ForceDeployOutput),
+    IndexEvents(IndexEventsOptions),
+    RestoreBackup(RestoreBackupOptions),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -537,6 +542,48 @@ pub struct TokenSupplyOptions {
     resource_type: StrView<StructTag>,
 }
 
+#[derive(Debug, Parser)]
+#[clap(
+    name = "index-events",
+    about = "build event bloom and event index for main chain blocks"
+)]
+pub struct IndexEventsOptions {
+    #[clap(long, short = 'n')]
+    /// Chain Network
+    pub net: BuiltinNetworkID,
+    #[clap(long, short = 'o', parse(from_os_str))]
+    /// starcoin node db path. like ~/.starcoin/main
+    pub to_path: PathBuf,
+    #[clap(long, short = 's', default_value = "0")]
+    pub start: BlockNumber,
+    #[clap(long, short = 'e')]
+    /// default is the head block number.
+    pub end: Option<BlockNumber>,
+    #[clap(long)]
+    /// also build the secondary event index, should be same as the node's `--event-index`.
+    pub event_index: bool,
+}
+
+#[derive(Debug, Clone, Parser)]
+#[clap(
+    name = "restore-backup",
+    about = "restore the db backup created by `node manager backup`"
+)]
+pub struct RestoreBackupOptions {
+    #[clap(long, short = 'n')]
+    /// Chain Network
+    pub net: BuiltinNetworkID,
+    #[clap(long, short = 'o', parse(from_os_str))]
+    /// starcoin node db path. like ~/.starcoin/main, the db must not exist.
+    pub to_path: PathBuf,
+    #[clap(long, short = 'i', parse(from_os_str))]
+    /// backup dir, the backup-<id> dirs in this dir
+    pub input_path: PathBuf,
+    #[clap(long, short = 'b')]
+    /// the backup to restore, default is the latest backup.
+    pub backup_id: Option<u64>,
+}
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> anyhow::Result<()> {
     let opt = Opt::parse();
@@ -741,6 +788,25 @@ async fn main() -> anyhow::Result<()> {
             );
             return result;
         }
+        Cmd::IndexEvents(option) => {
+            let result = index_events(
+                option.to_path,
+                option.net,
+                option.start,
+                option.end,
+                option.event_index,
+            );
+            return result;
+        }
+        Cmd::RestoreBackup(option) => {
+            let result = restore_backup(
+                option.to_path,
+                option.input_path,
+                option.backup_id,
+                option.net,
+            );
+            return result;
+        }
     }
     Ok(())
 }
@@ -2520,3 +2586,89 @@ fn token_supply(
     file.flush()?;
     Ok(())
 }
+
+fn index_events(
+    to_dir: PathBuf,
+    network: BuiltinNetworkID,
+    start: BlockNumber,
+    end: Option<BlockNumber>,
+    event_index: bool,
+) -> anyhow::Result<()> {
+    ::starcoin_logger::init();
+    let net = ChainNetwork::new_builtin(network);
+    let db_storage = DBStorage::new(to_dir.join("starcoindb/db"), RocksdbConfig::default(), None)?;
+    let storage = Arc::new(
+        Storage::new(StorageInstance::new_cache_and_db_instance(
+            CacheStorage::new(None),
+            db_storage,
+        ))?
+        .enable_event_index(event_index),
+    );
+    let (chain_info, _) = Genesis::init_and_check_storage(&net, storage.clone(), to_dir.as_ref())?;
+    let chain = BlockChain::new(
+        net.time_service(),
+        chain_info.head().id(),
+        storage.clone(),
+        None,
+    )
+    .expect("create block chain should success.");
+    let end = end.unwrap_or_else(|| chain.status().head().number());
+    if start > end {
+        bail!("start {} should not be greater than end {}", start, end);
+    }
+    let start_time = SystemTime::now();
+    let bar = ProgressBar::new(end - start + 1);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:100.cyan/blue} {percent}% {msg}"),
+    );
+    for block_number in start..=end {
+        let block_id = chain
+            .get_hash_by_number(block_number)?
+            .ok_or_else(|| format_err!("{} get block id error", block_number))?;
+        let txn_info_ids = storage.get_block_txn_info_ids(block_id)?;
+        let mut txn_events = Vec::with_capacity(txn_info_ids.len());
+        for txn_info_id in txn_info_ids.iter() {
+            let events = storage
+                .get_contract_events(*txn_info_id)?
+                .ok_or_else(|| format_err!("{} get events error", txn_info_id))?;
+            txn_events.push(events);
+        }
+        storage.save_block_event_index(
+            block_id,
+            block_number,
+            txn_info_ids.as_slice(),
+            txn_events.as_slice(),
+        )?;
+        bar.set_message(format!("index block {}", block_number));
+        bar.inc(1);
+    }
+    bar.finish();
+    let use_time = SystemTime::now().duration_since(start_time)?;
+    println!("index events use time: {:?}", use_time.as_secs());
+    Ok(())
+}
+
+pub fn restore_backup(
+    to_dir: PathBuf,
+    backup_dir: PathBuf,
+    backup_id: Option<u64>,
+    network: BuiltinNetworkID,
+) -> anyhow::Result<()> {
+    let net = ChainNetwork::new_builtin(network);
+    let genesis_hash = Genesis::load_or_build(&net)?.block().id();
+    let manifest = backup::restore_backup(
+        backup_dir.as_path(),
+        backup_id,
+        to_dir.join("starcoindb/db/starcoindb").as_path(),
+        genesis_hash,
+    )?;
+    println!(
+        "restore backup {} to {}, head block number {}, hash {}",
+        manifest.backup_id,
+        to_dir.display(),
+        manifest.head().number(),
+        manifest.head().id()
+    );
+    Ok(())
+}
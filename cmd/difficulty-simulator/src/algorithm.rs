@@ -0,0 +1,87 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The difficulty algorithms to simulate, the real algorithm of the chain and the alternatives.
+
+use anyhow::{bail, format_err, Result};
+use starcoin_consensus::difficulty::{get_next_target_helper, BlockDiffInfo};
+use starcoin_types::{U256, U512};
+use std::convert::TryFrom;
+
+/// A difficulty algorithm computes the target of the next block from the latest blocks.
+pub trait DifficultyAlgorithm {
+    fn name(&self) -> &str;
+
+    /// `blocks` are the latest blocks in the difficulty window, the latest first, as the chain
+    /// passes to `get_next_target_helper`.
+    fn next_target(&self, blocks: Vec<BlockDiffInfo>, block_time_target: u64) -> Result<U256>;
+}
+
+/// The difficulty algorithm of the chain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StarcoinDifficulty;
+
+impl DifficultyAlgorithm for StarcoinDifficulty {
+    fn name(&self) -> &str {
+        "starcoin"
+    }
+
+    fn next_target(&self, blocks: Vec<BlockDiffInfo>, block_time_target: u64) -> Result<U256> {
+        get_next_target_helper(blocks, block_time_target)
+    }
+}
+
+/// Linearly weighted moving average of the block times, the latest block time has the most
+/// weight, so the target responds faster to a hash rate change than a simple average.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LwmaDifficulty;
+
+impl DifficultyAlgorithm for LwmaDifficulty {
+    fn name(&self) -> &str {
+        "lwma"
+    }
+
+    fn next_target(&self, blocks: Vec<BlockDiffInfo>, block_time_target: u64) -> Result<U256> {
+        if blocks.is_empty() {
+            bail!("block diff info is empty")
+        }
+        if blocks.len() == 1 {
+            return Ok(blocks[0].target);
+        }
+        let block_n = blocks.len() - 1;
+        // solve times are limited to [1, 6 * target], so a timestamp outlier can not swing the
+        // target too much.
+        let max_solve_time = block_time_target.saturating_mul(6);
+        let mut weighted_time: u64 = 0;
+        let mut total_target = U512::zero();
+        for (idx, pair) in blocks.windows(2).rev().enumerate() {
+            let solve_time = pair[0]
+                .timestamp
+                .saturating_sub(pair[1].timestamp)
+                .clamp(1, max_solve_time);
+            weighted_time = weighted_time.saturating_add(solve_time.saturating_mul(idx as u64 + 1));
+            total_target = total_target
+                .checked_add(U512::from(&pair[0].target))
+                .ok_or_else(|| format_err!("calculate total target overflow"))?;
+        }
+        let total_weight = (block_n * (block_n + 1) / 2) as u64;
+        // new_target = avg_target * weighted_avg_time / time_plan
+        let new_target = total_target
+            .checked_mul(U512::from(weighted_time))
+            .and_then(|target| {
+                target.checked_div(U512::from(
+                    block_n as u64 * total_weight * block_time_target,
+                ))
+            })
+            .ok_or_else(|| format_err!("calculate new target overflow"))?;
+        Ok(U256::try_from(&new_target).unwrap_or_else(|_| U256::max_value()))
+    }
+}
+
+pub fn algorithm_by_name(name: &str) -> Result<Box<dyn DifficultyAlgorithm>> {
+    Ok(match name {
+        "starcoin" => Box::new(StarcoinDifficulty),
+        "lwma" => Box::new(LwmaDifficulty),
+        _ => bail!("Unknown difficulty algorithm: {}", name),
+    })
+}
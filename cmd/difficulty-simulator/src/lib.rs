@@ -0,0 +1,7 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod algorithm;
+pub mod report;
+pub mod scenario;
+pub mod simulator;
@@ -0,0 +1,156 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use starcoin_config::{BuiltinNetworkID, ChainNetwork};
+use starcoin_difficulty_simulator::algorithm::algorithm_by_name;
+use starcoin_difficulty_simulator::report::{write_csv, write_json, SimulationReport};
+use starcoin_difficulty_simulator::scenario::{Scenario, ScenarioKind};
+use starcoin_difficulty_simulator::simulator::{difficulty_from_f64, SimulationConfig, Simulator};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "csv" => Self::Csv,
+            "json" => Self::Json,
+            _ => bail!("Unknown output format: {}", s),
+        })
+    }
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "difficulty-simulator",
+    about = "Simulate the difficulty adjustment by the hash rate scenarios"
+)]
+pub struct SimulatorOpt {
+    #[clap(long, short = 'n', default_value = "main")]
+    /// Chain network to take the epoch parameters of the difficulty from.
+    pub net: BuiltinNetworkID,
+    #[clap(long)]
+    /// Block time target in milliseconds, default to the block time target of the network.
+    pub block_time_target: Option<u64>,
+    #[clap(long)]
+    /// Blocks of the difficulty window, default to the difficulty window of the network.
+    pub difficulty_window: Option<u64>,
+    #[clap(
+        long,
+        short = 's',
+        use_value_delimiter = true,
+        default_value = "steady,drop,oscillation,selfish"
+    )]
+    /// The hash rate scenarios: steady, drop, oscillation, selfish.
+    pub scenarios: Vec<ScenarioKind>,
+    #[clap(
+        long,
+        short = 'a',
+        use_value_delimiter = true,
+        default_value = "starcoin"
+    )]
+    /// The difficulty algorithms: starcoin, lwma.
+    pub algorithms: Vec<String>,
+    #[clap(long, default_value = "1000000")]
+    /// Hash rate in hashes per second.
+    pub hash_rate: f64,
+    #[clap(long)]
+    /// Difficulty of the genesis block, default to the hash rate * block time target.
+    pub initial_difficulty: Option<u64>,
+    #[clap(long, default_value = "86400")]
+    /// Simulated seconds of a scenario.
+    pub duration: u64,
+    #[clap(long, default_value = "0.5")]
+    /// Part of the hash rate lost at the middle of the drop scenario.
+    pub drop_ratio: f64,
+    #[clap(long, default_value = "3600")]
+    /// Seconds of the hash rate keeps high or low in the oscillation scenario.
+    pub oscillation_period: u64,
+    #[clap(long, default_value = "0.5")]
+    /// Part of the hash rate added and removed in the oscillation scenario.
+    pub oscillation_amplitude: f64,
+    #[clap(long, default_value = "0.3")]
+    /// Part of the hash rate of the selfish miners in the selfish scenario.
+    pub selfish_share: f64,
+    #[clap(long, default_value = "0.5")]
+    /// Part of the honest hash rate mining on the selfish block in a fork race.
+    pub selfish_gamma: f64,
+    #[clap(long, default_value = "0")]
+    /// Milliseconds to propagate a block, the blocks found in it by the others are orphaned.
+    pub propagation_delay: u64,
+    #[clap(long, default_value = "0.1")]
+    /// Average block time within this part of the target is converged.
+    pub convergence_tolerance: f64,
+    #[clap(long, default_value = "0")]
+    /// Seed of the simulation, the same seed simulates the same chain.
+    pub seed: u64,
+    #[clap(long, short = 'f', default_value = "csv")]
+    /// Report format: csv, json.
+    pub format: OutputFormat,
+    #[clap(long, short = 'o', parse(from_os_str))]
+    /// Write the report to the file, default to stdout.
+    pub output: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let opt: SimulatorOpt = SimulatorOpt::parse();
+    let epoch = ChainNetwork::new_builtin(opt.net).genesis_epoch();
+    let block_time_target = opt
+        .block_time_target
+        .unwrap_or_else(|| epoch.block_time_target());
+    let config = SimulationConfig {
+        block_time_target,
+        difficulty_window: opt
+            .difficulty_window
+            .unwrap_or_else(|| epoch.block_difficulty_window()),
+        initial_difficulty: match opt.initial_difficulty {
+            Some(difficulty) => difficulty.into(),
+            None => difficulty_from_f64(opt.hash_rate * block_time_target as f64 / 1000.0),
+        },
+        convergence_tolerance: opt.convergence_tolerance,
+        seed: opt.seed,
+    };
+    let algorithms = opt
+        .algorithms
+        .iter()
+        .map(|name| algorithm_by_name(name))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut reports: Vec<SimulationReport> = vec![];
+    for kind in &opt.scenarios {
+        let scenario = Scenario {
+            kind: *kind,
+            hash_rate: opt.hash_rate,
+            duration: opt.duration.saturating_mul(1000),
+            drop_ratio: opt.drop_ratio,
+            oscillation_period: opt.oscillation_period.saturating_mul(1000),
+            oscillation_amplitude: opt.oscillation_amplitude,
+            selfish_share: opt.selfish_share,
+            selfish_gamma: opt.selfish_gamma,
+            propagation_delay: opt.propagation_delay,
+        };
+        for algorithm in &algorithms {
+            reports.push(Simulator::new(&scenario, algorithm.as_ref(), &config).run()?);
+        }
+    }
+
+    let writer: Box<dyn Write> = match opt.output.as_ref() {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    match opt.format {
+        OutputFormat::Csv => write_csv(writer, &reports),
+        OutputFormat::Json => write_json(writer, &reports),
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The report of a simulation, flat so it is written as a CSV row or a JSON object.
+
+use crate::scenario::{Scenario, ScenarioKind};
+use crate::simulator::SimulationConfig;
+use anyhow::Result;
+use serde::Serialize;
+use starcoin_consensus::difficulty::BlockDiffInfo;
+use std::collections::VecDeque;
+use std::io::Write;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SimulationReport {
+    pub scenario: ScenarioKind,
+    pub algorithm: String,
+    /// Blocks of the main chain, exclude the genesis.
+    pub blocks: u64,
+    pub orphans: u64,
+    /// Orphans / (blocks + orphans).
+    pub orphan_rate: f64,
+    /// Block time target in milliseconds.
+    pub block_time_target: u64,
+    /// The block time distribution of the main chain in milliseconds.
+    pub block_time_mean: f64,
+    pub block_time_stddev: f64,
+    pub block_time_min: u64,
+    pub block_time_p50: u64,
+    pub block_time_p90: u64,
+    pub block_time_p99: u64,
+    pub block_time_max: u64,
+    /// Blocks after the hash rate change until the average block time of the difficulty window
+    /// converges to the target, `None` if it never converges.
+    pub convergence_blocks: Option<u64>,
+    pub convergence_secs: Option<f64>,
+}
+
+impl SimulationReport {
+    pub fn new(
+        scenario: &Scenario,
+        algorithm: &str,
+        config: &SimulationConfig,
+        chain: &[BlockDiffInfo],
+        orphans: u64,
+    ) -> Self {
+        let blocks = chain.len().saturating_sub(1) as u64;
+        let mut block_times: Vec<u64> = chain
+            .windows(2)
+            .map(|pair| pair[1].timestamp.saturating_sub(pair[0].timestamp))
+            .collect();
+        let count = block_times.len().max(1);
+        let mean = block_times.iter().sum::<u64>() as f64 / count as f64;
+        let variance = block_times
+            .iter()
+            .map(|time| (*time as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        block_times.sort_unstable();
+        let percentile = |p: usize| -> u64 {
+            block_times
+                .get((block_times.len() * p / 100).min(count - 1))
+                .copied()
+                .unwrap_or_default()
+        };
+        let (convergence_blocks, convergence_secs) = convergence(scenario, config, chain)
+            .map(|(blocks, time)| (Some(blocks), Some(time as f64 / 1000.0)))
+            .unwrap_or_default();
+        Self {
+            scenario: scenario.kind,
+            algorithm: algorithm.to_string(),
+            blocks,
+            orphans,
+            orphan_rate: if blocks + orphans == 0 {
+                0.0
+            } else {
+                orphans as f64 / (blocks + orphans) as f64
+            },
+            block_time_target: config.block_time_target,
+            block_time_mean: mean,
+            block_time_stddev: variance.sqrt(),
+            block_time_min: block_times.first().copied().unwrap_or_default(),
+            block_time_p50: percentile(50),
+            block_time_p90: percentile(90),
+            block_time_p99: percentile(99),
+            block_time_max: block_times.last().copied().unwrap_or_default(),
+            convergence_blocks,
+            convergence_secs,
+        }
+    }
+}
+
+/// The blocks and the time after the hash rate change, until the average block time of the
+/// difficulty window is within the tolerance of the target.
+fn convergence(
+    scenario: &Scenario,
+    config: &SimulationConfig,
+    chain: &[BlockDiffInfo],
+) -> Option<(u64, u64)> {
+    let change_at = scenario.change_at();
+    let window = config.difficulty_window.max(1) as usize;
+    let target = config.block_time_target as f64;
+    let mut block_times = VecDeque::with_capacity(window);
+    for (blocks, pair) in chain
+        .windows(2)
+        .filter(|pair| pair[0].timestamp >= change_at)
+        .enumerate()
+    {
+        if block_times.len() == window {
+            block_times.pop_front();
+        }
+        block_times.push_back(pair[1].timestamp.saturating_sub(pair[0].timestamp));
+        if block_times.len() < window {
+            continue;
+        }
+        let avg = block_times.iter().sum::<u64>() as f64 / window as f64;
+        if (avg - target).abs() <= target * config.convergence_tolerance {
+            return Some((
+                blocks as u64 + 1,
+                pair[1].timestamp.saturating_sub(change_at),
+            ));
+        }
+    }
+    None
+}
+
+pub fn write_csv<W: Write>(writer: W, reports: &[SimulationReport]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for report in reports {
+        writer.serialize(report)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_json<W: Write>(writer: W, reports: &[SimulationReport]) -> Result<()> {
+    serde_json::to_writer_pretty(writer, reports)?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The hash rate scenarios of a simulation.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioKind {
+    /// The hash rate never changes.
+    Steady,
+    /// The hash rate drops at the middle of the simulation.
+    Drop,
+    /// The hash rate switches between high and low periodically.
+    Oscillation,
+    /// A part of the hash rate mines selfishly, withholds the blocks and publishes them to
+    /// orphan the honest blocks.
+    Selfish,
+}
+
+impl ScenarioKind {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Steady, Self::Drop, Self::Oscillation, Self::Selfish]
+    }
+}
+
+impl fmt::Display for ScenarioKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Steady => "steady",
+            Self::Drop => "drop",
+            Self::Oscillation => "oscillation",
+            Self::Selfish => "selfish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ScenarioKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "steady" => Self::Steady,
+            "drop" => Self::Drop,
+            "oscillation" => Self::Oscillation,
+            "selfish" => Self::Selfish,
+            _ => bail!("Unknown scenario: {}", s),
+        })
+    }
+}
+
+/// The parameters of a scenario, the times are in milliseconds and the hash rate is in hashes
+/// per second.
+#[derive(Clone, Debug, Serialize)]
+pub struct Scenario {
+    pub kind: ScenarioKind,
+    pub hash_rate: f64,
+    /// The duration of the simulation.
+    pub duration: u64,
+    /// Part of the hash rate lost by the drop.
+    pub drop_ratio: f64,
+    /// Half period of the oscillation.
+    pub oscillation_period: u64,
+    /// Part of the hash rate added to and removed from the base by the oscillation.
+    pub oscillation_amplitude: f64,
+    /// Part of the hash rate of the selfish miners.
+    pub selfish_share: f64,
+    /// Part of the honest hash rate mining on the selfish block in a fork race.
+    pub selfish_gamma: f64,
+    /// Delay to propagate a block, the blocks found by the others in the delay are orphaned.
+    pub propagation_delay: u64,
+}
+
+impl Scenario {
+    /// The hash rate at the time since the start.
+    pub fn hash_rate_at(&self, time: u64) -> f64 {
+        match self.kind {
+            ScenarioKind::Steady | ScenarioKind::Selfish => self.hash_rate,
+            ScenarioKind::Drop => {
+                if time < self.change_at() {
+                    self.hash_rate
+                } else {
+                    self.hash_rate * (1.0 - self.drop_ratio)
+                }
+            }
+            ScenarioKind::Oscillation => {
+                if (time / self.oscillation_period.max(1)) % 2 == 0 {
+                    self.hash_rate * (1.0 + self.oscillation_amplitude)
+                } else {
+                    self.hash_rate * (1.0 - self.oscillation_amplitude)
+                }
+            }
+        }
+    }
+
+    /// The time since the start the hash rate changes, the convergence is measured from it.
+    pub fn change_at(&self) -> u64 {
+        match self.kind {
+            ScenarioKind::Steady | ScenarioKind::Selfish => 0,
+            ScenarioKind::Drop => self.duration / 2,
+            ScenarioKind::Oscillation => self.oscillation_period,
+        }
+    }
+
+    /// The time the hash rate changes next after the time, the block search is restarted at it.
+    pub fn next_change_after(&self, time: u64) -> Option<u64> {
+        match self.kind {
+            ScenarioKind::Steady | ScenarioKind::Selfish => None,
+            ScenarioKind::Drop => Some(self.change_at()).filter(|change_at| *change_at > time),
+            ScenarioKind::Oscillation => {
+                let period = self.oscillation_period.max(1);
+                Some((time / period + 1) * period)
+            }
+        }
+    }
+
+    pub fn selfish_share(&self) -> f64 {
+        match self.kind {
+            ScenarioKind::Selfish => self.selfish_share,
+            _ => 0.0,
+        }
+    }
+}
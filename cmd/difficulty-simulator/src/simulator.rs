@@ -0,0 +1,259 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Simulate the mining of a chain by the hash rate of a scenario and the difficulty algorithm.
+//!
+//! The time to find a block is exponential distributed with the mean of difficulty / hash rate.
+//! All the miners mine with the target of the public chain head, a block found by the others
+//! in the propagation delay of the former block is orphaned, and the selfish miners follow the
+//! selfish mining strategy of Eyal and Sirer.
+
+use crate::algorithm::DifficultyAlgorithm;
+use crate::report::SimulationReport;
+use crate::scenario::Scenario;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use starcoin_consensus::difficulty::BlockDiffInfo;
+use starcoin_consensus::{difficult_to_target, target_to_difficulty};
+use starcoin_types::U256;
+
+/// The epoch parameters of the difficulty.
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    /// Block time target in milliseconds.
+    pub block_time_target: u64,
+    pub difficulty_window: u64,
+    /// The difficulty of the genesis block, the steady hash rate finds a block by the block time
+    /// target with it by default.
+    pub initial_difficulty: U256,
+    /// Average block time within this part of the block time target is converged.
+    pub convergence_tolerance: f64,
+    pub seed: u64,
+}
+
+fn difficulty_to_f64(difficulty: U256) -> f64 {
+    if difficulty > U256::from(u128::MAX) {
+        u128::MAX as f64
+    } else {
+        difficulty.as_u128() as f64
+    }
+}
+
+pub fn difficulty_from_f64(difficulty: f64) -> U256 {
+    U256::from((difficulty.max(1.0) as u128).max(1))
+}
+
+pub struct Simulator<'a> {
+    scenario: &'a Scenario,
+    algorithm: &'a dyn DifficultyAlgorithm,
+    config: &'a SimulationConfig,
+    rng: StdRng,
+    /// The public chain, the latest last.
+    chain: Vec<BlockDiffInfo>,
+    /// The blocks withheld by the selfish miners.
+    private_blocks: Vec<BlockDiffInfo>,
+    /// The honest block racing with the published selfish block.
+    racing_block: Option<BlockDiffInfo>,
+    orphans: u64,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(
+        scenario: &'a Scenario,
+        algorithm: &'a dyn DifficultyAlgorithm,
+        config: &'a SimulationConfig,
+    ) -> Self {
+        let genesis = BlockDiffInfo::new(0, difficult_to_target(config.initial_difficulty));
+        Self {
+            scenario,
+            algorithm,
+            config,
+            rng: StdRng::seed_from_u64(config.seed),
+            chain: vec![genesis],
+            private_blocks: vec![],
+            racing_block: None,
+            orphans: 0,
+        }
+    }
+
+    /// The target of the next block on the public chain, as `get_next_work_required`.
+    fn next_target(&self) -> Result<U256> {
+        let head = self.chain.last().expect("chain has genesis");
+        if self.chain.len() <= 2 {
+            return Ok(head.target);
+        }
+        let window = (self.config.difficulty_window as usize).min(self.chain.len());
+        let blocks = self.chain[self.chain.len() - window..]
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+        self.algorithm
+            .next_target(blocks, self.config.block_time_target)
+    }
+
+    /// Sample the time of the next block found by the hash rate of the scenario.
+    fn next_block_time(&mut self, mut now: u64, target: U256) -> u64 {
+        let difficulty = difficulty_to_f64(target_to_difficulty(target));
+        loop {
+            let hash_rate = self.scenario.hash_rate_at(now).max(f64::MIN_POSITIVE);
+            let mean = difficulty / hash_rate * 1000.0;
+            let interval = (-(1.0 - self.rng.gen::<f64>()).ln() * mean) as u64;
+            let found_at = now.saturating_add(interval.max(1));
+            match self.scenario.next_change_after(now) {
+                // the search is memoryless, restart it with the new hash rate.
+                Some(change_at) if change_at < found_at => now = change_at,
+                _ => return found_at,
+            }
+        }
+    }
+
+    fn publish_private_blocks(&mut self, count: usize) {
+        let count = count.min(self.private_blocks.len());
+        self.chain.extend(self.private_blocks.drain(..count));
+    }
+
+    fn on_selfish_block(&mut self, block: BlockDiffInfo) {
+        self.private_blocks.push(block);
+        if self.racing_block.take().is_some() {
+            // the selfish branch wins the race, the racing honest block is orphaned.
+            self.orphans += 1;
+            let count = self.private_blocks.len();
+            self.publish_private_blocks(count);
+        }
+    }
+
+    fn on_honest_block(&mut self, block: BlockDiffInfo) {
+        if let Some(racing_block) = self.racing_block.take() {
+            // the published selfish block is the public head in the race.
+            if self
+                .rng
+                .gen_bool(self.scenario.selfish_gamma.clamp(0.0, 1.0))
+            {
+                self.orphans += 1;
+                self.chain.push(block);
+            } else {
+                // the published selfish block is orphaned.
+                self.chain.pop();
+                self.orphans += 1;
+                self.chain.push(racing_block);
+                self.chain.push(block);
+            }
+            return;
+        }
+        match self.private_blocks.len() {
+            0 => self.chain.push(block),
+            1 => {
+                // publish the withheld block to race with the honest block.
+                self.publish_private_blocks(1);
+                self.racing_block = Some(block);
+            }
+            2 => {
+                self.orphans += 1;
+                self.publish_private_blocks(2);
+            }
+            _ => {
+                self.orphans += 1;
+                self.publish_private_blocks(1);
+            }
+        }
+    }
+
+    pub fn run(mut self) -> Result<SimulationReport> {
+        let selfish_share = self.scenario.selfish_share().clamp(0.0, 1.0);
+        let mut now = 0;
+        loop {
+            let target = self.next_target()?;
+            let found_at = self.next_block_time(now, target);
+            if found_at >= self.scenario.duration {
+                break;
+            }
+            now = found_at;
+            let block = BlockDiffInfo::new(found_at, target);
+            let selfish = selfish_share > 0.0 && self.rng.gen_bool(selfish_share);
+            if selfish {
+                self.on_selfish_block(block);
+                continue;
+            }
+            let head_timestamp = self.chain.last().expect("chain has genesis").timestamp;
+            if self.chain.len() > 1
+                && found_at.saturating_sub(head_timestamp) < self.scenario.propagation_delay
+            {
+                // the block is found before the head propagated to the miner.
+                self.orphans += 1;
+                continue;
+            }
+            self.on_honest_block(block);
+        }
+        // the withheld blocks at the end are never published.
+        self.orphans += self.private_blocks.len() as u64;
+        if self.racing_block.is_some() {
+            self.orphans += 1;
+        }
+        Ok(SimulationReport::new(
+            self.scenario,
+            self.algorithm.name(),
+            self.config,
+            &self.chain,
+            self.orphans,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::StarcoinDifficulty;
+    use crate::scenario::ScenarioKind;
+
+    fn scenario(kind: ScenarioKind) -> Scenario {
+        Scenario {
+            kind,
+            hash_rate: 1_000_000.0,
+            duration: 24 * 3600 * 1000,
+            drop_ratio: 0.5,
+            oscillation_period: 3600 * 1000,
+            oscillation_amplitude: 0.5,
+            selfish_share: 0.3,
+            selfish_gamma: 0.5,
+            propagation_delay: 0,
+        }
+    }
+
+    #[test]
+    fn test_simulate_scenarios() {
+        let config = SimulationConfig {
+            block_time_target: 10_000,
+            difficulty_window: 24,
+            initial_difficulty: difficulty_from_f64(10_000_000.0),
+            convergence_tolerance: 0.1,
+            seed: 1,
+        };
+        let steady = scenario(ScenarioKind::Steady);
+        let report = Simulator::new(&steady, &StarcoinDifficulty, &config)
+            .run()
+            .unwrap();
+        assert!(report.blocks > 8000);
+        assert_eq!(report.orphans, 0);
+        assert!((report.block_time_mean - 10_000.0).abs() < 1000.0);
+        // the same seed simulates the same chain.
+        let report2 = Simulator::new(&steady, &StarcoinDifficulty, &config)
+            .run()
+            .unwrap();
+        assert_eq!(report.blocks, report2.blocks);
+
+        let drop = scenario(ScenarioKind::Drop);
+        let report = Simulator::new(&drop, &StarcoinDifficulty, &config)
+            .run()
+            .unwrap();
+        assert!(report.convergence_blocks.is_some());
+
+        let selfish = scenario(ScenarioKind::Selfish);
+        let report = Simulator::new(&selfish, &StarcoinDifficulty, &config)
+            .run()
+            .unwrap();
+        assert!(report.orphans > 0);
+        assert!(report.orphan_rate > 0.0 && report.orphan_rate < 1.0);
+    }
+}
@@ -1,28 +1,98 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Result};
+use crate::queue::{BatchTransfer, TransferError};
+use anyhow::{bail, ensure, format_err, Result};
 use starcoin_account_api::AccountInfo;
 use starcoin_crypto::HashValue;
 use starcoin_logger::prelude::*;
+use starcoin_rpc_api::types::TransactionStatusView;
 use starcoin_rpc_client::{RpcClient, StateRootOption};
 use starcoin_state_api::StateReaderExt;
-use starcoin_transaction_builder::build_transfer_txn;
+use starcoin_transaction_builder::{build_batch_transfer_txn_by_token_type, build_transfer_txn};
 use starcoin_types::account::DEFAULT_EXPIRATION_TIME;
 use starcoin_types::account_address::AccountAddress;
+use starcoin_types::account_config::token_code::TokenCode;
 use starcoin_types::account_config::token_value::TokenValue;
-use starcoin_types::account_config::STCUnit;
+use starcoin_types::account_config::{STCUnit, G_STC_TOKEN_CODE};
+use starcoin_types::transaction::RawUserTransaction;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::time::Duration;
 
+/// An amount of a token in the token's smallest unit, parsed from `TOKEN_CODE=AMOUNT`,
+/// see `parse_amount` for the format of the amount.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenAmount {
+    pub token_code: TokenCode,
+    pub amount: u128,
+}
+
+impl FromStr for TokenAmount {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (token_code, amount) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format_err!("Invalid token amount {}, expect TOKEN_CODE=AMOUNT", s))?;
+        let token_code = TokenCode::from_str(token_code.trim())?;
+        let amount = parse_amount(&token_code, amount)?;
+        Ok(Self { token_code, amount })
+    }
+}
+
+/// Parse the amount of the token to its smallest unit. STC amount supports decimals and units,
+/// such as `1.5 STC` or `100 nanoSTC`, and is in STC without a unit. The amount of other tokens
+/// is an integer in the token's smallest unit.
+pub fn parse_amount(token_code: &TokenCode, amount: &str) -> Result<u128> {
+    let amount = amount.trim();
+    if token_code == &*G_STC_TOKEN_CODE {
+        Ok(TokenValue::<STCUnit>::from_str(amount)?.scaling())
+    } else {
+        Ok(amount.parse()?)
+    }
+}
+
+/// The amount to fund for the requested `amount`, the `max_amount` if `amount` is absent or
+/// exceeds it.
+fn clamp_fund_amount(
+    token_code: &TokenCode,
+    amount: Option<&str>,
+    max_amount: u128,
+) -> Result<u128> {
+    let amount = match amount {
+        Some(amount) => parse_amount(token_code, amount)?,
+        None => max_amount,
+    };
+    ensure!(amount > 0, "Fund amount should be greater than 0");
+    Ok(amount.min(max_amount))
+}
+
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.token_code == *G_STC_TOKEN_CODE {
+            write!(f, "{}={}nanoSTC", self.token_code, self.amount)
+        } else {
+            write!(f, "{}={}", self.token_code, self.amount)
+        }
+    }
+}
+
 pub struct Faucet {
     client: RpcClient,
     faucet_account: AccountInfo,
     faucet_account_password: String,
-    max_amount_pre_request: TokenValue<STCUnit>,
+    /// The accepted tokens and their max amount per request.
+    max_amounts: HashMap<TokenCode, u128>,
 }
 
 const DEFAULT_GAS_PRICE: u64 = 1;
 const MAX_GAS: u64 = 1000000;
+/// Max gas of a batch transfer txn, it grows with the receivers.
+const MAX_BATCH_GAS: u64 = 40000000;
+/// Max time to wait for a batch transfer txn to be executed.
+const TXN_WATCH_TIMEOUT: Duration = Duration::from_secs(300);
 
 impl Faucet {
     pub fn new(
@@ -31,37 +101,115 @@ impl Faucet {
         faucet_account_password: String,
         max_amount_pre_request: TokenValue<STCUnit>,
     ) -> Self {
-        Faucet {
+        Self::new_with_tokens(
             client,
             faucet_account,
             faucet_account_password,
             max_amount_pre_request,
+            vec![],
+        )
+    }
+
+    /// The faucet funds STC and the `tokens`, an amount in `tokens` overrides the `max_amount_pre_request` of STC.
+    pub fn new_with_tokens(
+        client: RpcClient,
+        faucet_account: AccountInfo,
+        faucet_account_password: String,
+        max_amount_pre_request: TokenValue<STCUnit>,
+        tokens: Vec<TokenAmount>,
+    ) -> Self {
+        let mut max_amounts = HashMap::new();
+        max_amounts.insert(G_STC_TOKEN_CODE.clone(), max_amount_pre_request.scaling());
+        max_amounts.extend(
+            tokens
+                .into_iter()
+                .map(|token| (token.token_code, token.amount)),
+        );
+        Faucet {
+            client,
+            faucet_account,
+            faucet_account_password,
+            max_amounts,
         }
     }
 
+    pub fn is_accepted(&self, token_code: &TokenCode) -> bool {
+        self.max_amounts.contains_key(token_code)
+    }
+
+    /// The amount to fund for the requested `amount` string, the max amount per request if `amount`
+    /// is absent or exceeds the max amount, see `parse_amount` for the format of `amount`.
+    pub fn fund_amount(&self, token_code: &TokenCode, amount: Option<&str>) -> Result<u128> {
+        let max_amount = *self
+            .max_amounts
+            .get(token_code)
+            .ok_or_else(|| format_err!("Token {} is not supported by the faucet", token_code))?;
+        clamp_fund_amount(token_code, amount, max_amount)
+    }
+
     pub fn transfer(
         &self,
         amount: Option<TokenValue<STCUnit>>,
         receiver: AccountAddress,
     ) -> Result<HashValue> {
+        let max_amount = self.max_amounts[&*G_STC_TOKEN_CODE];
         let amount = amount
-            .and_then(|value| {
-                if value.scaling() > self.max_amount_pre_request.scaling() {
-                    None
-                } else {
-                    Some(value)
-                }
-            })
-            .unwrap_or(self.max_amount_pre_request);
-
-        let sequence_number = match self
+            .map(|value| value.scaling())
+            .filter(|value| *value <= max_amount)
+            .unwrap_or(max_amount);
+
+        let sequence_number = self.next_sequence_number()?;
+        let node_info = self.client.node_info()?;
+        let raw_tx = build_transfer_txn(
+            self.faucet_account.address,
+            receiver,
+            sequence_number,
+            amount,
+            DEFAULT_GAS_PRICE,
+            MAX_GAS,
+            node_info.now_seconds + DEFAULT_EXPIRATION_TIME,
+            node_info.net.chain_id(),
+        );
+        info!("sender transaction: {:?}", raw_tx);
+        self.sign_and_submit(raw_tx)
+    }
+
+    fn submit_batch_transfer(
+        &self,
+        token_code: TokenCode,
+        receivers: Vec<(AccountAddress, u128)>,
+    ) -> Result<HashValue> {
+        if !self.is_accepted(&token_code) {
+            bail!("Token {} is not supported by the faucet", token_code);
+        }
+        ensure!(!receivers.is_empty(), "Batch transfer without receivers");
+        let (receivers, amounts) = receivers.into_iter().unzip();
+        let sequence_number = self.next_sequence_number()?;
+        let node_info = self.client.node_info()?;
+        let raw_tx = build_batch_transfer_txn_by_token_type(
+            self.faucet_account.address,
+            receivers,
+            amounts,
+            sequence_number,
+            DEFAULT_GAS_PRICE,
+            MAX_BATCH_GAS,
+            token_code,
+            node_info.now_seconds + DEFAULT_EXPIRATION_TIME,
+            node_info.net.chain_id(),
+        )?;
+        info!("sender batch transaction: {:?}", raw_tx);
+        self.sign_and_submit(raw_tx)
+    }
+
+    fn next_sequence_number(&self) -> Result<u64> {
+        match self
             .client
             .next_sequence_number_in_txpool(*self.faucet_account.address())?
         {
-            Some(sequence_number) => sequence_number,
+            Some(sequence_number) => Ok(sequence_number),
             None => {
                 let chain_state_reader = self.client.state_reader(StateRootOption::Latest)?;
-                chain_state_reader
+                Ok(chain_state_reader
                     .get_account_resource(*self.faucet_account.address())?
                     .ok_or_else(|| {
                         format_err!(
@@ -69,21 +217,12 @@ impl Faucet {
                             self.faucet_account.address()
                         )
                     })?
-                    .sequence_number()
+                    .sequence_number())
             }
-        };
-        let node_info = self.client.node_info()?;
-        let raw_tx = build_transfer_txn(
-            self.faucet_account.address,
-            receiver,
-            sequence_number,
-            amount.scaling(),
-            DEFAULT_GAS_PRICE,
-            MAX_GAS,
-            node_info.now_seconds + DEFAULT_EXPIRATION_TIME,
-            node_info.net.chain_id(),
-        );
-        info!("sender transaction: {:?}", raw_tx);
+        }
+    }
+
+    fn sign_and_submit(&self, raw_tx: RawUserTransaction) -> Result<HashValue> {
         self.client.account_unlock(
             self.faucet_account.address,
             self.faucet_account_password.clone(),
@@ -95,3 +234,93 @@ impl Faucet {
         self.client.submit_transaction(signed_tx)
     }
 }
+
+impl BatchTransfer for Faucet {
+    fn batch_transfer(
+        &self,
+        token_code: TokenCode,
+        receivers: Vec<(AccountAddress, u128)>,
+    ) -> Result<HashValue, TransferError> {
+        let txn_hash = self
+            .submit_batch_transfer(token_code, receivers)
+            .map_err(TransferError::NotFunded)?;
+        self.client
+            .watch_txn(txn_hash, Some(TXN_WATCH_TIMEOUT))
+            .map_err(|e| {
+                TransferError::Unknown(format_err!("wait txn {} failed: {}", txn_hash, e))
+            })?;
+        let txn_info = self
+            .client
+            .chain_get_transaction_info(txn_hash)
+            .map_err(TransferError::Unknown)?
+            .ok_or_else(|| {
+                TransferError::Unknown(format_err!("Can not find txn info of {}", txn_hash))
+            })?;
+        if txn_info.status != TransactionStatusView::Executed {
+            return Err(TransferError::NotFunded(format_err!(
+                "Txn {} failed: {:?}",
+                txn_hash,
+                txn_info.status
+            )));
+        }
+        Ok(txn_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_amount() {
+        let stc = G_STC_TOKEN_CODE.clone();
+        let parse = |s: &str| TokenAmount::from_str(s).map(|token_amount| token_amount.amount);
+        let token = TokenAmount::from_str(" 0x1::STC::STC = 1 ").unwrap();
+        assert_eq!(token.token_code, stc);
+        assert_eq!(token.amount, 1_000_000_000);
+        // the display is parsed back to the same amount.
+        assert_eq!(
+            TokenAmount::from_str(token.to_string().as_str()).unwrap(),
+            token
+        );
+        // decimals and units.
+        assert_eq!(parse("0x1::STC::STC=1.5").unwrap(), 1_500_000_000);
+        assert_eq!(parse("0x1::STC::STC=1.5 STC").unwrap(), 1_500_000_000);
+        assert_eq!(parse("0x1::STC::STC=2milliSTC").unwrap(), 2_000_000);
+        assert_eq!(parse("0x1::STC::STC=100 nanoSTC").unwrap(), 100);
+        // other tokens are in the smallest unit.
+        assert_eq!(parse("0x1::XToken::XToken=100").unwrap(), 100);
+        assert!(parse("0x1::XToken::XToken=1.5").is_err());
+        assert!(parse("0x1::XToken::XToken=1 STC").is_err());
+        // invalid inputs.
+        assert!(parse("0x1::STC::STC").is_err());
+        assert!(parse("0x1::STC::STC=").is_err());
+        assert!(parse("0x1::STC::STC=-1").is_err());
+        assert!(parse("0x1::STC::STC=1 BTC").is_err());
+        assert!(parse("0x1::STC::STC=0.0000000001").is_err());
+        assert!(parse("STC=1").is_err());
+    }
+
+    #[test]
+    fn test_clamp_fund_amount() {
+        let stc = G_STC_TOKEN_CODE.clone();
+        let token = TokenCode::from_str("0x1::XToken::XToken").unwrap();
+        let max_amount = 10_000_000_000;
+        assert_eq!(
+            clamp_fund_amount(&stc, None, max_amount).unwrap(),
+            max_amount
+        );
+        assert_eq!(
+            clamp_fund_amount(&stc, Some("1.5"), max_amount).unwrap(),
+            1_500_000_000
+        );
+        assert_eq!(
+            clamp_fund_amount(&stc, Some("10000 STC"), max_amount).unwrap(),
+            max_amount
+        );
+        assert_eq!(clamp_fund_amount(&token, Some("100"), 50).unwrap(), 50);
+        assert_eq!(clamp_fund_amount(&token, Some("20"), 50).unwrap(), 20);
+        assert!(clamp_fund_amount(&stc, Some("0"), max_amount).is_err());
+        assert!(clamp_fund_amount(&token, Some("abc"), 50).is_err());
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod faucet;
+pub mod limiter;
+pub mod queue;
 pub mod web;
 
 #[macro_export]
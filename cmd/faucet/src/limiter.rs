@@ -0,0 +1,274 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limits of the fund requests per address and per IP over a sliding window,
+//! and the daily budget of every token. The state is kept in a local JSON file to survive restarts.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use starcoin_logger::prelude::*;
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::account_config::token_code::TokenCode;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug)]
+pub struct LimitConfig {
+    /// Max fund requests of an address in the window, 0 means no limit.
+    pub max_requests_per_address: usize,
+    /// Max fund requests from an IP in the window, 0 means no limit.
+    pub max_requests_per_ip: usize,
+    pub window_secs: u64,
+    /// Max amount of the token funded in a UTC day, the tokens absent are not limited.
+    pub daily_budgets: HashMap<TokenCode, u128>,
+}
+
+/// A fund request admitted by the limiter, release it if the fund fails.
+#[derive(Clone, Debug)]
+pub struct Admission {
+    address: AccountAddress,
+    ip: Option<IpAddr>,
+    token_code: TokenCode,
+    amount: u128,
+    time: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct DailySpent {
+    day: u64,
+    /// Spent amount of every token in the day, the token code is in string for the json map key.
+    spent: HashMap<String, u128>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LimitState {
+    /// The request timestamps in seconds of every address in the window, in ascending order.
+    addresses: HashMap<AccountAddress, VecDeque<u64>>,
+    ips: HashMap<IpAddr, VecDeque<u64>>,
+    daily: DailySpent,
+}
+
+fn expire(requests: &mut VecDeque<u64>, window_start: u64) {
+    while matches!(requests.front(), Some(time) if *time < window_start) {
+        requests.pop_front();
+    }
+}
+
+fn remove_request(requests: &mut VecDeque<u64>, time: u64) {
+    if let Some(idx) = requests.iter().rposition(|t| *t == time) {
+        requests.remove(idx);
+    }
+}
+
+impl LimitState {
+    /// Admit the fund request at `now` in seconds, or return the reason to reject it.
+    pub fn admit(
+        &mut self,
+        config: &LimitConfig,
+        address: AccountAddress,
+        ip: Option<IpAddr>,
+        token_code: &TokenCode,
+        amount: u128,
+        now: u64,
+    ) -> Result<Admission> {
+        let window_start = now.saturating_sub(config.window_secs);
+        let address_requests = self.addresses.entry(address).or_default();
+        expire(address_requests, window_start);
+        if config.max_requests_per_address > 0
+            && address_requests.len() >= config.max_requests_per_address
+        {
+            bail!(
+                "Address {} reached the limit of {} requests in {} seconds",
+                address,
+                config.max_requests_per_address,
+                config.window_secs
+            );
+        }
+        if let Some(ip) = ip {
+            let ip_requests = self.ips.entry(ip).or_default();
+            expire(ip_requests, window_start);
+            if config.max_requests_per_ip > 0 && ip_requests.len() >= config.max_requests_per_ip {
+                bail!(
+                    "IP {} reached the limit of {} requests in {} seconds",
+                    ip,
+                    config.max_requests_per_ip,
+                    config.window_secs
+                );
+            }
+        }
+        let day = now / SECONDS_PER_DAY;
+        if self.daily.day != day {
+            self.daily = DailySpent {
+                day,
+                spent: HashMap::new(),
+            };
+        }
+        let token_key = token_code.to_string();
+        let spent = self
+            .daily
+            .spent
+            .get(&token_key)
+            .copied()
+            .unwrap_or_default();
+        if let Some(budget) = config.daily_budgets.get(token_code) {
+            if spent.saturating_add(amount) > *budget {
+                bail!(
+                    "The daily budget of {} is exhausted, try again tomorrow",
+                    token_code
+                );
+            }
+        }
+
+        self.addresses.entry(address).or_default().push_back(now);
+        if let Some(ip) = ip {
+            self.ips.entry(ip).or_default().push_back(now);
+        }
+        self.daily
+            .spent
+            .insert(token_key, spent.saturating_add(amount));
+        Ok(Admission {
+            address,
+            ip,
+            token_code: token_code.clone(),
+            amount,
+            time: now,
+        })
+    }
+
+    /// Release an admitted request which failed to fund.
+    pub fn release(&mut self, admission: &Admission) {
+        if let Some(requests) = self.addresses.get_mut(&admission.address) {
+            remove_request(requests, admission.time);
+        }
+        if let Some(requests) = admission.ip.and_then(|ip| self.ips.get_mut(&ip)) {
+            remove_request(requests, admission.time);
+        }
+        if self.daily.day == admission.time / SECONDS_PER_DAY {
+            if let Some(spent) = self.daily.spent.get_mut(&admission.token_code.to_string()) {
+                *spent = spent.saturating_sub(admission.amount);
+            }
+        }
+    }
+
+    /// Remove the addresses and IPs without requests in the window.
+    fn prune(&mut self, window_start: u64) {
+        for requests in self.addresses.values_mut() {
+            expire(requests, window_start);
+        }
+        self.addresses.retain(|_, requests| !requests.is_empty());
+        for requests in self.ips.values_mut() {
+            expire(requests, window_start);
+        }
+        self.ips.retain(|_, requests| !requests.is_empty());
+    }
+}
+
+/// The limiter with its state persisted in a local file.
+pub struct FaucetLimiter {
+    config: LimitConfig,
+    path: PathBuf,
+    state: LimitState,
+}
+
+impl FaucetLimiter {
+    pub fn open(config: LimitConfig, path: PathBuf) -> Result<Self> {
+        let state = if path.exists() {
+            serde_json::from_slice(fs::read(&path)?.as_slice())?
+        } else {
+            LimitState::default()
+        };
+        Ok(Self {
+            config,
+            path,
+            state,
+        })
+    }
+
+    pub fn admit(
+        &mut self,
+        address: AccountAddress,
+        ip: Option<IpAddr>,
+        token_code: &TokenCode,
+        amount: u128,
+        now: u64,
+    ) -> Result<Admission> {
+        let admission = self
+            .state
+            .admit(&self.config, address, ip, token_code, amount, now)?;
+        self.state
+            .prune(now.saturating_sub(self.config.window_secs));
+        self.save();
+        Ok(admission)
+    }
+
+    pub fn release(&mut self, admission: &Admission) {
+        self.state.release(admission);
+        self.save();
+    }
+
+    fn save(&self) {
+        let result = serde_json::to_vec(&self.state)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| {
+                // write to a temp file then rename, so the store is not corrupted by a crash.
+                let tmp_path = self.path.with_extension("tmp");
+                fs::write(&tmp_path, bytes)?;
+                fs::rename(&tmp_path, &self.path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            error!("Save faucet limits to {:?} failed: {:?}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starcoin_types::account_config::G_STC_TOKEN_CODE;
+
+    #[test]
+    fn test_limit_state() {
+        let config = LimitConfig {
+            max_requests_per_address: 2,
+            max_requests_per_ip: 2,
+            window_secs: 100,
+            daily_budgets: vec![(G_STC_TOKEN_CODE.clone(), 50)].into_iter().collect(),
+        };
+        let stc = G_STC_TOKEN_CODE.clone();
+        let ip: Option<IpAddr> = Some("127.0.0.1".parse().unwrap());
+        let address1 = AccountAddress::random();
+        let address2 = AccountAddress::random();
+        let mut state = LimitState::default();
+
+        state.admit(&config, address1, ip, &stc, 10, 1000).unwrap();
+        let admission = state.admit(&config, address1, ip, &stc, 10, 1010).unwrap();
+        // the address limit.
+        assert!(state
+            .admit(&config, address1, None, &stc, 10, 1020)
+            .is_err());
+        // the released request is not counted.
+        state.release(&admission);
+        state.admit(&config, address1, ip, &stc, 10, 1020).unwrap();
+        // the ip limit.
+        assert!(state.admit(&config, address2, ip, &stc, 10, 1030).is_err());
+        // the daily budget.
+        assert!(state
+            .admit(&config, address2, None, &stc, 31, 1030)
+            .is_err());
+        state
+            .admit(&config, address2, None, &stc, 30, 1030)
+            .unwrap();
+        // the window slides.
+        assert!(state.admit(&config, address1, ip, &stc, 0, 1099).is_err());
+        state.admit(&config, address1, ip, &stc, 0, 1101).unwrap();
+        // the budget is reset in the next day.
+        state
+            .admit(&config, address2, None, &stc, 50, SECONDS_PER_DAY * 2)
+            .unwrap();
+    }
+}
@@ -4,12 +4,17 @@
 use anyhow::{format_err, Result};
 use clap::Parser;
 use futures::executor;
-use starcoin_faucet::{faucet::Faucet, web};
+use starcoin_faucet::faucet::{Faucet, TokenAmount};
+use starcoin_faucet::limiter::{FaucetLimiter, LimitConfig};
+use starcoin_faucet::queue::{FundQueue, QueueConfig};
+use starcoin_faucet::web::{self, FaucetService};
 use starcoin_rpc_client::RpcClient;
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::account_config::token_value::TokenValue;
 use starcoin_types::account_config::STCUnit;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tiny_http::Server;
 
 #[derive(Debug, Clone, Parser)]
@@ -25,6 +30,36 @@ pub struct FaucetOpt {
     pub faucet_account_password: String,
     #[clap(long, short = 'm', default_value = "1 STC")]
     pub max_amount_pre_request: TokenValue<STCUnit>,
+    /// Fund the token besides STC, in TOKEN_CODE=MAX_AMOUNT format, the max amount per request is in the token's smallest unit.
+    #[clap(long = "token-code", multiple_occurrences = true)]
+    pub token_codes: Vec<TokenAmount>,
+    /// Max amount of a token funded in a UTC day, in TOKEN_CODE=AMOUNT format, the STC amount supports units, such as `0x1::STC::STC=1000 STC`.
+    #[clap(long = "daily-budget", multiple_occurrences = true)]
+    pub daily_budgets: Vec<TokenAmount>,
+    /// Max fund requests of an address in the limit window, 0 means no limit.
+    #[clap(long, default_value = "3")]
+    pub max_requests_per_address: usize,
+    /// Max fund requests from an IP in the limit window, 0 means no limit.
+    #[clap(long, default_value = "10")]
+    pub max_requests_per_ip: usize,
+    /// The sliding window of the request limits in seconds.
+    #[clap(long, default_value = "86400")]
+    pub limit_window: u64,
+    /// The trusted proxies in front of the faucet, the client IP is taken from the X-Forwarded-For entry appended by the outermost trusted proxy, 0 ignores the header.
+    #[clap(long, default_value = "0")]
+    pub trusted_proxies: usize,
+    /// The local file to keep the request limits across restarts.
+    #[clap(long, parse(from_os_str), default_value = "faucet_limits.json")]
+    pub limit_store: PathBuf,
+    /// Max receivers funded in a batch transfer txn.
+    #[clap(long, default_value = "32")]
+    pub batch_size: usize,
+    /// Max seconds to wait for more requests to fill a batch.
+    #[clap(long, default_value = "5")]
+    pub batch_interval: u64,
+    /// Max queued fund requests.
+    #[clap(long, default_value = "1024")]
+    pub max_pending: usize,
 }
 
 fn main() -> Result<()> {
@@ -42,13 +77,37 @@ fn main() -> Result<()> {
     let account = account
         .ok_or_else(|| format_err!("Can not find default account, Please input from account."))?;
     let faucet_address = account.address;
-    let faucet = Faucet::new(
+    let faucet = Arc::new(Faucet::new_with_tokens(
         client,
         account,
         opts.faucet_account_password.clone(),
         opts.max_amount_pre_request,
-    );
-    let fut = web::run(server, faucet);
+        opts.token_codes.clone(),
+    ));
+    let limiter = Arc::new(Mutex::new(FaucetLimiter::open(
+        LimitConfig {
+            max_requests_per_address: opts.max_requests_per_address,
+            max_requests_per_ip: opts.max_requests_per_ip,
+            window_secs: opts.limit_window,
+            daily_budgets: opts
+                .daily_budgets
+                .iter()
+                .map(|budget| (budget.token_code.clone(), budget.amount))
+                .collect(),
+        },
+        opts.limit_store.clone(),
+    )?));
+    let queue = FundQueue::start(
+        QueueConfig {
+            max_pending: opts.max_pending,
+            max_batch_size: opts.batch_size.max(1),
+            batch_interval: Duration::from_secs(opts.batch_interval),
+        },
+        faucet.clone(),
+        limiter.clone(),
+    )?;
+    let service = FaucetService::new(faucet, limiter, queue, opts.trusted_proxies);
+    let fut = web::run(server, service);
     println!(
         "Faucet serve on: {}, with faucet account: {}",
         opts.server_addr, faucet_address
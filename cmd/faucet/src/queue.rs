@@ -0,0 +1,337 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Queue the admitted fund requests and batch them into a few transfer txns.
+
+use crate::limiter::{Admission, FaucetLimiter};
+use anyhow::{format_err, Result};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::account_config::token_code::TokenCode;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Transfer a token to all the receivers in a txn, implemented by the `Faucet`.
+pub trait BatchTransfer: Send + Sync + 'static {
+    /// Return the hash of the txn after it is executed successfully on chain.
+    fn batch_transfer(
+        &self,
+        token_code: TokenCode,
+        receivers: Vec<(AccountAddress, u128)>,
+    ) -> Result<HashValue, TransferError>;
+}
+
+#[derive(Debug)]
+pub enum TransferError {
+    /// No receiver is funded, such as the txn is rejected or fails on chain.
+    NotFunded(anyhow::Error),
+    /// The result of the submitted txn is unknown, the receivers may still be funded.
+    Unknown(anyhow::Error),
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFunded(e) => write!(f, "{}", e),
+            Self::Unknown(e) => write!(f, "unknown transfer result, {}", e),
+        }
+    }
+}
+
+/// Called with the txn hash of the batch funding the request, or the error.
+pub type FundCallback = Box<dyn FnOnce(Result<HashValue>) + Send>;
+
+pub struct PendingFund {
+    pub token_code: TokenCode,
+    pub receiver: AccountAddress,
+    pub amount: u128,
+    pub admission: Admission,
+    pub callback: FundCallback,
+}
+
+#[derive(Clone, Debug)]
+pub struct QueueConfig {
+    /// Max pending fund requests, the new requests are rejected when the queue is full.
+    pub max_pending: usize,
+    /// Max receivers in a batch transfer txn.
+    pub max_batch_size: usize,
+    /// Max time to wait for more requests to fill a batch.
+    pub batch_interval: Duration,
+}
+
+#[derive(Clone)]
+pub struct FundQueue {
+    sender: SyncSender<PendingFund>,
+}
+
+impl FundQueue {
+    /// Start the thread to fund the queued requests.
+    pub fn start<F: BatchTransfer>(
+        config: QueueConfig,
+        faucet: Arc<F>,
+        limiter: Arc<Mutex<FaucetLimiter>>,
+    ) -> Result<Self> {
+        let (sender, receiver) = sync_channel(config.max_pending);
+        std::thread::Builder::new()
+            .name("faucet-fund".to_string())
+            .spawn(move || fund_loop(config, faucet, limiter, receiver))?;
+        Ok(Self { sender })
+    }
+
+    /// Queue the request, return the request back if the queue is full or stopped.
+    pub fn push(&self, request: PendingFund) -> Result<(), PendingFund> {
+        self.sender.try_send(request).map_err(|e| match e {
+            TrySendError::Full(request) | TrySendError::Disconnected(request) => request,
+        })
+    }
+}
+
+fn fund_loop<F: BatchTransfer>(
+    config: QueueConfig,
+    faucet: Arc<F>,
+    limiter: Arc<Mutex<FaucetLimiter>>,
+    receiver: Receiver<PendingFund>,
+) {
+    loop {
+        let first = match receiver.recv() {
+            Ok(request) => request,
+            Err(_) => break,
+        };
+        let deadline = Instant::now() + config.batch_interval;
+        let mut requests = vec![first];
+        while requests.len() < config.max_batch_size {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(request) => requests.push(request),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        // a batch transfer txn per token.
+        let mut batches: BTreeMap<TokenCode, Vec<PendingFund>> = BTreeMap::new();
+        for request in requests {
+            batches
+                .entry(request.token_code.clone())
+                .or_default()
+                .push(request);
+        }
+        for (token_code, requests) in batches {
+            fund_batch(faucet.as_ref(), &limiter, token_code, requests);
+        }
+    }
+}
+
+/// Fund the requests of a token in a txn. If the txn funds nobody, the receivers are funded one
+/// by one, so a bad receiver only fails its own requests.
+fn fund_batch<F: BatchTransfer>(
+    faucet: &F,
+    limiter: &Mutex<FaucetLimiter>,
+    token_code: TokenCode,
+    requests: Vec<PendingFund>,
+) {
+    // the requests of a receiver are merged into one transfer.
+    let mut receivers: Vec<(AccountAddress, Vec<PendingFund>)> = vec![];
+    for request in requests {
+        match receivers
+            .iter_mut()
+            .find(|(receiver, _)| *receiver == request.receiver)
+        {
+            Some((_, requests)) => requests.push(request),
+            None => receivers.push((request.receiver, vec![request])),
+        }
+    }
+    let transfers = receivers
+        .iter()
+        .map(|(receiver, requests)| {
+            let amount = requests.iter().fold(0u128, |amount, request| {
+                amount.saturating_add(request.amount)
+            });
+            (*receiver, amount)
+        })
+        .collect();
+    let requests_count: usize = receivers.iter().map(|(_, requests)| requests.len()).sum();
+    let error = match faucet.batch_transfer(token_code.clone(), transfers) {
+        Ok(txn_hash) => {
+            info!(
+                "Fund {} requests of {} by txn {}",
+                requests_count, token_code, txn_hash
+            );
+            for (_, requests) in receivers {
+                for request in requests {
+                    (request.callback)(Ok(txn_hash));
+                }
+            }
+            return;
+        }
+        Err(TransferError::NotFunded(e)) if receivers.len() > 1 => {
+            warn!(
+                "Fund {} requests of {} failed: {:?}, fund the receivers one by one",
+                requests_count, token_code, e
+            );
+            for (_, requests) in receivers {
+                fund_batch(faucet, limiter, token_code.clone(), requests);
+            }
+            return;
+        }
+        Err(e) => e,
+    };
+    error!(
+        "Fund {} requests of {} failed: {:?}",
+        requests_count, token_code, error
+    );
+    // the admissions are kept if the receivers may still be funded.
+    if let TransferError::NotFunded(_) = &error {
+        let mut limiter = limiter.lock().expect("faucet limiter lock poisoned");
+        for (_, requests) in &receivers {
+            for request in requests {
+                limiter.release(&request.admission);
+            }
+        }
+    }
+    for (_, requests) in receivers {
+        for request in requests {
+            (request.callback)(Err(format_err!("{}", error)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limiter::LimitConfig;
+    use starcoin_types::account_config::G_STC_TOKEN_CODE;
+    use std::str::FromStr;
+    use std::sync::mpsc::channel;
+
+    type Batches = Mutex<Vec<(TokenCode, Vec<(AccountAddress, u128)>)>>;
+
+    /// Record the batches, a batch to `bad_receiver` funds nobody, and the result of the
+    /// `unknown_token` transfer is unknown.
+    struct MockTransfer {
+        batches: Batches,
+        bad_receiver: AccountAddress,
+        unknown_token: TokenCode,
+    }
+
+    impl BatchTransfer for MockTransfer {
+        fn batch_transfer(
+            &self,
+            token_code: TokenCode,
+            receivers: Vec<(AccountAddress, u128)>,
+        ) -> Result<HashValue, TransferError> {
+            self.batches
+                .lock()
+                .unwrap()
+                .push((token_code.clone(), receivers.clone()));
+            if token_code == self.unknown_token {
+                return Err(TransferError::Unknown(format_err!("wait txn timeout")));
+            }
+            if receivers
+                .iter()
+                .any(|(receiver, _)| *receiver == self.bad_receiver)
+            {
+                return Err(TransferError::NotFunded(format_err!("txn aborted")));
+            }
+            Ok(HashValue::random())
+        }
+    }
+
+    #[test]
+    fn test_fund_loop() {
+        let stc = G_STC_TOKEN_CODE.clone();
+        let token = TokenCode::from_str("0x1::XToken::XToken").unwrap();
+        let (receiver1, receiver2, receiver3, bad_receiver) = (
+            AccountAddress::random(),
+            AccountAddress::random(),
+            AccountAddress::random(),
+            AccountAddress::random(),
+        );
+        let limit_dir = starcoin_config::temp_dir();
+        let limiter = Arc::new(Mutex::new(
+            FaucetLimiter::open(
+                LimitConfig {
+                    max_requests_per_address: 2,
+                    max_requests_per_ip: 0,
+                    window_secs: 100,
+                    daily_budgets: Default::default(),
+                },
+                limit_dir.path().join("limits.json"),
+            )
+            .unwrap(),
+        ));
+        let faucet = Arc::new(MockTransfer {
+            batches: Mutex::new(vec![]),
+            bad_receiver,
+            unknown_token: token.clone(),
+        });
+
+        let requests = vec![
+            (stc.clone(), receiver1, 1),
+            (token.clone(), receiver2, 5),
+            (stc.clone(), receiver1, 2),
+            (stc.clone(), bad_receiver, 4),
+            (stc.clone(), receiver3, 3),
+        ];
+        let (sender, receiver) = sync_channel(requests.len());
+        let (result_sender, result_receiver) = channel();
+        for (idx, (token_code, receiver, amount)) in requests.into_iter().enumerate() {
+            let admission = limiter
+                .lock()
+                .unwrap()
+                .admit(receiver, None, &token_code, amount, idx as u64)
+                .unwrap();
+            let result_sender = result_sender.clone();
+            sender
+                .try_send(PendingFund {
+                    token_code,
+                    receiver,
+                    amount,
+                    admission,
+                    callback: Box::new(move |result| {
+                        result_sender.send((idx, result.is_ok())).unwrap();
+                    }),
+                })
+                .unwrap_or_else(|_| panic!("queue should not be full"));
+        }
+        drop(sender);
+        let config = QueueConfig {
+            max_pending: 5,
+            max_batch_size: 4,
+            batch_interval: Duration::from_millis(100),
+        };
+        // the loop exits after the queued requests are funded and the sender is dropped.
+        fund_loop(config, faucet.clone(), limiter.clone(), receiver);
+
+        // the first 4 requests are batched by token, and the requests of receiver1 are merged.
+        // the failed batch is funded one by one, so only the requests of bad_receiver fail.
+        let batches = faucet.batches.lock().unwrap().clone();
+        assert_eq!(
+            batches,
+            vec![
+                (stc.clone(), vec![(receiver1, 3), (bad_receiver, 4)]),
+                (stc.clone(), vec![(receiver1, 3)]),
+                (stc.clone(), vec![(bad_receiver, 4)]),
+                (token.clone(), vec![(receiver2, 5)]),
+                (stc.clone(), vec![(receiver3, 3)]),
+            ]
+        );
+        let mut results: Vec<_> = result_receiver.try_iter().collect();
+        results.sort();
+        assert_eq!(
+            results,
+            vec![(0, true), (1, false), (2, true), (3, false), (4, true)]
+        );
+
+        let mut limiter = limiter.lock().unwrap();
+        // the funded admissions are kept.
+        assert!(limiter.admit(receiver1, None, &stc, 1, 10).is_err());
+        // the admission of the request funding nobody is released.
+        assert!(limiter.admit(bad_receiver, None, &stc, 4, 10).is_ok());
+        assert!(limiter.admit(bad_receiver, None, &stc, 4, 10).is_ok());
+        assert!(limiter.admit(bad_receiver, None, &stc, 4, 10).is_err());
+        // the admission is kept if the result is unknown.
+        assert!(limiter.admit(receiver2, None, &token, 5, 10).is_ok());
+        assert!(limiter.admit(receiver2, None, &token, 5, 10).is_err());
+    }
+}
@@ -2,18 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::faucet::Faucet;
-use anyhow::{bail, Error, Result};
+use crate::limiter::FaucetLimiter;
+use crate::queue::{FundQueue, PendingFund};
+use anyhow::{bail, format_err, Error, Result};
 use ascii::AsciiString;
 use once_cell::sync::Lazy;
 use rust_embed::RustEmbed;
 use serde::{Deserialize, Serialize};
 use starcoin_logger::prelude::*;
 use starcoin_types::account_address::AccountAddress;
-use starcoin_types::account_config::token_value::TokenValue;
-use starcoin_types::account_config::STCUnit;
+use starcoin_types::account_config::token_code::TokenCode;
+use starcoin_types::account_config::G_STC_TOKEN_CODE;
 use std::fmt::{Debug, Formatter};
 use std::io::Cursor;
+use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tiny_http::{Header, Method, Request, Response, Server};
 
 #[derive(RustEmbed)]
@@ -43,6 +48,11 @@ fn response_error(error: Error) -> Response<Cursor<String>> {
         .with_header(Header::from_str("Access-Control-Allow-Origin: *").unwrap())
 }
 
+fn response_too_many_requests(error: Error) -> Response<Cursor<String>> {
+    info!("reject request: {}", error);
+    response_custom(429, format!("Too many requests: {}", error))
+}
+
 fn response_ok(resp_json: serde_json::Value) -> Response<Cursor<String>> {
     response_custom(200, resp_json.to_string())
 }
@@ -59,7 +69,57 @@ static G_CONTENT_TYPE: Lazy<Header> = Lazy::new(|| Header {
     value: AsciiString::from_ascii("text/html; charset=utf8").unwrap(),
 });
 
-pub async fn run(server: Server, faucet: Faucet) {
+/// Serve the fund requests, the admitted requests are queued and funded in batch.
+pub struct FaucetService {
+    faucet: Arc<Faucet>,
+    limiter: Arc<Mutex<FaucetLimiter>>,
+    queue: FundQueue,
+    /// The trusted proxies in front of the faucet, the client IP is taken from the
+    /// `X-Forwarded-For` entry appended by the outermost trusted proxy, 0 ignores the header.
+    trusted_proxies: usize,
+}
+
+impl FaucetService {
+    pub fn new(
+        faucet: Arc<Faucet>,
+        limiter: Arc<Mutex<FaucetLimiter>>,
+        queue: FundQueue,
+        trusted_proxies: usize,
+    ) -> Self {
+        Self {
+            faucet,
+            limiter,
+            queue,
+            trusted_proxies,
+        }
+    }
+
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let forwarded_for: Vec<&str> = request
+            .headers()
+            .iter()
+            .filter(|header| header.field.equiv("X-Forwarded-For"))
+            .map(|header| header.value.as_str())
+            .collect();
+        forwarded_client_ip(&forwarded_for.join(","), self.trusted_proxies)
+            .or_else(|| Some(request.remote_addr().ip()))
+    }
+}
+
+/// The client IP in the `X-Forwarded-For` value behind the `trusted_proxies`. Each proxy appends
+/// the address it receives the request from, so only the rightmost `trusted_proxies` entries are
+/// trusted, the entries on their left are set by the client.
+fn forwarded_client_ip(forwarded_for: &str, trusted_proxies: usize) -> Option<IpAddr> {
+    if trusted_proxies == 0 {
+        return None;
+    }
+    forwarded_for
+        .rsplit(',')
+        .nth(trusted_proxies - 1)
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+pub async fn run(server: Server, service: FaucetService) {
     for mut request in server.incoming_requests() {
         let pos = request
             .url()
@@ -73,12 +133,14 @@ pub async fn run(server: Server, faucet: Faucet) {
                 let _err = request.respond(response);
             }
             "/api/fund" => {
-                let resp = handle_fund(&faucet, &mut request).await;
-                if let Err(err) =
-                    request.respond(response(resp).with_header(G_CONTENT_TYPE.clone()))
-                {
-                    error!("response err: {}", err)
-                }
+                let fund_request = match parse_fund(&service, &mut request) {
+                    Ok(fund_request) => fund_request,
+                    Err(err) => {
+                        respond(request, response_error(err));
+                        continue;
+                    }
+                };
+                handle_fund(&service, request, fund_request);
             }
             _ => {
                 let _ = request.respond(response_custom(404, "Not found".to_string()));
@@ -87,7 +149,20 @@ pub async fn run(server: Server, faucet: Faucet) {
     }
 }
 
-async fn handle_fund(faucet: &Faucet, request: &mut Request) -> Result<serde_json::Value> {
+fn respond(request: Request, response: Response<Cursor<String>>) {
+    if let Err(err) = request.respond(response.with_header(G_CONTENT_TYPE.clone())) {
+        error!("response err: {}", err)
+    }
+}
+
+/// A fund request with the amount resolved.
+struct ParsedFund {
+    token_code: TokenCode,
+    receiver: AccountAddress,
+    amount: u128,
+}
+
+fn parse_fund(service: &FaucetService, request: &mut Request) -> Result<ParsedFund> {
     info!("fund: {}", request.url());
     debug!("request: {:?}", request);
 
@@ -97,23 +172,99 @@ async fn handle_fund(faucet: &Faucet, request: &mut Request) -> Result<serde_jso
     let mut body = String::new();
     request.as_reader().read_to_string(&mut body)?;
     let fund_request = serde_json::from_str::<FundRequest>(body.as_str())?;
-    let amount = fund_request
-        .amount
-        .and_then(|amount| TokenValue::<STCUnit>::from_str(amount.as_str()).ok());
-    let txn_hash = faucet.transfer(amount, fund_request.address)?;
-    Ok(serde_json::json!({
-       "transaction_id": txn_hash.to_string()
-    }))
+    let token_code = fund_request
+        .token_code
+        .unwrap_or_else(|| G_STC_TOKEN_CODE.clone());
+    let amount = service
+        .faucet
+        .fund_amount(&token_code, fund_request.amount.as_deref())?;
+    Ok(ParsedFund {
+        token_code,
+        receiver: fund_request.address,
+        amount,
+    })
+}
+
+/// Admit the request by the limits and queue it, the request is responded after it is funded.
+fn handle_fund(service: &FaucetService, request: Request, fund: ParsedFund) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let admission = service
+        .limiter
+        .lock()
+        .expect("faucet limiter lock poisoned")
+        .admit(
+            fund.receiver,
+            service.client_ip(&request),
+            &fund.token_code,
+            fund.amount,
+            now,
+        );
+    let admission = match admission {
+        Ok(admission) => admission,
+        Err(err) => return respond(request, response_too_many_requests(err)),
+    };
+    let pending = PendingFund {
+        token_code: fund.token_code,
+        receiver: fund.receiver,
+        amount: fund.amount,
+        admission,
+        callback: Box::new(move |result| {
+            let result = result.map(|txn_hash| {
+                serde_json::json!({
+                   "transaction_id": txn_hash.to_string()
+                })
+            });
+            respond(request, response(result))
+        }),
+    };
+    if let Err(pending) = service.queue.push(pending) {
+        service
+            .limiter
+            .lock()
+            .expect("faucet limiter lock poisoned")
+            .release(&pending.admission);
+        (pending.callback)(Err(format_err!(
+            "The faucet is busy, please try again later"
+        )));
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 struct FundRequest {
     address: AccountAddress,
     amount: Option<String>,
+    /// STC if absent.
+    token_code: Option<TokenCode>,
 }
 
 impl Debug for FundRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "address: {:?}, amount: {:?}", self.address, self.amount,)
+        write!(
+            f,
+            "address: {:?}, amount: {:?}, token_code: {:?}",
+            self.address, self.amount, self.token_code
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_client_ip() {
+        let ip = |s: &str| Some(IpAddr::from_str(s).unwrap());
+        let forwarded_for = "1.1.1.1, 2.2.2.2, 3.3.3.3";
+        assert_eq!(forwarded_client_ip(forwarded_for, 0), None);
+        // the client can not spoof the entry appended by the trusted proxy.
+        assert_eq!(forwarded_client_ip(forwarded_for, 1), ip("3.3.3.3"));
+        assert_eq!(forwarded_client_ip(forwarded_for, 2), ip("2.2.2.2"));
+        assert_eq!(forwarded_client_ip(forwarded_for, 3), ip("1.1.1.1"));
+        assert_eq!(forwarded_client_ip(forwarded_for, 4), None);
+        assert_eq!(forwarded_client_ip("1.1.1.1,invalid", 1), None);
+        assert_eq!(forwarded_client_ip("", 1), None);
     }
 }
@@ -8,13 +8,18 @@ use starcoin_chain::verifier::{BasicVerifier, ConsensusVerifier, FullVerifier, N
 use starcoin_chain::{BlockChain, ChainReader};
 use starcoin_config::RocksdbConfig;
 use starcoin_config::{BuiltinNetworkID, ChainNetwork};
+use starcoin_dev::playground::profile_transaction;
+use starcoin_gas::GasProfile;
 use starcoin_genesis::Genesis;
+use starcoin_rpc_api::types::GasProfileView;
+use starcoin_statedb::ChainStateDB;
 use starcoin_storage::cache_storage::CacheStorage;
 use starcoin_storage::db_storage::DBStorage;
 use starcoin_storage::storage::StorageInstance;
-use starcoin_storage::{BlockStore, Storage};
+use starcoin_storage::{BlockStore, Storage, Store};
+use starcoin_types::block::Block;
 use starcoin_types::startup_info::StartupInfo;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -42,6 +47,41 @@ pub struct ReplayOpt {
     #[clap(long, short = 'w')]
     /// Watch metrics logs.
     pub watch: bool,
+    #[clap(long, parse(from_os_str))]
+    /// Profile the gas used by the replayed user transactions, and write the merged
+    /// gas profile to `gas_profile.json` and `gas_profile.folded` in this dir.
+    pub profile: Option<PathBuf>,
+}
+
+/// Re-execute the user transactions of the block on the source storage with the gas profile recorded.
+fn profile_block(
+    storage: Arc<Storage>,
+    block: &Block,
+    profile: &mut GasProfile,
+) -> anyhow::Result<()> {
+    let txn_infos = storage.get_block_transaction_infos(block.id())?;
+    // the first transaction of a block is the block metadata transaction,
+    // so the parent state of the nth user transaction is the state after the nth transaction.
+    for (parent_txn_info, txn) in txn_infos.iter().zip(block.transactions()) {
+        let state_view =
+            ChainStateDB::new(storage.clone(), Some(parent_txn_info.state_root_hash()));
+        let (_, _, txn_profile) = profile_transaction(&state_view, txn.clone(), None)?;
+        if let Some(txn_profile) = txn_profile {
+            profile.merge(txn_profile);
+        }
+    }
+    Ok(())
+}
+
+fn write_profile(dir: &Path, profile: GasProfile) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join("gas_profile.folded"), profile.to_folded_stacks())?;
+    let profile_view = GasProfileView::from(profile);
+    std::fs::write(
+        dir.join("gas_profile.json"),
+        serde_json::to_string_pretty(&profile_view)?,
+    )?;
+    Ok(())
 }
 
 // deprecated use starcoin_db_exporter replace
@@ -80,8 +120,13 @@ fn main() -> anyhow::Result<()> {
     );
     let (chain_info, _) = Genesis::init_and_check_storage(&net, storage.clone(), from_dir.as_ref())
         .expect("init storage by genesis fail.");
-    let chain = BlockChain::new(net.time_service(), chain_info.head().id(), storage, None)
-        .expect("create block chain should success.");
+    let chain = BlockChain::new(
+        net.time_service(),
+        chain_info.head().id(),
+        storage.clone(),
+        None,
+    )
+    .expect("create block chain should success.");
 
     let storage2 = Arc::new(
         Storage::new(StorageInstance::new_cache_and_db_instance(
@@ -124,8 +169,12 @@ fn main() -> anyhow::Result<()> {
     }
     let start_block = chain2.current_header().number() + 1;
     let mut last_block_hash = None;
+    let mut gas_profile = opts.profile.as_ref().map(|_| GasProfile::default());
     for i in start_block..=end_block {
         if let Ok(Some(block)) = chain.get_block_by_number(i) {
+            if let Some(gas_profile) = gas_profile.as_mut() {
+                profile_block(storage.clone(), &block, gas_profile)?;
+            }
             let start = Instant::now();
             let expected_state_root = block.header().state_root();
             let block_id = block.id();
@@ -174,5 +223,9 @@ fn main() -> anyhow::Result<()> {
         storage2.save_startup_info(startup_info)?;
     }
     println!("apply use time: {:?}", time_begin.elapsed());
+    if let (Some(dir), Some(gas_profile)) = (opts.profile.as_ref(), gas_profile) {
+        write_profile(dir, gas_profile)?;
+        println!("write gas profile to {}", dir.display());
+    }
     Ok(())
 }
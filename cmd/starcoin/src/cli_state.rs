@@ -219,7 +219,7 @@ impl CliState {
         self.execute_transaction(raw_txn, txn_opts.dry_run, txn_opts.blocking)
     }
 
-    fn build_transaction(
+    pub(crate) fn build_transaction(
         &self,
         sender: Option<AccountAddress>,
         sequence_number: Option<u64>,
@@ -274,6 +274,25 @@ impl CliState {
         playground::dry_run_explain(&state_reader, txn, None)
     }
 
+    /// Dry run a transaction with the gas profile recorded, return the output and the gas profile
+    /// in folded stack format.
+    pub fn profile_transaction(
+        &self,
+        txn: DryRunTransaction,
+    ) -> Result<(DryRunOutputView, Option<String>)> {
+        let state_reader = self.client().state_reader(StateRootOption::Latest)?;
+        let payload = txn.raw_txn.payload().clone();
+        let (vm_status, output, gas_profile) =
+            playground::dry_run_profile(&state_reader, txn, None)?;
+        let folded_stacks = gas_profile
+            .as_ref()
+            .map(|gas_profile| gas_profile.to_folded_stacks());
+        let mut dry_output =
+            playground::explain_output(&state_reader, &payload, vm_status, output)?;
+        dry_output.gas_profile = gas_profile.map(Into::into);
+        Ok((dry_output, folded_stacks))
+    }
+
     pub fn execute_transaction(
         &self,
         raw_txn: RawUserTransaction,
@@ -7,7 +7,9 @@ use anyhow::Result;
 use clap::Parser;
 use scmd::{CommandAction, ExecContext};
 use starcoin_abi_decoder::DecodedMoveValue;
+use starcoin_crypto::HashValue;
 use starcoin_rpc_api::types::{ContractCall, FunctionIdView, TransactionArgumentView, TypeTagView};
+use starcoin_types::block::BlockNumber;
 
 /// Call Contract command
 ///  Some examples:
@@ -37,6 +39,14 @@ pub struct CallContractOpt {
         help = "can specify multi arg"
     )]
     args: Option<Vec<TransactionArgumentView>>,
+
+    #[clap(long = "block-number", conflicts_with = "block-hash")]
+    /// call on the state of the main chain block at this number, default is the latest state.
+    block_number: Option<BlockNumber>,
+
+    #[clap(long = "block-hash", name = "block-hash")]
+    /// call on the state of this block.
+    block_hash: Option<HashValue>,
 }
 
 pub struct CallContractCommand;
@@ -57,6 +67,9 @@ impl CommandAction for CallContractCommand {
             function_id: opt.function.clone(),
             type_args: opt.type_tags.clone().unwrap_or_default(),
             args: opt.args.clone().unwrap_or_default(),
+            block_number: opt.block_number,
+            block_hash: opt.block_hash,
+            state_root: None,
         };
 
         let result = ctx.state().client().contract_call(call)?;
@@ -26,10 +26,12 @@ pub(crate) mod log_cmd;
 pub(crate) mod move_explain;
 mod package_cmd;
 pub(crate) mod panic_cmd;
+pub(crate) mod profile_cmd;
 pub(crate) mod resolve_cmd;
 pub(crate) mod sign_txn_helper;
 pub(crate) mod sleep_cmd;
 mod subscribe_cmd;
+pub(crate) mod trace_cmd;
 mod upgrade_module_exe_cmd;
 mod upgrade_module_plan_cmd;
 mod upgrade_module_proposal_cmd;
@@ -0,0 +1,111 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cli_state::CliState;
+use crate::StarcoinOpt;
+use anyhow::{format_err, Result};
+use clap::Parser;
+use scmd::{CommandAction, ExecContext};
+use starcoin_rpc_api::types::{DryRunOutputView, FunctionIdView};
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::transaction::{parse_transaction_argument_advance, TransactionArgument};
+use starcoin_vm_types::transaction::{DryRunTransaction, ScriptFunction, TransactionPayload};
+use starcoin_vm_types::transaction_argument::convert_txn_args;
+use starcoin_vm_types::{language_storage::TypeTag, parser::parse_type_tag};
+use std::path::PathBuf;
+
+/// Dry run a script function with the gas profile recorded.
+///  Some examples:
+///  ``` shell
+///  dev profile --function 0x1::TransferScripts::peer_to_peer_v2 -t 0x1::STC::STC --arg 0xb6d69dd935edf7f2054acf12eb884df8 --arg 1000u128 --folded peer_to_peer.folded
+///  # render the flamegraph with inferno or FlameGraph
+///  inferno-flamegraph peer_to_peer.folded > peer_to_peer.svg
+///  ```
+#[derive(Debug, Parser)]
+#[clap(name = "profile")]
+pub struct ProfileOpt {
+    #[clap(
+    short = 't',
+    long = "type_tag",
+    name = "type-tag",
+    parse(try_from_str = parse_type_tag)
+    )]
+    /// type tags for the script
+    type_tags: Option<Vec<TypeTag>>,
+
+    #[clap(long = "arg", name = "transaction-args", parse(try_from_str = parse_transaction_argument_advance))]
+    /// args for the script.
+    args: Option<Vec<TransactionArgument>>,
+
+    #[clap(long = "function", name = "script-function")]
+    /// script function to profile, example: 0x1::TransferScripts::peer_to_peer_v2
+    script_function: FunctionIdView,
+
+    #[clap(short = 's', long)]
+    /// the sender of the transaction, if `sender` is absent, use default account.
+    sender: Option<AccountAddress>,
+
+    #[clap(long = "max-gas-amount")]
+    /// max gas of the transaction
+    max_gas_amount: Option<u64>,
+
+    #[clap(long = "gas-unit-price", alias = "gas-price")]
+    /// gas price of the transaction
+    gas_unit_price: Option<u64>,
+
+    #[clap(long = "gas-token")]
+    /// token code of gas to pay, for example: 0x1::STC::STC, default is STC.
+    gas_token: Option<String>,
+
+    #[clap(long = "folded", parse(from_os_str))]
+    /// write the gas used by call stacks to the file in folded stack format, which can be rendered as a flamegraph.
+    folded: Option<PathBuf>,
+}
+
+pub struct ProfileCommand;
+
+impl CommandAction for ProfileCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = ProfileOpt;
+    type ReturnItem = DryRunOutputView;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let opt = ctx.opt();
+        let state = ctx.state();
+        let script_function = opt.script_function.clone().0;
+        let payload = TransactionPayload::ScriptFunction(ScriptFunction::new(
+            script_function.module,
+            script_function.function,
+            opt.type_tags.clone().unwrap_or_default(),
+            convert_txn_args(&opt.args.clone().unwrap_or_default()),
+        ));
+        let (raw_txn, _) = state.build_transaction(
+            opt.sender,
+            None,
+            opt.gas_unit_price,
+            opt.max_gas_amount,
+            None,
+            payload,
+            opt.gas_token.clone(),
+        )?;
+        let sender = state.get_account(raw_txn.sender())?;
+        let (dry_output, folded_stacks) = state.profile_transaction(DryRunTransaction {
+            raw_txn,
+            public_key: sender.public_key,
+        })?;
+        if let Some(path) = opt.folded.as_ref() {
+            let folded_stacks = folded_stacks.ok_or_else(|| {
+                format_err!(
+                    "The transaction is discarded before execution: {:?}",
+                    dry_output.explained_status
+                )
+            })?;
+            std::fs::write(path, folded_stacks)?;
+        }
+        Ok(dry_output)
+    }
+}
@@ -4,7 +4,8 @@ use anyhow::Result;
 use clap::Parser;
 use futures::{TryStream, TryStreamExt};
 use scmd::{CommandAction, ExecContext};
-use starcoin_rpc_api::types::pubsub::EventFilter;
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::pubsub::{EventFilter, TxnStatusParams};
 use starcoin_rpc_api::types::TypeTagView;
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::event::EventKey;
@@ -121,6 +122,67 @@ impl CommandAction for SubscribeNewMintBlockCommand {
     }
 }
 
+/// Subscribe the reorganizations of the main chain.
+#[derive(Debug, Parser)]
+#[clap(name = "chain_reorg")]
+pub struct SubscribeChainReorgOpt {}
+pub struct SubscribeChainReorgCommand;
+impl CommandAction for SubscribeChainReorgCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = SubscribeChainReorgOpt;
+    type ReturnItem = ();
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let event_stream = ctx.state().client().subscribe_chain_reorg()?;
+        println!("Subscribe successful, Press `q` and Enter to quit");
+        blocking_display_notification(event_stream, |evt| {
+            serde_json::to_string(&evt).expect("should never fail")
+        });
+        Ok(())
+    }
+}
+
+/// Subscribe the lifecycle status changes of the txns, filtered by txn hash or sender.
+#[derive(Debug, Parser)]
+#[clap(name = "txn_status")]
+pub struct SubscribeTxnStatusOpt {
+    #[clap(
+        long = "txn-hash",
+        name = "txn-hash",
+        required_unless_present = "sender"
+    )]
+    /// hash of the txn to subscribe
+    txn_hash: Option<HashValue>,
+    #[clap(long = "sender", name = "sender")]
+    /// txns of which sender to subscribe
+    sender: Option<AccountAddress>,
+}
+pub struct SubscribeTxnStatusCommand;
+impl CommandAction for SubscribeTxnStatusCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = SubscribeTxnStatusOpt;
+    type ReturnItem = ();
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let params = TxnStatusParams {
+            txn_hash: ctx.opt().txn_hash,
+            sender: ctx.opt().sender,
+        };
+        let event_stream = ctx.state().client().subscribe_txn_status(params)?;
+        println!("Subscribe successful, Press `q` and Enter to quit");
+        blocking_display_notification(event_stream, |evt| {
+            serde_json::to_string(&evt).expect("should never fail")
+        });
+        Ok(())
+    }
+}
+
 #[derive(Debug, Parser)]
 #[clap(name = "new_pending_txn")]
 pub struct SubscribeNewTxnOpt {}
@@ -0,0 +1,36 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cli_state::CliState;
+use crate::StarcoinOpt;
+use anyhow::Result;
+use clap::Parser;
+use scmd::{CommandAction, ExecContext};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::TransactionTraceView;
+
+/// Re-execute a committed transaction on its parent state and print the call tree.
+#[derive(Debug, Parser)]
+#[clap(name = "trace")]
+pub struct TraceOpt {
+    #[clap(name = "txn-hash")]
+    /// txn hash
+    txn_hash: HashValue,
+}
+
+pub struct TraceCommand;
+
+impl CommandAction for TraceCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = TraceOpt;
+    type ReturnItem = TransactionTraceView;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let client = ctx.state().client();
+        client.debug_trace_transaction(ctx.opt().txn_hash)
+    }
+}
@@ -85,6 +85,8 @@ pub fn add_command(
                         .subcommand(node::network::KnownPeersCommand)
                         .subcommand(node::network::GetAddressCommand)
                         .subcommand(node::network::AddPeerCommand)
+                        .subcommand(node::network::AddReservedPeerCommand)
+                        .subcommand(node::network::RemoveReservedPeerCommand)
                         .subcommand(node::network::CallPeerCommand)
                         .subcommand(node::network::SetPeerReputation)
                         .subcommand(node::network::BanPeerCommand),
@@ -108,7 +110,12 @@ pub fn add_command(
             CustomCommand::with_name("txpool")
                 .subcommand(txpool::PendingTxnCommand)
                 .subcommand(txpool::PendingTxnsCommand)
-                .subcommand(txpool::TxPoolStatusCommand),
+                .subcommand(txpool::TxPoolStatusCommand)
+                .subcommand(txpool::TxnStatusCommand)
+                .subcommand(txpool::GasPriceCommand)
+                .subcommand(txpool::FeeHistoryCommand)
+                .subcommand(txpool::ContentCommand)
+                .subcommand(txpool::InspectCommand),
         )
         .command(
             CustomCommand::with_name("dev")
@@ -125,13 +132,17 @@ pub fn add_command(
                 .subcommand(dev::CallContractCommand)
                 .subcommand(dev::resolve_cmd::ResolveCommand)
                 .subcommand(dev::call_api_cmd::CallApiCommand)
+                .subcommand(dev::trace_cmd::TraceCommand)
+                .subcommand(dev::profile_cmd::ProfileCommand)
                 .subcommand(
                     CustomCommand::with_name("subscribe")
                         .with_about("Subscribe the chain events")
                         .subcommand(dev::SubscribeNewMintBlockCommand)
                         .subcommand(dev::SubscribeBlockCommand)
                         .subcommand(dev::SubscribeEventCommand)
-                        .subcommand(dev::SubscribeNewTxnCommand),
+                        .subcommand(dev::SubscribeNewTxnCommand)
+                        .subcommand(dev::SubscribeChainReorgCommand)
+                        .subcommand(dev::SubscribeTxnStatusCommand),
                 )
                 .subcommand(
                     CustomCommand::with_name("log")
@@ -6,6 +6,7 @@ use anyhow::Result;
 use clap::Parser;
 use scmd::{CommandAction, ExecContext};
 use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::BackupManifestView;
 
 /// Some commands for node manager.
 #[derive(Debug, Parser)]
@@ -36,6 +37,15 @@ pub enum NodeManagerOpt {
         #[clap(name = "block-hash")]
         block_hash: HashValue,
     },
+    /// Backup the node database into `backup-dir` while the node is running.
+    /// The backups in the same dir share the SST files, so a later backup only copies the new files.
+    /// Restore a backup with `starcoin_db_exporter restore-backup` when the node is stopped.
+    #[clap(name = "backup")]
+    Backup {
+        /// the backup dir on the node, it must be under the backup root of the node, which is `backup` in the data dir by default, a relative dir is relative to the backup root.
+        #[clap(name = "backup-dir")]
+        backup_dir: String,
+    },
 }
 
 pub struct NodeManagerCommand;
@@ -44,7 +54,7 @@ impl CommandAction for NodeManagerCommand {
     type State = CliState;
     type GlobalOpt = StarcoinOpt;
     type Opt = NodeManagerOpt;
-    type ReturnItem = ();
+    type ReturnItem = Option<BackupManifestView>;
 
     fn run(
         &self,
@@ -65,8 +75,11 @@ impl CommandAction for NodeManagerCommand {
             NodeManagerOpt::DeleteFailedBlock { block_hash } => {
                 client.node_delete_failed_block(*block_hash)?;
             }
+            NodeManagerOpt::Backup { backup_dir } => {
+                return Ok(Some(client.node_backup(backup_dir.clone())?));
+            }
         }
 
-        Ok(())
+        Ok(None)
     }
 }
@@ -0,0 +1,34 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cli_state::CliState;
+use crate::StarcoinOpt;
+use anyhow::Result;
+use clap::Parser;
+use scmd::{CommandAction, ExecContext};
+
+#[derive(Debug, Parser, Default)]
+#[clap(name = "add_reserved_peer")]
+///Add a reserved peer, the node always keeps connected with it
+pub struct AddReservedPeerOpt {
+    #[clap(name = "peer")]
+    /// format: multiaddr/p2p/peer_id
+    peer: String,
+}
+
+pub struct AddReservedPeerCommand;
+
+impl CommandAction for AddReservedPeerCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = AddReservedPeerOpt;
+    type ReturnItem = ();
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let client = ctx.state().client();
+        client.network_add_reserved_peer(ctx.opt().peer.clone())
+    }
+}
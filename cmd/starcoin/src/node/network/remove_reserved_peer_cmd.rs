@@ -0,0 +1,33 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cli_state::CliState;
+use crate::StarcoinOpt;
+use anyhow::Result;
+use clap::Parser;
+use scmd::{CommandAction, ExecContext};
+
+#[derive(Debug, Parser, Default)]
+#[clap(name = "remove_reserved_peer")]
+///Remove a peer from the reserved peers
+pub struct RemoveReservedPeerOpt {
+    #[clap(name = "peer-id")]
+    peer_id: String,
+}
+
+pub struct RemoveReservedPeerCommand;
+
+impl CommandAction for RemoveReservedPeerCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = RemoveReservedPeerOpt;
+    type ReturnItem = ();
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let client = ctx.state().client();
+        client.network_remove_reserved_peer(ctx.opt().peer_id.clone())
+    }
+}
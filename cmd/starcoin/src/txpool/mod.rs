@@ -7,8 +7,11 @@ use anyhow::Result;
 use clap::Parser;
 use scmd::{CommandAction, ExecContext};
 use starcoin_crypto::HashValue;
-use starcoin_rpc_api::types::SignedUserTransactionView;
-use starcoin_txpool_api::TxPoolStatus;
+use starcoin_rpc_api::types::{
+    BlockFeeHistoryView, GasPriceEstimateView, SignedUserTransactionView, TxPoolSenderView,
+    TxPoolTxnSummaryView, TxPoolTxnView,
+};
+use starcoin_txpool_api::{TxPoolStatus, TxnStatusInfo};
 use starcoin_vm_types::account_address::AccountAddress;
 
 /// Get txn data by its hash
@@ -88,3 +91,157 @@ impl CommandAction for TxPoolStatusCommand {
         client.txpool_status()
     }
 }
+
+/// Get the lifecycle status of a txn, from the txpool to the finality on the main chain
+#[derive(Debug, Parser)]
+#[clap(name = "txn-status")]
+pub struct TxnStatusOpt {
+    #[clap(name = "hash", help = "hash of the txn")]
+    hash: HashValue,
+}
+
+pub struct TxnStatusCommand;
+
+impl CommandAction for TxnStatusCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = TxnStatusOpt;
+    type ReturnItem = Option<TxnStatusInfo>;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let client = ctx.state().client();
+        client.txpool_transaction_status(ctx.opt().hash)
+    }
+}
+
+/// Get the suggested gas unit prices for low, medium and high priority txns
+#[derive(Debug, Parser)]
+#[clap(name = "gas-price")]
+pub struct GasPriceOpt {}
+
+pub struct GasPriceCommand;
+
+impl CommandAction for GasPriceCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = GasPriceOpt;
+    type ReturnItem = GasPriceEstimateView;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let client = ctx.state().client();
+        client.txpool_gas_price_estimate()
+    }
+}
+
+/// Get the gas usage and gas unit price percentiles of the recent blocks
+#[derive(Debug, Parser)]
+#[clap(name = "fee-history")]
+pub struct FeeHistoryOpt {
+    #[clap(
+        name = "block-count",
+        long = "count",
+        default_value = "10",
+        help = "max number of the recent blocks"
+    )]
+    block_count: u64,
+    #[clap(
+        name = "percentile",
+        long = "percentile",
+        short = 'p',
+        help = "gas unit price percentiles in [0, 100], default to 25, 50 and 75"
+    )]
+    percentiles: Vec<f64>,
+}
+
+pub struct FeeHistoryCommand;
+
+impl CommandAction for FeeHistoryCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = FeeHistoryOpt;
+    type ReturnItem = Vec<BlockFeeHistoryView>;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let opt = ctx.opt();
+        let percentiles = if opt.percentiles.is_empty() {
+            vec![25.0, 50.0, 75.0]
+        } else {
+            opt.percentiles.clone()
+        };
+        let client = ctx.state().client();
+        client.txpool_fee_history(opt.block_count, percentiles)
+    }
+}
+
+/// List the txns in txpool grouped by sender, split into the ready and the future txns
+#[derive(Debug, Parser)]
+#[clap(name = "content")]
+pub struct ContentOpt {
+    #[clap(
+        long = "sender",
+        short = 's',
+        help = "only list the txns of the sender"
+    )]
+    sender: Option<AccountAddress>,
+}
+
+pub struct ContentCommand;
+
+impl CommandAction for ContentCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = ContentOpt;
+    type ReturnItem = Vec<TxPoolSenderView<TxPoolTxnView>>;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let sender = ctx.opt().sender;
+        let client = ctx.state().client();
+        let mut content = client.txpool_content()?;
+        content.retain(|txns| sender.map_or(true, |sender| txns.sender == sender));
+        Ok(content)
+    }
+}
+
+/// Summarize the txns in txpool grouped by sender, split into the ready and the future txns
+#[derive(Debug, Parser)]
+#[clap(name = "inspect")]
+pub struct InspectOpt {
+    #[clap(
+        long = "sender",
+        short = 's',
+        help = "only list the txns of the sender"
+    )]
+    sender: Option<AccountAddress>,
+}
+
+pub struct InspectCommand;
+
+impl CommandAction for InspectCommand {
+    type State = CliState;
+    type GlobalOpt = StarcoinOpt;
+    type Opt = InspectOpt;
+    type ReturnItem = Vec<TxPoolSenderView<TxPoolTxnSummaryView>>;
+
+    fn run(
+        &self,
+        ctx: &ExecContext<Self::State, Self::GlobalOpt, Self::Opt>,
+    ) -> Result<Self::ReturnItem> {
+        let sender = ctx.opt().sender;
+        let client = ctx.state().client();
+        let mut content = client.txpool_inspect()?;
+        content.retain(|txns| sender.map_or(true, |sender| txns.sender == sender));
+        Ok(content)
+    }
+}
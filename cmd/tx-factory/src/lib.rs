@@ -1 +1,4 @@
+pub mod report;
+pub mod runner;
+pub mod scenario;
 pub mod txn_generator;
@@ -10,6 +10,9 @@ use starcoin_rpc_api::types::FactoryAction;
 use starcoin_rpc_client::RpcClient;
 use starcoin_rpc_client::StateRootOption;
 use starcoin_state_api::{ChainStateReader, StateReaderExt};
+use starcoin_tx_factory::report::LoadReport;
+use starcoin_tx_factory::runner::ScenarioRunner;
+use starcoin_tx_factory::scenario::Scenario;
 use starcoin_tx_factory::txn_generator::MockTxnGenerator;
 use starcoin_types::account::DEFAULT_EXPIRATION_TIME;
 use starcoin_types::account_address::AccountAddress;
@@ -78,6 +81,12 @@ pub struct TxFactoryOpt {
         help = "create account batch size"
     )]
     pub batch_size: u32,
+    #[clap(
+        long,
+        parse(from_os_str),
+        help = "run the load scenario in the TOML or YAML file, and report the stats"
+    )]
+    pub scenario: Option<PathBuf>,
 }
 
 const INITIAL_BALANCE: u128 = 1_000_000_000;
@@ -168,6 +177,30 @@ fn main() {
         stopping_signal_clone.store(true, Ordering::SeqCst);
     })
     .unwrap();
+    if let Some(scenario_path) = opts.scenario.as_ref() {
+        let scenario =
+            Scenario::load(scenario_path.as_path()).expect("load scenario should success");
+        let accounts = tx_mocker
+            .get_or_create_accounts(scenario.accounts, batch_size)
+            .expect("create accounts should success")
+            .into_iter()
+            .map(|account| account.address)
+            .collect();
+        let report_path = scenario.report.clone();
+        let runner = ScenarioRunner::new(
+            &tx_mocker.client,
+            scenario,
+            accounts,
+            opts.account_password.clone(),
+            Duration::from_secs(watch_timeout as u64),
+        )
+        .expect("init scenario runner should success");
+        let report = runner
+            .run(stopping_signal.as_ref())
+            .expect("run scenario should success");
+        write_report(&report, report_path).expect("write scenario report should success");
+        return;
+    }
     let handle = std::thread::spawn(move || {
         let accounts = tx_mocker
             .get_or_create_accounts(account_num, batch_size)
@@ -205,6 +238,18 @@ fn main() {
     info!("txfactory: stop now");
 }
 
+fn write_report(report: &LoadReport, path: Option<PathBuf>) -> Result<()> {
+    let json = serde_json::to_string_pretty(report)?;
+    match path {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            info!("scenario report is written to {:?}", path);
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
 struct TxnMocker {
     client: RpcClient,
     generator: MockTxnGenerator,
@@ -0,0 +1,276 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The machine readable report of a scenario load.
+
+use serde::Serialize;
+use starcoin_rpc_api::types::TransactionStatusView;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Latency percentiles in milliseconds.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub min: u64,
+    pub mean: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencySummary {
+    fn new(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let count = samples.len();
+        // nearest-rank percentile: the smallest sample that at least p% of the samples do not exceed.
+        let percentile = |p: usize| samples[((count * p + 99) / 100).clamp(1, count) - 1];
+        Self {
+            count: count as u64,
+            min: samples[0],
+            mean: samples.iter().sum::<u64>() / count as u64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples[count - 1],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TxnKindStats {
+    pub submitted: u64,
+    pub submit_failed: u64,
+    pub included: u64,
+    /// Included but not executed successfully.
+    pub vm_failed: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoadReport {
+    pub duration_secs: f64,
+    pub submitted: u64,
+    pub submit_failed: u64,
+    pub included: u64,
+    /// Submitted but not included in a block before the watch timeout.
+    pub not_included: u64,
+    /// The accepted txns per second of the load.
+    pub submitted_tps: f64,
+    /// The included txns per second of the load.
+    pub included_tps: f64,
+    /// Latency of the submit rpc.
+    pub submission_latency: LatencySummary,
+    /// Latency from the submission to the block including the txn.
+    pub inclusion_latency: LatencySummary,
+    /// The included txns by the vm status.
+    pub vm_status: BTreeMap<String, u64>,
+    /// The rejected submissions by the error.
+    pub submit_errors: BTreeMap<String, u64>,
+    pub txns: BTreeMap<String, TxnKindStats>,
+}
+
+/// Collect the stats of a scenario load.
+#[derive(Debug, Default)]
+pub struct LoadStats {
+    submission_latencies: Vec<u64>,
+    inclusion_latencies: Vec<u64>,
+    vm_status: BTreeMap<String, u64>,
+    submit_errors: BTreeMap<String, u64>,
+    txns: BTreeMap<String, TxnKindStats>,
+}
+
+/// The key of a vm status in the report.
+pub fn vm_status_key(status: &TransactionStatusView) -> String {
+    match status {
+        TransactionStatusView::Executed => "Executed".to_string(),
+        TransactionStatusView::OutOfGas => "OutOfGas".to_string(),
+        TransactionStatusView::MoveAbort { abort_code, .. } => {
+            format!("MoveAbort({})", abort_code.0)
+        }
+        TransactionStatusView::ExecutionFailure { .. } => "ExecutionFailure".to_string(),
+        TransactionStatusView::MiscellaneousError => "MiscellaneousError".to_string(),
+        TransactionStatusView::Discard {
+            status_code_name, ..
+        } => format!("Discard({})", status_code_name),
+        TransactionStatusView::Retry => "Retry".to_string(),
+    }
+}
+
+impl LoadStats {
+    fn kind(&mut self, name: &str) -> &mut TxnKindStats {
+        self.txns.entry(name.to_string()).or_default()
+    }
+
+    pub fn on_submitted(&mut self, name: &str, latency: Duration) {
+        self.submission_latencies.push(latency.as_millis() as u64);
+        self.kind(name).submitted += 1;
+    }
+
+    pub fn on_submit_failed(&mut self, name: &str, error: &anyhow::Error) {
+        // the root cause without the txn specific context.
+        let error = error.root_cause().to_string();
+        let key = error
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        *self.submit_errors.entry(key).or_default() += 1;
+        self.kind(name).submit_failed += 1;
+    }
+
+    pub fn on_included(
+        &mut self,
+        name: &str,
+        latency: Duration,
+        status: Option<&TransactionStatusView>,
+    ) {
+        self.inclusion_latencies.push(latency.as_millis() as u64);
+        let key = status
+            .map(vm_status_key)
+            .unwrap_or_else(|| "Unknown".to_string());
+        let failed = !matches!(status, Some(TransactionStatusView::Executed));
+        *self.vm_status.entry(key).or_default() += 1;
+        let kind = self.kind(name);
+        kind.included += 1;
+        if failed {
+            kind.vm_failed += 1;
+        }
+    }
+
+    pub fn into_report(self, duration: Duration) -> LoadReport {
+        let duration_secs = duration.as_secs_f64().max(f64::EPSILON);
+        let submitted: u64 = self.txns.values().map(|kind| kind.submitted).sum();
+        let submit_failed = self.txns.values().map(|kind| kind.submit_failed).sum();
+        let included: u64 = self.txns.values().map(|kind| kind.included).sum();
+        LoadReport {
+            duration_secs,
+            submitted,
+            submit_failed,
+            included,
+            not_included: submitted.saturating_sub(included),
+            submitted_tps: submitted as f64 / duration_secs,
+            included_tps: included as f64 / duration_secs,
+            submission_latency: LatencySummary::new(self.submission_latencies),
+            inclusion_latency: LatencySummary::new(self.inclusion_latencies),
+            vm_status: self.vm_status,
+            submit_errors: self.submit_errors,
+            txns: self.txns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::format_err;
+
+    #[test]
+    fn test_latency_summary() {
+        let empty = LatencySummary::new(vec![]);
+        assert_eq!(empty.count, 0);
+        assert_eq!(empty.max, 0);
+
+        let one = LatencySummary::new(vec![7]);
+        assert_eq!(one.count, 1);
+        assert_eq!(
+            (one.min, one.mean, one.p50, one.p90, one.p99, one.max),
+            (7, 7, 7, 7, 7, 7)
+        );
+
+        let mut samples: Vec<u64> = (1..=100).rev().collect();
+        samples.swap(10, 60);
+        let summary = LatencySummary::new(samples);
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.min, 1);
+        assert_eq!(summary.mean, 50);
+        assert_eq!(summary.p50, 50);
+        assert_eq!(summary.p90, 90);
+        assert_eq!(summary.p99, 99);
+        assert_eq!(summary.max, 100);
+
+        let two = LatencySummary::new(vec![20, 10]);
+        assert_eq!((two.min, two.mean, two.p50, two.p90), (10, 15, 10, 20));
+    }
+
+    #[test]
+    fn test_into_report() {
+        let mut stats = LoadStats::default();
+        stats.on_submitted("transfer", Duration::from_millis(10));
+        stats.on_submitted("transfer", Duration::from_millis(20));
+        stats.on_submitted("transfer", Duration::from_millis(30));
+        stats.on_submitted("mint", Duration::from_millis(40));
+        stats.on_submit_failed(
+            "transfer",
+            &format_err!("SEQUENCE_NUMBER_TOO_OLD: seq 1").context("submit txn 0x1"),
+        );
+        stats.on_submit_failed("mint", &format_err!("SEQUENCE_NUMBER_TOO_OLD: seq 2"));
+        stats.on_included(
+            "transfer",
+            Duration::from_millis(100),
+            Some(&TransactionStatusView::Executed),
+        );
+        stats.on_included(
+            "transfer",
+            Duration::from_millis(300),
+            Some(&TransactionStatusView::OutOfGas),
+        );
+        stats.on_included("mint", Duration::from_millis(200), None);
+
+        let report = stats.into_report(Duration::from_secs(2));
+        assert_eq!(report.duration_secs, 2.0);
+        assert_eq!(report.submitted, 4);
+        assert_eq!(report.submit_failed, 2);
+        assert_eq!(report.included, 3);
+        assert_eq!(report.not_included, 1);
+        assert_eq!(report.submitted_tps, 2.0);
+        assert_eq!(report.included_tps, 1.5);
+        assert_eq!(report.submission_latency.count, 4);
+        assert_eq!(report.submission_latency.mean, 25);
+        assert_eq!(report.inclusion_latency.count, 3);
+        assert_eq!(report.inclusion_latency.p50, 200);
+        assert_eq!(report.inclusion_latency.max, 300);
+        assert_eq!(
+            report.vm_status.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("Executed".to_string(), 1),
+                ("OutOfGas".to_string(), 1),
+                ("Unknown".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            report.submit_errors.into_iter().collect::<Vec<_>>(),
+            vec![("SEQUENCE_NUMBER_TOO_OLD".to_string(), 2)]
+        );
+        let transfer = &report.txns["transfer"];
+        assert_eq!(
+            (
+                transfer.submitted,
+                transfer.submit_failed,
+                transfer.included,
+                transfer.vm_failed
+            ),
+            (3, 1, 2, 1)
+        );
+        let mint = &report.txns["mint"];
+        assert_eq!(
+            (
+                mint.submitted,
+                mint.submit_failed,
+                mint.included,
+                mint.vm_failed
+            ),
+            (1, 1, 1, 1)
+        );
+
+        // a load without any txn does not divide by zero.
+        let empty = LoadStats::default().into_report(Duration::from_secs(0));
+        assert_eq!(empty.submitted, 0);
+        assert_eq!(empty.submitted_tps, 0.0);
+        assert_eq!(empty.inclusion_latency.count, 0);
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Submit the txns of a scenario at the target tps, and watch their inclusion by the new block subscription.
+
+use crate::report::{LoadReport, LoadStats};
+use crate::scenario::{Scenario, ScenarioGenerator};
+use anyhow::{format_err, Result};
+use futures::future::{select, Either};
+use futures::{TryStream, TryStreamExt};
+use futures_timer::Delay;
+use rand::Rng;
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_rpc_api::types::BlockView;
+use starcoin_rpc_client::{RpcClient, StateRootOption};
+use starcoin_state_api::StateReaderExt;
+use starcoin_types::account::DEFAULT_EXPIRATION_TIME;
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::genesis_config::ChainId;
+use starcoin_types::transaction::RawUserTransaction;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Interval to check the stop signal while waiting for the new blocks.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct PendingTxn {
+    name: String,
+    submitted_at: Instant,
+}
+
+pub struct ScenarioRunner<'a> {
+    client: &'a RpcClient,
+    scenario: Scenario,
+    generator: ScenarioGenerator,
+    accounts: Vec<AccountAddress>,
+    account_password: String,
+    /// Max time to wait for the inclusion of the submitted txns after the load.
+    watch_timeout: Duration,
+}
+
+impl<'a> ScenarioRunner<'a> {
+    pub fn new(
+        client: &'a RpcClient,
+        scenario: Scenario,
+        accounts: Vec<AccountAddress>,
+        account_password: String,
+        watch_timeout: Duration,
+    ) -> Result<Self> {
+        let generator = ScenarioGenerator::new(&scenario)?;
+        Ok(Self {
+            client,
+            scenario,
+            generator,
+            accounts,
+            account_password,
+            watch_timeout,
+        })
+    }
+
+    fn sequence_number(&self, address: AccountAddress) -> Result<u64> {
+        if let Some(n) = self.client.next_sequence_number_in_txpool(address)? {
+            return Ok(n);
+        }
+        let state_reader = self.client.state_reader(StateRootOption::Latest)?;
+        Ok(state_reader
+            .get_account_resource(address)?
+            .ok_or_else(|| format_err!("account {} not exists, please faucet it", address))?
+            .sequence_number())
+    }
+
+    /// Run the load until the scenario duration or the `stopping_signal`, and report the stats.
+    pub fn run(&self, stopping_signal: &AtomicBool) -> Result<LoadReport> {
+        let load_duration = Duration::from_secs(self.scenario.duration_secs);
+        let unlock_duration = load_duration + self.watch_timeout + Duration::from_secs(60);
+        let mut sequence_numbers = HashMap::new();
+        for address in &self.accounts {
+            self.client
+                .account_unlock(*address, self.account_password.clone(), unlock_duration)?;
+            sequence_numbers.insert(*address, self.sequence_number(*address)?);
+        }
+        let chain_id = self.client.node_info()?.net.chain_id();
+
+        let stats = Mutex::new(LoadStats::default());
+        let pending = Mutex::new(HashMap::<HashValue, PendingTxn>::new());
+        let load_finished = AtomicBool::new(false);
+        let block_stream = self.client.subscribe_new_blocks()?;
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                self.watch_inclusion(
+                    block_stream,
+                    &pending,
+                    &stats,
+                    &load_finished,
+                    stopping_signal,
+                )
+            });
+            let result = self.submit_load(
+                &mut sequence_numbers,
+                chain_id,
+                &pending,
+                &stats,
+                stopping_signal,
+            );
+            load_finished.store(true, Ordering::SeqCst);
+            result
+        })?;
+        let stats = stats.into_inner().expect("load stats lock poisoned");
+        Ok(stats.into_report(start.elapsed()))
+    }
+
+    fn submit_load(
+        &self,
+        sequence_numbers: &mut HashMap<AccountAddress, u64>,
+        chain_id: ChainId,
+        pending: &Mutex<HashMap<HashValue, PendingTxn>>,
+        stats: &Mutex<LoadStats>,
+        stopping_signal: &AtomicBool,
+    ) -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let load_duration = Duration::from_secs(self.scenario.duration_secs);
+        let start = Instant::now();
+        let mut expiration_timestamp =
+            self.client.node_info()?.now_seconds + DEFAULT_EXPIRATION_TIME;
+        let mut expiration_refreshed_at = Instant::now();
+        let mut sent = 0u64;
+        while start.elapsed() < load_duration && !stopping_signal.load(Ordering::SeqCst) {
+            if (sent as f64) >= self.scenario.expected_txns(start.elapsed().as_secs_f64()) {
+                std::thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+            sent += 1;
+            // keep the expiration away from the node time as the load goes on.
+            if expiration_refreshed_at.elapsed() > Duration::from_secs(DEFAULT_EXPIRATION_TIME / 2)
+            {
+                expiration_timestamp =
+                    self.client.node_info()?.now_seconds + DEFAULT_EXPIRATION_TIME;
+                expiration_refreshed_at = Instant::now();
+            }
+            let sender = self.accounts[rng.gen_range(0..self.accounts.len())];
+            let txn = self
+                .generator
+                .generate(&mut rng, sender, self.accounts.as_slice())?;
+            let sequence_number = sequence_numbers[&sender];
+            let raw_txn = RawUserTransaction::new_script_function(
+                sender,
+                sequence_number,
+                txn.script_function,
+                txn.max_gas,
+                txn.gas_price,
+                expiration_timestamp,
+                chain_id,
+            );
+            let result = self
+                .client
+                .account_sign_txn(raw_txn)
+                .and_then(|signed_txn| {
+                    let submit_start = Instant::now();
+                    self.client
+                        .submit_transaction(signed_txn)
+                        .map(|txn_hash| (txn_hash, submit_start))
+                });
+            match result {
+                Ok((txn_hash, submit_start)) => {
+                    let submitted_at = Instant::now();
+                    pending.lock().expect("pending txns lock poisoned").insert(
+                        txn_hash,
+                        PendingTxn {
+                            name: txn.name.to_string(),
+                            submitted_at: submit_start,
+                        },
+                    );
+                    stats
+                        .lock()
+                        .expect("load stats lock poisoned")
+                        .on_submitted(txn.name, submitted_at - submit_start);
+                    sequence_numbers.insert(sender, sequence_number + 1);
+                }
+                Err(e) => {
+                    debug!("Submit txn of {} failed: {:?}", txn.name, e);
+                    stats
+                        .lock()
+                        .expect("load stats lock poisoned")
+                        .on_submit_failed(txn.name, &e);
+                    match self.sequence_number(sender) {
+                        Ok(n) => {
+                            sequence_numbers.insert(sender, n);
+                        }
+                        Err(e) => warn!("Recheck sequence number of {} failed: {:?}", sender, e),
+                    }
+                }
+            }
+        }
+        info!(
+            "Scenario load finished, {} txns sent in {:?}",
+            sent,
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    fn watch_inclusion(
+        &self,
+        mut block_stream: impl TryStream<Ok = BlockView, Error = anyhow::Error> + Unpin,
+        pending: &Mutex<HashMap<HashValue, PendingTxn>>,
+        stats: &Mutex<LoadStats>,
+        load_finished: &AtomicBool,
+        stopping_signal: &AtomicBool,
+    ) {
+        let mut load_finished_at = None;
+        loop {
+            if load_finished_at.is_none() && load_finished.load(Ordering::SeqCst) {
+                load_finished_at = Some(Instant::now());
+            }
+            if let Some(finished_at) = load_finished_at {
+                let all_included = pending
+                    .lock()
+                    .expect("pending txns lock poisoned")
+                    .is_empty();
+                if all_included
+                    || stopping_signal.load(Ordering::SeqCst)
+                    || finished_at.elapsed() > self.watch_timeout
+                {
+                    break;
+                }
+            }
+            let block = match futures::executor::block_on(select(
+                block_stream.try_next(),
+                Delay::new(WATCH_POLL_INTERVAL),
+            )) {
+                Either::Left((Ok(Some(block)), _)) => block,
+                Either::Left((Ok(None), _)) => {
+                    warn!("The new block subscription is closed");
+                    break;
+                }
+                Either::Left((Err(e), _)) => {
+                    warn!("Watch new block failed: {:?}", e);
+                    break;
+                }
+                Either::Right(_) => continue,
+            };
+            let included_at = Instant::now();
+            let included: Vec<_> = {
+                let mut pending = pending.lock().expect("pending txns lock poisoned");
+                block
+                    .body
+                    .txn_hashes()
+                    .into_iter()
+                    .filter_map(|txn_hash| pending.remove(&txn_hash).map(|txn| (txn_hash, txn)))
+                    .collect()
+            };
+            for (txn_hash, txn) in included {
+                let status = match self.client.chain_get_transaction_info(txn_hash) {
+                    Ok(info) => info.map(|info| info.status),
+                    Err(e) => {
+                        warn!("Get txn info of {} failed: {:?}", txn_hash, e);
+                        None
+                    }
+                };
+                stats.lock().expect("load stats lock poisoned").on_included(
+                    txn.name.as_str(),
+                    included_at - txn.submitted_at,
+                    status.as_ref(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A load scenario mixes script function txns by weight, loaded from a TOML or YAML file.
+//!
+//! ```toml
+//! accounts = 20
+//! target_tps = 50.0
+//! ramp_up_secs = 30
+//! duration_secs = 300
+//!
+//! [[txns]]
+//! name = "transfer"
+//! weight = 3
+//! function = "0x1::TransferScripts::peer_to_peer_v2"
+//! type_args = ["0x1::STC::STC"]
+//! args = [{ kind = "pool_account" }, { kind = "u128", min = 1, max = 1000 }]
+//! ```
+
+use anyhow::{bail, ensure, format_err, Result};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::transaction::{
+    parse_transaction_argument_advance, ScriptFunction, TransactionArgument,
+};
+use starcoin_vm_types::language_storage::{FunctionId, TypeTag};
+use starcoin_vm_types::parser::parse_type_tag;
+use starcoin_vm_types::transaction_argument::convert_txn_args;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const DEFAULT_MAX_GAS: u64 = 10_000_000;
+const DEFAULT_GAS_PRICE: u64 = 1;
+
+/// Generate an argument of the script function for every txn.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArgGenerator {
+    /// A constant argument, such as `100u128`, `0x1`, `true` or `b"bytes"`.
+    Const { value: String },
+    /// A random u64 in [min, max].
+    U64 { min: u64, max: u64 },
+    /// A random u128 in [min, max].
+    U128 { min: u128, max: u128 },
+    /// The sender of the txn.
+    Sender,
+    /// A random account of the account pool.
+    PoolAccount,
+    /// A random address, the account does not exist on chain in general.
+    RandomAddress,
+    /// A random vector<u8> of `len` bytes.
+    Bytes { len: usize },
+}
+
+impl ArgGenerator {
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::Const { value } => {
+                parse_transaction_argument_advance(value)?;
+            }
+            Self::U64 { min, max } => ensure!(min <= max, "invalid u64 range [{}, {}]", min, max),
+            Self::U128 { min, max } => {
+                ensure!(min <= max, "invalid u128 range [{}, {}]", min, max)
+            }
+            Self::Sender | Self::PoolAccount | Self::RandomAddress | Self::Bytes { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn generate<R: Rng>(
+        &self,
+        rng: &mut R,
+        sender: AccountAddress,
+        accounts: &[AccountAddress],
+    ) -> Result<TransactionArgument> {
+        Ok(match self {
+            Self::Const { value } => parse_transaction_argument_advance(value)?,
+            Self::U64 { min, max } => TransactionArgument::U64(rng.gen_range(*min..=*max)),
+            Self::U128 { min, max } => TransactionArgument::U128(rng.gen_range(*min..=*max)),
+            Self::Sender => TransactionArgument::Address(sender),
+            Self::PoolAccount => {
+                TransactionArgument::Address(accounts[rng.gen_range(0..accounts.len())])
+            }
+            Self::RandomAddress => TransactionArgument::Address(AccountAddress::new(rng.gen())),
+            Self::Bytes { len } => {
+                TransactionArgument::U8Vector((0..*len).map(|_| rng.gen()).collect())
+            }
+        })
+    }
+}
+
+/// A kind of script function txn in the scenario.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TxnTemplate {
+    /// Name of the txn kind in the report.
+    pub name: String,
+    /// Relative frequency of the txn kind in the load.
+    pub weight: u32,
+    /// Script function id, such as `0x1::TransferScripts::peer_to_peer_v2`.
+    pub function: String,
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<ArgGenerator>,
+    pub max_gas: Option<u64>,
+    pub gas_price: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Number of the accounts in the pool to send the txns, they are created and funded if absent.
+    pub accounts: u32,
+    /// The submitted txns per second after the ramp-up.
+    pub target_tps: f64,
+    /// Seconds to raise the tps from 0 to `target_tps` linearly.
+    #[serde(default)]
+    pub ramp_up_secs: u64,
+    /// Seconds to submit the txns, including the ramp-up.
+    pub duration_secs: u64,
+    /// Write the report to the file instead of stdout.
+    pub report: Option<PathBuf>,
+    pub txns: Vec<TxnTemplate>,
+}
+
+impl Scenario {
+    /// Load the scenario from a `.yaml`/`.yml` file, or a TOML file otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let scenario: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(content.as_str())?,
+            _ => toml::from_str(content.as_str())?,
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure!(self.accounts > 0, "scenario should have accounts");
+        ensure!(self.target_tps > 0.0, "target_tps should be positive");
+        ensure!(
+            self.ramp_up_secs <= self.duration_secs,
+            "ramp_up_secs should not exceed duration_secs"
+        );
+        ensure!(
+            self.txns.iter().any(|txn| txn.weight > 0),
+            "scenario should have a txn with positive weight"
+        );
+        for txn in &self.txns {
+            for arg in &txn.args {
+                arg.validate()
+                    .map_err(|e| format_err!("invalid arg of txn {}: {}", txn.name, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of txns should be submitted in `elapsed_secs` since the start.
+    pub fn expected_txns(&self, elapsed_secs: f64) -> f64 {
+        let ramp_up = self.ramp_up_secs as f64;
+        if elapsed_secs < ramp_up {
+            self.target_tps * elapsed_secs * elapsed_secs / (2.0 * ramp_up)
+        } else {
+            self.target_tps * (elapsed_secs - ramp_up / 2.0)
+        }
+    }
+}
+
+/// A txn kind of the scenario with the function and type args parsed.
+struct CompiledTemplate {
+    name: String,
+    function: FunctionId,
+    type_args: Vec<TypeTag>,
+    args: Vec<ArgGenerator>,
+    max_gas: u64,
+    gas_price: u64,
+}
+
+/// The script function of a generated txn.
+pub struct GeneratedTxn<'a> {
+    pub name: &'a str,
+    pub script_function: ScriptFunction,
+    pub max_gas: u64,
+    pub gas_price: u64,
+}
+
+/// Generate the txns of the scenario by the weights.
+pub struct ScenarioGenerator {
+    templates: Vec<CompiledTemplate>,
+    weights: WeightedIndex<u32>,
+}
+
+impl ScenarioGenerator {
+    pub fn new(scenario: &Scenario) -> Result<Self> {
+        let templates = scenario
+            .txns
+            .iter()
+            .map(|txn| {
+                Ok(CompiledTemplate {
+                    name: txn.name.clone(),
+                    function: FunctionId::from_str(txn.function.as_str())?,
+                    type_args: txn
+                        .type_args
+                        .iter()
+                        .map(|type_arg| parse_type_tag(type_arg.as_str()))
+                        .collect::<Result<_>>()?,
+                    args: txn.args.clone(),
+                    max_gas: txn.max_gas.unwrap_or(DEFAULT_MAX_GAS),
+                    gas_price: txn.gas_price.unwrap_or(DEFAULT_GAS_PRICE),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let weights = WeightedIndex::new(scenario.txns.iter().map(|txn| txn.weight))?;
+        Ok(Self { templates, weights })
+    }
+
+    pub fn generate<R: Rng>(
+        &self,
+        rng: &mut R,
+        sender: AccountAddress,
+        accounts: &[AccountAddress],
+    ) -> Result<GeneratedTxn> {
+        if accounts.is_empty() {
+            bail!("the account pool is empty");
+        }
+        let template = &self.templates[self.weights.sample(rng)];
+        let args = template
+            .args
+            .iter()
+            .map(|arg| arg.generate(rng, sender, accounts))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(GeneratedTxn {
+            name: template.name.as_str(),
+            script_function: ScriptFunction::new(
+                template.function.module.clone(),
+                template.function.function.clone(),
+                template.type_args.clone(),
+                convert_txn_args(&args),
+            ),
+            max_gas: template.max_gas,
+            gas_price: template.gas_price,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use starcoin_config::temp_dir;
+
+    const SCENARIO_TOML: &str = r#"
+accounts = 20
+target_tps = 50.0
+ramp_up_secs = 30
+duration_secs = 300
+
+[[txns]]
+name = "transfer"
+weight = 3
+function = "0x1::TransferScripts::peer_to_peer_v2"
+type_args = ["0x1::STC::STC"]
+args = [{ kind = "pool_account" }, { kind = "u128", min = 1, max = 1000 }]
+"#;
+
+    const SCENARIO_YAML: &str = r#"
+accounts: 20
+target_tps: 50.0
+ramp_up_secs: 30
+duration_secs: 300
+txns:
+  - name: transfer
+    weight: 3
+    function: "0x1::TransferScripts::peer_to_peer_v2"
+    type_args: ["0x1::STC::STC"]
+    args:
+      - kind: pool_account
+      - kind: u128
+        min: 1
+        max: 1000
+"#;
+
+    fn load(file_name: &str, content: &str) -> Result<Scenario> {
+        let dir = temp_dir();
+        let path = dir.path().join(file_name);
+        std::fs::write(path.as_path(), content)?;
+        Scenario::load(path.as_path())
+    }
+
+    fn scenario(ramp_up_secs: u64, txns: Vec<TxnTemplate>) -> Scenario {
+        Scenario {
+            accounts: 2,
+            target_tps: 10.0,
+            ramp_up_secs,
+            duration_secs: 100,
+            report: None,
+            txns,
+        }
+    }
+
+    fn template(name: &str, weight: u32, args: Vec<ArgGenerator>) -> TxnTemplate {
+        TxnTemplate {
+            name: name.to_string(),
+            weight,
+            function: "0x1::TransferScripts::peer_to_peer_v2".to_string(),
+            type_args: vec!["0x1::STC::STC".to_string()],
+            args,
+            max_gas: None,
+            gas_price: None,
+        }
+    }
+
+    fn check_loaded(scenario: &Scenario) {
+        assert_eq!(scenario.accounts, 20);
+        assert_eq!(scenario.target_tps, 50.0);
+        assert_eq!(scenario.ramp_up_secs, 30);
+        assert_eq!(scenario.duration_secs, 300);
+        assert!(scenario.report.is_none());
+        assert_eq!(scenario.txns.len(), 1);
+        let txn = &scenario.txns[0];
+        assert_eq!(txn.name, "transfer");
+        assert_eq!(txn.weight, 3);
+        assert_eq!(txn.type_args, vec!["0x1::STC::STC".to_string()]);
+        assert!(matches!(txn.args[0], ArgGenerator::PoolAccount));
+        assert!(matches!(
+            txn.args[1],
+            ArgGenerator::U128 { min: 1, max: 1000 }
+        ));
+        assert!(txn.max_gas.is_none());
+        assert!(txn.gas_price.is_none());
+    }
+
+    #[test]
+    fn test_load_scenario() -> Result<()> {
+        check_loaded(&load("scenario.toml", SCENARIO_TOML)?);
+        check_loaded(&load("scenario.yaml", SCENARIO_YAML)?);
+        check_loaded(&load("scenario.yml", SCENARIO_YAML)?);
+        // the format is decided by the extension.
+        assert!(load("scenario.toml", SCENARIO_YAML).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_invalid_scenario() {
+        let invalid = [
+            ("accounts: 20", "accounts: 0"),
+            ("target_tps: 50.0", "target_tps: 0.0"),
+            ("target_tps: 50.0", "target_tps: -1.0"),
+            ("ramp_up_secs: 30", "ramp_up_secs: 301"),
+            ("weight: 3", "weight: 0"),
+            ("min: 1\n        max: 1000", "min: 1000\n        max: 1"),
+            (
+                "- kind: pool_account",
+                "- kind: const\n        value: not_an_argument",
+            ),
+        ];
+        for (from, to) in invalid {
+            let content = SCENARIO_YAML.replace(from, to);
+            assert_ne!(content, SCENARIO_YAML);
+            assert!(
+                load("scenario.yaml", content.as_str()).is_err(),
+                "scenario with `{}` should be rejected",
+                to
+            );
+        }
+        let no_txns = scenario(0, vec![]);
+        assert!(no_txns.validate().is_err());
+        let valid_range = scenario(
+            0,
+            vec![template(
+                "transfer",
+                1,
+                vec![ArgGenerator::U64 { min: 7, max: 7 }],
+            )],
+        );
+        assert!(valid_range.validate().is_ok());
+    }
+
+    #[test]
+    fn test_expected_txns() {
+        let ramp_up = scenario(10, vec![]);
+        assert_eq!(ramp_up.expected_txns(0.0), 0.0);
+        // tps raises linearly, the txns are the area under the tps line.
+        assert_eq!(ramp_up.expected_txns(5.0), 12.5);
+        assert_eq!(ramp_up.expected_txns(10.0), 50.0);
+        assert_eq!(ramp_up.expected_txns(20.0), 150.0);
+
+        let no_ramp_up = scenario(0, vec![]);
+        assert_eq!(no_ramp_up.expected_txns(0.0), 0.0);
+        assert_eq!(no_ramp_up.expected_txns(3.0), 30.0);
+    }
+
+    #[test]
+    fn test_generate_by_weight() -> Result<()> {
+        let generator = ScenarioGenerator::new(&scenario(
+            0,
+            vec![
+                template("heavy", 3, vec![]),
+                template("light", 1, vec![]),
+                template("disabled", 0, vec![]),
+            ],
+        ))?;
+        let sender = AccountAddress::random();
+        let accounts = vec![sender];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut heavy = 0;
+        let mut light = 0;
+        for _ in 0..4000 {
+            let txn = generator.generate(&mut rng, sender, accounts.as_slice())?;
+            match txn.name {
+                "heavy" => heavy += 1,
+                "light" => light += 1,
+                name => panic!("unexpected txn {}", name),
+            }
+            assert_eq!(txn.max_gas, DEFAULT_MAX_GAS);
+            assert_eq!(txn.gas_price, DEFAULT_GAS_PRICE);
+        }
+        assert!((2800..=3200).contains(&heavy), "heavy: {}", heavy);
+        assert_eq!(heavy + light, 4000);
+
+        assert!(generator.generate(&mut rng, sender, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_args() -> Result<()> {
+        let mut txn = template(
+            "all_args",
+            1,
+            vec![
+                ArgGenerator::Const {
+                    value: "100u128".to_string(),
+                },
+                ArgGenerator::U64 { min: 10, max: 20 },
+                ArgGenerator::U128 { min: 5, max: 5 },
+                ArgGenerator::Sender,
+                ArgGenerator::PoolAccount,
+                ArgGenerator::RandomAddress,
+                ArgGenerator::Bytes { len: 8 },
+            ],
+        );
+        txn.max_gas = Some(1000);
+        txn.gas_price = Some(2);
+        let generator = ScenarioGenerator::new(&scenario(0, vec![txn]))?;
+        let sender = AccountAddress::random();
+        let accounts = vec![AccountAddress::random(), AccountAddress::random()];
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let txn = generator.generate(&mut rng, sender, accounts.as_slice())?;
+            assert_eq!(txn.name, "all_args");
+            assert_eq!(txn.max_gas, 1000);
+            assert_eq!(txn.gas_price, 2);
+            let function = &txn.script_function;
+            assert_eq!(function.function().as_str(), "peer_to_peer_v2");
+            assert_eq!(function.ty_args().len(), 1);
+            let args = function.args();
+            assert_eq!(args.len(), 7);
+            assert_eq!(bcs_ext::from_bytes::<u128>(&args[0])?, 100);
+            assert!((10..=20).contains(&bcs_ext::from_bytes::<u64>(&args[1])?));
+            assert_eq!(bcs_ext::from_bytes::<u128>(&args[2])?, 5);
+            assert_eq!(bcs_ext::from_bytes::<AccountAddress>(&args[3])?, sender);
+            assert!(accounts.contains(&bcs_ext::from_bytes::<AccountAddress>(&args[4])?));
+            bcs_ext::from_bytes::<AccountAddress>(&args[5])?;
+            assert_eq!(bcs_ext::from_bytes::<Vec<u8>>(&args[6])?.len(), 8);
+        }
+
+        // the same seed generates the same txns.
+        let mut rng1 = StdRng::seed_from_u64(1);
+        let mut rng2 = StdRng::seed_from_u64(1);
+        for _ in 0..10 {
+            let txn1 = generator.generate(&mut rng1, sender, accounts.as_slice())?;
+            let txn2 = generator.generate(&mut rng2, sender, accounts.as_slice())?;
+            assert_eq!(txn1.script_function, txn2.script_function);
+        }
+        Ok(())
+    }
+}
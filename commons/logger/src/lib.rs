@@ -26,6 +26,7 @@ use std::str::FromStr;
 use std::sync::{Arc, Once};
 
 pub mod structured_log;
+pub mod tracer;
 
 /// Logger prelude which includes all logging macros.
 pub mod prelude {
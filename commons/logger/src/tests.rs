@@ -1,5 +1,8 @@
 use super::prelude::*;
+use crate::tracer::{current_context, disable_tracer, init_tracer, Span, TraceExporterConfig};
 use crate::LogLevelSpec;
+use serde_json::Value;
+use std::time::Duration;
 
 #[test]
 fn test_log() {
@@ -50,3 +53,54 @@ fn test_log_level_spec() {
         assert_eq!(actual, expected);
     }
 }
+
+#[test]
+fn test_tracer_file_exporter() {
+    let path = std::env::temp_dir().join(format!("starcoin_trace_{}.json", rand::random::<u64>()));
+    init_tracer(TraceExporterConfig::File(path.clone())).unwrap();
+    let parent_context = {
+        let parent = Span::enter("parent").with("number", 1);
+        {
+            let mut child = Span::enter("child");
+            assert_eq!(current_context(), child.context());
+            child.set_error("child failed");
+        }
+        assert_eq!(current_context(), parent.context());
+        parent.context().unwrap()
+    };
+    assert_eq!(current_context(), None);
+    let detached = Span::new("detached", Some(parent_context));
+    assert_eq!(current_context(), None);
+    drop(detached);
+    disable_tracer();
+    assert!(Span::enter("disabled").context().is_none());
+
+    let mut spans: Vec<Value> = vec![];
+    for _ in 0..50 {
+        std::thread::sleep(Duration::from_millis(100));
+        spans = std::fs::read_to_string(path.as_path())
+            .unwrap_or_default()
+            .lines()
+            .flat_map(|line| {
+                let request: Value = serde_json::from_str(line).unwrap();
+                request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect();
+        if spans.len() >= 3 {
+            break;
+        }
+    }
+    let span = |name: &str| spans.iter().find(|span| span["name"] == name).unwrap();
+    let parent_span_id = format!("{:016x}", parent_context.span_id);
+    assert_eq!(span("parent")["spanId"], parent_span_id.as_str());
+    assert!(span("parent")["parentSpanId"].is_null());
+    assert_eq!(span("parent")["attributes"][0]["value"]["stringValue"], "1");
+    assert_eq!(span("child")["parentSpanId"], parent_span_id.as_str());
+    assert_eq!(span("child")["traceId"], span("parent")["traceId"]);
+    assert_eq!(span("child")["status"]["code"], 2);
+    assert_eq!(span("detached")["parentSpanId"], parent_span_id.as_str());
+    let _ = std::fs::remove_file(path);
+}
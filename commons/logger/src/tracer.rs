@@ -0,0 +1,333 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! OpenTelemetry style tracing spans, exported to an OTLP/HTTP collector or a local JSON lines file.
+//! The spans are batched and exported in a background thread, tracing is a no-op until `init_tracer`.
+
+use anyhow::{ensure, format_err, Result};
+use arc_swap::ArcSwapOption;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request, Uri};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SERVICE_NAME: &str = "starcoin";
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+/// Max spans buffered for the exporter, the new spans are dropped if the exporter falls behind.
+const SPAN_CHANNEL_CAPACITY: usize = 10240;
+const MAX_EXPORT_BATCH_SIZE: usize = 512;
+const EXPORT_INTERVAL: Duration = Duration::from_secs(2);
+/// OTLP `SPAN_KIND_INTERNAL`.
+const SPAN_KIND_INTERNAL: u8 = 1;
+/// OTLP `STATUS_CODE_ERROR`.
+const STATUS_CODE_ERROR: u8 = 2;
+
+static G_SPAN_SENDER: Lazy<ArcSwapOption<Sender<SpanData>>> = Lazy::new(ArcSwapOption::empty);
+
+thread_local! {
+    static CURRENT_CONTEXT: Cell<Option<TraceContext>> = Cell::new(None);
+}
+
+/// Identify a span in a trace, propagate it to continue the trace in other threads or services.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TraceContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+}
+
+impl TraceContext {
+    fn new(trace_id: Option<u128>) -> Self {
+        Self {
+            // 0 is an invalid id in OpenTelemetry.
+            trace_id: trace_id.unwrap_or_else(|| rand::random::<u128>().max(1)),
+            span_id: rand::random::<u64>().max(1),
+        }
+    }
+}
+
+/// Where to export the spans.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceExporterConfig {
+    /// The OTLP/HTTP endpoint of a collector, such as `http://127.0.0.1:4318`, spans are posted as JSON.
+    Otlp(String),
+    /// Append the spans to a local file, a line of OTLP JSON for every batch.
+    File(PathBuf),
+}
+
+#[derive(Clone, Debug)]
+pub struct SpanData {
+    pub context: TraceContext,
+    pub parent_span_id: Option<u64>,
+    pub name: &'static str,
+    /// Unix timestamp in nanoseconds.
+    pub start_time: u64,
+    pub end_time: u64,
+    pub attributes: Vec<(&'static str, String)>,
+    pub error: Option<String>,
+}
+
+impl SpanData {
+    fn to_json(&self) -> Value {
+        let mut span = json!({
+            "traceId": format!("{:032x}", self.context.trace_id),
+            "spanId": format!("{:016x}", self.context.span_id),
+            "name": self.name,
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": self.start_time.to_string(),
+            "endTimeUnixNano": self.end_time.to_string(),
+            "attributes": self
+                .attributes
+                .iter()
+                .map(|(key, value)| string_attribute(key, value))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(parent_span_id) = self.parent_span_id {
+            span["parentSpanId"] = json!(format!("{:016x}", parent_span_id));
+        }
+        if let Some(error) = &self.error {
+            span["status"] = json!({"code": STATUS_CODE_ERROR, "message": error});
+        }
+        span
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({"key": key, "value": {"stringValue": value}})
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// The OTLP `ExportTraceServiceRequest` of the spans in JSON.
+fn export_request(spans: &[SpanData]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {"attributes": [string_attribute("service.name", SERVICE_NAME)]},
+            "scopeSpans": [{
+                "scope": {"name": SERVICE_NAME},
+                "spans": spans.iter().map(SpanData::to_json).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+trait SpanExporter: Send {
+    fn export(&mut self, spans: &[SpanData]) -> Result<()>;
+}
+
+struct FileExporter {
+    file: File,
+}
+
+impl SpanExporter for FileExporter {
+    fn export(&mut self, spans: &[SpanData]) -> Result<()> {
+        writeln!(self.file, "{}", export_request(spans))?;
+        Ok(())
+    }
+}
+
+struct OtlpHttpExporter {
+    runtime: tokio::runtime::Runtime,
+    client: Client<HttpConnector>,
+    uri: Uri,
+}
+
+impl OtlpHttpExporter {
+    fn new(endpoint: &str) -> Result<Self> {
+        let endpoint = endpoint.trim_end_matches('/');
+        let uri = if endpoint.ends_with(OTLP_TRACES_PATH) {
+            endpoint.to_string()
+        } else {
+            format!("{}{}", endpoint, OTLP_TRACES_PATH)
+        };
+        let uri: Uri = uri
+            .parse()
+            .map_err(|e| format_err!("Invalid OTLP endpoint {}: {}", endpoint, e))?;
+        ensure!(
+            uri.scheme_str() == Some("http"),
+            "Only the http OTLP endpoint is supported, got: {}",
+            uri
+        );
+        Ok(Self {
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?,
+            client: Client::new(),
+            uri,
+        })
+    }
+}
+
+impl SpanExporter for OtlpHttpExporter {
+    fn export(&mut self, spans: &[SpanData]) -> Result<()> {
+        let request = Request::post(self.uri.clone())
+            .header("content-type", "application/json")
+            .body(Body::from(export_request(spans).to_string()))?;
+        let response = self.runtime.block_on(self.client.request(request))?;
+        ensure!(
+            response.status().is_success(),
+            "Export spans to {} failed, status: {}",
+            self.uri,
+            response.status()
+        );
+        Ok(())
+    }
+}
+
+fn export_loop(receiver: Receiver<SpanData>, mut exporter: Box<dyn SpanExporter>) {
+    let mut batch = Vec::with_capacity(MAX_EXPORT_BATCH_SIZE);
+    loop {
+        let disconnected = match receiver.recv_timeout(EXPORT_INTERVAL) {
+            Ok(span) => {
+                batch.push(span);
+                batch.extend(receiver.try_iter().take(MAX_EXPORT_BATCH_SIZE - 1));
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if !batch.is_empty() {
+            if let Err(e) = exporter.export(batch.as_slice()) {
+                log::warn!("Export {} spans error: {:?}", batch.len(), e);
+            }
+            batch.clear();
+        }
+        if disconnected {
+            break;
+        }
+    }
+}
+
+/// Start to export the spans by the `config`, the previous exporter is stopped after its spans are exported.
+pub fn init_tracer(config: TraceExporterConfig) -> Result<()> {
+    let exporter: Box<dyn SpanExporter> = match &config {
+        TraceExporterConfig::Otlp(endpoint) => Box::new(OtlpHttpExporter::new(endpoint)?),
+        TraceExporterConfig::File(path) => Box::new(FileExporter {
+            file: OpenOptions::new().append(true).create(true).open(path)?,
+        }),
+    };
+    let (sender, receiver) = crossbeam_channel::bounded(SPAN_CHANNEL_CAPACITY);
+    std::thread::Builder::new()
+        .name("trace-exporter".to_string())
+        .spawn(move || export_loop(receiver, exporter))?;
+    G_SPAN_SENDER.store(Some(Arc::new(sender)));
+    log::info!("Export tracing spans to {:?}", config);
+    Ok(())
+}
+
+/// Stop exporting the spans.
+pub fn disable_tracer() {
+    G_SPAN_SENDER.store(None);
+}
+
+pub fn is_tracer_enabled() -> bool {
+    G_SPAN_SENDER.load().is_some()
+}
+
+/// The context of the span entered in the current thread.
+pub fn current_context() -> Option<TraceContext> {
+    CURRENT_CONTEXT.with(|current| current.get())
+}
+
+/// A span of work, it is exported when dropped.
+#[must_use]
+pub struct Span {
+    inner: Option<SpanInner>,
+}
+
+struct SpanInner {
+    data: SpanData,
+    /// The context to restore in the thread on drop, if the span is entered.
+    entered: Option<Option<TraceContext>>,
+}
+
+impl Span {
+    fn start(name: &'static str, parent: Option<TraceContext>, enter: bool) -> Self {
+        if !is_tracer_enabled() {
+            return Self { inner: None };
+        }
+        let context = TraceContext::new(parent.map(|parent| parent.trace_id));
+        let entered = enter.then(|| CURRENT_CONTEXT.with(|current| current.replace(Some(context))));
+        Self {
+            inner: Some(SpanInner {
+                data: SpanData {
+                    context,
+                    parent_span_id: parent.map(|parent| parent.span_id),
+                    name,
+                    start_time: now_nanos(),
+                    end_time: 0,
+                    attributes: vec![],
+                    error: None,
+                },
+                entered,
+            }),
+        }
+    }
+
+    /// Start a span as a child of the span entered in the current thread, or the root of a new trace,
+    /// and enter it until dropped, the spans started in the thread meanwhile are its children.
+    /// Do not hold an entered span across an `.await`, use `Span::new` in async code.
+    pub fn enter(name: &'static str) -> Self {
+        Self::start(name, current_context(), true)
+    }
+
+    /// Start a span as a child of the `parent`, or the root of a new trace, and enter it until dropped.
+    pub fn enter_with_parent(name: &'static str, parent: Option<TraceContext>) -> Self {
+        Self::start(name, parent, true)
+    }
+
+    /// Start a span as a child of the `parent`, or the root of a new trace, without entering it.
+    pub fn new(name: &'static str, parent: Option<TraceContext>) -> Self {
+        Self::start(name, parent, false)
+    }
+
+    pub fn context(&self) -> Option<TraceContext> {
+        self.inner.as_ref().map(|inner| inner.data.context)
+    }
+
+    pub fn record<V: Display>(&mut self, key: &'static str, value: V) {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.data.attributes.push((key, value.to_string()));
+        }
+    }
+
+    pub fn with<V: Display>(mut self, key: &'static str, value: V) -> Self {
+        self.record(key, value);
+        self
+    }
+
+    /// Mark the span as failed.
+    pub fn set_error<E: Display>(&mut self, error: E) {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.data.error = Some(error.to_string());
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(mut inner) = self.inner.take() {
+            if let Some(previous) = inner.entered {
+                CURRENT_CONTEXT.with(|current| current.set(previous));
+            }
+            inner.data.end_time = now_nanos();
+            if let Some(sender) = &*G_SPAN_SENDER.load() {
+                // drop the span if the exporter falls behind.
+                let _ = sender.try_send(inner.data);
+            }
+        }
+    }
+}
@@ -84,9 +84,12 @@ where
             return Ok(());
         }
         let service = self.service.take();
-        if let Some(mut service) = service {
-            service.stopped(ctx)?;
-        }
+        let result = match service {
+            Some(mut service) => service.stopped(ctx),
+            None => Ok(()),
+        };
+        ctx.cancel_service_tasks();
+        result?;
         info!("Service {} stop.", S::service_name());
         Ok(())
     }
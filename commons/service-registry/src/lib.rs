@@ -7,11 +7,13 @@ pub mod mocker;
 mod service;
 mod service_actor;
 mod service_cache;
+mod service_health;
 mod service_ref;
 mod service_registry;
 mod types;
 
 pub use service::*;
+pub use service_health::in_supervised_handler;
 pub use service_ref::*;
 pub use service_registry::{Registry, RegistryAsyncService, RegistryService};
 pub use types::*;
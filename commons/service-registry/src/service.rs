@@ -11,6 +11,7 @@ use actix::{ActorContext, ActorFutureExt, AsyncContext, Context};
 use anyhow::{format_err, Result};
 use futures::channel::oneshot::{channel, Receiver};
 use futures::executor::block_on;
+use futures::stream::abortable;
 use futures::{Future, Stream, StreamExt};
 use log::error;
 use std::any::type_name;
@@ -53,7 +54,7 @@ where
 
     /// Get Self's ServiceRef
     pub fn self_ref(&self) -> ServiceRef<S> {
-        self.ctx.address().into()
+        ServiceRef::new_with_health(self.ctx.address(), self.cache.health.clone())
     }
 
     pub fn registry_ref(&self) -> &ServiceRef<RegistryService> {
@@ -151,6 +152,8 @@ where
         S: EventHandler<S, M>,
         MS: Stream<Item = M> + 'static,
     {
+        let (stream, abort_handle) = abortable(stream);
+        self.cache.streams.push(abort_handle);
         self.ctx.add_message_stream(stream.map(EventMessage::new))
     }
 
@@ -204,10 +207,22 @@ where
     where
         F: FnMut(&mut ServiceContext<S>) + 'static,
     {
-        self.ctx.run_interval(dur, move |this, ctx| {
+        let handle = self.ctx.run_interval(dur, move |this, ctx| {
             let mut service_ctx = ServiceContext::new(&mut this.cache, ctx);
             f(&mut service_ctx)
         });
+        self.cache.intervals.push(handle);
+    }
+
+    /// Cancel the intervals and streams of the stopped service, so a restarted service
+    /// does not run them twice.
+    pub(crate) fn cancel_service_tasks(&mut self) {
+        for handle in self.cache.intervals.drain(..) {
+            self.ctx.cancel_future(handle);
+        }
+        for handle in self.cache.streams.drain(..) {
+            handle.abort();
+        }
     }
 
     /// Exec a future and get result.
@@ -5,16 +5,18 @@ use crate::handler_proxy::{HandlerProxy, MockHandlerProxy, ServiceHandlerProxy};
 use crate::mocker::MockHandler;
 use crate::service::{ActorService, ServiceContext, ServiceFactory, ServiceHandler};
 use crate::service_cache::ServiceCache;
+use crate::service_health::{catch_supervised, panic_message, MailboxTicket, ServiceHealthStats};
 use crate::service_registry::ServiceStatusChangeEvent;
 use crate::{
-    EventHandler, RegistryService, ServiceCmd, ServiceEventStream, ServicePing, ServiceQuery,
-    ServiceQueryResult, ServiceRef, ServiceRequest,
+    EventHandler, RegistryService, ServiceCmd, ServiceEscalatedEvent, ServiceEventStream,
+    ServicePing, ServiceQuery, ServiceQueryResult, ServiceRef, ServiceRequest, SupervisionPolicy,
 };
 use actix::{Actor, AsyncContext, Context, Handler, Message, MessageResult, Supervised};
 use anyhow::{format_err, Result};
 use futures::{Stream, StreamExt};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 const DEFAULT_MAIL_BOX_CAP: usize = 128;
 
@@ -24,6 +26,7 @@ where
 {
     proxy: Box<dyn HandlerProxy<S> + Send>,
     pub(crate) cache: ServiceCache,
+    policy: Option<SupervisionPolicy>,
 }
 
 impl<S> ServiceActor<S>
@@ -37,6 +40,7 @@ where
         Self {
             proxy: Box::new(ServiceHandlerProxy::new::<F>()),
             cache: ServiceCache::new(registry),
+            policy: None,
         }
     }
 
@@ -47,9 +51,126 @@ where
         Self {
             proxy: Box::new(MockHandlerProxy::new(mocker)),
             cache: ServiceCache::new(registry),
+            policy: None,
         }
     }
 
+    pub(crate) fn with_supervision(
+        mut self,
+        policy: Option<SupervisionPolicy>,
+        health: Arc<ServiceHealthStats>,
+    ) -> Self {
+        self.policy = policy;
+        self.cache.health = health;
+        self
+    }
+
+    /// Run a message handler, a panic in it is caught and handled by the supervision policy if the service has one.
+    /// Returns `None` if the handler panicked.
+    fn supervise<F, T>(&mut self, ctx: &mut Context<Self>, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut dyn HandlerProxy<S>, &mut ServiceContext<S>) -> T,
+    {
+        let supervised = self.policy.is_some();
+        let result = {
+            let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
+            let proxy = self.proxy.as_mut();
+            if supervised {
+                catch_supervised(|| f(proxy, &mut service_ctx))
+            } else {
+                Ok(f(proxy, &mut service_ctx))
+            }
+        };
+        self.cache.health.on_handled();
+        match result {
+            Ok(t) => Some(t),
+            Err(payload) => {
+                self.on_panic(ctx, panic_message(payload.as_ref()));
+                None
+            }
+        }
+    }
+
+    fn on_panic(&mut self, ctx: &mut Context<Self>, reason: String) {
+        self.cache.health.on_panic();
+        error!("{} service panicked: {}", S::service_name(), reason);
+        match self.policy {
+            Some(SupervisionPolicy::Ignore) | None => {
+                warn!("Ignore the panic of {} service.", S::service_name());
+            }
+            Some(SupervisionPolicy::Restart { .. }) => {
+                self.stop_failed_service(ctx);
+                self.schedule_restart(ctx);
+            }
+            Some(SupervisionPolicy::Escalate) => {
+                self.stop_failed_service(ctx);
+                error!(
+                    "Escalate the panic of {} service to shutdown the system.",
+                    S::service_name()
+                );
+                let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
+                service_ctx.broadcast(ServiceEscalatedEvent {
+                    service_name: S::service_name().to_string(),
+                    reason,
+                });
+            }
+        }
+        self.notify_status();
+    }
+
+    fn stop_failed_service(&mut self, ctx: &mut Context<Self>) {
+        let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
+        let proxy = self.proxy.as_mut();
+        match catch_supervised(|| proxy.stop(&mut service_ctx)) {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Stop failed {} service error: {:?}", S::service_name(), e),
+            Err(payload) => error!(
+                "Stop failed {} service panicked: {}",
+                S::service_name(),
+                panic_message(payload.as_ref())
+            ),
+        }
+    }
+
+    fn schedule_restart(&mut self, ctx: &mut Context<Self>) {
+        let restart_count = self.cache.health.restart_count();
+        match self
+            .policy
+            .and_then(|policy| policy.restart_backoff(restart_count))
+        {
+            Some(backoff) => {
+                info!(
+                    "Restart {} service after {:?}, restarted {} times.",
+                    S::service_name(),
+                    backoff,
+                    restart_count
+                );
+                ctx.run_later(backoff, |act, ctx| act.restart_failed_service(ctx));
+            }
+            None => error!(
+                "{} service restarted {} times, leave it stopped.",
+                S::service_name(),
+                restart_count
+            ),
+        }
+    }
+
+    fn restart_failed_service(&mut self, ctx: &mut Context<Self>) {
+        if !self.proxy.status().is_stopped() {
+            info!("{} service is already started.", S::service_name());
+            return;
+        }
+        self.cache.health.on_restart();
+        let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
+        if let Err(e) = self.proxy.start(&mut service_ctx) {
+            error!("Restart {} service error: {:?}", S::service_name(), e);
+            self.schedule_restart(ctx);
+        } else {
+            info!("{} service restarted by supervision.", S::service_name());
+        }
+        self.notify_status();
+    }
+
     fn notify_status(&self) {
         if self.cache.registry_ref().connected() {
             if let Err(e) = self
@@ -110,11 +231,22 @@ where
 #[derive(Debug)]
 pub struct ServiceMessage<R: ServiceRequest + 'static> {
     request: R,
+    ticket: Option<MailboxTicket>,
 }
 
 impl<R: ServiceRequest> ServiceMessage<R> {
     pub fn new(request: R) -> Self {
-        Self { request }
+        Self {
+            request,
+            ticket: None,
+        }
+    }
+
+    pub(crate) fn new_with_ticket(request: R, ticket: MailboxTicket) -> Self {
+        Self {
+            request,
+            ticket: Some(ticket),
+        }
     }
 
     pub fn into_inner(self) -> R {
@@ -127,7 +259,7 @@ where
     R: ServiceRequest,
 {
     fn from(request: R) -> Self {
-        ServiceMessage { request }
+        ServiceMessage::new(request)
     }
 }
 
@@ -144,19 +276,29 @@ where
 
     fn handle(&mut self, msg: ServiceMessage<R>, ctx: &mut Self::Context) -> Self::Result {
         debug!("{} handle request: {:?}", S::service_name(), &msg.request);
+        // keep the ticket until the request is handled.
+        let ServiceMessage {
+            request,
+            ticket: _ticket,
+        } = msg;
         if self.proxy.status().is_stopped() {
             return MessageResult(Err(format_err!("Service {} is stopped", S::service_name())));
         }
-        let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
-        let proxy_any = self.proxy.as_mut_any();
-        let resp = if let Some(proxy) = proxy_any.downcast_mut::<ServiceHandlerProxy<S>>() {
-            proxy.handle(msg.request, &mut service_ctx)
-        } else if let Some(proxy) = proxy_any.downcast_mut::<MockHandlerProxy<S>>() {
-            proxy.handle(msg.request, &mut service_ctx)
-        } else {
-            unreachable!("Unknown HandlerProxy type.")
-        };
-        MessageResult(Ok(resp))
+        let resp = self.supervise(ctx, move |proxy, service_ctx| {
+            let proxy_any = proxy.as_mut_any();
+            if let Some(proxy) = proxy_any.downcast_mut::<ServiceHandlerProxy<S>>() {
+                proxy.handle(request, service_ctx)
+            } else if let Some(proxy) = proxy_any.downcast_mut::<MockHandlerProxy<S>>() {
+                proxy.handle(request, service_ctx)
+            } else {
+                unreachable!("Unknown HandlerProxy type.")
+            }
+        });
+        MessageResult(
+            resp.ok_or_else(|| {
+                format_err!("Service {} panicked on the request", S::service_name())
+            }),
+        )
     }
 }
 
@@ -223,6 +365,7 @@ where
     M: Debug + Send,
 {
     msg: M,
+    ticket: Option<MailboxTicket>,
 }
 
 impl<M> EventMessage<M>
@@ -230,7 +373,14 @@ where
     M: Debug + Send,
 {
     pub fn new(msg: M) -> Self {
-        Self { msg }
+        Self { msg, ticket: None }
+    }
+
+    pub(crate) fn new_with_ticket(msg: M, ticket: MailboxTicket) -> Self {
+        Self {
+            msg,
+            ticket: Some(ticket),
+        }
     }
 
     pub fn into_inner(self) -> M {
@@ -254,19 +404,24 @@ where
 
     fn handle(&mut self, msg: EventMessage<M>, ctx: &mut Self::Context) -> Self::Result {
         debug!("{} handle event: {:?}", S::service_name(), &msg.msg);
+        // keep the ticket until the event is handled.
+        let EventMessage {
+            msg,
+            ticket: _ticket,
+        } = msg;
         if self.proxy.status().is_stopped() {
             info!("Service {} is already stopped", S::service_name());
             return;
         }
-        let mut service_ctx = ServiceContext::new(&mut self.cache, ctx);
-
-        let proxy_any = self.proxy.as_mut_any();
-        if let Some(proxy) = proxy_any.downcast_mut::<ServiceHandlerProxy<S>>() {
-            proxy.handle_event(msg.msg, &mut service_ctx);
-        } else if let Some(proxy) = proxy_any.downcast_mut::<MockHandlerProxy<S>>() {
-            proxy.handle_event(msg.msg, &mut service_ctx);
-        } else {
-            unreachable!("Unknown HandlerProxy type.")
-        };
+        self.supervise(ctx, move |proxy, service_ctx| {
+            let proxy_any = proxy.as_mut_any();
+            if let Some(proxy) = proxy_any.downcast_mut::<ServiceHandlerProxy<S>>() {
+                proxy.handle_event(msg, service_ctx);
+            } else if let Some(proxy) = proxy_any.downcast_mut::<MockHandlerProxy<S>>() {
+                proxy.handle_event(msg, service_ctx);
+            } else {
+                unreachable!("Unknown HandlerProxy type.")
+            };
+        });
     }
 }
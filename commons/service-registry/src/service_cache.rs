@@ -2,16 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::bus::BusService;
+use crate::service_health::ServiceHealthStats;
 use crate::{ActorService, RegistryAsyncService, RegistryService, ServiceRef};
+use actix::SpawnHandle;
 use anyhow::{format_err, Result};
 use futures::executor::block_on;
+use futures::future::AbortHandle;
 use std::any::{Any, TypeId};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 pub(crate) struct ServiceCache {
     registry: ServiceRef<RegistryService>,
     service_ref_cache: HashMap<TypeId, Box<dyn Any + Send>>,
+    pub(crate) health: Arc<ServiceHealthStats>,
+    /// The intervals run by the service, cancelled when the service stops.
+    pub(crate) intervals: Vec<SpawnHandle>,
+    /// The streams added by the service, aborted when the service stops.
+    pub(crate) streams: Vec<AbortHandle>,
 }
 
 impl ServiceCache {
@@ -19,6 +28,9 @@ impl ServiceCache {
         Self {
             registry,
             service_ref_cache: HashMap::new(),
+            health: Arc::new(ServiceHealthStats::default()),
+            intervals: vec![],
+            streams: vec![],
         }
     }
 
@@ -0,0 +1,109 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ServiceHealth;
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static IN_SUPERVISED_HANDLER: Cell<bool> = Cell::new(false);
+}
+
+/// Returns true if the current thread is running a message handler of a supervised service,
+/// a panic in it is caught and handled by the supervision policy of the service.
+/// A process wide panic hook should not abort the process for such a panic.
+pub fn in_supervised_handler() -> bool {
+    IN_SUPERVISED_HANDLER.with(|flag| flag.get())
+}
+
+/// Run a supervised message handler, and catch its panic.
+pub(crate) fn catch_supervised<F, R>(f: F) -> std::thread::Result<R>
+where
+    F: FnOnce() -> R,
+{
+    let previous = IN_SUPERVISED_HANDLER.with(|flag| flag.replace(true));
+    let result = catch_unwind(AssertUnwindSafe(f));
+    IN_SUPERVISED_HANDLER.with(|flag| flag.set(previous));
+    result
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Liveness stats of a service actor, shared by the actor, its `ServiceRef`s and the registry.
+#[derive(Debug, Default)]
+pub(crate) struct ServiceHealthStats {
+    mailbox_depth: AtomicU64,
+    /// 0 if the service never handled a message.
+    last_handled_time: AtomicU64,
+    restart_count: AtomicU64,
+    panic_count: AtomicU64,
+}
+
+impl ServiceHealthStats {
+    pub fn on_handled(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        self.last_handled_time.store(now, Ordering::Relaxed);
+    }
+
+    pub fn on_panic(&self) {
+        self.panic_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn on_restart(&self) {
+        self.restart_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn restart_count(&self) -> u64 {
+        self.restart_count.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> ServiceHealth {
+        let last_handled_time = self.last_handled_time.load(Ordering::Relaxed);
+        ServiceHealth {
+            mailbox_depth: self.mailbox_depth.load(Ordering::Relaxed),
+            last_handled_time: (last_handled_time > 0).then_some(last_handled_time),
+            restart_count: self.restart_count(),
+            panic_count: self.panic_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counted in the mailbox depth of a service while the message carrying it is alive.
+#[derive(Debug)]
+pub(crate) struct MailboxTicket {
+    stats: Arc<ServiceHealthStats>,
+}
+
+impl MailboxTicket {
+    pub fn new(stats: Arc<ServiceHealthStats>) -> Self {
+        stats.mailbox_depth.fetch_add(1, Ordering::Relaxed);
+        Self { stats }
+    }
+}
+
+impl Clone for MailboxTicket {
+    fn clone(&self) -> Self {
+        Self::new(self.stats.clone())
+    }
+}
+
+impl Drop for MailboxTicket {
+    fn drop(&mut self) {
+        self.stats.mailbox_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+}
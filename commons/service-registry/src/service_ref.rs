@@ -3,6 +3,7 @@
 
 use crate::service::{ActorService, ServiceHandler};
 use crate::service_actor::{EventMessage, ServiceActor, ServiceMessage};
+use crate::service_health::{MailboxTicket, ServiceHealthStats};
 use crate::{
     EventHandler, ServiceCmd, ServiceEventStream, ServiceQuery, ServiceQueryResult, ServiceRequest,
     ServiceStatus,
@@ -17,6 +18,7 @@ use log::warn;
 use std::any::type_name;
 use std::fmt::Debug;
 use std::sync::mpsc::TrySendError;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Clone)]
@@ -27,6 +29,7 @@ where
     // target service name.
     target_service: &'static str,
     recipient: Recipient<EventMessage<M>>,
+    health: Arc<ServiceHealthStats>,
 }
 
 impl<M> EventNotifier<M>
@@ -39,7 +42,10 @@ where
 
     pub fn notify(&self, msg: M) -> Result<(), TrySendError<M>> {
         self.recipient
-            .try_send(EventMessage::new(msg))
+            .try_send(EventMessage::new_with_ticket(
+                msg,
+                MailboxTicket::new(self.health.clone()),
+            ))
             .map_err(|e| match e {
                 SendError::Full(m) => TrySendError::Full(m.into_inner()),
                 SendError::Closed(m) => TrySendError::Disconnected(m.into_inner()),
@@ -60,6 +66,7 @@ where
         Self {
             target_service: S::service_name(),
             recipient: service_ref.addr.recipient::<EventMessage<M>>(),
+            health: service_ref.health,
         }
     }
 }
@@ -83,6 +90,7 @@ where
     S: ActorService + 'static,
 {
     pub(crate) addr: Addr<ServiceActor<S>>,
+    pub(crate) health: Arc<ServiceHealthStats>,
 }
 
 impl<S> Clone for ServiceRef<S>
@@ -92,6 +100,7 @@ where
     fn clone(&self) -> Self {
         Self {
             addr: self.addr.clone(),
+            health: self.health.clone(),
         }
     }
 }
@@ -101,7 +110,7 @@ where
     S: ActorService + 'static,
 {
     fn from(addr: Addr<ServiceActor<S>>) -> Self {
-        Self { addr }
+        Self::new(addr)
     }
 }
 
@@ -111,8 +120,21 @@ impl<S> ServiceRef<S>
 where
     S: ActorService,
 {
+    /// The messages sent by the ref are not counted in the mailbox depth of the service,
+    /// get the ref from the registry or the `ServiceContext` instead.
     pub fn new(addr: Addr<ServiceActor<S>>) -> Self {
-        Self { addr }
+        Self::new_with_health(addr, Arc::new(ServiceHealthStats::default()))
+    }
+
+    pub(crate) fn new_with_health(
+        addr: Addr<ServiceActor<S>>,
+        health: Arc<ServiceHealthStats>,
+    ) -> Self {
+        Self { addr, health }
+    }
+
+    fn mailbox_ticket(&self) -> MailboxTicket {
+        MailboxTicket::new(self.health.clone())
     }
 
     pub(crate) fn exec_service_cmd(&self, cmd: ServiceCmd) -> Result<()> {
@@ -144,7 +166,10 @@ where
     {
         async move {
             self.addr
-                .send(ServiceMessage::new(request))
+                .send(ServiceMessage::new_with_ticket(
+                    request,
+                    self.mailbox_ticket(),
+                ))
                 .await
                 .map_err(anyhow::Error::new)?
         }
@@ -157,7 +182,10 @@ where
         R: ServiceRequest + 'static,
         S: ServiceHandler<S, R>,
     {
-        self.addr.do_send(ServiceMessage::new(request))
+        self.addr.do_send(ServiceMessage::new_with_ticket(
+            request,
+            self.mailbox_ticket(),
+        ))
     }
 
     pub fn try_send<R>(&self, request: R) -> Result<(), TrySendError<R>>
@@ -166,7 +194,10 @@ where
         S: ServiceHandler<S, R>,
     {
         self.addr
-            .try_send(ServiceMessage::new(request))
+            .try_send(ServiceMessage::new_with_ticket(
+                request,
+                self.mailbox_ticket(),
+            ))
             .map_err(|e| match e {
                 SendError::Full(m) => TrySendError::Full(m.into_inner()),
                 SendError::Closed(m) => TrySendError::Disconnected(m.into_inner()),
@@ -180,7 +211,7 @@ where
         M: Clone + Debug + Send + 'static,
     {
         self.addr
-            .try_send(EventMessage::new(msg))
+            .try_send(EventMessage::new_with_ticket(msg, self.mailbox_ticket()))
             .map_err(|e| match e {
                 SendError::Full(m) => TrySendError::Full(m.into_inner()),
                 SendError::Closed(m) => TrySendError::Disconnected(m.into_inner()),
@@ -5,9 +5,10 @@ use crate::bus::BusService;
 use crate::mocker::MockHandler;
 use crate::service::{ActorService, ServiceFactory};
 use crate::service_actor::ServiceActor;
+use crate::service_health::ServiceHealthStats;
 use crate::{
     EventHandler, ServiceCmd, ServiceContext, ServiceHandler, ServiceInfo, ServicePing, ServiceRef,
-    ServiceRequest, ServiceStatus,
+    ServiceRequest, ServiceStatus, SupervisionPolicy,
 };
 use actix::prelude::SendError;
 use actix::{Actor, AsyncContext};
@@ -20,6 +21,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 trait ServiceRefProxy: Send + Sync {
     fn service_name(&self) -> &'static str;
@@ -66,6 +68,7 @@ where
         ServiceInfo {
             name: self.service_name().to_string(),
             status: self.status(),
+            health: self.service_ref.health.snapshot(),
         }
     }
 
@@ -184,7 +187,11 @@ impl Registry {
             .map(|handle| handle.check_status())
     }
 
-    fn do_register<S, F>(&mut self, f: F) -> Result<ServiceRef<S>>
+    fn do_register<S, F>(
+        &mut self,
+        f: F,
+        policy: Option<SupervisionPolicy>,
+    ) -> Result<ServiceRef<S>>
     where
         S: ActorService + 'static,
         F: FnOnce(ServiceRef<RegistryService>) -> ServiceActor<S> + Send + 'static,
@@ -193,12 +200,19 @@ impl Registry {
         if self.has_service(service_name) {
             bail!("Service with name: {} exist.", service_name)
         }
-        info!("Registry service: {}", service_name);
+        info!(
+            "Registry service: {} with supervision policy: {:?}",
+            service_name, policy
+        );
 
         let arbiter = Arbiter::new();
         let registry_ref = self.service_ref.clone();
-        let addr = ServiceActor::start_in_arbiter(&arbiter.handle(), move |_ctx| f(registry_ref));
-        let service_ref: ServiceRef<S> = addr.into();
+        let health = Arc::new(ServiceHealthStats::default());
+        let actor_health = health.clone();
+        let addr = ServiceActor::start_in_arbiter(&arbiter.handle(), move |_ctx| {
+            f(registry_ref).with_supervision(policy, actor_health)
+        });
+        let service_ref = ServiceRef::new_with_health(addr, health);
         let holder = ServiceHolder::new(arbiter, service_ref.clone());
         self.services.push(Box::new(holder));
         Ok(service_ref)
@@ -209,14 +223,26 @@ impl Registry {
         S: ActorService + 'static,
         F: ServiceFactory<S> + 'static,
     {
-        self.do_register(ServiceActor::new::<F>)
+        self.do_register(ServiceActor::new::<F>, None)
+    }
+
+    /// Register a service supervised by the `policy`.
+    pub fn register_with_policy<S, F>(&mut self, policy: SupervisionPolicy) -> Result<ServiceRef<S>>
+    where
+        S: ActorService + 'static,
+        F: ServiceFactory<S> + 'static,
+    {
+        self.do_register(ServiceActor::new::<F>, Some(policy))
     }
 
     pub fn register_mocker<S>(&mut self, mocker: Box<dyn MockHandler<S>>) -> Result<ServiceRef<S>>
     where
         S: ActorService + 'static,
     {
-        self.do_register(|registry_ref| ServiceActor::new_mocker(registry_ref, mocker))
+        self.do_register(
+            |registry_ref| ServiceActor::new_mocker(registry_ref, mocker),
+            None,
+        )
     }
 
     /// Stop service thread and remove from registry.
@@ -334,6 +360,7 @@ where
 {
     phantom_service: PhantomData<S>,
     phantom_factory: PhantomData<F>,
+    policy: Option<SupervisionPolicy>,
 }
 
 impl<S, F> Debug for RegisterRequest<S, F>
@@ -356,6 +383,15 @@ where
         Self {
             phantom_service: PhantomData,
             phantom_factory: PhantomData,
+            policy: None,
+        }
+    }
+
+    pub fn new_with_policy(policy: SupervisionPolicy) -> Self {
+        Self {
+            phantom_service: PhantomData,
+            phantom_factory: PhantomData,
+            policy: Some(policy),
         }
     }
 }
@@ -375,10 +411,13 @@ where
 {
     fn handle(
         &mut self,
-        _msg: RegisterRequest<S, F>,
+        msg: RegisterRequest<S, F>,
         _ctx: &mut ServiceContext<RegistryService>,
     ) -> Result<ServiceRef<S>> {
-        self.registry.register::<S, F>()
+        match msg.policy {
+            Some(policy) => self.registry.register_with_policy::<S, F>(policy),
+            None => self.registry.register::<S, F>(),
+        }
     }
 }
 
@@ -747,6 +786,19 @@ pub trait RegistryAsyncService {
         S: ActorService + 'static,
         F: ServiceFactory<S> + 'static;
 
+    /// Register a service supervised by the `policy`, a panic in its handlers is handled by the policy.
+    async fn register_with_policy<S>(&self, policy: SupervisionPolicy) -> Result<ServiceRef<S>>
+    where
+        S: ActorService + ServiceFactory<S> + 'static;
+
+    async fn register_by_factory_with_policy<S, F>(
+        &self,
+        policy: SupervisionPolicy,
+    ) -> Result<ServiceRef<S>>
+    where
+        S: ActorService + 'static,
+        F: ServiceFactory<S> + 'static;
+
     async fn register_mocker<S, Mocker>(&self, mocker: Mocker) -> Result<ServiceRef<S>>
     where
         S: ActorService + 'static,
@@ -885,6 +937,26 @@ impl RegistryAsyncService for ServiceRef<RegistryService> {
         self.send(RegisterRequest::<S, F>::new()).await?
     }
 
+    async fn register_with_policy<S>(&self, policy: SupervisionPolicy) -> Result<ServiceRef<S>>
+    where
+        S: ActorService + ServiceFactory<S> + 'static,
+    {
+        self.send(RegisterRequest::<S, S>::new_with_policy(policy))
+            .await?
+    }
+
+    async fn register_by_factory_with_policy<S, F>(
+        &self,
+        policy: SupervisionPolicy,
+    ) -> Result<ServiceRef<S>>
+    where
+        S: ActorService + 'static,
+        F: ServiceFactory<S> + 'static,
+    {
+        self.send(RegisterRequest::<S, F>::new_with_policy(policy))
+            .await?
+    }
+
     async fn register_mocker<S, Mocker>(&self, mocker: Mocker) -> Result<ServiceRef<S>>
     where
         S: ActorService + 'static,
@@ -7,6 +7,8 @@ use futures::Stream;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::time::Duration;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum ServiceStatus {
     /// Actor thread and Service is started and running.
@@ -33,6 +35,79 @@ impl ServiceStatus {
 pub struct ServiceInfo {
     pub name: String,
     pub status: ServiceStatus,
+    #[serde(default)]
+    pub health: ServiceHealth,
+}
+
+/// Liveness of a service actor.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ServiceHealth {
+    /// Requests and events sent to the service by `ServiceRef` and not handled yet.
+    pub mailbox_depth: u64,
+    /// The local timestamp in milliseconds when the service handled its last message, `None` if never.
+    pub last_handled_time: Option<u64>,
+    /// Times the service is restarted by its supervision policy.
+    pub restart_count: u64,
+    /// Times the message handlers of the service panicked.
+    pub panic_count: u64,
+}
+
+/// How the registry handles a panic in the message or event handlers of a service.
+/// A service registered without a policy is not supervised, the panic takes down its actor thread.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SupervisionPolicy {
+    /// Drop the message which caused the panic, and keep the service running.
+    Ignore,
+    /// Stop the service, and start a new one after the backoff, which doubles on every restart.
+    /// The service is left stopped after `max_restarts`.
+    Restart {
+        max_restarts: u64,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    },
+    /// Stop the service, and broadcast a `ServiceEscalatedEvent` to shutdown the node.
+    Escalate,
+}
+
+impl SupervisionPolicy {
+    pub const DEFAULT_MAX_RESTARTS: u64 = 10;
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+    /// Restart the service with the default backoff.
+    pub fn restart() -> Self {
+        Self::Restart {
+            max_restarts: Self::DEFAULT_MAX_RESTARTS,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// The backoff before the restart after `restart_count` restarts, or `None` if the service should not restart.
+    pub(crate) fn restart_backoff(&self, restart_count: u64) -> Option<Duration> {
+        match self {
+            Self::Restart {
+                max_restarts,
+                initial_backoff,
+                max_backoff,
+            } if restart_count < *max_restarts => {
+                let factor = 1u32.checked_shl(restart_count as u32).unwrap_or(u32::MAX);
+                Some(
+                    initial_backoff
+                        .checked_mul(factor)
+                        .map_or(*max_backoff, |backoff| backoff.min(*max_backoff)),
+                )
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Broadcast when a service under the `SupervisionPolicy::Escalate` panicked, the node should shutdown.
+#[derive(Debug, Clone)]
+pub struct ServiceEscalatedEvent {
+    pub service_name: String,
+    pub reason: String,
 }
 
 #[derive(Clone, Debug)]
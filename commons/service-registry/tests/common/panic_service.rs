@@ -1,14 +1,53 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::Result;
 use starcoin_service_registry::{ActorService, ServiceContext, ServiceHandler, ServiceRequest};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Default, Clone)]
 pub struct PanicService {
     counter: u64,
 }
 
-impl ActorService for PanicService {}
+/// The intervals of the started `PanicService`s, shared by the registry.
+#[derive(Default, Clone)]
+pub struct RunningIntervals(Arc<AtomicUsize>);
+
+impl RunningIntervals {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Counted in `RunningIntervals` until the interval is dropped.
+struct IntervalGuard(RunningIntervals);
+
+impl IntervalGuard {
+    fn new(intervals: RunningIntervals) -> Self {
+        intervals.0.fetch_add(1, Ordering::SeqCst);
+        Self(intervals)
+    }
+}
+
+impl Drop for IntervalGuard {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ActorService for PanicService {
+    fn started(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
+        let intervals = ctx.get_shared_or_put(|| Ok(RunningIntervals::default()))?;
+        let guard = IntervalGuard::new(intervals);
+        ctx.run_interval(Duration::from_millis(10), move |_ctx| {
+            let _guard = &guard;
+        });
+        Ok(())
+    }
+}
 
 #[derive(Debug)]
 pub struct PingRequest;
@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use actix_rt::System;
-use common::panic_service::{PanicRequest, PanicService, PingRequest};
+use common::panic_service::{PanicRequest, PanicService, PingRequest, RunningIntervals};
+use futures::StreamExt;
 use futures_timer::Delay;
+use starcoin_service_registry::bus::{Bus, BusService};
 use starcoin_service_registry::ServiceStatus::Shutdown;
 use starcoin_service_registry::{
-    ActorService, RegistryAsyncService, RegistryService, ServiceStatus,
+    ActorService, RegistryAsyncService, RegistryService, ServiceEscalatedEvent, ServiceInfo,
+    ServiceRef, ServiceStatus, SupervisionPolicy,
 };
 use std::time::Duration;
 
@@ -52,3 +55,101 @@ fn test_service_panic() {
         System::current().stop();
     });
 }
+
+async fn panic_service_info(registry: &ServiceRef<RegistryService>) -> ServiceInfo {
+    registry
+        .list_service()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|info| info.name == PanicService::service_name())
+        .unwrap()
+}
+
+#[stest::test]
+async fn test_service_panic_ignore() {
+    let registry = RegistryService::launch();
+    let service_ref = registry
+        .register_with_policy::<PanicService>(SupervisionPolicy::Ignore)
+        .await
+        .unwrap();
+    service_ref.send(PingRequest).await.unwrap();
+    assert!(service_ref.send(PanicRequest).await.is_err());
+
+    // the service keeps its state after the panic.
+    let ping_count = service_ref.send(PingRequest).await.unwrap();
+    assert_eq!(2, ping_count);
+
+    let info = panic_service_info(&registry).await;
+    assert_eq!(info.status, ServiceStatus::Started);
+    assert_eq!(info.health.panic_count, 1);
+    assert_eq!(info.health.restart_count, 0);
+    assert_eq!(info.health.mailbox_depth, 0);
+    assert!(info.health.last_handled_time.is_some());
+    registry.shutdown_system().await.unwrap();
+}
+
+#[stest::test]
+async fn test_service_panic_restart() {
+    let registry = RegistryService::launch();
+    let service_ref = registry
+        .register_with_policy::<PanicService>(SupervisionPolicy::Restart {
+            max_restarts: 1,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(100),
+        })
+        .await
+        .unwrap();
+    service_ref.send(PingRequest).await.unwrap();
+    assert!(service_ref.send(PanicRequest).await.is_err());
+
+    // stopped until the backoff elapsed.
+    assert!(service_ref.send(PingRequest).await.is_err());
+    Delay::new(Duration::from_millis(300)).await;
+
+    // a new service is created by the restart.
+    let ping_count = service_ref.send(PingRequest).await.unwrap();
+    assert_eq!(1, ping_count);
+    let info = panic_service_info(&registry).await;
+    assert_eq!(info.status, ServiceStatus::Started);
+    assert_eq!(info.health.panic_count, 1);
+    assert_eq!(info.health.restart_count, 1);
+    // the interval of the failed service is cancelled.
+    let intervals = registry.get_shared::<RunningIntervals>().await.unwrap();
+    assert_eq!(intervals.count(), 1);
+
+    // left stopped after max restarts.
+    assert!(service_ref.send(PanicRequest).await.is_err());
+    Delay::new(Duration::from_millis(300)).await;
+    assert!(service_ref.send(PingRequest).await.is_err());
+    let info = panic_service_info(&registry).await;
+    assert_eq!(info.status, ServiceStatus::Stopped);
+    assert_eq!(info.health.panic_count, 2);
+    assert_eq!(info.health.restart_count, 1);
+    assert_eq!(intervals.count(), 0);
+    registry.shutdown_system().await.unwrap();
+}
+
+#[stest::test]
+async fn test_service_panic_escalate() {
+    let registry = RegistryService::launch();
+    let bus = registry.service_ref::<BusService>().await.unwrap();
+    let mut escalated_events = bus.channel::<ServiceEscalatedEvent>().await.unwrap();
+    let service_ref = registry
+        .register_with_policy::<PanicService>(SupervisionPolicy::Escalate)
+        .await
+        .unwrap();
+    service_ref.send(PingRequest).await.unwrap();
+    assert!(service_ref.send(PanicRequest).await.is_err());
+
+    // the panic is escalated, and the service is left stopped.
+    let event = escalated_events.next().await.unwrap();
+    assert_eq!(event.service_name, PanicService::service_name());
+    assert!(event.reason.contains("Panic by request."));
+    assert!(service_ref.send(PingRequest).await.is_err());
+    let info = panic_service_info(&registry).await;
+    assert_eq!(info.status, ServiceStatus::Stopped);
+    assert_eq!(info.health.panic_count, 1);
+    assert_eq!(info.health.restart_count, 0);
+    registry.shutdown_system().await.unwrap();
+}
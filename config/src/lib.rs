@@ -35,6 +35,7 @@ mod logger_config;
 mod metrics_config;
 mod miner_config;
 mod network_config;
+mod rpc_auth;
 mod rpc_config;
 mod storage_config;
 mod stratum_config;
@@ -59,8 +60,11 @@ pub use genesis_config::{
 };
 pub use logger_config::LoggerConfig;
 pub use metrics_config::MetricsConfig;
-pub use miner_config::{MinerClientConfig, MinerConfig};
-pub use network_config::{NetworkConfig, NetworkRpcQuotaConfiguration};
+pub use miner_config::{BlockPackingStrategyType, MinerClientConfig, MinerConfig};
+pub use network_config::{
+    NetworkBandwidthConfiguration, NetworkConfig, NetworkRpcQuotaConfiguration,
+};
+pub use rpc_auth::{RpcCredential, RpcCredentials};
 pub use rpc_config::{
     ApiQuotaConfiguration, HttpConfiguration, IpcConfiguration, RpcConfig, TcpConfiguration,
     WsConfiguration,
@@ -68,7 +72,7 @@ pub use rpc_config::{
 pub use starcoin_crypto::ed25519::genesis_key_pair;
 pub use starcoin_time_service::{MockTimeService, RealTimeService, TimeService};
 pub use storage_config::{RocksdbConfig, StorageConfig, DEFAULT_CACHE_SIZE};
-pub use txpool_config::TxPoolConfig;
+pub use txpool_config::{TxPoolConfig, TxPoolJournalMode};
 
 pub static G_CRATE_VERSION: &str = clap::crate_version!();
 pub static G_GIT_VERSION: &str = git_version!(
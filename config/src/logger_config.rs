@@ -5,6 +5,7 @@ use crate::{BaseConfig, ConfigModule, StarcoinOpt};
 use anyhow::Result;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
+use starcoin_logger::tracer::TraceExporterConfig;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -33,6 +34,22 @@ pub struct LoggerConfig {
     #[clap(name = "logger-max-backup", long)]
     pub max_backup: Option<u32>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "logger-trace-otlp-endpoint",
+        long,
+        help = "export the tracing spans to the OTLP/HTTP endpoint, such as http://127.0.0.1:4318"
+    )]
+    pub trace_otlp_endpoint: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "logger-trace-file",
+        long,
+        help = "export the tracing spans to the JSON file, the relative path is under the data dir"
+    )]
+    pub trace_file: Option<PathBuf>,
+
     #[clap(skip)]
     #[serde(skip)]
     base: Option<Arc<BaseConfig>>,
@@ -75,6 +92,21 @@ impl LoggerConfig {
         })
     }
 
+    /// The exporter of the tracing spans, the OTLP endpoint is preferred if both are set.
+    /// `None` to disable tracing.
+    pub fn trace_exporter(&self) -> Option<TraceExporterConfig> {
+        if let Some(endpoint) = self.trace_otlp_endpoint.as_ref() {
+            return Some(TraceExporterConfig::Otlp(endpoint.clone()));
+        }
+        self.trace_file.as_ref().map(|trace_file| {
+            TraceExporterConfig::File(if trace_file.is_absolute() {
+                trace_file.clone()
+            } else {
+                self.base().data_dir.join(trace_file)
+            })
+        })
+    }
+
     pub fn max_backup(&self) -> u32 {
         self.max_backup.unwrap_or_else(|| {
             let base = self.base();
@@ -104,6 +136,12 @@ impl ConfigModule for LoggerConfig {
         if opt.logger.max_backup.is_some() {
             self.max_backup = opt.logger.max_backup;
         }
+        if opt.logger.trace_otlp_endpoint.is_some() {
+            self.trace_otlp_endpoint = opt.logger.trace_otlp_endpoint.clone();
+        }
+        if opt.logger.trace_file.is_some() {
+            self.trace_file = opt.logger.trace_file.clone();
+        }
         Ok(())
     }
 }
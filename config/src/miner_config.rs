@@ -2,11 +2,72 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{BaseConfig, ConfigModule, StarcoinOpt};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use starcoin_types::account_address::AccountAddress;
+use std::str::FromStr;
 use std::sync::Arc;
 
+/// How the block builder orders and selects the pending transactions of the txpool.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlockPackingStrategyType {
+    /// Pack the transactions in the txpool order, stop at the first one exceeding the block gas limit.
+    Pool,
+    /// Pack the transactions of the highest gas price first, and keep filling the block
+    /// with the following ones after a transaction exceeds the block gas limit.
+    HighestFee,
+}
+
+impl Default for BlockPackingStrategyType {
+    fn default() -> Self {
+        Self::Pool
+    }
+}
+
+impl std::fmt::Display for BlockPackingStrategyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Pool => "pool",
+            Self::HighestFee => "highest-fee",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for BlockPackingStrategyType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let strategy = match s {
+            "pool" => Self::Pool,
+            "highest-fee" => Self::HighestFee,
+            _ => bail!("invalid block packing strategy: {}", s),
+        };
+        Ok(strategy)
+    }
+}
+
+impl Serialize for BlockPackingStrategyType {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockPackingStrategyType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Parser)]
 #[serde(deny_unknown_fields)]
 pub struct MinerConfig {
@@ -31,6 +92,22 @@ pub struct MinerConfig {
     /// Miner client thread number, not work for dev network, default is 1
     pub miner_thread: Option<u16>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "miner-packing-strategy")]
+    /// How to order and select the txpool transactions for a block, pool or highest-fee. default is pool.
+    pub packing_strategy: Option<BlockPackingStrategyType>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "miner-max-txns-per-sender")]
+    /// Max number of transactions of a sender packed in a block, no limit if absent.
+    pub max_txns_per_sender: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "miner-priority-addresses", use_value_delimiter = true)]
+    /// The transactions of these senders are packed before others and are not limited by max_txns_per_sender,
+    /// multi addresses should use ',' as delimiter.
+    pub priority_addresses: Option<Vec<AccountAddress>>,
+
     #[serde(skip)]
     #[clap(skip)]
     base: Option<Arc<BaseConfig>>,
@@ -49,6 +126,15 @@ impl MinerConfig {
         self.disable_mint_empty_block
             .unwrap_or_else(|| self.base().net().is_dev())
     }
+    pub fn packing_strategy(&self) -> BlockPackingStrategyType {
+        self.packing_strategy.unwrap_or_default()
+    }
+    pub fn max_txns_per_sender(&self) -> Option<u64> {
+        self.max_txns_per_sender
+    }
+    pub fn priority_addresses(&self) -> Vec<AccountAddress> {
+        self.priority_addresses.clone().unwrap_or_default()
+    }
     pub fn miner_client_config(&self) -> Option<MinerClientConfig> {
         if self.disable_miner_client() {
             return None;
@@ -102,6 +188,15 @@ impl ConfigModule for MinerConfig {
         if opt.miner.block_gas_limit.is_some() {
             self.block_gas_limit = opt.miner.block_gas_limit;
         }
+        if opt.miner.packing_strategy.is_some() {
+            self.packing_strategy = opt.miner.packing_strategy;
+        }
+        if opt.miner.max_txns_per_sender.is_some() {
+            self.max_txns_per_sender = opt.miner.max_txns_per_sender;
+        }
+        if opt.miner.priority_addresses.is_some() {
+            self.priority_addresses = opt.miner.priority_addresses.clone();
+        }
 
         Ok(())
     }
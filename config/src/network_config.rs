@@ -9,11 +9,12 @@ use crate::{
 use anyhow::Result;
 use clap::Parser;
 use network_api::messages::{NotificationMessage, BLOCK_PROTOCOL_NAME};
+use network_p2p_types::bandwidth::DEFAULT_SYNC_BANDWIDTH_SHARE;
 use network_p2p_types::peer_id::PeerId;
 use network_p2p_types::{
     is_memory_addr, memory_addr,
     multiaddr::{Multiaddr, Protocol},
-    MultiaddrWithPeerId,
+    BandwidthLimits, MultiaddrWithPeerId,
 };
 use once_cell::sync::Lazy;
 use rand::seq::SliceRandom;
@@ -31,6 +32,7 @@ use std::sync::Arc;
 
 pub static G_DEFAULT_NETWORK_PORT: u16 = 9840;
 static G_NETWORK_KEY_FILE: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("network_key"));
+static G_PEER_STORE_FILE: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("peers.json"));
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize, Parser)]
 pub struct NetworkRpcQuotaConfiguration {
@@ -117,6 +119,87 @@ impl NetworkRpcQuotaConfiguration {
         Ok(())
     }
 }
+/// Upload and download rate limits of the p2p network, in bytes per second.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize, Parser)]
+pub struct NetworkBandwidthConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "p2p-peer-upload-rate",
+        long,
+        help = "max upload bytes per second to a peer, unlimited by default"
+    )]
+    pub peer_upload_rate: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "p2p-peer-download-rate",
+        long,
+        help = "max download bytes per second from a peer, unlimited by default"
+    )]
+    pub peer_download_rate: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "p2p-upload-rate",
+        long,
+        help = "max upload bytes per second to all the peers, unlimited by default"
+    )]
+    pub upload_rate: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "p2p-download-rate",
+        long,
+        help = "max download bytes per second from all the peers, unlimited by default"
+    )]
+    pub download_rate: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "p2p-sync-bandwidth-share",
+        long,
+        help = "percent of the upload/download rate the sync can use, the rest is kept for the block and txn gossip. Default 80"
+    )]
+    pub sync_bandwidth_share: Option<u8>,
+}
+
+impl NetworkBandwidthConfiguration {
+    pub fn sync_bandwidth_share(&self) -> u8 {
+        self.sync_bandwidth_share
+            .unwrap_or(DEFAULT_SYNC_BANDWIDTH_SHARE)
+            .min(100)
+    }
+
+    pub fn limits(&self) -> BandwidthLimits {
+        BandwidthLimits {
+            peer_upload: self.peer_upload_rate,
+            peer_download: self.peer_download_rate,
+            global_upload: self.upload_rate,
+            global_download: self.download_rate,
+            sync_share: self.sync_bandwidth_share(),
+        }
+    }
+
+    pub fn merge(&mut self, o: &Self) -> Result<()> {
+        if o.peer_upload_rate.is_some() {
+            self.peer_upload_rate = o.peer_upload_rate;
+        }
+        if o.peer_download_rate.is_some() {
+            self.peer_download_rate = o.peer_download_rate;
+        }
+        if o.upload_rate.is_some() {
+            self.upload_rate = o.upload_rate;
+        }
+        if o.download_rate.is_some() {
+            self.download_rate = o.download_rate;
+        }
+        if o.sync_bandwidth_share.is_some() {
+            self.sync_bandwidth_share = o.sync_bandwidth_share;
+        }
+        Ok(())
+    }
+}
+
 //for avoid conflict between seed vec and subcommand, so define a custom type to parse seeds.
 //https://github.com/TeXitoi/clap/issues/367
 #[derive(Default, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -186,6 +269,23 @@ pub struct NetworkConfig {
     /// P2P network seed, multi seed should use ',' as delimiter.
     pub seeds: Seeds,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "peer-store-file", parse(from_os_str))]
+    /// The file to persist the known peers, their reputation and bans, default is peers.json under the data dir.
+    pub peer_store_file: Option<PathBuf>,
+
+    #[serde(skip_serializing_if = "Seeds::is_empty")]
+    #[serde(default)]
+    #[clap(long = "reserved-peers", default_value = "")]
+    /// P2P reserved peers, always keep connected with them, they are not limited by the max incoming/outgoing peers
+    /// and not disconnected by the reputation. Multi peers should use ',' as delimiter.
+    pub reserved_peers: Seeds,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "reserved-only")]
+    /// Only connect with the reserved peers, reject the other peers. Default false.
+    pub reserved_only: Option<bool>,
+
     /// Enable peer discovery on local networks.
     /// By default this option is `false`. only support cli option.
     #[serde(skip)]
@@ -201,6 +301,10 @@ pub struct NetworkConfig {
     #[clap(flatten)]
     pub network_rpc_quotas: NetworkRpcQuotaConfiguration,
 
+    #[serde(default)]
+    #[clap(flatten)]
+    pub network_bandwidth: NetworkBandwidthConfiguration,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(long)]
     /// min peers to propagate new block and new transactions. Default 8.
@@ -211,6 +315,12 @@ pub struct NetworkConfig {
     ///max peers to propagate new block and new transactions. Default 128.
     max_peers_to_propagate: Option<u32>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long)]
+    /// max peers support the block announcement to push the new compact block to, the reserved peers come first,
+    /// then the peers relay the most recent new blocks first, then the random peers. The other peers get the block header announcement. Default 4.
+    max_fast_relay_peers: Option<u32>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(long)]
     ///max count for incoming peers. Default 25.
@@ -294,6 +404,20 @@ impl NetworkConfig {
         MultiaddrWithPeerId::new(host, self.self_peer_id().into())
     }
 
+    pub fn reserved_peers(&self) -> Vec<MultiaddrWithPeerId> {
+        let self_peer_id = self.self_peer_id();
+        self.reserved_peers
+            .clone()
+            .into_vec()
+            .into_iter()
+            .filter(|node| &node.peer_id != self_peer_id.origin())
+            .collect()
+    }
+
+    pub fn reserved_only(&self) -> bool {
+        self.reserved_only.unwrap_or(false)
+    }
+
     pub fn discover_local(&self) -> bool {
         self.discover_local.unwrap_or(false)
     }
@@ -314,6 +438,10 @@ impl NetworkConfig {
         self.min_peers_to_propagate.unwrap_or(8)
     }
 
+    pub fn max_fast_relay_peers(&self) -> u32 {
+        self.max_fast_relay_peers.unwrap_or(4)
+    }
+
     pub fn max_incoming_peers(&self) -> u32 {
         self.max_incoming_peers.unwrap_or(25)
     }
@@ -335,6 +463,15 @@ impl NetworkConfig {
         }
     }
 
+    pub fn peer_store_file(&self) -> PathBuf {
+        let path = self.peer_store_file.as_ref().unwrap_or(&G_PEER_STORE_FILE);
+        if path.is_absolute() {
+            path.clone()
+        } else {
+            self.base().data_dir().join(path.as_path())
+        }
+    }
+
     /// node key loader step:
     /// 1. if node_key is Some, directly decode the key.
     /// 2. try load node key from node_key_file
@@ -403,12 +540,23 @@ impl ConfigModule for NetworkConfig {
 
         self.seeds.merge(&opt.network.seeds);
 
+        if opt.network.peer_store_file.is_some() {
+            self.peer_store_file = opt.network.peer_store_file.clone();
+        }
+
+        self.reserved_peers.merge(&opt.network.reserved_peers);
+        if opt.network.reserved_only.is_some() {
+            self.reserved_only = opt.network.reserved_only;
+        }
+
         if opt.network.disable_seed {
             self.disable_seed = opt.network.disable_seed;
         }
 
         self.network_rpc_quotas
             .merge(&opt.network.network_rpc_quotas)?;
+        self.network_bandwidth
+            .merge(&opt.network.network_bandwidth)?;
 
         if opt.network.node_name.is_some() {
             self.node_name = opt.network.node_name.clone();
@@ -431,6 +579,9 @@ impl ConfigModule for NetworkConfig {
         if let Some(m) = opt.network.min_peers_to_propagate {
             self.min_peers_to_propagate = Some(m);
         }
+        if let Some(m) = opt.network.max_fast_relay_peers {
+            self.max_fast_relay_peers = Some(m);
+        }
         if opt.network.discover_local.is_some() {
             self.discover_local = opt.network.discover_local;
         }
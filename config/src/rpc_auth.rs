@@ -0,0 +1,95 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::helper::load_config;
+use crate::{ApiQuotaConfiguration, ApiSet};
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A json rpc credential, the caller is identified by a bearer token,
+/// or by a JWT signed with HMAC-SHA256 (HS256).
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcCredential {
+    /// Name of the credential, used as the user of the rpc calls.
+    pub name: String,
+    /// Bearer token of the credential.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Hex encoded secret to verify the JWT signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_secret: Option<String>,
+    /// Apis the credential can call, it is limited by the apis of the transport too.
+    /// Default is the unsafe context apis.
+    #[serde(default)]
+    pub apis: ApiSet,
+    /// Quotas of the credential, they apply to the calls of the credential with the node quotas.
+    /// The user quotas are shared by all the callers of the credential.
+    #[serde(default)]
+    pub api_quotas: ApiQuotaConfiguration,
+}
+
+impl RpcCredential {
+    pub fn jwt_secret(&self) -> Result<Option<Vec<u8>>> {
+        Ok(match self.jwt_secret.as_ref() {
+            Some(secret) => Some(hex::decode(secret.trim_start_matches("0x"))?),
+            None => None,
+        })
+    }
+}
+
+/// The credentials of json rpc, loaded from `RpcConfig::auth_file`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcCredentials {
+    #[serde(default)]
+    pub credentials: Vec<RpcCredential>,
+}
+
+impl RpcCredentials {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let credentials: Self = load_config(path)?;
+        credentials.validate()?;
+        Ok(credentials)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        let mut tokens = HashSet::new();
+        for credential in &self.credentials {
+            ensure!(
+                names.insert(credential.name.as_str()),
+                "Duplicate rpc credential name: {}",
+                credential.name
+            );
+            match (credential.token.as_ref(), credential.jwt_secret()?) {
+                (Some(token), None) => {
+                    ensure!(
+                        !token.is_empty(),
+                        "Token of rpc credential {} is empty",
+                        credential.name
+                    );
+                    ensure!(
+                        tokens.insert(token.as_str()),
+                        "Duplicate token of rpc credential {}",
+                        credential.name
+                    );
+                }
+                (None, Some(secret)) => {
+                    ensure!(
+                        !secret.is_empty(),
+                        "Jwt secret of rpc credential {} is empty",
+                        credential.name
+                    );
+                }
+                _ => bail!(
+                    "Rpc credential {} should have either a token or a jwt secret",
+                    credential.name
+                ),
+            }
+        }
+        Ok(())
+    }
+}
@@ -382,6 +382,15 @@ pub struct RpcConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(long = "query-max-txn-info-range")]
     pub txn_info_query_max_range: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(long = "rpc-auth-file", parse(from_os_str))]
+    /// Credential file of http and websocket rpc, relative path is in data_dir.
+    /// When set, the calls without a valid credential are rejected,
+    /// and the file is reloaded when it is changed.
+    /// Http clients pass the credential by the `Authorization: Bearer <token>` header,
+    /// websocket clients by the `Sec-WebSocket-Protocol: bearer.<token>` header.
+    pub auth_file: Option<PathBuf>,
 }
 
 #[derive(Clone, Eq, PartialEq)]
@@ -452,6 +461,16 @@ impl RpcConfig {
             .unwrap_or(DEFAULT_TXN_INFO_QUEYR_MAX_RANGE)
     }
 
+    pub fn auth_file(&self) -> Option<PathBuf> {
+        self.auth_file.as_ref().map(|path| {
+            if path.is_absolute() {
+                path.clone()
+            } else {
+                self.base().data_dir().join(path)
+            }
+        })
+    }
+
     fn base(&self) -> &BaseConfig {
         self.base.as_ref().expect("Config should init.")
     }
@@ -527,6 +546,9 @@ impl ConfigModule for RpcConfig {
         if opt.rpc.txn_info_query_max_range.is_some() {
             self.txn_info_query_max_range = opt.rpc.txn_info_query_max_range;
         }
+        if opt.rpc.auth_file.is_some() {
+            self.auth_file = opt.rpc.auth_file.clone();
+        }
         self.http.merge(&opt.rpc.http)?;
         self.tcp.merge(&opt.rpc.tcp)?;
         self.ws.merge(&opt.rpc.ws)?;
@@ -539,6 +561,9 @@ impl ConfigModule for RpcConfig {
         info!("TCP rpc address: {:?}", self.get_tcp_address());
         info!("Websocket rpc address: {:?}", self.get_ws_address());
         info!("Ipc file path: {:?}", self.get_ipc_file());
+        if let Some(auth_file) = self.auth_file() {
+            info!("Rpc auth file path: {:?}", auth_file);
+        }
 
         Ok(())
     }
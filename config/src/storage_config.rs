@@ -66,7 +66,10 @@ impl Default for RocksdbConfig {
 }
 
 static G_DEFAULT_DB_DIR: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("starcoindb/db"));
+static G_DEFAULT_BACKUP_DIR: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("backup"));
 pub const DEFAULT_CACHE_SIZE: usize = 20000;
+/// The state of blocks within the reorg window must be kept, so keep at least this many blocks.
+pub const MIN_STATE_PRUNE_KEEP_BLOCKS: u64 = 1000;
 
 #[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize, Parser)]
 #[serde(deny_unknown_fields)]
@@ -102,6 +105,31 @@ pub struct StorageConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[clap(name = "rocksdb-bytes-per-sync", long, help = "rocksdb bytes per sync")]
     pub bytes_per_sync: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "event-index",
+        long,
+        help = "maintain the secondary event index by type tag, event key and address, default false"
+    )]
+    pub event_index: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "state-prune-keep-blocks",
+        long,
+        help = "prune the state older than the last N blocks, at least 1000, default is archive mode which keeps all state"
+    )]
+    pub state_prune_keep_blocks: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "backup-root",
+        long,
+        help = "the root dir of the db backups created by the node manager api, the backups can only be created under it, default is backup in the data dir",
+        parse(from_os_str)
+    )]
+    pub backup_root: Option<PathBuf>,
 }
 
 impl StorageConfig {
@@ -129,6 +157,24 @@ impl StorageConfig {
     pub fn cache_size(&self) -> usize {
         self.cache_size.unwrap_or(DEFAULT_CACHE_SIZE)
     }
+
+    pub fn event_index(&self) -> bool {
+        self.event_index.unwrap_or(false)
+    }
+
+    /// The root dir of the db backups, a relative dir is relative to the data dir.
+    pub fn backup_root(&self) -> PathBuf {
+        match self.backup_root.as_ref() {
+            Some(backup_root) => self.base().data_dir().join(backup_root),
+            None => self.base().data_dir().join(G_DEFAULT_BACKUP_DIR.as_path()),
+        }
+    }
+
+    /// Return None in archive mode.
+    pub fn state_prune_keep_blocks(&self) -> Option<u64> {
+        self.state_prune_keep_blocks
+            .map(|keep_blocks| keep_blocks.max(MIN_STATE_PRUNE_KEEP_BLOCKS))
+    }
 }
 
 impl ConfigModule for StorageConfig {
@@ -149,6 +195,15 @@ impl ConfigModule for StorageConfig {
         if opt.storage.wal_bytes_per_sync.is_some() {
             self.wal_bytes_per_sync = opt.storage.wal_bytes_per_sync;
         }
+        if opt.storage.event_index.is_some() {
+            self.event_index = opt.storage.event_index;
+        }
+        if opt.storage.state_prune_keep_blocks.is_some() {
+            self.state_prune_keep_blocks = opt.storage.state_prune_keep_blocks;
+        }
+        if opt.storage.backup_root.is_some() {
+            self.backup_root = opt.storage.backup_root.clone();
+        }
         Ok(())
     }
 }
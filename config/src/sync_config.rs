@@ -8,6 +8,8 @@ use network_api::PeerStrategy;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+const DEFAULT_FAST_SYNC_PIVOT_DISTANCE: u64 = 500;
+
 #[derive(Clone, Default, Debug, Deserialize, PartialEq, Eq, Serialize, Parser)]
 #[serde(deny_unknown_fields)]
 pub struct SyncConfig {
@@ -28,6 +30,25 @@ pub struct SyncConfig {
         help = "max retry times once sync block failed, default 15."
     )]
     max_retry_times: Option<u64>,
+
+    /// A new node downloads the state of a pivot block from peers instead of executing all blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "fast-sync",
+        long,
+        help = "sync the state of a pivot block first when the node starts from genesis, default false."
+    )]
+    fast_sync: Option<bool>,
+
+    /// The pivot of fast sync is the start block of the epoch which contains block `target - distance`,
+    /// peers must keep the state of the pivot, so the distance should be less than their prune window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(
+        name = "fast-sync-pivot-distance",
+        long,
+        help = "distance between the sync target and the fast sync pivot, default 500."
+    )]
+    fast_sync_pivot_distance: Option<u64>,
 }
 
 impl SyncConfig {
@@ -38,6 +59,16 @@ impl SyncConfig {
     pub fn max_retry_times(&self) -> u64 {
         self.max_retry_times.unwrap_or(15)
     }
+
+    pub fn fast_sync(&self) -> bool {
+        self.fast_sync.unwrap_or(false)
+    }
+
+    pub fn fast_sync_pivot_distance(&self) -> u64 {
+        self.fast_sync_pivot_distance
+            .unwrap_or(DEFAULT_FAST_SYNC_PIVOT_DISTANCE)
+            .max(1)
+    }
 }
 
 impl ConfigModule for SyncConfig {
@@ -50,6 +81,14 @@ impl ConfigModule for SyncConfig {
             self.max_retry_times = opt.sync.max_retry_times;
         }
 
+        if opt.sync.fast_sync.is_some() {
+            self.fast_sync = opt.sync.fast_sync;
+        }
+
+        if opt.sync.fast_sync_pivot_distance.is_some() {
+            self.fast_sync_pivot_distance = opt.sync.fast_sync_pivot_distance;
+        }
+
         Ok(())
     }
 }
@@ -184,3 +184,44 @@ fn test_check_method_in_api_sets() {
     assert!(!ApiSet::UnsafeContext.check_rpc_method("unknown"));
     assert!(!ApiSet::UnsafeContext.check_rpc_method(""));
 }
+
+#[test]
+fn test_rpc_credentials() -> Result<()> {
+    let temp_path = temp_dir();
+    let auth_file = temp_path.path().join("rpc_auth.toml");
+    fs::write(
+        &auth_file,
+        r#"
+[[credentials]]
+name = "team-a"
+token = "token-a"
+apis = "chain,state"
+[credentials.api_quotas]
+default_user_api_quota = "100/s"
+
+[[credentials]]
+name = "team-b"
+jwt_secret = "0x736563726574"
+"#,
+    )?;
+    let credentials = RpcCredentials::load(&auth_file)?;
+    assert_eq!(credentials.credentials.len(), 2);
+    let team_a = &credentials.credentials[0];
+    assert!(team_a.apis.check_rpc_method("chain.info"));
+    assert!(!team_a.apis.check_rpc_method("txpool.submit_transaction"));
+    assert_eq!(
+        team_a.api_quotas.default_user_api_quota().to_string(),
+        "100/s"
+    );
+    let team_b = &credentials.credentials[1];
+    assert_eq!(team_b.apis, ApiSet::UnsafeContext);
+    assert_eq!(team_b.jwt_secret()?, Some(b"secret".to_vec()));
+
+    let mut invalid = credentials.clone();
+    invalid.credentials[1].name = "team-a".to_string();
+    assert!(invalid.validate().is_err());
+    let mut invalid = credentials;
+    invalid.credentials[1].token = Some("token-b".to_string());
+    assert!(invalid.validate().is_err());
+    Ok(())
+}
@@ -2,13 +2,80 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{BaseConfig, ConfigModule, StarcoinOpt};
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use starcoin_system::get_free_mem_size;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 
 pub const DEFAULT_MEM_SIZE: u64 = 128 * 1024 * 1024; // 128M
+pub const DEFAULT_JOURNAL_MAX_AGE: u64 = 3 * 60 * 60; // 3 hours
+pub const DEFAULT_TXN_FINALITY_CONFIRMATIONS: u64 = 12;
+const JOURNAL_FILE_NAME: &str = "txpool.journal";
+
+/// Which transactions of the pool are written to the journal, and re-imported after restart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TxPoolJournalMode {
+    /// Do not journal any transaction.
+    Disable,
+    /// Journal the transactions submitted to this node, not the ones received from peers.
+    Local,
+    /// Journal all transactions accepted by the pool.
+    All,
+}
+
+impl Default for TxPoolJournalMode {
+    fn default() -> Self {
+        Self::Disable
+    }
+}
+
+impl std::fmt::Display for TxPoolJournalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Disable => "disable",
+            Self::Local => "local",
+            Self::All => "all",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for TxPoolJournalMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mode = match s {
+            "disable" => Self::Disable,
+            "local" => Self::Local,
+            "all" => Self::All,
+            _ => bail!("invalid txpool journal mode: {}", s),
+        };
+        Ok(mode)
+    }
+}
+
+impl Serialize for TxPoolJournalMode {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TxPoolJournalMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(D::Error::custom)
+    }
+}
 
 #[derive(Default, Clone, Debug, Eq, PartialEq, Deserialize, Serialize, Parser)]
 #[serde(deny_unknown_fields)]
@@ -37,6 +104,25 @@ pub struct TxPoolConfig {
     #[clap(name = "txpool-min-gas-price", long)]
     /// reject transaction whose gas_price is less than the min_gas_price. default to 1.
     min_gas_price: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(name = "txpool-journal-mode", long)]
+    /// which transactions are journaled and re-imported after restart, disable, local or all. default to disable.
+    journal_mode: Option<TxPoolJournalMode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(name = "txpool-journal-max-age", long)]
+    /// max age(s) of journaled transactions, older ones are not re-imported. default to 10800.
+    journal_max_age: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[clap(name = "txpool-txn-finality-confirmations", long)]
+    /// number of main chain blocks, including the txn's block, to report a txn as finalized. default to 12.
+    txn_finality_confirmations: Option<u64>,
+
+    #[serde(skip)]
+    #[clap(skip)]
+    data_dir: Option<PathBuf>,
 }
 
 impl TxPoolConfig {
@@ -68,10 +154,34 @@ impl TxPoolConfig {
     pub fn min_gas_price(&self) -> u64 {
         self.min_gas_price.unwrap_or(1)
     }
+    pub fn set_journal_mode(&mut self, journal_mode: TxPoolJournalMode) {
+        self.journal_mode = Some(journal_mode);
+    }
+    pub fn journal_mode(&self) -> TxPoolJournalMode {
+        self.journal_mode.unwrap_or_default()
+    }
+    pub fn journal_max_age(&self) -> u64 {
+        self.journal_max_age.unwrap_or(DEFAULT_JOURNAL_MAX_AGE)
+    }
+    pub fn txn_finality_confirmations(&self) -> u64 {
+        self.txn_finality_confirmations
+            .unwrap_or(DEFAULT_TXN_FINALITY_CONFIRMATIONS)
+            .max(1)
+    }
+    /// The journal file in the data dir, None if the journal is disabled or the config is not init.
+    pub fn journal_path(&self) -> Option<PathBuf> {
+        if self.journal_mode() == TxPoolJournalMode::Disable {
+            return None;
+        }
+        self.data_dir
+            .as_ref()
+            .map(|data_dir| data_dir.join(JOURNAL_FILE_NAME))
+    }
 }
 
 impl ConfigModule for TxPoolConfig {
-    fn merge_with_opt(&mut self, opt: &StarcoinOpt, _base: Arc<BaseConfig>) -> Result<()> {
+    fn merge_with_opt(&mut self, opt: &StarcoinOpt, base: Arc<BaseConfig>) -> Result<()> {
+        self.data_dir = Some(base.data_dir().to_path_buf());
         let txpool_opt = &opt.txpool;
         if let Some(m) = txpool_opt.max_mem_usage.as_ref() {
             self.max_mem_usage = Some(*m);
@@ -88,6 +198,15 @@ impl ConfigModule for TxPoolConfig {
         if let Some(m) = txpool_opt.min_gas_price.as_ref() {
             self.min_gas_price = Some(*m);
         }
+        if let Some(m) = txpool_opt.journal_mode.as_ref() {
+            self.journal_mode = Some(*m);
+        }
+        if let Some(m) = txpool_opt.journal_max_age.as_ref() {
+            self.journal_max_age = Some(*m);
+        }
+        if let Some(m) = txpool_opt.txn_finality_confirmations.as_ref() {
+            self.txn_finality_confirmations = Some(*m);
+        }
         Ok(())
     }
 }
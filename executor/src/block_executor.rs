@@ -7,6 +7,7 @@ use log::info;
 use serde::{Deserialize, Serialize};
 use starcoin_crypto::HashValue;
 use starcoin_force_upgrade::ForceUpgrade;
+use starcoin_logger::tracer::Span;
 use starcoin_state_api::{ChainStateReader, ChainStateWriter};
 use starcoin_types::account::DEFAULT_EXPIRATION_TIME;
 use starcoin_types::error::BlockExecutorError;
@@ -56,6 +57,7 @@ pub fn block_execute<S: ChainStateReader + ChainStateWriter>(
     block_gas_limit: u64,
     vm_metrics: Option<VMMetrics>,
 ) -> ExecutorResult<BlockExecutedData> {
+    let _span = Span::enter("executor.block_execute").with("txn_count", txns.len());
     let txn_outputs = execute_block_transactions(
         chain_state,
         txns.clone(),
@@ -12,7 +12,7 @@ use starcoin_transaction_builder::{
 };
 use starcoin_types::account::peer_to_peer_txn;
 use starcoin_types::identifier::Identifier;
-use starcoin_types::language_storage::ModuleId;
+use starcoin_types::language_storage::{ModuleId, TypeTag};
 use starcoin_types::transaction::{RawUserTransaction, ScriptFunction, TransactionArgument};
 use starcoin_types::{
     account_config, block_metadata::BlockMetadata, transaction::Transaction,
@@ -40,7 +40,10 @@ use test_helper::executor::{
 use starcoin_state_api::StateReaderExt;
 use starcoin_types::account::Account;
 use starcoin_types::account_config::G_STC_TOKEN_CODE;
-use starcoin_vm_runtime::starcoin_vm::{chunk_block_transactions, StarcoinVM};
+use starcoin_vm_runtime::data_cache::{AsMoveResolver, StateViewCache};
+use starcoin_vm_runtime::starcoin_vm::{
+    chunk_block_transactions, ExecutionRecordOption, StarcoinVM,
+};
 use starcoin_vm_types::account_config::core_code_address;
 use starcoin_vm_types::state_store::state_key::StateKey;
 use test_helper::txn::create_account_txn_sent_as_association;
@@ -712,6 +715,80 @@ fn test_execute_real_txn_with_starcoin_vm() -> Result<()> {
     Ok(())
 }
 
+#[stest::test]
+fn test_trace_transaction() -> Result<()> {
+    let (chain_state, net) = prepare_genesis();
+
+    let account1 = Account::new();
+    let txn1 = Transaction::UserTransaction(create_account_txn_sent_as_association(
+        &account1,
+        0,
+        STCUnit::STC.value_of(100).scaling(),
+        1,
+        &net,
+    ));
+    let output1 = execute_and_apply(&chain_state, txn1);
+    assert_eq!(KeptVMStatus::Executed, output1.status().status().unwrap());
+
+    let account2 = Account::new();
+    let txn2 = peer_to_peer_txn(&account1, &account2, 0, 100, 1, net.chain_id());
+    let mut vm = StarcoinVM::new(None);
+    let state_view_cache = StateViewCache::new(&chain_state);
+    let (_, output, record) = vm.execute_user_transaction_with_record(
+        &state_view_cache.as_move_resolver(),
+        txn2,
+        ExecutionRecordOption {
+            call_trace: true,
+            gas_profile: true,
+        },
+    )?;
+    assert_eq!(KeptVMStatus::Executed, output.status().status().unwrap());
+
+    let trace = record.call_trace.expect("call trace should exist");
+    assert!(trace.module.is_some());
+    assert!(!trace.aborted);
+    assert!(!trace.calls.is_empty());
+    assert!(trace.gas_used > 0 && trace.gas_used <= output.gas_used());
+
+    // the balance of both the sender and the payee are borrowed by the transfer.
+    let mut frames = vec![&trace];
+    let mut balance_owners = vec![];
+    let mut events = vec![];
+    while let Some(frame) = frames.pop() {
+        frames.extend(frame.calls.iter());
+        events.extend(frame.events.iter());
+        for access in &frame.resources {
+            if let TypeTag::Struct(tag) = &access.type_tag {
+                if tag.module.as_str() == "Account" && tag.name.as_str() == "Balance" {
+                    assert!(access.success);
+                    balance_owners.push(access.address);
+                }
+            }
+        }
+    }
+    assert!(balance_owners.contains(&Some(*account1.address())));
+    assert!(balance_owners.contains(&Some(*account2.address())));
+    // the withdraw and deposit events are recorded on the frames which emit them.
+    assert!(!events.is_empty());
+    for event in events {
+        assert!(output.events().iter().any(|output_event| {
+            output_event.key().as_bytes() == event.key.as_slice()
+                && output_event.sequence_number() == event.seq_number
+                && output_event.type_tag() == &event.type_tag
+        }));
+    }
+
+    let profile = record.gas_profile.expect("gas profile should exist");
+    assert!(profile.total_gas() > 0);
+    assert!(!profile.instructions.is_empty());
+    assert!(!profile.storage.is_empty());
+    assert!(profile
+        .to_folded_stacks()
+        .lines()
+        .all(|line| line.starts_with("0x1::TransferScripts::peer_to_peer_v2")));
+    Ok(())
+}
+
 #[stest::test]
 fn test_execute_mint_txn_with_starcoin_vm() -> Result<()> {
     let (chain_state, net) = prepare_genesis();
@@ -0,0 +1,252 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Strategies to order and select the pending txns of the txpool for a block template.
+
+use starcoin_config::{BlockPackingStrategyType, MinerConfig};
+use starcoin_crypto::HashValue;
+use starcoin_types::account_address::AccountAddress;
+use starcoin_vm_types::transaction::SignedUserTransaction;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+/// Max times to refill the block with the txns following the one exceeding the block gas limit.
+const HIGHEST_FEE_REFILL_ROUNDS: usize = 4;
+
+/// Why a pending txn is left out of the block template.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ExcludedReason {
+    /// Its sender already has `max_txns_per_sender` txns in the block.
+    SenderLimit,
+    /// An earlier txn of its sender is left out, so its sequence number can not be executed.
+    SenderBlocked,
+    /// Not enough gas left in the block.
+    BlockGasLimit,
+    /// Discarded by the VM, and removed from the txpool as invalid.
+    Discarded,
+}
+
+impl ExcludedReason {
+    pub const ALL: [ExcludedReason; 4] = [
+        Self::SenderLimit,
+        Self::SenderBlocked,
+        Self::BlockGasLimit,
+        Self::Discarded,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SenderLimit => "sender_limit",
+            Self::SenderBlocked => "sender_blocked",
+            Self::BlockGasLimit => "block_gas_limit",
+            Self::Discarded => "discarded",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExcludedTxn {
+    pub txn_hash: HashValue,
+    pub sender: AccountAddress,
+    pub reason: ExcludedReason,
+}
+
+impl ExcludedTxn {
+    pub fn new(txn: &SignedUserTransaction, reason: ExcludedReason) -> Self {
+        Self {
+            txn_hash: txn.id(),
+            sender: txn.sender(),
+            reason,
+        }
+    }
+}
+
+/// The txns selected by a strategy in the packing order, and the ones left out.
+#[derive(Clone, Debug, Default)]
+pub struct PackingSelection {
+    pub selected: Vec<SignedUserTransaction>,
+    pub excluded: Vec<ExcludedTxn>,
+}
+
+pub trait BlockPackingStrategy: Send + Sync {
+    /// Order and select the pending `txns` for a block.
+    /// The txns of a sender in `txns` are in sequence number order, and must keep the order.
+    /// The gas used by a txn is only known after execution, so the block gas limit is not
+    /// checked here but by the greedy refill of the block builder, see `refill_rounds`.
+    fn select(&self, txns: Vec<SignedUserTransaction>) -> PackingSelection;
+
+    /// Max times to refill the block with the selected txns following the one exceeding the block gas limit,
+    /// the following txns of the same sender are left out. 0 to stop at the first txn exceeding the block gas limit.
+    /// A refill is greedy, it packs the following txns in order until one exceeds the gas left.
+    fn refill_rounds(&self) -> usize {
+        0
+    }
+}
+
+/// Create the strategy configured by `config`.
+pub fn packing_strategy(config: &MinerConfig) -> Box<dyn BlockPackingStrategy> {
+    let mut strategy: Box<dyn BlockPackingStrategy> = match config.packing_strategy() {
+        BlockPackingStrategyType::Pool => Box::new(PoolOrderStrategy),
+        BlockPackingStrategyType::HighestFee => Box::new(HighestFeeStrategy),
+    };
+    let priority_addresses = config.priority_addresses();
+    if let Some(max_txns_per_sender) = config.max_txns_per_sender() {
+        strategy = Box::new(SenderLimitStrategy::new(
+            strategy,
+            max_txns_per_sender,
+            priority_addresses.clone(),
+        ));
+    }
+    if !priority_addresses.is_empty() {
+        strategy = Box::new(PriorityAddressStrategy::new(strategy, priority_addresses));
+    }
+    strategy
+}
+
+/// Pack the txns in the txpool order.
+pub struct PoolOrderStrategy;
+
+impl BlockPackingStrategy for PoolOrderStrategy {
+    fn select(&self, txns: Vec<SignedUserTransaction>) -> PackingSelection {
+        PackingSelection {
+            selected: txns,
+            excluded: vec![],
+        }
+    }
+}
+
+/// Pack the txns of the highest gas unit price first, the txns of a sender are still in sequence number order.
+/// The block is greedily refilled with the following txns after a txn exceeds the block gas limit,
+/// at most `HIGHEST_FEE_REFILL_ROUNDS` times.
+pub struct HighestFeeStrategy;
+
+impl BlockPackingStrategy for HighestFeeStrategy {
+    fn select(&self, txns: Vec<SignedUserTransaction>) -> PackingSelection {
+        let total = txns.len();
+        let mut senders: HashMap<AccountAddress, VecDeque<(usize, SignedUserTransaction)>> =
+            HashMap::new();
+        for (idx, txn) in txns.into_iter().enumerate() {
+            senders
+                .entry(txn.sender())
+                .or_insert_with(VecDeque::new)
+                .push_back((idx, txn));
+        }
+        // the next txn of every sender, ties are broken by the txpool order.
+        let mut heads = BinaryHeap::new();
+        for (sender, queue) in senders.iter() {
+            if let Some((idx, txn)) = queue.front() {
+                heads.push((txn.gas_unit_price(), Reverse(*idx), *sender));
+            }
+        }
+        let mut selected = Vec::with_capacity(total);
+        while let Some((_, _, sender)) = heads.pop() {
+            let queue = senders
+                .get_mut(&sender)
+                .expect("queue of the sender should exist");
+            let (_, txn) = queue
+                .pop_front()
+                .expect("queue of the sender should not be empty");
+            selected.push(txn);
+            if let Some((idx, next)) = queue.front() {
+                heads.push((next.gas_unit_price(), Reverse(*idx), sender));
+            }
+        }
+        PackingSelection {
+            selected,
+            excluded: vec![],
+        }
+    }
+
+    fn refill_rounds(&self) -> usize {
+        HIGHEST_FEE_REFILL_ROUNDS
+    }
+}
+
+/// Limit the txns of a sender in a block, except the priority senders.
+pub struct SenderLimitStrategy {
+    inner: Box<dyn BlockPackingStrategy>,
+    max_txns_per_sender: u64,
+    exempt_senders: HashSet<AccountAddress>,
+}
+
+impl SenderLimitStrategy {
+    pub fn new(
+        inner: Box<dyn BlockPackingStrategy>,
+        max_txns_per_sender: u64,
+        exempt_senders: Vec<AccountAddress>,
+    ) -> Self {
+        Self {
+            inner,
+            max_txns_per_sender,
+            exempt_senders: exempt_senders.into_iter().collect(),
+        }
+    }
+}
+
+impl BlockPackingStrategy for SenderLimitStrategy {
+    fn select(&self, txns: Vec<SignedUserTransaction>) -> PackingSelection {
+        let PackingSelection {
+            selected,
+            mut excluded,
+        } = self.inner.select(txns);
+        let mut txn_counts: HashMap<AccountAddress, u64> = HashMap::new();
+        let selected = selected
+            .into_iter()
+            .filter(|txn| {
+                let sender = txn.sender();
+                if self.exempt_senders.contains(&sender) {
+                    return true;
+                }
+                let count = txn_counts.entry(sender).or_insert(0);
+                if *count >= self.max_txns_per_sender {
+                    excluded.push(ExcludedTxn::new(txn, ExcludedReason::SenderLimit));
+                    false
+                } else {
+                    *count += 1;
+                    true
+                }
+            })
+            .collect();
+        PackingSelection { selected, excluded }
+    }
+
+    fn refill_rounds(&self) -> usize {
+        self.inner.refill_rounds()
+    }
+}
+
+/// Pack the txns of the priority senders before others, such as the operational txns of the miner.
+pub struct PriorityAddressStrategy {
+    inner: Box<dyn BlockPackingStrategy>,
+    priority_senders: HashSet<AccountAddress>,
+}
+
+impl PriorityAddressStrategy {
+    pub fn new(
+        inner: Box<dyn BlockPackingStrategy>,
+        priority_senders: Vec<AccountAddress>,
+    ) -> Self {
+        Self {
+            inner,
+            priority_senders: priority_senders.into_iter().collect(),
+        }
+    }
+}
+
+impl BlockPackingStrategy for PriorityAddressStrategy {
+    fn select(&self, txns: Vec<SignedUserTransaction>) -> PackingSelection {
+        let PackingSelection { selected, excluded } = self.inner.select(txns);
+        let (mut priority_txns, others): (Vec<_>, Vec<_>) = selected
+            .into_iter()
+            .partition(|txn| self.priority_senders.contains(&txn.sender()));
+        priority_txns.extend(others);
+        PackingSelection {
+            selected: priority_txns,
+            excluded,
+        }
+    }
+
+    fn refill_rounds(&self) -> usize {
+        self.inner.refill_rounds()
+    }
+}
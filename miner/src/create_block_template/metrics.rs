@@ -1,11 +1,12 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use starcoin_metrics::{register, Opts, PrometheusError, Registry, UIntGauge};
+use starcoin_metrics::{register, Opts, PrometheusError, Registry, UIntGauge, UIntGaugeVec};
 
 #[derive(Clone)]
 pub struct BlockBuilderMetrics {
     pub current_epoch_maybe_uncles: UIntGauge,
+    pub excluded_txns: UIntGaugeVec,
 }
 
 impl BlockBuilderMetrics {
@@ -17,9 +18,20 @@ impl BlockBuilderMetrics {
             ))?,
             registry,
         )?;
+        let excluded_txns = register(
+            UIntGaugeVec::new(
+                Opts::new(
+                    "block_builder_excluded_txns",
+                    "Pending txns left out of the latest block template, by reason.",
+                ),
+                &["reason"],
+            )?,
+            registry,
+        )?;
 
         Ok(Self {
             current_epoch_maybe_uncles,
+            excluded_txns,
         })
     }
 }
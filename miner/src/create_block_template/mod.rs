@@ -1,6 +1,9 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::create_block_template::block_packing::{
+    BlockPackingStrategy, ExcludedReason, ExcludedTxn, PackingSelection,
+};
 use crate::create_block_template::metrics::BlockBuilderMetrics;
 use anyhow::{format_err, Result};
 use futures::executor::block_on;
@@ -29,6 +32,7 @@ use starcoin_vm_types::transaction::SignedUserTransaction;
 use std::cmp::min;
 use std::{collections::HashMap, sync::Arc};
 
+pub mod block_packing;
 mod metrics;
 #[cfg(test)]
 mod test_create_block_template;
@@ -51,6 +55,17 @@ impl ServiceRequest for BlockTemplateRequest {
 pub struct BlockTemplateResponse {
     pub parent: BlockHeader,
     pub template: BlockTemplate,
+    /// The pending txns left out of the template, and why.
+    pub excluded_txns: Vec<ExcludedTxn>,
+}
+
+impl BlockTemplateResponse {
+    pub fn excluded_count(&self, reason: ExcludedReason) -> usize {
+        self.excluded_txns
+            .iter()
+            .filter(|excluded| excluded.reason == reason)
+            .count()
+    }
 }
 
 pub struct BlockBuilderService {
@@ -85,6 +100,7 @@ impl ServiceFactory<Self> for BlockBuilderService {
             startup_info.main,
             txpool,
             config.miner.block_gas_limit,
+            block_packing::packing_strategy(&config.miner),
             miner_account,
             metrics,
             vm_metrics,
@@ -188,6 +204,7 @@ pub struct Inner<P> {
     parent_uncle: HashMap<HashValue, Vec<HashValue>>,
     uncles: HashMap<HashValue, BlockHeader>,
     local_block_gas_limit: Option<u64>,
+    packing_strategy: Box<dyn BlockPackingStrategy>,
     miner_account: AccountInfo,
     metrics: Option<BlockBuilderMetrics>,
     vm_metrics: Option<VMMetrics>,
@@ -203,6 +220,7 @@ where
         block_id: HashValue,
         tx_provider: P,
         local_block_gas_limit: Option<u64>,
+        packing_strategy: Box<dyn BlockPackingStrategy>,
         miner_account: AccountInfo,
         metrics: Option<BlockBuilderMetrics>,
         vm_metrics: Option<VMMetrics>,
@@ -221,6 +239,7 @@ where
             parent_uncle: HashMap::new(),
             uncles: HashMap::new(),
             local_block_gas_limit,
+            packing_strategy,
             miner_account,
             metrics,
             vm_metrics,
@@ -309,6 +328,8 @@ where
         let max_txns = (block_gas_limit / 200) * 2;
 
         let txns = self.tx_provider.get_txns(max_txns);
+        let txn_count = txns.len();
+        let selection = self.packing_strategy.select(txns);
 
         let author = *self.miner_account.address();
         let previous_header = self.chain.current_header();
@@ -326,7 +347,7 @@ where
             previous_header,
             block_gas_limit,
             max_txns,
-            txns.len(),
+            txn_count,
             uncles.len(),
             now_millis,
         );
@@ -346,15 +367,83 @@ where
             strategy,
             self.vm_metrics.clone(),
         )?;
-        let excluded_txns = opened_block.push_txns(txns)?;
+        let excluded_txns = self.pack_txns(&mut opened_block, selection)?;
         let template = opened_block.finalize()?;
-        for invalid_txn in excluded_txns.discarded_txns {
-            self.tx_provider.remove_invalid_txn(invalid_txn.id());
+        if !excluded_txns.is_empty() {
+            info!(
+                "[CreateBlockTemplate] included txn len: {}, excluded txn len: {}",
+                template.body.transactions.len(),
+                excluded_txns.len()
+            );
+        }
+        // the same pending txns are excluded again by every template rebuild, so only the latest
+        // template is counted.
+        if let Some(metrics) = self.metrics.as_ref() {
+            for reason in ExcludedReason::ALL {
+                let count = excluded_txns
+                    .iter()
+                    .filter(|excluded| excluded.reason == reason)
+                    .count();
+                metrics
+                    .excluded_txns
+                    .with_label_values(&[reason.as_str()])
+                    .set(count as u64);
+            }
         }
 
         Ok(BlockTemplateResponse {
             parent: previous_header,
             template,
+            excluded_txns,
         })
     }
+
+    /// Push the selected txns into the block, and refill the block after a txn exceeds the block gas limit
+    /// as many rounds as the packing strategy allows.
+    fn pack_txns(
+        &self,
+        opened_block: &mut OpenedBlock,
+        selection: PackingSelection,
+    ) -> Result<Vec<ExcludedTxn>> {
+        let PackingSelection {
+            selected: mut txns,
+            mut excluded,
+        } = selection;
+        let mut refill_rounds = self.packing_strategy.refill_rounds();
+        while !txns.is_empty() {
+            let excluded_txns = opened_block.push_txns(txns)?;
+            for invalid_txn in excluded_txns.discarded_txns {
+                self.tx_provider.remove_invalid_txn(invalid_txn.id());
+                excluded.push(ExcludedTxn::new(&invalid_txn, ExcludedReason::Discarded));
+            }
+            let mut untouched_txns = excluded_txns.untouched_txns.into_iter();
+            let exceeded_txn = match untouched_txns.next() {
+                Some(txn) => txn,
+                None => break,
+            };
+            excluded.push(ExcludedTxn::new(
+                &exceeded_txn,
+                ExcludedReason::BlockGasLimit,
+            ));
+            if refill_rounds == 0 {
+                excluded.extend(
+                    untouched_txns.map(|txn| ExcludedTxn::new(&txn, ExcludedReason::BlockGasLimit)),
+                );
+                break;
+            }
+            refill_rounds -= 1;
+            // the following txns of the sender can not be executed without the exceeded one.
+            let blocked_sender = exceeded_txn.sender();
+            txns = untouched_txns
+                .filter(|txn| {
+                    let blocked = txn.sender() == blocked_sender;
+                    if blocked {
+                        excluded.push(ExcludedTxn::new(txn, ExcludedReason::SenderBlocked));
+                    }
+                    !blocked
+                })
+                .collect();
+        }
+        Ok(excluded)
+    }
 }
@@ -1,8 +1,13 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::create_block_template::block_packing::{
+    BlockPackingStrategy, ExcludedReason, ExcludedTxn, HighestFeeStrategy, PoolOrderStrategy,
+    PriorityAddressStrategy, SenderLimitStrategy,
+};
 use crate::create_block_template::{
-    BlockBuilderService, BlockTemplateRequest, EmptyProvider, Inner,
+    BlockBuilderService, BlockTemplateRequest, BlockTemplateResponse, EmptyProvider, Inner,
+    TemplateTxProvider,
 };
 use anyhow::Result;
 use starcoin_account_api::AccountInfo;
@@ -10,15 +15,23 @@ use starcoin_account_service::AccountService;
 use starcoin_chain::BlockChain;
 use starcoin_chain::{ChainReader, ChainWriter};
 use starcoin_config::ChainNetworkID;
-use starcoin_config::{temp_dir, NodeConfig, StarcoinOpt};
+use starcoin_config::{genesis_key_pair, temp_dir, ChainNetwork, NodeConfig, StarcoinOpt};
 use starcoin_consensus::Consensus;
+use starcoin_crypto::HashValue;
 use starcoin_genesis::Genesis as StarcoinGenesis;
 use starcoin_logger::prelude::*;
 use starcoin_service_registry::{RegistryAsyncService, RegistryService};
-use starcoin_storage::BlockStore;
+use starcoin_storage::{BlockStore, Store};
 use starcoin_time_service::MockTimeService;
 use starcoin_txpool::TxPoolService;
-use std::sync::Arc;
+use starcoin_types::account::{peer_to_peer_txn, Account, DEFAULT_EXPIRATION_TIME};
+use starcoin_types::account_address::AccountAddress;
+use starcoin_vm_types::genesis_config::ChainId;
+use starcoin_vm_types::transaction::{
+    RawUserTransaction, Script, SignedUserTransaction, TransactionPayload,
+};
+use std::sync::{Arc, Mutex};
+use test_helper::txn::create_account_txn_sent_as_association;
 
 #[stest::test]
 fn test_create_block_template() {
@@ -46,6 +59,7 @@ fn test_create_block_template_by_net(net: ChainNetworkID) {
         genesis_id,
         EmptyProvider,
         None,
+        Box::new(PoolOrderStrategy),
         miner_account,
         None,
         None,
@@ -87,6 +101,7 @@ fn test_switch_main() {
             head_id,
             txpool.clone(),
             None,
+            Box::new(PoolOrderStrategy),
             miner_account.clone(),
             None,
             None,
@@ -126,6 +141,7 @@ fn test_switch_main() {
                 head_id,
                 txpool.clone(),
                 None,
+                Box::new(PoolOrderStrategy),
                 miner_account.clone(),
                 None,
                 None,
@@ -204,6 +220,7 @@ fn test_do_uncles() {
             head_id,
             txpool.clone(),
             None,
+            Box::new(PoolOrderStrategy),
             miner_account.clone(),
             None,
             None,
@@ -232,6 +249,7 @@ fn test_do_uncles() {
             genesis_id,
             txpool.clone(),
             None,
+            Box::new(PoolOrderStrategy),
             miner_account.clone(),
             None,
             None,
@@ -303,6 +321,7 @@ fn test_new_head() {
         genesis_id,
         txpool,
         None,
+        Box::new(PoolOrderStrategy),
         miner_account,
         None,
         None,
@@ -348,6 +367,7 @@ fn test_new_branch() {
         genesis_id,
         txpool.clone(),
         None,
+        Box::new(PoolOrderStrategy),
         miner_account.clone(),
         None,
         None,
@@ -375,6 +395,7 @@ fn test_new_branch() {
             new_head_id,
             txpool.clone(),
             None,
+            Box::new(PoolOrderStrategy),
             miner_account.clone(),
             None,
             None,
@@ -439,6 +460,7 @@ fn test_create_block_template_by_adjust_time() -> Result<()> {
         genesis.block().id(),
         EmptyProvider,
         None,
+        Box::new(PoolOrderStrategy),
         AccountInfo::random(),
         None,
         None,
@@ -469,3 +491,212 @@ fn test_create_block_template_by_adjust_time() -> Result<()> {
     inner.chain.apply(block)?;
     Ok(())
 }
+
+fn mock_txn(sender: AccountAddress, seq: u64, gas_unit_price: u64) -> SignedUserTransaction {
+    let (private_key, public_key) = genesis_key_pair();
+    RawUserTransaction::new_with_default_gas_token(
+        sender,
+        seq,
+        TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+        0,
+        gas_unit_price,
+        u64::max_value(),
+        ChainId::test(),
+    )
+    .sign(&private_key, public_key)
+    .unwrap()
+    .into_inner()
+}
+
+#[test]
+fn test_highest_fee_packing_strategy() {
+    let (sender_a, sender_b) = (AccountAddress::random(), AccountAddress::random());
+    let a0 = mock_txn(sender_a, 0, 1);
+    let a1 = mock_txn(sender_a, 1, 100);
+    let b0 = mock_txn(sender_b, 0, 10);
+    let selection = HighestFeeStrategy.select(vec![a0.clone(), b0.clone(), a1.clone()]);
+    // a1 can only be packed after a0.
+    assert_eq!(selection.selected, vec![b0, a0, a1]);
+    assert!(selection.excluded.is_empty());
+    assert!(HighestFeeStrategy.refill_rounds() > 0);
+}
+
+#[test]
+fn test_sender_limit_and_priority_packing_strategy() {
+    let (sender_a, sender_b, operator) = (
+        AccountAddress::random(),
+        AccountAddress::random(),
+        AccountAddress::random(),
+    );
+    let a0 = mock_txn(sender_a, 0, 1);
+    let a1 = mock_txn(sender_a, 1, 1);
+    let b0 = mock_txn(sender_b, 0, 1);
+    let op0 = mock_txn(operator, 0, 1);
+    let op1 = mock_txn(operator, 1, 1);
+    let strategy = PriorityAddressStrategy::new(
+        Box::new(SenderLimitStrategy::new(
+            Box::new(PoolOrderStrategy),
+            1,
+            vec![operator],
+        )),
+        vec![operator],
+    );
+    let selection = strategy.select(vec![
+        a0.clone(),
+        a1.clone(),
+        op0.clone(),
+        b0.clone(),
+        op1.clone(),
+    ]);
+    assert_eq!(selection.selected, vec![op0, op1, a0, b0]);
+    assert_eq!(
+        selection.excluded,
+        vec![ExcludedTxn::new(&a1, ExcludedReason::SenderLimit)]
+    );
+}
+
+struct MockTxProvider {
+    txns: Vec<SignedUserTransaction>,
+    invalid_txns: Mutex<Vec<HashValue>>,
+}
+
+impl MockTxProvider {
+    fn new(txns: Vec<SignedUserTransaction>) -> Self {
+        Self {
+            txns,
+            invalid_txns: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl TemplateTxProvider for MockTxProvider {
+    fn get_txns(&self, max: u64) -> Vec<SignedUserTransaction> {
+        self.txns.iter().take(max as usize).cloned().collect()
+    }
+
+    fn remove_invalid_txn(&self, txn_hash: HashValue) {
+        self.invalid_txns.lock().unwrap().push(txn_hash);
+    }
+}
+
+fn create_template_with_txns(
+    net: &ChainNetwork,
+    storage: Arc<dyn Store>,
+    head: HashValue,
+    txns: Vec<SignedUserTransaction>,
+    block_gas_limit: Option<u64>,
+    packing_strategy: Box<dyn BlockPackingStrategy>,
+) -> Result<(Inner<MockTxProvider>, BlockTemplateResponse)> {
+    let inner = Inner::new(
+        net,
+        storage,
+        head,
+        MockTxProvider::new(txns),
+        block_gas_limit,
+        packing_strategy,
+        AccountInfo::random(),
+        None,
+        None,
+    )?;
+    let response = inner.create_block_template()?;
+    Ok((inner, response))
+}
+
+#[stest::test]
+fn test_pack_txns_refill_after_block_gas_limit() -> Result<()> {
+    let node_config = Arc::new(NodeConfig::random_for_test());
+    let net = node_config.net();
+    let (storage, chain_info, _) = StarcoinGenesis::init_storage_for_test(net)?;
+    let expiration = net.time_service().now_secs() + DEFAULT_EXPIRATION_TIME;
+    let (account_a, account_b) = (Account::new(), Account::new());
+    let (mut inner, response) = create_template_with_txns(
+        net,
+        storage.clone(),
+        chain_info.head().id(),
+        vec![
+            create_account_txn_sent_as_association(&account_a, 0, 1_000_000_000, expiration, net),
+            create_account_txn_sent_as_association(&account_b, 1, 1_000_000_000, expiration, net),
+        ],
+        None,
+        Box::new(PoolOrderStrategy),
+    )?;
+    assert_eq!(response.template.body.transactions.len(), 2);
+    let block = net
+        .genesis_config()
+        .consensus()
+        .create_block(response.template, net.time_service().as_ref())?;
+    inner.chain.apply(block)?;
+    let head = inner.chain.current_header().id();
+
+    // a transfer creating the payee account costs more gas than a transfer to an existing one.
+    let big = peer_to_peer_txn(
+        &account_a,
+        &Account::new(),
+        0,
+        1000,
+        expiration,
+        net.chain_id(),
+    );
+    let blocked = peer_to_peer_txn(&account_a, &account_b, 1, 1000, expiration, net.chain_id());
+    let invalid = peer_to_peer_txn(
+        &Account::new(),
+        &account_a,
+        0,
+        1000,
+        expiration,
+        net.chain_id(),
+    );
+    let small = peer_to_peer_txn(&account_b, &account_a, 0, 1000, expiration, net.chain_id());
+    let gas_used = |txn: &SignedUserTransaction| -> Result<u64> {
+        let (_, response) = create_template_with_txns(
+            net,
+            storage.clone(),
+            head,
+            vec![txn.clone()],
+            None,
+            Box::new(PoolOrderStrategy),
+        )?;
+        assert_eq!(response.template.body.transactions.len(), 1);
+        Ok(response.template.gas_used)
+    };
+    let small_gas = gas_used(&small)?;
+    assert!(gas_used(&big)? > small_gas);
+    let txns = vec![big.clone(), blocked.clone(), invalid.clone(), small.clone()];
+
+    // the block is refilled with the small txn after the big one exceeds the block gas limit.
+    let (inner, response) = create_template_with_txns(
+        net,
+        storage.clone(),
+        head,
+        txns.clone(),
+        Some(small_gas),
+        Box::new(HighestFeeStrategy),
+    )?;
+    assert_eq!(response.template.body.transactions, vec![small]);
+    assert_eq!(response.template.gas_used, small_gas);
+    assert_eq!(
+        response.excluded_txns,
+        vec![
+            ExcludedTxn::new(&big, ExcludedReason::BlockGasLimit),
+            ExcludedTxn::new(&blocked, ExcludedReason::SenderBlocked),
+            ExcludedTxn::new(&invalid, ExcludedReason::Discarded),
+        ]
+    );
+    assert_eq!(
+        *inner.tx_provider.invalid_txns.lock().unwrap(),
+        vec![invalid.id()]
+    );
+
+    // the pool order strategy stops at the first txn exceeding the block gas limit.
+    let (_, response) = create_template_with_txns(
+        net,
+        storage,
+        head,
+        txns,
+        Some(small_gas),
+        Box::new(PoolOrderStrategy),
+    )?;
+    assert!(response.template.body.transactions.is_empty());
+    assert_eq!(response.excluded_count(ExcludedReason::BlockGasLimit), 4);
+    Ok(())
+}
@@ -21,7 +21,10 @@ pub mod generate_block_event_pacemaker;
 mod metrics;
 pub mod task;
 
-pub use create_block_template::{BlockBuilderService, BlockTemplateRequest};
+pub use create_block_template::block_packing::{
+    BlockPackingStrategy, ExcludedReason, ExcludedTxn, PackingSelection,
+};
+pub use create_block_template::{BlockBuilderService, BlockTemplateRequest, BlockTemplateResponse};
 use starcoin_crypto::HashValue;
 pub use starcoin_types::block::BlockHeaderExtra;
 pub use starcoin_types::system_events::{GenerateBlockEvent, MinedBlock, MintBlockEvent};
@@ -69,6 +69,7 @@ enum Action {
     SetReservedPeers(SetId, HashSet<PeerId>),
     SetReservedOnly(SetId, bool),
     ReportPeer(PeerId, ReputationChange),
+    SetReputation(PeerId, i32),
     AddToPeersSet(SetId, PeerId),
     RemoveFromPeersSet(SetId, PeerId),
     PeerReputations((Sender<Vec<(PeerId, i32)>>, i32)),
@@ -176,6 +177,13 @@ impl PeersetHandle {
             .unbounded_send(Action::ReportPeer(peer_id, score_diff));
     }
 
+    /// Sets the reputation of the given peer, instead of adjusting it by a change.
+    pub fn set_reputation(&self, peer_id: PeerId, reputation: i32) {
+        let _ = self
+            .tx
+            .unbounded_send(Action::SetReputation(peer_id, reputation));
+    }
+
     /// Add a peer to a set.
     pub fn add_to_peers_set(&self, set_id: SetId, peer_id: PeerId) {
         let _ = self
@@ -474,8 +482,32 @@ impl Peerset {
         );
 
         drop(reputation);
+        self.disconnect_banned(peer_id);
+    }
 
+    fn on_set_reputation(&mut self, peer_id: PeerId, value: i32) {
+        // Like reports, apply the updates over time before the reputation is replaced.
+        self.update_time();
+
+        let mut reputation = self.data.peer_reputation(peer_id);
+        reputation.set_reputation(value);
+        drop(reputation);
+        if value >= BANNED_THRESHOLD {
+            trace!(target: "peerset", "Set reputation of {} to {}", peer_id, value);
+            return;
+        }
+
+        debug!(target: "peerset", "Set reputation of {} to {}, Disconnecting", peer_id, value);
+        self.disconnect_banned(peer_id);
+    }
+
+    /// Disconnect a peer whose reputation is below the banned threshold.
+    fn disconnect_banned(&mut self, peer_id: PeerId) {
         for set_index in 0..self.data.num_sets() {
+            // Reserved nodes are kept connected whatever their reputation.
+            if self.reserved_nodes[set_index].0.contains(&peer_id) {
+                continue;
+            }
             if let peersstate::Peer::Connected(peer) = self.data.peer(set_index, &peer_id) {
                 let peer = peer.disconnect();
                 self.message_queue.push_back(Message::Drop {
@@ -621,7 +653,8 @@ impl Peerset {
 
         self.update_time();
 
-        if self.reserved_nodes[set_id.0].1 && !self.reserved_nodes[set_id.0].0.contains(&peer_id) {
+        let is_reserved = self.reserved_nodes[set_id.0].0.contains(&peer_id);
+        if self.reserved_nodes[set_id.0].1 && !is_reserved {
             self.message_queue.push_back(Message::Reject(index));
             return;
         }
@@ -636,7 +669,7 @@ impl Peerset {
             peersstate::Peer::Unknown(entry) => entry.discover(),
         };
 
-        if not_connected.reputation() < BANNED_THRESHOLD {
+        if !is_reserved && not_connected.reputation() < BANNED_THRESHOLD {
             self.message_queue.push_back(Message::Reject(index));
             return;
         }
@@ -761,6 +794,9 @@ impl Stream for Peerset {
                     self.on_set_reserved_only(set_id, reserved)
                 }
                 Action::ReportPeer(peer_id, score_diff) => self.on_report_peer(peer_id, score_diff),
+                Action::SetReputation(peer_id, reputation) => {
+                    self.on_set_reputation(peer_id, reputation)
+                }
                 Action::AddToPeersSet(sets_name, peer_id) => {
                     self.add_to_peers_set(sets_name, peer_id)
                 }
@@ -983,4 +1019,86 @@ mod tests {
 
         futures::executor::block_on(fut);
     }
+
+    #[test]
+    fn test_peerset_set_reputation() {
+        let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
+            sets: vec![SetConfig {
+                in_peers: 25,
+                out_peers: 25,
+                bootnodes: vec![],
+                reserved_nodes: Default::default(),
+                reserved_only: false,
+            }],
+        });
+
+        // The reports before and after the set are applied in order, none of them is lost.
+        let peer_id = PeerId::random();
+        handle.report_peer(peer_id, ReputationChange::new(-50, ""));
+        handle.set_reputation(peer_id, BANNED_THRESHOLD - 20);
+        handle.report_peer(peer_id, ReputationChange::new(10, ""));
+        let mut reputations = handle.reputations(i32::MIN);
+
+        let fut = futures::future::poll_fn(move |cx| {
+            assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+            let reputations = match Pin::new(&mut reputations).poll(cx) {
+                Poll::Ready(reputations) => reputations.unwrap(),
+                Poll::Pending => panic!(),
+            };
+            assert_eq!(reputations, vec![(peer_id, BANNED_THRESHOLD - 10)]);
+
+            // A banned peer is refused.
+            peerset.incoming(SetId::from(0), peer_id, IncomingIndex(1));
+            if let Poll::Ready(msg) = Stream::poll_next(Pin::new(&mut peerset), cx) {
+                assert_eq!(msg.unwrap(), Message::Reject(IncomingIndex(1)));
+            } else {
+                panic!()
+            }
+            Poll::Ready(())
+        });
+
+        futures::executor::block_on(fut);
+    }
+
+    #[test]
+    fn test_peerset_keep_banned_reserved_peer() {
+        let reserved_peer = PeerId::random();
+        let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
+            sets: vec![SetConfig {
+                in_peers: 0,
+                out_peers: 0,
+                bootnodes: vec![],
+                reserved_nodes: vec![reserved_peer].into_iter().collect(),
+                reserved_only: false,
+            }],
+        });
+
+        handle.report_peer(
+            reserved_peer,
+            ReputationChange::new(BANNED_THRESHOLD - 1, ""),
+        );
+
+        let fut = futures::future::poll_fn(move |cx| {
+            // The reserved peer is connected without a slot.
+            if let Poll::Ready(msg) = Stream::poll_next(Pin::new(&mut peerset), cx) {
+                assert_eq!(
+                    msg.unwrap(),
+                    Message::Connect {
+                        set_id: SetId::from(0),
+                        peer_id: reserved_peer,
+                    }
+                );
+            } else {
+                panic!()
+            }
+
+            // The report bans the peer, but it's not dropped.
+            assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+            assert_eq!(peerset.data.connected_peers(0).count(), 1);
+
+            Poll::Ready(())
+        });
+
+        futures::executor::block_on(fut);
+    }
 }
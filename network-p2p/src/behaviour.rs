@@ -16,6 +16,7 @@
 
 use crate::business_layer_handle::BusinessLayerHandle;
 use crate::discovery::DiscoveryConfig;
+use crate::peer_bandwidth::PeerBandwidth;
 use crate::protocol::generic_proto::NotificationsSink;
 use crate::protocol::{CustomMessageOutcome, Protocol};
 use crate::request_responses::{Event, IfDisconnected, RequestFailure, ResponseFailure};
@@ -32,6 +33,7 @@ use libp2p::swarm::NetworkBehaviour;
 use sc_peerset::ReputationChange;
 use std::borrow::Cow;
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// General behaviour of the network. Combines all protocols together.
@@ -154,6 +156,7 @@ impl<T: BusinessLayerHandle + Send> Behaviour<T> {
         local_public_key: PublicKey,
         disco_config: DiscoveryConfig,
         request_response_protocols: Vec<request_responses::ProtocolConfig>,
+        peer_bandwidth: Arc<PeerBandwidth>,
     ) -> Result<Self, request_responses::RegisterError> {
         Ok(Behaviour {
             protocol,
@@ -162,6 +165,7 @@ impl<T: BusinessLayerHandle + Send> Behaviour<T> {
             discovery: disco_config.finish(),
             request_responses: request_responses::RequestResponsesBehaviour::new(
                 request_response_protocols.into_iter(),
+                peer_bandwidth,
             )?,
         })
     }
@@ -24,7 +24,7 @@ use zeroize::Zeroize;
 
 pub use crate::request_responses::{IncomingRequest, ProtocolConfig as RequestResponseConfig};
 pub use libp2p::{build_multiaddr, core::PublicKey, identity};
-pub use network_p2p_types::{parse_addr, parse_str_addr, MultiaddrWithPeerId};
+pub use network_p2p_types::{parse_addr, parse_str_addr, BandwidthLimits, MultiaddrWithPeerId};
 
 /// Name of a protocol, transmitted on the wire. Should be unique for each chain. Always UTF-8.
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -110,6 +110,8 @@ pub struct NetworkConfiguration {
     /// Require iterative Kademlia DHT queries to use disjoint paths for increased resiliency in the
     /// presence of potentially adversarial nodes.
     pub kademlia_disjoint_query_paths: bool,
+    /// The upload and download rate limits of the peers and the whole node.
+    pub bandwidth_limits: BandwidthLimits,
 }
 
 /// Configuration for the transport layer.
@@ -153,6 +155,7 @@ impl Default for NetworkConfiguration {
             request_response_protocols: vec![],
             allow_non_globals_in_dht: false,
             kademlia_disjoint_query_paths: false,
+            bandwidth_limits: BandwidthLimits::default(),
         }
     }
 }
@@ -183,6 +186,7 @@ impl NetworkConfiguration {
             request_response_protocols: vec![],
             allow_non_globals_in_dht: false,
             kademlia_disjoint_query_paths: false,
+            bandwidth_limits: BandwidthLimits::default(),
         }
     }
 
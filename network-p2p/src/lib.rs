@@ -24,9 +24,12 @@ pub mod config;
 #[allow(deprecated)]
 pub mod discovery;
 mod errors;
+#[cfg(feature = "link-conditions")]
+pub mod link_conditions;
 mod metrics;
 mod network_state;
 mod out_events;
+pub mod peer_bandwidth;
 mod peer_info;
 //TODO change to private
 #[allow(clippy::result_unit_err)]
@@ -0,0 +1,145 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conditions of the links between the in-process nodes, to simulate the latency, message loss
+//! and partitions in the integration tests. The conditions only take effect on the nodes they are
+//! installed to by `NetworkService::set_link_conditions`, and only with the `link-conditions`
+//! feature.
+//!
+//! The conditions are directed and applied by the sender: the notifications to the peer and the
+//! requests to the peer by the link to it, the responses by the link from it.
+//! The drop decisions of a link are deterministic by its seed, but the delayed messages are sent
+//! by timers, so their order and the interleaving with other links are not.
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkCondition {
+    /// Delay of every message on the link.
+    pub latency: Duration,
+    /// Probability in [0, 1] to drop a message on the link.
+    pub drop_rate: f64,
+    /// Drop all the messages on the link.
+    pub partitioned: bool,
+    /// Seed of the drop decisions, the same seed drops the same messages of a message sequence.
+    pub seed: u64,
+}
+
+/// What happens to a message on the link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkFate {
+    Deliver(Duration),
+    Drop,
+    Partitioned,
+}
+
+#[derive(Debug)]
+struct Link {
+    condition: LinkCondition,
+    rng: StdRng,
+}
+
+#[derive(Debug, Default)]
+pub struct LinkConditions {
+    links: Mutex<HashMap<(PeerId, PeerId), Link>>,
+}
+
+impl LinkConditions {
+    pub fn set(&self, from: PeerId, to: PeerId, condition: LinkCondition) {
+        let rng = StdRng::seed_from_u64(condition.seed);
+        self.links
+            .lock()
+            .insert((from, to), Link { condition, rng });
+    }
+
+    pub fn get(&self, from: &PeerId, to: &PeerId) -> Option<LinkCondition> {
+        self.links
+            .lock()
+            .get(&(*from, *to))
+            .map(|link| link.condition.clone())
+    }
+
+    pub fn remove(&self, from: &PeerId, to: &PeerId) -> Option<LinkCondition> {
+        self.links
+            .lock()
+            .remove(&(*from, *to))
+            .map(|link| link.condition)
+    }
+
+    /// Remove the conditions of the links from or to the peer.
+    pub fn remove_peer(&self, peer: &PeerId) {
+        self.links
+            .lock()
+            .retain(|(from, to), _| from != peer && to != peer);
+    }
+
+    /// Decide the fate of the next message on the link, a link without condition delivers at once.
+    pub fn transmit(&self, from: &PeerId, to: &PeerId) -> LinkFate {
+        let mut links = self.links.lock();
+        let link = match links.get_mut(&(*from, *to)) {
+            Some(link) => link,
+            None => return LinkFate::Deliver(Duration::ZERO),
+        };
+        if link.condition.partitioned {
+            LinkFate::Partitioned
+        } else if link.condition.drop_rate > 0.0
+            && link.rng.gen_bool(link.condition.drop_rate.min(1.0))
+        {
+            LinkFate::Drop
+        } else {
+            LinkFate::Deliver(link.condition.latency)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_conditions() {
+        let conditions = LinkConditions::default();
+        let (a, b) = (PeerId::random(), PeerId::random());
+        assert_eq!(
+            conditions.transmit(&a, &b),
+            LinkFate::Deliver(Duration::ZERO)
+        );
+
+        let condition = LinkCondition {
+            latency: Duration::from_millis(100),
+            drop_rate: 0.5,
+            seed: 7,
+            ..Default::default()
+        };
+        conditions.set(a, b, condition.clone());
+        let fates: Vec<_> = (0..32).map(|_| conditions.transmit(&a, &b)).collect();
+        assert!(fates.contains(&LinkFate::Drop));
+        assert!(fates.contains(&LinkFate::Deliver(Duration::from_millis(100))));
+        // the same seed drops the same messages.
+        conditions.set(a, b, condition);
+        let fates2: Vec<_> = (0..32).map(|_| conditions.transmit(&a, &b)).collect();
+        assert_eq!(fates, fates2);
+        assert_eq!(
+            conditions.transmit(&b, &a),
+            LinkFate::Deliver(Duration::ZERO)
+        );
+
+        conditions.set(
+            b,
+            a,
+            LinkCondition {
+                partitioned: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(conditions.transmit(&b, &a), LinkFate::Partitioned);
+        conditions.remove_peer(&a);
+        assert!(conditions.get(&a, &b).is_none());
+        assert!(conditions.get(&b, &a).is_none());
+    }
+}
@@ -23,6 +23,7 @@ pub struct Metrics {
     pub notifications_sizes: HistogramVec,
     pub notifications_streams_closed_total: UIntCounter,
     pub notifications_streams_opened_total: UIntCounter,
+    pub peer_bandwidth_bytes_total: UIntCounterVec,
     pub peerset_num_discovered: UIntGauge,
     pub peerset_num_requested: UIntGauge,
     pub pending_connections: UIntGauge,
@@ -187,6 +188,17 @@ impl Metrics {
                 )?,
                 registry,
             )?,
+            peer_bandwidth_bytes_total: register(
+                UIntCounterVec::new(
+                    Opts::new(
+                        "networkp2p_peer_bandwidth_bytes_total",
+                        "Total bytes of the notifications and requests transferred with the peers, \
+                         by protocol and direction",
+                    ),
+                    &["protocol", "direction"],
+                )?,
+                registry,
+            )?,
             peerset_num_discovered: register(
                 UIntGauge::new(
                     "networkp2p_peerset_num_discovered",
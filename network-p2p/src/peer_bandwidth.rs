@@ -0,0 +1,314 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Account the transferred bytes per peer and per protocol, and enforce the upload and download
+//! rate limits.
+//!
+//! The limits are debt based: the bytes are always accounted, and the caller delays the next
+//! transfer by the returned wait time. The outbound rpc requests and notifications are delayed,
+//! the responses to inbound requests are delayed to throttle the requesting peer, and the
+//! notifications received from a peer in debt are delivered after the debt is paid. No
+//! notification is dropped for the limits.
+//!
+//! Every peer has a bucket per traffic class, so a peer busy with the sync still gets and sends
+//! the new blocks in time. The sync traffic is limited by its share of the global limits in
+//! addition, so the gossip always has some headroom.
+
+use libp2p::PeerId;
+use network_p2p_types::{BandwidthLimits, Direction, PeerBandwidthStats, TrafficClass};
+use parking_lot::Mutex;
+use starcoin_metrics::UIntCounterVec;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A token bucket allows a burst of a second, the tokens go negative when the bytes are over the
+/// rate, and the debt is paid back over time.
+#[derive(Debug)]
+struct RateLimiter {
+    rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated_at = now;
+    }
+
+    fn consume(&mut self, bytes: u64, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= bytes as f64;
+        self.wait_time()
+    }
+
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 0.0 || self.rate <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Limiters {
+    upload: Option<RateLimiter>,
+    download: Option<RateLimiter>,
+}
+
+impl Limiters {
+    fn new(upload: Option<u64>, download: Option<u64>, now: Instant) -> Self {
+        Self {
+            upload: upload.map(|rate| RateLimiter::new(rate, now)),
+            download: download.map(|rate| RateLimiter::new(rate, now)),
+        }
+    }
+
+    fn get_mut(&mut self, direction: Direction) -> Option<&mut RateLimiter> {
+        match direction {
+            Direction::Inbound => self.download.as_mut(),
+            Direction::Outbound => self.upload.as_mut(),
+        }
+    }
+
+    fn consume(&mut self, direction: Direction, bytes: u64, now: Instant) -> Duration {
+        self.get_mut(direction)
+            .map(|limiter| limiter.consume(bytes, now))
+            .unwrap_or_default()
+    }
+
+    fn wait_time(&mut self, direction: Direction, now: Instant) -> Duration {
+        self.get_mut(direction)
+            .map(|limiter| {
+                limiter.refill(now);
+                limiter.wait_time()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+struct PeerState {
+    gossip: Limiters,
+    sync: Limiters,
+    stats: PeerBandwidthStats,
+}
+
+impl PeerState {
+    fn new(limits: &BandwidthLimits, now: Instant) -> Self {
+        Self {
+            gossip: Limiters::new(limits.peer_upload, limits.peer_download, now),
+            sync: Limiters::new(limits.peer_upload, limits.peer_download, now),
+            stats: PeerBandwidthStats::default(),
+        }
+    }
+
+    fn limiters(&mut self, class: TrafficClass) -> &mut Limiters {
+        match class {
+            TrafficClass::Gossip => &mut self.gossip,
+            TrafficClass::Sync => &mut self.sync,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    peers: HashMap<PeerId, PeerState>,
+    global: Limiters,
+    sync: Limiters,
+}
+
+#[derive(Debug)]
+pub struct PeerBandwidth {
+    limits: BandwidthLimits,
+    state: Mutex<State>,
+    bytes_metric: Option<UIntCounterVec>,
+}
+
+impl Default for PeerBandwidth {
+    fn default() -> Self {
+        Self::new(BandwidthLimits::default(), None)
+    }
+}
+
+impl PeerBandwidth {
+    pub fn new(limits: BandwidthLimits, bytes_metric: Option<UIntCounterVec>) -> Self {
+        let now = Instant::now();
+        let sync_share =
+            |rate: u64| rate.saturating_mul(u64::from(limits.sync_share.min(100))) / 100;
+        let state = State {
+            peers: HashMap::new(),
+            global: Limiters::new(limits.global_upload, limits.global_download, now),
+            sync: Limiters::new(
+                limits.global_upload.map(sync_share),
+                limits.global_download.map(sync_share),
+                now,
+            ),
+        };
+        Self {
+            limits,
+            state: Mutex::new(state),
+            bytes_metric,
+        }
+    }
+
+    pub fn limits(&self) -> &BandwidthLimits {
+        &self.limits
+    }
+
+    /// Account the bytes transferred with the peer, and return the time to wait before the next
+    /// transfer in the direction to keep under the limits.
+    pub fn on_transfer(
+        &self,
+        peer: PeerId,
+        protocol: &str,
+        class: TrafficClass,
+        direction: Direction,
+        bytes: usize,
+    ) -> Duration {
+        let bytes = bytes as u64;
+        if let Some(metric) = self.bytes_metric.as_ref() {
+            metric
+                .with_label_values(&[protocol, direction.as_str()])
+                .inc_by(bytes);
+        }
+        let now = Instant::now();
+        let mut guard = self.state.lock();
+        let state = &mut *guard;
+        let limits = &self.limits;
+        let peer_state = state
+            .peers
+            .entry(peer)
+            .or_insert_with(|| PeerState::new(limits, now));
+        peer_state.stats.add(protocol, direction, bytes);
+
+        let mut wait = peer_state.limiters(class).consume(direction, bytes, now);
+        wait = wait.max(state.global.consume(direction, bytes, now));
+        if class == TrafficClass::Sync {
+            wait = wait.max(state.sync.consume(direction, bytes, now));
+        }
+        wait
+    }
+
+    /// The time to wait before a transfer with the peer, to pay back the debt of the former
+    /// transfers.
+    pub fn wait_time(&self, peer: &PeerId, class: TrafficClass, direction: Direction) -> Duration {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let mut wait = state
+            .peers
+            .get_mut(peer)
+            .map(|peer_state| peer_state.limiters(class).wait_time(direction, now))
+            .unwrap_or_default();
+        wait = wait.max(state.global.wait_time(direction, now));
+        if class == TrafficClass::Sync {
+            wait = wait.max(state.sync.wait_time(direction, now));
+        }
+        wait
+    }
+
+    pub fn peer_stats(&self, peer: &PeerId) -> Option<PeerBandwidthStats> {
+        self.state
+            .lock()
+            .peers
+            .get(peer)
+            .map(|peer_state| peer_state.stats.clone())
+    }
+
+    pub fn stats(&self) -> HashMap<PeerId, PeerBandwidthStats> {
+        self.state
+            .lock()
+            .peers
+            .iter()
+            .map(|(peer, peer_state)| (*peer, peer_state.stats.clone()))
+            .collect()
+    }
+
+    /// Forget the disconnected peer.
+    pub fn remove_peer(&self, peer: &PeerId) {
+        self.state.lock().peers.remove(peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_bandwidth_limits() {
+        let bandwidth = PeerBandwidth::new(
+            BandwidthLimits {
+                peer_upload: Some(1000),
+                global_download: Some(1000),
+                sync_share: 50,
+                ..Default::default()
+            },
+            None,
+        );
+        let peer = PeerId::random();
+        let wait = bandwidth.on_transfer(
+            peer,
+            "/starcoin/txn/1",
+            TrafficClass::Gossip,
+            Direction::Outbound,
+            500,
+        );
+        assert_eq!(wait, Duration::ZERO);
+        let wait = bandwidth.on_transfer(
+            peer,
+            "/starcoin/txn/1",
+            TrafficClass::Gossip,
+            Direction::Outbound,
+            1500,
+        );
+        assert!(wait > Duration::from_millis(900));
+
+        // the sync uses half of the global download.
+        let wait = bandwidth.on_transfer(
+            peer,
+            "get_block",
+            TrafficClass::Sync,
+            Direction::Inbound,
+            1000,
+        );
+        assert!(wait > Duration::from_millis(900));
+        assert!(
+            bandwidth.wait_time(&peer, TrafficClass::Sync, Direction::Inbound) > Duration::ZERO
+        );
+        assert_eq!(
+            bandwidth.wait_time(&peer, TrafficClass::Gossip, Direction::Inbound),
+            Duration::ZERO
+        );
+
+        // the sync with the peer over the peer limit does not delay the gossip.
+        let wait = bandwidth.on_transfer(
+            peer,
+            "get_block",
+            TrafficClass::Sync,
+            Direction::Outbound,
+            3000,
+        );
+        assert!(wait > Duration::from_millis(1900));
+        assert!(
+            bandwidth.wait_time(&peer, TrafficClass::Gossip, Direction::Outbound)
+                < Duration::from_millis(1100)
+        );
+
+        let stats = bandwidth.peer_stats(&peer).unwrap();
+        assert_eq!(stats.outbound_bytes, 5000);
+        assert_eq!(stats.inbound_bytes, 1000);
+        assert_eq!(stats.protocols["get_block"].inbound_bytes, 1000);
+        bandwidth.remove_peer(&peer);
+        assert!(bandwidth.peer_stats(&peer).is_none());
+    }
+}
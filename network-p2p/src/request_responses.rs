@@ -33,6 +33,7 @@
 //! is used to handle incoming requests.
 //!
 
+use crate::peer_bandwidth::PeerBandwidth;
 use futures::{
     channel::{mpsc, oneshot},
     prelude::*,
@@ -51,6 +52,7 @@ use libp2p::{
         handler::multi::MultiHandler, NetworkBehaviour, NetworkBehaviourAction, PollParameters,
     },
 };
+use network_p2p_types::{Direction, TrafficClass};
 pub use network_p2p_types::{
     IfDisconnected, InboundFailure, IncomingRequest, OutboundFailure, OutgoingResponse,
     RequestFailure, ResponseFailure,
@@ -62,6 +64,7 @@ use std::{
     convert::TryFrom as _,
     io, iter,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -199,6 +202,23 @@ pub struct RequestResponsesBehaviour {
 
     /// Whenever an incoming request arrives, the arrival [`Instant`] is recorded here.
     pending_responses_arrival_time: HashMap<ProtocolRequestId, Instant>,
+
+    /// Accounts the bytes of the requests and responses, the requests and responses to a peer
+    /// over the bandwidth limits are delayed.
+    peer_bandwidth: Arc<PeerBandwidth>,
+
+    /// The outbound requests waiting for the upload debt to the peer to be paid.
+    delayed_requests:
+        stream::FuturesUnordered<Pin<Box<dyn Future<Output = DelayedRequest> + Send>>>,
+}
+
+/// An outbound request delayed by the upload limits, it is sent when the future is ready.
+struct DelayedRequest {
+    target: PeerId,
+    protocol_name: String,
+    request: Vec<u8>,
+    pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+    connect: IfDisconnected,
 }
 
 /// Generated by the response builder and waiting to be processed.
@@ -213,7 +233,10 @@ struct RequestProcessingOutcome {
 impl RequestResponsesBehaviour {
     /// Creates a new behaviour. Must be passed a list of supported protocols. Returns an error if
     /// the same protocol is passed twice.
-    pub fn new(list: impl Iterator<Item = ProtocolConfig>) -> Result<Self, RegisterError> {
+    pub fn new(
+        list: impl Iterator<Item = ProtocolConfig>,
+        peer_bandwidth: Arc<PeerBandwidth>,
+    ) -> Result<Self, RegisterError> {
         let mut protocols = HashMap::new();
         for protocol in list {
             let mut cfg = RequestResponseConfig::default();
@@ -248,6 +271,8 @@ impl RequestResponsesBehaviour {
             pending_requests: Default::default(),
             pending_responses: Default::default(),
             pending_responses_arrival_time: Default::default(),
+            peer_bandwidth,
+            delayed_requests: Default::default(),
         })
     }
 
@@ -256,6 +281,8 @@ impl RequestResponsesBehaviour {
     /// If there is no established connection to the target peer, the behavior is determined by the choice of `connect`.
     ///
     /// An error is returned if the protocol doesn't match one that has been registered.
+    ///
+    /// The request is accounted to the upload limits, and delayed by the debt to them.
     pub fn send_request(
         &mut self,
         target: &PeerId,
@@ -263,6 +290,43 @@ impl RequestResponsesBehaviour {
         request: Vec<u8>,
         pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
         connect: IfDisconnected,
+    ) {
+        if let Some((protocol, _)) = self.protocols.get_mut(protocol_name) {
+            if protocol.is_connected(target) || connect.should_connect() {
+                let wait = self.peer_bandwidth.on_transfer(
+                    *target,
+                    protocol_name,
+                    TrafficClass::Sync,
+                    Direction::Outbound,
+                    request.len(),
+                );
+                if !wait.is_zero() {
+                    let delayed_request = DelayedRequest {
+                        target: *target,
+                        protocol_name: protocol_name.to_string(),
+                        request,
+                        pending_response,
+                        connect,
+                    };
+                    self.delayed_requests.push(Box::pin(async move {
+                        futures_timer::Delay::new(wait).await;
+                        delayed_request
+                    }));
+                    return;
+                }
+            }
+        }
+        self.dispatch_request(target, protocol_name, request, pending_response, connect);
+    }
+
+    /// Send the request accounted already.
+    fn dispatch_request(
+        &mut self,
+        target: &PeerId,
+        protocol_name: &str,
+        request: Vec<u8>,
+        pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+        connect: IfDisconnected,
     ) {
         if let Some((protocol, _)) = self.protocols.get_mut(protocol_name) {
             if protocol.is_connected(target) || connect.should_connect() {
@@ -482,6 +546,19 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
         params: &mut impl PollParameters,
     ) -> Poll<NetworkBehaviourAction<Self::OutEvent, Self::ConnectionHandler>> {
         'poll_all: loop {
+            // Send the requests delayed by the upload limits.
+            while let Poll::Ready(Some(delayed_request)) = self.delayed_requests.poll_next_unpin(cx)
+            {
+                let DelayedRequest {
+                    target,
+                    protocol_name,
+                    request,
+                    pending_response,
+                    connect,
+                } = delayed_request;
+                self.dispatch_request(&target, &protocol_name, request, pending_response, connect);
+            }
+
             // Poll to see if any response is ready to be sent back.
             while let Poll::Ready(Some(outcome)) = self.pending_responses.poll_next_unpin(cx) {
                 let RequestProcessingOutcome {
@@ -589,6 +666,16 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
                                 .insert((protocol.clone(), request_id).into(), Instant::now());
 
                             let (tx, rx) = oneshot::channel();
+                            // The request can not be delayed as it is received already, the
+                            // response is delayed instead, so a peer waiting for it before the
+                            // next request is throttled to the download limits.
+                            let request_wait = self.peer_bandwidth.on_transfer(
+                                peer,
+                                protocol,
+                                TrafficClass::Sync,
+                                Direction::Inbound,
+                                request.len(),
+                            );
 
                             // Submit the request to the "response builder" passed by the user at
                             // initialization.
@@ -606,11 +693,26 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
                             }
 
                             let protocol = protocol.clone();
+                            let peer_bandwidth = self.peer_bandwidth.clone();
                             self.pending_responses.push(Box::pin(async move {
                                 // The `tx` created above can be dropped if we are not capable of
                                 // processing this request, which is reflected as a
                                 // `InboundFailure::Omission` event.
                                 if let Ok(response) = rx.await {
+                                    // Delay the response to keep the peer under the limits.
+                                    let mut wait = request_wait;
+                                    if let Ok(payload) = response.result.as_ref() {
+                                        wait = wait.max(peer_bandwidth.on_transfer(
+                                            peer,
+                                            &protocol,
+                                            TrafficClass::Sync,
+                                            Direction::Outbound,
+                                            payload.len(),
+                                        ));
+                                    }
+                                    if !wait.is_zero() {
+                                        futures_timer::Delay::new(wait).await;
+                                    }
                                     Some(RequestProcessingOutcome {
                                         peer,
                                         request_id,
@@ -645,6 +747,13 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
                                 Some((started, pending_response)) => {
                                     let response_len =
                                         response.as_ref().map(|resp| resp.len()).unwrap_or(0);
+                                    self.peer_bandwidth.on_transfer(
+                                        peer,
+                                        protocol,
+                                        TrafficClass::Sync,
+                                        Direction::Inbound,
+                                        response_len,
+                                    );
                                     let delivered = pending_response
                                         .send(response.map_err(|()| RequestFailure::Refused))
                                         .map_err(|_| RequestFailure::Obsolete);
@@ -948,7 +1057,8 @@ mod tests {
             .multiplex(libp2p::yamux::YamuxConfig::default())
             .boxed();
 
-        let behaviour = RequestResponsesBehaviour::new(list).unwrap();
+        let behaviour =
+            RequestResponsesBehaviour::new(list, Arc::new(PeerBandwidth::default())).unwrap();
 
         let mut swarm =
             Swarm::with_threadpool_executor(transport, behaviour, keypair.public().to_peer_id());
@@ -37,13 +37,16 @@ use crate::business_layer_handle::BusinessLayerHandle;
 use crate::config::{Params, TransportConfig};
 use crate::discovery::DiscoveryConfig;
 use crate::errors::Error;
+#[cfg(feature = "link-conditions")]
+use crate::link_conditions::{LinkConditions, LinkFate};
 use crate::metrics::Metrics;
 use crate::network_state::{
     NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 };
+use crate::peer_bandwidth::PeerBandwidth;
 use crate::protocol::event::Event;
 use crate::protocol::generic_proto::{NotificationsSink, NotifsHandlerError, Ready};
-use crate::protocol::{Protocol, HARD_CORE_PROTOCOL_ID};
+use crate::protocol::Protocol;
 use crate::request_responses::{InboundFailure, OutboundFailure, RequestFailure, ResponseFailure};
 use crate::{
     behaviour::{Behaviour, BehaviourOut},
@@ -52,6 +55,7 @@ use crate::{
 use crate::{config, Multiaddr};
 use crate::{config::parse_str_addr, transport};
 use async_std::future;
+use bytes::Bytes;
 use futures::channel::oneshot::{Canceled, Receiver};
 use futures::{
     channel::{mpsc, oneshot},
@@ -70,7 +74,7 @@ use libp2p::{
     PeerId,
 };
 use log::{error, info, trace, warn};
-use network_p2p_types::IfDisconnected;
+use network_p2p_types::{Direction, IfDisconnected, TrafficClass};
 use parking_lot::Mutex;
 use sc_peerset::{peersstate, PeersetHandle, ReputationChange};
 use starcoin_metrics::{Histogram, HistogramVec};
@@ -112,9 +116,16 @@ pub struct NetworkService {
     local_peer_id: PeerId,
     /// Bandwidth logging system. Can be queried to know the average bandwidth consumed.
     bandwidth: Arc<transport::BandwidthSinks>,
+    /// Bandwidth of the peers by protocol, and the upload and download rate limits.
+    peer_bandwidth: Arc<PeerBandwidth>,
+    /// The simulated link conditions, installed by the network simulator.
+    #[cfg(feature = "link-conditions")]
+    link_conditions: once_cell::sync::OnceCell<Arc<LinkConditions>>,
     /// Peerset manager (PSM); manages the reputation of nodes and indicates the network which
     /// nodes it should be connected to or not.
     peerset: PeersetHandle,
+    /// Number of the peer sets, a set per notifications protocol.
+    num_sets: usize,
     /// For each peer and protocol combination, an object that allows sending notifications to
     /// that peer. Updated by the [`NetworkWorker`].
     peers_notifications_sinks: Arc<Mutex<HashMap<(PeerId, Cow<'static, str>), NotificationsSink>>>,
@@ -172,6 +183,11 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
             known_addresses.push((bootnode.peer_id, bootnode.multiaddr.clone()));
         }
 
+        // The networking has to know the addresses of the reserved nodes to connect them.
+        for reserved in params.network_config.reserved_nodes.iter() {
+            known_addresses.push((reserved.peer_id, reserved.multiaddr.clone()));
+        }
+
         let boot_node_ids = Arc::new(boot_node_ids);
 
         // Check for duplicate bootnodes.
@@ -199,7 +215,8 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
         let is_major_syncing = Arc::new(AtomicBool::new(false));
 
         let notif_protocols = params.network_config.notifications_protocols.clone();
-        let mut sets_conf = Vec::with_capacity(notif_protocols.len());
+        let num_sets = notif_protocols.len();
+        let mut sets_conf = Vec::with_capacity(num_sets);
         let s: Vec<PeerId> = params
             .network_config
             .reserved_nodes
@@ -232,6 +249,17 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
                 .collect(),
         )?;
 
+        let metrics = params
+            .metrics_registry
+            .as_ref()
+            .and_then(|registry| Metrics::register(registry).ok());
+        let peer_bandwidth = Arc::new(PeerBandwidth::new(
+            params.network_config.bandwidth_limits.clone(),
+            metrics
+                .as_ref()
+                .map(|metrics| metrics.peer_bandwidth_bytes_total.clone()),
+        ));
+
         // Build the swarm.
         let (mut swarm, bandwidth): (Swarm<Behaviour<T>>, _) = {
             let user_agent = format!(
@@ -270,6 +298,7 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
                 local_public,
                 discovery_config,
                 params.network_config.request_response_protocols,
+                peer_bandwidth.clone(),
             ) {
                 Ok(behaviour) => behaviour,
                 Err(crate::request_responses::RegisterError::DuplicateProtocol(proto)) => {
@@ -327,16 +356,16 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
         let external_addresses = Arc::new(Mutex::new(Vec::new()));
         let peers_notifications_sinks = Arc::new(Mutex::new(HashMap::new()));
 
-        let metrics = params
-            .metrics_registry
-            .as_ref()
-            .and_then(|registry| Metrics::register(registry).ok());
         let service = Arc::new(NetworkService {
             bandwidth,
+            peer_bandwidth,
+            #[cfg(feature = "link-conditions")]
+            link_conditions: Default::default(),
             external_addresses,
             num_connected,
             is_major_syncing,
             peerset: peerset_handle,
+            num_sets,
             local_peer_id,
             peers_notifications_sinks: peers_notifications_sinks.clone(),
             to_worker,
@@ -352,6 +381,7 @@ impl<T: BusinessLayerHandle + Send> NetworkWorker<T> {
             event_streams: out_events::OutChannels::new(params.metrics_registry.as_ref())?,
             metrics,
             unbans: Default::default(),
+            delayed_notifications: Default::default(),
             boot_node_ids,
             peers_notifications_sinks,
         })
@@ -551,10 +581,35 @@ impl NetworkService {
             }
         };
 
+        let latency = match self.notification_latency(&target) {
+            Some(latency) => latency,
+            None => return,
+        };
+
         // Used later for the metrics report.
         let message_len = message.len();
+        // The notification is delayed instead of dropped while the peer is over the upload
+        // limits, the new blocks must get through.
+        let wait =
+            self.peer_bandwidth
+                .wait_time(&target, TrafficClass::Gossip, Direction::Outbound);
+        self.peer_bandwidth.on_transfer(
+            target,
+            &protocol_name,
+            TrafficClass::Gossip,
+            Direction::Outbound,
+            message_len,
+        );
 
-        sink.send_sync_notification(message);
+        let delay = wait.saturating_add(latency);
+        if delay.is_zero() {
+            sink.send_sync_notification(message);
+        } else {
+            async_std::task::spawn(async move {
+                futures_timer::Delay::new(delay).await;
+                sink.send_sync_notification(message);
+            });
+        }
 
         if let Some(notifications_sizes_metric) = self.notifications_sizes_metric.as_ref() {
             notifications_sizes_metric
@@ -661,6 +716,24 @@ impl NetworkService {
         protocol_name: Cow<'static, str>,
         data: Vec<u8>,
     ) -> Result<(), NotificationSenderError> {
+        let wait =
+            self.peer_bandwidth
+                .wait_time(&target, TrafficClass::Gossip, Direction::Outbound);
+        if !wait.is_zero() {
+            futures_timer::Delay::new(wait).await;
+        }
+        match self.notification_latency(&target) {
+            Some(latency) if !latency.is_zero() => futures_timer::Delay::new(latency).await,
+            Some(_) => {}
+            None => return Ok(()),
+        }
+        self.peer_bandwidth.on_transfer(
+            target,
+            &protocol_name,
+            TrafficClass::Gossip,
+            Direction::Outbound,
+            data.len(),
+        );
         let sender = self.notification_sender(target, protocol_name)?;
         let ready_sender = sender.ready().await?;
         ready_sender.send(data)
@@ -773,6 +846,15 @@ impl NetworkService {
         request: Vec<u8>,
         connect: IfDisconnected,
     ) -> Result<Vec<u8>, RequestFailure> {
+        // Wait for the download of the former responses from the peer, the request is accounted
+        // and delayed by the upload limits in the request-response behaviour.
+        let wait = self
+            .peer_bandwidth
+            .wait_time(&target, TrafficClass::Sync, Direction::Inbound);
+        if !wait.is_zero() {
+            futures_timer::Delay::new(wait).await;
+        }
+        self.transmit_on_link(&self.local_peer_id, &target).await?;
         let (tx, rx) = oneshot::channel();
         let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::Request {
             target,
@@ -781,7 +863,12 @@ impl NetworkService {
             pending_response: tx,
             connect,
         });
-        match future::timeout(Duration::from_secs(REQUEST_RESPONSE_TIMEOUT_SECONDS), rx).await {
+        let response = match future::timeout(
+            Duration::from_secs(REQUEST_RESPONSE_TIMEOUT_SECONDS),
+            rx,
+        )
+        .await
+        {
             Ok(Ok(v)) => v,
             // The channel can only be closed if the network worker no longer exists. If the
             // network worker no longer exists, then all connections to `target` are necessarily
@@ -794,9 +881,68 @@ impl NetworkService {
                 error!("[network-p2p] request to worker failed: {}", e);
                 Err(RequestFailure::Network(OutboundFailure::ConnectionClosed))
             }
+        }?;
+        self.transmit_on_link(&target, &self.local_peer_id).await?;
+        Ok(response)
+    }
+
+    /// Install the simulated link conditions, they are shared by the nodes of a simulator.
+    /// Return false if the node has the conditions already.
+    #[cfg(feature = "link-conditions")]
+    pub fn set_link_conditions(&self, conditions: Arc<LinkConditions>) -> bool {
+        self.link_conditions.set(conditions).is_ok()
+    }
+
+    /// The latency of a notification to the peer by the simulated link conditions, `None` if the
+    /// notification is dropped.
+    #[cfg(feature = "link-conditions")]
+    fn notification_latency(&self, target: &PeerId) -> Option<Duration> {
+        match self.link_conditions.get() {
+            Some(conditions) => match conditions.transmit(&self.local_peer_id, target) {
+                LinkFate::Deliver(latency) => Some(latency),
+                LinkFate::Drop | LinkFate::Partitioned => None,
+            },
+            None => Some(Duration::ZERO),
         }
     }
 
+    #[cfg(not(feature = "link-conditions"))]
+    fn notification_latency(&self, _target: &PeerId) -> Option<Duration> {
+        Some(Duration::ZERO)
+    }
+
+    /// Delay a request or response on the simulated link by the latency, a dropped message times
+    /// out.
+    #[cfg(feature = "link-conditions")]
+    async fn transmit_on_link(&self, from: &PeerId, to: &PeerId) -> Result<(), RequestFailure> {
+        let fate = match self.link_conditions.get() {
+            Some(conditions) => conditions.transmit(from, to),
+            None => return Ok(()),
+        };
+        match fate {
+            LinkFate::Deliver(latency) => {
+                if !latency.is_zero() {
+                    futures_timer::Delay::new(latency).await;
+                }
+                Ok(())
+            }
+            LinkFate::Drop => Err(RequestFailure::Network(OutboundFailure::Timeout)),
+            LinkFate::Partitioned => {
+                Err(RequestFailure::Network(OutboundFailure::ConnectionClosed))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "link-conditions"))]
+    async fn transmit_on_link(&self, _from: &PeerId, _to: &PeerId) -> Result<(), RequestFailure> {
+        Ok(())
+    }
+
+    /// Bandwidth of the peers by protocol, and the upload and download rate limits.
+    pub fn peer_bandwidth(&self) -> &Arc<PeerBandwidth> {
+        &self.peer_bandwidth
+    }
+
     /// Report a given peer as either beneficial (+) or costly (-) according to the
     /// given scalar.
     pub fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange) {
@@ -807,6 +953,11 @@ impl NetworkService {
         self.peerset.reputations(reputation_threshold)
     }
 
+    /// Set the reputation of a peer, the reports before and after it are applied in order.
+    pub fn set_reputation(&self, who: PeerId, reputation: i32) {
+        self.peerset.set_reputation(who, reputation);
+    }
+
     /// Disconnect from a node as soon as possible.
     ///
     /// This triggers the same effects as if the connection had closed itself spontaneously.
@@ -843,32 +994,50 @@ impl NetworkService {
 
     /// Connect to unreserved peers and allow unreserved peers to connect.
     pub fn accept_unreserved_peers(&self) {
-        self.peerset.set_reserved_only(HARD_CORE_PROTOCOL_ID, false);
+        for set_id in self.peer_sets() {
+            self.peerset.set_reserved_only(set_id, false);
+        }
     }
 
     /// Disconnect from unreserved peers and deny new unreserved peers to connect.
     pub fn deny_unreserved_peers(&self) {
-        self.peerset.set_reserved_only(HARD_CORE_PROTOCOL_ID, true);
+        for set_id in self.peer_sets() {
+            self.peerset.set_reserved_only(set_id, true);
+        }
     }
 
     /// Removes a `PeerId` from the list of reserved peers.
     pub fn remove_reserved_peer(&self, peer: PeerId) {
-        self.peerset
-            .remove_reserved_peer(HARD_CORE_PROTOCOL_ID, peer);
+        for set_id in self.peer_sets() {
+            self.peerset.remove_reserved_peer(set_id, peer);
+        }
     }
 
     /// Adds a `PeerId` and its address as reserved. The string should encode the address
     /// and peer ID of the remote node.
     pub fn add_reserved_peer(&self, peer: String) -> Result<(), String> {
         let (peer_id, addr) = parse_str_addr(&peer).map_err(|e| format!("{:?}", e))?;
-        self.peerset
-            .add_reserved_peer(HARD_CORE_PROTOCOL_ID, peer_id);
+        for set_id in self.peer_sets() {
+            self.peerset.add_reserved_peer(set_id, peer_id);
+        }
         let _ = self
             .to_worker
             .unbounded_send(ServiceToWorkerMsg::AddKnownAddress(peer_id, addr));
         Ok(())
     }
 
+    /// Adds an address of a peer to the discovery, the peer is then discovered by the peerset.
+    pub fn add_known_address(&self, peer_id: PeerId, addr: Multiaddr) {
+        let _ = self
+            .to_worker
+            .unbounded_send(ServiceToWorkerMsg::AddKnownAddress(peer_id, addr));
+    }
+
+    /// The reserved peers are reserved in all the peer sets, so they keep all the protocols open.
+    fn peer_sets(&self) -> impl Iterator<Item = sc_peerset::SetId> {
+        (0..self.num_sets).map(sc_peerset::SetId::from)
+    }
+
     /// Returns the number of peers we're connected to.
     pub fn num_connected(&self) -> usize {
         self.num_connected.load(Ordering::Relaxed)
@@ -1027,6 +1196,11 @@ pub struct NetworkWorker<T: 'static + BusinessLayerHandle + Send> {
     /// Prometheus network metrics.
     metrics: Option<Metrics>,
     unbans: stream::FuturesUnordered<Pin<Box<dyn Future<Output = PeerId> + Send>>>,
+    /// The notifications received from the peers over the download limits, delivered after the
+    /// debt is paid.
+    delayed_notifications: stream::FuturesUnordered<
+        Pin<Box<dyn Future<Output = (PeerId, Vec<(Cow<'static, str>, Bytes)>)> + Send>>,
+    >,
     /// The `PeerId`'s of all boot nodes.
     boot_node_ids: Arc<HashSet<PeerId>>,
     /// For each peer, an object that allows sending notifications to
@@ -1351,8 +1525,35 @@ impl<T: BusinessLayerHandle + Send> Future for NetworkWorker<T> {
                                 .observe(message.len() as f64);
                         }
                     }
-                    this.event_streams
-                        .send(Event::NotificationsReceived { remote, messages });
+                    // The bytes are received already, the notifications of a peer over the
+                    // download limits are deprioritized, they are delivered after the debt is
+                    // paid but never dropped.
+                    let peer_bandwidth = &this.service.peer_bandwidth;
+                    let wait =
+                        peer_bandwidth.wait_time(&remote, TrafficClass::Gossip, Direction::Inbound);
+                    for (protocol, message) in &messages {
+                        peer_bandwidth.on_transfer(
+                            remote,
+                            protocol,
+                            TrafficClass::Gossip,
+                            Direction::Inbound,
+                            message.len(),
+                        );
+                    }
+                    if wait.is_zero() {
+                        this.event_streams
+                            .send(Event::NotificationsReceived { remote, messages });
+                    } else {
+                        log::debug!(
+                            target: "sub-libp2p",
+                            "Delay notifications over the download limits: {}, {:?}",
+                            remote, wait,
+                        );
+                        this.delayed_notifications.push(Box::pin(async move {
+                            futures_timer::Delay::new(wait).await;
+                            (remote, messages)
+                        }));
+                    }
                 }
 
                 Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::Dht(event, duration))) => {
@@ -1423,6 +1624,9 @@ impl<T: BusinessLayerHandle + Send> Future for NetworkWorker<T> {
                             metrics.distinct_peers_connections_closed_total.inc();
                         }
                     }
+                    if num_established == 0 {
+                        this.service.peer_bandwidth.remove_peer(&peer_id);
+                    }
                 }
                 Poll::Ready(SwarmEvent::NewListenAddr { address, .. }) => {
                     trace!(target: "sub-libp2p", "Libp2p => NewListenAddr({})", address)
@@ -1570,6 +1774,12 @@ impl<T: BusinessLayerHandle + Send> Future for NetworkWorker<T> {
         while let Poll::Ready(Some(peer_id)) = Pin::new(&mut this.unbans).poll_next(cx) {
             this.network_service.unban_peer_id(peer_id);
         }
+        while let Poll::Ready(Some((remote, messages))) =
+            Pin::new(&mut this.delayed_notifications).poll_next(cx)
+        {
+            this.event_streams
+                .send(Event::NotificationsReceived { remote, messages });
+        }
         Poll::Pending
     }
 }
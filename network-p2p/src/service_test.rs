@@ -12,7 +12,7 @@ use bcs_ext::BCSCodec;
 use futures::prelude::*;
 use futures::stream::StreamExt;
 use libp2p::PeerId;
-use network_p2p_types::MultiaddrWithPeerId;
+use network_p2p_types::{Direction, MultiaddrWithPeerId, TrafficClass};
 use once_cell::sync::Lazy;
 use sc_peerset::ReputationChange;
 use serde::{Deserialize, Serialize};
@@ -143,6 +143,22 @@ fn build_nodes_one_proto() -> (
     impl Stream<Item = Event>,
     Arc<NetworkService>,
     impl Stream<Item = Event>,
+) {
+    build_nodes_one_proto_with_limits(
+        config::BandwidthLimits::default(),
+        config::BandwidthLimits::default(),
+    )
+}
+
+/// Builds two connected nodes like `build_nodes_one_proto`, with the bandwidth limits.
+fn build_nodes_one_proto_with_limits(
+    node1_limits: config::BandwidthLimits,
+    node2_limits: config::BandwidthLimits,
+) -> (
+    Arc<NetworkService>,
+    impl Stream<Item = Event>,
+    Arc<NetworkService>,
+    impl Stream<Item = Event>,
 ) {
     let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
 
@@ -151,6 +167,7 @@ fn build_nodes_one_proto() -> (
         notifications_protocols: vec![From::from(PROTOCOL_NAME)],
         listen_addresses: vec![listen_addr.clone()],
         transport: config::TransportConfig::MemoryOnly,
+        bandwidth_limits: node1_limits,
         ..config::NetworkConfiguration::new_local()
     });
 
@@ -163,6 +180,7 @@ fn build_nodes_one_proto() -> (
             peer_id: node1.local_peer_id(),
         }],
         transport: config::TransportConfig::MemoryOnly,
+        bandwidth_limits: node2_limits,
         ..config::NetworkConfiguration::new_local()
     });
 
@@ -290,6 +308,123 @@ async fn notifications_back_pressure() {
     receiver.await.unwrap();
 }
 
+/// Wait until the notification stream from node 1 to node 2 is open.
+async fn wait_notification_stream_opened(events_stream: &mut (impl Stream<Item = Event> + Unpin)) {
+    loop {
+        if let NotificationStreamOpened { .. } = events_stream.next().await.unwrap() {
+            break;
+        }
+    }
+}
+
+/// Receive the notifications until none comes in 2 seconds, return the count and the time the
+/// last one is received after the start.
+async fn receive_notifications(
+    events_stream: &mut (impl Stream<Item = Event> + Unpin),
+    start: std::time::Instant,
+) -> (usize, Duration) {
+    let mut received = 0;
+    let mut last_received = Duration::ZERO;
+    while let std::result::Result::Ok(event) =
+        tokio::time::timeout(Duration::from_secs(2), events_stream.next()).await
+    {
+        if let Some(Event::NotificationsReceived { messages, .. }) = event {
+            received += messages.len();
+            last_received = start.elapsed();
+        }
+    }
+    (received, last_received)
+}
+
+/// Node 1 writes 3 notifications of 800 bytes to node 2, the third one is over the limit of
+/// 1000 bytes per second, return the notifications node 2 receives and the time of the last.
+async fn notifications_under_limits(
+    node1_limits: config::BandwidthLimits,
+    node2_limits: config::BandwidthLimits,
+) -> (Arc<NetworkService>, Arc<NetworkService>, usize, Duration) {
+    let (node1, mut events_stream1, node2, mut events_stream2) =
+        build_nodes_one_proto_with_limits(node1_limits, node2_limits);
+    wait_notification_stream_opened(&mut events_stream1).await;
+    let start = std::time::Instant::now();
+    for _ in 0..3 {
+        node1.write_notification(
+            node2.local_peer_id(),
+            From::from(PROTOCOL_NAME),
+            vec![0; 800],
+        );
+    }
+    let (received, last_received) = receive_notifications(&mut events_stream2, start).await;
+    (node1, node2, received, last_received)
+}
+
+#[stest::test]
+async fn test_notification_bandwidth_limits() {
+    let limit = config::BandwidthLimits {
+        peer_upload: Some(1000),
+        peer_download: Some(1000),
+        ..Default::default()
+    };
+
+    // the notification over the upload limit is delayed by the sender.
+    let (node1, node2, received, last_received) =
+        notifications_under_limits(limit.clone(), config::BandwidthLimits::default()).await;
+    assert_eq!(received, 3);
+    assert!(last_received > Duration::from_millis(500));
+    let stats = node1
+        .peer_bandwidth()
+        .peer_stats(&node2.local_peer_id())
+        .unwrap();
+    assert_eq!(stats.outbound_bytes, 2400);
+
+    // the notification over the download limit is delayed by the receiver.
+    let (node1, node2, received, last_received) =
+        notifications_under_limits(config::BandwidthLimits::default(), limit).await;
+    assert_eq!(received, 3);
+    assert!(last_received > Duration::from_millis(500));
+    let stats = node2
+        .peer_bandwidth()
+        .peer_stats(&node1.local_peer_id())
+        .unwrap();
+    assert_eq!(stats.inbound_bytes, 2400);
+}
+
+#[stest::test]
+async fn test_sync_traffic_not_delay_notifications() {
+    let limit = config::BandwidthLimits {
+        peer_upload: Some(1000),
+        peer_download: Some(1000),
+        ..Default::default()
+    };
+    let (node1, mut events_stream1, node2, mut events_stream2) =
+        build_nodes_one_proto_with_limits(limit.clone(), limit);
+    wait_notification_stream_opened(&mut events_stream1).await;
+
+    // the sync traffic between the nodes is far over the limits in both directions.
+    for (node, remote) in [(&node1, &node2), (&node2, &node1)] {
+        for direction in [Direction::Outbound, Direction::Inbound] {
+            let wait = node.peer_bandwidth().on_transfer(
+                remote.local_peer_id(),
+                "get_block",
+                TrafficClass::Sync,
+                direction,
+                60_000,
+            );
+            assert!(wait > Duration::from_secs(50));
+        }
+    }
+
+    // the block announcement still gets through in time.
+    let start = std::time::Instant::now();
+    node1.write_notification(
+        node2.local_peer_id(),
+        From::from(PROTOCOL_NAME),
+        vec![0; 500],
+    );
+    let (received, last_received) = receive_notifications(&mut events_stream2, start).await;
+    assert_eq!(received, 1);
+    assert!(last_received < Duration::from_secs(1));
+}
+
 #[test]
 #[should_panic(expected = "don't match the transport")]
 fn ensure_listen_addresses_consistent_with_transport_memory() {
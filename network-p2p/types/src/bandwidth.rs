@@ -0,0 +1,96 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types of the per peer and per protocol bandwidth accounting and rate limits.
+
+use schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default percent of the global bandwidth the sync traffic can use.
+pub const DEFAULT_SYNC_BANDWIDTH_SHARE: u8 = 80;
+
+/// Upload and download rate limits in bytes per second, `None` means unlimited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BandwidthLimits {
+    pub peer_upload: Option<u64>,
+    pub peer_download: Option<u64>,
+    pub global_upload: Option<u64>,
+    pub global_download: Option<u64>,
+    /// Percent of the global limits the sync traffic can use, the rest is kept for the gossip,
+    /// so the block and txn propagation is not starved by a sync.
+    pub sync_share: u8,
+}
+
+impl Default for BandwidthLimits {
+    fn default() -> Self {
+        Self {
+            peer_upload: None,
+            peer_download: None,
+            global_upload: None,
+            global_download: None,
+            sync_share: DEFAULT_SYNC_BANDWIDTH_SHARE,
+        }
+    }
+}
+
+/// The traffic classes scheduled by the bandwidth limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+    /// Network rpc requests and responses, mostly for the sync.
+    Sync,
+    /// Notifications, such as the new block and txn announcements.
+    Gossip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inbound => "in",
+            Self::Outbound => "out",
+        }
+    }
+}
+
+/// Transferred bytes of a protocol.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProtocolBandwidth {
+    pub inbound_bytes: u64,
+    pub outbound_bytes: u64,
+}
+
+impl ProtocolBandwidth {
+    pub fn add(&mut self, direction: Direction, bytes: u64) {
+        match direction {
+            Direction::Inbound => self.inbound_bytes = self.inbound_bytes.saturating_add(bytes),
+            Direction::Outbound => self.outbound_bytes = self.outbound_bytes.saturating_add(bytes),
+        }
+    }
+}
+
+/// Transferred bytes with a peer, in total and by the notification and rpc protocols.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PeerBandwidthStats {
+    pub inbound_bytes: u64,
+    pub outbound_bytes: u64,
+    pub protocols: BTreeMap<String, ProtocolBandwidth>,
+}
+
+impl PeerBandwidthStats {
+    pub fn add(&mut self, protocol: &str, direction: Direction, bytes: u64) {
+        self.protocols
+            .entry(protocol.to_string())
+            .or_default()
+            .add(direction, bytes);
+        match direction {
+            Direction::Inbound => self.inbound_bytes = self.inbound_bytes.saturating_add(bytes),
+            Direction::Outbound => self.outbound_bytes = self.outbound_bytes.saturating_add(bytes),
+        }
+    }
+}
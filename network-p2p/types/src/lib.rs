@@ -5,10 +5,14 @@ use libp2p::futures::channel::oneshot;
 use std::borrow::Cow;
 use std::fmt;
 
+pub mod bandwidth;
 pub mod multi_address_with_peer_id;
 pub mod network_state;
 pub mod peer_id;
 
+pub use bandwidth::{
+    BandwidthLimits, Direction, PeerBandwidthStats, ProtocolBandwidth, TrafficClass,
+};
 pub use libp2p::core::{identity, multiaddr, Multiaddr, PeerId, PublicKey};
 pub use libp2p::request_response::{InboundFailure, OutboundFailure};
 pub use libp2p::{build_multiaddr, multihash};
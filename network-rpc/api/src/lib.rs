@@ -18,6 +18,7 @@ use starcoin_types::access_path::AccessPath;
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::account_state::AccountState;
 use starcoin_types::block::{Block, BlockHeader, BlockInfo, BlockNumber};
+use starcoin_types::compact_block::CompactBlock;
 use starcoin_types::transaction::{SignedUserTransaction, Transaction, TransactionInfo};
 use starcoin_vm_types::state_store::table::TableInfo;
 
@@ -282,6 +283,13 @@ pub trait NetworkRpc: Sized + Send + Sync + 'static {
         ids: Vec<HashValue>,
     ) -> BoxFuture<Result<Vec<Option<Block>>>>;
 
+    ///Get the compact blocks, for fill the announced blocks by the txpool.
+    fn get_compact_blocks(
+        &self,
+        peer_id: PeerId,
+        ids: Vec<HashValue>,
+    ) -> BoxFuture<Result<Vec<Option<CompactBlock>>>>;
+
     fn get_state_with_table_item_proof(
         &self,
         peer_id: PeerId,
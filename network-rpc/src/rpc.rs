@@ -23,6 +23,7 @@ use starcoin_storage::Store;
 use starcoin_txpool::TxPoolService;
 use starcoin_txpool_api::TxPoolSyncService;
 use starcoin_types::block::Block;
+use starcoin_types::compact_block::CompactBlock;
 use starcoin_types::{
     account_state::AccountState,
     block::{BlockHeader, BlockInfo, BlockNumber},
@@ -317,4 +318,27 @@ impl gen_server::NetworkRpc for NetworkRpcImpl {
         };
         Box::pin(fut)
     }
+
+    fn get_compact_blocks(
+        &self,
+        _peer_id: PeerId,
+        ids: Vec<HashValue>,
+    ) -> BoxFuture<Result<Vec<Option<CompactBlock>>>> {
+        let chain_service = self.chain_service.clone();
+        let fut = async move {
+            if ids.len() as u64 > MAX_BLOCK_REQUEST_SIZE {
+                return Err(NetRpcError::client_err(format!(
+                    "max block ids size > {}",
+                    MAX_BLOCK_REQUEST_SIZE
+                ))
+                .into());
+            }
+            let blocks = chain_service.get_blocks(ids).await?;
+            Ok(blocks
+                .into_iter()
+                .map(|block| block.map(CompactBlock::from))
+                .collect())
+        };
+        Box::pin(fut)
+    }
 }
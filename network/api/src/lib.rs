@@ -16,6 +16,7 @@ use std::sync::mpsc::TrySendError;
 pub mod messages;
 mod peer_message_handler;
 mod peer_provider;
+mod peer_record;
 pub mod peer_score;
 #[cfg(test)]
 mod tests;
@@ -26,6 +27,7 @@ pub use network_p2p_types::{ReputationChange, BANNED_THRESHOLD};
 pub use peer_message_handler::PeerMessageHandler;
 pub use peer_provider::PeerDetail;
 pub use peer_provider::{PeerProvider, PeerSelector, PeerStrategy};
+pub use peer_record::{PeerRecord, PERMANENT_BAN};
 
 use futures::channel::oneshot::Receiver;
 pub use network_p2p_types::peer_id::PeerId;
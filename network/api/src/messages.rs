@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::ReputationChange;
+use crate::{PeerRecord, ReputationChange};
 use anyhow::*;
 use bcs_ext::{BCSCodec, Sample};
 use futures::channel::oneshot::Receiver;
@@ -10,7 +10,7 @@ use network_types::peer_info::PeerInfo;
 use serde::{Deserialize, Serialize};
 use starcoin_crypto::HashValue;
 use starcoin_service_registry::ServiceRequest;
-use starcoin_types::block::BlockInfo;
+use starcoin_types::block::{BlockHeader, BlockInfo};
 use starcoin_types::compact_block::CompactBlock;
 use starcoin_types::startup_info::ChainInfo;
 use starcoin_types::transaction::SignedUserTransaction;
@@ -21,6 +21,7 @@ use std::result::Result::Ok;
 pub const TXN_PROTOCOL_NAME: &str = "/starcoin/txn/1";
 pub const BLOCK_PROTOCOL_NAME: &str = "/starcoin/block/1";
 pub const ANNOUNCEMENT_PROTOCOL_NAME: &str = "/starcoin/announcement/1";
+pub const BLOCK_ANNOUNCEMENT_PROTOCOL_NAME: &str = "/starcoin/block_announcement/1";
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TransactionsMessage {
@@ -65,6 +66,26 @@ impl Sample for CompactBlockMessage {
     }
 }
 
+/// Message of sending or receive a new block header, the peer fetches the compact block by rpc
+/// if it does not have the block.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BlockAnnouncement {
+    pub header: BlockHeader,
+    pub block_info: BlockInfo,
+}
+
+impl BlockAnnouncement {
+    pub fn new(header: BlockHeader, block_info: BlockInfo) -> Self {
+        Self { header, block_info }
+    }
+}
+
+impl Sample for BlockAnnouncement {
+    fn sample() -> Self {
+        Self::new(BlockHeader::sample(), BlockInfo::sample())
+    }
+}
+
 pub enum AnnouncementType {
     Txn,
 }
@@ -123,6 +144,7 @@ pub enum NotificationMessage {
     Transactions(TransactionsMessage),
     CompactBlock(Box<CompactBlockMessage>),
     Announcement(Announcement),
+    BlockAnnouncement(Box<BlockAnnouncement>),
 }
 
 impl NotificationMessage {
@@ -137,6 +159,9 @@ impl NotificationMessage {
             ANNOUNCEMENT_PROTOCOL_NAME => {
                 NotificationMessage::Announcement(Announcement::decode(bytes)?)
             }
+            BLOCK_ANNOUNCEMENT_PROTOCOL_NAME => {
+                NotificationMessage::BlockAnnouncement(Box::new(BlockAnnouncement::decode(bytes)?))
+            }
             unknown_protocol => bail!(
                 "Unknown protocol {}'s message: {}",
                 unknown_protocol,
@@ -152,6 +177,9 @@ impl NotificationMessage {
             NotificationMessage::Announcement(msg) => {
                 (ANNOUNCEMENT_PROTOCOL_NAME.into(), msg.encode()?)
             }
+            NotificationMessage::BlockAnnouncement(msg) => {
+                (BLOCK_ANNOUNCEMENT_PROTOCOL_NAME.into(), msg.encode()?)
+            }
         })
     }
 
@@ -160,6 +188,7 @@ impl NotificationMessage {
             Self::Transactions(_) => TXN_PROTOCOL_NAME.into(),
             Self::CompactBlock(_) => BLOCK_PROTOCOL_NAME.into(),
             Self::Announcement(_) => ANNOUNCEMENT_PROTOCOL_NAME.into(),
+            Self::BlockAnnouncement(_) => BLOCK_ANNOUNCEMENT_PROTOCOL_NAME.into(),
         }
     }
 
@@ -169,6 +198,7 @@ impl NotificationMessage {
             BLOCK_PROTOCOL_NAME.into(),
             TXN_PROTOCOL_NAME.into(),
             ANNOUNCEMENT_PROTOCOL_NAME.into(),
+            BLOCK_ANNOUNCEMENT_PROTOCOL_NAME.into(),
         ]
     }
 
@@ -192,6 +222,13 @@ impl NotificationMessage {
             _ => None,
         }
     }
+
+    pub fn into_block_announcement(self) -> Option<BlockAnnouncement> {
+        match self {
+            NotificationMessage::BlockAnnouncement(message) => Some(*message),
+            _ => None,
+        }
+    }
 }
 
 /// Message for send or receive from peer
@@ -223,6 +260,13 @@ impl PeerMessage {
         Self::new(peer_id, NotificationMessage::Announcement(announcement))
     }
 
+    pub fn new_block_announcement(peer_id: PeerId, announcement: BlockAnnouncement) -> Self {
+        Self::new(
+            peer_id,
+            NotificationMessage::BlockAnnouncement(Box::new(announcement)),
+        )
+    }
+
     pub fn into_transactions(self) -> Option<PeerTransactionsMessage> {
         let peer_id = self.peer_id;
         self.notification
@@ -243,6 +287,13 @@ impl PeerMessage {
             .into_announcement()
             .map(|message| PeerAnnouncementMessage { peer_id, message })
     }
+
+    pub fn into_block_announcement(self) -> Option<PeerBlockAnnouncementMessage> {
+        let peer_id = self.peer_id;
+        self.notification
+            .into_block_announcement()
+            .map(|message| PeerBlockAnnouncementMessage { peer_id, message })
+    }
 }
 
 impl ServiceRequest for PeerMessage {
@@ -309,6 +360,26 @@ impl Into<PeerMessage> for PeerAnnouncementMessage {
     }
 }
 
+/// Message for combine PeerId and BlockAnnouncement
+#[derive(Clone, Debug)]
+pub struct PeerBlockAnnouncementMessage {
+    pub peer_id: PeerId,
+    pub message: BlockAnnouncement,
+}
+
+impl PeerBlockAnnouncementMessage {
+    pub fn new(peer_id: PeerId, message: BlockAnnouncement) -> Self {
+        Self { peer_id, message }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<PeerMessage> for PeerBlockAnnouncementMessage {
+    fn into(self) -> PeerMessage {
+        PeerMessage::new_block_announcement(self.peer_id, self.message)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum PeerEvent {
     Open(PeerId, Box<ChainInfo>),
@@ -328,6 +399,41 @@ pub struct BanPeer {
     pub ban: bool,
 }
 
+/// Get the records of the peer store.
+#[derive(Clone, Debug)]
+pub struct GetPeerRecords;
+
+impl ServiceRequest for GetPeerRecords {
+    type Response = Vec<PeerRecord>;
+}
+
+/// Insert or replace a record of the peer store, and apply it to the network.
+#[derive(Clone, Debug)]
+pub struct PutPeerRecord {
+    pub record: PeerRecord,
+}
+
+impl ServiceRequest for PutPeerRecord {
+    type Response = Result<()>;
+}
+
+/// Remove a record from the peer store and lift its ban and reputation, the removed record is
+/// returned.
+#[derive(Clone, Debug)]
+pub struct RemovePeerRecord {
+    pub peer_id: PeerId,
+}
+
+impl ServiceRequest for RemovePeerRecord {
+    type Response = Option<PeerRecord>;
+}
+
+/// Save the sync scores of the peers to the peer store, so they are restored after restart.
+#[derive(Clone, Debug)]
+pub struct UpdatePeerScores {
+    pub scores: Vec<(PeerId, u64)>,
+}
+
 #[derive(Clone, Debug)]
 pub struct PeerReputations {
     pub threshold: i32,
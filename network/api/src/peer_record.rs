@@ -0,0 +1,55 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use network_p2p_types::peer_id::PeerId;
+use network_p2p_types::Multiaddr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The ban expiry of a peer banned until it is unbanned manually.
+pub const PERMANENT_BAN: u64 = u64::MAX;
+
+/// What the node remembers about a peer across restarts.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    /// Known addresses of the peer.
+    #[serde(default)]
+    pub addresses: Vec<Multiaddr>,
+    /// Unix timestamp in seconds of the latest time the peer was connected.
+    #[serde(default)]
+    pub last_seen: u64,
+    /// Reputation of the peer in the peerset.
+    #[serde(default)]
+    pub reputation: i32,
+    /// Unix timestamp in seconds when the ban of the peer expires, `PERMANENT_BAN` for a manual ban.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ban_expiry: Option<u64>,
+    /// The network rpc protocols the peer supports.
+    #[serde(default)]
+    pub rpc_protocols: Vec<String>,
+    /// Score of the peer in the sync `PeerSelector`, earned by serving the sync rpc requests,
+    /// 0 if the peer never served the sync.
+    #[serde(default)]
+    pub score: u64,
+}
+
+impl PeerRecord {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            addresses: vec![],
+            last_seen: 0,
+            reputation: 0,
+            ban_expiry: None,
+            rpc_protocols: vec![],
+            score: 0,
+        }
+    }
+
+    pub fn is_banned(&self, now_secs: u64) -> bool {
+        self.ban_expiry
+            .map(|expiry| expiry > now_secs)
+            .unwrap_or(false)
+    }
+}
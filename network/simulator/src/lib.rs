@@ -0,0 +1,390 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A multi-node network simulator for the integration tests of the sync and block relay.
+//!
+//! The nodes run in process on the memory transport, with the mock time service of the test
+//! network. A test injects the latency, message loss and partitions between the nodes by the
+//! [`LinkCondition`]s, and asserts on the chain heads, reorgs and txpool convergence.
+//! The link conditions are owned by the simulator and installed to its nodes only.
+//! The message loss is decided by the seeded rng of every link, so a test with the same seed drops
+//! the same messages of the same message sequence, but the delayed messages are sent by timers,
+//! so a test with latency is not deterministic.
+//!
+//! The waits are driven by the events of the nodes, the new heads, reorgs, peer events and txpool
+//! changes, a wait that does not hold before its timeout returns an error.
+
+use anyhow::{ensure, format_err, Result};
+use futures::executor::block_on;
+use futures::{Stream, StreamExt};
+use network_api::messages::PeerEvent;
+use network_api::{PeerId, PeerProvider};
+pub use network_p2p::link_conditions::LinkCondition;
+use network_p2p::link_conditions::LinkConditions;
+use starcoin_chain_service::ChainAsyncService;
+use starcoin_config::{NodeConfig, TimeService};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_node::NodeHandle;
+use starcoin_service_registry::bus::Bus;
+use starcoin_txpool_api::TxPoolSyncService;
+use starcoin_types::block::{Block, BlockHeader};
+use starcoin_types::system_events::{ChainReorg, NewHeadBlock};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use test_helper::run_node_by_config;
+
+/// Timeout of the nodes to connect each other on start.
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Count of the events of all the nodes, a waiter rechecks its condition on every change.
+#[derive(Default)]
+struct Events {
+    count: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl Events {
+    fn count(&self) -> u64 {
+        *self.count.lock().expect("events lock poisoned")
+    }
+
+    fn notify(&self) {
+        *self.count.lock().expect("events lock poisoned") += 1;
+        self.changed.notify_all();
+    }
+
+    /// Block until an event happens after the `seen` count, return false if the deadline passed.
+    fn wait(&self, seen: u64, deadline: Instant) -> bool {
+        let count = self.count.lock().expect("events lock poisoned");
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let (_count, result) = self
+            .changed
+            .wait_timeout_while(count, timeout, |count| *count == seen)
+            .expect("events lock poisoned");
+        !result.timed_out()
+    }
+}
+
+/// Consume the stream in a thread, and notify the events on every item.
+fn listen<S, F>(stream: S, events: Arc<Events>, mut on_item: F)
+where
+    S: Stream + Unpin + Send + 'static,
+    F: FnMut(S::Item) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        block_on(stream.for_each(|item| {
+            on_item(item);
+            events.notify();
+            futures::future::ready(())
+        }))
+    });
+}
+
+pub struct SimNode {
+    handle: NodeHandle,
+    peer_id: PeerId,
+    reorgs: Arc<Mutex<Vec<ChainReorg>>>,
+}
+
+impl SimNode {
+    fn start(
+        config: NodeConfig,
+        conditions: Arc<LinkConditions>,
+        events: Arc<Events>,
+    ) -> Result<Self> {
+        let peer_id = config.network.self_peer_id();
+        let handle = run_node_by_config(Arc::new(config))?;
+        ensure!(
+            handle
+                .network()
+                .network_service()
+                .set_link_conditions(conditions),
+            "link conditions of node {} are installed already",
+            peer_id
+        );
+        let bus = handle.bus()?;
+        let reorgs = Arc::new(Mutex::new(vec![]));
+        let reorgs_clone = reorgs.clone();
+        listen(
+            block_on(bus.channel::<ChainReorg>())?,
+            events.clone(),
+            move |reorg| {
+                reorgs_clone
+                    .lock()
+                    .expect("reorgs lock poisoned")
+                    .push(reorg)
+            },
+        );
+        listen(
+            block_on(bus.channel::<NewHeadBlock>())?,
+            events.clone(),
+            |_| {},
+        );
+        listen(
+            block_on(bus.channel::<PeerEvent>())?,
+            events.clone(),
+            |_| {},
+        );
+        listen(handle.txpool().subscribe_txns(), events, |_| {});
+        Ok(Self {
+            handle,
+            peer_id,
+            reorgs,
+        })
+    }
+
+    pub fn handle(&self) -> &NodeHandle {
+        &self.handle
+    }
+
+    pub fn config(&self) -> Arc<NodeConfig> {
+        self.handle.config()
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id.clone()
+    }
+
+    pub fn time_service(&self) -> Arc<dyn TimeService> {
+        self.config().net().time_service()
+    }
+
+    pub fn head(&self) -> Result<Block> {
+        let chain_service = self.handle.chain_service()?;
+        block_on(async { chain_service.main_head_block().await })
+    }
+
+    pub fn generate_block(&self) -> Result<Block> {
+        self.handle.generate_block()
+    }
+
+    /// The reorgs of the main chain since the node start.
+    pub fn reorgs(&self) -> Vec<ChainReorg> {
+        self.reorgs.lock().expect("reorgs lock poisoned").clone()
+    }
+
+    /// Hashes of the pending txns in the txpool.
+    pub fn pending_txns(&self) -> BTreeSet<HashValue> {
+        self.handle
+            .txpool()
+            .get_pending_txns(None, None)
+            .into_iter()
+            .map(|txn| txn.id())
+            .collect()
+    }
+
+    pub fn is_connected(&self, peer_id: PeerId) -> bool {
+        block_on(self.handle.network().is_connected(peer_id))
+    }
+}
+
+pub struct Simulator {
+    nodes: Vec<SimNode>,
+    seed: u64,
+    conditions: Arc<LinkConditions>,
+    events: Arc<Events>,
+    /// The node pairs separated by a partition.
+    partitioned: BTreeSet<(usize, usize)>,
+}
+
+impl Simulator {
+    /// Start `num_nodes` nodes, every node connects to the former nodes, and wait them connected.
+    /// The miner clients are disabled, the blocks are only generated by [`SimNode::generate_block`].
+    pub fn start(num_nodes: usize, seed: u64) -> Result<Self> {
+        ensure!(num_nodes > 0, "simulator should have nodes");
+        let conditions = Arc::new(LinkConditions::default());
+        let events = Arc::new(Events::default());
+        let mut nodes: Vec<SimNode> = Vec::with_capacity(num_nodes);
+        for index in 0..num_nodes {
+            let mut config = NodeConfig::random_for_test();
+            config.miner.disable_miner_client = Some(true);
+            config.network.seeds = nodes
+                .iter()
+                .map(|node| node.config().network.self_address())
+                .collect::<Vec<_>>()
+                .into();
+            let node = SimNode::start(config, conditions.clone(), events.clone())?;
+            info!("Simulator node {} started: {}", index, node.peer_id());
+            nodes.push(node);
+        }
+        let simulator = Self {
+            nodes,
+            seed,
+            conditions,
+            events,
+            partitioned: BTreeSet::new(),
+        };
+        simulator.wait_until(START_TIMEOUT, |sim| {
+            Ok(sim.nodes.iter().all(|node| {
+                let peers = block_on(node.handle.network().peer_set()).unwrap_or_default();
+                peers.len() + 1 >= sim.nodes.len()
+            }))
+        })?;
+        Ok(simulator)
+    }
+
+    pub fn nodes(&self) -> &[SimNode] {
+        self.nodes.as_slice()
+    }
+
+    pub fn node(&self, index: usize) -> &SimNode {
+        &self.nodes[index]
+    }
+
+    fn conditions(&self) -> &LinkConditions {
+        self.conditions.as_ref()
+    }
+
+    /// Set the condition of the link from a node to another, the drop decisions are seeded by the
+    /// simulator seed and the link.
+    pub fn set_link(&self, from: usize, to: usize, mut condition: LinkCondition) {
+        condition.seed = self.seed ^ (((from as u64) << 32) | to as u64);
+        self.conditions().set(
+            self.nodes[from].peer_id().into(),
+            self.nodes[to].peer_id().into(),
+            condition,
+        );
+    }
+
+    fn update_link<F: Fn(&mut LinkCondition)>(&self, a: usize, b: usize, update: F) {
+        for (from, to) in [(a, b), (b, a)] {
+            let mut condition = self
+                .conditions()
+                .get(
+                    &self.nodes[from].peer_id().into(),
+                    &self.nodes[to].peer_id().into(),
+                )
+                .unwrap_or_default();
+            update(&mut condition);
+            self.set_link(from, to, condition);
+        }
+    }
+
+    /// Delay the messages between the two nodes in both directions.
+    pub fn set_latency(&self, a: usize, b: usize, latency: Duration) {
+        self.update_link(a, b, |condition| condition.latency = latency);
+    }
+
+    /// Drop the messages between the two nodes in both directions by the probability.
+    pub fn set_drop_rate(&self, a: usize, b: usize, drop_rate: f64) {
+        self.update_link(a, b, |condition| condition.drop_rate = drop_rate);
+    }
+
+    /// Split the nodes into the groups, the nodes in different groups can not communicate.
+    /// The nodes absent from the groups are not affected.
+    pub fn partition(&mut self, groups: &[&[usize]]) {
+        for (i, group) in groups.iter().enumerate() {
+            for other in &groups[i + 1..] {
+                for a in group.iter() {
+                    for b in other.iter() {
+                        self.update_link(*a, *b, |condition| condition.partitioned = true);
+                        self.ban(*a, *b, true);
+                        self.partitioned.insert((*a.min(b), *a.max(b)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove all the link conditions and the partitions.
+    pub fn heal(&mut self) {
+        for (a, b) in std::mem::take(&mut self.partitioned) {
+            self.ban(a, b, false);
+        }
+        for node in &self.nodes {
+            self.conditions().remove_peer(&node.peer_id().into());
+        }
+    }
+
+    fn ban(&self, a: usize, b: usize, ban: bool) {
+        self.nodes[a]
+            .handle
+            .network()
+            .ban_peer(self.nodes[b].peer_id(), ban);
+        self.nodes[b]
+            .handle
+            .network()
+            .ban_peer(self.nodes[a].peer_id(), ban);
+    }
+
+    /// Move the clock of all the nodes forward.
+    pub fn advance_time(&self, milliseconds: u64) {
+        for node in &self.nodes {
+            node.time_service().adjust(milliseconds);
+        }
+    }
+
+    /// Check the condition on every event of the nodes, until it is true,
+    /// or return an error if it is still false after the `timeout`.
+    pub fn wait_until<F>(&self, timeout: Duration, mut condition: F) -> Result<()>
+    where
+        F: FnMut(&Self) -> Result<bool>,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            // the events after the count are not missed by the check.
+            let seen = self.events.count();
+            if condition(self)? {
+                return Ok(());
+            }
+            ensure!(
+                self.events.wait(seen, deadline),
+                "Simulator wait timeout after {:?}",
+                timeout
+            );
+        }
+    }
+
+    pub fn wait_connected(&self, a: usize, b: usize, timeout: Duration) -> Result<()> {
+        self.wait_until(timeout, |sim| {
+            Ok(sim.nodes[a].is_connected(sim.nodes[b].peer_id()))
+        })
+    }
+
+    /// Wait all the nodes have the same head, and return the head.
+    pub fn wait_heads_converged(&self, timeout: Duration) -> Result<BlockHeader> {
+        let mut head = None;
+        self.wait_until(timeout, |sim| {
+            let heads = sim
+                .nodes
+                .iter()
+                .map(|node| node.head().map(|block| block.header))
+                .collect::<Result<Vec<_>>>()?;
+            debug!(
+                "Simulator heads: {:?}",
+                heads
+                    .iter()
+                    .map(|header| (header.number(), header.id()))
+                    .collect::<Vec<_>>()
+            );
+            let converged = heads.windows(2).all(|pair| pair[0].id() == pair[1].id());
+            head = heads.into_iter().next().filter(|_| converged);
+            Ok(converged)
+        })?;
+        head.ok_or_else(|| format_err!("Simulator has no node"))
+    }
+
+    /// Wait all the nodes have the same pending txns, and return the txn hashes.
+    pub fn wait_txpool_converged(&self, timeout: Duration) -> Result<BTreeSet<HashValue>> {
+        let mut txns = BTreeSet::new();
+        self.wait_until(timeout, |sim| {
+            let pending: Vec<_> = sim.nodes.iter().map(SimNode::pending_txns).collect();
+            let converged = pending.windows(2).all(|pair| pair[0] == pair[1]);
+            if converged {
+                txns = pending.into_iter().next().unwrap_or_default();
+            }
+            Ok(converged)
+        })?;
+        Ok(txns)
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        self.heal();
+        for node in self.nodes {
+            node.handle.stop()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use starcoin_network_simulator::Simulator;
+use starcoin_txpool_api::TxPoolSyncService;
+use starcoin_types::account_config::association_address;
+use std::time::Duration;
+use test_helper::txn::create_account_txn_sent_as_association;
+use test_helper::Account;
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[stest::test(timeout = 180)]
+fn test_partition_and_heal() -> Result<()> {
+    let mut simulator = Simulator::start(3, 1)?;
+    simulator.node(0).generate_block()?;
+    let head = simulator.wait_heads_converged(WAIT_TIMEOUT)?;
+    assert_eq!(head.number(), 1);
+
+    // node 0 is split from node 1 and 2, the majority side builds a longer chain.
+    simulator.partition(&[&[0], &[1, 2]]);
+    simulator.node(0).generate_block()?;
+    for _ in 0..3 {
+        simulator.node(1).generate_block()?;
+    }
+    assert_ne!(
+        simulator.node(0).head()?.id(),
+        simulator.node(1).head()?.id()
+    );
+
+    simulator.heal();
+    let head = simulator.wait_heads_converged(WAIT_TIMEOUT)?;
+    assert_eq!(head.number(), 4);
+    assert_eq!(head.id(), simulator.node(1).head()?.id());
+    let reorgs = simulator.node(0).reorgs();
+    assert!(!reorgs.is_empty());
+    assert_eq!(reorgs[0].ancestor.number(), 1);
+    simulator.stop()
+}
+
+#[stest::test(timeout = 180)]
+fn test_latency_and_message_loss() -> Result<()> {
+    let simulator = Simulator::start(2, 2)?;
+    simulator.set_latency(0, 1, Duration::from_millis(500));
+    simulator.set_drop_rate(0, 1, 0.3);
+    for _ in 0..3 {
+        simulator.node(0).generate_block()?;
+    }
+    let head = simulator.wait_heads_converged(WAIT_TIMEOUT)?;
+    assert_eq!(head.number(), 3);
+    simulator.stop()
+}
+
+#[stest::test(timeout = 180)]
+fn test_txpool_converged() -> Result<()> {
+    let simulator = Simulator::start(3, 3)?;
+    let node = simulator.node(0);
+    let txpool = node.handle().txpool();
+    let seq_num = txpool
+        .next_sequence_number(association_address())
+        .unwrap_or_default();
+    let expiration_timestamp_secs = node.time_service().now_secs() + 60 * 60;
+    let txn = create_account_txn_sent_as_association(
+        &Account::new(),
+        seq_num,
+        1_000_000,
+        expiration_timestamp_secs,
+        node.config().net(),
+    );
+    let txn_hash = txn.id();
+    txpool
+        .add_txns(vec![txn])
+        .pop()
+        .expect("txn should be added")?;
+
+    let txns = simulator.wait_txpool_converged(WAIT_TIMEOUT)?;
+    assert!(txns.contains(&txn_hash));
+    for node in simulator.nodes() {
+        assert!(node.pending_txns().contains(&txn_hash));
+    }
+    simulator.stop()
+}
@@ -6,6 +6,7 @@
 pub mod helper;
 mod network_metrics;
 pub mod network_p2p_handle;
+pub mod peer_store;
 mod service;
 pub mod service_ref;
 pub mod worker;
@@ -0,0 +1,192 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persist the known peers, their reputation and bans to a file, so the node reconnects to the
+//! good peers and keeps away from the bad ones after restart.
+
+use anyhow::Result;
+use network_api::{Multiaddr, PeerId, PeerRecord, PERMANENT_BAN};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Max records in the store, the least recently seen peers are dropped first.
+const MAX_PEER_RECORDS: usize = 2048;
+/// Max addresses remembered for a peer.
+const MAX_PEER_ADDRESSES: usize = 8;
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+pub struct PeerStore {
+    path: PathBuf,
+    records: HashMap<PeerId, PeerRecord>,
+}
+
+impl PeerStore {
+    /// An empty store persisted to `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            records: HashMap::new(),
+        }
+    }
+
+    /// Load the store from `path`, the store is empty if the file does not exist.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let mut store = Self::new(path);
+        if store.path.exists() {
+            let records: Vec<PeerRecord> = serde_json::from_slice(&std::fs::read(&store.path)?)?;
+            store.records = records
+                .into_iter()
+                .map(|record| (record.peer_id.clone(), record))
+                .collect();
+        }
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Write the store to a temp file then rename it, so a crash does not corrupt the store.
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_vec_pretty(&self.records())?;
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// All the records, the latest seen first.
+    pub fn records(&self) -> Vec<PeerRecord> {
+        let mut records: Vec<PeerRecord> = self.records.values().cloned().collect();
+        records.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        records
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&PeerRecord> {
+        self.records.get(peer_id)
+    }
+
+    pub fn put(&mut self, record: PeerRecord) -> Option<PeerRecord> {
+        self.records.insert(record.peer_id.clone(), record)
+    }
+
+    pub fn remove(&mut self, peer_id: &PeerId) -> Option<PeerRecord> {
+        self.records.remove(peer_id)
+    }
+
+    fn entry(&mut self, peer_id: PeerId) -> &mut PeerRecord {
+        self.records
+            .entry(peer_id.clone())
+            .or_insert_with(|| PeerRecord::new(peer_id))
+    }
+
+    pub fn on_connected(&mut self, peer_id: PeerId, rpc_protocols: Vec<String>, now: u64) {
+        let record = self.entry(peer_id);
+        record.last_seen = now;
+        record.rpc_protocols = rpc_protocols;
+    }
+
+    /// Refresh the addresses and the last seen time of a connected peer.
+    pub fn on_seen(&mut self, peer_id: &PeerId, mut addresses: Vec<Multiaddr>, now: u64) {
+        if let Some(record) = self.records.get_mut(peer_id) {
+            record.last_seen = now;
+            if !addresses.is_empty() {
+                addresses.truncate(MAX_PEER_ADDRESSES);
+                record.addresses = addresses;
+            }
+        }
+    }
+
+    /// Update the reputation of the peers in the store, the other peers are ignored.
+    pub fn update_reputations(&mut self, reputations: &[(PeerId, i32)]) {
+        for (peer_id, reputation) in reputations {
+            if let Some(record) = self.records.get_mut(peer_id) {
+                record.reputation = *reputation;
+            }
+        }
+    }
+
+    /// Update the sync score of the peers in the store, the other peers are ignored.
+    pub fn update_scores(&mut self, scores: &[(PeerId, u64)]) {
+        for (peer_id, score) in scores {
+            if let Some(record) = self.records.get_mut(peer_id) {
+                record.score = *score;
+            }
+        }
+    }
+
+    /// A manual ban never expires, until the peer is unbanned.
+    pub fn set_banned(&mut self, peer_id: PeerId, ban: bool) {
+        self.entry(peer_id).ban_expiry = if ban { Some(PERMANENT_BAN) } else { None };
+    }
+
+    /// Clear the expired bans, and drop the least recently seen peers over the capacity,
+    /// the banned peers are always kept.
+    pub fn prune(&mut self, now: u64) {
+        for record in self.records.values_mut() {
+            if record.ban_expiry.is_some() && !record.is_banned(now) {
+                record.ban_expiry = None;
+            }
+        }
+        let overflow = self.records.len().saturating_sub(MAX_PEER_RECORDS);
+        if overflow == 0 {
+            return;
+        }
+        let mut candidates: Vec<(u64, PeerId)> = self
+            .records
+            .values()
+            .filter(|record| !record.is_banned(now))
+            .map(|record| (record.last_seen, record.peer_id.clone()))
+            .collect();
+        candidates.sort_by_key(|(last_seen, _)| *last_seen);
+        for (_, peer_id) in candidates.into_iter().take(overflow) {
+            self.records.remove(&peer_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_store_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.json");
+        let peer = PeerId::random();
+        let banned = PeerId::random();
+        let expired = PeerId::random();
+
+        let mut store = PeerStore::load(path.clone()).unwrap();
+        store.on_connected(peer.clone(), vec!["get_block".to_string()], 100);
+        store.on_seen(&peer, vec!["/ip4/127.0.0.1/tcp/9840".parse().unwrap()], 200);
+        store.update_reputations(&[(peer.clone(), -10), (PeerId::random(), 5)]);
+        store.update_scores(&[(peer.clone(), 30), (PeerId::random(), 5)]);
+        store.set_banned(banned.clone(), true);
+        let mut record = PeerRecord::new(expired.clone());
+        record.ban_expiry = Some(150);
+        store.put(record);
+        store.prune(200);
+        store.save().unwrap();
+
+        let store = PeerStore::load(path).unwrap();
+        assert_eq!(store.records().len(), 3);
+        let record = store.get(&peer).unwrap();
+        assert_eq!(record.last_seen, 200);
+        assert_eq!(record.reputation, -10);
+        assert_eq!(record.score, 30);
+        assert_eq!(record.addresses.len(), 1);
+        assert_eq!(record.rpc_protocols, vec!["get_block".to_string()]);
+        assert!(store.get(&banned).unwrap().is_banned(200));
+        assert!(!store.get(&expired).unwrap().is_banned(200));
+        assert_eq!(store.get(&expired).unwrap().ban_expiry, None);
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::network_metrics::NetworkMetrics;
 use crate::network_p2p_handle::Networkp2pHandle;
+use crate::peer_store::{now_secs, PeerStore};
 use crate::{build_network_worker, Announcement};
 use anyhow::{format_err, Result};
 use bcs_ext::BCSCodec;
@@ -14,13 +15,17 @@ use futures::{
 };
 use lru::LruCache;
 use network_api::messages::{
-    AnnouncementType, BanPeer, GetPeerById, GetPeerSet, GetSelfPeer, NotificationMessage,
-    PeerEvent, PeerMessage, PeerReputations, ReportReputation, TransactionsMessage,
+    AnnouncementType, BanPeer, BlockAnnouncement, GetPeerById, GetPeerRecords, GetPeerSet,
+    GetSelfPeer, NotificationMessage, PeerEvent, PeerMessage, PeerReputations, PutPeerRecord,
+    RemovePeerRecord, ReportReputation, TransactionsMessage, UpdatePeerScores,
+    BLOCK_ANNOUNCEMENT_PROTOCOL_NAME,
 };
 use network_api::{
-    BroadcastProtocolFilter, NetworkActor, PeerId, PeerInfo, PeerMessageHandler, RpcInfo,
+    BroadcastProtocolFilter, NetworkActor, PeerId, PeerInfo, PeerMessageHandler, PeerRecord,
+    RpcInfo, PERMANENT_BAN,
 };
 use network_p2p::{Event, NetworkWorker};
+use parking_lot::Mutex;
 use rand::prelude::SliceRandom;
 use starcoin_config::NodeConfig;
 use starcoin_crypto::HashValue;
@@ -34,13 +39,16 @@ use starcoin_types::startup_info::{ChainInfo, ChainStatus};
 use starcoin_types::sync_status::SyncStatus;
 use starcoin_types::system_events::SyncStatusChangeEvent;
 use std::borrow::Cow;
-use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
 use std::ops::RangeInclusive;
 use std::sync::Arc;
+use std::time::Duration;
 
 const BARNARD_HARD_FORK_PEER_VERSION_STRING_PREFIX: &str = "barnard_rollback_block_fix";
 const BARNARD_HARD_FORK_VERSION: [i32; 3] = [1, 13, 11];
+/// Interval to snapshot the connected peers into the peer store and save it.
+const PEER_STORE_SAVE_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct NetworkActorService {
     /// Worker and inner have ChainInfo instances separately. There might be some way to solve the problem.
@@ -72,7 +80,16 @@ impl NetworkActorService {
         )?;
         let service = worker.service().clone();
         //let self_info = PeerInfo::new(config.network.self_peer_id(), chain_info);
-        let inner = Inner::new(config, self_info, service, peer_message_handler)?;
+        let peer_store_path = config.network.peer_store_file();
+        let peer_store = PeerStore::load(peer_store_path.clone()).unwrap_or_else(|e| {
+            warn!(
+                "Load peer store from {:?} failed, start with an empty one: {:?}",
+                peer_store_path, e
+            );
+            PeerStore::new(peer_store_path)
+        });
+        let inner = Inner::new(config, self_info, service, peer_message_handler, peer_store)?;
+        inner.restore_peers();
         Ok(Self {
             worker: Some(worker),
             inner,
@@ -104,6 +121,20 @@ impl ActorService for NetworkActorService {
                 Ok(Ok(_)) => {}
             }
         }));
+        let now = now_secs();
+        let records = self.inner.peer_store.lock().records();
+        for record in records {
+            if let Some(ban_expiry) = record.ban_expiry {
+                if ban_expiry != PERMANENT_BAN && record.is_banned(now) {
+                    let peer_id = record.peer_id;
+                    ctx.run_later(
+                        Duration::from_secs(ban_expiry.saturating_sub(now)),
+                        move |ctx| ctx.notify(UnbanExpiredPeer(peer_id.clone())),
+                    );
+                }
+            }
+        }
+        ctx.run_interval(PEER_STORE_SAVE_INTERVAL, |ctx| ctx.notify(SavePeerStore));
         Ok(())
     }
 
@@ -113,10 +144,71 @@ impl ActorService for NetworkActorService {
         if let Some(abort_handle) = self.network_worker_handle.take() {
             abort_handle.abort();
         }
+        self.inner.save_peer_store();
         Ok(())
     }
 }
 
+/// Snapshot the connected peers into the peer store and save it.
+#[derive(Clone, Debug)]
+struct SavePeerStore;
+
+impl EventHandler<Self, SavePeerStore> for NetworkActorService {
+    fn handle_event(&mut self, _msg: SavePeerStore, ctx: &mut ServiceContext<Self>) {
+        let connected_peers: Vec<PeerId> = self.inner.peers.keys().cloned().collect();
+        let network_service = self.network_service();
+        let peer_store = self.inner.peer_store.clone();
+        ctx.spawn(async move {
+            let mut addresses = Vec::with_capacity(connected_peers.len());
+            for peer_id in connected_peers {
+                let peer_addresses = network_service.get_address(peer_id.clone().into()).await;
+                addresses.push((peer_id, peer_addresses));
+            }
+            let reputations: Vec<(PeerId, i32)> = match network_service.reputations(i32::MIN).await
+            {
+                Ok(reputations) => reputations
+                    .into_iter()
+                    .map(|(peer_id, reputation)| (PeerId::new(peer_id), reputation))
+                    .collect(),
+                Err(e) => {
+                    debug!("Get peer reputations for peer store failed: {}", e);
+                    vec![]
+                }
+            };
+            let now = now_secs();
+            let mut peer_store = peer_store.lock();
+            for (peer_id, peer_addresses) in addresses {
+                peer_store.on_seen(&peer_id, peer_addresses, now);
+            }
+            peer_store.update_reputations(&reputations);
+            peer_store.prune(now);
+            if let Err(e) = peer_store.save() {
+                warn!("Save peer store to {:?} failed: {:?}", peer_store.path(), e);
+            }
+        });
+    }
+}
+
+/// Lift the ban of a peer when its ban in the peer store expires.
+#[derive(Clone, Debug)]
+struct UnbanExpiredPeer(PeerId);
+
+impl EventHandler<Self, UnbanExpiredPeer> for NetworkActorService {
+    fn handle_event(&mut self, msg: UnbanExpiredPeer, _ctx: &mut ServiceContext<Self>) {
+        let mut peer_store = self.inner.peer_store.lock();
+        let still_banned = peer_store
+            .get(&msg.0)
+            .map(|record| record.is_banned(now_secs()))
+            .unwrap_or(false);
+        if still_banned {
+            return;
+        }
+        peer_store.prune(now_secs());
+        info!("The ban of peer {} expired", msg.0);
+        self.inner.network_service.ban_peer(msg.0.into(), false);
+    }
+}
+
 impl EventHandler<Self, SyncStatusChangeEvent> for NetworkActorService {
     fn handle_event(&mut self, msg: SyncStatusChangeEvent, _ctx: &mut ServiceContext<Self>) {
         self.inner.update_chain_status(msg.0);
@@ -252,6 +344,10 @@ impl EventHandler<Self, ReportReputation> for NetworkActorService {
 
 impl EventHandler<Self, BanPeer> for NetworkActorService {
     fn handle_event(&mut self, msg: BanPeer, _ctx: &mut ServiceContext<NetworkActorService>) {
+        self.inner
+            .peer_store
+            .lock()
+            .set_banned(msg.peer_id.clone(), msg.ban);
         self.inner
             .network_service
             .ban_peer(msg.peer_id.into(), msg.ban);
@@ -385,6 +481,80 @@ impl ServiceHandler<Self, PeerReputations> for NetworkActorService {
     }
 }
 
+impl ServiceHandler<Self, GetPeerRecords> for NetworkActorService {
+    fn handle(
+        &mut self,
+        _msg: GetPeerRecords,
+        _ctx: &mut ServiceContext<NetworkActorService>,
+    ) -> <GetPeerRecords as ServiceRequest>::Response {
+        self.inner.peer_store.lock().records()
+    }
+}
+
+impl ServiceHandler<Self, PutPeerRecord> for NetworkActorService {
+    fn handle(
+        &mut self,
+        msg: PutPeerRecord,
+        ctx: &mut ServiceContext<NetworkActorService>,
+    ) -> <PutPeerRecord as ServiceRequest>::Response {
+        let record = msg.record;
+        let now = now_secs();
+        let was_banned = self
+            .inner
+            .peer_store
+            .lock()
+            .get(&record.peer_id)
+            .map(|old| old.is_banned(now))
+            .unwrap_or(false);
+        self.inner.apply_peer_record(&record, was_banned);
+        if let Some(ban_expiry) = record.ban_expiry {
+            if ban_expiry != PERMANENT_BAN && record.is_banned(now) {
+                let peer_id = record.peer_id.clone();
+                ctx.run_later(
+                    Duration::from_secs(ban_expiry.saturating_sub(now)),
+                    move |ctx| ctx.notify(UnbanExpiredPeer(peer_id.clone())),
+                );
+            }
+        }
+        // set in the peerset's own order, so the reports of the peer are not lost.
+        self.inner
+            .network_service
+            .set_reputation(record.peer_id.clone().into(), record.reputation);
+        let mut peer_store = self.inner.peer_store.lock();
+        peer_store.put(record);
+        peer_store.save()
+    }
+}
+
+impl EventHandler<Self, UpdatePeerScores> for NetworkActorService {
+    fn handle_event(&mut self, msg: UpdatePeerScores, _ctx: &mut ServiceContext<Self>) {
+        self.inner.peer_store.lock().update_scores(&msg.scores);
+    }
+}
+
+impl ServiceHandler<Self, RemovePeerRecord> for NetworkActorService {
+    fn handle(
+        &mut self,
+        msg: RemovePeerRecord,
+        _ctx: &mut ServiceContext<NetworkActorService>,
+    ) -> <RemovePeerRecord as ServiceRequest>::Response {
+        let mut peer_store = self.inner.peer_store.lock();
+        let removed = peer_store.remove(&msg.peer_id);
+        if let Some(record) = removed.as_ref() {
+            // forget the ban and the reputation of the peer, in the swarm and the peerset.
+            let peer_id: network_p2p_types::PeerId = record.peer_id.clone().into();
+            if record.ban_expiry.is_some() {
+                self.inner.network_service.ban_peer(peer_id, false);
+            }
+            self.inner.network_service.set_reputation(peer_id, 0);
+            if let Err(e) = peer_store.save() {
+                warn!("Save peer store to {:?} failed: {:?}", peer_store.path(), e);
+            }
+        }
+        removed
+    }
+}
+
 impl ServiceHandler<Self, GetPeerById> for NetworkActorService {
     fn handle(
         &mut self,
@@ -411,6 +581,9 @@ impl ServiceHandler<Self, GetSelfPeer> for NetworkActorService {
 // max peers is 100(in: 25 + out:75), so blocks lru + txn lru max memory usage about is:
 // (100 +1 ) * ( LRU_CACHE_SIZE * 32) *2 = 64M
 const LRU_CACHE_SIZE: usize = 10240;
+/// The first relayed blocks of the peers are halved every such count of new blocks relayed by
+/// the peers, so the fast relay peers follow the recent blocks.
+const FIRST_RELAYED_BLOCKS_DECAY_INTERVAL: u64 = 64;
 
 #[derive(Debug)]
 pub struct Peer {
@@ -418,6 +591,8 @@ pub struct Peer {
     known_transactions: LruCache<HashValue, ()>,
     /// Holds a set of blocks known to this peer.
     known_blocks: LruCache<HashValue, ()>,
+    /// Count of the new blocks this peer relayed to us before the other peers.
+    first_relayed_blocks: u64,
 }
 
 impl Peer {
@@ -426,6 +601,7 @@ impl Peer {
             peer_info,
             known_blocks: LruCache::new(LRU_CACHE_SIZE),
             known_transactions: LruCache::new(LRU_CACHE_SIZE),
+            first_relayed_blocks: 0,
         }
     }
 
@@ -439,8 +615,13 @@ pub(crate) struct Inner {
     network_service: Arc<network_p2p::NetworkService>,
     self_peer: Peer,
     peers: HashMap<PeerId, Peer>,
+    /// Count of the new blocks relayed by the peers since the last decay.
+    relayed_blocks: u64,
+    /// The blocks announced by the peers, which are not known until the block relayer fetches them.
+    announced_blocks: LruCache<HashValue, ()>,
     peer_message_handler: Arc<dyn PeerMessageHandler>,
     metrics: Option<NetworkMetrics>,
+    peer_store: Arc<Mutex<PeerStore>>,
 }
 
 impl BroadcastProtocolFilter for Inner {
@@ -464,6 +645,7 @@ impl Inner {
         self_info: PeerInfo,
         network_service: Arc<network_p2p::NetworkService>,
         peer_message_handler: H,
+        peer_store: PeerStore,
     ) -> Result<Inner>
     where
         H: PeerMessageHandler + 'static,
@@ -478,11 +660,50 @@ impl Inner {
             network_service,
             self_peer: Peer::new(self_info),
             peers: HashMap::new(),
+            relayed_blocks: 0,
+            announced_blocks: LruCache::new(LRU_CACHE_SIZE),
             peer_message_handler: Arc::new(peer_message_handler),
             metrics,
+            peer_store: Arc::new(Mutex::new(peer_store)),
         })
     }
 
+    /// Load the peer store into the discovery and the peerset.
+    pub(crate) fn restore_peers(&self) {
+        let records = self.peer_store.lock().records();
+        info!("Restore {} peers from the peer store", records.len());
+        for record in records {
+            self.apply_peer_record(&record, false);
+            if record.reputation != 0 {
+                self.network_service
+                    .set_reputation(record.peer_id.into(), record.reputation);
+            }
+        }
+    }
+
+    /// Apply the addresses and the ban of the record to the network.
+    fn apply_peer_record(&self, record: &PeerRecord, was_banned: bool) {
+        let peer_id: network_p2p_types::PeerId = record.peer_id.clone().into();
+        if record.is_banned(now_secs()) {
+            self.network_service.ban_peer(peer_id, true);
+            return;
+        }
+        if was_banned {
+            self.network_service.ban_peer(peer_id, false);
+        }
+        for address in &record.addresses {
+            self.network_service
+                .add_known_address(peer_id, address.clone());
+        }
+    }
+
+    pub(crate) fn save_peer_store(&self) {
+        let peer_store = self.peer_store.lock();
+        if let Err(e) = peer_store.save() {
+            warn!("Save peer store to {:?} failed: {:?}", peer_store.path(), e);
+        }
+    }
+
     pub(crate) fn update_chain_status(&mut self, sync_status: SyncStatus) {
         let chain_status = sync_status.chain_status().clone();
         self.self_peer
@@ -510,6 +731,7 @@ impl Inner {
         if let Some(peer_info) = self.peers.get_mut(&peer_id) {
             let notification =
                 NotificationMessage::decode_notification(protocol.as_ref(), message.as_ref())?;
+            let mut first_relayed = false;
             let notification = match &notification {
                 NotificationMessage::Transactions(peer_transactions) => {
                     for txn in &peer_transactions.txns {
@@ -562,6 +784,30 @@ impl Inner {
                         None
                     } else {
                         self.self_peer.known_blocks.put(block_id, ());
+                        first_relayed = true;
+                        Some(notification)
+                    }
+                }
+                NotificationMessage::BlockAnnouncement(block_announcement) => {
+                    let block_header = block_announcement.header.clone();
+                    let block_id = block_header.id();
+                    debug!(
+                        "Receive new block announcement from {:?} with hash {:?}",
+                        peer_id, block_id
+                    );
+                    peer_info.known_blocks.put(block_id, ());
+                    peer_info.peer_info.update_chain_status(ChainStatus::new(
+                        block_header,
+                        block_announcement.block_info.clone(),
+                    ));
+
+                    // The announced block is known only after its body is fetched and verified,
+                    // so the later announcements are delivered too, and the block relayer tries
+                    // their announcers if the former ones fail to serve it.
+                    if self.self_peer.known_blocks.contains(&block_id) {
+                        None
+                    } else {
+                        first_relayed = self.announced_blocks.put(block_id, ()).is_none();
                         Some(notification)
                     }
                 }
@@ -591,6 +837,9 @@ impl Inner {
                     }
                 }
             };
+            if first_relayed {
+                self.on_first_relayed_block(&peer_id);
+            }
 
             if let Some(metrics) = self.metrics.as_ref() {
                 metrics
@@ -623,6 +872,21 @@ impl Inner {
         Ok(())
     }
 
+    /// Count the new block first relayed by the peer, and decay the counts of all the peers
+    /// every `FIRST_RELAYED_BLOCKS_DECAY_INTERVAL` new blocks.
+    fn on_first_relayed_block(&mut self, peer_id: &PeerId) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.first_relayed_blocks = peer.first_relayed_blocks.saturating_add(1);
+        }
+        self.relayed_blocks = self.relayed_blocks.saturating_add(1);
+        if self.relayed_blocks >= FIRST_RELAYED_BLOCKS_DECAY_INTERVAL {
+            self.relayed_blocks = 0;
+            for peer in self.peers.values_mut() {
+                peer.first_relayed_blocks = peer.first_relayed_blocks.saturating_div(2);
+            }
+        }
+    }
+
     pub(crate) fn on_peer_connected(
         &mut self,
         peer_id: PeerId,
@@ -631,6 +895,14 @@ impl Inner {
         rpc_protocols: Vec<Cow<'static, str>>,
         version_string: Option<String>,
     ) {
+        self.peer_store.lock().on_connected(
+            peer_id.clone(),
+            rpc_protocols
+                .iter()
+                .map(|protocol| protocol.to_string())
+                .collect(),
+            now_secs(),
+        );
         self.peers
             .entry(peer_id.clone())
             .and_modify(|peer| {
@@ -685,6 +957,11 @@ impl Inner {
                     .known_blocks
                     .put(block.compact_block.header.id(), ());
             }
+            NotificationMessage::BlockAnnouncement(block_announcement) => {
+                self.self_peer
+                    .known_blocks
+                    .put(block_announcement.header.id(), ());
+            }
             NotificationMessage::Announcement(announcement) => {
                 if announcement.is_txn() {
                     announcement.ids().into_iter().for_each(|txn_id| {
@@ -748,14 +1025,31 @@ impl Inner {
                 let peers_after_protocol_filter = filtered_peer_ids.len();
                 let peers_len = self.peers.len() as u32;
 
-                let selected_peers = select_random_peers(
+                let reserved_peers: HashSet<PeerId> = self
+                    .config
+                    .network
+                    .reserved_peers()
+                    .into_iter()
+                    .map(|node| node.peer_id.into())
+                    .collect();
+                let (selected_peers, announce_peers) = select_block_peers(
+                    filtered_peer_ids,
+                    |peer_id| self.is_supported(peer_id, BLOCK_ANNOUNCEMENT_PROTOCOL_NAME.into()),
                     self.config
                         .network
                         .min_peers_to_propagate()
                         .max(peers_len / 2)
                         ..=self.config.network.max_peers_to_propagate().max(peers_len), // use max(max_peers_to_propagate,peers_len) to ensure range [min,max] , max > min.
-                    filtered_peer_ids.iter(),
+                    &reserved_peers,
+                    |peer_id| {
+                        self.peers
+                            .get(peer_id)
+                            .map(|peer| peer.first_relayed_blocks)
+                            .unwrap_or_default()
+                    },
+                    self.config.network.max_fast_relay_peers() as usize,
                 );
+
                 let peers_send_message = selected_peers.len();
                 for peer_id in &selected_peers {
                     let peer = self.peers.get_mut(peer_id).expect("peer should exists");
@@ -766,9 +1060,28 @@ impl Inner {
                         message.clone(),
                     ));
                 }
+                let peers_send_announcement = announce_peers.len();
+                if !announce_peers.is_empty() {
+                    let (announcement_protocol_name, announcement) =
+                        NotificationMessage::BlockAnnouncement(Box::new(BlockAnnouncement::new(
+                            msg.compact_block.header.clone(),
+                            msg.block_info.clone(),
+                        )))
+                        .encode_notification()
+                        .expect("Encode notification BlockAnnouncement message should ok");
+                    for peer_id in announce_peers {
+                        let peer = self.peers.get_mut(&peer_id).expect("peer should exists");
+                        peer.known_blocks.put(id, ());
+                        prepare_to_broadcast.push((
+                            announcement_protocol_name.clone(),
+                            peer_id,
+                            announcement.clone(),
+                        ));
+                    }
+                }
                 debug!(
-                    "[network] broadcast new compact block message {:?} to {} peers, total_peers: {}, peers_after_known_hash_filter: {}, peers_after_protocol_filter: {}",
-                    id, peers_send_message, peers_len, peers_after_known_hash_filter, peers_after_protocol_filter
+                    "[network] broadcast new compact block message {:?} to {} peers, announcement to {} peers, total_peers: {}, peers_after_known_hash_filter: {}, peers_after_protocol_filter: {}",
+                    id, peers_send_message, peers_send_announcement, peers_len, peers_after_known_hash_filter, peers_after_protocol_filter
                 );
             }
             NotificationMessage::Transactions(msg) => {
@@ -858,7 +1171,7 @@ impl Inner {
                     send_peer_count
                 );
             }
-            NotificationMessage::Announcement(_msg) => {
+            NotificationMessage::Announcement(_) | NotificationMessage::BlockAnnouncement(_) => {
                 error!("[network] can not broadcast announcement message directly.");
             }
         }
@@ -866,6 +1179,64 @@ impl Inner {
     }
 }
 
+/// Split the peers to send the new block to, into the peers get the compact block and the peers
+/// get the header announcement. The peers not support the block announcement get the compact
+/// block as before, from the others only the push peers get the compact block.
+fn select_block_peers<S, F>(
+    peer_ids: Vec<PeerId>,
+    supports_announcement: S,
+    legacy_peers_range: RangeInclusive<u32>,
+    reserved_peers: &HashSet<PeerId>,
+    first_relayed_blocks: F,
+    max_fast_relay_peers: usize,
+) -> (Vec<PeerId>, Vec<PeerId>)
+where
+    S: Fn(&PeerId) -> bool,
+    F: Fn(&PeerId) -> u64,
+{
+    let (announce_peer_ids, legacy_peer_ids): (Vec<_>, Vec<_>) = peer_ids
+        .into_iter()
+        .partition(|peer_id| supports_announcement(peer_id));
+    let mut compact_block_peers = select_random_peers(legacy_peers_range, legacy_peer_ids.iter());
+    let push_peers = select_push_peers(
+        announce_peer_ids.clone(),
+        reserved_peers,
+        first_relayed_blocks,
+        max_fast_relay_peers,
+    );
+    let announce_peers = announce_peer_ids
+        .into_iter()
+        .filter(|peer_id| !push_peers.contains(peer_id))
+        .collect();
+    compact_block_peers.extend(push_peers);
+    (compact_block_peers, announce_peers)
+}
+
+/// Select the peers to push the new compact block to, from the peers support the block
+/// announcement, the other peers get the header announcement. The reserved peers come first,
+/// then the peers relayed the most recent new blocks first, then the random peers, up to the
+/// max fast relay peers in total.
+fn select_push_peers<F>(
+    mut peer_ids: Vec<PeerId>,
+    reserved_peers: &HashSet<PeerId>,
+    first_relayed_blocks: F,
+    max_fast_relay_peers: usize,
+) -> Vec<PeerId>
+where
+    F: Fn(&PeerId) -> u64,
+{
+    peer_ids.shuffle(&mut rand::thread_rng());
+    // the sort is stable, so the peers of the same rank stay in the random order.
+    peer_ids.sort_by_key(|peer_id| {
+        (
+            !reserved_peers.contains(peer_id),
+            Reverse(first_relayed_blocks(peer_id)),
+        )
+    });
+    peer_ids.truncate(max_fast_relay_peers);
+    peer_ids
+}
+
 fn select_random_peers<'a, P>(peer_num_range: RangeInclusive<u32>, peers: P) -> Vec<PeerId>
 where
     P: ExactSizeIterator<Item = &'a PeerId>,
@@ -886,8 +1257,9 @@ where
 #[cfg(test)]
 mod test {
     use crate::service::greater_barnard_fork_version;
-    use crate::service::select_random_peers;
+    use crate::service::{select_block_peers, select_random_peers};
     use network_api::PeerId;
+    use std::collections::HashSet;
 
     fn create_peers(n: u32) -> Vec<PeerId> {
         (0..n).map(|_| PeerId::random()).collect()
@@ -920,6 +1292,91 @@ mod test {
         assert_eq!(select_random_peers(3..=3, create_peers(3).iter()).len(), 3);
     }
 
+    #[test]
+    fn test_select_block_peers() {
+        let legacy_peers = create_peers(2);
+        let reserved_peer = PeerId::random();
+        let fast_peers = create_peers(2);
+        let other_peers = create_peers(3);
+        let peers = legacy_peers
+            .iter()
+            .chain(other_peers.iter())
+            .chain(fast_peers.iter())
+            .chain(std::iter::once(&reserved_peer))
+            .cloned()
+            .collect::<Vec<_>>();
+        let reserved_peers: HashSet<PeerId> = std::iter::once(reserved_peer.clone()).collect();
+        let supports_announcement = |peer_id: &PeerId| !legacy_peers.contains(peer_id);
+        let first_relayed_blocks = |peer_id: &PeerId| {
+            if *peer_id == fast_peers[0] {
+                2
+            } else if *peer_id == fast_peers[1] {
+                5
+            } else {
+                0
+            }
+        };
+
+        // the legacy peers get the compact block, then the reserved peer and the fast relay peers
+        // by the first relayed blocks, the others get the announcement.
+        let (compact_block_peers, announce_peers) = select_block_peers(
+            peers.clone(),
+            supports_announcement,
+            2..=2,
+            &reserved_peers,
+            first_relayed_blocks,
+            3,
+        );
+        assert_eq!(
+            compact_block_peers[..2].iter().collect::<HashSet<_>>(),
+            legacy_peers.iter().collect::<HashSet<_>>()
+        );
+        assert_eq!(
+            compact_block_peers[2..].to_vec(),
+            vec![
+                reserved_peer.clone(),
+                fast_peers[1].clone(),
+                fast_peers[0].clone()
+            ]
+        );
+        assert_eq!(
+            announce_peers.iter().collect::<HashSet<_>>(),
+            other_peers.iter().collect::<HashSet<_>>()
+        );
+
+        // the push peers are filled by the random peers up to the max fast relay peers.
+        let (compact_block_peers, announce_peers) = select_block_peers(
+            peers.clone(),
+            supports_announcement,
+            1..=1,
+            &reserved_peers,
+            first_relayed_blocks,
+            4,
+        );
+        assert_eq!(compact_block_peers.len(), 5);
+        assert!(legacy_peers.contains(&compact_block_peers[0]));
+        assert!(other_peers.contains(&compact_block_peers[4]));
+        assert_eq!(announce_peers.len(), 2);
+        assert!(
+            announce_peers
+                .iter()
+                .all(|peer_id| other_peers.contains(peer_id)
+                    && !compact_block_peers.contains(peer_id))
+        );
+
+        // no push peers, all the peers support the block announcement get the announcement.
+        let (compact_block_peers, announce_peers) = select_block_peers(
+            peers,
+            supports_announcement,
+            2..=2,
+            &reserved_peers,
+            first_relayed_blocks,
+            0,
+        );
+        assert_eq!(compact_block_peers.len(), 2);
+        assert_eq!(announce_peers.len(), 6);
+    }
+
     #[test]
     fn greater_version_test() {
         let v1 = String::from("starcoin/1.12.6 (build:v1.12.6) (kele01)");
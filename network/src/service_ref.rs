@@ -9,15 +9,19 @@ use futures::channel::oneshot::Receiver;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use log::warn;
-use network_api::messages::NotificationMessage;
+use network_api::messages::{
+    GetPeerRecords, NotificationMessage, PutPeerRecord, RemovePeerRecord, UpdatePeerScores,
+};
 use network_api::{
-    NetworkService, PeerId, PeerInfo, PeerProvider, ReputationChange, SupportedRpcProtocol,
+    NetworkService, PeerId, PeerInfo, PeerProvider, PeerRecord, ReputationChange,
+    SupportedRpcProtocol,
 };
 use network_p2p_core::{NetRpcError, RawRpcClient};
 use network_p2p_types::network_state::NetworkState;
-use network_p2p_types::{IfDisconnected, Multiaddr, RequestFailure};
+use network_p2p_types::{IfDisconnected, Multiaddr, PeerBandwidthStats, RequestFailure};
 use starcoin_service_registry::ServiceRef;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 //TODO Service registry should support custom service ref.
@@ -126,12 +130,61 @@ impl NetworkServiceRef {
             service_ref,
         }
     }
+
+    /// The p2p network service of the node.
+    pub fn network_service(&self) -> &Arc<network_p2p::NetworkService> {
+        &self.network_service
+    }
+
     pub fn add_peer(&self, peer: String) -> Result<()> {
         self.network_service
             .add_reserved_peer(peer)
             .map_err(|e| format_err!("{:?}", e))
     }
 
+    /// Reserve the peer, the node always keeps connected with it.
+    pub fn add_reserved_peer(&self, peer: String) -> Result<()> {
+        self.network_service
+            .add_reserved_peer(peer)
+            .map_err(|e| format_err!("{:?}", e))
+    }
+
+    pub fn remove_reserved_peer(&self, peer_id: PeerId) {
+        self.network_service.remove_reserved_peer(peer_id.into())
+    }
+
+    /// Transferred bytes with the connected peers, by protocol.
+    pub fn peer_bandwidth(&self) -> HashMap<PeerId, PeerBandwidthStats> {
+        self.network_service
+            .peer_bandwidth()
+            .stats()
+            .into_iter()
+            .map(|(peer_id, stats)| (peer_id.into(), stats))
+            .collect()
+    }
+
+    /// The records of the peer store, the latest seen first.
+    pub async fn peer_records(&self) -> Result<Vec<PeerRecord>> {
+        self.service_ref.send(GetPeerRecords).await
+    }
+
+    /// Insert or replace the record in the peer store, its addresses, ban and reputation take
+    /// effect at once.
+    pub async fn put_peer_record(&self, record: PeerRecord) -> Result<()> {
+        self.service_ref.send(PutPeerRecord { record }).await?
+    }
+
+    pub async fn remove_peer_record(&self, peer_id: PeerId) -> Result<Option<PeerRecord>> {
+        self.service_ref.send(RemovePeerRecord { peer_id }).await
+    }
+
+    /// Save the sync scores of the peers to their records in the peer store.
+    pub fn update_peer_scores(&self, scores: Vec<(PeerId, u64)>) {
+        if let Err(e) = self.service_ref.notify(UpdatePeerScores { scores }) {
+            warn!("Update peer scores error: {}.", e);
+        }
+    }
+
     pub async fn network_state(&self) -> Result<NetworkState> {
         self.network_service
             .network_state()
@@ -7,7 +7,7 @@ use futures::channel::mpsc::channel;
 use futures::prelude::*;
 use log::{debug, error, info};
 use network_api::{PeerInfo, RpcInfo};
-use network_p2p::config::{RequestResponseConfig, TransportConfig};
+use network_p2p::config::{NonReservedPeerMode, RequestResponseConfig, TransportConfig};
 use network_p2p::{
     identity, NetworkConfiguration, NetworkWorker, NodeKeyConfig, Params, ProtocolId, Secret,
 };
@@ -76,6 +76,18 @@ pub fn build_network_worker(
     let boot_nodes = network_config.seeds();
 
     info!("Final bootstrap seeds: {:?}", boot_nodes);
+    let reserved_nodes = network_config.reserved_peers();
+    let non_reserved_mode = if network_config.reserved_only() {
+        NonReservedPeerMode::Deny
+    } else {
+        NonReservedPeerMode::Accept
+    };
+    if !reserved_nodes.is_empty() || network_config.reserved_only() {
+        info!(
+            "Reserved peers: {:?}, non reserved mode: {:?}",
+            reserved_nodes, non_reserved_mode
+        );
+    }
     let self_info = PeerInfo::new(
         network_config.self_peer_id(),
         chain_info.clone(),
@@ -89,6 +101,8 @@ pub fn build_network_worker(
     let config = NetworkConfiguration {
         listen_addresses: vec![network_config.listen()],
         boot_nodes,
+        reserved_nodes,
+        non_reserved_mode,
         node_key: {
             let secret = identity::ed25519::SecretKey::from_bytes(
                 &mut network_config.network_keypair().0.to_bytes(),
@@ -104,6 +118,7 @@ pub fn build_network_worker(
         node_name,
         client_version: starcoin_config::G_APP_NAME_WITH_VERSION.clone(),
         allow_non_globals_in_dht,
+        bandwidth_limits: network_config.network_bandwidth.limits(),
         ..NetworkConfiguration::default()
     };
     // protocol id is chain/{chain_id}, `RegisteredProtocol` will append `/starcoin` prefix
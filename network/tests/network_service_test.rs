@@ -4,15 +4,16 @@
 use futures::stream::StreamExt;
 use futures_timer::Delay;
 use network_api::messages::{
-    Announcement, AnnouncementType, CompactBlockMessage, NotificationMessage, PeerMessage,
-    TransactionsMessage, ANNOUNCEMENT_PROTOCOL_NAME, TXN_PROTOCOL_NAME,
+    Announcement, AnnouncementType, BlockAnnouncement, CompactBlockMessage, NotificationMessage,
+    PeerMessage, TransactionsMessage, ANNOUNCEMENT_PROTOCOL_NAME, TXN_PROTOCOL_NAME,
 };
-use network_api::{Multiaddr, NetworkService};
+use network_api::{Multiaddr, NetworkService, PeerId, PeerProvider, PeerRecord, PERMANENT_BAN};
 use network_p2p_types::MultiaddrWithPeerId;
 use starcoin_config::{BuiltinNetworkID, NetworkConfig, NodeConfig};
 use starcoin_crypto::hash::HashValue;
 use starcoin_logger::prelude::*;
 use starcoin_network::build_network_worker;
+use starcoin_network::peer_store::PeerStore;
 use starcoin_types::block::{AccumulatorInfo, Block, BlockBody, BlockHeader, BlockInfo};
 use starcoin_types::compact_block::CompactBlock;
 use starcoin_types::startup_info::{ChainInfo, ChainStatus};
@@ -20,7 +21,7 @@ use starcoin_types::transaction::SignedUserTransaction;
 use starcoin_types::U256;
 use std::sync::Arc;
 use std::time::Duration;
-use test_helper::network::build_network_with_config;
+use test_helper::network::{build_network, build_network_with_config, TestNetworkService};
 
 pub type NetworkComponent = (Arc<network_p2p::NetworkService>, NetworkConfig);
 
@@ -317,6 +318,62 @@ async fn test_send_announcement() {
     assert_eq!(announcement, msg_2.notification);
 }
 
+#[stest::test]
+async fn test_send_block_announcement() {
+    let node_config_1 = Arc::new(NodeConfig::random_for_test());
+    let service1 = build_network_with_config(node_config_1.clone(), None)
+        .await
+        .unwrap();
+
+    let nodes = vec![MultiaddrWithPeerId::new(
+        node_config_1.network.listen(),
+        service1.peer_id().into(),
+    )];
+    let mut node_config_2 = NodeConfig::random_for_test();
+    node_config_2.network.seeds = nodes.into();
+    let service2 = build_network_with_config(Arc::new(node_config_2), None)
+        .await
+        .unwrap();
+    Delay::new(Duration::from_secs(2)).await;
+    assert!(service2.service_ref.is_connected(service1.peer_id()).await);
+
+    let block = Block::new(BlockHeader::random(), BlockBody::new_empty());
+    let announcement = NotificationMessage::BlockAnnouncement(Box::new(BlockAnnouncement::new(
+        block.header().clone(),
+        mock_block_info(10.into()),
+    )));
+    let mut receiver2 = service2.message_handler.channel();
+    service1
+        .service_ref
+        .send_peer_message(PeerMessage::new(service2.peer_id(), announcement.clone()));
+    let msg_2 = receiver2.next().await.unwrap();
+    assert_eq!(announcement, msg_2.notification);
+
+    // the announced block is not known until it is fetched, so the later announcements are
+    // delivered for the relayer to retry with.
+    service1
+        .service_ref
+        .send_peer_message(PeerMessage::new(service2.peer_id(), announcement.clone()));
+    let msg_2 = receiver2.next().await.unwrap();
+    assert_eq!(announcement, msg_2.notification);
+
+    // the compact block makes the block known, the repeated one is ignored.
+    let compact_block = NotificationMessage::CompactBlock(Box::new(CompactBlockMessage::new(
+        CompactBlock::new(block),
+        mock_block_info(10.into()),
+    )));
+    service1
+        .service_ref
+        .send_peer_message(PeerMessage::new(service2.peer_id(), compact_block.clone()));
+    let msg_2 = receiver2.next().await.unwrap();
+    assert_eq!(compact_block, msg_2.notification);
+    service1
+        .service_ref
+        .send_peer_message(PeerMessage::new(service2.peer_id(), compact_block));
+    let msg_2 = async_std::future::timeout(Duration::from_secs(1), receiver2.next()).await;
+    assert!(msg_2.is_err());
+}
+
 #[stest::test]
 async fn test_filter_protocol() {
     let node_config_1 = Arc::new(NodeConfig::random_for_test());
@@ -368,3 +425,109 @@ async fn test_filter_protocol() {
         msg_3.notification.protocol_name()
     );
 }
+
+/// Reputation of the peer in the peerset, the reputation decays to 0 over time.
+async fn peer_reputation(service: &TestNetworkService, peer_id: &PeerId) -> Option<i32> {
+    service
+        .service_ref
+        .reputations(i32::MIN)
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|(peer, _)| peer == peer_id)
+        .map(|(_, reputation)| reputation)
+}
+
+fn mock_peer_record(reputation: i32, ban_expiry: Option<u64>) -> PeerRecord {
+    let mut record = PeerRecord::new(PeerId::random());
+    record.addresses = vec!["/ip4/127.0.0.1/tcp/9840".parse().unwrap()];
+    record.last_seen = 100;
+    record.reputation = reputation;
+    record.ban_expiry = ban_expiry;
+    record.score = 7;
+    record
+}
+
+#[stest::test]
+async fn test_restore_peer_store() {
+    let node_config = NodeConfig::random_for_test();
+    let path = node_config.network.peer_store_file();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let good = mock_peer_record(-10000, None);
+    let banned = mock_peer_record(0, Some(PERMANENT_BAN));
+    let mut peer_store = PeerStore::new(path);
+    peer_store.put(good.clone());
+    peer_store.put(banned.clone());
+    peer_store.save().unwrap();
+
+    let service = build_network_with_config(Arc::new(node_config), None)
+        .await
+        .unwrap();
+    let records = service.service_ref.peer_records().await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(records.contains(&good));
+    assert!(records.contains(&banned));
+    // the reputation is set again, it only decays a little since the start.
+    let reputation = peer_reputation(&service, &good.peer_id).await.unwrap();
+    assert!((-10000..-9000).contains(&reputation), "{}", reputation);
+}
+
+#[stest::test]
+async fn test_peer_record_handlers() {
+    let service = build_network(None, None).await.unwrap();
+    assert!(service.service_ref.peer_records().await.unwrap().is_empty());
+
+    let record = mock_peer_record(-10000, Some(PERMANENT_BAN));
+    service
+        .service_ref
+        .put_peer_record(record.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        service.service_ref.peer_records().await.unwrap(),
+        vec![record.clone()]
+    );
+    let reputation = peer_reputation(&service, &record.peer_id).await.unwrap();
+    assert!((-10000..-9000).contains(&reputation), "{}", reputation);
+
+    // put replaces the record, the reputation is set instead of adjusted.
+    let mut updated = record.clone();
+    updated.reputation = -20000;
+    updated.ban_expiry = None;
+    service
+        .service_ref
+        .put_peer_record(updated.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        service.service_ref.peer_records().await.unwrap(),
+        vec![updated.clone()]
+    );
+    let reputation = peer_reputation(&service, &record.peer_id).await.unwrap();
+    assert!((-20000..-18000).contains(&reputation), "{}", reputation);
+
+    // remove forgets the reputation in the peerset.
+    assert_eq!(
+        service
+            .service_ref
+            .remove_peer_record(record.peer_id.clone())
+            .await
+            .unwrap(),
+        Some(updated)
+    );
+    assert!(service.service_ref.peer_records().await.unwrap().is_empty());
+    assert!(peer_reputation(&service, &record.peer_id)
+        .await
+        .map(|reputation| reputation == 0)
+        .unwrap_or(true));
+    assert_eq!(
+        service
+            .service_ref
+            .remove_peer_record(record.peer_id.clone())
+            .await
+            .unwrap(),
+        None
+    );
+}
@@ -5,6 +5,8 @@ use anyhow::Result;
 use futures::channel::oneshot::Receiver;
 use starcoin_crypto::HashValue;
 use starcoin_service_registry::{ServiceInfo, ServiceRequest, ServiceStatus};
+use starcoin_storage::backup::BackupManifest;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum NodeRequest {
@@ -19,6 +21,7 @@ pub enum NodeRequest {
     ReExecuteBlock(HashValue),
     DeleteBlock(HashValue),
     DeleteFailedBlock(HashValue),
+    Backup(PathBuf),
 }
 
 #[derive(Debug)]
@@ -27,6 +30,7 @@ pub enum NodeResponse {
     Result(Result<()>),
     AsyncResult(Receiver<Result<()>>),
     ServiceStatus(ServiceStatus),
+    BackupResult(Receiver<Result<BackupManifest>>),
 }
 
 impl ServiceRequest for NodeRequest {
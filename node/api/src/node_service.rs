@@ -7,6 +7,8 @@ use starcoin_crypto::HashValue;
 use starcoin_service_registry::{
     ActorService, ServiceHandler, ServiceInfo, ServiceRef, ServiceStatus,
 };
+use starcoin_storage::backup::BackupManifest;
+use std::path::PathBuf;
 
 #[async_trait::async_trait]
 pub trait NodeAsyncService:
@@ -29,6 +31,8 @@ pub trait NodeAsyncService:
     async fn re_execute_block(&self, block_hash: HashValue) -> Result<()>;
     async fn delete_block(&self, block_hash: HashValue) -> Result<()>;
     async fn delete_failed_block(&self, block_hash: HashValue) -> Result<()>;
+    /// Backup the node db into `backup_dir` under the backup root of the node, a relative dir is relative to the backup root.
+    async fn backup(&self, backup_dir: PathBuf) -> Result<BackupManifest>;
 }
 
 #[async_trait::async_trait]
@@ -122,4 +126,13 @@ where
         self.try_send(NodeRequest::DeleteFailedBlock(block_hash))?;
         Ok(())
     }
+
+    async fn backup(&self, backup_dir: PathBuf) -> Result<BackupManifest> {
+        let response = self.send(NodeRequest::Backup(backup_dir)).await??;
+        if let NodeResponse::BackupResult(receiver) = response {
+            receiver.await?
+        } else {
+            panic!("Unexpect response type.")
+        }
+    }
 }
@@ -3,6 +3,7 @@
 
 use backtrace::Backtrace;
 use starcoin_logger::prelude::*;
+use starcoin_service_registry::in_supervised_handler;
 use std::{
     panic::{self, PanicInfo},
     process, thread, time,
@@ -27,6 +28,11 @@ fn handle_panic(panic_info: &PanicInfo<'_>) {
     error!("backtrace: {}", backtrace);
     eprintln!("backtrace: {}", backtrace);
 
+    // The panic is caught and handled by the supervision policy of the service.
+    if in_supervised_handler() {
+        return;
+    }
+
     // Provide some time to save the log to disk
     thread::sleep(time::Duration::from_millis(100));
     // Kill the process
@@ -4,8 +4,10 @@
 use anyhow::{bail, Result};
 use starcoin_config::NodeConfig;
 use starcoin_logger::prelude::*;
-use starcoin_metrics::{default_registry, Registry};
-use starcoin_service_registry::{ActorService, EventHandler, ServiceContext, ServiceFactory};
+use starcoin_metrics::{default_registry, register, Opts, PrometheusError, Registry, UIntGaugeVec};
+use starcoin_service_registry::{
+    ActorService, EventHandler, ServiceContext, ServiceFactory, ServiceInfo, ServiceStatus,
+};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -139,3 +141,64 @@ impl EventHandler<Self, PushMetricsEvent> for MetricsPushActorService {
         );
     }
 }
+
+/// Health of the services in the registry, refreshed by the `NodeService`.
+#[derive(Clone)]
+pub struct ServiceHealthMetrics {
+    pub service_started: UIntGaugeVec,
+    pub service_mailbox_depth: UIntGaugeVec,
+    pub service_last_handled_time: UIntGaugeVec,
+    pub service_restarts: UIntGaugeVec,
+    pub service_panics: UIntGaugeVec,
+}
+
+impl ServiceHealthMetrics {
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        let service_gauge = |name: &str, help: &str| -> Result<UIntGaugeVec, PrometheusError> {
+            register(
+                UIntGaugeVec::new(Opts::new(name, help), &["service"])?,
+                registry,
+            )
+        };
+        Ok(Self {
+            service_started: service_gauge("service_started", "1 if the service is started")?,
+            service_mailbox_depth: service_gauge(
+                "service_mailbox_depth",
+                "messages sent to the service and not handled yet",
+            )?,
+            service_last_handled_time: service_gauge(
+                "service_last_handled_time",
+                "timestamp in milliseconds when the service handled its last message",
+            )?,
+            service_restarts: service_gauge(
+                "service_restarts",
+                "times the service is restarted by its supervision policy",
+            )?,
+            service_panics: service_gauge(
+                "service_panics",
+                "times the message handlers of the service panicked",
+            )?,
+        })
+    }
+
+    pub fn update(&self, services: &[ServiceInfo]) {
+        for service in services {
+            let labels = [service.name.as_str()];
+            self.service_started
+                .with_label_values(&labels)
+                .set((service.status == ServiceStatus::Started) as u64);
+            self.service_mailbox_depth
+                .with_label_values(&labels)
+                .set(service.health.mailbox_depth);
+            self.service_last_handled_time
+                .with_label_values(&labels)
+                .set(service.health.last_handled_time.unwrap_or_default());
+            self.service_restarts
+                .with_label_values(&labels)
+                .set(service.health.restart_count);
+            self.service_panics
+                .with_label_values(&labels)
+                .set(service.health.panic_count);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metrics::{MetricsPushActorService, MetricsServerActorService};
+use crate::metrics::{MetricsPushActorService, MetricsServerActorService, ServiceHealthMetrics};
 use crate::network_service_factory::NetworkServiceFactory;
 use crate::peer_message_handler::NodePeerMessageHandler;
 use crate::rpc_service_factory::RpcServiceFactory;
@@ -20,6 +20,7 @@ use starcoin_config::NodeConfig;
 use starcoin_genesis::{Genesis, GenesisError};
 use starcoin_logger::prelude::*;
 use starcoin_logger::structured_log::init_slog_logger;
+use starcoin_logger::tracer::init_tracer;
 use starcoin_logger::LoggerHandle;
 use starcoin_miner::generate_block_event_pacemaker::GenerateBlockEventPacemaker;
 use starcoin_miner::{BlockBuilderService, MinerService};
@@ -34,9 +35,10 @@ use starcoin_rpc_server::service::RpcService;
 use starcoin_service_registry::bus::{Bus, BusService};
 use starcoin_service_registry::{
     ActorService, EventHandler, RegistryAsyncService, RegistryService, ServiceContext,
-    ServiceFactory, ServiceHandler, ServiceRef,
+    ServiceEscalatedEvent, ServiceFactory, ServiceHandler, ServiceRef, SupervisionPolicy,
 };
-use starcoin_state_service::ChainStateService;
+use starcoin_state_service::{ChainStateService, StatePrunerService};
+use starcoin_storage::backup;
 use starcoin_storage::block_info::BlockInfoStore;
 use starcoin_storage::cache_storage::CacheStorage;
 use starcoin_storage::db_storage::DBStorage;
@@ -57,14 +59,19 @@ use starcoin_vm_runtime::metrics::VMMetrics;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
+/// Interval to refresh the service health metrics.
+const SERVICE_HEALTH_METRICS_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct NodeService {
     registry: ServiceRef<RegistryService>,
+    health_metrics: Option<ServiceHealthMetrics>,
 }
 
 impl ServiceFactory<Self> for NodeService {
     fn create(ctx: &mut ServiceContext<NodeService>) -> Result<NodeService> {
         Ok(Self {
             registry: ctx.registry_ref().clone(),
+            health_metrics: ctx.get_shared_opt::<ServiceHealthMetrics>()?,
         })
     }
 }
@@ -72,11 +79,25 @@ impl ServiceFactory<Self> for NodeService {
 impl ActorService for NodeService {
     fn started(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
         ctx.subscribe::<SystemShutdown>();
+        ctx.subscribe::<ServiceEscalatedEvent>();
+        if let Some(health_metrics) = self.health_metrics.clone() {
+            ctx.run_interval(SERVICE_HEALTH_METRICS_INTERVAL, move |ctx| {
+                let registry = ctx.registry_ref().clone();
+                let health_metrics = health_metrics.clone();
+                ctx.spawn(async move {
+                    match registry.list_service().await {
+                        Ok(services) => health_metrics.update(services.as_slice()),
+                        Err(e) => warn!("List service for health metrics error: {:?}", e),
+                    }
+                });
+            });
+        }
         Ok(())
     }
 
     fn stopped(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
         ctx.unsubscribe::<SystemShutdown>();
+        ctx.unsubscribe::<ServiceEscalatedEvent>();
         Ok(())
     }
 }
@@ -87,6 +108,16 @@ impl EventHandler<Self, SystemShutdown> for NodeService {
     }
 }
 
+impl EventHandler<Self, ServiceEscalatedEvent> for NodeService {
+    fn handle_event(&mut self, msg: ServiceEscalatedEvent, _: &mut ServiceContext<Self>) {
+        error!(
+            "Service {} panicked: {}, shutdown the system.",
+            msg.service_name, msg.reason
+        );
+        self.shutdown_system();
+    }
+}
+
 impl ServiceHandler<Self, NodeRequest> for NodeService {
     fn handle(
         &mut self,
@@ -203,6 +234,29 @@ impl ServiceHandler<Self, NodeRequest> for NodeService {
                 info!("Prepare to delete failed block {:?}", block_hash);
                 NodeResponse::Result(storage.delete_failed_block(block_hash))
             }
+            NodeRequest::Backup(backup_dir) => {
+                let storage = self
+                    .registry
+                    .get_shared_sync::<Arc<Storage>>()
+                    .expect("Storage must exist.");
+                let config = ctx.get_shared::<Arc<NodeConfig>>()?;
+                let backup_dir =
+                    backup::resolve_backup_dir(config.storage.backup_root().as_path(), &backup_dir);
+                let fut = async move {
+                    let backup_dir = backup_dir?;
+                    info!("Prepare to backup db to {:?}", backup_dir);
+                    // copying the db files may take a long time, do not block the actor.
+                    tokio::task::spawn_blocking(move || {
+                        let db = storage
+                            .db()
+                            .ok_or_else(|| format_err!("The storage of node has no db."))?;
+                        backup::create_backup(db, backup_dir.as_path())
+                    })
+                    .await?
+                };
+                let receiver = ctx.exec(fut);
+                NodeResponse::BackupResult(receiver)
+            }
         })
     }
 }
@@ -232,6 +286,12 @@ impl NodeService {
             logger_handle.enable_stderr();
         }
 
+        if let Some(trace_exporter) = config.logger.trace_exporter() {
+            if let Err(e) = init_tracer(trace_exporter) {
+                warn!("Init tracer error: {}", e);
+            }
+        }
+
         // XXX FIXME YSG add execute_config
         // StarcoinVM::set_concurrency_level_once(num_cpus::get());
         let (start_sender, start_receiver) = oneshot::channel();
@@ -287,6 +347,13 @@ impl NodeService {
         if let Some(vm_metrics) = vm_metrics {
             registry.put_shared(vm_metrics).await?;
         }
+        let health_metrics = config
+            .metrics
+            .registry()
+            .and_then(|registry| ServiceHealthMetrics::register(registry).ok());
+        if let Some(health_metrics) = health_metrics {
+            registry.put_shared(health_metrics).await?;
+        }
         let bus = registry.service_ref::<BusService>().await?;
         let storage_metrics = config
             .metrics
@@ -311,7 +378,11 @@ impl NodeService {
         // disable dragon hard fork
         //storage_instance.dragon_hard_fork(config.clone())?;
         let upgrade_time = SystemTime::now().duration_since(start_time)?;
-        let storage = Arc::new(Storage::new(storage_instance)?);
+        let storage = Arc::new(
+            Storage::new(storage_instance)?
+                .enable_event_index(config.storage.event_index())
+                .enable_state_prune(config.storage.state_prune_keep_blocks().is_some()),
+        );
         registry.put_shared(storage.clone()).await?;
         let (chain_info, genesis) =
             Genesis::init_and_check_storage(config.net(), storage.clone(), config.data_dir())?;
@@ -328,6 +399,9 @@ impl NodeService {
         let node_service = registry.register::<NodeService>().await?;
 
         registry.register::<ChainStateService>().await?;
+        if config.storage.state_prune_keep_blocks().is_some() {
+            registry.register::<StatePrunerService>().await?;
+        }
 
         let vault_config = &config.vault;
         let account_storage =
@@ -339,7 +413,9 @@ impl NodeService {
         registry.register::<AccountService>().await?;
         registry.register::<AccountEventService>().await?;
 
-        let txpool_service = registry.register::<TxPoolActorService>().await?;
+        let txpool_service = registry
+            .register_with_policy::<TxPoolActorService>(SupervisionPolicy::restart())
+            .await?;
 
         //wait TxPoolService put shared..
         Delay::new(Duration::from_millis(200)).await;
@@ -349,10 +425,17 @@ impl NodeService {
 
         registry.register::<ChainNotifyHandlerService>().await?;
 
-        registry.register::<BlockConnectorService>().await?;
-        registry.register::<SyncService>().await?;
+        // the chain may be left inconsistent by a panic when writing blocks.
+        registry
+            .register_with_policy::<BlockConnectorService>(SupervisionPolicy::Escalate)
+            .await?;
+        registry
+            .register_with_policy::<SyncService>(SupervisionPolicy::restart())
+            .await?;
 
-        let block_relayer = registry.register::<BlockRelayer>().await?;
+        let block_relayer = registry
+            .register_with_policy::<BlockRelayer>(SupervisionPolicy::restart())
+            .await?;
 
         registry.register::<NetworkRpcService>().await?;
         let announcement_service = registry.register::<AnnouncementService>().await?;
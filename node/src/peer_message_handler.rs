@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use network_api::messages::{
-    NotificationMessage, PeerCompactBlockMessage, PeerMessage, PeerTransactionsMessage,
+    NotificationMessage, PeerBlockAnnouncementMessage, PeerCompactBlockMessage, PeerMessage,
+    PeerTransactionsMessage,
 };
 use network_api::PeerMessageHandler;
 use starcoin_block_relayer::BlockRelayer;
@@ -81,6 +82,21 @@ impl PeerMessageHandler for NodePeerMessageHandler {
                     }
                 }
             }
+            NotificationMessage::BlockAnnouncement(message) => {
+                if let Err(e) = self.block_relayer.notify(PeerBlockAnnouncementMessage::new(
+                    peer_message.peer_id,
+                    *message,
+                )) {
+                    match e {
+                        TrySendError::Full(_) => {
+                            warn!("Handle PeerBlockAnnouncement error, BlockRelayer is too busy.");
+                        }
+                        TrySendError::Disconnected(_) => {
+                            error!("Handle PeerBlockAnnouncement error, BlockRelayer is shutdown.");
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -58,7 +58,9 @@ impl ServiceFactory<RpcService> for RpcServiceFactory {
 
         let state_api = ctx
             .service_ref_opt::<ChainStateService>()?
-            .map(|service_ref| StateRpcImpl::new(service_ref.clone(), storage.clone()));
+            .map(|service_ref| {
+                StateRpcImpl::new_with_storage(service_ref.clone(), storage.clone())
+            });
         let chain_state_service = ctx.service_ref::<ChainStateService>()?.clone();
         let account_service = ctx.service_ref_opt::<AccountService>()?.cloned();
         let account_api = account_service.clone().map(|service_ref| {
@@ -71,17 +73,22 @@ impl ServiceFactory<RpcService> for RpcServiceFactory {
         });
         let pubsub_service = ctx.service_ref::<PubSubService>()?.clone();
         let pubsub_api = Some(PubSubImpl::new(pubsub_service));
+        let vm_metrics = ctx.get_shared_opt::<VMMetrics>()?;
         let debug_api = Some(DebugRpcImpl::new(
             config.clone(),
             log_handler,
             ctx.bus_ref().clone(),
+            account_service.clone(),
+            txpool_service.clone(),
+            chain_state_service.clone(),
+            storage.clone(),
+            vm_metrics.clone(),
         ));
         let miner_api = ctx
             .service_ref_opt::<MinerService>()?
             .map(|service_ref| MinerRpcImpl::new(service_ref.clone()));
 
         let contract_api = {
-            let vm_metrics = ctx.get_shared_opt::<VMMetrics>()?;
             let dev_playground = PlaygroudService::new(storage.clone(), vm_metrics);
             ContractRpcImpl::new(
                 config.clone(),
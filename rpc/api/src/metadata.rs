@@ -12,6 +12,28 @@ pub struct Metadata {
     /// Request PubSub Session
     pub session: Option<Arc<Session>>,
     pub user: Option<String>,
+    /// The credential presented by the caller to the transport, authenticated on each call.
+    pub credential: Option<AuthToken>,
+}
+
+/// A bearer token or JWT of a rpc caller, it is not printed by `Debug`.
+#[derive(Clone, Eq, PartialEq)]
+pub struct AuthToken(String);
+
+impl AuthToken {
+    pub fn new(token: String) -> Self {
+        Self(token)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl std::fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AuthToken(***)")
+    }
 }
 
 impl Metadata {
@@ -19,6 +41,7 @@ impl Metadata {
         Self {
             session: Some(session),
             user: None,
+            credential: None,
         }
     }
 }
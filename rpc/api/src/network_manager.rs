@@ -5,6 +5,7 @@ pub use self::gen_client::Client as NetworkManagerClient;
 use crate::types::StrView;
 use crate::FutureResult;
 use jsonrpc_core::Result;
+use network_api::PeerRecord;
 use network_p2p_types::network_state::NetworkState;
 use network_p2p_types::peer_id::PeerId;
 use network_types::peer_info::Multiaddr;
@@ -25,6 +26,26 @@ pub trait NetworkManagerApi {
     #[rpc(name = "network_manager.add_peer")]
     fn add_peer(&self, peer: String) -> FutureResult<()>;
 
+    /// Reserve the peer, format: multiaddr/p2p/peer_id
+    #[rpc(name = "network_manager.add_reserved_peer")]
+    fn add_reserved_peer(&self, peer: String) -> FutureResult<()>;
+
+    /// Remove the peer from the reserved peers.
+    #[rpc(name = "network_manager.remove_reserved_peer")]
+    fn remove_reserved_peer(&self, peer_id: String) -> Result<()>;
+
+    /// Get the records of the peer store, the latest seen first.
+    #[rpc(name = "network_manager.peer_records")]
+    fn peer_records(&self) -> FutureResult<Vec<PeerRecord>>;
+
+    /// Insert or replace a record of the peer store, its addresses, ban and reputation take effect at once.
+    #[rpc(name = "network_manager.put_peer_record")]
+    fn put_peer_record(&self, record: PeerRecord) -> FutureResult<()>;
+
+    /// Remove a record from the peer store and lift its ban and reputation, return the removed record.
+    #[rpc(name = "network_manager.remove_peer_record")]
+    fn remove_peer_record(&self, peer_id: String) -> FutureResult<Option<PeerRecord>>;
+
     /// Call peer's network rpc method.
     #[rpc(name = "network_manager.call")]
     fn call_peer(
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2
 
 pub use self::gen_client::Client as NodeManagerClient;
+use crate::types::BackupManifestView;
 use crate::FutureResult;
 use openrpc_derive::openrpc;
 use starcoin_crypto::HashValue;
@@ -45,6 +46,11 @@ pub trait NodeManagerApi {
     /// Delete failed block of block_id from failed block database
     #[rpc(name = "node_manager.delete_failed_block")]
     fn delete_failed_block(&self, block_hash: HashValue) -> FutureResult<()>;
+
+    /// Backup the node db into `backup_dir` while the node is running, the dir must be under the backup root of the node, and a relative dir is relative to the backup root.
+    /// The backups in the same dir share the SST files, so a later backup only copies the new SST files.
+    #[rpc(name = "node_manager.backup")]
+    fn backup(&self, backup_dir: String) -> FutureResult<BackupManifestView>;
 }
 #[test]
 fn test() {
@@ -12,6 +12,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use starcoin_crypto::HashValue;
+use starcoin_types::block::BlockNumber;
 use starcoin_types::language_storage::{ModuleId, StructTag};
 use starcoin_types::{
     access_path::AccessPath, account_address::AccountAddress, account_state::AccountState,
@@ -37,11 +38,15 @@ pub trait StateApi {
     #[rpc(name = "state.get_account_state")]
     fn get_account_state(&self, address: AccountAddress) -> FutureResult<Option<AccountState>>;
 
+    /// Return the account state set at `state_root`, or at the state of the block selected by
+    /// `block_number` or `block_hash`, default is the latest block state.
     #[rpc(name = "state.get_account_state_set")]
     fn get_account_state_set(
         &self,
         address: AccountAddress,
         state_root: Option<HashValue>,
+        block_number: Option<BlockNumber>,
+        block_hash: Option<HashValue>,
     ) -> FutureResult<Option<AccountStateSetView>>;
 
     #[rpc(name = "state.get_state_root")]
@@ -122,7 +127,12 @@ pub trait StateApi {
 #[serde(default)]
 pub struct GetResourceOption {
     pub decode: bool,
+    /// The state tree root, default is the latest block state root
     pub state_root: Option<HashValue>,
+    /// Read the state of the main chain block at this number.
+    pub block_number: Option<BlockNumber>,
+    /// Read the state of this block.
+    pub block_hash: Option<HashValue>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, Hash, PartialEq, JsonSchema)]
@@ -138,6 +148,10 @@ pub struct ListResourceOption {
     pub decode: bool,
     /// The state tree root, default is the latest block state root
     pub state_root: Option<HashValue>,
+    /// Read the state of the main chain block at this number.
+    pub block_number: Option<BlockNumber>,
+    /// Read the state of this block.
+    pub block_hash: Option<HashValue>,
     pub start_index: usize,
     pub max_size: usize,
     pub resource_types: Option<Vec<StructTagView>>,
@@ -148,6 +162,8 @@ impl Default for ListResourceOption {
         ListResourceOption {
             decode: false,
             state_root: None,
+            block_number: None,
+            block_hash: None,
             start_index: 0,
             max_size: std::usize::MAX,
             resource_types: None,
@@ -6,9 +6,12 @@ use openrpc_derive::openrpc;
 use starcoin_types::transaction::SignedUserTransaction;
 
 pub use self::gen_client::Client as TxPoolClient;
-use crate::types::{SignedUserTransactionView, StrView};
+use crate::types::{
+    BlockFeeHistoryView, GasPriceEstimateView, SignedUserTransactionView, StrView,
+    TxPoolSenderView, TxPoolTxnSummaryView, TxPoolTxnView,
+};
 use starcoin_crypto::HashValue;
-use starcoin_txpool_api::TxPoolStatus;
+use starcoin_txpool_api::{TxPoolStatus, TxnStatusInfo};
 use starcoin_types::account_address::AccountAddress;
 
 #[openrpc]
@@ -19,9 +22,23 @@ pub trait TxPoolApi {
     #[rpc(name = "txpool.submit_hex_transaction")]
     fn submit_hex_transaction(&self, tx: String) -> FutureResult<HashValue>;
 
-    /// return current gas price
+    /// return current gas price, the medium suggestion of `txpool.gas_price_estimate`.
     #[rpc(name = "txpool.gas_price")]
     fn gas_price(&self) -> FutureResult<StrView<u64>>;
+
+    /// return the suggested gas unit prices for low, medium and high priority txns,
+    /// estimated by the txns included in the recent blocks and the pending txns in txpool.
+    #[rpc(name = "txpool.gas_price_estimate")]
+    fn gas_price_estimate(&self) -> FutureResult<GasPriceEstimateView>;
+
+    /// return the gas usage and the gas unit price percentiles of at most `block_count` recent blocks,
+    /// in ascending order of block number. `percentiles` should be in [0, 100].
+    #[rpc(name = "txpool.fee_history")]
+    fn fee_history(
+        &self,
+        block_count: u64,
+        percentiles: Vec<f64>,
+    ) -> FutureResult<Vec<BlockFeeHistoryView>>;
     /// get all pending txns in txpool of given sender.
     /// no matter the state of txn is ready or in future.
     #[rpc(name = "txpool.pending_txns_of_sender")]
@@ -43,6 +60,20 @@ pub trait TxPoolApi {
     /// or `None` if there are no pending transactions from that sender in txpool.
     #[rpc(name = "txpool.state")]
     fn state(&self) -> FutureResult<TxPoolStatus>;
+
+    /// return the lifecycle status of a txn, from the txpool to the finality on the main chain,
+    /// or `None` if the txn is unknown.
+    #[rpc(name = "txpool.transaction_status")]
+    fn transaction_status(&self, txn_hash: HashValue) -> FutureResult<Option<TxnStatusInfo>>;
+
+    /// return all the txns in txpool grouped by sender, split into the ready txns
+    /// and the future txns which are blocked by the sequence number or the expiration.
+    #[rpc(name = "txpool.content")]
+    fn content(&self) -> FutureResult<Vec<TxPoolSenderView<TxPoolTxnView>>>;
+
+    /// same as `txpool.content`, but return the summary of the txns.
+    #[rpc(name = "txpool.inspect")]
+    fn inspect(&self) -> FutureResult<Vec<TxPoolSenderView<TxPoolTxnSummaryView>>>;
 }
 #[test]
 fn test() {
@@ -7,6 +7,7 @@ use hex::FromHex;
 use jsonrpc_core_client::RpcChannel;
 use move_core_types::u256;
 use network_p2p_types::peer_id::PeerId;
+use network_p2p_types::PeerBandwidthStats;
 use network_types::peer_info::PeerInfo;
 pub use node_api_types::*;
 use schemars::{self, JsonSchema};
@@ -20,9 +21,15 @@ use starcoin_abi_decoder::{
 use starcoin_abi_types::ModuleABI;
 use starcoin_accumulator::proof::AccumulatorProof;
 use starcoin_crypto::{CryptoMaterialError, HashValue, ValidCryptoMaterialStringExt};
+use starcoin_gas::{
+    CallTrace, EmittedEvent, GasProfile, GasStat, ResourceAccess, ResourceAccessKind,
+};
 use starcoin_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue};
 use starcoin_service_registry::ServiceRequest;
 use starcoin_state_api::{StateProof, StateWithProof, StateWithTableItemProof};
+use starcoin_txpool_api::{
+    BlockFeeHistory, GasPriceEstimate, PoolTxnInfo, SenderPoolTxns, TxnFutureReason, TxnOrigin,
+};
 use starcoin_types::block::{
     Block, BlockBody, BlockHeader, BlockHeaderExtra, BlockInfo, BlockNumber,
 };
@@ -242,6 +249,18 @@ pub struct DryRunTransactionRequest {
     pub transaction: TransactionRequest,
     /// Sender's public key
     pub sender_public_key: StrView<AccountPublicKey>,
+    /// Record the gas profile of the execution.
+    #[serde(default)]
+    pub profile: bool,
+    /// Dry run on the state of the main chain block at this number, default is the latest state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
+    /// Dry run on the state of this block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<HashValue>,
+    /// Dry run on the state of this state root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_root: Option<HashValue>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -1090,6 +1109,9 @@ pub struct TransactionEventResponse {
     pub decode_event_data: Option<DecodedMoveValue>,
     #[serde(flatten)]
     pub event: TransactionEventView,
+    /// The event is from a block retracted from the main chain, only set by the `events` subscription.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
@@ -1188,6 +1210,177 @@ pub struct DryRunOutputView {
     pub explained_status: VmStatusExplainView,
     #[serde(flatten)]
     pub txn_output: TransactionOutputView,
+    /// The gas profile of the execution, only exists when the profile is requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<GasProfileView>,
+}
+
+/// Gas used by the metered execution, in internal gas units.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GasProfileView {
+    pub total_gas: StrView<u64>,
+    pub intrinsic: StrView<u64>,
+    /// Bytecode instructions by opcode.
+    pub instructions: BTreeMap<String, GasStatView>,
+    /// Native functions by `address::module::function`.
+    pub natives: BTreeMap<String, GasStatView>,
+    /// Global storage instructions, and the write set of the transaction.
+    pub storage: BTreeMap<String, GasStatView>,
+    /// Self gas by call stack, the frames of a stack are separated by `;`.
+    pub frames: BTreeMap<String, StrView<u64>>,
+}
+
+impl From<GasProfile> for GasProfileView {
+    fn from(profile: GasProfile) -> Self {
+        let stats = |stats: BTreeMap<String, GasStat>| {
+            stats
+                .into_iter()
+                .map(|(key, stat)| (key, stat.into()))
+                .collect()
+        };
+        Self {
+            total_gas: profile.total_gas().into(),
+            intrinsic: profile.intrinsic.into(),
+            instructions: stats(profile.instructions),
+            natives: stats(profile.natives),
+            storage: stats(profile.storage),
+            frames: profile
+                .frames
+                .into_iter()
+                .map(|(stack, gas)| (stack, gas.into()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GasStatView {
+    pub count: u64,
+    pub gas: StrView<u64>,
+}
+
+impl From<GasStat> for GasStatView {
+    fn from(stat: GasStat) -> Self {
+        Self {
+            count: stat.count,
+            gas: stat.gas.into(),
+        }
+    }
+}
+
+/// The result of a traced transaction execution.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionTraceView {
+    /// The traced transaction hash, None for a traced dry run transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<HashValue>,
+    pub explained_status: VmStatusExplainView,
+    #[serde(flatten)]
+    pub txn_output: TransactionOutputView,
+    /// The state keys read by the execution, including the prologue and epilogue.
+    pub read_set: Vec<StateKeyView>,
+    /// The call tree of the execution, None if the transaction is discarded before execution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_trace: Option<CallTraceView>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CallTraceView {
+    /// None for the script of a script transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<ModuleIdView>,
+    pub function: String,
+    pub ty_args: Vec<TypeTagView>,
+    /// The rendered arguments, the arguments of the entry function are bcs hex.
+    pub args: Vec<String>,
+    pub is_native: bool,
+    /// Gas used by the call, including the sub calls.
+    pub gas_used: StrView<u64>,
+    pub resources: Vec<ResourceAccessView>,
+    pub events: Vec<EmittedEventView>,
+    pub calls: Vec<CallTraceView>,
+    /// The execution aborts in the call or in its sub calls.
+    pub aborted: bool,
+}
+
+impl From<CallTrace> for CallTraceView {
+    fn from(trace: CallTrace) -> Self {
+        Self {
+            module: trace.module.map(StrView),
+            function: trace.function,
+            ty_args: trace.ty_args.into_iter().map(StrView).collect(),
+            args: trace.args,
+            is_native: trace.is_native,
+            gas_used: trace.gas_used.into(),
+            resources: trace.resources.into_iter().map(Into::into).collect(),
+            events: trace.events.into_iter().map(Into::into).collect(),
+            calls: trace.calls.into_iter().map(Into::into).collect(),
+            aborted: trace.aborted,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ResourceAccessKindView {
+    BorrowGlobal,
+    BorrowGlobalMut,
+    Exists,
+    MoveFrom,
+    MoveTo,
+}
+
+impl From<ResourceAccessKind> for ResourceAccessKindView {
+    fn from(kind: ResourceAccessKind) -> Self {
+        match kind {
+            ResourceAccessKind::BorrowGlobal => Self::BorrowGlobal,
+            ResourceAccessKind::BorrowGlobalMut => Self::BorrowGlobalMut,
+            ResourceAccessKind::Exists => Self::Exists,
+            ResourceAccessKind::MoveFrom => Self::MoveFrom,
+            ResourceAccessKind::MoveTo => Self::MoveTo,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceAccessView {
+    pub kind: ResourceAccessKindView,
+    /// None if the address is not visible to the tracer, such as a signer borrowed from a local.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AccountAddress>,
+    pub type_tag: TypeTagView,
+    /// False if the resource does not exist, or already exists for MoveTo.
+    pub success: bool,
+}
+
+impl From<ResourceAccess> for ResourceAccessView {
+    fn from(access: ResourceAccess) -> Self {
+        Self {
+            kind: access.kind.into(),
+            address: access.address,
+            type_tag: access.type_tag.into(),
+            success: access.success,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EmittedEventView {
+    pub event_key: StrView<Vec<u8>>,
+    pub event_seq_number: StrView<u64>,
+    pub type_tag: TypeTagView,
+    /// The rendered event data.
+    pub data: String,
+}
+
+impl From<EmittedEvent> for EmittedEventView {
+    fn from(event: EmittedEvent) -> Self {
+        Self {
+            event_key: StrView(event.key),
+            event_seq_number: event.seq_number.into(),
+            type_tag: event.type_tag.into(),
+            data: event.data,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -1315,6 +1508,172 @@ impl From<ChainInfo> for ChainInfoView {
     }
 }
 
+/// The manifest of a node db backup.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BackupManifestView {
+    pub backup_id: u64,
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub storage_version: u8,
+    pub chain_info: ChainInfoView,
+    /// Number of the db files in the backup.
+    pub files: u64,
+    pub total_size: StrView<u64>,
+    /// Number of the files copied by the backup, the other files are shared with the former backups.
+    pub copied_files: u64,
+    pub copied_size: StrView<u64>,
+}
+
+/// Suggested gas unit prices for the transactions of different priority.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GasPriceEstimateView {
+    pub low: StrView<u64>,
+    pub medium: StrView<u64>,
+    pub high: StrView<u64>,
+}
+
+impl From<GasPriceEstimate> for GasPriceEstimateView {
+    fn from(estimate: GasPriceEstimate) -> Self {
+        Self {
+            low: estimate.low.into(),
+            medium: estimate.medium.into(),
+            high: estimate.high.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BlockFeeHistoryView {
+    pub number: StrView<u64>,
+    pub block_hash: HashValue,
+    pub gas_used: StrView<u64>,
+    pub gas_limit: StrView<u64>,
+    pub txn_count: u64,
+    /// Gas unit prices at the requested percentiles, 0 if the block has no user txn.
+    pub gas_price_percentiles: Vec<StrView<u64>>,
+}
+
+impl From<BlockFeeHistory> for BlockFeeHistoryView {
+    fn from(history: BlockFeeHistory) -> Self {
+        Self {
+            number: history.number.into(),
+            block_hash: history.block_hash,
+            gas_used: history.gas_used.into(),
+            gas_limit: history.gas_limit.into(),
+            txn_count: history.txn_count,
+            gas_price_percentiles: history
+                .gas_price_percentiles
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// A txn in the txpool with its scoring and readiness.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TxPoolTxnView {
+    pub transaction: SignedUserTransactionView,
+    pub score: StrView<u64>,
+    /// The local timestamp in milliseconds when the txn entered the txpool.
+    pub insertion_time: StrView<u64>,
+    pub origin: TxnOrigin,
+    /// `None` if the txn is ready.
+    pub future_reason: Option<TxnFutureReason>,
+}
+
+impl TryFrom<PoolTxnInfo> for TxPoolTxnView {
+    type Error = anyhow::Error;
+
+    fn try_from(info: PoolTxnInfo) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction: info.txn.try_into()?,
+            score: info.score.into(),
+            insertion_time: info.insertion_time.into(),
+            origin: info.origin,
+            future_reason: info.future_reason,
+        })
+    }
+}
+
+/// The summary of a txn in the txpool.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TxPoolTxnSummaryView {
+    pub transaction_hash: HashValue,
+    pub sequence_number: StrView<u64>,
+    pub gas_unit_price: StrView<u64>,
+    pub max_gas_amount: StrView<u64>,
+    pub gas_token_code: String,
+    pub expiration_timestamp_secs: StrView<u64>,
+    pub score: StrView<u64>,
+    /// The local timestamp in milliseconds when the txn entered the txpool.
+    pub insertion_time: StrView<u64>,
+    pub origin: TxnOrigin,
+    /// `None` if the txn is ready.
+    pub future_reason: Option<TxnFutureReason>,
+}
+
+impl From<PoolTxnInfo> for TxPoolTxnSummaryView {
+    fn from(info: PoolTxnInfo) -> Self {
+        Self {
+            transaction_hash: info.txn.id(),
+            sequence_number: info.txn.sequence_number().into(),
+            gas_unit_price: info.txn.gas_unit_price().into(),
+            max_gas_amount: info.txn.max_gas_amount().into(),
+            gas_token_code: info.txn.gas_token_code().to_string(),
+            expiration_timestamp_secs: info.txn.expiration_timestamp_secs().into(),
+            score: info.score.into(),
+            insertion_time: info.insertion_time.into(),
+            origin: info.origin,
+            future_reason: info.future_reason,
+        }
+    }
+}
+
+/// The txns of a sender in the txpool, in ascending order of sequence number.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TxPoolSenderView<T> {
+    pub sender: AccountAddress,
+    /// The sequence number of the sender on chain.
+    pub sequence_number: StrView<u64>,
+    /// The txns which can be packed into the next block.
+    pub ready: Vec<T>,
+    /// The txns blocked by the sequence number or the expiration.
+    pub future: Vec<T>,
+}
+
+impl TryFrom<SenderPoolTxns> for TxPoolSenderView<TxPoolTxnView> {
+    type Error = anyhow::Error;
+
+    fn try_from(txns: SenderPoolTxns) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sender: txns.sender,
+            sequence_number: txns.sequence_number.into(),
+            ready: txns
+                .ready
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            future: txns
+                .future
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<SenderPoolTxns> for TxPoolSenderView<TxPoolTxnSummaryView> {
+    fn from(txns: SenderPoolTxns) -> Self {
+        Self {
+            sender: txns.sender,
+            sequence_number: txns.sequence_number.into(),
+            ready: txns.ready.into_iter().map(Into::into).collect(),
+            future: txns.future.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PeerInfoView {
     pub peer_id: PeerId,
@@ -1322,6 +1681,9 @@ pub struct PeerInfoView {
     pub notif_protocols: String,
     pub rpc_protocols: String,
     pub version_string: Option<String>,
+    /// Transferred bytes with the peer, by protocol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bandwidth: Option<PeerBandwidthStats>,
 }
 
 impl From<PeerInfo> for PeerInfoView {
@@ -1332,6 +1694,7 @@ impl From<PeerInfo> for PeerInfoView {
             notif_protocols: info.notif_protocols.join(","),
             rpc_protocols: info.rpc_protocols.join(","),
             version_string: info.version_string,
+            bandwidth: None,
         }
     }
 }
@@ -1822,6 +2185,15 @@ pub struct ContractCall {
     pub function_id: FunctionIdView,
     pub type_args: Vec<TypeTagView>,
     pub args: Vec<TransactionArgumentView>,
+    /// Call on the state of the main chain block at this number, default is the latest state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
+    /// Call on the state of this block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<HashValue>,
+    /// Call on the state of this state root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_root: Option<HashValue>,
 }
 
 #[derive(Debug, Clone)]
@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors;
-use crate::types::{BlockView, TransactionEventResponse, TypeTagView};
+use crate::types::{BlockHeaderView, BlockView, TransactionEventResponse, TypeTagView};
 use jsonrpc_core::error::Error as JsonRpcError;
 use schemars::{self, JsonSchema};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Value};
 use starcoin_crypto::HashValue;
+use starcoin_txpool_api::TxnStatusInfo;
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::event::EventKey;
 use starcoin_types::filter::Filter;
@@ -30,6 +31,10 @@ pub enum Kind {
     NewPendingTransactions,
     /// New block for minting
     NewMintBlock,
+    /// Main chain switches to another branch.
+    ChainReorg,
+    /// Lifecycle status changes of the txns, filtered by txn hash or sender.
+    TxnStatus,
 }
 
 /// Subscription result.
@@ -41,6 +46,8 @@ pub enum Result {
     TransactionHash(Vec<HashValue>),
     Event(Box<TransactionEventResponse>),
     MintBlock(Box<MintBlockEvent>),
+    ChainReorg(Box<ChainReorgView>),
+    TxnStatus(Box<TxnStatusInfo>),
 }
 
 impl Serialize for Result {
@@ -53,6 +60,8 @@ impl Serialize for Result {
             Result::Event(ref evt) => evt.serialize(serializer),
             Result::TransactionHash(ref hash) => hash.serialize(serializer),
             Result::MintBlock(ref block) => block.serialize(serializer), // Result::SyncState(ref sync) => sync.serialize(serializer),
+            Result::ChainReorg(ref reorg) => reorg.serialize(serializer),
+            Result::TxnStatus(ref status) => status.serialize(serializer),
         }
     }
 }
@@ -64,6 +73,8 @@ pub enum Params {
     None,
     /// Log parameters.
     Events(EventParams),
+    /// Txn status parameters.
+    TxnStatus(TxnStatusParams),
 }
 
 impl Default for Params {
@@ -82,6 +93,12 @@ impl<'a> Deserialize<'a> for Params {
         if v.is_null() {
             return Ok(Params::None);
         }
+        // an empty object is an event filter.
+        if let Ok(params) = from_value::<TxnStatusParams>(v.clone()) {
+            if !params.is_empty() {
+                return Ok(Params::TxnStatus(params));
+            }
+        }
         // Err(D::Error::custom("Invalid Pub-Sub parameters"));
         from_value(v)
             .map(Params::Events)
@@ -97,6 +114,28 @@ pub struct EventParams {
     pub decode: bool,
 }
 
+/// Filter of the txn status subscription, a txn matches if it matches all the given fields.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Eq, Hash, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TxnStatusParams {
+    #[serde(default)]
+    pub txn_hash: Option<HashValue>,
+    #[serde(default)]
+    pub sender: Option<AccountAddress>,
+}
+
+impl TxnStatusParams {
+    pub fn is_empty(&self) -> bool {
+        self.txn_hash.is_none() && self.sender.is_none()
+    }
+
+    pub fn matching(&self, status: &TxnStatusInfo) -> bool {
+        self.txn_hash
+            .map_or(true, |txn_hash| txn_hash == status.txn_hash)
+            && self.sender.map_or(true, |sender| sender == status.sender)
+    }
+}
+
 /// Filter
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Eq, Hash, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -165,3 +204,15 @@ pub struct MintBlock {
     pub difficulty: U256,
     pub block_number: u64,
 }
+
+/// The main chain switches to another branch,
+/// the events of the retracted blocks are notified to the `events` subscriptions with `removed: true`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ChainReorgView {
+    /// The common ancestor of the old and the new main chain.
+    pub ancestor: BlockHeaderView,
+    /// The blocks removed from the main chain, in ascending order of block number.
+    pub retracted: Vec<HashValue>,
+    /// The blocks added to the main chain, in ascending order of block number.
+    pub enacted: Vec<HashValue>,
+}
@@ -4,6 +4,7 @@
 use crate::chain_watcher::{ChainWatcher, StartSubscribe, WatchBlock, WatchTxn};
 use crate::pubsub_client::PubSubClient;
 pub use crate::remote_state_reader::{RemoteStateReader, StateRootOption};
+pub use crate::verified_client::VerifiedClient;
 use actix::{Addr, Arbiter, System};
 use anyhow::anyhow;
 use bcs_ext::BCSCodec;
@@ -12,7 +13,7 @@ use futures::{TryStream, TryStreamExt};
 use jsonrpc_client_transports::RawClient;
 pub use jsonrpc_core::Params;
 use jsonrpc_core_client::{transports::ipc, transports::ws, RpcChannel};
-use network_api::PeerStrategy;
+use network_api::{PeerRecord, PeerStrategy};
 use network_p2p_types::network_state::NetworkState;
 use network_p2p_types::peer_id::PeerId;
 use network_types::peer_info::Multiaddr;
@@ -30,15 +31,16 @@ use starcoin_rpc_api::service::RpcAsyncService;
 use starcoin_rpc_api::state::{
     GetCodeOption, GetResourceOption, ListCodeOption, ListResourceOption,
 };
-use starcoin_rpc_api::types::pubsub::EventFilter;
+use starcoin_rpc_api::types::pubsub::{ChainReorgView, EventFilter, TxnStatusParams};
 use starcoin_rpc_api::types::{
-    AccountStateSetView, AnnotatedMoveStructView, BlockHeaderView, BlockInfoView, BlockView,
-    ChainId, ChainInfoView, CodeView, ContractCall, DecodedMoveValue, DryRunOutputView,
-    DryRunTransactionRequest, FactoryAction, FunctionIdView, ListCodeView, ListResourceView,
-    MintedBlockView, ModuleIdView, PeerInfoView, ResourceView, SignedMessageView,
-    SignedUserTransactionView, StateWithProofView, StateWithTableItemProofView, StrView,
-    StructTagView, TableInfoView, TransactionEventResponse, TransactionInfoView,
-    TransactionInfoWithProofView, TransactionRequest, TransactionView,
+    AccountStateSetView, AnnotatedMoveStructView, BackupManifestView, BlockFeeHistoryView,
+    BlockHeaderView, BlockInfoView, BlockView, ChainId, ChainInfoView, CodeView, ContractCall,
+    DecodedMoveValue, DryRunOutputView, DryRunTransactionRequest, FactoryAction, FunctionIdView,
+    GasPriceEstimateView, ListCodeView, ListResourceView, MintedBlockView, ModuleIdView,
+    PeerInfoView, ResourceView, SignedMessageView, SignedUserTransactionView, StateWithProofView,
+    StateWithTableItemProofView, StrView, StructTagView, TableInfoView, TransactionEventResponse,
+    TransactionInfoView, TransactionInfoWithProofView, TransactionRequest, TransactionTraceView,
+    TransactionView, TxPoolSenderView, TxPoolTxnSummaryView, TxPoolTxnView,
 };
 use starcoin_rpc_api::{
     account::AccountClient, chain::ChainClient, contract_api::ContractClient, debug::DebugClient,
@@ -48,7 +50,7 @@ use starcoin_rpc_api::{
 };
 use starcoin_service_registry::{ServiceInfo, ServiceStatus};
 use starcoin_sync_api::{PeerScoreResponse, SyncProgressReport};
-use starcoin_txpool_api::TxPoolStatus;
+use starcoin_txpool_api::{TxPoolStatus, TxnStatusInfo};
 use starcoin_types::access_path::AccessPath;
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::account_state::AccountState;
@@ -70,6 +72,7 @@ use tokio::runtime::Runtime;
 pub mod chain_watcher;
 mod pubsub_client;
 mod remote_state_reader;
+mod verified_client;
 
 #[derive(Clone)]
 enum ConnSource {
@@ -288,6 +291,11 @@ impl RpcClient {
             .map_err(map_err)
     }
 
+    pub fn node_backup(&self, backup_dir: String) -> anyhow::Result<BackupManifestView> {
+        self.call_rpc_blocking(|inner| inner.node_manager_client.backup(backup_dir))
+            .map_err(map_err)
+    }
+
     pub fn next_sequence_number_in_txpool(
         &self,
         address: AccountAddress,
@@ -500,6 +508,11 @@ impl RpcClient {
         RemoteStateReader::new(self, state_root_opt)
     }
 
+    /// Create a client verifies the chain data by a light client from the trusted checkpoint.
+    pub fn verified_client(&self, checkpoint: HashValue) -> anyhow::Result<VerifiedClient> {
+        VerifiedClient::new(self, checkpoint)
+    }
+
     pub fn state_get(&self, access_path: AccessPath) -> anyhow::Result<Option<Vec<u8>>> {
         self.call_rpc_blocking(|inner| inner.state_client.get(access_path))
             .map_err(map_err)
@@ -560,7 +573,7 @@ impl RpcClient {
         self.call_rpc_blocking(|inner| {
             inner
                 .state_client
-                .get_account_state_set(address, state_root)
+                .get_account_state_set(address, state_root, None, None)
         })
         .map_err(map_err)
     }
@@ -576,7 +589,11 @@ impl RpcClient {
             inner.state_client.get_resource(
                 address,
                 StrView(resource_type),
-                Some(GetResourceOption { decode, state_root }),
+                Some(GetResourceOption {
+                    decode,
+                    state_root,
+                    ..Default::default()
+                }),
             )
         })
         .map_err(map_err)
@@ -600,6 +617,7 @@ impl RpcClient {
                     start_index,
                     max_size,
                     resource_types,
+                    ..Default::default()
                 }),
             )
         })
@@ -741,6 +759,22 @@ impl RpcClient {
             .map_err(map_err)
     }
 
+    pub fn debug_trace_transaction(
+        &self,
+        txn_hash: HashValue,
+    ) -> anyhow::Result<TransactionTraceView> {
+        self.call_rpc_blocking(|inner| inner.debug_client.trace_transaction(txn_hash))
+            .map_err(map_err)
+    }
+
+    pub fn debug_trace_call(
+        &self,
+        txn: DryRunTransactionRequest,
+    ) -> anyhow::Result<TransactionTraceView> {
+        self.call_rpc_blocking(|inner| inner.debug_client.trace_call(txn))
+            .map_err(map_err)
+    }
+
     pub fn chain_id(&self) -> anyhow::Result<ChainId> {
         self.call_rpc_blocking(|inner| inner.chain_client.id())
             .map_err(map_err)
@@ -938,6 +972,38 @@ impl RpcClient {
             .map_err(map_err)
     }
 
+    pub fn txpool_transaction_status(
+        &self,
+        txn_hash: HashValue,
+    ) -> anyhow::Result<Option<TxnStatusInfo>> {
+        self.call_rpc_blocking(|inner| inner.txpool_client.transaction_status(txn_hash))
+            .map_err(map_err)
+    }
+
+    pub fn txpool_gas_price_estimate(&self) -> anyhow::Result<GasPriceEstimateView> {
+        self.call_rpc_blocking(|inner| inner.txpool_client.gas_price_estimate())
+            .map_err(map_err)
+    }
+
+    pub fn txpool_fee_history(
+        &self,
+        block_count: u64,
+        percentiles: Vec<f64>,
+    ) -> anyhow::Result<Vec<BlockFeeHistoryView>> {
+        self.call_rpc_blocking(|inner| inner.txpool_client.fee_history(block_count, percentiles))
+            .map_err(map_err)
+    }
+
+    pub fn txpool_content(&self) -> anyhow::Result<Vec<TxPoolSenderView<TxPoolTxnView>>> {
+        self.call_rpc_blocking(|inner| inner.txpool_client.content())
+            .map_err(map_err)
+    }
+
+    pub fn txpool_inspect(&self) -> anyhow::Result<Vec<TxPoolSenderView<TxPoolTxnSummaryView>>> {
+        self.call_rpc_blocking(|inner| inner.txpool_client.inspect())
+            .map_err(map_err)
+    }
+
     pub fn subscribe_events(
         &self,
         filter: EventFilter,
@@ -967,6 +1033,26 @@ impl RpcClient {
         })
         .map_err(map_err)
     }
+    pub fn subscribe_chain_reorg(
+        &self,
+    ) -> anyhow::Result<impl TryStream<Ok = ChainReorgView, Error = anyhow::Error>> {
+        self.call_rpc_blocking(|inner| async move {
+            let res = inner.pubsub_client.subscribe_chain_reorg().await;
+            res.map(|s| s.map_err(map_err))
+        })
+        .map_err(map_err)
+    }
+
+    pub fn subscribe_txn_status(
+        &self,
+        params: TxnStatusParams,
+    ) -> anyhow::Result<impl TryStream<Ok = TxnStatusInfo, Error = anyhow::Error>> {
+        self.call_rpc_blocking(|inner| async move {
+            let res = inner.pubsub_client.subscribe_txn_status(params).await;
+            res.map(|s| s.map_err(map_err))
+        })
+        .map_err(map_err)
+    }
 
     pub fn subscribe_new_mint_blocks(
         &self,
@@ -1091,6 +1177,34 @@ impl RpcClient {
             .map_err(map_err)
     }
 
+    pub fn network_add_reserved_peer(&self, peer: String) -> anyhow::Result<()> {
+        self.call_rpc_blocking(|inner| inner.network_client.add_reserved_peer(peer))
+            .map_err(map_err)
+    }
+
+    pub fn network_remove_reserved_peer(&self, peer_id: String) -> anyhow::Result<()> {
+        self.call_rpc_blocking(|inner| inner.network_client.remove_reserved_peer(peer_id))
+            .map_err(map_err)
+    }
+
+    pub fn network_peer_records(&self) -> anyhow::Result<Vec<PeerRecord>> {
+        self.call_rpc_blocking(|inner| inner.network_client.peer_records())
+            .map_err(map_err)
+    }
+
+    pub fn network_put_peer_record(&self, record: PeerRecord) -> anyhow::Result<()> {
+        self.call_rpc_blocking(|inner| inner.network_client.put_peer_record(record))
+            .map_err(map_err)
+    }
+
+    pub fn network_remove_peer_record(
+        &self,
+        peer_id: String,
+    ) -> anyhow::Result<Option<PeerRecord>> {
+        self.call_rpc_blocking(|inner| inner.network_client.remove_peer_record(peer_id))
+            .map_err(map_err)
+    }
+
     pub fn network_call_peer(
         &self,
         peer_id: String,
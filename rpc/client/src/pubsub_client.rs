@@ -3,8 +3,9 @@
 
 use jsonrpc_core_client::*;
 use starcoin_crypto::HashValue;
-use starcoin_rpc_api::types::pubsub::EventParams;
+use starcoin_rpc_api::types::pubsub::{ChainReorgView, EventParams, TxnStatusParams};
 use starcoin_rpc_api::types::{pubsub::EventFilter, pubsub::Kind, BlockView, TransactionEventView};
+use starcoin_txpool_api::TxnStatusInfo;
 use starcoin_types::system_events::MintBlockEvent;
 
 const STARCOIN_SUBSCRIPTION: &str = "starcoin_subscription";
@@ -76,4 +77,27 @@ impl PubSubClient {
             "MintBlockEvent",
         )
     }
+    pub async fn subscribe_chain_reorg(
+        &self,
+    ) -> Result<TypedSubscriptionStream<ChainReorgView>, RpcError> {
+        self.client.subscribe(
+            STARCOIN_SUBSCRIBE,
+            vec![Kind::ChainReorg],
+            STARCOIN_SUBSCRIPTION,
+            STARCOIN_UNSUBSCRIBE,
+            "ChainReorgView",
+        )
+    }
+    pub async fn subscribe_txn_status(
+        &self,
+        params: TxnStatusParams,
+    ) -> Result<TypedSubscriptionStream<TxnStatusInfo>, RpcError> {
+        self.client.subscribe(
+            STARCOIN_SUBSCRIBE,
+            (Kind::TxnStatus, params),
+            STARCOIN_SUBSCRIPTION,
+            STARCOIN_UNSUBSCRIBE,
+            "TxnStatusInfo",
+        )
+    }
 }
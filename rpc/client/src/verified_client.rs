@@ -0,0 +1,220 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::RpcClient;
+use anyhow::{ensure, format_err, Result};
+use starcoin_chain_api::TransactionInfoWithProof;
+use starcoin_crypto::HashValue;
+use starcoin_light_client::{Checkpoint, LightClient};
+use starcoin_logger::prelude::*;
+use starcoin_state_api::StateWithProof;
+use starcoin_types::access_path::AccessPath;
+use starcoin_types::block::{BlockHeader, BlockNumber};
+use starcoin_vm_types::on_chain_resource::Epoch;
+
+/// Max headers fetched by one request.
+const MAX_HEADERS_PER_REQUEST: u64 = 32;
+
+/// `VerifiedClient` verifies the chain data from the node by a light client, it trusts nothing
+/// but the checkpoint block. The light client follows the node head by `sync`, and the state and
+/// transaction proofs are verified against the synced headers.
+pub struct VerifiedClient<'a> {
+    client: &'a RpcClient,
+    light_client: LightClient,
+}
+
+impl<'a> VerifiedClient<'a> {
+    pub(crate) fn new(client: &'a RpcClient, checkpoint: HashValue) -> Result<Self> {
+        let trusted_header: BlockHeader = client
+            .chain_get_block_by_hash(checkpoint, None)?
+            .ok_or_else(|| format_err!("Can not find block by hash:{}", checkpoint))?
+            .header
+            .into();
+        let epoch = get_state_with_proof(
+            client,
+            LightClient::epoch_access_path(),
+            trusted_header.state_root(),
+        )?;
+        // the epoch is verified by the light client, it is decoded here for the difficulty
+        // window only.
+        let difficulty_window = match epoch.state.as_deref() {
+            Some(epoch) => bcs_ext::from_bytes::<Epoch>(epoch)?.block_difficulty_window(),
+            None => 0,
+        };
+        let block_info = client
+            .chain_get_block_info_by_number(trusted_header.number())?
+            .ok_or_else(|| {
+                format_err!(
+                    "Can not find block info by number: {}",
+                    trusted_header.number()
+                )
+            })?
+            .into_info();
+        let mut headers = match trusted_header.number().checked_sub(1) {
+            Some(parent_number) if difficulty_window > 1 => get_headers_by_number(
+                client,
+                trusted_header
+                    .number()
+                    .saturating_sub(difficulty_window.saturating_sub(1)),
+                parent_number,
+            )?,
+            _ => vec![],
+        };
+        headers.push(trusted_header);
+        let light_client = LightClient::new(
+            checkpoint,
+            Checkpoint {
+                headers,
+                block_info,
+                epoch,
+            },
+        )?;
+        Ok(Self {
+            client,
+            light_client,
+        })
+    }
+
+    /// The verified head. The light client is not exposed, the total difficulty in it starts
+    /// from the unverified block info of the checkpoint.
+    pub fn head(&self) -> &BlockHeader {
+        self.light_client.head()
+    }
+
+    /// The verified header of the block, None if the block is not in the headers kept by the
+    /// light client.
+    pub fn get_header(&self, block_id: HashValue) -> Option<&BlockHeader> {
+        self.light_client.get_header(block_id)
+    }
+
+    /// Apply the headers to the node head, return the verified head.
+    pub fn sync(&mut self) -> Result<&BlockHeader> {
+        let node_head = self.client.chain_info()?.head.number.0;
+        while self.light_client.head().number() < node_head {
+            let start = self.light_client.head().number().saturating_add(1);
+            let end = node_head.min(start.saturating_add(MAX_HEADERS_PER_REQUEST - 1));
+            for header in get_headers_by_number(self.client, start, end)? {
+                if self.light_client.epoch_outdated() {
+                    let epoch = get_state_with_proof(
+                        self.client,
+                        LightClient::epoch_access_path(),
+                        self.light_client.head().state_root(),
+                    )?;
+                    self.light_client.update_epoch(&epoch)?;
+                }
+                self.light_client.apply_header(header)?;
+            }
+            debug!(
+                "Verified client synced to block {}",
+                self.light_client.head().number()
+            );
+        }
+        Ok(self.light_client.head())
+    }
+
+    /// Get the verified state at the block, default to the verified head.
+    pub fn state_get(
+        &self,
+        access_path: AccessPath,
+        block_id: Option<HashValue>,
+    ) -> Result<Option<Vec<u8>>> {
+        let header = self.verified_header(block_id)?;
+        let state_proof =
+            get_state_with_proof(self.client, access_path.clone(), header.state_root())?;
+        self.light_client
+            .verify_state(header.id(), access_path, &state_proof)?;
+        Ok(state_proof.state)
+    }
+
+    /// Get the verified transaction info, the event and the state in it are verified if the index
+    /// and the access path are given.
+    pub fn chain_get_transaction_proof(
+        &self,
+        block_id: Option<HashValue>,
+        transaction_global_index: u64,
+        event_index: Option<u64>,
+        access_path: Option<AccessPath>,
+    ) -> Result<Option<TransactionInfoWithProof>> {
+        let header = self.verified_header(block_id)?;
+        let txn_proof = match self.client.chain_get_transaction_proof_raw(
+            header.id(),
+            transaction_global_index,
+            event_index,
+            access_path.clone(),
+        )? {
+            Some(txn_proof) => {
+                bcs_ext::from_bytes::<TransactionInfoWithProof>(txn_proof.0.as_slice())?
+            }
+            None => return Ok(None),
+        };
+        self.light_client.verify_transaction(
+            header.id(),
+            &txn_proof,
+            transaction_global_index,
+            event_index,
+            access_path,
+        )?;
+        Ok(Some(txn_proof))
+    }
+
+    fn verified_header(&self, block_id: Option<HashValue>) -> Result<&BlockHeader> {
+        match block_id {
+            Some(block_id) => self
+                .light_client
+                .get_header(block_id)
+                .ok_or_else(|| format_err!("Block {} is not verified", block_id)),
+            None => Ok(self.light_client.head()),
+        }
+    }
+}
+
+fn get_state_with_proof(
+    client: &RpcClient,
+    access_path: AccessPath,
+    state_root: HashValue,
+) -> Result<StateWithProof> {
+    let state_proof = client.state_get_with_proof_by_root_raw(access_path, state_root)?;
+    bcs_ext::from_bytes::<StateWithProof>(state_proof.0.as_slice())
+}
+
+/// Get the headers in [start, end] of the node main chain, the headers are verified by the
+/// light client later.
+fn get_headers_by_number(
+    client: &RpcClient,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> Result<Vec<BlockHeader>> {
+    let mut headers: Vec<BlockHeader> = vec![];
+    let mut next_end = Some(end);
+    while let Some(current_end) = next_end.filter(|number| *number >= start) {
+        let count = current_end.saturating_sub(start).saturating_add(1);
+        let blocks = client.chain_get_blocks_by_number(
+            Some(current_end),
+            count.min(MAX_HEADERS_PER_REQUEST),
+            None,
+        )?;
+        ensure!(
+            !blocks.is_empty(),
+            "Can not find blocks by number: {}",
+            current_end
+        );
+        let mut lowest = current_end;
+        for block in blocks {
+            let header: BlockHeader = block.header.into();
+            if header.number() >= start && header.number() <= current_end {
+                lowest = lowest.min(header.number());
+                headers.push(header);
+            }
+        }
+        next_end = lowest.checked_sub(1);
+    }
+    headers.sort_by_key(|header| header.number());
+    headers.dedup_by_key(|header| header.number());
+    ensure!(
+        !headers.is_empty(),
+        "Can not find headers by number: [{}, {}]",
+        start,
+        end
+    );
+    Ok(headers)
+}
@@ -4,6 +4,8 @@
 use anyhow::Result;
 use futures::{StreamExt, TryStreamExt};
 use starcoin_config::NodeConfig;
+use starcoin_crypto::HashValue;
+use starcoin_light_client::LightClient;
 use starcoin_logger::prelude::*;
 use starcoin_rpc_client::RpcClient;
 use starcoin_types::system_events::MintBlockEvent;
@@ -139,3 +141,56 @@ fn test_client_reconnect_subscribe() -> Result<()> {
     assert_ne!(events2.len(), 0);
     Ok(())
 }
+
+#[stest::test(timeout = 120)]
+fn test_verified_client() -> Result<()> {
+    let config = Arc::new(NodeConfig::random_for_test());
+    let node_handle = test_helper::run_node_by_config(config)?;
+    let client = RpcClient::connect_local(node_handle.rpc_service()?)?;
+    for _i in 0..3 {
+        node_handle.generate_block()?;
+    }
+    let checkpoint = client.chain_info()?.head.block_hash;
+    for _i in 0..3 {
+        node_handle.generate_block()?;
+    }
+
+    // the checkpoint is not a block of the node.
+    assert!(client.verified_client(HashValue::random()).is_err());
+    let mut verified_client = client.verified_client(checkpoint)?;
+    let head = verified_client.sync()?.clone();
+    let chain_info = client.chain_info()?;
+    assert_eq!(head.id(), chain_info.head.block_hash);
+    assert_eq!(verified_client.head().id(), head.id());
+    assert!(verified_client.get_header(checkpoint).is_some());
+
+    let access_path = LightClient::epoch_access_path();
+    assert!(verified_client
+        .state_get(access_path.clone(), None)?
+        .is_some());
+    assert!(verified_client
+        .state_get(access_path.clone(), Some(checkpoint))?
+        .is_some());
+    // the block is not verified by the light client.
+    assert!(verified_client
+        .state_get(access_path, Some(HashValue::random()))
+        .is_err());
+
+    // the block metadata transaction of the head.
+    let txn_global_index = chain_info
+        .block_info
+        .into_info()
+        .get_txn_accumulator_info()
+        .get_num_leaves()
+        - 1;
+    let txn_proof =
+        verified_client.chain_get_transaction_proof(None, txn_global_index, Some(0), None)?;
+    assert!(txn_proof.is_some());
+
+    drop(verified_client);
+    client.close();
+    if let Err(e) = node_handle.stop() {
+        error!("node stop error: {:?}", e)
+    }
+    Ok(())
+}
@@ -1,14 +1,20 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2
 
+use crate::auth::{RpcAuthMiddleware, RpcAuthenticator};
 use crate::rate_limit_middleware::JsonApiRateLimitMiddleware;
 use jsonrpc_core::{MetaIoHandler, RemoteProcedure};
 use starcoin_config::{Api, ApiQuotaConfiguration};
 use starcoin_rpc_api::metadata::Metadata;
 use starcoin_rpc_middleware::{MetricMiddleware, RpcMetrics};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-type Middlewares = (MetricMiddleware, JsonApiRateLimitMiddleware);
+type Middlewares = (
+    MetricMiddleware,
+    RpcAuthMiddleware,
+    JsonApiRateLimitMiddleware,
+);
 
 pub struct ApiRegistry {
     apis: HashMap<Api, MetaIoHandler<Metadata, Middlewares>>,
@@ -34,15 +40,18 @@ impl ApiRegistry {
         let io_handler = self.apis.entry(api_type).or_insert_with(|| {
             MetaIoHandler::<Metadata, Middlewares>::with_middleware((
                 MetricMiddleware::new(metrics),
+                RpcAuthMiddleware::default(),
                 rate_limit_middleware,
             ))
         });
         io_handler.extend_with(apis);
     }
 
+    /// Get the io handler of the apis, the calls are authenticated if `authenticator` is set.
     pub fn get_apis(
         &self,
         api_types: impl IntoIterator<Item = Api>,
+        authenticator: Option<Arc<RpcAuthenticator>>,
     ) -> MetaIoHandler<Metadata, Middlewares> {
         let rate_limit_middleware = JsonApiRateLimitMiddleware::from_config(self.quotas.clone());
         let metrics = self.metrics.clone();
@@ -52,6 +61,7 @@ impl ApiRegistry {
             .fold(
                 MetaIoHandler::<Metadata, Middlewares>::with_middleware((
                     MetricMiddleware::new(metrics),
+                    RpcAuthMiddleware::new(authenticator),
                     rate_limit_middleware,
                 )),
                 |mut init, apis| {
@@ -0,0 +1,375 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Credential authentication of http and websocket json rpc.
+
+use crate::rate_limit_middleware::JsonApiRateLimitMiddleware;
+use anyhow::{bail, ensure, format_err, Result};
+use hmac::{Hmac, Mac};
+use jsonrpc_core::futures::future::Either;
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::middleware::NoopCallFuture;
+use jsonrpc_core::{
+    Call, Error, ErrorCode, Failure, FutureResponse, Id, Middleware, Output, Version,
+};
+use jsonrpc_ws_server::{ws, MiddlewareAction, RequestMiddleware};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use sha2::Sha256;
+use starcoin_config::{RpcCredential, RpcCredentials};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_rpc_api::metadata::{AuthToken, Metadata};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const AUTHORIZATION_HEADER: &str = "Authorization";
+const BEARER_PREFIX: &str = "bearer ";
+/// Websocket clients pass the credential as a `Sec-WebSocket-Protocol` of `bearer.<token or jwt>`,
+/// as the protocols of the handshake are kept in the session, but the headers are not.
+const TOKEN_PROTOCOL_PREFIX: &str = "bearer.";
+const UNAUTHORIZED_ERROR_CODE: i64 = -10001;
+
+/// Get the token of the `Authorization` header value, which should be `Bearer <token or jwt>`.
+pub(crate) fn bearer_token(value: &str) -> Option<AuthToken> {
+    let value = value.trim();
+    match value.get(..BEARER_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(BEARER_PREFIX) => Some(AuthToken::new(
+            value[BEARER_PREFIX.len()..].trim().to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// Get the token of the websocket protocols, see `TOKEN_PROTOCOL_PREFIX`.
+pub(crate) fn protocol_token<'a>(
+    protocols: impl IntoIterator<Item = &'a str>,
+) -> Option<AuthToken> {
+    protocols.into_iter().find_map(|protocol| {
+        protocol
+            .trim()
+            .strip_prefix(TOKEN_PROTOCOL_PREFIX)
+            .map(|token| AuthToken::new(token.to_string()))
+    })
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct JwtClaims {
+    exp: Option<u64>,
+    nbf: Option<u64>,
+}
+
+fn decode_jwt_part(part: &str) -> Result<Vec<u8>> {
+    Ok(base64::decode_config(part, base64::URL_SAFE_NO_PAD)?)
+}
+
+/// Verify the JWT signed with HMAC-SHA256, and the `exp` and `nbf` claims if present.
+fn verify_jwt(token: &str, secret: &[u8], now_secs: u64) -> Result<()> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let (header, claims, signature) = match parts.as_slice() {
+        [header, claims, signature] => (*header, *claims, *signature),
+        _ => bail!("Invalid jwt format"),
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).map_err(|e| format_err!("{}", e))?;
+    mac.update(header.as_bytes());
+    mac.update(b".");
+    mac.update(claims.as_bytes());
+    mac.verify_slice(&decode_jwt_part(signature)?)
+        .map_err(|_| format_err!("Invalid jwt signature"))?;
+
+    let header: JwtHeader = serde_json::from_slice(&decode_jwt_part(header)?)?;
+    ensure!(header.alg == "HS256", "Unsupported jwt alg: {}", header.alg);
+    let claims: JwtClaims = serde_json::from_slice(&decode_jwt_part(claims)?)?;
+    if let Some(exp) = claims.exp {
+        ensure!(now_secs < exp, "Jwt is expired");
+    }
+    if let Some(nbf) = claims.nbf {
+        ensure!(now_secs >= nbf, "Jwt is not valid yet");
+    }
+    Ok(())
+}
+
+struct AuthCredential {
+    credential: RpcCredential,
+    jwt_secret: Option<Vec<u8>>,
+    rate_limit: JsonApiRateLimitMiddleware,
+}
+
+#[derive(Default)]
+struct AuthState {
+    modified: Option<SystemTime>,
+    /// Credentials by the sha3 hash of the token.
+    tokens: HashMap<HashValue, Arc<AuthCredential>>,
+    jwt_credentials: Vec<Arc<AuthCredential>>,
+    credentials: HashMap<String, Arc<AuthCredential>>,
+}
+
+impl AuthState {
+    fn new(credentials: RpcCredentials, modified: SystemTime) -> Result<Self> {
+        let mut state = Self {
+            modified: Some(modified),
+            ..Default::default()
+        };
+        for credential in credentials.credentials {
+            let jwt_secret = credential.jwt_secret()?;
+            let auth_credential = Arc::new(AuthCredential {
+                rate_limit: JsonApiRateLimitMiddleware::from_config(credential.api_quotas.clone()),
+                jwt_secret,
+                credential,
+            });
+            if let Some(token) = auth_credential.credential.token.as_ref() {
+                state.tokens.insert(
+                    HashValue::sha3_256_of(token.as_bytes()),
+                    auth_credential.clone(),
+                );
+            }
+            if auth_credential.jwt_secret.is_some() {
+                state.jwt_credentials.push(auth_credential.clone());
+            }
+            state
+                .credentials
+                .insert(auth_credential.credential.name.clone(), auth_credential);
+        }
+        Ok(state)
+    }
+}
+
+/// Authenticate the bearer token or JWT of the rpc callers with the credentials in the auth file.
+pub struct RpcAuthenticator {
+    auth_file: PathBuf,
+    state: RwLock<AuthState>,
+}
+
+impl RpcAuthenticator {
+    pub fn new(auth_file: PathBuf) -> Result<Self> {
+        let authenticator = Self {
+            auth_file,
+            state: RwLock::new(AuthState::default()),
+        };
+        authenticator.reload()?;
+        Ok(authenticator)
+    }
+
+    /// Reload the credentials if the auth file is changed, return true if reloaded.
+    /// The current credentials are kept if the new file is invalid.
+    pub fn reload(&self) -> Result<bool> {
+        let modified = fs::metadata(&self.auth_file)?.modified()?;
+        if self.state.read().modified == Some(modified) {
+            return Ok(false);
+        }
+        let credentials = RpcCredentials::load(&self.auth_file)?;
+        let count = credentials.credentials.len();
+        *self.state.write() = AuthState::new(credentials, modified)?;
+        info!("Load {} rpc credentials from {:?}", count, self.auth_file);
+        Ok(true)
+    }
+
+    /// Authenticate a bearer token or a JWT, return the credential name.
+    pub fn authenticate(&self, token: &str) -> Option<String> {
+        self.find_credential(token)
+            .map(|credential| credential.credential.name.clone())
+    }
+
+    /// Authenticate the value of `Authorization` header, which should be `Bearer <token or jwt>`.
+    pub fn authenticate_header(&self, value: &str) -> Option<String> {
+        bearer_token(value).and_then(|token| self.authenticate(token.as_str()))
+    }
+
+    /// Find the credential of the token in the current credentials, so a credential removed
+    /// from the auth file or an expired JWT is rejected by the next call.
+    fn find_credential(&self, token: &str) -> Option<Arc<AuthCredential>> {
+        let state = self.state.read();
+        if let Some(credential) = state.tokens.get(&HashValue::sha3_256_of(token.as_bytes())) {
+            return Some(credential.clone());
+        }
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        state
+            .jwt_credentials
+            .iter()
+            .find(|credential| {
+                credential
+                    .jwt_secret
+                    .as_ref()
+                    .map(|secret| verify_jwt(token, secret, now_secs).is_ok())
+                    .unwrap_or(false)
+            })
+            .cloned()
+    }
+}
+
+/// Reject the calls without a valid credential, or not allowed by the apis of the credential,
+/// and limit the calls with the quotas of the credential, in addition to the quotas of the node.
+/// Do nothing if the authenticator is not set, for the transports without authentication.
+#[derive(Clone, Default)]
+pub struct RpcAuthMiddleware {
+    authenticator: Option<Arc<RpcAuthenticator>>,
+}
+
+impl RpcAuthMiddleware {
+    pub fn new(authenticator: Option<Arc<RpcAuthenticator>>) -> Self {
+        Self { authenticator }
+    }
+
+    fn failure(jsonrpc: Option<Version>, id: Id, message: String) -> Output {
+        Output::Failure(Failure {
+            jsonrpc,
+            error: Error {
+                code: ErrorCode::ServerError(UNAUTHORIZED_ERROR_CODE),
+                message,
+                data: None,
+            },
+            id,
+        })
+    }
+}
+
+impl Middleware<Metadata> for RpcAuthMiddleware {
+    type Future = FutureResponse;
+    type CallFuture = NoopCallFuture;
+
+    fn on_call<F, X>(&self, call: Call, meta: Metadata, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, Metadata) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        let authenticator = match self.authenticator.as_ref() {
+            Some(authenticator) => authenticator,
+            None => return Either::Right(next(call, meta)),
+        };
+        let (method, jsonrpc, id) = match &call {
+            Call::MethodCall(m) => (m.method.clone(), m.jsonrpc, m.id.clone()),
+            Call::Notification(n) => (n.method.clone(), n.jsonrpc, Id::Null),
+            Call::Invalid { .. } => return Either::Right(next(call, meta)),
+        };
+        let credential = meta
+            .credential
+            .as_ref()
+            .and_then(|token| authenticator.find_credential(token.as_str()));
+        let output = match credential {
+            None => Some(Self::failure(jsonrpc, id, "Unauthorized".to_string())),
+            Some(credential) if !credential.credential.apis.check_rpc_method(&method) => {
+                Some(Self::failure(
+                    jsonrpc,
+                    id,
+                    format!(
+                        "Method {} is not allowed for credential {}",
+                        method, credential.credential.name
+                    ),
+                ))
+            }
+            // the user quotas of the credential are shared by all the callers of the credential.
+            Some(credential) => credential
+                .rate_limit
+                .check(&call, Some(&credential.credential.name)),
+        };
+        match output {
+            Some(output) => Either::Left(Box::pin(futures::future::ready(Some(output)))),
+            None => Either::Right(next(call, meta)),
+        }
+    }
+}
+
+/// Reject the websocket handshake without a valid credential.
+pub struct WsAuthMiddleware {
+    authenticator: Arc<RpcAuthenticator>,
+}
+
+impl WsAuthMiddleware {
+    pub fn new(authenticator: Arc<RpcAuthenticator>) -> Self {
+        Self { authenticator }
+    }
+}
+
+impl RequestMiddleware for WsAuthMiddleware {
+    fn process(&self, req: &ws::Request) -> MiddlewareAction {
+        let authorized = req
+            .protocols()
+            .ok()
+            .and_then(protocol_token)
+            .and_then(|token| self.authenticator.authenticate(token.as_str()))
+            .is_some();
+        if authorized {
+            MiddlewareAction::Proceed
+        } else {
+            MiddlewareAction::Respond {
+                response: ws::Response::new(401, "Unauthorized", b"Unauthorized".to_vec()),
+                validate_origin: true,
+                validate_hosts: true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_jwt(claims: &str, secret: &[u8]) -> String {
+        let header =
+            base64::encode_config(r#"{"alg":"HS256","typ":"JWT"}"#, base64::URL_SAFE_NO_PAD);
+        let claims = base64::encode_config(claims, base64::URL_SAFE_NO_PAD);
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(format!("{}.{}", header, claims).as_bytes());
+        let signature = base64::encode_config(mac.finalize().into_bytes(), base64::URL_SAFE_NO_PAD);
+        format!("{}.{}.{}", header, claims, signature)
+    }
+
+    #[test]
+    fn test_verify_jwt() {
+        let secret = b"secret";
+        let jwt = sign_jwt(r#"{"sub":"test","exp":200}"#, secret);
+        assert!(verify_jwt(jwt.as_str(), secret, 100).is_ok());
+        // expired
+        assert!(verify_jwt(jwt.as_str(), secret, 200).is_err());
+        // wrong secret
+        assert!(verify_jwt(jwt.as_str(), b"other", 100).is_err());
+
+        let jwt = sign_jwt(r#"{"nbf":100}"#, secret);
+        assert!(verify_jwt(jwt.as_str(), secret, 99).is_err());
+        assert!(verify_jwt(jwt.as_str(), secret, 100).is_ok());
+        assert!(verify_jwt("a.b", secret, 100).is_err());
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(
+            bearer_token("Bearer abc").as_ref().map(AuthToken::as_str),
+            Some("abc")
+        );
+        assert_eq!(
+            bearer_token(" bearer  abc ")
+                .as_ref()
+                .map(AuthToken::as_str),
+            Some("abc")
+        );
+        assert_eq!(bearer_token("Basic abc"), None);
+    }
+
+    #[test]
+    fn test_protocol_token() {
+        assert_eq!(
+            protocol_token(vec!["bearer.abc"])
+                .as_ref()
+                .map(AuthToken::as_str),
+            Some("abc")
+        );
+        assert_eq!(
+            protocol_token(vec!["jsonrpc", "bearer.a.b.c"])
+                .as_ref()
+                .map(AuthToken::as_str),
+            Some("a.b.c")
+        );
+        assert_eq!(protocol_token(vec!["jsonrpc"]), None);
+    }
+}
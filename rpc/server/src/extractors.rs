@@ -1,6 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::auth::{bearer_token, protocol_token, AUTHORIZATION_HEADER};
 use jsonrpc_http_server::hyper;
 use jsonrpc_pubsub::Session;
 use starcoin_rpc_api::metadata::Metadata;
@@ -27,9 +28,16 @@ impl jsonrpc_http_server::MetaExtractor<Metadata> for RpcExtractor {
             }
         }
 
+        let credential = _req
+            .headers()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(bearer_token);
+
         Metadata {
             session: None,
             user: client_ip.map(|ip| ip.to_string()),
+            credential,
         }
     }
 }
@@ -38,6 +46,7 @@ impl jsonrpc_ipc_server::MetaExtractor<Metadata> for RpcExtractor {
         Metadata {
             session: Some(Arc::new(Session::new(req.sender.clone()))),
             user: None,
+            credential: None,
         }
     }
 }
@@ -46,6 +55,7 @@ impl jsonrpc_tcp_server::MetaExtractor<Metadata> for RpcExtractor {
         Metadata {
             session: Some(Arc::new(Session::new(context.sender.clone()))),
             user: Some(context.peer_addr.ip().to_string()),
+            credential: None,
         }
     }
 }
@@ -57,6 +67,7 @@ impl jsonrpc_ws_server::MetaExtractor<Metadata> for WsExtractor {
         Metadata {
             session,
             user: None,
+            credential: protocol_token(req.protocols.iter().map(String::as_str)),
         }
     }
 }
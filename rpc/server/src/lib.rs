@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2
 
 mod api_registry;
+pub mod auth;
 mod extractors;
 pub mod module;
 mod rate_limit_middleware;
@@ -297,6 +297,7 @@ where
                 .map(|e| TransactionEventResponse {
                     event: e.into(),
                     decode_event_data: None,
+                    removed: false,
                 })
                 .collect();
 
@@ -363,6 +364,7 @@ where
                 .map(|e| TransactionEventResponse {
                     event: e.into(),
                     decode_event_data: None,
+                    removed: false,
                 })
                 .collect();
             if let Some(state_root) = state_root {
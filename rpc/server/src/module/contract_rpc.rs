@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::module::helpers::TransactionRequestFiller;
+use crate::module::helpers::{StateRootResolver, TransactionRequestFiller};
 use crate::module::map_err;
 use anyhow::format_err;
 use futures::future::TryFutureExt;
@@ -76,6 +76,14 @@ where
             node_config: self.node_config.clone(),
         }
     }
+
+    fn state_root_resolver(&self) -> StateRootResolver<State> {
+        StateRootResolver {
+            chain_state: self.chain_state.clone(),
+            state_store: self.storage.clone(),
+            storage: Some(self.storage.clone()),
+        }
+    }
 }
 
 impl<Account, Pool, State> ContractApi for ContractRpcImpl<Account, Pool, State>
@@ -120,15 +128,20 @@ where
         Box::pin(f.map_err(map_err).boxed())
     }
     fn call(&self, call: ContractCall) -> FutureResult<Vec<AnnotatedMoveValueView>> {
-        let service = self.chain_state.clone();
+        let resolver = self.state_root_resolver();
         let playground = self.playground.clone();
         let ContractCall {
             function_id,
             type_args,
             args,
+            block_number,
+            block_hash,
+            state_root,
         } = call;
         let f = async move {
-            let state_root = service.state_root().await?;
+            let state_root = resolver
+                .resolve(block_number, block_hash, state_root)
+                .await?;
             let output = playground.call_contract(
                 state_root,
                 function_id.0.module,
@@ -143,16 +156,21 @@ where
     }
 
     fn call_v2(&self, call: ContractCall) -> FutureResult<Vec<DecodedMoveValue>> {
-        let service = self.chain_state.clone();
+        let resolver = self.state_root_resolver();
         let storage = self.storage.clone();
         let ContractCall {
             function_id,
             type_args,
             args,
+            block_number,
+            block_hash,
+            state_root,
         } = call;
         let metrics = self.playground.metrics.clone();
         let f = async move {
-            let state_root = service.state_root().await?;
+            let state_root = resolver
+                .resolve(block_number, block_hash, state_root)
+                .await?;
             let state = ChainStateDB::new(storage, Some(state_root));
             let output = call_contract(
                 &state,
@@ -173,16 +191,22 @@ where
     }
 
     fn dry_run(&self, txn: DryRunTransactionRequest) -> FutureResult<DryRunOutputView> {
-        let service = self.chain_state.clone();
+        let resolver = self.state_root_resolver();
         let storage = self.storage.clone();
         let txn_builder = self.txn_request_filler();
         let metrics = self.playground.metrics.clone();
         let f = async move {
-            let state_root = service.state_root().await?;
             let DryRunTransactionRequest {
                 transaction,
                 sender_public_key,
+                profile,
+                block_number,
+                block_hash,
+                state_root,
             } = txn;
+            let state_root = resolver
+                .resolve(block_number, block_hash, state_root)
+                .await?;
 
             let txn = txn_builder.fill_transaction(transaction).await?;
             let state_view = ChainStateDB::new(storage, Some(state_root));
@@ -192,6 +216,7 @@ where
                     raw_txn: txn,
                     public_key: sender_public_key.0,
                 },
+                profile,
                 metrics,
             )
         }
@@ -217,6 +242,7 @@ where
                     raw_txn,
                     public_key: sender_public_key.0,
                 },
+                false,
                 metrics,
             )
         }
@@ -277,9 +303,16 @@ where
 pub fn dry_run<S: StateView>(
     state_view: &S,
     txn: DryRunTransaction,
+    profile: bool,
     metrics: Option<VMMetrics>,
 ) -> anyhow::Result<DryRunOutputView> {
-    let (vm_status, output) = starcoin_dev::playground::dry_run(state_view, txn.clone(), metrics)?;
+    let (vm_status, output, gas_profile) = if profile {
+        starcoin_dev::playground::dry_run_profile(state_view, txn.clone(), metrics)?
+    } else {
+        let (vm_status, output) =
+            starcoin_dev::playground::dry_run(state_view, txn.clone(), metrics)?;
+        (vm_status, output, None)
+    };
     let vm_status_explain = vm_status_translator::explain_vm_status(state_view, vm_status)?;
     let mut txn_output: TransactionOutputView = output.into();
 
@@ -321,5 +354,6 @@ pub fn dry_run<S: StateView>(
     Ok(DryRunOutputView {
         explained_status: vm_status_explain,
         txn_output,
+        gas_profile: gas_profile.map(Into::into),
     })
 }
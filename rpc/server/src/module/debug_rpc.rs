@@ -1,43 +1,100 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::module::to_invalid_param_err;
+use crate::module::helpers::{StateRootResolver, TransactionRequestFiller};
 use crate::module::txfactory_rpc::TxFactoryStatusHandle;
+use crate::module::{map_err, to_invalid_param_err};
+use anyhow::format_err;
+use futures::future::TryFutureExt;
+use futures::FutureExt;
 use jsonrpc_core::Result;
+use starcoin_account_api::AccountAsyncService;
 use starcoin_config::NodeConfig;
+use starcoin_crypto::HashValue;
+use starcoin_dev::trace::{trace_dry_run, trace_transaction};
+use starcoin_executor::VMMetrics;
 use starcoin_logger::prelude::LevelFilter;
 use starcoin_logger::structured_log::set_slog_level;
 use starcoin_logger::{LogPattern, LoggerHandle};
 use starcoin_rpc_api::debug::DebugApi;
-use starcoin_rpc_api::types::FactoryAction;
+use starcoin_rpc_api::types::{DryRunTransactionRequest, FactoryAction, TransactionTraceView};
+use starcoin_rpc_api::FutureResult;
 use starcoin_service_registry::bus::{Bus, BusService};
 use starcoin_service_registry::ServiceRef;
+use starcoin_state_api::ChainStateAsyncService;
+use starcoin_statedb::ChainStateDB;
+use starcoin_storage::{BlockTransactionInfoStore, Storage, Store, TransactionStore};
+use starcoin_txpool_api::TxPoolSyncService;
 use starcoin_types::system_events::GenerateBlockEvent;
+use starcoin_types::transaction::{DryRunTransaction, Transaction};
 use starcoin_vm_runtime::starcoin_vm::StarcoinVM;
 use std::str::FromStr;
 use std::sync::Arc;
 
-pub struct DebugRpcImpl {
+pub struct DebugRpcImpl<Account, Pool, State> {
     config: Arc<NodeConfig>,
     log_handle: Arc<LoggerHandle>,
     bus: ServiceRef<BusService>,
+    account: Option<Account>,
+    pool: Pool,
+    chain_state: State,
+    storage: Arc<Storage>,
+    metrics: Option<VMMetrics>,
 }
 
-impl DebugRpcImpl {
+impl<Account, Pool, State> DebugRpcImpl<Account, Pool, State>
+where
+    Account: AccountAsyncService + 'static,
+    Pool: TxPoolSyncService + 'static,
+    State: ChainStateAsyncService + 'static,
+{
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Arc<NodeConfig>,
         log_handle: Arc<LoggerHandle>,
         bus: ServiceRef<BusService>,
+        account: Option<Account>,
+        pool: Pool,
+        chain_state: State,
+        storage: Arc<Storage>,
+        metrics: Option<VMMetrics>,
     ) -> Self {
         Self {
             config,
             log_handle,
             bus,
+            account,
+            pool,
+            chain_state,
+            storage,
+            metrics,
+        }
+    }
+
+    fn txn_request_filler(&self) -> TransactionRequestFiller<Account, Pool, State> {
+        TransactionRequestFiller {
+            account: self.account.clone(),
+            pool: self.pool.clone(),
+            chain_state: self.chain_state.clone(),
+            node_config: self.config.clone(),
+        }
+    }
+
+    fn state_root_resolver(&self) -> StateRootResolver<State> {
+        StateRootResolver {
+            chain_state: self.chain_state.clone(),
+            state_store: self.storage.clone(),
+            storage: Some(self.storage.clone()),
         }
     }
 }
 
-impl DebugApi for DebugRpcImpl {
+impl<Account, Pool, State> DebugApi for DebugRpcImpl<Account, Pool, State>
+where
+    Account: AccountAsyncService + 'static,
+    Pool: TxPoolSyncService + 'static,
+    State: ChainStateAsyncService + 'static,
+{
     fn set_log_level(&self, logger_name: Option<String>, level: String) -> Result<()> {
         let logger_name = logger_name.and_then(|s| {
             let s = s.trim();
@@ -92,4 +149,75 @@ impl DebugApi for DebugRpcImpl {
     fn get_concurrency_level(&self) -> Result<usize> {
         Ok(StarcoinVM::get_concurrency_level())
     }
+
+    fn trace_transaction(&self, txn_hash: HashValue) -> FutureResult<TransactionTraceView> {
+        let storage = self.storage.clone();
+        let metrics = self.metrics.clone();
+        let f = async move {
+            let txn = match storage.get_transaction(txn_hash)? {
+                Some(Transaction::UserTransaction(txn)) => txn,
+                Some(Transaction::BlockMetadata(_)) => {
+                    anyhow::bail!("Block metadata transaction {} can not be traced", txn_hash)
+                }
+                None => anyhow::bail!("Can not find transaction {}", txn_hash),
+            };
+            // a transaction may be included by blocks of different branches, trace the first one.
+            let txn_info = storage
+                .get_transaction_info_by_txn_hash(txn_hash)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format_err!("Can not find transaction info of {}", txn_hash))?;
+            // the first transaction of a block is the block metadata transaction,
+            // so the parent state of a user transaction is the state after the previous transaction.
+            let parent_index = txn_info
+                .transaction_index
+                .checked_sub(1)
+                .ok_or_else(|| format_err!("Invalid transaction index of {}", txn_hash))?;
+            let parent_txn_info = storage
+                .get_transaction_info_by_block_and_index(txn_info.block_id, parent_index as u64)?
+                .ok_or_else(|| {
+                    format_err!(
+                        "Can not find transaction info at index {} of block {}",
+                        parent_index,
+                        txn_info.block_id
+                    )
+                })?;
+            let state_view = ChainStateDB::new(storage, Some(parent_txn_info.state_root_hash()));
+            trace_transaction(&state_view, txn, metrics)
+        }
+        .map_err(map_err);
+        Box::pin(f.boxed())
+    }
+
+    fn trace_call(&self, txn: DryRunTransactionRequest) -> FutureResult<TransactionTraceView> {
+        let resolver = self.state_root_resolver();
+        let storage = self.storage.clone();
+        let txn_builder = self.txn_request_filler();
+        let metrics = self.metrics.clone();
+        let f = async move {
+            let DryRunTransactionRequest {
+                transaction,
+                sender_public_key,
+                block_number,
+                block_hash,
+                state_root,
+                ..
+            } = txn;
+            let state_root = resolver
+                .resolve(block_number, block_hash, state_root)
+                .await?;
+            let txn = txn_builder.fill_transaction(transaction).await?;
+            let state_view = ChainStateDB::new(storage, Some(state_root));
+            trace_dry_run(
+                &state_view,
+                DryRunTransaction {
+                    raw_txn: txn,
+                    public_key: sender_public_key.0,
+                },
+                metrics,
+            )
+        }
+        .map_err(map_err);
+        Box::pin(f.boxed())
+    }
 }
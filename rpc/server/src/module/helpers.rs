@@ -1,9 +1,15 @@
+use anyhow::{bail, ensure, format_err};
 use starcoin_account_api::AccountAsyncService;
 use starcoin_config::NodeConfig;
+use starcoin_crypto::hash::SPARSE_MERKLE_PLACEHOLDER_HASH;
+use starcoin_crypto::HashValue;
 use starcoin_rpc_api::types::TransactionRequest;
 use starcoin_state_api::ChainStateAsyncService;
+use starcoin_state_tree::StateNodeStore;
+use starcoin_storage::{BlockStore, Store};
 use starcoin_txpool_api::TxPoolSyncService;
 use starcoin_types::account_config::AccountResource;
+use starcoin_types::block::BlockNumber;
 use starcoin_types::transaction::{Module, Package, RawUserTransaction, TransactionPayload};
 use std::sync::Arc;
 
@@ -103,3 +109,193 @@ where
         Ok(raw_txn)
     }
 }
+
+/// Resolve the state root of the state to read or execute on, the state is selected by
+/// the block number of the main chain, the block hash or the state root,
+/// the latest block state is used if none of them is set.
+#[derive(Clone)]
+pub(crate) struct StateRootResolver<State> {
+    pub(crate) chain_state: State,
+    pub(crate) state_store: Arc<dyn StateNodeStore>,
+    /// Used to find the block by number or hash, None if the node does not provide it.
+    pub(crate) storage: Option<Arc<dyn Store>>,
+}
+
+impl<State> StateRootResolver<State>
+where
+    State: ChainStateAsyncService + 'static,
+{
+    pub(crate) async fn resolve(
+        &self,
+        block_number: Option<BlockNumber>,
+        block_hash: Option<HashValue>,
+        state_root: Option<HashValue>,
+    ) -> anyhow::Result<HashValue> {
+        let state_root = match (block_number, block_hash, state_root) {
+            (None, None, None) => return self.chain_state.clone().state_root().await,
+            (Some(block_number), None, None) => {
+                let block_hash = self
+                    .storage()?
+                    .get_main_block_id_by_number(block_number)?
+                    .ok_or_else(|| format_err!("Can not find block by number: {}", block_number))?;
+                self.block_state_root(block_hash)?
+            }
+            (None, Some(block_hash), None) => self.block_state_root(block_hash)?,
+            (None, None, Some(state_root)) => state_root,
+            _ => bail!("Only one of block_number, block_hash and state_root can be set"),
+        };
+        ensure!(
+            state_root == *SPARSE_MERKLE_PLACEHOLDER_HASH
+                || self.state_store.get(&state_root)?.is_some(),
+            "The state of root {} has been pruned or is not present on this node",
+            state_root
+        );
+        Ok(state_root)
+    }
+
+    fn block_state_root(&self, block_hash: HashValue) -> anyhow::Result<HashValue> {
+        let header = self
+            .storage()?
+            .get_block_header_by_hash(block_hash)?
+            .ok_or_else(|| format_err!("Can not find block by hash: {}", block_hash))?;
+        Ok(header.state_root())
+    }
+
+    fn storage(&self) -> anyhow::Result<&dyn Store> {
+        self.storage
+            .as_deref()
+            .ok_or_else(|| format_err!("Query state by block is not supported by this node"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starcoin_account_api::AccountInfo;
+    use starcoin_chain::ChainReader;
+    use starcoin_chain_mock::MockChain;
+    use starcoin_config::ChainNetwork;
+    use starcoin_genesis::Genesis;
+    use starcoin_state_api::mock::MockChainStateService;
+    use starcoin_state_tree::mock::MockStateNodeStore;
+    use starcoin_types::startup_info::StartupInfo;
+
+    /// A resolver on a chain with 2 blocks after the genesis.
+    fn resolver() -> (StateRootResolver<MockChainStateService>, MockChain) {
+        let net = ChainNetwork::new_test();
+        let (storage, chain_info, _) =
+            Genesis::init_storage_for_test(&net).expect("init storage by genesis fail.");
+        let mut chain = MockChain::new_with_storage(
+            net,
+            storage.clone(),
+            chain_info.head().id(),
+            AccountInfo::random(),
+        )
+        .unwrap();
+        chain.produce_and_apply_times(2).unwrap();
+        storage
+            .save_startup_info(StartupInfo::new(chain.head().current_header().id()))
+            .unwrap();
+        let resolver = StateRootResolver {
+            chain_state: MockChainStateService::new(),
+            state_store: storage.clone(),
+            storage: Some(storage),
+        };
+        (resolver, chain)
+    }
+
+    #[stest::test]
+    async fn test_resolve_state_root() {
+        let (resolver, chain) = resolver();
+        let head = chain.head().current_header();
+        let parent = chain
+            .head()
+            .get_header(head.parent_hash())
+            .unwrap()
+            .unwrap();
+        assert_ne!(head.state_root(), parent.state_root());
+
+        for header in [&head, &parent] {
+            let state_root = resolver
+                .resolve(Some(header.number()), None, None)
+                .await
+                .unwrap();
+            assert_eq!(state_root, header.state_root());
+            let state_root = resolver
+                .resolve(None, Some(header.id()), None)
+                .await
+                .unwrap();
+            assert_eq!(state_root, header.state_root());
+            let state_root = resolver
+                .resolve(None, None, Some(header.state_root()))
+                .await
+                .unwrap();
+            assert_eq!(state_root, header.state_root());
+        }
+    }
+
+    #[stest::test]
+    async fn test_resolve_state_root_conflicting_params() {
+        let (resolver, chain) = resolver();
+        let head = chain.head().current_header();
+        let conflicts = [
+            (Some(head.number()), Some(head.id()), None),
+            (Some(head.number()), None, Some(head.state_root())),
+            (None, Some(head.id()), Some(head.state_root())),
+            (
+                Some(head.number()),
+                Some(head.id()),
+                Some(head.state_root()),
+            ),
+        ];
+        for (block_number, block_hash, state_root) in conflicts {
+            assert!(resolver
+                .resolve(block_number, block_hash, state_root)
+                .await
+                .is_err());
+        }
+    }
+
+    #[stest::test]
+    async fn test_resolve_unknown_state_root() {
+        let (resolver, chain) = resolver();
+        let head = chain.head().current_header();
+
+        assert!(resolver
+            .resolve(Some(head.number() + 1), None, None)
+            .await
+            .is_err());
+        assert!(resolver
+            .resolve(None, Some(HashValue::random()), None)
+            .await
+            .is_err());
+        assert!(resolver
+            .resolve(None, None, Some(HashValue::random()))
+            .await
+            .is_err());
+
+        // the state of the block is pruned from the state store.
+        let pruned = StateRootResolver {
+            state_store: Arc::new(MockStateNodeStore::new()),
+            ..resolver.clone()
+        };
+        assert!(pruned
+            .resolve(Some(head.number()), None, None)
+            .await
+            .is_err());
+        assert!(pruned.resolve(None, Some(head.id()), None).await.is_err());
+
+        let no_storage = StateRootResolver {
+            storage: None,
+            ..resolver
+        };
+        assert!(no_storage
+            .resolve(Some(head.number()), None, None)
+            .await
+            .is_err());
+        assert!(no_storage
+            .resolve(None, None, Some(head.state_root()))
+            .await
+            .is_ok());
+    }
+}
@@ -5,7 +5,7 @@ use crate::module::map_err;
 use futures::future::TryFutureExt;
 use futures::FutureExt;
 use jsonrpc_core::Result;
-use network_api::{PeerProvider, ReputationChange, BANNED_THRESHOLD};
+use network_api::{PeerProvider, PeerRecord, ReputationChange, BANNED_THRESHOLD};
 use network_p2p_core::RawRpcClient;
 use network_p2p_types::network_state::NetworkState;
 use network_p2p_types::peer_id::PeerId;
@@ -61,6 +61,40 @@ impl NetworkManagerApi for NetworkManagerRpcImpl {
         Box::pin(fut.boxed())
     }
 
+    fn add_reserved_peer(&self, peer: String) -> FutureResult<()> {
+        let service = self.service.clone();
+        let fut = async move { service.add_reserved_peer(peer) }.map_err(map_err);
+        Box::pin(fut.boxed())
+    }
+
+    fn remove_reserved_peer(&self, peer_id: String) -> Result<()> {
+        let peer_id = PeerId::from_str(peer_id.as_str()).map_err(map_err)?;
+        self.service.remove_reserved_peer(peer_id);
+        Ok(())
+    }
+
+    fn peer_records(&self) -> FutureResult<Vec<PeerRecord>> {
+        let service = self.service.clone();
+        let fut = async move { service.peer_records().await }.map_err(map_err);
+        Box::pin(fut.boxed())
+    }
+
+    fn put_peer_record(&self, record: PeerRecord) -> FutureResult<()> {
+        let service = self.service.clone();
+        let fut = async move { service.put_peer_record(record).await }.map_err(map_err);
+        Box::pin(fut.boxed())
+    }
+
+    fn remove_peer_record(&self, peer_id: String) -> FutureResult<Option<PeerRecord>> {
+        let service = self.service.clone();
+        let fut = async move {
+            let peer_id = PeerId::from_str(peer_id.as_str())?;
+            service.remove_peer_record(peer_id).await
+        }
+        .map_err(map_err);
+        Box::pin(fut.boxed())
+    }
+
     fn call_peer(
         &self,
         peer_id: String,
@@ -7,8 +7,11 @@ use futures::FutureExt;
 use starcoin_crypto::HashValue;
 use starcoin_node_api::node_service::NodeAsyncService;
 use starcoin_rpc_api::node_manager::NodeManagerApi;
+use starcoin_rpc_api::types::{BackupManifestView, StrView};
 use starcoin_rpc_api::FutureResult;
 use starcoin_service_registry::{ServiceInfo, ServiceStatus};
+use starcoin_storage::backup::BackupManifest;
+use std::path::PathBuf;
 
 pub struct NodeManagerRpcImpl<S>
 where
@@ -115,4 +118,27 @@ where
         .map_err(map_err);
         Box::pin(fut.boxed())
     }
+
+    fn backup(&self, backup_dir: String) -> FutureResult<BackupManifestView> {
+        let service = self.service.clone();
+        let fut = async move {
+            let manifest = service.backup(PathBuf::from(backup_dir)).await?;
+            Ok(backup_manifest_view(manifest))
+        }
+        .map_err(map_err);
+        Box::pin(fut.boxed())
+    }
+}
+
+fn backup_manifest_view(manifest: BackupManifest) -> BackupManifestView {
+    BackupManifestView {
+        backup_id: manifest.backup_id,
+        timestamp: manifest.timestamp,
+        storage_version: manifest.storage_version,
+        files: manifest.files.len() as u64,
+        total_size: StrView(manifest.total_size()),
+        copied_files: manifest.copied_files,
+        copied_size: StrView(manifest.copied_size),
+        chain_info: manifest.chain_info.into(),
+    }
 }
@@ -55,9 +55,15 @@ impl NodeApi for NodeRpcImpl {
         let service = self.service.clone().unwrap();
         let fut = async move {
             let peers = service.peer_set().await?;
+            let mut bandwidth = service.peer_bandwidth();
             Ok(peers
                 .into_iter()
-                .map(PeerInfoView::from)
+                .map(|peer| {
+                    let peer_bandwidth = bandwidth.remove(&peer.peer_id);
+                    let mut view = PeerInfoView::from(peer);
+                    view.bandwidth = peer_bandwidth;
+                    view
+                })
                 .collect::<Vec<_>>())
         };
         Box::pin(fut.map_err(map_err).boxed())
@@ -11,11 +11,14 @@ use jsonrpc_pubsub::SubscriptionId;
 use parking_lot::RwLock;
 use starcoin_abi_decoder::decode_move_value;
 use starcoin_abi_resolver::ABIResolver;
-use starcoin_chain_notify::message::{ContractEventNotification, Notification, ThinBlock};
+use starcoin_chain_notify::message::{
+    ChainReorgNotification, ContractEventNotification, Notification, ThinBlock,
+};
 use starcoin_crypto::HashValue;
 use starcoin_logger::prelude::*;
 use starcoin_miner::{MinerService, UpdateSubscriberNumRequest};
 use starcoin_rpc_api::metadata::Metadata;
+use starcoin_rpc_api::types::pubsub::ChainReorgView;
 use starcoin_rpc_api::types::{BlockView, TransactionEventResponse, TransactionEventView};
 use starcoin_rpc_api::{errors, pubsub::StarcoinPubSub, types::pubsub};
 use starcoin_service_registry::{
@@ -25,7 +28,7 @@ use starcoin_service_registry::{
 use starcoin_statedb::ChainStateDB;
 use starcoin_storage::Storage;
 use starcoin_txpool::TxPoolService;
-use starcoin_txpool_api::TxPoolSyncService;
+use starcoin_txpool_api::{TxPoolSyncService, TxnStatusInfo};
 use starcoin_types::filter::Filter;
 use starcoin_types::system_events::MintBlockEvent;
 use std::collections::HashMap;
@@ -131,6 +134,40 @@ impl PubSubImpl {
                 subscriber,
                 errors::invalid_params("events", "Expected a filter object."),
             )),
+            (pubsub::Kind::ChainReorg, None) => self
+                .service
+                .try_send(SubscribeChainReorg(subscriber))
+                .map_err(|e| {
+                    let msg = map_send_err(&e);
+                    (
+                        match e {
+                            TrySendError::Disconnected(t) => t.0,
+                            TrySendError::Full(t) => t.0,
+                        },
+                        msg,
+                    )
+                }),
+            (pubsub::Kind::ChainReorg, _) => Err((
+                subscriber,
+                errors::invalid_params("chainReorg", "Expected no parameters."),
+            )),
+            (pubsub::Kind::TxnStatus, Some(pubsub::Params::TxnStatus(params))) => self
+                .service
+                .try_send(SubscribeTxnStatus { subscriber, params })
+                .map_err(|e| {
+                    let msg = map_send_err(&e);
+                    (
+                        match e {
+                            TrySendError::Disconnected(t) => t.subscriber,
+                            TrySendError::Full(t) => t.subscriber,
+                        },
+                        msg,
+                    )
+                }),
+            (pubsub::Kind::TxnStatus, _) => Err((
+                subscriber,
+                errors::invalid_params("txnStatus", "Expected a txn_hash or sender filter."),
+            )),
             (pubsub::Kind::NewMintBlock, _) => self
                 .service
                 .try_send(SubscribeMintBlock(subscriber))
@@ -206,7 +243,9 @@ pub struct PubSubService {
     new_event_subscribers:
         HashMap<SubscriptionId, mpsc::UnboundedSender<ContractEventNotification>>,
     mint_block_subscribers: HashMap<SubscriptionId, mpsc::UnboundedSender<MintBlockEvent>>,
+    chain_reorg_subscribers: HashMap<SubscriptionId, mpsc::UnboundedSender<ChainReorgNotification>>,
     new_pending_txn_tasks: Arc<RwLock<HashMap<SubscriptionId, AbortHandle>>>,
+    txn_status_tasks: Arc<RwLock<HashMap<SubscriptionId, AbortHandle>>>,
 }
 
 impl PubSubService {
@@ -224,7 +263,9 @@ impl PubSubService {
             new_event_subscribers: Default::default(),
             new_header_subscribers: Default::default(),
             mint_block_subscribers: Default::default(),
+            chain_reorg_subscribers: Default::default(),
             new_pending_txn_tasks: Arc::new(RwLock::new(HashMap::default())),
+            txn_status_tasks: Arc::new(RwLock::new(HashMap::default())),
         }
     }
     fn next_id(&self) -> SubscriptionId {
@@ -242,6 +283,7 @@ impl ActorService for PubSubService {
         ctx.subscribe::<NewHeadNotification>();
         ctx.subscribe::<ContractEventNotification>();
         ctx.subscribe::<MintBlockEvent>();
+        ctx.subscribe::<ChainReorgNotification>();
 
         Ok(())
     }
@@ -269,6 +311,16 @@ impl ActorEventHandler<Self, MintBlockEvent> for PubSubService {
     }
 }
 
+impl ActorEventHandler<Self, ChainReorgNotification> for PubSubService {
+    fn handle_event(
+        &mut self,
+        msg: ChainReorgNotification,
+        _ctx: &mut ServiceContext<PubSubService>,
+    ) {
+        send_to_all(&mut self.chain_reorg_subscribers, msg);
+    }
+}
+
 #[derive(Debug)]
 struct SubscribeNewHeads(Subscriber<pubsub::Result>);
 
@@ -292,6 +344,29 @@ impl ServiceHandler<Self, SubscribeNewHeads> for PubSubService {
     }
 }
 
+#[derive(Debug)]
+struct SubscribeChainReorg(Subscriber<pubsub::Result>);
+
+impl ServiceRequest for SubscribeChainReorg {
+    type Response = ();
+}
+
+impl ServiceHandler<Self, SubscribeChainReorg> for PubSubService {
+    fn handle(&mut self, msg: SubscribeChainReorg, ctx: &mut ServiceContext<Self>) {
+        let SubscribeChainReorg(sink) = msg;
+        let (sender, receiver) = mpsc::unbounded();
+        let subscriber_id = self.next_id();
+        self.chain_reorg_subscribers
+            .insert(subscriber_id.clone(), sender);
+        ctx.spawn(run_subscription(
+            receiver,
+            subscriber_id,
+            sink,
+            ChainReorgHandler,
+        ));
+    }
+}
+
 #[derive(Debug)]
 struct SubscribeMintBlock(Subscriber<pubsub::Result>);
 
@@ -406,6 +481,45 @@ impl ServiceHandler<Self, SubscribeNewPendingTxns> for PubSubService {
     }
 }
 
+#[derive(Debug)]
+struct SubscribeTxnStatus {
+    subscriber: Subscriber<pubsub::Result>,
+    params: pubsub::TxnStatusParams,
+}
+
+impl ServiceRequest for SubscribeTxnStatus {
+    type Response = ();
+}
+
+impl ServiceHandler<Self, SubscribeTxnStatus> for PubSubService {
+    fn handle(&mut self, msg: SubscribeTxnStatus, ctx: &mut ServiceContext<Self>) {
+        let SubscribeTxnStatus { subscriber, params } = msg;
+        let subscriber_id = self.next_id();
+        let tasks = self.txn_status_tasks.clone();
+        let subscriber_id_clone = subscriber_id.clone();
+        let receiver = self.txpool.subscribe_txn_status();
+        let (f, abort_handle) = futures::future::abortable(async move {
+            run_subscription(
+                receiver,
+                subscriber_id_clone.clone(),
+                subscriber,
+                TxnStatusHandler { params },
+            )
+            .await;
+            // remove self from task list.
+            tasks.write().remove(&subscriber_id_clone);
+        });
+
+        ctx.spawn(async move {
+            let _ = f.await;
+        });
+
+        self.txn_status_tasks
+            .write()
+            .insert(subscriber_id, abort_handle);
+    }
+}
+
 #[derive(Debug)]
 struct Unsubscribe(SubscriptionId);
 
@@ -418,12 +532,16 @@ impl ServiceHandler<Self, Unsubscribe> for PubSubService {
         self.new_header_subscribers.remove(&msg.0);
         self.new_event_subscribers.remove(&msg.0);
         self.mint_block_subscribers.remove(&msg.0);
+        self.chain_reorg_subscribers.remove(&msg.0);
         self.miner_service.do_send(UpdateSubscriberNumRequest {
             number: Some(self.mint_block_subscribers.len() as u32),
         });
         if let Some(h) = self.new_pending_txn_tasks.write().remove(&msg.0) {
             h.abort();
         }
+        if let Some(h) = self.txn_status_tasks.write().remove(&msg.0) {
+            h.abort();
+        }
     }
 }
 
@@ -489,6 +607,20 @@ impl EventHandler<Arc<[HashValue]>> for TxnEventHandler {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct TxnStatusHandler {
+    params: pubsub::TxnStatusParams,
+}
+
+impl EventHandler<Arc<[TxnStatusInfo]>> for TxnStatusHandler {
+    fn handle(&self, msg: Arc<[TxnStatusInfo]>) -> Vec<jsonrpc_core::Result<pubsub::Result>> {
+        msg.iter()
+            .filter(|status| self.params.matching(status))
+            .map(|status| Ok(pubsub::Result::TxnStatus(Box::new(status.clone()))))
+            .collect()
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct NewHeadHandler;
 
@@ -504,6 +636,20 @@ impl EventHandler<Notification<ThinBlock>> for NewHeadHandler {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct ChainReorgHandler;
+
+impl EventHandler<ChainReorgNotification> for ChainReorgHandler {
+    fn handle(&self, msg: ChainReorgNotification) -> Vec<jsonrpc_core::Result<pubsub::Result>> {
+        let Notification(reorg) = msg;
+        vec![Ok(pubsub::Result::ChainReorg(Box::new(ChainReorgView {
+            ancestor: reorg.ancestor.clone().into(),
+            retracted: reorg.retracted.clone(),
+            enacted: reorg.enacted.clone(),
+        })))]
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct NewMintBlockHandler;
 
@@ -563,6 +709,7 @@ impl EventHandler<ContractEventNotification> for ContractEventHandler {
                         &e.contract_event,
                     ),
                     decode_event_data: decoded_data,
+                    removed: e.removed,
                 })
             })
             .map(|e| {
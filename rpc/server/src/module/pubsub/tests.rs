@@ -22,7 +22,7 @@ use starcoin_state_api::StateReaderExt;
 use starcoin_storage::BlockStore;
 use starcoin_txpool_api::TxPoolSyncService;
 use starcoin_types::system_events::MintBlockEvent;
-use starcoin_types::system_events::NewHeadBlock;
+use starcoin_types::system_events::{ChainReorg, NewHeadBlock};
 use starcoin_types::{account_address, U256};
 use starcoin_vm_types::genesis_config::ConsensusStrategy;
 use std::sync::Arc;
@@ -191,6 +191,78 @@ pub async fn test_subscribe_to_pending_transactions() -> Result<()> {
     Ok(())
 }
 
+#[stest::test]
+pub async fn test_subscribe_to_txn_status() -> Result<()> {
+    let (txpool_service, _, config, _, registry) =
+        test_helper::start_txpool_with_miner(1000, true).await;
+    let service = registry
+        .register_by_factory::<PubSubService, PubSubServiceFactory>()
+        .await?;
+    let pubsub = PubSubImpl::new(service);
+    let pubsub = pubsub.to_delegate();
+
+    let mut io = MetaIoHandler::default();
+    io.extend_with(pubsub);
+
+    let mut metadata = Metadata::default();
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    metadata.session = Some(Arc::new(Session::new(sender)));
+
+    // Fail if no filter is provided
+    let request = r#"{"jsonrpc": "2.0", "method": "starcoin_subscribe", "params": [{"type_name":"txnStatus"}, {}], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: txnStatus","data":"\"Expected a txn_hash or sender filter.\""},"id":1}"#;
+    let resp = io.handle_request(request, metadata.clone()).await;
+    assert_eq!(resp, Some(response.to_owned()));
+
+    let new_txn = |seq_num: u64| {
+        let account = AccountInfo::random();
+        let txn = starcoin_transaction_builder::build_transfer_from_association(
+            account.address,
+            seq_num,
+            10000,
+            starcoin_transaction_builder::DEFAULT_EXPIRATION_TIME,
+            config.net(),
+        );
+        txn.as_signed_user_txn().map(|txn| txn.clone())
+    };
+    let txn = new_txn(0)?;
+    let txn_id = txn.id();
+
+    // Subscribe
+    let request = format!(
+        r#"{{"jsonrpc": "2.0", "method": "starcoin_subscribe", "params": [{{"type_name":"txnStatus"}}, {{"txn_hash":{}}}], "id": 1}}"#,
+        serde_json::to_string(&txn_id)?
+    );
+    let response = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
+    let resp = io.handle_request(request.as_str(), metadata.clone()).await;
+    assert_eq!(resp, Some(response.to_owned()));
+
+    // the status of other txns are filtered out.
+    let other_txn = new_txn(1)?;
+    let _ = txpool_service.add_txns(vec![other_txn]);
+    txpool_service.add_txns(vec![txn]).pop().unwrap().unwrap();
+    let mut receiver = receiver;
+    let res = receiver.next().await.unwrap();
+    let notification = serde_json::from_str::<jsonrpc_core::Notification>(res.as_str()).unwrap();
+    match notification.params {
+        jsonrpc_core::Params::Map(s) => {
+            let result = s.get("result").unwrap();
+            assert_eq!(
+                result.get("txn_hash").unwrap(),
+                &serde_json::to_value(txn_id)?
+            );
+            assert_eq!(
+                result.get("status").unwrap(),
+                &Value::String("Pending".to_string())
+            );
+        }
+        p => {
+            panic!("subscribe return unexpected result, {:?}", &p);
+        }
+    }
+    Ok(())
+}
+
 #[stest::test]
 pub async fn test_subscribe_to_mint_block() -> Result<()> {
     let (_txpool_service, .., registry) = test_helper::start_txpool_with_miner(1000, true).await;
@@ -240,3 +312,99 @@ pub async fn test_subscribe_to_mint_block() -> Result<()> {
     assert_eq!(resp, Some(response.to_owned()));
     Ok(())
 }
+
+#[stest::test]
+pub async fn test_subscribe_to_chain_reorg() -> Result<()> {
+    let (_txpool_service, storage, config, _, registry) =
+        test_helper::start_txpool_with_miner(1000, true).await;
+    let startup_info = storage.get_startup_info()?.unwrap();
+    let net = config.net();
+    let mut block_chain = BlockChain::new(net.time_service(), startup_info.main, storage, None)?;
+    let genesis_header = block_chain.current_header();
+
+    // a block with the events of the transfer, which is retracted later.
+    let txn = starcoin_transaction_builder::build_transfer_from_association(
+        AccountInfo::random().address,
+        0,
+        10000,
+        net.time_service().now_secs() + starcoin_transaction_builder::DEFAULT_EXPIRATION_TIME,
+        net,
+    );
+    let (block_template, _) = block_chain.create_block_template(
+        *AccountInfo::random().address(),
+        None,
+        vec![txn.as_signed_user_txn()?.clone()],
+        vec![],
+        None,
+    )?;
+    let block = block_chain
+        .consensus()
+        .create_block(block_template, net.time_service().as_ref())?;
+    let retracted_block = block_chain.apply(block)?;
+
+    let bus = registry.service_ref::<BusService>().await?;
+    let _notify_service = registry.register::<ChainNotifyHandlerService>().await?;
+    let service = registry
+        .register_by_factory::<PubSubService, PubSubServiceFactory>()
+        .await?;
+    let pubsub = PubSubImpl::new(service);
+    let pubsub = pubsub.to_delegate();
+
+    let mut io = MetaIoHandler::default();
+    io.extend_with(pubsub);
+
+    let mut metadata = Metadata::default();
+    let (sender, mut receiver) = futures::channel::mpsc::unbounded();
+    metadata.session = Some(Arc::new(Session::new(sender)));
+
+    let request = r#"{"jsonrpc": "2.0", "method": "starcoin_subscribe", "params": [{"type_name":"chainReorg"}, {}], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: chainReorg","data":"\"Expected no parameters.\""},"id":1}"#;
+    let resp = io.handle_request(request, metadata.clone()).await;
+    assert_eq!(resp, Some(response.to_owned()));
+
+    let request = r#"{"jsonrpc": "2.0", "method": "starcoin_subscribe", "params": [{"type_name":"chainReorg"}], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
+    let resp = io.handle_request(request, metadata.clone()).await;
+    assert_eq!(resp, Some(response.to_owned()));
+    let request = r#"{"jsonrpc": "2.0", "method": "starcoin_subscribe", "params": [{"type_name":"events"}, {}], "id": 2}"#;
+    let response = r#"{"jsonrpc":"2.0","result":1,"id":2}"#;
+    let resp = io.handle_request(request, metadata.clone()).await;
+    assert_eq!(resp, Some(response.to_owned()));
+
+    let enacted = HashValue::random();
+    bus.broadcast(ChainReorg {
+        ancestor: genesis_header.clone(),
+        retracted: vec![retracted_block.block().id()],
+        enacted: vec![enacted],
+    })?;
+
+    let mut reorg = None;
+    let mut removed_events = vec![];
+    // the notifications of the two subscriptions may be out of order.
+    while reorg.is_none() || removed_events.is_empty() {
+        let res = timeout(Duration::from_secs(5), receiver.next())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Empty value"))?;
+        let r: Value = serde_json::from_str(&res)?;
+        let result = r["params"]["result"].clone();
+        match r["params"]["subscription"].as_u64() {
+            Some(0) => reorg = Some(result),
+            _ => removed_events.push(result),
+        }
+    }
+    let reorg = reorg.unwrap();
+    assert_eq!(
+        reorg["ancestor"]["block_hash"],
+        serde_json::to_value(genesis_header.id())?
+    );
+    assert_eq!(
+        reorg["retracted"],
+        serde_json::to_value(vec![retracted_block.block().id()])?
+    );
+    assert_eq!(reorg["enacted"], serde_json::to_value(vec![enacted])?);
+    for event in removed_events {
+        assert_eq!(event["removed"], Value::Bool(true));
+        assert_eq!(event["block_number"].as_str(), Some("1"));
+    }
+    Ok(())
+}
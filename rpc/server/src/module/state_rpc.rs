@@ -1,6 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::module::helpers::StateRootResolver;
 use crate::module::map_err;
 use bcs_ext::BCSCodec;
 use futures::future::TryFutureExt;
@@ -21,6 +22,8 @@ use starcoin_rpc_api::FutureResult;
 use starcoin_state_api::{ChainStateAsyncService, StateView};
 use starcoin_state_tree::StateNodeStore;
 use starcoin_statedb::{ChainStateDB, ChainStateReader};
+use starcoin_storage::Store;
+use starcoin_types::block::BlockNumber;
 use starcoin_types::language_storage::ModuleId;
 use starcoin_types::{
     access_path::AccessPath, account_address::AccountAddress, account_state::AccountState,
@@ -38,16 +41,38 @@ where
 {
     service: S,
     state_store: Arc<dyn StateNodeStore>,
+    storage: Option<Arc<dyn Store>>,
 }
 
 impl<S> StateRpcImpl<S>
 where
     S: ChainStateAsyncService,
 {
+    /// The state can only be selected by state root, use `new_with_storage` to select it by block.
     pub fn new(service: S, state_store: Arc<dyn StateNodeStore>) -> Self {
         Self {
             service,
             state_store,
+            storage: None,
+        }
+    }
+
+    pub fn new_with_storage<T>(service: S, storage: Arc<T>) -> Self
+    where
+        T: Store + 'static,
+    {
+        Self {
+            service,
+            state_store: storage.clone(),
+            storage: Some(storage),
+        }
+    }
+
+    fn state_root_resolver(&self) -> StateRootResolver<S> {
+        StateRootResolver {
+            chain_state: self.service.clone(),
+            state_store: self.state_store.clone(),
+            storage: self.storage.clone(),
         }
     }
 }
@@ -105,11 +130,15 @@ where
         &self,
         address: AccountAddress,
         state_root: Option<HashValue>,
+        block_number: Option<BlockNumber>,
+        block_hash: Option<HashValue>,
     ) -> FutureResult<Option<AccountStateSetView>> {
-        let state_service = self.service.clone();
+        let resolver = self.state_root_resolver();
         let db = self.state_store.clone();
         let fut = async move {
-            let state_root = state_root.unwrap_or(state_service.state_root().await?);
+            let state_root = resolver
+                .resolve(block_number, block_hash, state_root)
+                .await?;
             let statedb = ChainStateDB::new(db, Some(state_root));
             let state = statedb.get_account_state_set(&address)?;
             let annotator = MoveValueAnnotator::new(&statedb);
@@ -265,13 +294,13 @@ where
         resource_type: StrView<StructTag>,
         option: Option<GetResourceOption>,
     ) -> FutureResult<Option<ResourceView>> {
-        let service = self.service.clone();
+        let resolver = self.state_root_resolver();
         let state_store = self.state_store.clone();
         let option = option.unwrap_or_default();
         let f = async move {
-            let state_root = option
-                .state_root
-                .unwrap_or(service.clone().state_root().await?);
+            let state_root = resolver
+                .resolve(option.block_number, option.block_hash, option.state_root)
+                .await?;
             let chain_state = ChainStateDB::new(state_store, Some(state_root));
             let data = chain_state.get_state_value(&StateKey::AccessPath(
                 AccessPath::resource_access_path(addr, resource_type.0.clone()),
@@ -301,13 +330,13 @@ where
         addr: AccountAddress,
         option: Option<ListResourceOption>,
     ) -> FutureResult<ListResourceView> {
-        let state_service = self.service.clone();
+        let resolver = self.state_root_resolver();
         let db = self.state_store.clone();
         let option = option.unwrap_or_default();
         let fut = async move {
-            let state_root = option
-                .state_root
-                .unwrap_or(state_service.state_root().await?);
+            let state_root = resolver
+                .resolve(option.block_number, option.block_hash, option.state_root)
+                .await?;
             let statedb = ChainStateDB::new(db, Some(state_root));
 
             let state = statedb.get_account_state_set(&addr)?;
@@ -4,11 +4,15 @@
 use crate::module::{convert_to_rpc_error, map_err};
 use bcs_ext::BCSCodec;
 use starcoin_crypto::HashValue;
+use starcoin_logger::tracer::Span;
 /// Re-export the API
 pub use starcoin_rpc_api::txpool::*;
-use starcoin_rpc_api::types::{SignedUserTransactionView, StrView};
+use starcoin_rpc_api::types::{
+    BlockFeeHistoryView, GasPriceEstimateView, SignedUserTransactionView, StrView,
+    TxPoolSenderView, TxPoolTxnSummaryView, TxPoolTxnView,
+};
 use starcoin_rpc_api::{txpool::TxPoolApi, FutureResult};
-use starcoin_txpool_api::{TxPoolStatus, TxPoolSyncService};
+use starcoin_txpool_api::{TxPoolStatus, TxPoolSyncService, TxnStatusInfo};
 use starcoin_types::account_address::AccountAddress;
 use starcoin_types::transaction::SignedUserTransaction;
 use std::convert::TryInto;
@@ -38,12 +42,18 @@ where
 {
     fn submit_transaction(&self, txn: SignedUserTransaction) -> FutureResult<HashValue> {
         let txn_hash = txn.id();
+        // the txpool continues the trace when the txn is included in a block.
+        let mut span = Span::enter("txpool.submit_transaction").with("txn_hash", txn_hash);
         let result: Result<(), jsonrpc_core::Error> = self
             .service
             .add_txns(vec![txn])
             .pop()
             .expect("txpool should return result")
             .map_err(convert_to_rpc_error);
+        if let Err(e) = &result {
+            span.set_error(&e.message);
+        }
+        drop(span);
 
         Box::pin(futures::future::ready(result.map(|_| txn_hash)))
     }
@@ -66,10 +76,28 @@ where
     }
 
     fn gas_price(&self) -> FutureResult<StrView<u64>> {
-        let gas_price = 1u64;
+        let gas_price = self.service.gas_price_estimate().medium;
         Box::pin(futures::future::ok(gas_price.into()))
     }
 
+    fn gas_price_estimate(&self) -> FutureResult<GasPriceEstimateView> {
+        let estimate = self.service.gas_price_estimate();
+        Box::pin(futures::future::ok(estimate.into()))
+    }
+
+    fn fee_history(
+        &self,
+        block_count: u64,
+        percentiles: Vec<f64>,
+    ) -> FutureResult<Vec<BlockFeeHistoryView>> {
+        let history = self
+            .service
+            .fee_history(block_count, percentiles)
+            .map(|history| history.into_iter().map(Into::into).collect())
+            .map_err(map_err);
+        Box::pin(futures::future::ready(history))
+    }
+
     fn pending_txns(
         &self,
         addr: AccountAddress,
@@ -103,6 +131,26 @@ where
         let state = self.service.status();
         Box::pin(futures::future::ok(state))
     }
+
+    fn transaction_status(&self, txn_hash: HashValue) -> FutureResult<Option<TxnStatusInfo>> {
+        let status = self.service.txn_status(txn_hash).map_err(map_err);
+        Box::pin(futures::future::ready(status))
+    }
+
+    fn content(&self) -> FutureResult<Vec<TxPoolSenderView<TxPoolTxnView>>> {
+        let content: Result<Vec<TxPoolSenderView<TxPoolTxnView>>, _> = self
+            .service
+            .content()
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect();
+        Box::pin(futures::future::ready(content.map_err(map_err)))
+    }
+
+    fn inspect(&self) -> FutureResult<Vec<TxPoolSenderView<TxPoolTxnSummaryView>>> {
+        let content = self.service.content().into_iter().map(Into::into).collect();
+        Box::pin(futures::future::ready(Ok(content)))
+    }
 }
 
 #[cfg(test)]
@@ -48,6 +48,29 @@ impl JsonApiRateLimitMiddleware {
     }
 }
 
+impl JsonApiRateLimitMiddleware {
+    /// Check the quota of the call, return the failure output if it is limited.
+    pub fn check(&self, call: &Call, user: Option<&String>) -> Option<Output> {
+        let (m, json_version, id) = match call {
+            Call::MethodCall(m) => (m.method.clone(), m.jsonrpc, m.id.clone()),
+            Call::Notification(n) => (n.method.clone(), n.jsonrpc, Id::Null),
+            Call::Invalid { .. } => return None,
+        };
+        match self.limiters.check(&m, user) {
+            Ok(_) => None,
+            Err(e) => Some(Output::Failure(Failure {
+                jsonrpc: json_version,
+                error: Error {
+                    code: ErrorCode::ServerError(-10000),
+                    message: e.to_string(),
+                    data: None,
+                },
+                id,
+            })),
+        }
+    }
+}
+
 impl Middleware<Metadata> for JsonApiRateLimitMiddleware {
     type Future = FutureResponse;
     type CallFuture = NoopCallFuture;
@@ -58,29 +81,9 @@ impl Middleware<Metadata> for JsonApiRateLimitMiddleware {
         F: Fn(Call, Metadata) -> X + Send + Sync,
         X: Future<Output = Option<Output>> + Send + 'static,
     {
-        let method = match &call {
-            Call::MethodCall(m) => Some((m.method.clone(), m.jsonrpc, m.id.clone())),
-            Call::Notification(n) => Some((n.method.clone(), n.jsonrpc, Id::Null)),
-            Call::Invalid { .. } => None,
-        };
-        if let Some((m, json_version, id)) = method {
-            match self.limiters.check(&m, meta.user.as_ref()) {
-                Ok(_) => Either::Right(next(call, meta)),
-                Err(e) => {
-                    let output = Output::Failure(Failure {
-                        jsonrpc: json_version,
-                        error: Error {
-                            code: ErrorCode::ServerError(-10000),
-                            message: e.to_string(),
-                            data: None,
-                        },
-                        id,
-                    });
-                    Either::Left(Box::pin(futures::future::ready(Some(output))))
-                }
-            }
-        } else {
-            Either::Right(next(call, meta))
+        match self.check(&call, meta.user.as_ref()) {
+            Some(output) => Either::Left(Box::pin(futures::future::ready(Some(output)))),
+            None => Either::Right(next(call, meta)),
         }
     }
 }
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::api_registry::ApiRegistry;
+use crate::auth::{RpcAuthenticator, WsAuthMiddleware};
 use crate::extractors::{RpcExtractor, WsExtractor};
 use anyhow::Result;
 use futures::stream::*;
@@ -32,10 +33,15 @@ use starcoin_service_registry::{ActorService, ServiceContext, ServiceHandler};
 use std::collections::HashSet;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Interval to check whether the rpc auth file is changed.
+const AUTH_FILE_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct RpcService {
     config: Arc<NodeConfig>,
     api_registry: ApiRegistry,
+    authenticator: Option<Arc<RpcAuthenticator>>,
     ipc: Option<jsonrpc_ipc_server::Server>,
     http: Option<jsonrpc_http_server::Server>,
     tcp: Option<jsonrpc_tcp_server::Server>,
@@ -43,7 +49,17 @@ pub struct RpcService {
 }
 
 impl ActorService for RpcService {
-    fn started(&mut self, _ctx: &mut ServiceContext<Self>) -> Result<()> {
+    fn started(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
+        if let Some(auth_file) = self.config.rpc.auth_file() {
+            let authenticator = Arc::new(RpcAuthenticator::new(auth_file)?);
+            let reload_authenticator = authenticator.clone();
+            ctx.run_interval(AUTH_FILE_RELOAD_INTERVAL, move |_ctx| {
+                if let Err(e) = reload_authenticator.reload() {
+                    warn!("Reload rpc auth file failed: {:?}", e);
+                }
+            });
+            self.authenticator = Some(authenticator);
+        }
         self.ipc = self.start_ipc()?;
         self.http = self.start_http()?;
         self.tcp = self.start_tcp()?;
@@ -62,6 +78,7 @@ impl RpcService {
         Self {
             config,
             api_registry,
+            authenticator: None,
             ipc: None,
             http: None,
             tcp: None,
@@ -158,7 +175,7 @@ impl RpcService {
         } else {
             let ipc_file = self.config.rpc.get_ipc_file();
             let apis: HashSet<Api> = self.config.rpc.ipc.apis().list_apis();
-            let io_handler = self.api_registry.get_apis(apis);
+            let io_handler = self.api_registry.get_apis(apis, None);
 
             info!("Ipc rpc server start at :{:?}", ipc_file);
             Some(
@@ -173,7 +190,7 @@ impl RpcService {
         Ok(if let Some(addr) = self.config.rpc.get_http_address() {
             let address = addr.into();
             let apis = self.config.rpc.http.apis().list_apis();
-            let io_handler = self.api_registry.get_apis(apis);
+            let io_handler = self.api_registry.get_apis(apis, self.authenticator.clone());
             let http = jsonrpc_http_server::ServerBuilder::new(io_handler)
                 .meta_extractor(RpcExtractor {
                     http_ip_headers: self.config.rpc.http.ip_headers(),
@@ -198,7 +215,7 @@ impl RpcService {
             let address = addr.into();
             let apis = self.config.rpc.tcp.apis().list_apis();
 
-            let io_handler = self.api_registry.get_apis(apis);
+            let io_handler = self.api_registry.get_apis(apis, None);
             let tcp_server = jsonrpc_tcp_server::ServerBuilder::new(io_handler)
                 .session_meta_extractor(RpcExtractor::default())
                 .start(&address)?;
@@ -213,11 +230,14 @@ impl RpcService {
         Ok(if let Some(addr) = self.config.rpc.get_ws_address() {
             let address = addr.into();
             let apis = self.config.rpc.ws.apis().list_apis();
-            let io_handler = self.api_registry.get_apis(apis);
-            let ws_server = jsonrpc_ws_server::ServerBuilder::new(io_handler)
+            let io_handler = self.api_registry.get_apis(apis, self.authenticator.clone());
+            let mut builder = jsonrpc_ws_server::ServerBuilder::new(io_handler)
                 .session_meta_extractor(WsExtractor)
-                .max_payload(self.config.rpc.ws.max_request_body_size())
-                .start(&address)?;
+                .max_payload(self.config.rpc.ws.max_request_body_size());
+            if let Some(authenticator) = self.authenticator.clone() {
+                builder = builder.request_middleware(WsAuthMiddleware::new(authenticator));
+            }
+            let ws_server = builder.start(&address)?;
             info!("Rpc: websocket server start at: {}", address);
             Some(ws_server)
         } else {
@@ -270,7 +290,7 @@ pub fn connect_local(
 impl ServiceHandler<Self, ConnectLocal> for RpcService {
     fn handle(&mut self, _msg: ConnectLocal, ctx: &mut ServiceContext<RpcService>) -> RpcChannel {
         let apis = ApiSet::All.list_apis();
-        let io_handler = self.api_registry.get_apis(apis);
+        let io_handler = self.api_registry.get_apis(apis, None);
         //remove middleware.
         let mut local_io_handler = MetaIoHandler::default();
         local_io_handler.extend_with(io_handler.iter().map(|(n, f)| (n.clone(), f.clone())));
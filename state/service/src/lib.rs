@@ -1,6 +1,8 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod pruner;
 mod service;
 
+pub use pruner::StatePrunerService;
 pub use service::ChainStateService;
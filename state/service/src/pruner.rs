@@ -0,0 +1,96 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, format_err, Result};
+use starcoin_config::NodeConfig;
+use starcoin_logger::prelude::*;
+use starcoin_service_registry::{ActorService, EventHandler, ServiceContext, ServiceFactory};
+use starcoin_storage::state_node::{StateNodePruneStore, StateNodePruner};
+use starcoin_storage::Storage;
+use starcoin_types::block::BlockNumber;
+use starcoin_types::system_events::NewHeadBlock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Prune once every `PRUNE_INTERVAL` blocks, the pruner scans the stale index of the reorg window.
+const PRUNE_INTERVAL: BlockNumber = 16;
+/// Max blocks to prune at one time, larger than `PRUNE_INTERVAL` to catch up with the history.
+const PRUNE_MAX_BLOCKS: usize = 256;
+
+/// Prune the state nodes in background when the head block changes,
+/// only registered when `storage.state_prune_keep_blocks` is set.
+pub struct StatePrunerService {
+    pruner: Arc<StateNodePruner>,
+    last_prune_number: BlockNumber,
+    /// a prune round is running on the blocking thread pool.
+    pruning: Arc<AtomicBool>,
+}
+
+impl ServiceFactory<Self> for StatePrunerService {
+    fn create(ctx: &mut ServiceContext<StatePrunerService>) -> Result<StatePrunerService> {
+        let config = ctx.get_shared::<Arc<NodeConfig>>()?;
+        let storage = ctx.get_shared::<Arc<Storage>>()?;
+        let keep_blocks = config
+            .storage
+            .state_prune_keep_blocks()
+            .ok_or_else(|| format_err!("State prune is not enabled."))?;
+        ensure!(
+            storage.is_state_prune_enabled(),
+            "State prune should be enabled in storage."
+        );
+        info!(
+            "State pruner keeps the state of last {} blocks",
+            keep_blocks
+        );
+        Ok(Self {
+            pruner: Arc::new(StateNodePruner::new(storage, keep_blocks)),
+            last_prune_number: 0,
+            pruning: Arc::new(AtomicBool::new(false)),
+        })
+    }
+}
+
+impl ActorService for StatePrunerService {
+    fn started(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
+        ctx.subscribe::<NewHeadBlock>();
+        Ok(())
+    }
+
+    fn stopped(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
+        ctx.unsubscribe::<NewHeadBlock>();
+        Ok(())
+    }
+}
+
+impl EventHandler<Self, NewHeadBlock> for StatePrunerService {
+    fn handle_event(&mut self, msg: NewHeadBlock, ctx: &mut ServiceContext<StatePrunerService>) {
+        let NewHeadBlock(block) = msg;
+        let number = block.header().number();
+        // a reorg may switch to a lower head, prune again after the interval.
+        if number < self.last_prune_number.saturating_add(PRUNE_INTERVAL)
+            && number >= self.last_prune_number
+        {
+            return;
+        }
+        // skip the head if the last round is still running, the next head will catch up.
+        if self.pruning.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.last_prune_number = number;
+        let pruner = self.pruner.clone();
+        let pruning = self.pruning.clone();
+        ctx.spawn(async move {
+            // pruning reads and deletes a lot of nodes, do not block the actor.
+            let result = tokio::task::spawn_blocking(move || {
+                pruner.prune(block.header(), block.block_info(), PRUNE_MAX_BLOCKS)
+            })
+            .await;
+            pruning.store(false, Ordering::SeqCst);
+            match result {
+                Ok(Err(e)) => error!("Prune state nodes at block {} failed: {:?}", number, e),
+                Err(e) => error!("Prune state nodes task at block {} failed: {:?}", number, e),
+                Ok(Ok(_)) => {}
+            }
+        });
+    }
+}
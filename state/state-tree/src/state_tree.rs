@@ -16,7 +16,7 @@ use starcoin_types::account_state::AccountState;
 use starcoin_types::language_storage::StructTag;
 use starcoin_types::state_set::{AccountStateSet, StateSet};
 use starcoin_vm_types::access_path::ModuleName;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
 use std::ops::DerefMut;
 use std::sync::Arc;
@@ -203,6 +203,16 @@ where
 
     /// commit the state change into underline storage.
     pub fn flush(&self) -> Result<()> {
+        self.flush_inner(false).map(|_| ())
+    }
+
+    /// commit the state change into underline storage,
+    /// and return the keys of nodes which are not referenced by the new root any more.
+    pub fn flush_with_stale_nodes(&self) -> Result<BTreeSet<NodeKey>> {
+        self.flush_inner(true)
+    }
+
+    fn flush_inner(&self, collect_stale_nodes: bool) -> Result<BTreeSet<NodeKey>> {
         let change_set_list = {
             let mut cache_guard = self.cache.lock();
             cache_guard.split_off_idx = Some(cache_guard.change_set_list.len());
@@ -213,12 +223,22 @@ where
         // when self::commit call self::updates(&self, updates: Vec<(K, Option<Blob>)>)
         // the param updates is empty cause this situation
         if change_set_list.is_empty() {
-            return Ok(());
+            return Ok(BTreeSet::new());
         }
         let mut root_hash = HashValue::default();
         let mut node_map = BTreeMap::new();
+        let mut stale_nodes = BTreeSet::new();
         for (hash, change_sets) in change_set_list.into_iter() {
+            if collect_stale_nodes {
+                for stale_node in change_sets.stale_node_index_batch.into_iter() {
+                    stale_nodes.insert(stale_node.node_key);
+                }
+            }
             for (nk, n) in change_sets.node_batch.into_iter() {
+                // the node key is the hash of node, so a stale node may be created again.
+                if collect_stale_nodes {
+                    stale_nodes.remove(&nk);
+                }
                 node_map.insert(nk, n.try_into()?);
             }
             root_hash = hash;
@@ -227,7 +247,7 @@ where
         // and then advance the storage root hash
         *self.storage_root_hash.write() = root_hash;
         self.cache.lock().reset(root_hash);
-        Ok(())
+        Ok(stale_nodes)
     }
 
     /// Dump tree to state set.
@@ -368,3 +368,32 @@ pub fn test_state_multi_commit_and_flush() -> Result<()> {
     assert_eq!(state.get(&hash_value3)?, None);
     Ok(())
 }
+
+#[test]
+pub fn test_state_flush_with_stale_nodes() -> Result<()> {
+    let s = MockStateNodeStore::new();
+    let state = StateTree::<HashValueKey>::new(Arc::new(s), None);
+    let hash_value1 = HashValueKey(HashValue::random());
+    state.put(hash_value1, vec![1u8]);
+    state.commit()?;
+    state.flush()?;
+    let root_hash1 = state.root_hash();
+
+    // the stale nodes of multi commits are merged.
+    state.put(hash_value1, vec![2u8]);
+    let root_hash2 = state.commit()?;
+    state.put(HashValueKey(HashValue::random()), vec![3u8]);
+    state.commit()?;
+    let stale_nodes = state.flush_with_stale_nodes()?;
+    assert!(stale_nodes.contains(&root_hash1));
+    assert!(stale_nodes.contains(&root_hash2));
+
+    // a node created again is not stale.
+    state.put(hash_value1, vec![1u8]);
+    state.commit()?;
+    state.put(hash_value1, vec![2u8]);
+    state.commit()?;
+    let stale_nodes = state.flush_with_stale_nodes()?;
+    assert!(!stale_nodes.contains(&root_hash2));
+    Ok(())
+}
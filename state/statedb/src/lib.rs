@@ -31,7 +31,7 @@ use starcoin_vm_types::language_storage::StructTag;
 use starcoin_vm_types::state_store::table::TableInfo;
 use starcoin_vm_types::state_store::{state_key::StateKey, table::TableHandle};
 use starcoin_vm_types::state_view::StateView;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::convert::TryInto;
 use std::sync::Arc;
 use thiserror::Error;
@@ -185,13 +185,17 @@ impl AccountStateObject {
         Ok(self.to_state())
     }
 
-    pub fn flush(&self) -> Result<()> {
-        self.resource_tree.lock().flush()?;
+    /// flush the account trees, and return the stale nodes of them if `collect_stale_nodes`.
+    pub fn flush(&self, collect_stale_nodes: bool) -> Result<Vec<HashValue>> {
+        let mut stale_nodes: Vec<HashValue> =
+            flush_tree(&*self.resource_tree.lock(), collect_stale_nodes)?
+                .into_iter()
+                .collect();
         if let Some(code_tree) = self.code_tree.lock().as_ref() {
-            code_tree.flush()?;
+            stale_nodes.extend(flush_tree(code_tree, collect_stale_nodes)?);
         }
 
-        Ok(())
+        Ok(stale_nodes)
     }
 
     fn to_state_set(&self) -> Result<AccountStateSet> {
@@ -211,6 +215,18 @@ impl AccountStateObject {
     }
 }
 
+/// Flush the tree, and return its stale nodes only if `collect_stale_nodes`.
+fn flush_tree<K: RawKey>(
+    tree: &StateTree<K>,
+    collect_stale_nodes: bool,
+) -> Result<BTreeSet<HashValue>> {
+    if collect_stale_nodes {
+        tree.flush_with_stale_nodes()
+    } else {
+        tree.flush().map(|_| BTreeSet::new())
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 pub struct ChainStateDB {
     store: Arc<dyn StateNodeStore>,
@@ -225,6 +241,11 @@ pub struct ChainStateDB {
     /// state_tree_table_handles SMT save TableHandle -> TableHandleState.root_hash
     state_tree_table_handles_list: Vec<StateTree<TableHandle>>,
     update_table_handle_idx_list: Mutex<HashSet<usize>>,
+    /// nodes which are not referenced any more by the flushed state, since last `take_stale_nodes`.
+    /// A node may be shared by different trees, so it may appear more than once.
+    stale_nodes: Mutex<Vec<HashValue>>,
+    /// only collect the stale nodes when state pruning is enabled, it is not free.
+    collect_stale_nodes: bool,
 }
 
 static G_DEFAULT_CACHE_SIZE: usize = 10240;
@@ -244,6 +265,8 @@ impl ChainStateDB {
             cache_table_handle: Mutex::new(LruCache::new(G_DEFAULT_CACHE_SIZE)),
             state_tree_table_handles_list: vec![],
             update_table_handle_idx_list: Mutex::new(HashSet::new()),
+            stale_nodes: Mutex::new(vec![]),
+            collect_stale_nodes: false,
         };
         for (handle_address, table_path) in
             TABLE_HANDLE_ADDRESS_LIST.iter().zip(TABLE_PATH_LIST.iter())
@@ -273,11 +296,23 @@ impl ChainStateDB {
     /// Fork a new statedb base current statedb
     pub fn fork(&self) -> Self {
         Self::new(self.store.clone(), Some(self.state_root()))
+            .enable_stale_nodes(self.collect_stale_nodes)
     }
 
     /// Fork a new statedb at `root_hash`
     pub fn fork_at(&self, state_root: HashValue) -> Self {
-        Self::new(self.store.clone(), Some(state_root))
+        Self::new(self.store.clone(), Some(state_root)).enable_stale_nodes(self.collect_stale_nodes)
+    }
+
+    /// Enable or disable collecting the stale nodes on flush, disabled by default.
+    pub fn enable_stale_nodes(mut self, enabled: bool) -> Self {
+        self.collect_stale_nodes = enabled;
+        self
+    }
+
+    /// Take the stale nodes produced by flushes since last call, used by state pruning.
+    pub fn take_stale_nodes(&self) -> Vec<HashValue> {
+        std::mem::take(&mut *self.stale_nodes.lock())
     }
 
     fn new_state_tree<K: RawKey>(&self, root_hash: HashValue) -> StateTree<K> {
@@ -599,7 +634,9 @@ impl ChainStateWriter for ChainStateDB {
             let code_root = if let Some(state_set) = account_state_set.code_set() {
                 let state_tree = StateTree::<ModuleName>::new(self.store.clone(), None);
                 state_tree.apply(state_set.clone())?;
-                state_tree.flush()?;
+                self.stale_nodes
+                    .lock()
+                    .extend(flush_tree(&state_tree, self.collect_stale_nodes)?);
                 Some(state_tree.root_hash())
             } else {
                 None
@@ -607,7 +644,9 @@ impl ChainStateWriter for ChainStateDB {
             let resource_root = if let Some(state_set) = account_state_set.resource_set() {
                 let state_tree = StateTree::<StructTag>::new(self.store.clone(), None);
                 state_tree.apply(state_set.clone())?;
-                state_tree.flush()?;
+                self.stale_nodes
+                    .lock()
+                    .extend(flush_tree(&state_tree, self.collect_stale_nodes)?);
                 state_tree.root_hash()
             } else {
                 unreachable!("this should never happened")
@@ -616,7 +655,9 @@ impl ChainStateWriter for ChainStateDB {
             self.state_tree.put(*address, new_account_state.try_into()?);
         }
         self.state_tree.commit()?;
-        self.state_tree.flush()?;
+        self.stale_nodes
+            .lock()
+            .extend(flush_tree(&self.state_tree, self.collect_stale_nodes)?);
         Ok(())
     }
 
@@ -707,29 +748,35 @@ impl ChainStateWriter for ChainStateDB {
 
     /// flush data to db.
     fn flush(&self) -> Result<()> {
+        let mut stale_nodes = vec![];
         //cache flush
         let mut locks_table_handle = self.updates_table_handle.write();
         for h in locks_table_handle.iter() {
             let table_handle_state_object = self.get_table_handle_state_object(h)?;
-            table_handle_state_object.flush()?;
+            stale_nodes.extend(table_handle_state_object.flush(self.collect_stale_nodes)?);
         }
         locks_table_handle.clear();
 
         for idx in self.update_table_handle_idx_list.lock().iter() {
             let state_tree_table_handle = self.get_state_tree_table_handles(*idx)?;
-            state_tree_table_handle.flush()?;
+            stale_nodes.extend(flush_tree(
+                state_tree_table_handle,
+                self.collect_stale_nodes,
+            )?);
         }
         self.update_table_handle_idx_list.lock().clear();
 
         let mut locks = self.updates.write();
         for address in locks.iter() {
             let account_state_object = self.get_account_state_object(address, false)?;
-            account_state_object.flush()?;
+            stale_nodes.extend(account_state_object.flush(self.collect_stale_nodes)?);
         }
         locks.clear();
 
         // self tree flush
-        self.state_tree.flush()
+        stale_nodes.extend(flush_tree(&self.state_tree, self.collect_stale_nodes)?);
+        self.stale_nodes.lock().extend(stale_nodes);
+        Ok(())
     }
 }
 
@@ -765,9 +812,8 @@ impl TableHandleStateObject {
         Ok(())
     }
 
-    pub fn flush(&self) -> Result<()> {
-        self.state_tree.lock().flush()?;
-        Ok(())
+    pub fn flush(&self, collect_stale_nodes: bool) -> Result<BTreeSet<HashValue>> {
+        flush_tree(&*self.state_tree.lock(), collect_stale_nodes)
     }
 
     pub fn root_hash(&self) -> HashValue {
@@ -99,6 +99,24 @@ fn test_state_db_dump_and_apply() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_state_db_stale_nodes() -> Result<()> {
+    let storage = Arc::new(MockStateNodeStore::new());
+    let access_path = AccessPath::random_resource();
+    for enabled in [false, true] {
+        let chain_state_db = ChainStateDB::new(storage.clone(), None).enable_stale_nodes(enabled);
+        chain_state_db.apply_write_set(to_write_set(access_path.clone(), random_bytes()))?;
+        chain_state_db.commit()?;
+        chain_state_db.flush()?;
+        chain_state_db.apply_write_set(to_write_set(access_path.clone(), random_bytes()))?;
+        chain_state_db.commit()?;
+        chain_state_db.flush()?;
+        // the old nodes of the account and global tree are stale.
+        assert_eq!(!chain_state_db.take_stale_nodes().is_empty(), enabled);
+    }
+    Ok(())
+}
+
 #[test]
 fn test_state_version() -> Result<()> {
     let storage = Arc::new(MockStateNodeStore::new());
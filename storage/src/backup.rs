@@ -0,0 +1,467 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Online backup of the node db, built from the RocksDB checkpoints.
+//!
+//! A backup dir keeps the SST files of all the backups in `shared`, and every backup in `backup-<id>`,
+//! with the other db files and the manifest of the backup. The SST files are immutable and their names
+//! are never reused by a db, so a later backup only copies the SST files which are not in `shared`.
+
+use crate::db_storage::DBStorage;
+use crate::storage::StorageInstance;
+use crate::{BlockStore, Storage, StorageVersion, Store};
+use anyhow::{bail, ensure, format_err, Result};
+use parking_lot::{const_mutex, Mutex};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use starcoin_accumulator::node::AccumulatorStoreType;
+use starcoin_accumulator::{Accumulator, MerkleAccumulator};
+use starcoin_config::RocksdbConfig;
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_types::block::BlockHeader;
+use starcoin_types::startup_info::ChainInfo;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const BACKUP_MANIFEST_FILE: &str = "manifest.json";
+const SHARED_DIR: &str = "shared";
+const BACKUP_DIR_PREFIX: &str = "backup-";
+const SST_FILE_EXTENSION: &str = "sst";
+
+/// Only one backup can be created at a time.
+static BACKUP_LOCK: Mutex<()> = const_mutex(());
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub name: String,
+    pub size: u64,
+    /// The sha3-256 hash of the file content.
+    pub hash: HashValue,
+    /// The file is in the shared dir, otherwise in the dir of the backup.
+    pub shared: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backup_id: u64,
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub storage_version: u8,
+    pub genesis_hash: HashValue,
+    /// The chain info of the backed up db.
+    pub chain_info: ChainInfo,
+    pub files: Vec<BackupFile>,
+    /// Number of the files copied by this backup, the other files are shared with the former backups.
+    pub copied_files: u64,
+    /// Size of the files copied by this backup.
+    pub copied_size: u64,
+}
+
+impl BackupManifest {
+    pub fn head(&self) -> &BlockHeader {
+        self.chain_info.head()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files
+            .iter()
+            .fold(0u64, |total, file| total.saturating_add(file.size))
+    }
+
+    pub fn load(backup_dir: &Path, backup_id: u64) -> Result<Self> {
+        let path = backup_path(backup_dir, backup_id).join(BACKUP_MANIFEST_FILE);
+        let bytes = fs::read(&path)
+            .map_err(|e| format_err!("Read backup manifest {} error: {}", path.display(), e))?;
+        Ok(serde_json::from_slice(bytes.as_slice())?)
+    }
+
+    fn file_path(&self, backup_dir: &Path, file: &BackupFile) -> PathBuf {
+        if file.shared {
+            backup_dir.join(SHARED_DIR).join(file.name.as_str())
+        } else {
+            backup_path(backup_dir, self.backup_id).join(file.name.as_str())
+        }
+    }
+}
+
+fn backup_path(backup_dir: &Path, backup_id: u64) -> PathBuf {
+    backup_dir.join(format!("{}{}", BACKUP_DIR_PREFIX, backup_id))
+}
+
+/// The sha3-256 hash of the file content.
+fn file_hash(path: &Path) -> Result<HashValue> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha3_256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let len = file.read(buf.as_mut_slice())?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+    }
+    Ok(HashValue::from_slice(hasher.finalize().as_slice())?)
+}
+
+/// Resolve the requested `backup_dir` under `backup_root`, a relative dir is relative to `backup_root`,
+/// and a dir outside of `backup_root` is rejected.
+pub fn resolve_backup_dir(backup_root: &Path, backup_dir: &Path) -> Result<PathBuf> {
+    let relative_dir = if backup_dir.is_absolute() {
+        backup_dir.strip_prefix(backup_root).map_err(|_| {
+            format_err!(
+                "The backup dir {} is not under the backup root {}",
+                backup_dir.display(),
+                backup_root.display()
+            )
+        })?
+    } else {
+        backup_dir
+    };
+    ensure!(
+        relative_dir
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir)),
+        "The backup dir {} should not leave the backup root {}",
+        backup_dir.display(),
+        backup_root.display()
+    );
+    Ok(backup_root.join(relative_dir))
+}
+
+/// List the ids of the backups in `backup_dir` in ascending order.
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<u64>> {
+    if !backup_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut backup_ids = vec![];
+    for entry in fs::read_dir(backup_dir)? {
+        let entry = entry?;
+        let backup_id = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(BACKUP_DIR_PREFIX))
+            .and_then(|id| id.parse::<u64>().ok());
+        if let Some(backup_id) = backup_id {
+            if entry.path().join(BACKUP_MANIFEST_FILE).is_file() {
+                backup_ids.push(backup_id);
+            }
+        }
+    }
+    backup_ids.sort_unstable();
+    Ok(backup_ids)
+}
+
+/// Backup the db into `backup_dir` while the db is in use,
+/// only the SST files not in the former backups of `backup_dir` are copied.
+pub fn create_backup(db: &DBStorage, backup_dir: &Path) -> Result<BackupManifest> {
+    let _guard = BACKUP_LOCK
+        .try_lock()
+        .ok_or_else(|| format_err!("Another backup is in progress."))?;
+    fs::create_dir_all(backup_dir.join(SHARED_DIR))?;
+    let last_backup = match list_backups(backup_dir)?.last() {
+        Some(backup_id) => Some(BackupManifest::load(backup_dir, *backup_id)?),
+        None => None,
+    };
+    let backup_id = last_backup
+        .as_ref()
+        .map(|manifest| manifest.backup_id.saturating_add(1))
+        .unwrap_or(1);
+    // create the checkpoint beside the db, so the SST files are hard linked instead of copied.
+    let db_dir_name = db
+        .path()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("db");
+    let checkpoint_dir = db
+        .path()
+        .with_file_name(format!("{}.checkpoint-{}", db_dir_name, backup_id));
+    if checkpoint_dir.exists() {
+        fs::remove_dir_all(&checkpoint_dir)?;
+    }
+    db.create_checkpoint(&checkpoint_dir)?;
+    let result = copy_checkpoint(
+        checkpoint_dir.as_path(),
+        backup_dir,
+        backup_id,
+        last_backup.as_ref(),
+    );
+    if let Err(e) = fs::remove_dir_all(&checkpoint_dir) {
+        warn!(
+            "Remove backup checkpoint {} error: {}",
+            checkpoint_dir.display(),
+            e
+        );
+    }
+    result
+}
+
+fn copy_checkpoint(
+    checkpoint_dir: &Path,
+    backup_dir: &Path,
+    backup_id: u64,
+    last_backup: Option<&BackupManifest>,
+) -> Result<BackupManifest> {
+    // read the chain info from the checkpoint, so it is consistent with the backed up files.
+    let (storage_version, genesis_hash, chain_info) =
+        read_chain_info(checkpoint_dir, StorageVersion::current_version())?;
+    if let Some(last_backup) = last_backup {
+        ensure!(
+            last_backup.genesis_hash == genesis_hash,
+            "The backup dir {} contains the backups of another chain, genesis: {}",
+            backup_dir.display(),
+            last_backup.genesis_hash
+        );
+    }
+
+    let tmp_dir = backup_path(backup_dir, backup_id).with_extension("tmp");
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+    let mut entries = fs::read_dir(checkpoint_dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut files = vec![];
+    let (mut copied_files, mut copied_size) = (0u64, 0u64);
+    for entry in entries {
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|name| format_err!("Invalid db file name: {:?}", name))?;
+        let size = entry.metadata()?.len();
+        let shared =
+            entry.path().extension().and_then(|ext| ext.to_str()) == Some(SST_FILE_EXTENSION);
+        let target = if shared {
+            backup_dir.join(SHARED_DIR).join(name.as_str())
+        } else {
+            tmp_dir.join(name.as_str())
+        };
+        let hash = file_hash(entry.path().as_path())?;
+        // a shared SST file is reused only if its content is not corrupted.
+        let exists = shared
+            && fs::metadata(&target)
+                .map(|metadata| metadata.len() == size)
+                .unwrap_or(false)
+            && file_hash(target.as_path()).ok() == Some(hash);
+        if !exists {
+            // copy to a temp file first, an incomplete SST file must not be shared.
+            let tmp_target = target.with_extension("tmp");
+            fs::copy(entry.path(), &tmp_target)?;
+            ensure!(
+                file_hash(tmp_target.as_path())? == hash,
+                "The copied backup file {} does not match the db file",
+                tmp_target.display()
+            );
+            fs::rename(&tmp_target, &target)?;
+            copied_files = copied_files.saturating_add(1);
+            copied_size = copied_size.saturating_add(size);
+        }
+        files.push(BackupFile {
+            name,
+            size,
+            hash,
+            shared,
+        });
+    }
+
+    let manifest = BackupManifest {
+        backup_id,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        storage_version: storage_version.into(),
+        genesis_hash,
+        chain_info,
+        files,
+        copied_files,
+        copied_size,
+    };
+    fs::write(
+        tmp_dir.join(BACKUP_MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    fs::rename(&tmp_dir, backup_path(backup_dir, backup_id))?;
+    info!(
+        "Create backup {} at block {}, copied {} files, {} bytes",
+        backup_id,
+        manifest.head().number(),
+        copied_files,
+        copied_size
+    );
+    Ok(manifest)
+}
+
+fn open_readonly_storage(path: &Path, storage_version: StorageVersion) -> Result<Storage> {
+    let db = DBStorage::open_with_cfs(
+        path,
+        storage_version.get_column_family_names().to_vec(),
+        true,
+        RocksdbConfig::default(),
+        None,
+    )?;
+    Storage::new(StorageInstance::new_db_instance(db))
+}
+
+fn read_chain_info(
+    path: &Path,
+    storage_version: StorageVersion,
+) -> Result<(StorageVersion, HashValue, ChainInfo)> {
+    let storage = open_readonly_storage(path, storage_version)?;
+    let storage_version = storage.chain_info_storage.get_storage_version()?;
+    let genesis_hash = storage
+        .get_genesis()?
+        .ok_or_else(|| format_err!("Genesis not found in db {}", path.display()))?;
+    let chain_info = storage
+        .get_chain_info()?
+        .ok_or_else(|| format_err!("Chain info not found in db {}", path.display()))?;
+    Ok((storage_version, genesis_hash, chain_info))
+}
+
+/// Restore the backup of `backup_id`, or the latest backup, in `backup_dir` to `db_path`, which must not contain a db.
+/// The restored db is verified against `genesis_hash` and the accumulator roots in the manifest before it is adopted.
+pub fn restore_backup(
+    backup_dir: &Path,
+    backup_id: Option<u64>,
+    db_path: &Path,
+    genesis_hash: HashValue,
+) -> Result<BackupManifest> {
+    let backup_id = match backup_id {
+        Some(backup_id) => backup_id,
+        None => *list_backups(backup_dir)?
+            .last()
+            .ok_or_else(|| format_err!("No backup found in {}", backup_dir.display()))?,
+    };
+    let manifest = BackupManifest::load(backup_dir, backup_id)?;
+    ensure!(
+        manifest.genesis_hash == genesis_hash,
+        "The genesis {} of backup {} does not match the genesis {} of the network",
+        manifest.genesis_hash,
+        backup_id,
+        genesis_hash
+    );
+    let storage_version = StorageVersion::try_from(manifest.storage_version)?;
+    ensure!(
+        storage_version <= StorageVersion::current_version(),
+        "The storage version {:?} of backup {} is newer than the current version {:?}",
+        storage_version,
+        backup_id,
+        StorageVersion::current_version()
+    );
+    ensure!(
+        !DBStorage::db_exists(db_path),
+        "The db {} already exists, remove it before restoring",
+        db_path.display()
+    );
+
+    let restore_dir = db_path.with_extension("restoring");
+    if restore_dir.exists() {
+        fs::remove_dir_all(&restore_dir)?;
+    }
+    fs::create_dir_all(&restore_dir)?;
+    let result = copy_backup_files(backup_dir, &manifest, restore_dir.as_path())
+        .and_then(|_| verify_restored_db(restore_dir.as_path(), storage_version, &manifest));
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&restore_dir);
+        return Err(e);
+    }
+    if db_path.exists() {
+        fs::remove_dir_all(db_path)?;
+    }
+    fs::rename(&restore_dir, db_path)?;
+    info!(
+        "Restore backup {} at block {} to {}",
+        backup_id,
+        manifest.head().number(),
+        db_path.display()
+    );
+    Ok(manifest)
+}
+
+fn copy_backup_files(backup_dir: &Path, manifest: &BackupManifest, target: &Path) -> Result<()> {
+    for file in &manifest.files {
+        let source = manifest.file_path(backup_dir, file);
+        let size = fs::metadata(&source).map(|metadata| metadata.len()).ok();
+        if size != Some(file.size) {
+            bail!(
+                "Backup file {} is missing or corrupted, expect size {}, got {:?}",
+                source.display(),
+                file.size,
+                size
+            );
+        }
+        fs::copy(&source, target.join(file.name.as_str()))?;
+    }
+    Ok(())
+}
+
+fn verify_restored_db(
+    path: &Path,
+    storage_version: StorageVersion,
+    manifest: &BackupManifest,
+) -> Result<()> {
+    for file in &manifest.files {
+        let hash = file_hash(path.join(file.name.as_str()).as_path())?;
+        ensure!(
+            hash == file.hash,
+            "The restored file {} is corrupted, expect hash {}, got {}",
+            file.name,
+            file.hash,
+            hash
+        );
+    }
+    let storage = open_readonly_storage(path, storage_version)?;
+    let genesis_hash = storage
+        .get_genesis()?
+        .ok_or_else(|| format_err!("Genesis not found in the restored db"))?;
+    ensure!(
+        genesis_hash == manifest.genesis_hash,
+        "The genesis {} of the restored db does not match the manifest {}",
+        genesis_hash,
+        manifest.genesis_hash
+    );
+    let chain_info = storage
+        .get_chain_info()?
+        .ok_or_else(|| format_err!("Chain info not found in the restored db"))?;
+    ensure!(
+        chain_info == manifest.chain_info,
+        "The chain info of the restored db does not match the manifest, head: {}",
+        chain_info.head().id()
+    );
+
+    let head = chain_info.head();
+    let block_info = chain_info.status().info();
+    ensure!(
+        head.txn_accumulator_root() == block_info.txn_accumulator_info.accumulator_root,
+        "The transaction accumulator root of head {} does not match its block info",
+        head.id()
+    );
+    let block_accumulator = MerkleAccumulator::new_with_info(
+        block_info.block_accumulator_info.clone(),
+        storage.get_accumulator_store(AccumulatorStoreType::Block),
+    );
+    ensure!(
+        block_accumulator.get_leaf(0)? == Some(genesis_hash),
+        "The genesis is not the first leaf of the block accumulator {}",
+        block_accumulator.root_hash()
+    );
+    ensure!(
+        block_accumulator.get_leaf(head.number())? == Some(head.id()),
+        "The head {} is not in the block accumulator {}",
+        head.id(),
+        block_accumulator.root_hash()
+    );
+    let txn_accumulator = MerkleAccumulator::new_with_info(
+        block_info.txn_accumulator_info.clone(),
+        storage.get_accumulator_store(AccumulatorStoreType::Transaction),
+    );
+    if let Some(last_leaf) = txn_accumulator.num_leaves().checked_sub(1) {
+        ensure!(
+            txn_accumulator.get_leaf(last_leaf)?.is_some(),
+            "The transaction accumulator {} is incomplete",
+            txn_accumulator.root_hash()
+        );
+    }
+    Ok(())
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::{CodecWriteBatch, KeyCodec, ValueCodec, WriteOp};
+use crate::storage::{CodecWriteBatch, ColumnFamilyName, KeyCodec, ValueCodec, WriteOp};
 use anyhow::Result;
 use std::convert::TryFrom;
 
@@ -54,3 +54,30 @@ where
         Ok(WriteBatch::new_with_rows(rows?))
     }
 }
+
+/// Batches of several column families, the db storage writes them atomically.
+#[derive(Debug, Default, Clone)]
+pub struct WriteBatchWithColumn {
+    pub data: Vec<(ColumnFamilyName, WriteBatch)>,
+}
+
+impl WriteBatchWithColumn {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the batch of the column family `column`.
+    pub fn put_batch<K, V>(
+        &mut self,
+        column: ColumnFamilyName,
+        batch: CodecWriteBatch<K, V>,
+    ) -> Result<()>
+    where
+        K: KeyCodec,
+        V: ValueCodec,
+    {
+        self.data.push((column, WriteBatch::try_from(batch)?));
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::batch::WriteBatch;
+use crate::batch::{WriteBatch, WriteBatchWithColumn};
 use crate::metrics::{record_metrics, StorageMetrics};
 use crate::storage::{InnerStore, WriteOp};
 use anyhow::{Error, Result};
@@ -118,6 +118,13 @@ impl InnerStore for CacheStorage {
         }
         Ok(result)
     }
+
+    fn write_batch_with_column(&self, batch: WriteBatchWithColumn) -> Result<()> {
+        for (prefix_name, batch) in batch.data {
+            self.write_batch(prefix_name, batch)?;
+        }
+        Ok(())
+    }
 }
 
 fn compose_key(prefix_name: String, source_key: Vec<u8>) -> Vec<u8> {
@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::batch::WriteBatch;
+use crate::batch::{WriteBatch, WriteBatchWithColumn};
 use crate::errors::StorageInitError;
 use crate::metrics::{record_metrics, StorageMetrics};
 use crate::storage::{ColumnFamilyName, InnerStore, KeyCodec, ValueCodec, WriteOp};
@@ -183,11 +183,24 @@ impl DBStorage {
         Ok(rocksdb::DB::list_cf(&rocksdb::Options::default(), path)?)
     }
 
-    fn db_exists(path: &Path) -> bool {
+    pub(crate) fn db_exists(path: &Path) -> bool {
         let rocksdb_current_file = path.join("CURRENT");
         rocksdb_current_file.is_file()
     }
 
+    /// Path of the db dir.
+    pub fn path(&self) -> &Path {
+        self.db.path()
+    }
+
+    /// Create a consistent checkpoint of the db in `path`, which must not exist.
+    /// The SST files are hard linked if `path` is on the same file system as the db.
+    pub fn create_checkpoint(&self, path: impl AsRef<Path>) -> Result<()> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.db)?;
+        checkpoint.create_checkpoint(path)?;
+        Ok(())
+    }
+
     fn get_cf_handle(&self, cf_name: &str) -> Result<&rocksdb::ColumnFamily> {
         self.db.cf_handle(cf_name).ok_or_else(|| {
             format_err!(
@@ -459,4 +472,23 @@ impl InnerStore for DBStorage {
             Ok(res)
         })
     }
+
+    /// Writes the batches of several column families in one rocksdb WriteBatch.
+    fn write_batch_with_column(&self, batch: WriteBatchWithColumn) -> Result<()> {
+        let mut db_batch = DBWriteBatch::default();
+        for (prefix_name, batch) in &batch.data {
+            let cf_handle = self.get_cf_handle(prefix_name)?;
+            for (key, write_op) in &batch.rows {
+                match write_op {
+                    WriteOp::Value(value) => db_batch.put_cf(cf_handle, key, value),
+                    WriteOp::Deletion => db_batch.delete_cf(cf_handle, key),
+                };
+            }
+        }
+        record_metrics("db", "multi_column", "write_batch", self.metrics.as_ref()).call(|| {
+            self.db
+                .write_opt(db_batch, &Self::default_write_options())?;
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,161 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::{CodecKVStore, CodecWriteBatch, KeyCodec, ValueCodec};
+use crate::{define_storage, EVENT_BLOOM_PREFIX_NAME, EVENT_INDEX_PREFIX_NAME};
+use anyhow::{ensure, Result};
+use bcs_ext::BCSCodec;
+use byteorder::{BigEndian, ReadBytesExt};
+use serde::{Deserialize, Serialize};
+use starcoin_crypto::HashValue;
+use starcoin_types::block::BlockNumber;
+use starcoin_types::contract_event::ContractEvent;
+use starcoin_types::event_bloom::{EventBloom, EventTopic};
+use std::collections::BTreeMap;
+
+/// The bloom of all events in a block.
+/// `indexed` is true if the event index of the block has been written too.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BlockEventBloom {
+    pub bloom: EventBloom,
+    pub indexed: bool,
+}
+
+/// Key of the event index, a topic at a block number.
+/// Blocks of different branches share the same key, so the value holds txn info ids of all of them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct EventIndexKey {
+    pub topic: EventTopic,
+    pub block_number: BlockNumber,
+}
+
+impl EventIndexKey {
+    pub fn new(topic: EventTopic, block_number: BlockNumber) -> Self {
+        Self {
+            topic,
+            block_number,
+        }
+    }
+}
+
+define_storage!(
+    BlockEventBloomStorage,
+    HashValue,
+    BlockEventBloom,
+    EVENT_BLOOM_PREFIX_NAME
+);
+
+define_storage!(
+    EventIndexStorage,
+    EventIndexKey,
+    Vec<HashValue>,
+    EVENT_INDEX_PREFIX_NAME
+);
+
+impl KeyCodec for EventIndexKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded_key = Vec::with_capacity(HashValue::LENGTH + 8);
+        encoded_key.extend_from_slice(self.topic.hash().as_ref());
+        encoded_key.extend_from_slice(&self.block_number.to_be_bytes());
+        Ok(encoded_key)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == HashValue::LENGTH + 8,
+            "invalid event index key length: {}",
+            data.len()
+        );
+        let topic = EventTopic::from_hash(HashValue::from_slice(&data[..HashValue::LENGTH])?);
+        let block_number = (&data[HashValue::LENGTH..]).read_u64::<BigEndian>()?;
+        Ok(Self::new(topic, block_number))
+    }
+}
+
+impl ValueCodec for BlockEventBloom {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        self.encode()
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Self::decode(data)
+    }
+}
+
+pub trait EventIndexStore {
+    /// Save the event bloom of the block, and the event index if it is enabled.
+    /// `txn_info_ids` and `txn_events` must have the same length.
+    fn save_block_event_index(
+        &self,
+        block_id: HashValue,
+        block_number: BlockNumber,
+        txn_info_ids: &[HashValue],
+        txn_events: &[Vec<ContractEvent>],
+    ) -> Result<()>;
+
+    /// Get the event bloom of block `block_id`.
+    /// Return None if the block is not indexed, for example it was executed before the bloom was introduced.
+    fn get_block_event_bloom(&self, block_id: HashValue) -> Result<Option<BlockEventBloom>>;
+
+    /// Get txn info ids which have events with the `topic` at `block_number`, of all branches.
+    fn get_event_index(
+        &self,
+        topic: EventTopic,
+        block_number: BlockNumber,
+    ) -> Result<Option<Vec<HashValue>>>;
+}
+
+impl BlockEventBloomStorage {
+    pub fn save_bloom(&self, block_id: HashValue, bloom: BlockEventBloom) -> Result<()> {
+        self.put(block_id, bloom)
+    }
+}
+
+impl EventIndexStorage {
+    /// Merge the txn info ids of a block into the index.
+    pub fn save_index(
+        &self,
+        block_number: BlockNumber,
+        topics: BTreeMap<EventTopic, Vec<HashValue>>,
+    ) -> Result<()> {
+        let mut batch = CodecWriteBatch::new();
+        for (topic, txn_info_ids) in topics {
+            let key = EventIndexKey::new(topic, block_number);
+            let mut ids = self.get(key)?.unwrap_or_default();
+            for id in txn_info_ids {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            batch.put(key, ids)?;
+        }
+        self.write_batch(batch)
+    }
+}
+
+/// Build the bloom and the topic index of a block from the events of its transactions.
+pub fn build_block_event_index(
+    txn_info_ids: &[HashValue],
+    txn_events: &[Vec<ContractEvent>],
+) -> Result<(EventBloom, BTreeMap<EventTopic, Vec<HashValue>>)> {
+    ensure!(
+        txn_info_ids.len() == txn_events.len(),
+        "txn info ids' length {} should be equal to txn events' length {}",
+        txn_info_ids.len(),
+        txn_events.len()
+    );
+    let mut bloom = EventBloom::default();
+    let mut topics: BTreeMap<EventTopic, Vec<HashValue>> = BTreeMap::new();
+    for (txn_info_id, events) in txn_info_ids.iter().zip(txn_events.iter()) {
+        for event in events {
+            for topic in EventTopic::topics_of(event) {
+                bloom.accrue(&topic);
+                let ids = topics.entry(topic).or_default();
+                if !ids.contains(txn_info_id) {
+                    ids.push(*txn_info_id);
+                }
+            }
+        }
+    }
+    Ok((bloom, topics))
+}
@@ -8,7 +8,15 @@ use crate::block::BlockStorage;
 use crate::block_info::{BlockInfoStorage, BlockInfoStore};
 use crate::chain_info::ChainInfoStorage;
 use crate::contract_event::ContractEventStorage;
-use crate::state_node::StateStorage;
+use crate::db_storage::DBStorage;
+use crate::event_index::{
+    build_block_event_index, BlockEventBloom, BlockEventBloomStorage, EventIndexKey,
+    EventIndexStorage, EventIndexStore,
+};
+use crate::state_node::{
+    StaleNodeIndexKey, StateNodePruneStore, StateNodeRefCount, StateNodeRefCountStorage,
+    StateNodeStaleIndexStorage, StateStorage,
+};
 use crate::storage::{CodecKVStore, CodecWriteBatch, ColumnFamilyName, StorageInstance};
 use crate::table_info::{TableInfoStorage, TableInfoStore};
 use crate::transaction::TransactionStorage;
@@ -17,15 +25,17 @@ use anyhow::{bail, format_err, Error, Result};
 use network_p2p_types::peer_id::PeerId;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use starcoin_accumulator::node::AccumulatorStoreType;
-use starcoin_accumulator::AccumulatorTreeStore;
+use starcoin_accumulator::{Accumulator, AccumulatorTreeStore, MerkleAccumulator};
 use starcoin_crypto::HashValue;
 use starcoin_state_store_api::{StateNode, StateNodeStore};
 use starcoin_types::contract_event::ContractEvent;
+use starcoin_types::event_bloom::EventTopic;
 use starcoin_types::startup_info::{ChainInfo, ChainStatus, SnapshotRange};
 use starcoin_types::transaction::{RichTransactionInfo, Transaction};
 use starcoin_types::{
-    block::{Block, BlockBody, BlockHeader, BlockInfo},
+    block::{Block, BlockBody, BlockHeader, BlockInfo, BlockNumber},
     startup_info::StartupInfo,
 };
 //use starcoin_vm_types::state_store::table::{TableHandle, TableInfo};
@@ -38,6 +48,7 @@ pub use upgrade::BARNARD_HARD_FORK_HASH;
 pub use upgrade::BARNARD_HARD_FORK_HEIGHT;
 
 pub mod accumulator;
+pub mod backup;
 pub mod batch;
 pub mod block;
 pub mod block_info;
@@ -46,6 +57,7 @@ pub mod chain_info;
 pub mod contract_event;
 pub mod db_storage;
 pub mod errors;
+pub mod event_index;
 pub mod metrics;
 pub mod state_node;
 pub mod storage;
@@ -78,6 +90,10 @@ pub const TRANSACTION_INFO_HASH_PREFIX_NAME: ColumnFamilyName = "transaction_inf
 pub const CONTRACT_EVENT_PREFIX_NAME: ColumnFamilyName = "contract_event";
 pub const FAILED_BLOCK_PREFIX_NAME: ColumnFamilyName = "failed_block";
 pub const TABLE_INFO_PREFIX_NAME: ColumnFamilyName = "table_info";
+pub const EVENT_BLOOM_PREFIX_NAME: ColumnFamilyName = "event_bloom";
+pub const EVENT_INDEX_PREFIX_NAME: ColumnFamilyName = "event_index";
+pub const STATE_NODE_STALE_INDEX_PREFIX_NAME: ColumnFamilyName = "state_node_stale_index";
+pub const STATE_NODE_REF_COUNT_PREFIX_NAME: ColumnFamilyName = "state_node_ref_count";
 
 ///db storage use prefix_name vec to init
 /// Please note that adding a prefix needs to be added in vec simultaneously, remember！！
@@ -143,17 +159,69 @@ static VEC_PREFIX_NAME_V3: Lazy<Vec<ColumnFamilyName>> = Lazy::new(|| {
         TABLE_INFO_PREFIX_NAME,
     ]
 });
+
+static VEC_PREFIX_NAME_V4: Lazy<Vec<ColumnFamilyName>> = Lazy::new(|| {
+    vec![
+        BLOCK_ACCUMULATOR_NODE_PREFIX_NAME,
+        TRANSACTION_ACCUMULATOR_NODE_PREFIX_NAME,
+        BLOCK_PREFIX_NAME,
+        BLOCK_HEADER_PREFIX_NAME,
+        BLOCK_BODY_PREFIX_NAME, // unused column
+        BLOCK_INFO_PREFIX_NAME,
+        BLOCK_TRANSACTIONS_PREFIX_NAME,
+        BLOCK_TRANSACTION_INFOS_PREFIX_NAME,
+        STATE_NODE_PREFIX_NAME,
+        CHAIN_INFO_PREFIX_NAME,
+        TRANSACTION_PREFIX_NAME,
+        TRANSACTION_INFO_PREFIX_NAME, // unused column
+        TRANSACTION_INFO_PREFIX_NAME_V2,
+        TRANSACTION_INFO_HASH_PREFIX_NAME,
+        CONTRACT_EVENT_PREFIX_NAME,
+        FAILED_BLOCK_PREFIX_NAME,
+        TABLE_INFO_PREFIX_NAME,
+        EVENT_BLOOM_PREFIX_NAME,
+        EVENT_INDEX_PREFIX_NAME,
+    ]
+});
+
+static VEC_PREFIX_NAME_V5: Lazy<Vec<ColumnFamilyName>> = Lazy::new(|| {
+    vec![
+        BLOCK_ACCUMULATOR_NODE_PREFIX_NAME,
+        TRANSACTION_ACCUMULATOR_NODE_PREFIX_NAME,
+        BLOCK_PREFIX_NAME,
+        BLOCK_HEADER_PREFIX_NAME,
+        BLOCK_BODY_PREFIX_NAME, // unused column
+        BLOCK_INFO_PREFIX_NAME,
+        BLOCK_TRANSACTIONS_PREFIX_NAME,
+        BLOCK_TRANSACTION_INFOS_PREFIX_NAME,
+        STATE_NODE_PREFIX_NAME,
+        CHAIN_INFO_PREFIX_NAME,
+        TRANSACTION_PREFIX_NAME,
+        TRANSACTION_INFO_PREFIX_NAME, // unused column
+        TRANSACTION_INFO_PREFIX_NAME_V2,
+        TRANSACTION_INFO_HASH_PREFIX_NAME,
+        CONTRACT_EVENT_PREFIX_NAME,
+        FAILED_BLOCK_PREFIX_NAME,
+        TABLE_INFO_PREFIX_NAME,
+        EVENT_BLOOM_PREFIX_NAME,
+        EVENT_INDEX_PREFIX_NAME,
+        STATE_NODE_STALE_INDEX_PREFIX_NAME,
+        STATE_NODE_REF_COUNT_PREFIX_NAME,
+    ]
+});
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, IntoPrimitive, TryFromPrimitive)]
 #[repr(u8)]
 pub enum StorageVersion {
     V1 = 1,
     V2 = 2,
     V3 = 3,
+    V4 = 4,
+    V5 = 5,
 }
 
 impl StorageVersion {
     pub fn current_version() -> StorageVersion {
-        StorageVersion::V3
+        StorageVersion::V5
     }
 
     pub fn get_column_family_names(&self) -> &'static [ColumnFamilyName] {
@@ -161,6 +229,8 @@ impl StorageVersion {
             StorageVersion::V1 => &VEC_PREFIX_NAME_V1,
             StorageVersion::V2 => &VEC_PREFIX_NAME_V2,
             StorageVersion::V3 => &VEC_PREFIX_NAME_V3,
+            StorageVersion::V4 => &VEC_PREFIX_NAME_V4,
+            StorageVersion::V5 => &VEC_PREFIX_NAME_V5,
         }
     }
 }
@@ -277,7 +347,15 @@ pub struct Storage {
     event_storage: ContractEventStorage,
     chain_info_storage: ChainInfoStorage,
     table_info_storage: TableInfoStorage,
-    // instance: StorageInstance,
+    event_bloom_storage: BlockEventBloomStorage,
+    event_index_storage: EventIndexStorage,
+    event_index_enabled: bool,
+    state_node_stale_index_storage: StateNodeStaleIndexStorage,
+    state_node_ref_count_storage: StateNodeRefCountStorage,
+    state_prune_enabled: bool,
+    /// serialize writing state nodes and pruning them.
+    state_prune_lock: Arc<Mutex<()>>,
+    instance: StorageInstance,
 }
 
 impl Storage {
@@ -296,12 +374,37 @@ impl Storage {
             block_info_storage: BlockInfoStorage::new(instance.clone()),
             event_storage: ContractEventStorage::new(instance.clone()),
             chain_info_storage: ChainInfoStorage::new(instance.clone()),
-            table_info_storage: TableInfoStorage::new(instance),
-            // instance,
+            table_info_storage: TableInfoStorage::new(instance.clone()),
+            event_bloom_storage: BlockEventBloomStorage::new(instance.clone()),
+            event_index_storage: EventIndexStorage::new(instance.clone()),
+            event_index_enabled: false,
+            state_node_stale_index_storage: StateNodeStaleIndexStorage::new(instance.clone()),
+            state_node_ref_count_storage: StateNodeRefCountStorage::new(instance.clone()),
+            state_prune_enabled: false,
+            state_prune_lock: Arc::new(Mutex::new(())),
+            instance,
         };
         Ok(storage)
     }
 
+    /// Enable or disable the secondary event index, the event bloom is always saved.
+    pub fn enable_event_index(mut self, enabled: bool) -> Self {
+        self.event_index_enabled = enabled;
+        self
+    }
+
+    /// Enable or disable recording the stale state nodes and reference count of state nodes,
+    /// which are required by `StateNodePruner`.
+    pub fn enable_state_prune(mut self, enabled: bool) -> Self {
+        self.state_prune_enabled = enabled;
+        self
+    }
+
+    /// The rocksdb of the storage, None for the cache only storage.
+    pub fn db(&self) -> Option<&DBStorage> {
+        self.instance.db()
+    }
+
     pub fn get_block_accumulator_storage(&self) -> AccumulatorStorage<BlockAccumulatorStorage> {
         self.block_accumulator_storage.clone()
     }
@@ -319,12 +422,37 @@ impl StateNodeStore for Storage {
     }
 
     fn put(&self, key: HashValue, node: StateNode) -> Result<()> {
+        if self.state_prune_enabled {
+            return self.write_nodes(BTreeMap::from([(key, node)]));
+        }
         self.state_node_storage.put(key, node)
     }
 
     fn write_nodes(&self, nodes: BTreeMap<HashValue, StateNode>) -> Result<()> {
+        if !self.state_prune_enabled {
+            let batch = CodecWriteBatch::new_puts(nodes.into_iter().collect());
+            return self.state_node_storage.write_batch(batch);
+        }
+        let _guard = self.state_prune_lock.lock();
+        let keys: Vec<HashValue> = nodes.keys().cloned().collect();
+        let ref_counts = self
+            .state_node_ref_count_storage
+            .multiple_get(keys.clone())?;
+        let mut ref_count_batch = CodecWriteBatch::new();
+        for (key, ref_count) in keys.into_iter().zip(ref_counts) {
+            let ref_count = match ref_count {
+                Some(ref_count) => ref_count.increase(),
+                // the node is written before pruning is enabled, its references are unknown.
+                None if self.state_node_storage.contains_key(key)? => StateNodeRefCount::Pinned,
+                None => StateNodeRefCount::Count(1),
+            };
+            ref_count_batch.put(key, ref_count)?;
+        }
+        // write nodes first, if the process crashes before writing counts, the nodes become pinned.
         let batch = CodecWriteBatch::new_puts(nodes.into_iter().collect());
-        self.state_node_storage.write_batch(batch)
+        self.state_node_storage.write_batch(batch)?;
+        self.state_node_ref_count_storage
+            .write_batch(ref_count_batch)
     }
 
     fn get_table_info(&self, address: AccountAddress) -> Result<Option<TableInfo>> {
@@ -550,6 +678,61 @@ impl ContractEventStore for Storage {
     }
 }
 
+impl EventIndexStore for Storage {
+    fn save_block_event_index(
+        &self,
+        block_id: HashValue,
+        block_number: BlockNumber,
+        txn_info_ids: &[HashValue],
+        txn_events: &[Vec<ContractEvent>],
+    ) -> Result<(), Error> {
+        let (bloom, topics) = build_block_event_index(txn_info_ids, txn_events)?;
+        if self.event_index_enabled {
+            self.event_index_storage.save_index(block_number, topics)?;
+        }
+        self.event_bloom_storage.save_bloom(
+            block_id,
+            BlockEventBloom {
+                bloom,
+                indexed: self.event_index_enabled,
+            },
+        )
+    }
+
+    fn get_block_event_bloom(&self, block_id: HashValue) -> Result<Option<BlockEventBloom>> {
+        self.event_bloom_storage.get(block_id)
+    }
+
+    fn get_event_index(
+        &self,
+        topic: EventTopic,
+        block_number: BlockNumber,
+    ) -> Result<Option<Vec<HashValue>>> {
+        self.event_index_storage
+            .get(EventIndexKey::new(topic, block_number))
+    }
+}
+
+impl StateNodePruneStore for Storage {
+    fn is_state_prune_enabled(&self) -> bool {
+        self.state_prune_enabled
+    }
+
+    fn save_stale_state_nodes(
+        &self,
+        block_id: HashValue,
+        block_number: BlockNumber,
+        stale_nodes: Vec<HashValue>,
+    ) -> Result<()> {
+        if !self.state_prune_enabled {
+            return Ok(());
+        }
+        // save the index even if there is no stale node, the pruner finds forks by the index.
+        self.state_node_stale_index_storage
+            .put(StaleNodeIndexKey::new(block_number, block_id), stale_nodes)
+    }
+}
+
 impl TransactionStore for Storage {
     fn get_transaction(&self, txn_hash: HashValue) -> Result<Option<Transaction>, Error> {
         self.transaction_storage.get(txn_hash)
@@ -579,6 +762,8 @@ pub trait Store:
     + TransactionStore
     + BlockTransactionInfoStore
     + ContractEventStore
+    + EventIndexStore
+    + StateNodePruneStore
     + IntoSuper<dyn StateNodeStore>
     + TableInfoStore
 {
@@ -615,6 +800,22 @@ pub trait Store:
         Ok(txn_infos)
     }
 
+    /// Get the id of the block at `number` of the main chain, the main chain head is read from the startup info.
+    fn get_main_block_id_by_number(&self, number: BlockNumber) -> Result<Option<HashValue>> {
+        let head_block_id = match self.get_startup_info()? {
+            Some(startup_info) => startup_info.main,
+            None => return Ok(None),
+        };
+        let head_block_info = self
+            .get_block_info(head_block_id)?
+            .ok_or_else(|| format_err!("Can not find block info of main head {}", head_block_id))?;
+        let accumulator = MerkleAccumulator::new_with_info(
+            head_block_info.block_accumulator_info,
+            self.get_accumulator_store(AccumulatorStoreType::Block),
+        );
+        accumulator.get_leaf(number)
+    }
+
     fn get_accumulator_store(
         &self,
         accumulator_type: AccumulatorStoreType,
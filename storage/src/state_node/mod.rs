@@ -2,14 +2,36 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::define_storage;
-use crate::storage::ValueCodec;
-use crate::STATE_NODE_PREFIX_NAME;
-use anyhow::Result;
+use crate::storage::{KeyCodec, ValueCodec};
+use crate::{
+    STATE_NODE_PREFIX_NAME, STATE_NODE_REF_COUNT_PREFIX_NAME, STATE_NODE_STALE_INDEX_PREFIX_NAME,
+};
+use anyhow::{ensure, Result};
+use byteorder::{BigEndian, ReadBytesExt};
 use starcoin_crypto::HashValue;
 use starcoin_state_store_api::StateNode;
+use starcoin_types::block::BlockNumber;
+
+mod pruner;
+
+pub use pruner::StateNodePruner;
 
 define_storage!(StateStorage, HashValue, StateNode, STATE_NODE_PREFIX_NAME);
 
+define_storage!(
+    StateNodeStaleIndexStorage,
+    StaleNodeIndexKey,
+    Vec<HashValue>,
+    STATE_NODE_STALE_INDEX_PREFIX_NAME
+);
+
+define_storage!(
+    StateNodeRefCountStorage,
+    HashValue,
+    StateNodeRefCount,
+    STATE_NODE_REF_COUNT_PREFIX_NAME
+);
+
 impl ValueCodec for StateNode {
     fn encode_value(&self) -> Result<Vec<u8>> {
         Ok(self.0.clone())
@@ -18,3 +40,94 @@ impl ValueCodec for StateNode {
         Ok(StateNode(data.to_vec()))
     }
 }
+
+/// Key of the stale node index, the stale nodes of block `block_id` at `block_number`.
+/// Keys are ordered by block number first, so the pruner can scan from the lowest block.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct StaleNodeIndexKey {
+    pub block_number: BlockNumber,
+    pub block_id: HashValue,
+}
+
+impl StaleNodeIndexKey {
+    pub fn new(block_number: BlockNumber, block_id: HashValue) -> Self {
+        Self {
+            block_number,
+            block_id,
+        }
+    }
+}
+
+impl KeyCodec for StaleNodeIndexKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded_key = Vec::with_capacity(8 + HashValue::LENGTH);
+        encoded_key.extend_from_slice(&self.block_number.to_be_bytes());
+        encoded_key.extend_from_slice(self.block_id.as_ref());
+        Ok(encoded_key)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == 8 + HashValue::LENGTH,
+            "invalid stale node index key length: {}",
+            data.len()
+        );
+        let block_number = (&data[..8]).read_u64::<BigEndian>()?;
+        let block_id = HashValue::from_slice(&data[8..])?;
+        Ok(Self::new(block_number, block_id))
+    }
+}
+
+/// How many trees reference a state node, only maintained when state pruning is enabled.
+/// A node is content addressed, so it can be shared by different trees and different versions.
+/// Nodes written before pruning was enabled have no count, they are `Pinned` once written again,
+/// and are never pruned.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StateNodeRefCount {
+    Count(u64),
+    Pinned,
+}
+
+impl StateNodeRefCount {
+    const PINNED: u64 = u64::MAX;
+
+    pub fn increase(self) -> Self {
+        match self {
+            Self::Count(count) if count < Self::PINNED - 1 => Self::Count(count + 1),
+            _ => Self::Pinned,
+        }
+    }
+}
+
+impl ValueCodec for StateNodeRefCount {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        let count = match self {
+            Self::Count(count) => *count,
+            Self::Pinned => Self::PINNED,
+        };
+        Ok(count.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(mut data: &[u8]) -> Result<Self> {
+        let count = data.read_u64::<BigEndian>()?;
+        Ok(if count == Self::PINNED {
+            Self::Pinned
+        } else {
+            Self::Count(count)
+        })
+    }
+}
+
+pub trait StateNodePruneStore {
+    /// Whether the stale state nodes and reference count of state nodes are recorded.
+    fn is_state_prune_enabled(&self) -> bool;
+
+    /// Save the state nodes which are not referenced by the state of block `block_id` any more,
+    /// compared with its parent. Do nothing if state pruning is disabled.
+    fn save_stale_state_nodes(
+        &self,
+        block_id: HashValue,
+        block_number: BlockNumber,
+        stale_nodes: Vec<HashValue>,
+    ) -> Result<()>;
+}
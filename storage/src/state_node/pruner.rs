@@ -0,0 +1,187 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state_node::{StaleNodeIndexKey, StateNodeRefCount};
+use crate::storage::{CodecKVStore, CodecWriteBatch, InnerStore, KeyCodec, WriteBatchWithColumn};
+use crate::{
+    BlockStore, Storage, Store, STATE_NODE_PREFIX_NAME, STATE_NODE_REF_COUNT_PREFIX_NAME,
+    STATE_NODE_STALE_INDEX_PREFIX_NAME,
+};
+use anyhow::Result;
+use starcoin_accumulator::node::AccumulatorStoreType;
+use starcoin_accumulator::{Accumulator, MerkleAccumulator};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::{debug, warn};
+use starcoin_state_store_api::StateNode;
+use starcoin_types::block::{BlockHeader, BlockInfo, BlockNumber};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Delete state nodes which are not referenced by the state of the last `keep_blocks` blocks.
+///
+/// The stale node index of main chain blocks below the prune target is used to decrease
+/// the reference count of the nodes, and a node is deleted when its count reaches zero.
+/// The prune target is lowered to the fork point of any side branch (uncles included) above it,
+/// so the state of blocks which may be switched to by a reorg is kept.
+/// The stale node index of side branch blocks below the target is dropped, their nodes are leaked.
+pub struct StateNodePruner {
+    storage: Arc<Storage>,
+    keep_blocks: u64,
+}
+
+impl StateNodePruner {
+    pub fn new(storage: Arc<Storage>, keep_blocks: u64) -> Self {
+        Self {
+            storage,
+            keep_blocks,
+        }
+    }
+
+    /// Prune the stale nodes of at most `max_blocks` blocks with the chain `head`,
+    /// return the number of pruned blocks and deleted nodes.
+    pub fn prune(
+        &self,
+        head: &BlockHeader,
+        head_block_info: &BlockInfo,
+        max_blocks: usize,
+    ) -> Result<(usize, usize)> {
+        let head_number = head.number();
+        if head_number <= self.keep_blocks {
+            return Ok((0, 0));
+        }
+        let accumulator = MerkleAccumulator::new_with_info(
+            head_block_info.block_accumulator_info.clone(),
+            self.storage
+                .get_accumulator_store(AccumulatorStoreType::Block),
+        );
+        let is_main = |number: BlockNumber, block_id: HashValue| -> Result<bool> {
+            if number > head_number {
+                return Ok(false);
+            }
+            Ok(accumulator.get_leaf(number)? == Some(block_id))
+        };
+
+        let mut target = head_number - self.keep_blocks;
+        let mut fork_numbers = HashMap::new();
+        let mut iter = self.storage.state_node_stale_index_storage.iter()?;
+        iter.seek(StaleNodeIndexKey::new(target + 1, HashValue::zero()).encode_key()?)?;
+        for item in iter {
+            let (key, _) = item?;
+            if is_main(key.block_number, key.block_id)? {
+                continue;
+            }
+            if let Some(fork_number) =
+                self.fork_number(key.block_id, &is_main, &mut fork_numbers)?
+            {
+                target = target.min(fork_number);
+            }
+        }
+
+        let mut pruned_blocks = 0;
+        let mut stale_index_batch = CodecWriteBatch::new();
+        let mut releases: BTreeMap<HashValue, u64> = BTreeMap::new();
+        let mut iter = self.storage.state_node_stale_index_storage.iter()?;
+        iter.seek_to_first();
+        for item in iter {
+            let (key, stale_nodes) = item?;
+            if key.block_number > target || pruned_blocks >= max_blocks {
+                break;
+            }
+            if is_main(key.block_number, key.block_id)? {
+                for node in stale_nodes {
+                    *releases.entry(node).or_default() += 1;
+                }
+            }
+            stale_index_batch.delete(key)?;
+            pruned_blocks += 1;
+        }
+        if pruned_blocks == 0 {
+            return Ok((0, 0));
+        }
+        let pruned_nodes = self.release_nodes(stale_index_batch, releases)?;
+        debug!(
+            "Prune state nodes of {} blocks, delete {} nodes, prune target: {}",
+            pruned_blocks, pruned_nodes, target
+        );
+        Ok((pruned_blocks, pruned_nodes))
+    }
+
+    /// Find the number of the main chain block where the branch of `block_id` forks.
+    /// Return None if the block can not be found, for example it has been deleted.
+    fn fork_number(
+        &self,
+        block_id: HashValue,
+        is_main: &dyn Fn(BlockNumber, HashValue) -> Result<bool>,
+        fork_numbers: &mut HashMap<HashValue, Option<BlockNumber>>,
+    ) -> Result<Option<BlockNumber>> {
+        let mut visited = vec![];
+        let mut current = block_id;
+        let fork_number = loop {
+            if let Some(fork_number) = fork_numbers.get(&current) {
+                break *fork_number;
+            }
+            visited.push(current);
+            match self.storage.get_block_header_by_hash(current)? {
+                Some(header) => {
+                    if header.number() == 0 || is_main(header.number(), header.id())? {
+                        break Some(header.number());
+                    }
+                    current = header.parent_hash();
+                }
+                None => {
+                    warn!(
+                        "Can not find block header {} when prune state nodes, ignore its branch.",
+                        current
+                    );
+                    break None;
+                }
+            }
+        };
+        for id in visited {
+            fork_numbers.insert(id, fork_number);
+        }
+        Ok(fork_number)
+    }
+
+    /// Decrease the reference count of the nodes by `releases`, delete the nodes which are not
+    /// referenced, and write them with the stale index deletions in one batch.
+    fn release_nodes(
+        &self,
+        stale_index_batch: CodecWriteBatch<StaleNodeIndexKey, Vec<HashValue>>,
+        releases: BTreeMap<HashValue, u64>,
+    ) -> Result<usize> {
+        // hold the lock, so no node can be written again between updating count and deleting it.
+        let _guard = self.storage.state_prune_lock.lock();
+        let keys: Vec<HashValue> = releases.keys().cloned().collect();
+        let ref_counts = self
+            .storage
+            .state_node_ref_count_storage
+            .multiple_get(keys.clone())?;
+        let mut ref_count_batch = CodecWriteBatch::new();
+        let mut deleted_nodes = vec![];
+        for (key, ref_count) in keys.into_iter().zip(ref_counts) {
+            let release = releases.get(&key).cloned().unwrap_or_default();
+            match ref_count {
+                Some(StateNodeRefCount::Count(count)) if count > release => {
+                    ref_count_batch.put(key, StateNodeRefCount::Count(count - release))?;
+                }
+                Some(StateNodeRefCount::Count(_)) => {
+                    ref_count_batch.delete(key)?;
+                    deleted_nodes.push(key);
+                }
+                // nodes written before pruning was enabled.
+                Some(StateNodeRefCount::Pinned) | None => {}
+            }
+        }
+        let deleted = deleted_nodes.len();
+        let mut batch = WriteBatchWithColumn::new();
+        batch.put_batch(STATE_NODE_STALE_INDEX_PREFIX_NAME, stale_index_batch)?;
+        batch.put_batch(STATE_NODE_REF_COUNT_PREFIX_NAME, ref_count_batch)?;
+        batch.put_batch(
+            STATE_NODE_PREFIX_NAME,
+            CodecWriteBatch::<HashValue, StateNode>::new_deletes(deleted_nodes),
+        )?;
+        self.storage.instance.write_batch_with_column(batch)?;
+        Ok(deleted)
+    }
+}
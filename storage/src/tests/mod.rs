@@ -1,6 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 mod test_accumulator;
+mod test_backup;
 mod test_batch;
 mod test_block;
 mod test_storage;
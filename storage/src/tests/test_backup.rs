@@ -0,0 +1,174 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::backup::{create_backup, list_backups, resolve_backup_dir, restore_backup};
+use crate::block_info::BlockInfoStore;
+use crate::db_storage::DBStorage;
+use crate::storage::StorageInstance;
+use crate::{BlockStore, Storage, StorageVersion, Store};
+use anyhow::Result;
+use starcoin_accumulator::node::AccumulatorStoreType;
+use starcoin_accumulator::{Accumulator, MerkleAccumulator};
+use starcoin_config::RocksdbConfig;
+use starcoin_crypto::HashValue;
+use starcoin_types::block::{Block, BlockBody, BlockHeaderBuilder, BlockInfo};
+use starcoin_types::startup_info::StartupInfo;
+use std::fs;
+use std::path::Path;
+
+/// Save a genesis block with its accumulators, and make it the head of the storage.
+fn init_genesis(storage: &Storage) -> Result<HashValue> {
+    let txn_accumulator = MerkleAccumulator::new_empty(
+        storage.get_accumulator_store(AccumulatorStoreType::Transaction),
+    );
+    txn_accumulator.append(&[HashValue::random()])?;
+    txn_accumulator.flush()?;
+    let header = BlockHeaderBuilder::random()
+        .with_number(0)
+        .with_accumulator_root(txn_accumulator.root_hash())
+        .build();
+    let block = Block::new(header, BlockBody::new_empty());
+    let block_id = block.id();
+    let block_accumulator =
+        MerkleAccumulator::new_empty(storage.get_accumulator_store(AccumulatorStoreType::Block));
+    block_accumulator.append(&[block_id])?;
+    block_accumulator.flush()?;
+
+    storage.commit_block(block)?;
+    storage.save_block_info(BlockInfo::new(
+        block_id,
+        0.into(),
+        txn_accumulator.get_info(),
+        block_accumulator.get_info(),
+    ))?;
+    storage.save_genesis(block_id)?;
+    storage.save_startup_info(StartupInfo::new(block_id))?;
+    storage
+        .chain_info_storage
+        .set_storage_version(StorageVersion::current_version())?;
+    Ok(block_id)
+}
+
+#[test]
+fn test_backup_and_restore() -> Result<()> {
+    let tmpdir = starcoin_config::temp_dir();
+    let storage = Storage::new(StorageInstance::new_db_instance(DBStorage::new(
+        tmpdir.path(),
+        RocksdbConfig::default(),
+        None,
+    )?))?;
+    let genesis_hash = init_genesis(&storage)?;
+    let db = storage.db().expect("db must exist");
+    let backup_dir = tmpdir.path().join("backup");
+
+    let first = create_backup(db, backup_dir.as_path())?;
+    assert_eq!(first.backup_id, 1);
+    assert_eq!(first.head().id(), genesis_hash);
+    assert_eq!(first.copied_files, first.files.len() as u64);
+    assert!(first.files.iter().any(|file| file.shared));
+
+    // nothing is written after the first backup, so all the SST files are shared.
+    let second = create_backup(db, backup_dir.as_path())?;
+    assert_eq!(second.backup_id, 2);
+    assert_eq!(
+        second.copied_files,
+        second.files.iter().filter(|file| !file.shared).count() as u64
+    );
+    assert_eq!(list_backups(backup_dir.as_path())?, vec![1, 2]);
+
+    let db_path = tmpdir.path().join("restore").join("starcoindb");
+    assert!(restore_backup(
+        backup_dir.as_path(),
+        None,
+        db_path.as_path(),
+        HashValue::random()
+    )
+    .is_err());
+    let restored = restore_backup(backup_dir.as_path(), None, db_path.as_path(), genesis_hash)?;
+    assert_eq!(restored, second);
+    let restored_storage =
+        Storage::new(StorageInstance::new_db_instance(DBStorage::open_with_cfs(
+            db_path.as_path(),
+            StorageVersion::current_version()
+                .get_column_family_names()
+                .to_vec(),
+            true,
+            RocksdbConfig::default(),
+            None,
+        )?))?;
+    assert_eq!(
+        restored_storage.get_chain_info()?,
+        Some(second.chain_info.clone())
+    );
+    // never overwrite a db.
+    assert!(restore_backup(
+        backup_dir.as_path(),
+        Some(1),
+        db_path.as_path(),
+        genesis_hash
+    )
+    .is_err());
+    Ok(())
+}
+
+/// Flip the first byte of the file, the size of the file is not changed.
+fn corrupt_file(path: &Path) -> Result<()> {
+    let mut bytes = fs::read(path)?;
+    bytes[0] = !bytes[0];
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+#[test]
+fn test_backup_file_hash() -> Result<()> {
+    let tmpdir = starcoin_config::temp_dir();
+    let storage = Storage::new(StorageInstance::new_db_instance(DBStorage::new(
+        tmpdir.path(),
+        RocksdbConfig::default(),
+        None,
+    )?))?;
+    let genesis_hash = init_genesis(&storage)?;
+    let db = storage.db().expect("db must exist");
+    let backup_dir = tmpdir.path().join("backup");
+
+    let first = create_backup(db, backup_dir.as_path())?;
+    let shared_file = first
+        .files
+        .iter()
+        .find(|file| file.shared)
+        .expect("the backup should have SST files");
+    let shared_path = backup_dir.join("shared").join(shared_file.name.as_str());
+    corrupt_file(shared_path.as_path())?;
+
+    // the corrupted SST file is copied again instead of reused.
+    let second = create_backup(db, backup_dir.as_path())?;
+    assert_eq!(
+        second.copied_files,
+        second.files.iter().filter(|file| !file.shared).count() as u64 + 1
+    );
+    let db_path = tmpdir.path().join("restore").join("starcoindb");
+    restore_backup(backup_dir.as_path(), None, db_path.as_path(), genesis_hash)?;
+
+    // the corrupted file fails the restore.
+    corrupt_file(shared_path.as_path())?;
+    let db_path = tmpdir.path().join("restore2").join("starcoindb");
+    assert!(restore_backup(backup_dir.as_path(), None, db_path.as_path(), genesis_hash).is_err());
+    assert!(!db_path.exists());
+    Ok(())
+}
+
+#[test]
+fn test_resolve_backup_dir() {
+    let backup_root = Path::new("/data/backup");
+    assert_eq!(
+        resolve_backup_dir(backup_root, Path::new("daily")).unwrap(),
+        backup_root.join("daily")
+    );
+    assert_eq!(
+        resolve_backup_dir(backup_root, Path::new("/data/backup/daily")).unwrap(),
+        backup_root.join("daily")
+    );
+    assert!(resolve_backup_dir(backup_root, Path::new("/data/starcoindb")).is_err());
+    assert!(resolve_backup_dir(backup_root, Path::new("../starcoindb")).is_err());
+    assert!(resolve_backup_dir(backup_root, Path::new("/data/backup/../starcoindb")).is_err());
+}
@@ -5,29 +5,39 @@ extern crate chrono;
 
 use crate::cache_storage::CacheStorage;
 use crate::db_storage::DBStorage;
+use crate::event_index::EventIndexStore;
+use crate::state_node::{StateNodePruneStore, StateNodePruner};
 use crate::storage::{CodecKVStore, InnerStore, StorageInstance, ValueCodec};
 use crate::table_info::TableInfoStore;
 use crate::transaction_info::{BlockTransactionInfo, OldTransactionInfoStorage};
 use crate::{
     BlockInfoStore, BlockStore, BlockTransactionInfoStore, Storage,
     StorageVersion, /*TableInfoStore,*/
-    TransactionStore, DEFAULT_PREFIX_NAME, TRANSACTION_INFO_PREFIX_NAME,
+    Store, TransactionStore, DEFAULT_PREFIX_NAME, TRANSACTION_INFO_PREFIX_NAME,
     TRANSACTION_INFO_PREFIX_NAME_V2,
 };
 use anyhow::Result;
 use starcoin_accumulator::accumulator_info::AccumulatorInfo;
+use starcoin_accumulator::node::AccumulatorStoreType;
+use starcoin_accumulator::{Accumulator, MerkleAccumulator};
 use starcoin_config::RocksdbConfig;
 use starcoin_crypto::HashValue;
+use starcoin_state_store_api::{StateNode, StateNodeStore};
 use starcoin_types::{
     account_address::AccountAddress,
-    block::{Block, BlockBody, BlockHeader, BlockInfo},
+    block::{Block, BlockBody, BlockHeader, BlockHeaderBuilder, BlockInfo, BlockNumber},
+    contract_event::ContractEvent,
+    event::EventKey,
+    event_bloom::EventTopic,
     language_storage::TypeTag,
     startup_info::SnapshotRange,
     transaction::{RichTransactionInfo, SignedUserTransaction, Transaction, TransactionInfo},
     vm_error::KeptVMStatus,
 };
 use starcoin_vm_types::state_store::table::{TableHandle, TableInfo};
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::Arc;
 
 #[test]
 fn test_reopen() {
@@ -518,3 +528,133 @@ fn test_table_info_storage() -> Result<()> {
     assert_eq!(vals, vals2);
     Ok(())
 }
+
+#[test]
+fn test_event_index_storage() -> Result<()> {
+    let tmpdir = starcoin_config::temp_dir();
+    let instance = StorageInstance::new_cache_and_db_instance(
+        CacheStorage::new(None),
+        DBStorage::new(tmpdir.path(), RocksdbConfig::default(), None)?,
+    );
+    let storage = Storage::new(instance)?.enable_event_index(true);
+    let address = AccountAddress::random();
+    let key = EventKey::new_from_address(&address, 0);
+    let event = ContractEvent::new(key, 0, TypeTag::U64, vec![]);
+    let block_id = HashValue::random();
+    let txn_info_ids = vec![HashValue::random(), HashValue::random()];
+    storage.save_block_event_index(block_id, 1, txn_info_ids.as_slice(), &[vec![], vec![event]])?;
+    let bloom = storage.get_block_event_bloom(block_id)?.unwrap();
+    assert!(bloom.indexed);
+    assert!(bloom.bloom.contains(&EventTopic::from_event_key(&key)));
+    assert!(bloom.bloom.contains(&EventTopic::from_address(&address)));
+
+    // another branch at the same block number is merged into the index.
+    let fork_txn_info_id = HashValue::random();
+    storage.save_block_event_index(
+        HashValue::random(),
+        1,
+        &[fork_txn_info_id],
+        &[vec![ContractEvent::new(key, 0, TypeTag::U64, vec![])]],
+    )?;
+    let ids = storage
+        .get_event_index(EventTopic::from_event_key(&key), 1)?
+        .unwrap();
+    assert_eq!(ids, vec![txn_info_ids[1], fork_txn_info_id]);
+    assert!(storage
+        .get_event_index(EventTopic::from_event_key(&key), 2)?
+        .is_none());
+    assert!(storage
+        .get_block_event_bloom(HashValue::random())?
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_state_node_prune() -> Result<()> {
+    let tmpdir = starcoin_config::temp_dir();
+    let instance = StorageInstance::new_cache_and_db_instance(
+        CacheStorage::new(None),
+        DBStorage::new(tmpdir.path(), RocksdbConfig::default(), None)?,
+    );
+    let (old_key, shared_key, node_key, side_key) = (
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+        HashValue::random(),
+    );
+    let old_node = StateNode(vec![0]);
+    let shared_node = StateNode(vec![1]);
+    let node = StateNode(vec![2]);
+    let side_node = StateNode(vec![3]);
+    // a node written before pruning is enabled.
+    StateNodeStore::put(&Storage::new(instance.clone())?, old_key, old_node.clone())?;
+    let storage = Arc::new(Storage::new(instance)?.enable_state_prune(true));
+
+    // the shared node is written by two trees.
+    storage.write_nodes(BTreeMap::from([
+        (old_key, old_node),
+        (shared_key, shared_node.clone()),
+        (node_key, node.clone()),
+        (side_key, side_node),
+    ]))?;
+    storage.write_nodes(BTreeMap::from([(shared_key, shared_node)]))?;
+
+    let commit_header = |parent_hash: HashValue, number: BlockNumber| -> Result<BlockHeader> {
+        let header = BlockHeaderBuilder::random()
+            .with_parent_hash(parent_hash)
+            .with_number(number)
+            .build();
+        storage.commit_block(Block::new(header.clone(), BlockBody::new_empty()))?;
+        Ok(header)
+    };
+    let mut main = vec![commit_header(HashValue::zero(), 0)?];
+    for number in 1..8 {
+        let parent_hash = main.last().unwrap().id();
+        main.push(commit_header(parent_hash, number)?);
+    }
+    // a side branch forks at block 1.
+    let side2 = commit_header(main[1].id(), 2)?;
+    let side3 = commit_header(side2.id(), 3)?;
+
+    let accumulator = MerkleAccumulator::new_with_info(
+        AccumulatorInfo::default(),
+        storage.get_accumulator_store(AccumulatorStoreType::Block),
+    );
+    let mut block_infos = vec![];
+    for header in main.iter() {
+        accumulator.append(&[header.id()])?;
+        accumulator.flush()?;
+        block_infos.push(BlockInfo::new(
+            header.id(),
+            0.into(),
+            AccumulatorInfo::default(),
+            accumulator.get_info(),
+        ));
+    }
+
+    storage.save_stale_state_nodes(main[1].id(), 1, vec![old_key, shared_key, node_key])?;
+    storage.save_stale_state_nodes(main[2].id(), 2, vec![shared_key])?;
+    storage.save_stale_state_nodes(side2.id(), 2, vec![side_key])?;
+    storage.save_stale_state_nodes(side3.id(), 3, vec![])?;
+
+    let pruner = StateNodePruner::new(storage.clone(), 3);
+    // the prune target 5 - 3 is lowered to the fork point 1.
+    let (blocks, nodes) = pruner.prune(&main[5], &block_infos[5], 100)?;
+    assert_eq!((blocks, nodes), (1, 1));
+    assert!(StateNodeStore::get(storage.as_ref(), &node_key)?.is_none());
+    assert!(StateNodeStore::get(storage.as_ref(), &shared_key)?.is_some());
+    assert!(StateNodeStore::get(storage.as_ref(), &old_key)?.is_some());
+
+    // the side branch is out of the window now.
+    let (blocks, nodes) = pruner.prune(&main[7], &block_infos[7], 100)?;
+    assert_eq!((blocks, nodes), (3, 1));
+    assert!(StateNodeStore::get(storage.as_ref(), &shared_key)?.is_none());
+    // nodes of the side branch are leaked, and pinned nodes are never pruned.
+    assert!(StateNodeStore::get(storage.as_ref(), &side_key)?.is_some());
+    assert!(StateNodeStore::get(storage.as_ref(), &old_key)?.is_some());
+
+    // a pruned node can be written again.
+    storage.write_nodes(BTreeMap::from([(node_key, node)]))?;
+    assert!(StateNodeStore::get(storage.as_ref(), &node_key)?.is_some());
+    Ok(())
+}
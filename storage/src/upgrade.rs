@@ -9,7 +9,9 @@ use crate::transaction_info::OldTransactionInfoStorage;
 use crate::transaction_info::TransactionInfoStorage;
 use crate::{
     CodecKVStore, RichTransactionInfo, StorageInstance, StorageVersion, TransactionStore,
-    BLOCK_BODY_PREFIX_NAME, TRANSACTION_INFO_PREFIX_NAME,
+    BLOCK_BODY_PREFIX_NAME, EVENT_BLOOM_PREFIX_NAME, EVENT_INDEX_PREFIX_NAME,
+    STATE_NODE_REF_COUNT_PREFIX_NAME, STATE_NODE_STALE_INDEX_PREFIX_NAME,
+    TRANSACTION_INFO_PREFIX_NAME,
 };
 use anyhow::{bail, ensure, format_err, Result};
 use once_cell::sync::Lazy;
@@ -171,6 +173,26 @@ impl DBUpgrade {
         Ok(())
     }
 
+    fn db_upgrade_v3_v4(_instance: &mut StorageInstance) -> Result<()> {
+        // The event bloom and event index columns are created on open,
+        // blocks executed before the upgrade can be indexed by `db-exporter index-events`.
+        info!(
+            "add column {}, column {}",
+            EVENT_BLOOM_PREFIX_NAME, EVENT_INDEX_PREFIX_NAME
+        );
+        Ok(())
+    }
+
+    fn db_upgrade_v4_v5(_instance: &mut StorageInstance) -> Result<()> {
+        // The stale node index and the reference count of state nodes are only written
+        // when state pruning is enabled, nodes written before are never pruned.
+        info!(
+            "add column {}, column {}",
+            STATE_NODE_STALE_INDEX_PREFIX_NAME, STATE_NODE_REF_COUNT_PREFIX_NAME
+        );
+        Ok(())
+    }
+
     pub fn do_upgrade(
         version_in_db: StorageVersion,
         version_in_code: StorageVersion,
@@ -193,6 +215,43 @@ impl DBUpgrade {
             (StorageVersion::V2, StorageVersion::V3) => {
                 Self::db_upgrade_v2_v3(instance)?;
             }
+
+            (StorageVersion::V1, StorageVersion::V4) => {
+                Self::db_upgrade_v1_v2(instance)?;
+                Self::db_upgrade_v2_v3(instance)?;
+                Self::db_upgrade_v3_v4(instance)?;
+            }
+
+            (StorageVersion::V2, StorageVersion::V4) => {
+                Self::db_upgrade_v2_v3(instance)?;
+                Self::db_upgrade_v3_v4(instance)?;
+            }
+
+            (StorageVersion::V3, StorageVersion::V4) => {
+                Self::db_upgrade_v3_v4(instance)?;
+            }
+
+            (StorageVersion::V1, StorageVersion::V5) => {
+                Self::db_upgrade_v1_v2(instance)?;
+                Self::db_upgrade_v2_v3(instance)?;
+                Self::db_upgrade_v3_v4(instance)?;
+                Self::db_upgrade_v4_v5(instance)?;
+            }
+
+            (StorageVersion::V2, StorageVersion::V5) => {
+                Self::db_upgrade_v2_v3(instance)?;
+                Self::db_upgrade_v3_v4(instance)?;
+                Self::db_upgrade_v4_v5(instance)?;
+            }
+
+            (StorageVersion::V3, StorageVersion::V5) => {
+                Self::db_upgrade_v3_v4(instance)?;
+                Self::db_upgrade_v4_v5(instance)?;
+            }
+
+            (StorageVersion::V4, StorageVersion::V5) => {
+                Self::db_upgrade_v4_v5(instance)?;
+            }
             _ => bail!(
                 "Can not upgrade db from {:?} to {:?}",
                 version_in_db,
@@ -8,6 +8,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 pub use service::{SyncAsyncService, SyncServiceHandler};
 use starcoin_crypto::HashValue;
+use starcoin_logger::tracer::TraceContext;
 use starcoin_service_registry::ServiceRequest;
 use starcoin_types::block::{Block, BlockIdAndNumber, BlockInfo, BlockNumber};
 use starcoin_types::sync_status::SyncStatus;
@@ -23,11 +24,26 @@ pub struct StartSyncTxnEvent;
 pub struct PeerNewBlock {
     peer_id: PeerId,
     new_block: Block,
+    trace_context: Option<TraceContext>,
 }
 
 impl PeerNewBlock {
     pub fn new(peer_id: PeerId, new_block: Block) -> Self {
-        PeerNewBlock { peer_id, new_block }
+        PeerNewBlock {
+            peer_id,
+            new_block,
+            trace_context: None,
+        }
+    }
+
+    /// Continue the trace of receiving the block in the connecting.
+    pub fn with_trace_context(mut self, trace_context: Option<TraceContext>) -> Self {
+        self.trace_context = trace_context;
+        self
+    }
+
+    pub fn trace_context(&self) -> Option<TraceContext> {
+        self.trace_context
     }
 
     pub fn get_peer_id(&self) -> PeerId {
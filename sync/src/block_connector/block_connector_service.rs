@@ -3,13 +3,14 @@
 
 use crate::block_connector::{ExecuteRequest, ResetRequest, WriteBlockChainService};
 use crate::sync::{CheckSyncEvent, SyncService};
-use crate::tasks::{BlockConnectedEvent, BlockDiskCheckEvent};
+use crate::tasks::{BlockConnectedEvent, BlockDiskCheckEvent, FastSyncDoneEvent};
 use anyhow::{format_err, Result};
 use network_api::PeerProvider;
 use starcoin_chain_api::{ConnectBlockError, WriteableChainService};
 use starcoin_config::{NodeConfig, G_CRATE_VERSION};
 use starcoin_executor::VMMetrics;
 use starcoin_logger::prelude::*;
+use starcoin_logger::tracer::Span;
 use starcoin_network::NetworkServiceRef;
 use starcoin_service_registry::{
     ActorService, EventHandler, ServiceContext, ServiceFactory, ServiceHandler,
@@ -177,6 +178,16 @@ impl EventHandler<Self, BlockConnectedEvent> for BlockConnectorService {
     }
 }
 
+impl ServiceHandler<Self, FastSyncDoneEvent> for BlockConnectorService {
+    fn handle(
+        &mut self,
+        msg: FastSyncDoneEvent,
+        _ctx: &mut ServiceContext<BlockConnectorService>,
+    ) -> Result<()> {
+        self.chain_service.reset_to_snapshot(msg.pivot.id)
+    }
+}
+
 impl EventHandler<Self, MinedBlock> for BlockConnectorService {
     fn handle_event(&mut self, msg: MinedBlock, _ctx: &mut ServiceContext<Self>) {
         let MinedBlock(new_block) = msg;
@@ -205,6 +216,9 @@ impl EventHandler<Self, PeerNewBlock> for BlockConnectorService {
             return;
         }
         let peer_id = msg.get_peer_id();
+        let _span =
+            Span::enter_with_parent("block_connector.connect_peer_block", msg.trace_context())
+                .with("peer_id", &peer_id);
         if let Err(e) = self.chain_service.try_connect(msg.get_block().clone()) {
             match e.downcast::<ConnectBlockError>() {
                 Ok(connect_error) => {
@@ -9,15 +9,16 @@ use starcoin_config::NodeConfig;
 use starcoin_crypto::HashValue;
 use starcoin_executor::VMMetrics;
 use starcoin_logger::prelude::*;
+use starcoin_logger::tracer::Span;
 use starcoin_service_registry::bus::{Bus, BusService};
 use starcoin_service_registry::ServiceRef;
 use starcoin_storage::Store;
 use starcoin_txpool_api::TxPoolSyncService;
-use starcoin_types::block::BlockInfo;
+use starcoin_types::block::{BlockInfo, BlockNumber};
 use starcoin_types::{
     block::{Block, BlockHeader, ExecutedBlock},
     startup_info::StartupInfo,
-    system_events::{NewBranch, NewHeadBlock},
+    system_events::{ChainReorg, NewBranch, NewHeadBlock},
 };
 use std::fmt::Formatter;
 use std::sync::Arc;
@@ -70,8 +71,15 @@ where
             .metrics
             .as_ref()
             .map(|metrics| metrics.chain_block_connect_time.start_timer());
+        let mut span = Span::enter("write_block_chain.try_connect")
+            .with("block_hash", block.id())
+            .with("block_number", block.header().number());
 
         let result = self.connect_inner(block);
+        match result.as_ref() {
+            Ok(connect) => span.record("result", connect),
+            Err(err) => span.set_error(err),
+        }
 
         if let Some(metrics) = self.metrics.as_ref() {
             let result = match result.as_ref() {
@@ -176,14 +184,17 @@ where
         let parent_is_main_head = self.is_main_head(&executed_block.header().parent_hash());
 
         if branch_total_difficulty > main_total_difficulty {
-            let (enacted_count, enacted_blocks, retracted_count, retracted_blocks) =
+            let (enacted_count, enacted_blocks, retracted_count, retracted_blocks, reorg) =
                 if !parent_is_main_head {
                     self.find_ancestors_from_accumulator(&new_branch)?
                 } else {
-                    (1, vec![executed_block.block.clone()], 0, vec![])
+                    (1, vec![executed_block.block.clone()], 0, vec![], None)
                 };
             self.main = new_branch;
 
+            if let Some(reorg) = reorg {
+                self.broadcast_chain_reorg(reorg);
+            }
             self.do_new_head(
                 executed_block,
                 enacted_count,
@@ -277,6 +288,40 @@ where
         Ok(())
     }
 
+    /// Reset the main chain to the pivot block of fast sync,
+    /// the blocks before the pivot may be absent, so the new head is not connected to the old main.
+    pub fn reset_to_snapshot(&mut self, block_id: HashValue) -> Result<()> {
+        let new_branch = BlockChain::new(
+            self.config.net().time_service(),
+            block_id,
+            self.storage.clone(),
+            self.vm_metrics.clone(),
+        )?;
+        let new_head_number = new_branch.current_header().number();
+        let main_head_number = self.main.current_header().number();
+        if new_head_number <= main_head_number {
+            return Err(format_err!(
+                "Snapshot block {}({}) should be higher than the main head {}",
+                block_id,
+                new_head_number,
+                main_head_number
+            ));
+        }
+        info!(
+            "[connector] Reset main chain to snapshot block {}({})",
+            block_id, new_head_number
+        );
+        let executed_block = new_branch.head_block();
+        self.main = new_branch;
+        self.do_new_head(
+            executed_block.clone(),
+            1,
+            vec![executed_block.block],
+            0,
+            vec![],
+        )
+    }
+
     ///Directly execute the block and save result, do not try to connect.
     pub fn execute(&mut self, block: Block) -> Result<ExecutedBlock> {
         let chain = BlockChain::new(
@@ -304,10 +349,12 @@ where
         }
     }
 
+    /// Find the blocks enacted and retracted by switching the main chain to `new_branch`,
+    /// the `ChainReorg` is None if no block is retracted.
     fn find_ancestors_from_accumulator(
         &self,
         new_branch: &BlockChain,
-    ) -> Result<(u64, Vec<Block>, u64, Vec<Block>)> {
+    ) -> Result<(u64, Vec<Block>, u64, Vec<Block>, Option<ChainReorg>)> {
         let ancestor = self.main.find_ancestor(new_branch)?.ok_or_else(|| {
             format_err!(
                 "Can not find ancestors between main chain: {:?} and branch: {:?}",
@@ -339,11 +386,28 @@ where
         let retracted =
             self.find_blocks_until(block_retracted, ancestor.id, MAX_ROLL_BACK_BLOCK)?;
 
+        let reorg = if retracted_count > 0 {
+            Some(ChainReorg {
+                ancestor: ancestor_block.header().clone(),
+                retracted: Self::block_ids_after(&self.main, ancestor.number)?,
+                enacted: Self::block_ids_after(new_branch, ancestor.number)?,
+            })
+        } else {
+            None
+        };
+
         debug!(
             "Commit block count:{}, rollback block count:{}",
             enacted_count, retracted_count,
         );
-        Ok((enacted_count, enacted, retracted_count, retracted))
+        Ok((enacted_count, enacted, retracted_count, retracted, reorg))
+    }
+
+    /// Ids of the blocks after `number` in `chain`, in ascending order of block number.
+    fn block_ids_after(chain: &BlockChain, number: BlockNumber) -> Result<Vec<HashValue>> {
+        (number.saturating_add(1)..=chain.current_header().number())
+            .map(|number| chain.get_hash_by_number_ensure(number))
+            .collect()
     }
 
     fn find_blocks_until(
@@ -386,6 +450,19 @@ where
         }
     }
 
+    fn broadcast_chain_reorg(&self, reorg: ChainReorg) {
+        info!(
+            "[chain] Reorg main chain at ancestor {}({}), retracted_block_count: {}, enacted_block_count: {}",
+            reorg.ancestor.id(),
+            reorg.ancestor.number(),
+            reorg.retracted.len(),
+            reorg.enacted.len()
+        );
+        if let Err(e) = self.bus.broadcast(reorg) {
+            error!("Broadcast ChainReorg error: {:?}", e);
+        }
+    }
+
     fn broadcast_new_branch(&self, block: ExecutedBlock) {
         if let Some(metrics) = self.metrics.as_ref() {
             metrics
@@ -3,7 +3,7 @@
 
 use crate::block_connector::BlockConnectorService;
 use crate::sync_metrics::SyncMetrics;
-use crate::tasks::{full_sync_task, AncestorEvent, SyncFetcher};
+use crate::tasks::{fast_sync_task, full_sync_task, AncestorEvent, SyncFetcher};
 use crate::verified_rpc_client::{RpcVerifyError, VerifiedRpcClient};
 use anyhow::{format_err, Result};
 use futures::FutureExt;
@@ -30,6 +30,7 @@ use starcoin_types::block::BlockIdAndNumber;
 use starcoin_types::startup_info::ChainStatus;
 use starcoin_types::sync_status::SyncStatus;
 use starcoin_types::system_events::{NewHeadBlock, SyncStatusChangeEvent, SystemStarted};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use stream_task::{TaskError, TaskEventCounterHandle, TaskHandle};
@@ -176,7 +177,7 @@ impl SyncService {
                 }
             }
 
-            let peer_reputations = network
+            let mut peer_reputations: HashMap<PeerId, u64> = network
                 .reputations(REPUTATION_THRESHOLD)
                 .await?
                 .await?
@@ -188,9 +189,19 @@ impl SyncService {
                     )
                 })
                 .collect();
+            // the scores earned by the previous syncs take precedence over the reputation.
+            match network.peer_records().await {
+                Ok(records) => peer_reputations.extend(
+                    records
+                        .into_iter()
+                        .filter(|record| record.score > 0)
+                        .map(|record| (record.peer_id, record.score)),
+                ),
+                Err(e) => warn!("[sync] Get peer records failed: {:?}", e),
+            }
 
             let peer_selector = PeerSelector::new_with_reputation(
-                peer_reputations,
+                peer_reputations.into_iter().collect(),
                 peer_set,
                 peer_select_strategy,
                 peer_score_metrics,
@@ -222,20 +233,50 @@ impl SyncService {
             {
                 info!("[sync] Find target({}), total_difficulty:{}, current head({})'s total_difficulty({})", target.target_id.id(), target.block_info.total_difficulty, current_block_id, current_block_info.total_difficulty);
 
-                let (fut, task_handle, task_event_handle) = full_sync_task(
-                    current_block_id,
-                    target.clone(),
-                    skip_pow_verify,
-                    config.net().time_service(),
-                    storage.clone(),
-                    connector_service.clone(),
-                    rpc_client.clone(),
-                    self_ref.clone(),
-                    network.clone(),
-                    config.sync.max_retry_times(),
-                    sync_metrics.clone(),
-                    vm_metrics.clone(),
-                )?;
+                let current_block_number = storage
+                    .get_block_header_by_hash(current_block_id)?
+                    .ok_or_else(|| {
+                        format_err!("Can not find block header by id: {}", current_block_id)
+                    })?
+                    .number();
+                let (fut, task_handle, task_event_handle) =
+                    if config.sync.fast_sync() && current_block_number == 0 {
+                        info!(
+                            "[sync] Start fast sync, pivot distance: {}",
+                            config.sync.fast_sync_pivot_distance()
+                        );
+                        fast_sync_task(
+                            current_block_id,
+                            target.clone(),
+                            skip_pow_verify,
+                            config.net().time_service(),
+                            storage.clone(),
+                            connector_service.clone(),
+                            rpc_client.clone(),
+                            self_ref.clone(),
+                            connector_service.clone(),
+                            network.clone(),
+                            config.sync.fast_sync_pivot_distance(),
+                            config.sync.max_retry_times(),
+                            sync_metrics.clone(),
+                            vm_metrics.clone(),
+                        )?
+                    } else {
+                        full_sync_task(
+                            current_block_id,
+                            target.clone(),
+                            skip_pow_verify,
+                            config.net().time_service(),
+                            storage.clone(),
+                            connector_service.clone(),
+                            rpc_client.clone(),
+                            self_ref.clone(),
+                            network.clone(),
+                            config.sync.max_retry_times(),
+                            sync_metrics.clone(),
+                            vm_metrics.clone(),
+                        )?
+                    };
 
                 self_ref.notify(SyncBeginEvent {
                     target,
@@ -557,6 +598,10 @@ impl EventHandler<Self, SyncDoneEvent> for SyncService {
                         "[sync] Current SyncStatus is invalid, receive sync done event ,but sync task not done.",
                     )
                 }
+                match ctx.get_shared::<NetworkServiceRef>() {
+                    Ok(network) => network.update_peer_scores(task_handle.peer_selector.scores()),
+                    Err(e) => warn!("[sync] Save peer scores failed: {:?}", e),
+                }
                 self.sync_status.sync_done();
                 ctx.broadcast(SyncStatusChangeEvent(self.sync_status.clone()));
                 // check sync again
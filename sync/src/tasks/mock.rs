@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::tasks::{
-    BlockConnectedEvent, BlockFetcher, BlockIdFetcher, BlockInfoFetcher, PeerOperator, SyncFetcher,
+    BlockConnectedEvent, BlockFetcher, BlockIdFetcher, BlockInfoFetcher, PeerOperator,
+    StateSyncFetcher, SyncFetcher,
 };
 use anyhow::{format_err, Context, Result};
 use async_std::task::JoinHandle;
@@ -14,15 +15,22 @@ use network_api::messages::NotificationMessage;
 use network_api::{PeerId, PeerInfo, PeerSelector, PeerStrategy};
 use network_p2p_core::{NetRpcError, RpcErrorCode};
 use rand::Rng;
-use starcoin_accumulator::{Accumulator, MerkleAccumulator};
+use starcoin_accumulator::node::AccumulatorStoreType;
+use starcoin_accumulator::{Accumulator, AccumulatorNode, MerkleAccumulator};
 use starcoin_chain::BlockChain;
 use starcoin_chain_api::ChainReader;
 use starcoin_chain_mock::MockChain;
 use starcoin_config::ChainNetwork;
 use starcoin_crypto::HashValue;
 use starcoin_network_rpc_api::G_RPC_INFO;
+use starcoin_state_api::{ChainStateReader, StateNodeStore, StateWithProof};
+use starcoin_state_tree::StateNode;
+use starcoin_statedb::ChainStateDB;
+use starcoin_storage::table_info::TableInfoStore;
 use starcoin_sync_api::SyncTarget;
+use starcoin_types::access_path::AccessPath;
 use starcoin_types::block::{Block, BlockIdAndNumber, BlockInfo, BlockNumber};
+use starcoin_vm_types::state_store::table::{TableHandle, TableInfo};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -348,3 +356,64 @@ impl BlockInfoFetcher for SyncNodeMocker {
 }
 
 impl SyncFetcher for SyncNodeMocker {}
+
+impl StateSyncFetcher for SyncNodeMocker {
+    fn fetch_state_node(&self, node_hash: HashValue) -> BoxFuture<Result<Option<StateNode>>> {
+        let result = StateNodeStore::get(self.chain().get_storage().as_ref(), &node_hash);
+        async move {
+            let _ = self.select_a_peer()?;
+            self.err_mocker.random_err().await?;
+            result
+        }
+        .boxed()
+    }
+
+    fn fetch_accumulator_node(
+        &self,
+        node_hash: HashValue,
+        accumulator_type: AccumulatorStoreType,
+    ) -> BoxFuture<Result<AccumulatorNode>> {
+        let result = self
+            .chain()
+            .get_storage()
+            .get_accumulator_store(accumulator_type)
+            .get_node(node_hash)
+            .and_then(|node| {
+                node.ok_or_else(|| format_err!("Can not find accumulator node: {}", node_hash))
+            });
+        async move {
+            let _ = self.select_a_peer()?;
+            self.err_mocker.random_err().await?;
+            result
+        }
+        .boxed()
+    }
+
+    fn fetch_table_info(&self, handle: TableHandle) -> BoxFuture<Result<Option<TableInfo>>> {
+        let result = TableInfoStore::get_table_info(self.chain().get_storage().as_ref(), handle);
+        async move {
+            let _ = self.select_a_peer()?;
+            self.err_mocker.random_err().await?;
+            result
+        }
+        .boxed()
+    }
+
+    fn fetch_state_with_proof(
+        &self,
+        state_root: HashValue,
+        access_path: AccessPath,
+    ) -> BoxFuture<Result<StateWithProof>> {
+        let result = ChainStateDB::new(
+            self.chain().get_storage().into_super_arc(),
+            Some(state_root),
+        )
+        .get_with_proof(&access_path);
+        async move {
+            let _ = self.select_a_peer()?;
+            self.err_mocker.random_err().await?;
+            result
+        }
+        .boxed()
+    }
+}
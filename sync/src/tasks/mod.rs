@@ -11,17 +11,23 @@ use futures::{FutureExt, TryFutureExt};
 use network_api::{PeerId, PeerProvider, PeerSelector};
 use network_p2p_core::{NetRpcError, RpcErrorCode};
 use starcoin_accumulator::node::AccumulatorStoreType;
-use starcoin_accumulator::MerkleAccumulator;
+use starcoin_accumulator::{AccumulatorNode, MerkleAccumulator};
 use starcoin_chain::{BlockChain, ChainReader};
 use starcoin_crypto::HashValue;
 use starcoin_logger::prelude::*;
-use starcoin_service_registry::{ActorService, EventHandler, ServiceRef};
+use starcoin_service_registry::{
+    ActorService, EventHandler, ServiceHandler, ServiceRef, ServiceRequest,
+};
+use starcoin_state_api::StateWithProof;
+use starcoin_state_tree::StateNode;
 use starcoin_storage::Store;
 use starcoin_sync_api::SyncTarget;
 use starcoin_time_service::TimeService;
+use starcoin_types::access_path::AccessPath;
 use starcoin_types::block::{Block, BlockIdAndNumber, BlockInfo, BlockNumber};
 use starcoin_types::startup_info::ChainStatus;
 use starcoin_types::U256;
+use starcoin_vm_types::state_store::table::{TableHandle, TableInfo};
 use std::str::FromStr;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
@@ -360,6 +366,90 @@ impl BlockInfoFetcher for VerifiedRpcClient {
 
 impl SyncFetcher for VerifiedRpcClient {}
 
+/// Fetch the state and accumulator nodes for fast sync.
+pub trait StateSyncFetcher: SyncFetcher {
+    fn fetch_state_node(&self, node_hash: HashValue) -> BoxFuture<Result<Option<StateNode>>>;
+
+    fn fetch_accumulator_node(
+        &self,
+        node_hash: HashValue,
+        accumulator_type: AccumulatorStoreType,
+    ) -> BoxFuture<Result<AccumulatorNode>>;
+
+    fn fetch_table_info(&self, handle: TableHandle) -> BoxFuture<Result<Option<TableInfo>>>;
+
+    /// The returned state should have been verified with the `state_root`.
+    fn fetch_state_with_proof(
+        &self,
+        state_root: HashValue,
+        access_path: AccessPath,
+    ) -> BoxFuture<Result<StateWithProof>>;
+}
+
+impl<T> StateSyncFetcher for Arc<T>
+where
+    T: StateSyncFetcher,
+{
+    fn fetch_state_node(&self, node_hash: HashValue) -> BoxFuture<Result<Option<StateNode>>> {
+        StateSyncFetcher::fetch_state_node(self.as_ref(), node_hash)
+    }
+
+    fn fetch_accumulator_node(
+        &self,
+        node_hash: HashValue,
+        accumulator_type: AccumulatorStoreType,
+    ) -> BoxFuture<Result<AccumulatorNode>> {
+        StateSyncFetcher::fetch_accumulator_node(self.as_ref(), node_hash, accumulator_type)
+    }
+
+    fn fetch_table_info(&self, handle: TableHandle) -> BoxFuture<Result<Option<TableInfo>>> {
+        StateSyncFetcher::fetch_table_info(self.as_ref(), handle)
+    }
+
+    fn fetch_state_with_proof(
+        &self,
+        state_root: HashValue,
+        access_path: AccessPath,
+    ) -> BoxFuture<Result<StateWithProof>> {
+        StateSyncFetcher::fetch_state_with_proof(self.as_ref(), state_root, access_path)
+    }
+}
+
+impl StateSyncFetcher for VerifiedRpcClient {
+    fn fetch_state_node(&self, node_hash: HashValue) -> BoxFuture<Result<Option<StateNode>>> {
+        self.get_state_node_by_node_hash(node_hash)
+            .map_ok(|(_peer_id, state_node)| state_node)
+            .map_err(fetcher_err_map)
+            .boxed()
+    }
+
+    fn fetch_accumulator_node(
+        &self,
+        node_hash: HashValue,
+        accumulator_type: AccumulatorStoreType,
+    ) -> BoxFuture<Result<AccumulatorNode>> {
+        self.get_accumulator_node_by_node_hash(node_hash, accumulator_type)
+            .map_ok(|(_peer_id, accumulator_node)| accumulator_node)
+            .map_err(fetcher_err_map)
+            .boxed()
+    }
+
+    fn fetch_table_info(&self, handle: TableHandle) -> BoxFuture<Result<Option<TableInfo>>> {
+        self.get_table_info(handle).map_err(fetcher_err_map).boxed()
+    }
+
+    fn fetch_state_with_proof(
+        &self,
+        state_root: HashValue,
+        access_path: AccessPath,
+    ) -> BoxFuture<Result<StateWithProof>> {
+        self.get_state_with_proof(state_root, access_path)
+            .map_ok(|(_peer_id, state_with_proof)| state_with_proof)
+            .map_err(fetcher_err_map)
+            .boxed()
+    }
+}
+
 pub trait BlockLocalStore: Send + Sync {
     fn get_block_with_info(&self, block_ids: Vec<HashValue>) -> Result<Vec<Option<SyncBlockData>>>;
 }
@@ -435,9 +525,49 @@ where
     }
 }
 
+/// The state of the pivot block has been synced, the chain should be reset to it.
+#[derive(Clone, Debug)]
+pub struct FastSyncDoneEvent {
+    pub pivot: BlockIdAndNumber,
+}
+
+impl ServiceRequest for FastSyncDoneEvent {
+    type Response = Result<()>;
+}
+
+pub trait FastSyncEventHandle: Send + Clone + std::marker::Unpin {
+    /// The blocks after the pivot are synced when the returned future is done,
+    /// so they are connected to the chain reset to the pivot.
+    fn handle(&mut self, event: FastSyncDoneEvent) -> BoxFuture<'static, Result<()>>;
+}
+
+impl FastSyncEventHandle for UnboundedSender<FastSyncDoneEvent> {
+    fn handle(&mut self, event: FastSyncDoneEvent) -> BoxFuture<'static, Result<()>> {
+        let result: Result<()> = self.start_send(event).map_err(Into::into);
+        futures::future::ready(result).boxed()
+    }
+}
+
+impl<S> FastSyncEventHandle for ServiceRef<S>
+where
+    S: ActorService + ServiceHandler<S, FastSyncDoneEvent>,
+{
+    fn handle(&mut self, event: FastSyncDoneEvent) -> BoxFuture<'static, Result<()>> {
+        let service_ref = self.clone();
+        async move { service_ref.send(event).await? }.boxed()
+    }
+}
+
 #[derive(Clone)]
 pub struct NoOpEventHandle;
 
+impl FastSyncEventHandle for NoOpEventHandle {
+    fn handle(&mut self, event: FastSyncDoneEvent) -> BoxFuture<'static, Result<()>> {
+        debug!("Handle FastSyncDoneEvent {:?}", event);
+        futures::future::ready(Ok(())).boxed()
+    }
+}
+
 impl BlockConnectedEventHandle for NoOpEventHandle {
     fn handle(&mut self, event: BlockConnectedEvent) -> Result<()> {
         debug!("Handle BlockConnectedEvent {:?}", event);
@@ -508,6 +638,7 @@ mod find_ancestor_task;
 mod inner_sync_task;
 #[cfg(test)]
 pub(crate) mod mock;
+mod state_sync_task;
 #[cfg(test)]
 mod tests;
 
@@ -516,6 +647,7 @@ pub use accumulator_sync_task::{AccumulatorCollector, BlockAccumulatorSyncTask};
 pub use block_sync_task::{BlockCollector, BlockSyncTask};
 pub use find_ancestor_task::{AncestorCollector, FindAncestorTask};
 use starcoin_executor::VMMetrics;
+pub use state_sync_task::StateSyncTask;
 
 pub fn full_sync_task<H, A, F, N>(
     current_block_id: HashValue,
@@ -535,6 +667,150 @@ pub fn full_sync_task<H, A, F, N>(
     TaskHandle,
     Arc<TaskEventCounterHandle>,
 )>
+where
+    H: BlockConnectedEventHandle + Sync + 'static,
+    A: AncestorEventHandle + Sync + 'static,
+    F: SyncFetcher + 'static,
+    N: PeerProvider + Clone + 'static,
+{
+    let event_handle = Arc::new(TaskEventCounterHandle::new());
+    let all_fut = sync_from_block(
+        current_block_id,
+        target,
+        skip_pow_verify,
+        time_service,
+        storage,
+        block_event_handle,
+        fetcher,
+        ancestor_event_handle,
+        peer_provider,
+        max_retry_times,
+        sync_metrics,
+        vm_metrics,
+        event_handle.clone(),
+    )?;
+    let task = TaskFuture::new(all_fut);
+    let (fut, handle) = task.with_handle();
+    Ok((fut, handle, event_handle))
+}
+
+/// Sync the state of a pivot block first, then sync blocks from the pivot like `full_sync_task`.
+/// Fall back to sync from `current_block_id` if the target is too low to select a pivot.
+#[allow(clippy::too_many_arguments)]
+pub fn fast_sync_task<H, A, E, F, N>(
+    current_block_id: HashValue,
+    target: SyncTarget,
+    skip_pow_verify: bool,
+    time_service: Arc<dyn TimeService>,
+    storage: Arc<dyn Store>,
+    block_event_handle: H,
+    fetcher: Arc<F>,
+    ancestor_event_handle: A,
+    fast_sync_event_handle: E,
+    peer_provider: N,
+    pivot_distance: u64,
+    max_retry_times: u64,
+    sync_metrics: Option<SyncMetrics>,
+    vm_metrics: Option<VMMetrics>,
+) -> Result<(
+    BoxFuture<'static, Result<BlockChain, TaskError>>,
+    TaskHandle,
+    Arc<TaskEventCounterHandle>,
+)>
+where
+    H: BlockConnectedEventHandle + Sync + 'static,
+    A: AncestorEventHandle + Sync + 'static,
+    E: FastSyncEventHandle + Sync + 'static,
+    F: StateSyncFetcher + 'static,
+    N: PeerProvider + Clone + 'static,
+{
+    let event_handle = Arc::new(TaskEventCounterHandle::new());
+    fetcher.peer_selector().retain(target.peers.as_slice());
+    let state_sync_task = StateSyncTask::new(
+        target.clone(),
+        pivot_distance,
+        storage.clone(),
+        fetcher.clone(),
+        event_handle.clone(),
+        max_retry_times,
+        100,
+    );
+    let event_handle_clone = event_handle.clone();
+    let all_fut = async move {
+        let pivot = state_sync_task.sync().await.map_err(|e| {
+            // the verify error has been wrapped by the fetcher.
+            match e.downcast::<TaskError>() {
+                Ok(task_err) => task_err,
+                Err(e) => TaskError::BreakError(e),
+            }
+        })?;
+        let start_block_id = match pivot {
+            Some(pivot) => {
+                let mut fast_sync_event_handle = fast_sync_event_handle;
+                fast_sync_event_handle
+                    .handle(FastSyncDoneEvent { pivot })
+                    .await
+                    .map_err(|e| {
+                        TaskError::BreakError(format_err!(
+                            "Reset chain to pivot {:?} error: {:?}",
+                            pivot,
+                            e
+                        ))
+                    })?;
+                pivot.id
+            }
+            None => current_block_id,
+        };
+        let target_total_difficulty = target.block_info.total_difficulty;
+        let branch = sync_from_block(
+            start_block_id,
+            target,
+            skip_pow_verify,
+            time_service,
+            storage,
+            block_event_handle,
+            fetcher,
+            ancestor_event_handle,
+            peer_provider,
+            max_retry_times,
+            sync_metrics,
+            vm_metrics,
+            event_handle_clone,
+        )
+        .map_err(TaskError::BreakError)?
+        .await?;
+        // the total difficulty of the pivot is taken from the peers, it is verified by the target
+        // after the blocks from the pivot are executed.
+        let total_difficulty = branch.status().total_difficulty();
+        if pivot.is_some() && total_difficulty != target_total_difficulty {
+            return Err(TaskError::BreakError(format_err!(
+                "Total difficulty {} of the chain synced from pivot mismatch with target {}",
+                total_difficulty,
+                target_total_difficulty
+            )));
+        }
+        Ok(branch)
+    };
+    let task = TaskFuture::new(all_fut.boxed());
+    let (fut, handle) = task.with_handle();
+    Ok((fut, handle, event_handle))
+}
+
+fn sync_from_block<H, A, F, N>(
+    current_block_id: HashValue,
+    target: SyncTarget,
+    skip_pow_verify: bool,
+    time_service: Arc<dyn TimeService>,
+    storage: Arc<dyn Store>,
+    block_event_handle: H,
+    fetcher: Arc<F>,
+    ancestor_event_handle: A,
+    peer_provider: N,
+    max_retry_times: u64,
+    sync_metrics: Option<SyncMetrics>,
+    vm_metrics: Option<VMMetrics>,
+    event_handle: Arc<TaskEventCounterHandle>,
+) -> Result<BoxFuture<'static, Result<BlockChain, TaskError>>>
 where
     H: BlockConnectedEventHandle + Sync + 'static,
     A: AncestorEventHandle + Sync + 'static,
@@ -550,8 +826,6 @@ where
         .get_block_info(current_block_id)?
         .ok_or_else(|| format_err!("Can not find block info by id: {}", current_block_id))?;
 
-    let event_handle = Arc::new(TaskEventCounterHandle::new());
-
     let target_block_number = target.target_id.number();
 
     let current_block_accumulator_info = current_block_info.block_accumulator_info.clone();
@@ -687,9 +961,7 @@ where
         }
         Ok(latest_block_chain)
     };
-    let task = TaskFuture::new(all_fut.boxed());
-    let (fut, handle) = task.with_handle();
-    Ok((fut, handle, event_handle))
+    Ok(all_fut.boxed())
 }
 
 const MAX_BETTER_PEER_SIZE: u64 = 20;
@@ -0,0 +1,690 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tasks::StateSyncFetcher;
+use anyhow::{bail, ensure, format_err, Result};
+use forkable_jellyfish_merkle::node_type::Node;
+use forkable_jellyfish_merkle::RawKey;
+use futures::future::try_join_all;
+use futures::{Future, TryFutureExt};
+use futures_timer::Delay;
+use lru::LruCache;
+use starcoin_accumulator::accumulator_info::AccumulatorInfo;
+use starcoin_accumulator::node::{AccumulatorStoreType, InternalNode};
+use starcoin_accumulator::node_index::{FrozenSubTreeIterator, NodeIndex};
+use starcoin_accumulator::AccumulatorNode;
+use starcoin_chain::verifier::StaticVerifier;
+use starcoin_crypto::hash::{
+    PlainCryptoHash, ACCUMULATOR_PLACEHOLDER_HASH, SPARSE_MERKLE_PLACEHOLDER_HASH,
+};
+use starcoin_crypto::HashValue;
+use starcoin_logger::prelude::*;
+use starcoin_state_api::TABLE_PATH_LIST;
+use starcoin_state_tree::StateNode;
+use starcoin_storage::Store;
+use starcoin_sync_api::SyncTarget;
+use starcoin_types::access_path::AccessPath;
+use starcoin_types::account_address::AccountAddress;
+use starcoin_types::account_state::AccountState;
+use starcoin_types::block::{Block, BlockHeader, BlockIdAndNumber, BlockInfo, BlockNumber};
+use starcoin_types::startup_info::{SnapshotRange, StartupInfo};
+use starcoin_vm_types::access_path::ModuleName;
+use starcoin_vm_types::account_config::{genesis_address, TABLE_HANDLE_ADDRESS_LIST};
+use starcoin_vm_types::language_storage::StructTag;
+use starcoin_vm_types::move_resource::MoveResource;
+use starcoin_vm_types::on_chain_resource::Epoch;
+use starcoin_vm_types::state_store::table::TableHandle;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use stream_task::{TaskError, TaskEventHandle};
+
+/// Max state, accumulator nodes or table infos requested concurrently.
+const NODE_BATCH_SIZE: usize = 32;
+/// Max blocks requested at one time.
+const BLOCK_BATCH_SIZE: usize = 10;
+/// Max state node hashes remembered to skip the shared nodes, a forgotten node is fetched again.
+const MAX_VISITED_STATE_NODES: usize = 1 << 20;
+
+/// The key type of the tree which a state node belongs to, used to decode and verify the node.
+#[derive(Clone, Debug)]
+enum StateTreeKind {
+    Account,
+    Code,
+    /// The resource tree of an account, with the struct tag of the table handles root
+    /// if the account is a table handle address.
+    Resource(Option<StructTag>),
+    TableHandles,
+    TableItems,
+}
+
+/// A state node verified by its hash, and the nodes it references.
+struct VerifiedStateNode {
+    node: StateNode,
+    children: Vec<(HashValue, StateTreeKind)>,
+    table_handle: Option<TableHandle>,
+}
+
+type LeafChildren = (Vec<(HashValue, StateTreeKind)>, Option<TableHandle>);
+
+impl StateTreeKind {
+    fn verify(&self, hash: HashValue, state_node: StateNode) -> Result<VerifiedStateNode> {
+        let (children, table_handle) = match self {
+            StateTreeKind::Account => {
+                self.verify_node::<AccountAddress, _>(hash, &state_node, |address, blob| {
+                    let account_state = AccountState::try_from(blob)?;
+                    let mut children = vec![(
+                        account_state.resource_root(),
+                        Self::resource_tree_of(address),
+                    )];
+                    if let Some(code_root) = account_state.code_root() {
+                        children.push((code_root, StateTreeKind::Code));
+                    }
+                    Ok((children, None))
+                })?
+            }
+            StateTreeKind::Code => {
+                self.verify_node::<ModuleName, _>(hash, &state_node, |_, _| Ok((vec![], None)))?
+            }
+            StateTreeKind::Resource(table_path) => {
+                self.verify_node::<StructTag, _>(hash, &state_node, |struct_tag, blob| {
+                    if table_path.as_ref() == Some(struct_tag) {
+                        let root = HashValue::from_slice(blob)?;
+                        Ok((vec![(root, StateTreeKind::TableHandles)], None))
+                    } else {
+                        Ok((vec![], None))
+                    }
+                })?
+            }
+            StateTreeKind::TableHandles => {
+                self.verify_node::<TableHandle, _>(hash, &state_node, |handle, blob| {
+                    let root = HashValue::from_slice(blob)?;
+                    Ok((vec![(root, StateTreeKind::TableItems)], Some(*handle)))
+                })?
+            }
+            StateTreeKind::TableItems => {
+                self.verify_node::<Vec<u8>, _>(hash, &state_node, |_, _| Ok((vec![], None)))?
+            }
+        };
+        Ok(VerifiedStateNode {
+            node: state_node,
+            children: children
+                .into_iter()
+                .filter(|(child, _)| *child != *SPARSE_MERKLE_PLACEHOLDER_HASH)
+                .collect(),
+            table_handle,
+        })
+    }
+
+    fn resource_tree_of(address: &AccountAddress) -> StateTreeKind {
+        let table_path = TABLE_HANDLE_ADDRESS_LIST
+            .iter()
+            .position(|handle_address| handle_address == address)
+            .and_then(|idx| TABLE_PATH_LIST.get(idx))
+            .and_then(|data_path| data_path.as_struct_tag().cloned());
+        StateTreeKind::Resource(table_path)
+    }
+
+    /// Decode the node with key type `K` and check its hash,
+    /// `leaf_children` returns the roots of the trees referenced by a leaf.
+    fn verify_node<K, F>(
+        &self,
+        hash: HashValue,
+        state_node: &StateNode,
+        leaf_children: F,
+    ) -> Result<LeafChildren>
+    where
+        K: RawKey,
+        F: FnOnce(&K, &[u8]) -> Result<LeafChildren>,
+    {
+        let node = Node::<K>::decode(state_node.0.as_slice())?;
+        let node_hash = node.hash();
+        ensure!(
+            node_hash == hash,
+            "State node hash mismatch, expect: {}, got: {}",
+            hash,
+            node_hash
+        );
+        match &node {
+            Node::Null => Ok((vec![], None)),
+            Node::Internal(internal) => Ok((
+                internal
+                    .all_child()
+                    .into_iter()
+                    .map(|child| (child, self.clone()))
+                    .collect(),
+                None,
+            )),
+            Node::Leaf(leaf) => {
+                ensure!(
+                    leaf.blob().crypto_hash() == leaf.blob_hash(),
+                    "State leaf node {} blob hash mismatch",
+                    hash
+                );
+                leaf_children(leaf.raw_key(), leaf.blob().as_ref())
+            }
+        }
+    }
+}
+
+/// Check the accumulator root is built from the frozen subtree roots,
+/// so the accumulator can be appended from the info.
+pub fn verify_accumulator_info(info: &AccumulatorInfo) -> Result<()> {
+    let mut frozen_subtrees: Vec<(NodeIndex, HashValue)> =
+        FrozenSubTreeIterator::new(info.num_leaves)
+            .zip(info.frozen_subtree_roots.iter().cloned())
+            .collect();
+    ensure!(
+        frozen_subtrees.len() == info.frozen_subtree_roots.len()
+            && frozen_subtrees.len() == info.num_leaves.count_ones() as usize,
+        "Frozen subtree roots count {} mismatch with leaves count {}",
+        info.frozen_subtree_roots.len(),
+        info.num_leaves
+    );
+    let root = match frozen_subtrees.pop() {
+        None => *ACCUMULATOR_PLACEHOLDER_HASH,
+        Some((mut pos, mut hash)) => {
+            let root_level = NodeIndex::root_level_from_leaf_count(info.num_leaves);
+            for _ in pos.level()..root_level {
+                hash = if pos.is_left_child() {
+                    InternalNode::new(pos.parent(), hash, *ACCUMULATOR_PLACEHOLDER_HASH).hash()
+                } else {
+                    let (sibling, left) = frozen_subtrees
+                        .pop()
+                        .ok_or_else(|| format_err!("Missing frozen subtree {:?}", pos.sibling()))?;
+                    ensure!(
+                        sibling == pos.sibling(),
+                        "Frozen subtree {:?} should be the sibling of {:?}",
+                        sibling,
+                        pos
+                    );
+                    InternalNode::new(pos.parent(), left, hash).hash()
+                };
+                pos = pos.parent();
+            }
+            ensure!(
+                frozen_subtrees.is_empty(),
+                "Frozen subtrees {:?} are not in the accumulator",
+                frozen_subtrees
+            );
+            hash
+        }
+    };
+    ensure!(
+        root == info.accumulator_root,
+        "Accumulator root mismatch, expect: {}, got: {}",
+        info.accumulator_root,
+        root
+    );
+    Ok(())
+}
+
+/// Verify the block info of `header` by the header itself and the header of its child.
+fn verify_block_info(
+    block_info: &BlockInfo,
+    header: &BlockHeader,
+    next_header: &BlockHeader,
+) -> Result<()> {
+    ensure!(
+        block_info.block_id == header.id(),
+        "Block info's block id {} mismatch with block {}",
+        block_info.block_id,
+        header.id()
+    );
+    ensure!(
+        block_info.txn_accumulator_info.accumulator_root == header.txn_accumulator_root(),
+        "Block {} txn accumulator root mismatch",
+        header.id()
+    );
+    ensure!(
+        block_info.block_accumulator_info.accumulator_root == next_header.block_accumulator_root()
+            && block_info.block_accumulator_info.num_leaves == header.number().saturating_add(1),
+        "Block {} block accumulator mismatch",
+        header.id()
+    );
+    verify_accumulator_info(&block_info.txn_accumulator_info)?;
+    verify_accumulator_info(&block_info.block_accumulator_info)
+}
+
+/// Sync the state of a pivot block from peers, instead of executing all blocks from genesis.
+///
+/// The pivot is the start block of the epoch which contains block `target - pivot_distance`,
+/// the chain at the pivot only needs the blocks of current and previous epoch.
+/// The task downloads the state of the pivot and its parent, the block accumulator nodes
+/// and the blocks of the two epochs, all of them are verified from the block accumulator of the
+/// sync target. The total difficulty in the block info of the pivot can not be verified by the
+/// accumulator, it is checked with the target's when the blocks after the pivot are synced.
+/// When done, the pivot is saved as the startup block, and the history before it is
+/// recorded by `SnapshotRange`.
+pub struct StateSyncTask<F>
+where
+    F: StateSyncFetcher + 'static,
+{
+    target: SyncTarget,
+    pivot_distance: u64,
+    storage: Arc<dyn Store>,
+    fetcher: Arc<F>,
+    event_handle: Arc<dyn TaskEventHandle>,
+    max_retry_times: u64,
+    delay_milliseconds_on_error: u64,
+}
+
+impl<F> StateSyncTask<F>
+where
+    F: StateSyncFetcher + 'static,
+{
+    pub fn new(
+        target: SyncTarget,
+        pivot_distance: u64,
+        storage: Arc<dyn Store>,
+        fetcher: Arc<F>,
+        event_handle: Arc<dyn TaskEventHandle>,
+        max_retry_times: u64,
+        delay_milliseconds_on_error: u64,
+    ) -> Self {
+        Self {
+            target,
+            pivot_distance,
+            storage,
+            fetcher,
+            event_handle,
+            max_retry_times,
+            delay_milliseconds_on_error,
+        }
+    }
+
+    /// Sync to the pivot block, return None if the target is too low to fast sync.
+    pub async fn sync(self) -> Result<Option<BlockIdAndNumber>> {
+        let target_number = self.target.target_id.number();
+        let candidate = target_number.saturating_sub(self.pivot_distance);
+        if candidate == 0 {
+            info!(
+                "[fast-sync] Target {} is too low to fast sync, pivot distance: {}",
+                target_number, self.pivot_distance
+            );
+            return Ok(None);
+        }
+        let target_accumulator = self.target.block_info.block_accumulator_info.clone();
+
+        self.event_handle
+            .on_start("FastSyncPivotTask".to_string(), None);
+        let leaves = self
+            .sync_block_accumulator(&target_accumulator, candidate, candidate, false)
+            .await?;
+        let candidate_block = self
+            .fetch_blocks(vec![Self::leaf(&leaves, candidate)?])
+            .await?
+            .pop()
+            .ok_or_else(|| format_err!("Can not fetch block {}", candidate))?;
+        let pivot = self
+            .fetch_epoch(candidate_block.header().state_root())
+            .await?
+            .start_block_number();
+        // the chain at the parent of pivot needs the previous epoch.
+        if pivot <= 1 {
+            info!(
+                "[fast-sync] Pivot {} is in the first epoch, fall back to full sync",
+                pivot
+            );
+            return Ok(None);
+        }
+        let prev = pivot.saturating_sub(1);
+        let next = pivot.saturating_add(1);
+        let leaves = self
+            .sync_block_accumulator(&target_accumulator, prev, next, false)
+            .await?;
+        let mut blocks = self
+            .fetch_blocks(vec![
+                Self::leaf(&leaves, prev)?,
+                Self::leaf(&leaves, pivot)?,
+                Self::leaf(&leaves, next)?,
+            ])
+            .await?;
+        let (next_block, pivot_block, prev_block) = match (blocks.pop(), blocks.pop(), blocks.pop())
+        {
+            (Some(next_block), Some(pivot_block), Some(prev_block)) => {
+                (next_block, pivot_block, prev_block)
+            }
+            _ => bail!("Can not fetch blocks around pivot {}", pivot),
+        };
+        let mut block_infos = self
+            .retry(|| {
+                self.fetcher
+                    .fetch_block_infos(None, vec![prev_block.id(), pivot_block.id()])
+            })
+            .await?;
+        let (pivot_info, prev_info) = match (block_infos.pop(), block_infos.pop()) {
+            (Some(Some(pivot_info)), Some(Some(prev_info))) => (pivot_info, prev_info),
+            _ => bail!("Can not fetch block infos around pivot {}", pivot),
+        };
+        verify_block_info(&prev_info, prev_block.header(), pivot_block.header())?;
+        verify_block_info(&pivot_info, pivot_block.header(), next_block.header())?;
+        ensure!(
+            pivot_info.total_difficulty
+                == prev_info
+                    .total_difficulty
+                    .saturating_add(pivot_block.header().difficulty()),
+            "Pivot {} total difficulty {} mismatch with its parent's {}",
+            pivot,
+            pivot_info.total_difficulty,
+            prev_info.total_difficulty
+        );
+        self.event_handle.on_finish("FastSyncPivotTask".to_string());
+        info!(
+            "[fast-sync] Select pivot block {:?}, target: {:?}",
+            pivot_block.header(),
+            self.target.target_id
+        );
+
+        self.sync_state(vec![
+            prev_block.header().state_root(),
+            pivot_block.header().state_root(),
+        ])
+        .await?;
+
+        let pivot_epoch = self.fetch_epoch(pivot_block.header().state_root()).await?;
+        ensure!(
+            pivot_epoch.start_block_number() == pivot,
+            "Pivot {} should be the start of epoch {:?}",
+            pivot,
+            pivot_epoch
+        );
+        let prev_epoch = self.fetch_epoch(prev_block.header().state_root()).await?;
+        let difficulty_window = pivot_epoch
+            .block_difficulty_window()
+            .max(prev_epoch.block_difficulty_window());
+        let start = prev_epoch
+            .start_block_number()
+            .min(prev.saturating_sub(difficulty_window))
+            .max(1);
+
+        self.event_handle
+            .on_start("FastSyncAccumulatorTask".to_string(), None);
+        self.sync_block_accumulator(&prev_info.block_accumulator_info, start, prev, true)
+            .await?;
+        let leaves = self
+            .sync_block_accumulator(&pivot_info.block_accumulator_info, start, pivot, true)
+            .await?;
+        self.event_handle
+            .on_finish("FastSyncAccumulatorTask".to_string());
+
+        self.event_handle.on_start(
+            "FastSyncBlockTask".to_string(),
+            Some(pivot.saturating_sub(start).saturating_add(1)),
+        );
+        let block_ids: Vec<HashValue> = leaves.range(start..=pivot).map(|(_, id)| *id).collect();
+        for ids in block_ids.chunks(BLOCK_BATCH_SIZE) {
+            for block in self.fetch_blocks(ids.to_vec()).await? {
+                self.storage.commit_block(block)?;
+                self.event_handle.on_item();
+            }
+            self.event_handle.on_sub_task();
+        }
+        self.event_handle.on_finish("FastSyncBlockTask".to_string());
+
+        self.storage.save_block_info(prev_info)?;
+        self.storage.save_block_info(pivot_info)?;
+        self.storage
+            .save_snapshot_range(SnapshotRange::new(1, pivot))?;
+        self.storage
+            .save_startup_info(StartupInfo::new(pivot_block.id()))?;
+        info!(
+            "[fast-sync] Sync state of pivot block {}({}) done, blocks from {} are saved",
+            pivot,
+            pivot_block.id(),
+            start
+        );
+        Ok(Some(BlockIdAndNumber::new(pivot_block.id(), pivot)))
+    }
+
+    fn leaf(leaves: &BTreeMap<BlockNumber, HashValue>, number: BlockNumber) -> Result<HashValue> {
+        leaves
+            .get(&number)
+            .cloned()
+            .ok_or_else(|| format_err!("Can not find block id by number {}", number))
+    }
+
+    /// Retry the fetch on error, except the error which breaks the task, such as verify error.
+    async fn retry<T, Fut>(&self, fetch: impl Fn() -> Fut) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry_times: u64 = 0;
+        loop {
+            match fetch().await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    if e.is::<TaskError>() || retry_times >= self.max_retry_times {
+                        return Err(e);
+                    }
+                    retry_times = retry_times.saturating_add(1);
+                    debug!("[fast-sync] Fetch error: {:?}, retry {}", e, retry_times);
+                    self.event_handle.on_retry();
+                    Delay::new(Duration::from_millis(self.delay_milliseconds_on_error)).await;
+                }
+            }
+        }
+    }
+
+    async fn fetch_blocks(&self, block_ids: Vec<HashValue>) -> Result<Vec<Block>> {
+        let blocks = self
+            .retry(|| self.fetcher.fetch_blocks(block_ids.clone()))
+            .await?;
+        ensure!(
+            blocks.len() == block_ids.len(),
+            "Fetch {} blocks, but got {}",
+            block_ids.len(),
+            blocks.len()
+        );
+        blocks
+            .into_iter()
+            .zip(block_ids)
+            .map(|((block, _peer), block_id)| {
+                ensure!(
+                    block.id() == block_id,
+                    "Fetch block {}, but got {}",
+                    block_id,
+                    block.id()
+                );
+                StaticVerifier::verify_body_hash(&block)?;
+                Ok(block)
+            })
+            .collect()
+    }
+
+    async fn fetch_epoch(&self, state_root: HashValue) -> Result<Epoch> {
+        let access_path = AccessPath::resource_access_path(genesis_address(), Epoch::struct_tag());
+        let state_with_proof = self
+            .retry(|| {
+                self.fetcher
+                    .fetch_state_with_proof(state_root, access_path.clone())
+            })
+            .await?;
+        let state = state_with_proof
+            .state
+            .ok_or_else(|| format_err!("Can not find epoch at state {}", state_root))?;
+        bcs_ext::from_bytes(state.as_slice())
+    }
+
+    /// Fetch the nodes on the paths from the root of the block accumulator `info`
+    /// to the leaves in `[start, end]`, return the leaves.
+    /// The nodes are saved if `save` is true, so the leaves can be read by the local accumulator.
+    async fn sync_block_accumulator(
+        &self,
+        info: &AccumulatorInfo,
+        start: u64,
+        end: u64,
+        save: bool,
+    ) -> Result<BTreeMap<BlockNumber, HashValue>> {
+        ensure!(
+            start <= end && end < info.num_leaves,
+            "Invalid leaf range [{}, {}] of accumulator with {} leaves",
+            start,
+            end,
+            info.num_leaves
+        );
+        let store = self
+            .storage
+            .get_accumulator_store(AccumulatorStoreType::Block);
+        let mut leaves = BTreeMap::new();
+        let mut pending = vec![(
+            info.accumulator_root,
+            NodeIndex::root_from_leaf_count(info.num_leaves),
+        )];
+        while !pending.is_empty() {
+            let batch = pending.split_off(pending.len().saturating_sub(NODE_BATCH_SIZE));
+            let mut internal_indexes = vec![];
+            for (hash, index) in batch {
+                match index.to_leaf_index() {
+                    Some(leaf_index) => {
+                        leaves.insert(leaf_index, hash);
+                    }
+                    None => internal_indexes.push((hash, index)),
+                }
+            }
+            let nodes = try_join_all(
+                internal_indexes
+                    .into_iter()
+                    .map(|(hash, index)| self.fetch_accumulator_node(hash, index)),
+            )
+            .await?;
+            for node in nodes.iter() {
+                let internal = match node {
+                    AccumulatorNode::Internal(internal) => internal,
+                    _ => bail!("Accumulator node {} should be internal node", node.hash()),
+                };
+                let index = internal.index();
+                for (child_index, child_hash) in [
+                    (index.left_child(), internal.left()),
+                    (index.right_child(), internal.right()),
+                ] {
+                    if child_hash == *ACCUMULATOR_PLACEHOLDER_HASH {
+                        continue;
+                    }
+                    if let (Some(first_leaf), Some(last_leaf)) = (
+                        child_index.left_most_child().to_leaf_index(),
+                        child_index.right_most_child().to_leaf_index(),
+                    ) {
+                        if first_leaf <= end && last_leaf >= start {
+                            pending.push((child_hash, child_index));
+                        }
+                    }
+                }
+                self.event_handle.on_item();
+            }
+            if save && !nodes.is_empty() {
+                store.save_nodes(nodes)?;
+            }
+        }
+        ensure!(
+            leaves.len() as u64 == end.saturating_sub(start).saturating_add(1),
+            "Can not find all leaves in [{}, {}] of accumulator {}",
+            start,
+            end,
+            info.accumulator_root
+        );
+        Ok(leaves)
+    }
+
+    async fn fetch_accumulator_node(
+        &self,
+        hash: HashValue,
+        index: NodeIndex,
+    ) -> Result<AccumulatorNode> {
+        self.retry(|| {
+            self.fetcher
+                .fetch_accumulator_node(hash, AccumulatorStoreType::Block)
+                .and_then(move |node| async move {
+                    ensure!(
+                        node.hash() == hash && node.index() == index,
+                        "Accumulator node {:?} mismatch with hash {} at {:?}",
+                        node,
+                        hash,
+                        index
+                    );
+                    Ok(node)
+                })
+        })
+        .await
+    }
+
+    /// Download all nodes of the state trees from the roots, and the table infos.
+    /// The recently visited nodes are kept in memory, so the nodes shared by the roots are
+    /// mostly fetched once.
+    async fn sync_state(&self, state_roots: Vec<HashValue>) -> Result<()> {
+        self.event_handle
+            .on_start("FastSyncStateTask".to_string(), None);
+        let mut visited = LruCache::new(MAX_VISITED_STATE_NODES);
+        let mut pending = vec![];
+        for root in state_roots {
+            if root != *SPARSE_MERKLE_PLACEHOLDER_HASH && visited.put(root, ()).is_none() {
+                pending.push((root, StateTreeKind::Account));
+            }
+        }
+        let mut table_handles = vec![];
+        while !pending.is_empty() {
+            let batch = pending.split_off(pending.len().saturating_sub(NODE_BATCH_SIZE));
+            let nodes = try_join_all(
+                batch
+                    .iter()
+                    .map(|(hash, kind)| self.fetch_state_node(*hash, kind)),
+            )
+            .await?;
+            let mut write_nodes = BTreeMap::new();
+            for ((hash, _), verified_node) in batch.into_iter().zip(nodes) {
+                for (child, kind) in verified_node.children {
+                    if visited.put(child, ()).is_none() {
+                        pending.push((child, kind));
+                    }
+                }
+                table_handles.extend(verified_node.table_handle);
+                write_nodes.insert(hash, verified_node.node);
+                self.event_handle.on_item();
+            }
+            self.storage.write_nodes(write_nodes)?;
+            self.event_handle.on_sub_task();
+        }
+        self.sync_table_infos(table_handles).await?;
+        self.event_handle.on_finish("FastSyncStateTask".to_string());
+        Ok(())
+    }
+
+    async fn fetch_state_node(
+        &self,
+        hash: HashValue,
+        kind: &StateTreeKind,
+    ) -> Result<VerifiedStateNode> {
+        self.retry(|| {
+            let kind = kind.clone();
+            self.fetcher
+                .fetch_state_node(hash)
+                .and_then(move |state_node| async move {
+                    let state_node = state_node
+                        .ok_or_else(|| format_err!("Can not find state node {}", hash))?;
+                    kind.verify(hash, state_node)
+                })
+        })
+        .await
+    }
+
+    /// The table infos are not in the state tree, they are required to decode table items.
+    async fn sync_table_infos(&self, table_handles: Vec<TableHandle>) -> Result<()> {
+        let mut table_infos = vec![];
+        for handles in table_handles.chunks(NODE_BATCH_SIZE) {
+            let infos = try_join_all(
+                handles
+                    .iter()
+                    .map(|handle| self.retry(|| self.fetcher.fetch_table_info(*handle))),
+            )
+            .await?;
+            for (handle, info) in handles.iter().zip(infos) {
+                match info {
+                    Some(info) => table_infos.push((*handle, info)),
+                    None => warn!("[fast-sync] Can not find table info of {:?}", handle),
+                }
+            }
+        }
+        self.storage.save_table_infos(table_infos)
+    }
+}
@@ -4,9 +4,11 @@
 #![allow(clippy::integer_arithmetic)]
 use crate::tasks::block_sync_task::SyncBlockData;
 use crate::tasks::mock::{ErrorStrategy, MockBlockIdFetcher, SyncNodeMocker};
+use crate::tasks::state_sync_task::verify_accumulator_info;
 use crate::tasks::{
-    full_sync_task, AccumulatorCollector, AncestorCollector, BlockAccumulatorSyncTask,
-    BlockCollector, BlockFetcher, BlockLocalStore, BlockSyncTask, FindAncestorTask, SyncFetcher,
+    fast_sync_task, full_sync_task, AccumulatorCollector, AncestorCollector,
+    BlockAccumulatorSyncTask, BlockCollector, BlockFetcher, BlockLocalStore, BlockSyncTask,
+    FindAncestorTask, SyncFetcher,
 };
 use crate::verified_rpc_client::RpcVerifyError;
 use anyhow::Context;
@@ -27,6 +29,7 @@ use starcoin_config::{BuiltinNetworkID, ChainNetwork};
 use starcoin_crypto::HashValue;
 use starcoin_genesis::Genesis;
 use starcoin_logger::prelude::*;
+use starcoin_state_tree::StateNodeStore;
 use starcoin_storage::BlockStore;
 use starcoin_sync_api::SyncTarget;
 use starcoin_types::{
@@ -119,6 +122,140 @@ pub async fn test_full_sync_new_node() -> Result<()> {
     Ok(())
 }
 
+#[stest::test(timeout = 120)]
+pub async fn test_fast_sync_new_node() -> Result<()> {
+    let net1 = ChainNetwork::new_builtin(BuiltinNetworkID::Test);
+    let mut node1 = SyncNodeMocker::new(net1, 1, 10)?;
+    // the epoch of test network has 48 blocks.
+    node1.produce_block(60)?;
+
+    let arc_node1 = Arc::new(node1);
+
+    let net2 = ChainNetwork::new_builtin(BuiltinNetworkID::Test);
+
+    let node2 = SyncNodeMocker::new(net2.clone(), 1, 10)?;
+
+    let target = arc_node1.sync_target();
+
+    let current_block_header = node2.chain().current_header();
+
+    let storage = node2.chain().get_storage();
+    let (sender_1, receiver_1) = unbounded();
+    let (sender_2, _receiver_2) = unbounded();
+    let (sender_3, mut receiver_3) = unbounded();
+    let (sync_task, _task_handle, task_event_counter) = fast_sync_task(
+        current_block_header.id(),
+        target.clone(),
+        false,
+        net2.time_service(),
+        storage.clone(),
+        sender_1,
+        arc_node1.clone(),
+        sender_2,
+        sender_3,
+        DummyNetworkService::default(),
+        5,
+        15,
+        None,
+        None,
+    )?;
+    let join_handle = node2.process_block_connect_event(receiver_1).await;
+    let branch = sync_task.await?;
+    let node2 = join_handle.await;
+    let current_block_header = node2.chain().current_header();
+    assert_eq!(branch.current_header().id(), target.target_id.id());
+    assert_eq!(target.target_id.id(), current_block_header.id());
+
+    let pivot = receiver_3
+        .try_next()?
+        .expect("FastSyncDoneEvent should be sent")
+        .pivot;
+    assert!(pivot.number > 1 && pivot.number < target.target_id.number());
+    assert_eq!(
+        Some(pivot.id),
+        arc_node1.chain().get_hash_by_number(pivot.number)?
+    );
+    let snapshot_range = storage
+        .get_snapshot_range()?
+        .expect("Snapshot range should exist");
+    assert_eq!(snapshot_range.get_end(), pivot.number);
+    let pivot_header = storage
+        .get_block_header_by_hash(pivot.id)?
+        .expect("Pivot block should exist");
+    assert!(StateNodeStore::get(storage.as_ref(), &pivot_header.state_root())?.is_some());
+
+    let reports = task_event_counter.get_reports();
+    reports
+        .iter()
+        .for_each(|report| debug!("reports: {}", report));
+    Ok(())
+}
+
+#[stest::test(timeout = 120)]
+pub async fn test_fast_sync_fallback_to_full_sync() -> Result<()> {
+    let net1 = ChainNetwork::new_builtin(BuiltinNetworkID::Test);
+    let mut node1 = SyncNodeMocker::new(net1, 1, 0)?;
+    node1.produce_block(10)?;
+
+    let arc_node1 = Arc::new(node1);
+
+    let net2 = ChainNetwork::new_builtin(BuiltinNetworkID::Test);
+
+    let node2 = SyncNodeMocker::new(net2.clone(), 1, 0)?;
+
+    let target = arc_node1.sync_target();
+
+    let current_block_header = node2.chain().current_header();
+
+    let storage = node2.chain().get_storage();
+    let (sender_1, receiver_1) = unbounded();
+    let (sender_2, _receiver_2) = unbounded();
+    let (sender_3, mut receiver_3) = unbounded();
+    // the pivot is in the first epoch, so fall back to full sync.
+    let (sync_task, _task_handle, _task_event_counter) = fast_sync_task(
+        current_block_header.id(),
+        target.clone(),
+        false,
+        net2.time_service(),
+        storage.clone(),
+        sender_1,
+        arc_node1.clone(),
+        sender_2,
+        sender_3,
+        DummyNetworkService::default(),
+        5,
+        15,
+        None,
+        None,
+    )?;
+    let join_handle = node2.process_block_connect_event(receiver_1).await;
+    let branch = sync_task.await?;
+    let node2 = join_handle.await;
+    assert_eq!(branch.current_header().id(), target.target_id.id());
+    assert_eq!(target.target_id.id(), node2.chain().current_header().id());
+    assert!(receiver_3.try_next()?.is_none());
+    assert!(storage.get_snapshot_range()?.is_none());
+    Ok(())
+}
+
+#[stest::test]
+async fn test_verify_accumulator_info() -> Result<()> {
+    let accumulator = MerkleAccumulator::new_empty(Arc::new(MockAccumulatorStore::new()));
+    for i in 0..20u64 {
+        accumulator.append(&[HashValue::sha3_256_of(&i.to_be_bytes())])?;
+        let info = accumulator.get_info();
+        verify_accumulator_info(&info)?;
+
+        let mut invalid_info = info.clone();
+        invalid_info.frozen_subtree_roots[0] = HashValue::random();
+        assert!(verify_accumulator_info(&invalid_info).is_err());
+        let mut invalid_info = info;
+        invalid_info.num_leaves += 1;
+        assert!(verify_accumulator_info(&invalid_info).is_err());
+    }
+    Ok(())
+}
+
 #[stest::test]
 pub async fn test_sync_invalid_target() -> Result<()> {
     let net1 = ChainNetwork::new_builtin(BuiltinNetworkID::Test);
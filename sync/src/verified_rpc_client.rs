@@ -12,15 +12,19 @@ use starcoin_crypto::hash::HashValue;
 use starcoin_logger::prelude::*;
 use starcoin_network_rpc_api::{
     gen_client::NetworkRpcClient, BlockBody, GetAccumulatorNodeByNodeHash, GetBlockHeadersByNumber,
-    GetBlockIds, GetTxnsWithHash, RawRpcClient,
+    GetBlockIds, GetStateWithProof, GetTableInfo, GetTxnsWithHash, RawRpcClient,
 };
+use starcoin_state_api::StateWithProof;
 use starcoin_state_tree::StateNode;
+use starcoin_types::access_path::AccessPath;
 use starcoin_types::block::Block;
+use starcoin_types::compact_block::CompactBlock;
 use starcoin_types::transaction::{SignedUserTransaction, Transaction};
 use starcoin_types::{
     block::{BlockHeader, BlockInfo, BlockNumber},
     transaction::TransactionInfo,
 };
+use starcoin_vm_types::state_store::table::{TableHandle, TableInfo};
 use std::fmt::Debug;
 use std::time::Instant;
 use thiserror::Error;
@@ -358,6 +362,37 @@ impl VerifiedRpcClient {
         }
     }
 
+    /// Get the state at `access_path` with proof, the proof is verified with `state_root`.
+    pub async fn get_state_with_proof(
+        &self,
+        state_root: HashValue,
+        access_path: AccessPath,
+    ) -> Result<(PeerId, StateWithProof)> {
+        let peer_id = self.select_a_peer()?;
+        let state_with_proof = self
+            .client
+            .get_state_with_proof(
+                peer_id.clone(),
+                GetStateWithProof {
+                    state_root,
+                    access_path: access_path.clone(),
+                },
+            )
+            .await?;
+        state_with_proof
+            .verify(state_root, access_path)
+            .map_err(|e| RpcVerifyError::new(peer_id.clone(), e.to_string()))?;
+        Ok((peer_id, state_with_proof))
+    }
+
+    /// The table info is not in the state tree, so it can not be verified.
+    pub async fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
+        let peer_id = self.select_a_peer()?;
+        self.client
+            .get_state_table_info(peer_id, GetTableInfo(handle.0))
+            .await
+    }
+
     pub async fn get_block_ids(
         &self,
         peer_id: Option<PeerId>,
@@ -411,4 +446,30 @@ impl VerifiedRpcClient {
             })
             .collect())
     }
+
+    /// Get the compact block of the announced block from the peer.
+    pub async fn get_compact_block(
+        &self,
+        peer_id: PeerId,
+        id: HashValue,
+    ) -> Result<Option<CompactBlock>> {
+        let compact_block = self
+            .client
+            .get_compact_blocks(peer_id.clone(), vec![id])
+            .await?
+            .pop()
+            .flatten();
+        match compact_block {
+            Some(compact_block) if compact_block.header.id() != id => Err(RpcVerifyError::new(
+                peer_id,
+                format!(
+                    "request compact block with id: {}, but got block: {}",
+                    id,
+                    compact_block.header.id()
+                ),
+            )
+            .into()),
+            compact_block => Ok(compact_block),
+        }
+    }
 }
@@ -23,6 +23,111 @@ pub struct TxPoolStatus {
     pub is_full: bool,
 }
 
+/// Lifecycle status of a user txn, from the pool to the finality on the main chain.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum TxnLifecycleStatus {
+    /// In the pool, waiting to be packed into a block.
+    Pending,
+    /// Replaced in the pool by another txn of the same sender and sequence number.
+    Replaced { by: HashValue },
+    /// Not accepted by the pool.
+    Rejected { reason: String },
+    /// Dropped by the pool limits.
+    Dropped,
+    /// Removed from the pool as invalid when packing a block.
+    Invalid,
+    /// Removed from the pool on request.
+    Canceled,
+    /// Removed from the pool as stale, such as expired or its sequence number is used by another txn.
+    Culled,
+    /// Included in a block of the main chain, the block is counted in the `confirmations`.
+    Included {
+        block_hash: HashValue,
+        block_number: u64,
+        confirmations: u64,
+    },
+    /// Included in a block of the main chain, and confirmed by enough blocks.
+    Finalized {
+        block_hash: HashValue,
+        block_number: u64,
+        confirmations: u64,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TxnStatusInfo {
+    pub txn_hash: HashValue,
+    pub sender: AccountAddress,
+    pub status: TxnLifecycleStatus,
+}
+
+/// Suggested gas unit prices for the transactions of different priority.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GasPriceEstimate {
+    pub low: u64,
+    pub medium: u64,
+    pub high: u64,
+}
+
+/// Gas usage and gas unit price percentiles of a block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BlockFeeHistory {
+    pub number: u64,
+    pub block_hash: HashValue,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub txn_count: u64,
+    /// Gas unit prices of the user txns at the requested percentiles, 0 if the block has no user txn.
+    pub gas_price_percentiles: Vec<u64>,
+}
+
+/// Where a txn in the pool comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum TxnOrigin {
+    /// Submitted to this node.
+    Local,
+    /// Re-imported from a block retracted from the main chain.
+    Retracted,
+    /// Received from the peers.
+    Remote,
+}
+
+/// Why a txn in the pool can not be packed into the next block.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum TxnFutureReason {
+    /// Waiting for the txns before it, `expected` is the next sequence number the sender can pack.
+    SeqNumberGap { expected: u64 },
+    /// The txn is expired, it will be culled from the pool.
+    Expired,
+    /// The sequence number is already used on chain, it will be culled from the pool.
+    Stale,
+}
+
+/// A txn in the pool with its scoring and readiness.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolTxnInfo {
+    pub txn: SignedUserTransaction,
+    /// The score used to order the ready txns and to evict txns when the pool is full.
+    pub score: u64,
+    /// The local timestamp in milliseconds when the txn entered the pool.
+    pub insertion_time: u64,
+    pub origin: TxnOrigin,
+    /// `None` if the txn is ready.
+    pub future_reason: Option<TxnFutureReason>,
+}
+
+/// The txns of a sender in the pool, in ascending order of sequence number.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SenderPoolTxns {
+    pub sender: AccountAddress,
+    /// The sequence number of the sender on chain.
+    pub sequence_number: u64,
+    /// The txns which can be packed into the next block.
+    pub ready: Vec<PoolTxnInfo>,
+    /// The txns blocked by the sequence number or the expiration.
+    pub future: Vec<PoolTxnInfo>,
+}
+
 pub trait TxPoolSyncService: Clone + Send + Sync + Unpin {
     fn add_txns(
         &self,
@@ -53,6 +158,13 @@ pub trait TxPoolSyncService: Clone + Send + Sync + Unpin {
 
     fn subscribe_pending_txn(&self) -> mpsc::UnboundedReceiver<Arc<[HashValue]>>;
 
+    /// subscribe the lifecycle status changes of txns, including the inclusion and the finality.
+    fn subscribe_txn_status(&self) -> mpsc::UnboundedReceiver<Arc<[TxnStatusInfo]>>;
+
+    /// Lifecycle status of a txn which is in the pool, recently removed from the pool, or on the main chain.
+    /// Returns `None` if the txn is unknown.
+    fn txn_status(&self, txn_hash: HashValue) -> Result<Option<TxnStatusInfo>>;
+
     /// notify txpool about chain new blocks
     /// `enacted` is the blocks which enter the main chain.
     /// `retracted` is the blocks which belongs to previous main chain.
@@ -61,12 +173,22 @@ pub trait TxPoolSyncService: Clone + Send + Sync + Unpin {
     /// Tx Pool status
     fn status(&self) -> TxPoolStatus;
 
+    /// Suggest gas unit prices by the txns included in the recent blocks and the pool backlog.
+    fn gas_price_estimate(&self) -> GasPriceEstimate;
+
+    /// Gas usage and gas unit price `percentiles` of at most `block_count` recent blocks,
+    /// in ascending order of block number, `percentiles` should be in [0, 100].
+    fn fee_history(&self, block_count: u64, percentiles: Vec<f64>) -> Result<Vec<BlockFeeHistory>>;
+
     fn find_txn(&self, hash: &HashValue) -> Option<SignedUserTransaction>;
     fn txns_of_sender(
         &self,
         sender: &AccountAddress,
         max_len: Option<usize>,
     ) -> Vec<SignedUserTransaction>;
+
+    /// All the txns in the pool grouped by sender, split into the ready and the future ones.
+    fn content(&self) -> Vec<SenderPoolTxns>;
 }
 
 #[derive(Clone, Debug)]
@@ -4,7 +4,10 @@
 use anyhow::Result;
 use futures_channel::mpsc;
 use starcoin_crypto::hash::HashValue;
-use starcoin_txpool_api::{TxPoolStatus, TxPoolSyncService, TxnStatusFullEvent};
+use starcoin_txpool_api::{
+    BlockFeeHistory, GasPriceEstimate, SenderPoolTxns, TxPoolStatus, TxPoolSyncService,
+    TxnStatusFullEvent, TxnStatusInfo,
+};
 use starcoin_types::{
     account_address::AccountAddress, block::Block, transaction, transaction::SignedUserTransaction,
 };
@@ -82,6 +85,12 @@ impl TxPoolSyncService for MockTxPoolService {
     fn subscribe_pending_txn(&self) -> mpsc::UnboundedReceiver<Arc<[HashValue]>> {
         todo!()
     }
+    fn subscribe_txn_status(&self) -> mpsc::UnboundedReceiver<Arc<[TxnStatusInfo]>> {
+        mpsc::unbounded().1
+    }
+    fn txn_status(&self, _txn_hash: HashValue) -> Result<Option<TxnStatusInfo>> {
+        Ok(None)
+    }
     fn chain_new_block(&self, _enacted: Vec<Block>, _retracted: Vec<Block>) -> Result<()> {
         Ok(())
     }
@@ -90,6 +99,22 @@ impl TxPoolSyncService for MockTxPoolService {
         unimplemented!()
     }
 
+    fn gas_price_estimate(&self) -> GasPriceEstimate {
+        GasPriceEstimate {
+            low: 1,
+            medium: 1,
+            high: 1,
+        }
+    }
+
+    fn fee_history(
+        &self,
+        _block_count: u64,
+        _percentiles: Vec<f64>,
+    ) -> Result<Vec<BlockFeeHistory>> {
+        Ok(vec![])
+    }
+
     fn find_txn(&self, _hash: &HashValue) -> Option<SignedUserTransaction> {
         unimplemented!()
     }
@@ -101,6 +126,10 @@ impl TxPoolSyncService for MockTxPoolService {
     ) -> Vec<SignedUserTransaction> {
         todo!()
     }
+
+    fn content(&self) -> Vec<SenderPoolTxns> {
+        vec![]
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,170 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Estimate the gas unit price by the transactions included in the recent blocks of the main chain.
+
+use anyhow::{ensure, Result};
+use parking_lot::RwLock;
+use starcoin_crypto::HashValue;
+use starcoin_txpool_api::{BlockFeeHistory, GasPriceEstimate};
+use starcoin_types::block::Block;
+use std::collections::VecDeque;
+
+/// Max number of the recent blocks kept for the fee history.
+pub(crate) const MAX_FEE_HISTORY_BLOCKS: usize = 128;
+/// Number of the recent blocks used to suggest the gas price.
+const SUGGEST_BLOCKS: usize = 20;
+/// The blocks are congested if they used more than this percent of the block gas limit.
+const CONGESTED_GAS_USAGE_PERCENT: u64 = 80;
+
+/// Gas usage and the gas unit prices of a block.
+#[derive(Clone, Debug)]
+pub(crate) struct BlockFeeStats {
+    number: u64,
+    block_hash: HashValue,
+    gas_used: u64,
+    gas_limit: u64,
+    /// Gas unit prices of the user transactions in the block, in ascending order.
+    gas_prices: Vec<u64>,
+}
+
+impl BlockFeeStats {
+    pub(crate) fn new(block: &Block, gas_limit: u64) -> Self {
+        let mut gas_prices: Vec<u64> = block
+            .transactions()
+            .iter()
+            .map(|txn| txn.gas_unit_price())
+            .collect();
+        gas_prices.sort_unstable();
+        Self {
+            number: block.header().number(),
+            block_hash: block.id(),
+            gas_used: block.header().gas_used(),
+            gas_limit,
+            gas_prices,
+        }
+    }
+
+    fn to_history(&self, percentiles: &[f64]) -> BlockFeeHistory {
+        BlockFeeHistory {
+            number: self.number,
+            block_hash: self.block_hash,
+            gas_used: self.gas_used,
+            gas_limit: self.gas_limit,
+            txn_count: self.gas_prices.len() as u64,
+            gas_price_percentiles: percentiles
+                .iter()
+                .map(|p| percentile(self.gas_prices.as_slice(), *p))
+                .collect(),
+        }
+    }
+}
+
+/// The nearest-rank percentile of the `sorted` values, `p` is in [0, 100], return 0 if `sorted` is empty.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+#[derive(Debug)]
+pub(crate) struct FeeEstimator {
+    /// Recent blocks of the main chain, in ascending order of block number.
+    blocks: RwLock<VecDeque<BlockFeeStats>>,
+    max_blocks: usize,
+}
+
+impl FeeEstimator {
+    pub(crate) fn new(max_blocks: usize) -> Self {
+        Self {
+            blocks: RwLock::new(VecDeque::with_capacity(max_blocks)),
+            max_blocks,
+        }
+    }
+
+    /// Append a new block of the main chain, the kept blocks at or after its number are replaced.
+    pub(crate) fn push(&self, stats: BlockFeeStats) {
+        let mut blocks = self.blocks.write();
+        while matches!(blocks.back(), Some(last) if last.number >= stats.number) {
+            blocks.pop_back();
+        }
+        blocks.push_back(stats);
+        while blocks.len() > self.max_blocks {
+            blocks.pop_front();
+        }
+    }
+
+    /// Remove a block which is retracted from the main chain.
+    pub(crate) fn retract(&self, block_hash: HashValue) {
+        self.blocks
+            .write()
+            .retain(|stats| stats.block_hash != block_hash);
+    }
+
+    /// Gas usage and gas unit price `percentiles` of at most `block_count` recent blocks,
+    /// in ascending order of block number.
+    pub(crate) fn fee_history(
+        &self,
+        block_count: u64,
+        percentiles: &[f64],
+    ) -> Result<Vec<BlockFeeHistory>> {
+        ensure!(
+            percentiles.iter().all(|p| (0.0..=100.0).contains(p)),
+            "percentiles should be in [0, 100], got: {:?}",
+            percentiles
+        );
+        let blocks = self.blocks.read();
+        let skip = blocks.len().saturating_sub(block_count as usize);
+        Ok(blocks
+            .iter()
+            .skip(skip)
+            .map(|stats| stats.to_history(percentiles))
+            .collect())
+    }
+
+    /// Suggest the gas unit prices by the recent blocks and the `pending_txn_count` of the pool.
+    /// All the suggestions fall back to `min_gas_price` while the blocks have spare room.
+    pub(crate) fn estimate(&self, min_gas_price: u64, pending_txn_count: u64) -> GasPriceEstimate {
+        let blocks = self.blocks.read();
+        let recent = blocks
+            .iter()
+            .skip(blocks.len().saturating_sub(SUGGEST_BLOCKS));
+        let mut gas_used = 0u64;
+        let mut gas_limit = 0u64;
+        let mut block_count = 0u64;
+        let mut gas_prices = vec![];
+        for stats in recent {
+            gas_used = gas_used.saturating_add(stats.gas_used);
+            gas_limit = gas_limit.saturating_add(stats.gas_limit);
+            block_count += 1;
+            gas_prices.extend_from_slice(stats.gas_prices.as_slice());
+        }
+        if gas_prices.is_empty() {
+            return GasPriceEstimate {
+                low: min_gas_price,
+                medium: min_gas_price,
+                high: min_gas_price,
+            };
+        }
+        gas_prices.sort_unstable();
+
+        let congested = gas_limit > 0
+            && gas_used.saturating_mul(100)
+                >= gas_limit.saturating_mul(CONGESTED_GAS_USAGE_PERCENT);
+        // the pool has enough pending txns to fill a block.
+        let txns_per_block = (gas_prices.len() as u64 / block_count).max(1);
+        let backlog = pending_txn_count >= txns_per_block;
+        let p = |p: f64| percentile(gas_prices.as_slice(), p);
+        let (low, medium, high) = match (congested, backlog) {
+            (true, true) => (p(50.0), p(75.0), p(100.0)),
+            (true, false) | (false, true) => (p(25.0), p(50.0), p(90.0)),
+            (false, false) => (min_gas_price, min_gas_price, p(50.0)),
+        };
+        let low = low.max(min_gas_price);
+        let medium = medium.max(low);
+        let high = high.max(medium);
+        GasPriceEstimate { low, medium, high }
+    }
+}
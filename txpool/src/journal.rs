@@ -0,0 +1,208 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Journal of the transactions accepted by the pool, so they survive node restarts.
+
+use anyhow::{format_err, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use starcoin_config::TxPoolJournalMode;
+use starcoin_crypto::hash::HashValue;
+use starcoin_types::transaction::SignedUserTransaction;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Every record of the journal file is a little endian u32 length followed by a bcs encoded entry.
+const RECORD_LEN_BYTES: usize = 4;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    /// Timestamp in seconds when the transaction is accepted by the pool.
+    received_at: u64,
+    txn: SignedUserTransaction,
+}
+
+#[derive(Default)]
+struct JournalState {
+    writer: Option<File>,
+    /// Receive time of the journaled transactions, only the pool keeps the transactions.
+    received: HashMap<HashValue, u64>,
+}
+
+/// An append only journal file of pool transactions.
+/// It is rewritten with the transactions still in the pool when rotating,
+/// so mined, dropped and expired transactions are removed from it.
+pub(crate) struct TxPoolJournal {
+    path: PathBuf,
+    mode: TxPoolJournalMode,
+    max_age: u64,
+    state: Mutex<JournalState>,
+}
+
+impl std::fmt::Debug for TxPoolJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "journal: {:?}, mode: {}", self.path, self.mode)
+    }
+}
+
+impl TxPoolJournal {
+    pub(crate) fn new(path: PathBuf, mode: TxPoolJournalMode, max_age: u64) -> Self {
+        Self {
+            path,
+            mode,
+            max_age,
+            state: Mutex::new(JournalState::default()),
+        }
+    }
+
+    /// Whether a transaction accepted by the pool should be journaled,
+    /// `is_local` is true if the transaction is submitted to this node instead of received from peers.
+    pub(crate) fn should_journal(&self, is_local: bool) -> bool {
+        match self.mode {
+            TxPoolJournalMode::Disable => false,
+            TxPoolJournalMode::Local => is_local,
+            TxPoolJournalMode::All => true,
+        }
+    }
+
+    fn is_expired(&self, received_at: u64, now: u64) -> bool {
+        now.saturating_sub(received_at) > self.max_age
+    }
+
+    /// Load the transactions which are not expired from the journal file.
+    /// A broken tail, for example written by a crashed node, is ignored.
+    pub(crate) fn load(&self, now: u64) -> Result<Vec<SignedUserTransaction>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let data = fs::read(&self.path)?;
+        let mut state = self.state.lock();
+        let mut txns = vec![];
+        let mut offset = 0usize;
+        while offset < data.len() {
+            let entry = match Self::decode_record(&data, &mut offset) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!(
+                        "Ignore broken txpool journal {:?} from offset {}: {}",
+                        self.path, offset, e
+                    );
+                    break;
+                }
+            };
+            if self.is_expired(entry.received_at, now) {
+                continue;
+            }
+            let id = entry.txn.id();
+            if state.received.contains_key(&id) {
+                continue;
+            }
+            state.received.insert(id, entry.received_at);
+            txns.push(entry.txn);
+        }
+        Ok(txns)
+    }
+
+    /// Append the transactions to the journal file, the ones already journaled are skipped.
+    pub(crate) fn insert(&self, txns: Vec<SignedUserTransaction>, now: u64) -> Result<()> {
+        let mut state = self.state.lock();
+        let mut buf = vec![];
+        for txn in txns {
+            let id = txn.id();
+            if state.received.contains_key(&id) {
+                continue;
+            }
+            Self::encode_record(
+                &JournalEntry {
+                    received_at: now,
+                    txn,
+                },
+                &mut buf,
+            )?;
+            state.received.insert(id, now);
+        }
+        if buf.is_empty() {
+            return Ok(());
+        }
+        if state.writer.is_none() {
+            state.writer = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        if let Some(writer) = state.writer.as_mut() {
+            writer.write_all(&buf)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the journal file with the journaled transactions which are still in the pool
+    /// and not expired, `find` gets the transaction from the pool by hash.
+    /// Return the number of transactions in the new journal.
+    pub(crate) fn rotate<F>(&self, now: u64, find: F) -> Result<usize>
+    where
+        F: Fn(&HashValue) -> Option<SignedUserTransaction>,
+    {
+        let mut state = self.state.lock();
+        let mut buf = vec![];
+        let mut received = HashMap::new();
+        for (id, received_at) in state.received.iter() {
+            if self.is_expired(*received_at, now) {
+                continue;
+            }
+            if let Some(txn) = find(id) {
+                Self::encode_record(
+                    &JournalEntry {
+                        received_at: *received_at,
+                        txn,
+                    },
+                    &mut buf,
+                )?;
+                received.insert(*id, *received_at);
+            }
+        }
+        // close the old file before replacing it.
+        state.writer = None;
+        let tmp_path = self.path.with_extension("journal.new");
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&buf)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        state.writer = Some(OpenOptions::new().append(true).open(&self.path)?);
+        let count = received.len();
+        state.received = received;
+        debug!("Rotate txpool journal with {} transactions", count);
+        Ok(count)
+    }
+
+    fn encode_record(entry: &JournalEntry, buf: &mut Vec<u8>) -> Result<()> {
+        let bytes = bcs_ext::to_bytes(entry)?;
+        let len: u32 = bytes.len().try_into()?;
+        buf.extend_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn decode_record(data: &[u8], offset: &mut usize) -> Result<JournalEntry> {
+        let len_end = offset
+            .checked_add(RECORD_LEN_BYTES)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| format_err!("truncated record length"))?;
+        let len = u32::from_le_bytes(data[*offset..len_end].try_into()?) as usize;
+        let end = len_end
+            .checked_add(len)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| format_err!("truncated record"))?;
+        let entry = bcs_ext::from_bytes(&data[len_end..end])?;
+        *offset = end;
+        Ok(entry)
+    }
+}
@@ -27,6 +27,8 @@ use std::time::Duration;
 use tx_pool_service_impl::Inner;
 pub use tx_pool_service_impl::TxPoolService;
 
+mod fee_estimator;
+mod journal;
 mod metrics;
 mod pool;
 mod pool_client;
@@ -49,6 +51,8 @@ impl std::fmt::Debug for TxPoolActorService {
 
 const MIN_TXN_TO_PROPAGATE: usize = 256;
 const PROPAGATE_FOR_BLOCKS: u64 = 4;
+/// interval(s) of rewriting the txpool journal.
+const JOURNAL_ROTATE_INTERVAL: u64 = 60;
 
 impl TxPoolActorService {
     fn new(inner: Inner) -> Self {
@@ -95,26 +99,31 @@ impl ServiceFactory<Self> for TxPoolActorService {
         let storage = ctx.get_shared::<Arc<Storage>>()?;
         let node_config = ctx.get_shared::<Arc<NodeConfig>>()?;
         let vm_metrics = ctx.get_shared_opt::<VMMetrics>()?;
-        let txpool_service = ctx.get_shared_or_put(|| {
-            let startup_info = storage
-                .get_startup_info()?
-                .ok_or_else(|| format_err!("StartupInfo should exist when service init."))?;
-            let best_block = storage
-                .get_block_by_hash(startup_info.main)?
-                .ok_or_else(|| {
-                    format_err!(
-                        "best block id {} should exists in storage",
-                        startup_info.main
-                    )
-                })?;
-            let best_block_header = best_block.into_inner().0;
-            Ok(TxPoolService::new(
-                node_config,
-                storage,
-                best_block_header,
-                vm_metrics,
-            ))
-        })?;
+        let startup_info = storage
+            .get_startup_info()?
+            .ok_or_else(|| format_err!("StartupInfo should exist when service init."))?;
+        let best_block = storage
+            .get_block_by_hash(startup_info.main)?
+            .ok_or_else(|| {
+                format_err!(
+                    "best block id {} should exists in storage",
+                    startup_info.main
+                )
+            })?;
+        let best_block_header = best_block.into_inner().0;
+        let txpool_service = match ctx.get_shared_opt::<TxPoolService>()? {
+            // the service is restarted, rebuild the pool state left by the stopped service.
+            Some(txpool_service) => {
+                txpool_service.get_inner().reset(best_block_header);
+                txpool_service
+            }
+            None => {
+                let txpool_service =
+                    TxPoolService::new(node_config, storage, best_block_header, vm_metrics);
+                ctx.put_shared(txpool_service.clone())?;
+                txpool_service
+            }
+        };
         Ok(Self::new(txpool_service.get_inner()))
     }
 }
@@ -153,11 +162,17 @@ impl ActorService for TxPoolActorService {
             myself.try_propagate_txns(ctx)
         });
 
+        let inner = self.inner.clone();
+        ctx.run_interval(Duration::from_secs(JOURNAL_ROTATE_INTERVAL), move |_ctx| {
+            inner.rotate_journal()
+        });
+
         Ok(())
     }
 
     fn stopped(&mut self, ctx: &mut ServiceContext<Self>) -> Result<()> {
         ctx.unsubscribe::<SyncStatusChangeEvent>();
+        self.inner.rotate_journal();
         Ok(())
     }
 }
@@ -215,7 +230,7 @@ impl EventHandler<Self, PeerTransactionsMessage> for TxPoolActorService {
     fn handle_event(&mut self, msg: PeerTransactionsMessage, _ctx: &mut ServiceContext<Self>) {
         if self.is_synced() {
             // JUST need to keep at most once delivery.
-            let _ = self.inner.import_txns(msg.message.txns);
+            let _ = self.inner.import_txns(msg.message.txns, false);
         } else {
             //TODO should keep txn in a buffer, then execute after sync finished.
             debug!("[txpool] Ignore PeerTransactions event because the node has not been synchronized yet.");
@@ -160,6 +160,8 @@ pub struct VerifiedTransaction {
     sender: AccountAddress,
     priority: Priority,
     insertion_id: usize,
+    /// Local timestamp in milliseconds when the transaction is verified to enter the pool.
+    insertion_time: u64,
 }
 
 impl VerifiedTransaction {
@@ -177,6 +179,7 @@ impl VerifiedTransaction {
             sender,
             priority: Priority::Retracted,
             insertion_id: 0,
+            insertion_time: 0,
         }
     }
 
@@ -185,6 +188,11 @@ impl VerifiedTransaction {
         self.insertion_id
     }
 
+    /// Gets transaction insertion time in milliseconds.
+    pub(crate) fn insertion_time(&self) -> u64 {
+        self.insertion_time
+    }
+
     /// Gets wrapped `SignedUserTransaction`
     pub fn signed(&self) -> &transaction::SignedUserTransaction {
         &self.transaction
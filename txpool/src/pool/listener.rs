@@ -5,8 +5,10 @@ use std::{fmt, sync::Arc};
 
 use super::{TxStatus, VerifiedTransaction as Transaction};
 use futures_channel::mpsc;
+use linked_hash_map::LinkedHashMap;
 use starcoin_crypto::hash::HashValue as H256;
 use starcoin_logger::prelude::*;
+use starcoin_txpool_api::{TxnLifecycleStatus, TxnStatusInfo};
 use transaction_pool as tx_pool;
 use tx_pool::VerifiedTransaction;
 /// Transaction pool logger.
@@ -128,12 +130,19 @@ impl tx_pool::Listener<Transaction> for StatusLogger {
     }
 }
 
+/// Max number of the txns whose latest lifecycle status are kept by the notifier.
+const MAX_TXN_STATUSES: usize = 16384;
+
 /// Transactions pool notifier
 #[derive(Default)]
 pub struct TransactionsPoolNotifier {
     full_listeners: Vec<mpsc::UnboundedSender<Arc<[(H256, TxStatus)]>>>,
     pending_listeners: Vec<mpsc::UnboundedSender<Arc<[H256]>>>,
+    status_listeners: Vec<mpsc::UnboundedSender<Arc<[TxnStatusInfo]>>>,
     tx_statuses: Vec<(H256, TxStatus)>,
+    txn_status_changes: Vec<TxnStatusInfo>,
+    /// Latest lifecycle status of the recently seen txns, in the order of the last change.
+    latest_txn_statuses: LinkedHashMap<H256, TxnStatusInfo>,
 }
 
 impl TransactionsPoolNotifier {
@@ -147,8 +156,49 @@ impl TransactionsPoolNotifier {
         self.pending_listeners.push(f);
     }
 
+    /// Add new status listener to receive the lifecycle status changes.
+    pub fn add_status_listener(&mut self, f: mpsc::UnboundedSender<Arc<[TxnStatusInfo]>>) {
+        self.status_listeners.push(f);
+    }
+
+    /// Latest lifecycle status of a recently seen txn.
+    pub fn txn_status(&self, hash: &H256) -> Option<TxnStatusInfo> {
+        self.latest_txn_statuses.get(hash).cloned()
+    }
+
+    /// Record a lifecycle status change, it is sent to the status listeners on `notify`.
+    pub fn txn_status_changed(&mut self, info: TxnStatusInfo) {
+        self.latest_txn_statuses.insert(info.txn_hash, info.clone());
+        while self.latest_txn_statuses.len() > MAX_TXN_STATUSES {
+            self.latest_txn_statuses.pop_front();
+        }
+        self.txn_status_changes.push(info);
+    }
+
+    fn pool_status_changed(&mut self, tx: &Transaction, status: TxnLifecycleStatus) {
+        self.txn_status_changed(TxnStatusInfo {
+            txn_hash: tx.hash,
+            sender: *tx.sender(),
+            status,
+        });
+    }
+
+    fn is_included(&self, hash: &H256) -> bool {
+        matches!(
+            self.latest_txn_statuses.get(hash).map(|info| &info.status),
+            Some(TxnLifecycleStatus::Included { .. }) | Some(TxnLifecycleStatus::Finalized { .. })
+        )
+    }
+
     /// Notify listeners about all currently transactions.
     pub fn notify(&mut self) {
+        if !self.txn_status_changes.is_empty() {
+            let to_status_send: Arc<[TxnStatusInfo]> =
+                std::mem::take(&mut self.txn_status_changes).into();
+            self.status_listeners
+                .retain(|listener| listener.unbounded_send(to_status_send.clone()).is_ok());
+        }
+
         if self.tx_statuses.is_empty() {
             return;
         }
@@ -174,37 +224,55 @@ impl fmt::Debug for TransactionsPoolNotifier {
         fmt.debug_struct("TransactionsPoolNotifier")
             .field("full_listeners", &self.full_listeners.len())
             .field("pending_listeners", &self.pending_listeners.len())
+            .field("status_listeners", &self.status_listeners.len())
             .finish()
     }
 }
 
 impl tx_pool::Listener<Transaction> for TransactionsPoolNotifier {
-    fn added(&mut self, tx: &Arc<Transaction>, _old: Option<&Arc<Transaction>>) {
+    fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
         self.tx_statuses.push((tx.hash, TxStatus::Added));
+        self.pool_status_changed(tx, TxnLifecycleStatus::Pending);
+        if let Some(old) = old {
+            self.pool_status_changed(old, TxnLifecycleStatus::Replaced { by: tx.hash });
+        }
     }
 
     fn rejected<H: fmt::Debug + fmt::LowerHex>(
         &mut self,
         tx: &Arc<Transaction>,
-        _reason: &tx_pool::Error<H>,
+        reason: &tx_pool::Error<H>,
     ) {
         self.tx_statuses.push((tx.hash, TxStatus::Rejected));
+        self.pool_status_changed(
+            tx,
+            TxnLifecycleStatus::Rejected {
+                reason: reason.to_string(),
+            },
+        );
     }
 
     fn dropped(&mut self, tx: &Arc<Transaction>, _new: Option<&Transaction>) {
         self.tx_statuses.push((tx.hash, TxStatus::Dropped));
+        self.pool_status_changed(tx, TxnLifecycleStatus::Dropped);
     }
 
     fn invalid(&mut self, tx: &Arc<Transaction>) {
         self.tx_statuses.push((tx.hash, TxStatus::Invalid));
+        self.pool_status_changed(tx, TxnLifecycleStatus::Invalid);
     }
 
     fn canceled(&mut self, tx: &Arc<Transaction>) {
         self.tx_statuses.push((tx.hash, TxStatus::Canceled));
+        self.pool_status_changed(tx, TxnLifecycleStatus::Canceled);
     }
 
     fn culled(&mut self, tx: &Arc<Transaction>) {
         self.tx_statuses.push((tx.hash, TxStatus::Culled));
+        // the txns included by the new blocks are culled as their sequence numbers are used.
+        if !self.is_included(&tx.hash) {
+            self.pool_status_changed(tx, TxnLifecycleStatus::Culled);
+        }
     }
 }
 
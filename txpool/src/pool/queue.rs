@@ -12,7 +12,10 @@ use crate::{pool, pool::PoolTransaction};
 use futures_channel::mpsc;
 use parking_lot::RwLock;
 use starcoin_crypto::hash::HashValue;
-use starcoin_txpool_api::TxPoolStatus;
+use starcoin_txpool_api::{
+    PoolTxnInfo, SenderPoolTxns, TxPoolStatus, TxnFutureReason, TxnLifecycleStatus, TxnOrigin,
+    TxnStatusInfo,
+};
 use starcoin_types::{account_address::AccountAddress as Address, transaction};
 use std::{
     cmp,
@@ -308,6 +311,7 @@ impl TransactionQueue {
         let mut results = Vec::new();
         for transaction in transactions.into_iter() {
             let hash = transaction.hash();
+            let sender = transaction.signed().sender();
 
             if self.pool.read().find(&hash).is_some() {
                 results.push(Err(transaction::TransactionError::AlreadyImported));
@@ -331,6 +335,15 @@ impl TransactionQueue {
                 Ok(_) => Ok(()),
                 Err(err) => {
                     self.recently_rejected.insert(hash, &err);
+                    (self.pool.write().listener_mut().1)
+                        .0
+                        .txn_status_changed(TxnStatusInfo {
+                            txn_hash: hash,
+                            sender,
+                            status: TxnLifecycleStatus::Rejected {
+                                reason: err.to_string(),
+                            },
+                        });
                     Err(err)
                 }
             });
@@ -359,6 +372,75 @@ impl TransactionQueue {
             .collect()
     }
 
+    /// Returns all the transactions in the pool.
+    pub fn all_transactions(&self) -> Vec<Arc<pool::VerifiedTransaction>> {
+        let senders: Vec<_> = self.pool.read().senders().cloned().collect();
+        senders
+            .iter()
+            .flat_map(|sender| self.txns_of_sender(sender, usize::max_value()))
+            .collect()
+    }
+
+    /// Returns all the transactions in the pool grouped by sender, split into the ready ones
+    /// and the future ones by the same readiness checks as the pending set.
+    pub fn content<C>(&self, client: C, now: u64) -> Vec<SenderPoolTxns>
+    where
+        C: client::AccountSeqNumberClient,
+    {
+        let senders: Vec<_> = self.pool.read().senders().cloned().collect();
+        senders
+            .into_iter()
+            .map(|sender| {
+                let sequence_number = client.account_seq_number(&sender);
+                let mut readiness = (
+                    ready::Expiration::new(now),
+                    ready::State::new(client.clone(), None),
+                );
+                let mut ready = vec![];
+                let mut future = vec![];
+                for txn in self.txns_of_sender(&sender, usize::max_value()) {
+                    let txn_readiness = tx_pool::Ready::is_ready(&mut readiness, txn.as_ref());
+                    let future_reason = match txn_readiness {
+                        tx_pool::Readiness::Ready => None,
+                        tx_pool::Readiness::Future => Some(TxnFutureReason::SeqNumberGap {
+                            expected: sequence_number.saturating_add(ready.len() as u64),
+                        }),
+                        tx_pool::Readiness::Stale => {
+                            if txn.signed().expiration_timestamp_secs() <= now {
+                                Some(TxnFutureReason::Expired)
+                            } else {
+                                Some(TxnFutureReason::Stale)
+                            }
+                        }
+                    };
+                    let priority = pool::ScoredTransaction::priority(txn.as_ref());
+                    let info = PoolTxnInfo {
+                        txn: txn.signed().clone(),
+                        score: scoring::score(txn.signed().gas_unit_price(), priority),
+                        insertion_time: txn.insertion_time(),
+                        origin: match priority {
+                            pool::Priority::Local => TxnOrigin::Local,
+                            pool::Priority::Retracted => TxnOrigin::Retracted,
+                            pool::Priority::Regular => TxnOrigin::Remote,
+                        },
+                        future_reason,
+                    };
+                    if info.future_reason.is_none() {
+                        ready.push(info);
+                    } else {
+                        future.push(info);
+                    }
+                }
+                SenderPoolTxns {
+                    sender,
+                    sequence_number,
+                    ready,
+                    future,
+                }
+            })
+            .collect()
+    }
+
     /// Returns current pending transactions ordered by priority.
     ///
     /// NOTE: This may return a cached version of pending transaction set.
@@ -441,6 +523,7 @@ impl TransactionQueue {
             let readiness = (ready::Expiration::new(now), state_readiness);
             removed += self.pool.write().cull(Some(chunk), readiness);
         }
+        (self.pool.write().listener_mut().1).0.notify();
         debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
     }
 
@@ -488,6 +571,7 @@ impl TransactionQueue {
             for hash in hashes.into_iter() {
                 removed.push(pool.remove(hash, is_invalid));
             }
+            (pool.listener_mut().1).0.notify();
             removed
         };
 
@@ -501,6 +585,7 @@ impl TransactionQueue {
     /// Clear the entire pool.
     pub fn clear(&self) {
         self.pool.write().clear();
+        self.cached_pending.write().clear();
     }
 
     /// Penalize given senders.
@@ -568,6 +653,28 @@ impl TransactionQueue {
         (self.pool.write().listener_mut().1).0.add_full_listener(f);
     }
 
+    /// Add a listener to be notified about the lifecycle status changes of txns.
+    pub fn add_status_listener(&self, f: mpsc::UnboundedSender<Arc<[TxnStatusInfo]>>) {
+        (self.pool.write().listener_mut().1)
+            .0
+            .add_status_listener(f);
+    }
+
+    /// Latest lifecycle status of a recently seen txn.
+    pub fn txn_status(&self, hash: &HashValue) -> Option<TxnStatusInfo> {
+        (self.pool.read().listener().1).0.txn_status(hash)
+    }
+
+    /// Record the txns included by the new blocks of the main chain, and notify the status listeners.
+    pub fn txns_included(&self, included: Vec<TxnStatusInfo>) {
+        let mut pool = self.pool.write();
+        let notifier = &mut (pool.listener_mut().1).0;
+        for info in included {
+            notifier.txn_status_changed(info);
+        }
+        notifier.notify();
+    }
+
     /// Check if pending set is cached.
     #[cfg(test)]
     pub fn is_pending_cached(&self) -> bool {
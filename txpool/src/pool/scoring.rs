@@ -3,7 +3,7 @@
 
 use std::cmp;
 
-use super::{GasPrice, PrioritizationStrategy, ScoredTransaction, VerifiedTransaction};
+use super::{GasPrice, PrioritizationStrategy, Priority, ScoredTransaction, VerifiedTransaction};
 use tx_pool::{self, scoring};
 /// Transaction with the same (sender, nonce) can be replaced only if
 /// `new_gas_price >= old_gas_price + old_gas_price >> SHIFT`
@@ -17,6 +17,17 @@ fn bump_gas_price(old_gp: GasPrice) -> GasPrice {
     // old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT as u64)
 }
 
+/// The score of a newly inserted transaction, the gas price boosted by its priority.
+#[inline]
+pub(crate) fn score(gas_price: GasPrice, priority: Priority) -> u64 {
+    let boost = match priority {
+        Priority::Local => 15,
+        Priority::Retracted => 10,
+        Priority::Regular => 0,
+    };
+    gas_price << boost
+}
+
 /// Simple, gas-price based scoring for transactions.
 ///
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
@@ -91,13 +102,7 @@ where
                 assert!(i < txs.len());
                 assert!(i < scores.len());
 
-                scores[i] = txs[i].transaction.gas_price();
-                let boost = match txs[i].priority() {
-                    super::Priority::Local => 15,
-                    super::Priority::Retracted => 10,
-                    super::Priority::Regular => 0,
-                };
-                scores[i] <<= boost;
+                scores[i] = score(txs[i].transaction.gas_price(), txs[i].priority());
             }
             // We are only sending an event in case of penalization.
             // So just lower the priority of all non-local transactions.
@@ -13,7 +13,10 @@ use crate::pool::{
     VerifiedTransaction,
 };
 use starcoin_types::transaction;
-use std::sync::{atomic::AtomicUsize, Arc};
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Verification options.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -108,6 +111,10 @@ impl<C: Client> tx_pool::Verifier<PoolTransaction>
             sender,
             priority,
             insertion_id: self.id.fetch_add(1, std::sync::atomic::Ordering::AcqRel),
+            insertion_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
         })
     }
 }
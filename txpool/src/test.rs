@@ -1,14 +1,17 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::fee_estimator::{BlockFeeStats, FeeEstimator};
 use crate::pool::AccountSeqNumberClient;
-use crate::TxStatus;
+use crate::{TxPoolService, TxStatus};
 use anyhow::Result;
+use futures::StreamExt;
 use network_api::messages::{PeerTransactionsMessage, TransactionsMessage};
 use network_api::PeerId;
 use parking_lot::RwLock;
-use starcoin_config::{MetricsConfig, NodeConfig};
+use starcoin_config::{MetricsConfig, NodeConfig, TxPoolJournalMode};
 use starcoin_crypto::keygen::KeyGen;
+use starcoin_genesis::Genesis;
 // use starcoin_executor::{
 //     create_signed_txn_with_association_account, encode_transfer_script_function,
 //     DEFAULT_EXPIRATION_TIME, DEFAULT_MAX_GAS_AMOUNT,
@@ -17,10 +20,14 @@ use starcoin_open_block::OpenedBlock;
 use starcoin_state_api::ChainStateWriter;
 use starcoin_statedb::ChainStateDB;
 use starcoin_storage::BlockStore;
-use starcoin_txpool_api::{TxPoolSyncService, TxnStatusFullEvent};
+use starcoin_txpool_api::{
+    GasPriceEstimate, TxPoolSyncService, TxnFutureReason, TxnLifecycleStatus, TxnOrigin,
+    TxnStatusFullEvent,
+};
 use starcoin_types::{
     account_address::{self, AccountAddress},
     account_config,
+    block::{Block, BlockBody, BlockHeader, BlockHeaderBuilder},
     transaction::{SignedUserTransaction, Transaction, TransactionPayload},
     U256,
 };
@@ -68,6 +75,31 @@ async fn test_txn_expire() -> Result<()> {
     Ok(())
 }
 
+#[stest::test]
+async fn test_txpool_restart() -> Result<()> {
+    let (txpool_service, storage, config, pool_actor, _) = test_helper::start_txpool().await;
+    let txn = generate_txn(config, 0);
+    let txn_hash = txn.id();
+    txpool_service.add_txns(vec![txn]).pop().unwrap()?;
+    let best_header = txpool_service.get_inner().get_chain_header();
+    // a stale state left by the failed service.
+    txpool_service
+        .get_inner()
+        .notify_new_chain_header(BlockHeader::random());
+
+    pool_actor.restart_self()?;
+    sleep(Duration::from_millis(200)).await;
+
+    // the restart rebuilds the pool state on the best block, and re-imports the txns.
+    let startup_info = storage.get_startup_info()?.unwrap();
+    assert_eq!(startup_info.main, best_header.id());
+    assert_eq!(txpool_service.get_inner().get_chain_header(), best_header);
+    let pendings = txpool_service.get_pending_txns(None, Some(0));
+    assert_eq!(pendings.len(), 1);
+    assert_eq!(pendings[0].id(), txn_hash);
+    Ok(())
+}
+
 #[stest::test]
 async fn test_tx_pool() -> Result<()> {
     let (txpool_service, _storage, config, _, _) = test_helper::start_txpool().await;
@@ -271,6 +303,40 @@ async fn test_rollback() -> Result<()> {
     Ok(())
 }
 
+#[stest::test]
+async fn test_txpool_journal() -> Result<()> {
+    let mut config = NodeConfig::random_for_test();
+    config.tx_pool.set_journal_mode(TxPoolJournalMode::Local);
+    let config = Arc::new(config);
+    let (storage, chain_info, _) = Genesis::init_storage_for_test(config.net())?;
+    let chain_header = chain_info.head().clone();
+
+    let pool = TxPoolService::new(config.clone(), storage.clone(), chain_header.clone(), None);
+    let local_txn = generate_txn(config.clone(), 0);
+    pool.add_txns(vec![local_txn.clone()]).pop().unwrap()?;
+    // txns received from peers are not journaled in local mode.
+    let peer_txn = generate_txn(config.clone(), 1);
+    pool.get_inner()
+        .import_txns(vec![peer_txn.clone()], false)
+        .pop()
+        .unwrap()?;
+    drop(pool);
+
+    // restart the pool.
+    let pool = TxPoolService::new(config.clone(), storage.clone(), chain_header.clone(), None);
+    assert!(pool.find_txn(&local_txn.id()).is_some());
+    assert!(pool.find_txn(&peer_txn.id()).is_none());
+
+    // txns removed from the pool are dropped from the journal after rotation.
+    assert!(pool.remove_txn(local_txn.id(), false).is_some());
+    pool.get_inner().rotate_journal();
+    drop(pool);
+
+    let pool = TxPoolService::new(config, storage, chain_header, None);
+    assert!(pool.find_txn(&local_txn.id()).is_none());
+    Ok(())
+}
+
 #[stest::test(timeout = 480)]
 async fn test_txpool_actor_service() {
     let (_txpool_service, _storage, config, tx_pool_actor, _registry) =
@@ -310,3 +376,175 @@ fn generate_txn(config: Arc<NodeConfig>, seq: u64) -> SignedUserTransaction {
     );
     txn
 }
+
+#[test]
+fn test_fee_estimator() -> Result<()> {
+    let config = Arc::new(NodeConfig::random_for_test());
+    let gas_limit = 1000;
+    let new_block = |number: u64, gas_used: u64, gas_prices: &[u64]| {
+        let txns = gas_prices
+            .iter()
+            .enumerate()
+            .map(|(seq, gas_price)| {
+                starcoin_transaction_builder::create_signed_txn_with_association_account(
+                    TransactionPayload::ScriptFunction(
+                        starcoin_transaction_builder::encode_transfer_script_function(
+                            AccountAddress::random(),
+                            10000,
+                        ),
+                    ),
+                    seq as u64,
+                    starcoin_transaction_builder::DEFAULT_MAX_GAS_AMOUNT,
+                    *gas_price,
+                    2,
+                    config.net(),
+                )
+            })
+            .collect();
+        let header = BlockHeaderBuilder::random()
+            .with_number(number)
+            .with_gas_used(gas_used)
+            .build();
+        Block::new(header, BlockBody::new(txns, None))
+    };
+    let estimator = FeeEstimator::new(3);
+    let estimate = |low, medium, high| GasPriceEstimate { low, medium, high };
+    assert_eq!(estimator.estimate(1, 0), estimate(1, 1, 1));
+
+    // the blocks have spare room, and no backlog in the pool.
+    for number in 1..=4 {
+        estimator.push(BlockFeeStats::new(
+            &new_block(number, 100, &[1, 2, 3, 4]),
+            gas_limit,
+        ));
+    }
+    let history = estimator.fee_history(10, &[0.0, 50.0, 100.0])?;
+    assert_eq!(
+        history.iter().map(|block| block.number).collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+    assert_eq!(history[0].gas_price_percentiles, vec![1, 2, 4]);
+    assert!(estimator.fee_history(1, &[101.0]).is_err());
+    assert_eq!(estimator.estimate(1, 0), estimate(1, 1, 2));
+    assert_eq!(estimator.estimate(3, 0), estimate(3, 3, 3));
+
+    // the block 4 is replaced by the new branch, and the new blocks are congested.
+    let mut new_branch = vec![];
+    for number in 4..=6 {
+        let block = new_block(number, 900, &[5, 6, 7, 8]);
+        estimator.push(BlockFeeStats::new(&block, gas_limit));
+        new_branch.push(block.id());
+    }
+    let history = estimator.fee_history(10, &[50.0])?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|block| block.block_hash)
+            .collect::<Vec<_>>(),
+        new_branch
+    );
+    assert_eq!(estimator.estimate(1, 0), estimate(5, 6, 8));
+    // the pending txns in the pool can fill a block.
+    assert_eq!(estimator.estimate(1, 4), estimate(6, 7, 8));
+
+    estimator.retract(new_branch[2]);
+    assert_eq!(estimator.fee_history(10, &[50.0])?.len(), 2);
+    Ok(())
+}
+
+#[stest::test]
+async fn test_txn_status() -> Result<()> {
+    let (txpool_service, _storage, config, _, _) = test_helper::start_txpool().await;
+    let mut status_receiver = txpool_service.subscribe_txn_status();
+    let new_txn = |gas_unit_price: u64| {
+        starcoin_transaction_builder::create_signed_txn_with_association_account(
+            TransactionPayload::ScriptFunction(
+                starcoin_transaction_builder::encode_transfer_script_function(
+                    AccountAddress::random(),
+                    10000,
+                ),
+            ),
+            0,
+            starcoin_transaction_builder::DEFAULT_MAX_GAS_AMOUNT,
+            gas_unit_price,
+            2,
+            config.net(),
+        )
+    };
+    let txn = new_txn(1);
+    let txn_hash = txn.id();
+    assert!(txpool_service.txn_status(txn_hash)?.is_none());
+    txpool_service.add_txns(vec![txn]).pop().unwrap()?;
+    let status = txpool_service.txn_status(txn_hash)?.unwrap();
+    assert_eq!(status.sender, account_config::association_address());
+    assert_eq!(status.status, TxnLifecycleStatus::Pending);
+
+    // replaced by the txn with the same sequence number and a higher gas price.
+    let new_txn = new_txn(10);
+    let new_txn_hash = new_txn.id();
+    txpool_service.add_txns(vec![new_txn]).pop().unwrap()?;
+    assert_eq!(
+        txpool_service.txn_status(txn_hash)?.unwrap().status,
+        TxnLifecycleStatus::Replaced { by: new_txn_hash }
+    );
+
+    txpool_service.remove_txn(new_txn_hash, false).unwrap();
+    assert_eq!(
+        txpool_service.txn_status(new_txn_hash)?.unwrap().status,
+        TxnLifecycleStatus::Canceled
+    );
+
+    let mut changes = vec![];
+    while changes.len() < 4 {
+        let statuses = status_receiver.next().await.unwrap();
+        changes.extend(
+            statuses
+                .iter()
+                .map(|info| (info.txn_hash, info.status.clone())),
+        );
+    }
+    assert_eq!(
+        changes,
+        vec![
+            (txn_hash, TxnLifecycleStatus::Pending),
+            (new_txn_hash, TxnLifecycleStatus::Pending),
+            (txn_hash, TxnLifecycleStatus::Replaced { by: new_txn_hash }),
+            (new_txn_hash, TxnLifecycleStatus::Canceled),
+        ]
+    );
+    Ok(())
+}
+
+#[stest::test]
+async fn test_txpool_content() -> Result<()> {
+    let (txpool_service, _storage, config, _, _) = test_helper::start_txpool().await;
+    let txns: Vec<_> = [0, 1, 3]
+        .into_iter()
+        .map(|seq| generate_txn(config.clone(), seq))
+        .collect();
+    for result in txpool_service.add_txns(txns.clone()) {
+        result?;
+    }
+
+    let content = txpool_service.content();
+    assert_eq!(content.len(), 1);
+    let sender_txns = &content[0];
+    assert_eq!(sender_txns.sender, account_config::association_address());
+    assert_eq!(sender_txns.sequence_number, 0);
+    let ready: Vec<_> = sender_txns.ready.iter().map(|info| info.txn.id()).collect();
+    assert_eq!(ready, vec![txns[0].id(), txns[1].id()]);
+    assert_eq!(sender_txns.future.len(), 1);
+    let future = &sender_txns.future[0];
+    assert_eq!(future.txn.id(), txns[2].id());
+    // blocked by the missing txn of sequence number 2.
+    assert_eq!(
+        future.future_reason,
+        Some(TxnFutureReason::SeqNumberGap { expected: 2 })
+    );
+    for info in sender_txns.ready.iter().chain(sender_txns.future.iter()) {
+        assert_eq!(info.origin, TxnOrigin::Remote);
+        assert_eq!(info.score, info.txn.gas_unit_price());
+        assert!(info.insertion_time > 0);
+    }
+    Ok(())
+}
@@ -10,25 +10,38 @@ use crate::{
     pool_client::{NonceCache, PoolClient},
 };
 
+use crate::fee_estimator::{BlockFeeStats, FeeEstimator, MAX_FEE_HISTORY_BLOCKS};
+use crate::journal::TxPoolJournal;
 use crate::metrics::TxPoolMetrics;
 use crate::pool::{Client, TransactionQueue};
 use anyhow::Result;
 use futures_channel::mpsc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use starcoin_config::NodeConfig;
 use starcoin_crypto::hash::HashValue;
 use starcoin_executor::VMMetrics;
+use starcoin_logger::tracer::{current_context, Span, TraceContext};
+use starcoin_state_api::AccountStateReader;
 use starcoin_statedb::ChainStateDB;
-use starcoin_storage::Store;
-use starcoin_txpool_api::{TxPoolStatus, TxPoolSyncService, TxnStatusFullEvent};
+use starcoin_storage::{BlockStore, BlockTransactionInfoStore, Store, TransactionStore};
+use starcoin_txpool_api::{
+    BlockFeeHistory, GasPriceEstimate, SenderPoolTxns, TxPoolStatus, TxPoolSyncService,
+    TxnLifecycleStatus, TxnStatusFullEvent, TxnStatusInfo,
+};
 use starcoin_types::{
     account_address::AccountAddress,
     block::{Block, BlockHeader},
     transaction,
-    transaction::SignedUserTransaction,
+    transaction::{SignedUserTransaction, Transaction},
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Max local txns traced from the submission to the inclusion.
+const MAX_TRACED_TXNS: usize = 4096;
+/// Max blocks read for the finalized txns on a new head, the rest are read on the next heads.
+const MAX_FINALIZED_BLOCKS_PER_HEAD: u64 = 16;
+
 #[derive(Clone, Debug)]
 pub struct TxPoolService {
     inner: Inner,
@@ -60,6 +73,13 @@ impl TxPoolService {
             PrioritizationStrategy::GasPriceOnly,
         );
         let queue = Arc::new(queue);
+        let journal = pool_config.journal_path().map(|path| {
+            Arc::new(TxPoolJournal::new(
+                path,
+                pool_config.journal_mode(),
+                pool_config.journal_max_age(),
+            ))
+        });
         let inner = Inner {
             node_config,
             queue,
@@ -68,7 +88,13 @@ impl TxPoolService {
             sequence_number_cache: NonceCache::new(128),
             metrics,
             vm_metrics,
+            journal,
+            fee_estimator: Arc::new(FeeEstimator::new(MAX_FEE_HISTORY_BLOCKS)),
+            txn_traces: Arc::new(Mutex::new(HashMap::new())),
+            finalized_cursor: Arc::new(Mutex::new(None)),
         };
+        inner.load_journal();
+        inner.load_fee_history();
 
         Self { inner }
     }
@@ -107,7 +133,7 @@ impl TxPoolSyncService for TxPoolService {
                 .with_label_values(&["add_txns"])
                 .start_timer()
         });
-        self.inner.import_txns(txns)
+        self.inner.import_txns(txns, true)
     }
 
     fn remove_txn(&self, txn_hash: HashValue, is_invalid: bool) -> Option<SignedUserTransaction> {
@@ -175,6 +201,26 @@ impl TxPoolSyncService for TxPoolService {
         self.inner.subscribe_pending_txns()
     }
 
+    fn subscribe_txn_status(&self) -> mpsc::UnboundedReceiver<Arc<[TxnStatusInfo]>> {
+        let _timer = self.inner.metrics.as_ref().map(|metrics| {
+            metrics
+                .txpool_service_time
+                .with_label_values(&["subscribe_txn_status"])
+                .start_timer()
+        });
+        self.inner.subscribe_txn_status()
+    }
+
+    fn txn_status(&self, txn_hash: HashValue) -> Result<Option<TxnStatusInfo>> {
+        let _timer = self.inner.metrics.as_ref().map(|metrics| {
+            metrics
+                .txpool_service_time
+                .with_label_values(&["txn_status"])
+                .start_timer()
+        });
+        self.inner.txn_status(txn_hash)
+    }
+
     /// rollback
     fn chain_new_block(&self, enacted: Vec<Block>, retracted: Vec<Block>) -> Result<()> {
         let _timer = self.inner.metrics.as_ref().map(|metrics| {
@@ -191,6 +237,20 @@ impl TxPoolSyncService for TxPoolService {
         self.inner.queue.status().into()
     }
 
+    fn gas_price_estimate(&self) -> GasPriceEstimate {
+        let pending_txn_count = self.inner.queue.status().status.transaction_count as u64;
+        self.inner.fee_estimator.estimate(
+            self.inner.node_config.tx_pool.min_gas_price(),
+            pending_txn_count,
+        )
+    }
+
+    fn fee_history(&self, block_count: u64, percentiles: Vec<f64>) -> Result<Vec<BlockFeeHistory>> {
+        self.inner
+            .fee_estimator
+            .fee_history(block_count, percentiles.as_slice())
+    }
+
     fn find_txn(&self, hash: &HashValue) -> Option<SignedUserTransaction> {
         self.inner
             .queue
@@ -209,6 +269,16 @@ impl TxPoolSyncService for TxPoolService {
             .map(|t| t.signed().clone())
             .collect()
     }
+
+    fn content(&self) -> Vec<SenderPoolTxns> {
+        let _timer = self.inner.metrics.as_ref().map(|metrics| {
+            metrics
+                .txpool_service_time
+                .with_label_values(&["content"])
+                .start_timer()
+        });
+        self.inner.content()
+    }
 }
 
 pub(crate) type TxnQueue = TransactionQueue;
@@ -221,6 +291,12 @@ pub(crate) struct Inner {
     sequence_number_cache: NonceCache,
     pub(crate) metrics: Option<TxPoolMetrics>,
     vm_metrics: Option<VMMetrics>,
+    journal: Option<Arc<TxPoolJournal>>,
+    fee_estimator: Arc<FeeEstimator>,
+    /// The trace context of the local txns submitted in a span, to trace their inclusion.
+    txn_traces: Arc<Mutex<HashMap<HashValue, TraceContext>>>,
+    /// The next block number whose txns are notified as finalized.
+    finalized_cursor: Arc<Mutex<Option<u64>>>,
 }
 impl std::fmt::Debug for Inner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -245,6 +321,41 @@ impl Inner {
         self.sequence_number_cache.clear();
     }
 
+    /// Rebuild the pool state on the `chain_header`, the txns in the pool are re-verified
+    /// against the state of the header, and the caches are dropped.
+    pub(crate) fn reset(&self, chain_header: BlockHeader) {
+        let txns = self.queue.all_transactions();
+        self.queue.clear();
+        self.notify_new_chain_header(chain_header);
+        self.txn_traces.lock().clear();
+        *self.finalized_cursor.lock() = None;
+        let total = txns.len();
+        let txns = txns.into_iter().map(|txn| {
+            let signed = txn.signed().clone();
+            match pool::ScoredTransaction::priority(txn.as_ref()) {
+                pool::Priority::Local => PoolTransaction::Local(signed.into()),
+                pool::Priority::Retracted => {
+                    PoolTransaction::Retracted(UnverifiedUserTransaction::from(signed))
+                }
+                pool::Priority::Regular => {
+                    PoolTransaction::Unverified(UnverifiedUserTransaction::from(signed))
+                }
+            }
+        });
+        let imported = self
+            .queue
+            .import(self.get_pool_client(), txns)
+            .into_iter()
+            .filter(|result| result.is_ok())
+            .count();
+        info!(
+            "Reset txpool on block {}, re-import {} of {} transactions",
+            self.get_chain_header().number(),
+            imported,
+            total
+        );
+    }
+
     pub(crate) fn get_chain_reader(&self) -> ChainStateDB {
         ChainStateDB::new(
             self.storage.clone().into_super_arc(),
@@ -255,6 +366,45 @@ impl Inner {
         self.chain_header.read().clone()
     }
 
+    /// The block gas limit of the current epoch, 0 if failed to read the epoch.
+    fn block_gas_limit(&self) -> u64 {
+        let statedb = self.get_chain_reader();
+        match AccountStateReader::new(&statedb).get_epoch() {
+            Ok(epoch) => epoch.block_gas_limit(),
+            Err(e) => {
+                warn!("Failed to read the block gas limit of the epoch: {:?}", e);
+                0
+            }
+        }
+    }
+
+    /// Load the recent blocks of the main chain into the fee estimator.
+    pub(crate) fn load_fee_history(&self) {
+        let gas_limit = self.block_gas_limit();
+        let mut blocks = vec![];
+        let mut block_id = self.get_chain_header().id();
+        while blocks.len() < MAX_FEE_HISTORY_BLOCKS {
+            match self.storage.get_block_by_hash(block_id) {
+                Ok(Some(block)) => {
+                    let is_genesis = block.header().number() == 0;
+                    block_id = block.header().parent_hash();
+                    blocks.push(BlockFeeStats::new(&block, gas_limit));
+                    if is_genesis {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Failed to load block {} for fee history: {:?}", block_id, e);
+                    break;
+                }
+            }
+        }
+        for stats in blocks.into_iter().rev() {
+            self.fee_estimator.push(stats);
+        }
+    }
+
     pub(crate) fn cull(&self) {
         // NOTICE: as the new head block event is repeated with chain_new_block event,
         // we need to remove invalid txn here.
@@ -264,14 +414,88 @@ impl Inner {
         self.queue.cull(self.get_pool_client(), now_seconds)
     }
 
+    /// Import the txns, `is_local` is true if the txns are submitted to this node instead of received from peers.
     pub(crate) fn import_txns(
         &self,
         txns: Vec<transaction::SignedUserTransaction>,
+        is_local: bool,
     ) -> Vec<Result<(), transaction::TransactionError>> {
+        let journal = self
+            .journal
+            .as_ref()
+            .filter(|journal| journal.should_journal(is_local));
+        let journal_txns = journal.map(|_| txns.clone());
+        let trace = current_context()
+            .filter(|_| is_local)
+            .map(|context| (context, txns.iter().map(|txn| txn.id()).collect::<Vec<_>>()));
         let txns = txns
             .into_iter()
             .map(|t| PoolTransaction::Unverified(UnverifiedUserTransaction::from(t)));
-        self.queue.import(self.get_pool_client(), txns)
+        let results = self.queue.import(self.get_pool_client(), txns);
+        if let Some((context, txn_hashes)) = trace {
+            let accepted = txn_hashes
+                .into_iter()
+                .zip(results.iter())
+                .filter_map(|(txn_hash, result)| result.is_ok().then(|| txn_hash));
+            self.trace_txns(context, accepted);
+        }
+        if let (Some(journal), Some(journal_txns)) = (journal, journal_txns) {
+            let accepted_txns = journal_txns
+                .into_iter()
+                .zip(results.iter())
+                .filter_map(|(txn, result)| result.is_ok().then(|| txn))
+                .collect();
+            if let Err(e) = journal.insert(accepted_txns, self.now_secs()) {
+                warn!("Write txpool journal failed: {:?}", e);
+            }
+        }
+        results
+    }
+
+    /// Re-import the journaled txns, they are verified against the state of current chain header.
+    pub(crate) fn load_journal(&self) {
+        let journal = match self.journal.as_ref() {
+            Some(journal) => journal,
+            None => return,
+        };
+        match journal.load(self.now_secs()) {
+            Ok(txns) if !txns.is_empty() => {
+                let total = txns.len();
+                let txns = txns
+                    .into_iter()
+                    .map(|t| PoolTransaction::Unverified(UnverifiedUserTransaction::from(t)));
+                let imported = self
+                    .queue
+                    .import(self.get_pool_client(), txns)
+                    .into_iter()
+                    .filter(|result| result.is_ok())
+                    .count();
+                info!(
+                    "Re-import {} of {} transactions from txpool journal",
+                    imported, total
+                );
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Load txpool journal failed: {:?}", e);
+            }
+        }
+        self.rotate_journal();
+    }
+
+    /// Rewrite the journal with the journaled txns still in the pool.
+    pub(crate) fn rotate_journal(&self) {
+        if let Some(journal) = self.journal.as_ref() {
+            if let Err(e) = journal.rotate(self.now_secs(), |hash| {
+                self.queue.find(hash).map(|txn| txn.signed().clone())
+            }) {
+                warn!("Rotate txpool journal failed: {:?}", e);
+            }
+        }
+    }
+
+    fn now_secs(&self) -> u64 {
+        self.node_config.net().time_service().now_secs()
     }
     pub(crate) fn remove_txn(
         &self,
@@ -301,6 +525,9 @@ impl Inner {
         );
         self.queue.pending(self.get_pool_client(), pending_settings)
     }
+    pub(crate) fn content(&self) -> Vec<SenderPoolTxns> {
+        self.queue.content(self.get_pool_client(), self.now_secs())
+    }
     pub(crate) fn next_sequence_number(&self, address: AccountAddress) -> Option<u64> {
         self.queue
             .next_sequence_number(self.get_pool_client(), &address)
@@ -317,6 +544,185 @@ impl Inner {
         rx
     }
 
+    pub(crate) fn subscribe_txn_status(&self) -> mpsc::UnboundedReceiver<Arc<[TxnStatusInfo]>> {
+        let (tx, rx) = mpsc::unbounded();
+        self.queue.add_status_listener(tx);
+        rx
+    }
+
+    pub(crate) fn txn_status(&self, txn_hash: HashValue) -> Result<Option<TxnStatusInfo>> {
+        if let Some(txn) = self.queue.find(&txn_hash) {
+            return Ok(Some(TxnStatusInfo {
+                txn_hash,
+                sender: txn.signed().sender(),
+                status: TxnLifecycleStatus::Pending,
+            }));
+        }
+        let head_number = self.get_chain_header().number();
+        // the txn infos of the txn in the forks are skipped, only the one on the main chain is read.
+        for txn_info_id in self
+            .storage
+            .get_transaction_info_ids_by_txn_hash(txn_hash)?
+        {
+            let txn_info = match self.storage.get_transaction_info(txn_info_id)? {
+                Some(txn_info) => txn_info,
+                None => continue,
+            };
+            if self
+                .storage
+                .get_main_block_id_by_number(txn_info.block_number)?
+                != Some(txn_info.block_id)
+            {
+                continue;
+            }
+            if let Some(Transaction::UserTransaction(txn)) =
+                self.storage.get_transaction(txn_hash)?
+            {
+                return Ok(Some(TxnStatusInfo {
+                    txn_hash,
+                    sender: txn.sender(),
+                    status: self.inclusion_status(
+                        txn_info.block_id,
+                        txn_info.block_number,
+                        head_number,
+                    ),
+                }));
+            }
+        }
+        // the inclusion recorded by the pool is outdated if the block is not on the main chain.
+        Ok(self.queue.txn_status(&txn_hash).filter(|info| {
+            !matches!(
+                info.status,
+                TxnLifecycleStatus::Included { .. } | TxnLifecycleStatus::Finalized { .. }
+            )
+        }))
+    }
+
+    fn inclusion_status(
+        &self,
+        block_hash: HashValue,
+        block_number: u64,
+        head_number: u64,
+    ) -> TxnLifecycleStatus {
+        let confirmations = head_number.saturating_sub(block_number).saturating_add(1);
+        if confirmations >= self.node_config.tx_pool.txn_finality_confirmations() {
+            TxnLifecycleStatus::Finalized {
+                block_hash,
+                block_number,
+                confirmations,
+            }
+        } else {
+            TxnLifecycleStatus::Included {
+                block_hash,
+                block_number,
+                confirmations,
+            }
+        }
+    }
+
+    /// Notify the status listeners about the txns included by the `enacted` blocks,
+    /// and the txns finalized as the main chain grows from `old_head_number`.
+    fn notify_included_txns(&self, enacted: &[Block], old_head_number: u64) {
+        let head_number = self.get_chain_header().number();
+        let mut included = vec![];
+        for block in enacted {
+            let block_number = block.header().number();
+            for txn in block.transactions() {
+                included.push(TxnStatusInfo {
+                    txn_hash: txn.id(),
+                    sender: txn.sender(),
+                    status: self.inclusion_status(block.id(), block_number, head_number),
+                });
+            }
+        }
+        // the blocks which reach the finality confirmations by this head, the blocks left behind
+        // by the cursor are caught up in batches, so a long gap does not stall the pool.
+        let confirmations = self.node_config.tx_pool.txn_finality_confirmations();
+        if let Some(last_finalized) = head_number.saturating_add(1).checked_sub(confirmations) {
+            let mut finalized_cursor = self.finalized_cursor.lock();
+            let first_finalized = finalized_cursor.unwrap_or_else(|| {
+                old_head_number
+                    .saturating_add(2)
+                    .saturating_sub(confirmations)
+            });
+            let enacted_from = enacted
+                .first()
+                .map(|block| block.header().number())
+                .unwrap_or(head_number);
+            // the finalized blocks in `enacted` are already notified as finalized.
+            let last_unnotified = last_finalized.min(enacted_from.saturating_sub(1));
+            let last_read = last_unnotified.min(
+                first_finalized.saturating_add(MAX_FINALIZED_BLOCKS_PER_HEAD.saturating_sub(1)),
+            );
+            for number in first_finalized..=last_read {
+                match self.finalized_txns(number, head_number) {
+                    Ok(txns) => included.extend(txns),
+                    Err(e) => warn!("Failed to read the finalized block {}: {:?}", number, e),
+                }
+            }
+            *finalized_cursor = Some(if last_read >= last_unnotified {
+                last_finalized.saturating_add(1).max(first_finalized)
+            } else {
+                last_read.saturating_add(1)
+            });
+        }
+        if !included.is_empty() {
+            self.queue.txns_included(included);
+        }
+    }
+
+    fn trace_txns(&self, context: TraceContext, txn_hashes: impl Iterator<Item = HashValue>) {
+        let mut txn_traces = self.txn_traces.lock();
+        for txn_hash in txn_hashes {
+            if txn_traces.len() >= MAX_TRACED_TXNS {
+                // forget the txns which already left the pool.
+                txn_traces.retain(|txn_hash, _| self.queue.find(txn_hash).is_some());
+                if txn_traces.len() >= MAX_TRACED_TXNS {
+                    return;
+                }
+            }
+            txn_traces.insert(txn_hash, context);
+        }
+    }
+
+    /// Continue the trace of the traced txns included in the `enacted` blocks.
+    fn trace_included_txns(&self, enacted: &[Block]) {
+        let mut txn_traces = self.txn_traces.lock();
+        if txn_traces.is_empty() {
+            return;
+        }
+        for block in enacted {
+            for txn in block.transactions() {
+                if let Some(context) = txn_traces.remove(&txn.id()) {
+                    let _span = Span::new("txpool.txn_included", Some(context))
+                        .with("txn_hash", txn.id())
+                        .with("block_hash", block.id())
+                        .with("block_number", block.header().number());
+                }
+            }
+        }
+    }
+
+    fn finalized_txns(&self, number: u64, head_number: u64) -> Result<Vec<TxnStatusInfo>> {
+        let block = match self.storage.get_main_block_id_by_number(number)? {
+            Some(block_id) => self.storage.get_block_by_hash(block_id)?,
+            None => None,
+        };
+        Ok(block
+            .map(|block| {
+                block
+                    .transactions()
+                    .iter()
+                    .map(|txn| TxnStatusInfo {
+                        txn_hash: txn.id(),
+                        sender: txn.sender(),
+                        status: self.inclusion_status(block.id(), number, head_number),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     pub(crate) fn chain_new_block(&self, enacted: Vec<Block>, retracted: Vec<Block>) {
         debug!(
             "receive chain_new_block msg, enacted: {:?}, retracted: {:?}",
@@ -331,9 +737,24 @@ impl Inner {
         );
 
         // new head block, update chain header
+        let old_head_number = self.get_chain_header().number();
         if let Some(block) = enacted.last() {
             self.notify_new_chain_header(block.header().clone());
         }
+        // before culling, so the included txns are not reported as culled.
+        self.notify_included_txns(&enacted, old_head_number);
+        self.trace_included_txns(&enacted);
+
+        for block in &retracted {
+            self.fee_estimator.retract(block.id());
+        }
+        if !enacted.is_empty() {
+            let gas_limit = self.block_gas_limit();
+            for block in &enacted {
+                self.fee_estimator
+                    .push(BlockFeeStats::new(block, gas_limit));
+            }
+        }
 
         // remove outdated txns.
         self.cull();
@@ -0,0 +1,166 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per block bloom filter of contract events, used to skip blocks which can not match a `Filter`.
+
+use crate::account_address::AccountAddress;
+use crate::contract_event::ContractEvent;
+use crate::event::EventKey;
+use crate::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use starcoin_crypto::HashValue;
+
+/// Bloom filter size in bytes, 2048 bits.
+pub const EVENT_BLOOM_BYTES: usize = 256;
+const EVENT_BLOOM_BITS_MASK: usize = 2047;
+/// Number of bits set for every item.
+const EVENT_BLOOM_HASHES: usize = 3;
+
+const TOPIC_EVENT_KEY: u8 = 0;
+const TOPIC_ADDRESS: u8 = 1;
+const TOPIC_TYPE_TAG: u8 = 2;
+
+/// A topic is the item of an event which is put into the bloom and the event index.
+/// The event key, the creator address of the event key and the type tag of the event are topics.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct EventTopic(HashValue);
+
+impl EventTopic {
+    fn new(kind: u8, data: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(data.len().saturating_add(1));
+        bytes.push(kind);
+        bytes.extend_from_slice(data);
+        Self(HashValue::sha3_256_of(bytes.as_slice()))
+    }
+
+    pub fn from_hash(hash: HashValue) -> Self {
+        Self(hash)
+    }
+
+    pub fn from_event_key(key: &EventKey) -> Self {
+        Self::new(TOPIC_EVENT_KEY, key.as_bytes())
+    }
+
+    pub fn from_address(address: &AccountAddress) -> Self {
+        Self::new(TOPIC_ADDRESS, address.as_ref())
+    }
+
+    /// Struct type tags only take `address::module::name` into account,
+    /// because `Filter` allows a struct tag without type params to match any instantiation.
+    pub fn from_type_tag(type_tag: &TypeTag) -> Self {
+        match type_tag {
+            TypeTag::Struct(s) => Self::new(
+                TOPIC_TYPE_TAG,
+                format!("{}::{}::{}", s.address, s.module, s.name).as_bytes(),
+            ),
+            t => Self::new(TOPIC_TYPE_TAG, t.to_string().as_bytes()),
+        }
+    }
+
+    /// All topics of the event, in the order of event key, creator address and type tag.
+    pub fn topics_of(event: &ContractEvent) -> [EventTopic; 3] {
+        [
+            Self::from_event_key(event.key()),
+            Self::from_address(&event.key().get_creator_address()),
+            Self::from_type_tag(event.type_tag()),
+        ]
+    }
+
+    pub fn hash(&self) -> HashValue {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventBloom(Vec<u8>);
+
+impl Default for EventBloom {
+    fn default() -> Self {
+        Self(vec![0u8; EVENT_BLOOM_BYTES])
+    }
+}
+
+impl EventBloom {
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a ContractEvent>) -> Self {
+        let mut bloom = Self::default();
+        for event in events {
+            bloom.accrue_event(event);
+        }
+        bloom
+    }
+
+    pub fn accrue_event(&mut self, event: &ContractEvent) {
+        for topic in EventTopic::topics_of(event).iter() {
+            self.accrue(topic);
+        }
+    }
+
+    pub fn accrue(&mut self, topic: &EventTopic) {
+        for (byte_index, mask) in Self::bit_positions(topic) {
+            if let Some(byte) = self.0.get_mut(byte_index) {
+                *byte |= mask;
+            }
+        }
+    }
+
+    /// Return false if the topic is definitely not in the bloom.
+    pub fn contains(&self, topic: &EventTopic) -> bool {
+        Self::bit_positions(topic)
+            .into_iter()
+            .all(|(byte_index, mask)| {
+                self.0
+                    .get(byte_index)
+                    .map(|byte| byte & mask == mask)
+                    .unwrap_or(false)
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+
+    fn bit_positions(topic: &EventTopic) -> [(usize, u8); EVENT_BLOOM_HASHES] {
+        let hash = topic.hash();
+        let bytes = hash.as_ref();
+        let mut positions = [(0usize, 0u8); EVENT_BLOOM_HASHES];
+        for (i, position) in positions.iter_mut().enumerate() {
+            // every two bytes of the hash take one bit position in 0..2048.
+            let high = bytes[i.wrapping_mul(2)] as usize;
+            let low = bytes[i.wrapping_mul(2).wrapping_add(1)] as usize;
+            let bit = (high.wrapping_shl(8) | low) & EVENT_BLOOM_BITS_MASK;
+            *position = (bit.wrapping_shr(3), 1u8.wrapping_shl((bit & 7) as u32));
+        }
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_config::genesis_address;
+    use crate::identifier::Identifier;
+    use crate::language_storage::StructTag;
+
+    fn struct_tag(name: &str, type_params: Vec<TypeTag>) -> TypeTag {
+        TypeTag::Struct(Box::new(StructTag {
+            address: genesis_address(),
+            module: Identifier::new("Test").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params,
+        }))
+    }
+
+    #[test]
+    fn test_event_bloom() {
+        let key = EventKey::new_from_address(&genesis_address(), 1);
+        let event = ContractEvent::new(key, 0, struct_tag("Foo", vec![TypeTag::U64]), vec![]);
+        let bloom = EventBloom::from_events(vec![&event]);
+        assert!(!bloom.is_empty());
+        assert!(bloom.contains(&EventTopic::from_event_key(&key)));
+        assert!(bloom.contains(&EventTopic::from_address(&genesis_address())));
+        // type params are ignored.
+        assert!(bloom.contains(&EventTopic::from_type_tag(&struct_tag("Foo", vec![]))));
+        assert!(!bloom.contains(&EventTopic::from_type_tag(&struct_tag("Bar", vec![]))));
+        assert!(EventBloom::default().is_empty());
+    }
+}
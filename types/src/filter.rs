@@ -6,6 +6,7 @@ use crate::account_address::AccountAddress;
 use crate::block::BlockNumber;
 use crate::contract_event::ContractEvent;
 use crate::event::EventKey;
+use crate::event_bloom::{EventBloom, EventTopic};
 use crate::language_storage::TypeTag;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -72,4 +73,48 @@ impl Filter {
         }
         false
     }
+
+    /// Check whether the block with the `bloom` may contain events matching the filter.
+    /// Return false only if the block definitely has no matching event.
+    pub fn bloom_possible(&self, bloom: &EventBloom) -> bool {
+        (self.event_keys.is_empty()
+            || self
+                .event_keys
+                .iter()
+                .any(|key| bloom.contains(&EventTopic::from_event_key(key))))
+            && (self.addrs.is_empty()
+                || self
+                    .addrs
+                    .iter()
+                    .any(|addr| bloom.contains(&EventTopic::from_address(addr))))
+            && (self.type_tags.is_empty()
+                || self
+                    .type_tags
+                    .iter()
+                    .any(|type_tag| bloom.contains(&EventTopic::from_type_tag(type_tag))))
+    }
+
+    /// The topics to lookup in the event index, a matching event must have one of these topics.
+    /// Return None if the filter matches any event.
+    pub fn index_topics(&self) -> Option<Vec<EventTopic>> {
+        if !self.event_keys.is_empty() {
+            Some(
+                self.event_keys
+                    .iter()
+                    .map(EventTopic::from_event_key)
+                    .collect(),
+            )
+        } else if !self.type_tags.is_empty() {
+            Some(
+                self.type_tags
+                    .iter()
+                    .map(EventTopic::from_type_tag)
+                    .collect(),
+            )
+        } else if !self.addrs.is_empty() {
+            Some(self.addrs.iter().map(EventTopic::from_address).collect())
+        } else {
+            None
+        }
+    }
 }
@@ -44,6 +44,8 @@ pub mod event {
     pub use starcoin_vm_types::event::*;
 }
 
+pub mod event_bloom;
+
 pub mod filter;
 
 #[cfg(any(test, feature = "fuzzing"))]
@@ -1,7 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block::{Block, BlockHeaderExtra, ExecutedBlock};
+use crate::block::{Block, BlockHeader, BlockHeaderExtra, ExecutedBlock};
 use crate::sync_status::SyncStatus;
 use crate::U256;
 use schemars::JsonSchema;
@@ -16,6 +16,17 @@ pub struct NewHeadBlock(pub Arc<ExecutedBlock>);
 #[derive(Clone, Debug)]
 pub struct NewBranch(pub Arc<ExecutedBlock>);
 
+/// Fire this event when the main chain switches to another branch, before the `NewHeadBlock` of the new head.
+#[derive(Clone, Debug)]
+pub struct ChainReorg {
+    /// The common ancestor of the old and the new main chain.
+    pub ancestor: BlockHeader,
+    /// The blocks removed from the main chain, in ascending order of block number.
+    pub retracted: Vec<HashValue>,
+    /// The blocks added to the main chain, in ascending order of block number.
+    pub enacted: Vec<HashValue>,
+}
+
 #[derive(Clone, Debug)]
 pub struct MinedBlock(pub Arc<Block>);
 
@@ -6,6 +6,7 @@ use starcoin_abi_decoder::decode_move_value;
 use starcoin_abi_resolver::ABIResolver;
 use starcoin_abi_types::TypeInstantiation;
 use starcoin_crypto::HashValue;
+use starcoin_gas::GasProfile;
 use starcoin_resource_viewer::module_cache::ModuleCache;
 use starcoin_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator};
 use starcoin_rpc_api::types::{DryRunOutputView, TransactionOutputView, WriteOpValueView};
@@ -13,12 +14,14 @@ use starcoin_state_api::StateNodeStore;
 use starcoin_statedb::ChainStateDB;
 use starcoin_vm_runtime::data_cache::{AsMoveResolver, StateViewCache};
 use starcoin_vm_runtime::metrics::VMMetrics;
-use starcoin_vm_runtime::starcoin_vm::StarcoinVM;
+use starcoin_vm_runtime::starcoin_vm::{ExecutionRecordOption, StarcoinVM};
 use starcoin_vm_types::file_format::CompiledModule;
 use starcoin_vm_types::identifier::{IdentStr, Identifier};
 use starcoin_vm_types::language_storage::{ModuleId, StructTag, TypeTag};
 use starcoin_vm_types::state_view::StateView;
-use starcoin_vm_types::transaction::{DryRunTransaction, TransactionOutput, TransactionPayload};
+use starcoin_vm_types::transaction::{
+    DryRunTransaction, SignedUserTransaction, TransactionOutput, TransactionPayload,
+};
 use starcoin_vm_types::transaction_argument::convert_txn_args;
 use starcoin_vm_types::transaction_argument::TransactionArgument;
 use starcoin_vm_types::vm_status::VMStatus;
@@ -102,19 +105,68 @@ pub fn dry_run<S: StateView>(
     vm.dry_run_transaction(&state_view_cache.as_move_resolver(), txn)
 }
 
+const PROFILE_OPTION: ExecutionRecordOption = ExecutionRecordOption {
+    call_trace: false,
+    gas_profile: true,
+};
+
+/// Dry run a transaction with the gas profile recorded, the profile is None if the
+/// transaction is discarded before execution.
+pub fn dry_run_profile<S: StateView>(
+    state_view: &S,
+    txn: DryRunTransaction,
+    metrics: Option<VMMetrics>,
+) -> Result<(VMStatus, TransactionOutput, Option<GasProfile>)> {
+    let mut vm = StarcoinVM::new(metrics);
+    let state_view_cache = StateViewCache::new(state_view);
+    let (vm_status, output, record) = vm.dry_run_transaction_with_record(
+        &state_view_cache.as_move_resolver(),
+        txn,
+        PROFILE_OPTION,
+    )?;
+    Ok((vm_status, output, record.gas_profile))
+}
+
+/// Re-execute a user transaction with the gas profile recorded,
+/// `state_view` should be the state before the transaction.
+pub fn profile_transaction<S: StateView>(
+    state_view: &S,
+    txn: SignedUserTransaction,
+    metrics: Option<VMMetrics>,
+) -> Result<(VMStatus, TransactionOutput, Option<GasProfile>)> {
+    let mut vm = StarcoinVM::new(metrics);
+    let state_view_cache = StateViewCache::new(state_view);
+    let (vm_status, output, record) = vm.execute_user_transaction_with_record(
+        &state_view_cache.as_move_resolver(),
+        txn,
+        PROFILE_OPTION,
+    )?;
+    Ok((vm_status, output, record.gas_profile))
+}
+
 pub fn dry_run_explain<S: StateView>(
     state_view: &S,
     txn: DryRunTransaction,
     metrics: Option<VMMetrics>,
 ) -> anyhow::Result<DryRunOutputView> {
     let (vm_status, output) = dry_run(state_view, txn.clone(), metrics)?;
+    explain_output(state_view, txn.raw_txn.payload(), vm_status, output)
+}
+
+/// Explain the vm status and decode the write set of the transaction output.
+pub fn explain_output<S: StateView>(
+    state_view: &S,
+    payload: &TransactionPayload,
+    vm_status: VMStatus,
+    output: TransactionOutput,
+) -> anyhow::Result<DryRunOutputView> {
     let vm_status_explain = vm_status_translator::explain_vm_status(state_view, vm_status)?;
     let mut txn_output: TransactionOutputView = output.into();
 
     let resolver = {
         let module_cache = ModuleCache::new();
         // If the txn is package txn, we need to use modules in the package to resolve transaction output.
-        if let TransactionPayload::Package(p) = txn.raw_txn.into_payload() {
+        if let TransactionPayload::Package(p) = payload {
             let modules = p
                 .modules()
                 .iter()
@@ -149,6 +201,7 @@ pub fn dry_run_explain<S: StateView>(
     Ok(DryRunOutputView {
         explained_status: vm_status_explain,
         txn_output,
+        gas_profile: None,
     })
 }
 
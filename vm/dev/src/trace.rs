@@ -0,0 +1,114 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::playground::explain_output;
+use anyhow::{format_err, Result};
+use starcoin_rpc_api::types::{DryRunOutputView, TransactionTraceView};
+use starcoin_vm_runtime::data_cache::{AsMoveResolver, StateViewCache};
+use starcoin_vm_runtime::metrics::VMMetrics;
+use starcoin_vm_runtime::starcoin_vm::{ExecutionRecordOption, StarcoinVM};
+use starcoin_vm_types::state_store::state_key::StateKey;
+use starcoin_vm_types::state_view::StateView;
+use starcoin_vm_types::transaction::{DryRunTransaction, SignedUserTransaction};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+const TRACE_OPTION: ExecutionRecordOption = ExecutionRecordOption {
+    call_trace: true,
+    gas_profile: false,
+};
+
+/// A state view which records the state keys read by the vm.
+struct ReadRecordingStateView<'a, S> {
+    inner: &'a S,
+    read_set: Mutex<BTreeSet<StateKey>>,
+}
+
+impl<'a, S: StateView> ReadRecordingStateView<'a, S> {
+    fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            read_set: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    fn into_read_set(self) -> Result<BTreeSet<StateKey>> {
+        self.read_set
+            .into_inner()
+            .map_err(|e| format_err!("read set lock poisoned: {}", e))
+    }
+}
+
+impl<'a, S: StateView> StateView for ReadRecordingStateView<'a, S> {
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<Vec<u8>>> {
+        if let Ok(mut read_set) = self.read_set.lock() {
+            read_set.insert(state_key.clone());
+        }
+        self.inner.get_state_value(state_key)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.inner.is_genesis()
+    }
+}
+
+/// Re-execute a committed user transaction on `state_view`, which should be the state before the transaction.
+pub fn trace_transaction<S: StateView>(
+    state_view: &S,
+    txn: SignedUserTransaction,
+    metrics: Option<VMMetrics>,
+) -> Result<TransactionTraceView> {
+    let txn_hash = txn.id();
+    let payload = txn.payload().clone();
+    let recording_view = ReadRecordingStateView::new(state_view);
+    let (vm_status, output, record) = {
+        let mut vm = StarcoinVM::new(metrics);
+        let state_view_cache = StateViewCache::new(&recording_view);
+        vm.execute_user_transaction_with_record(
+            &state_view_cache.as_move_resolver(),
+            txn,
+            TRACE_OPTION,
+        )?
+    };
+    let read_set = recording_view.into_read_set()?;
+    let DryRunOutputView {
+        explained_status,
+        txn_output,
+        ..
+    } = explain_output(state_view, &payload, vm_status, output)?;
+    Ok(TransactionTraceView {
+        transaction_hash: Some(txn_hash),
+        explained_status,
+        txn_output,
+        read_set: read_set.into_iter().map(Into::into).collect(),
+        call_trace: record.call_trace.map(Into::into),
+    })
+}
+
+/// Dry run a transaction on `state_view` with the call tree traced.
+pub fn trace_dry_run<S: StateView>(
+    state_view: &S,
+    txn: DryRunTransaction,
+    metrics: Option<VMMetrics>,
+) -> Result<TransactionTraceView> {
+    let payload = txn.raw_txn.payload().clone();
+    let recording_view = ReadRecordingStateView::new(state_view);
+    let (vm_status, output, record) = {
+        let mut vm = StarcoinVM::new(metrics);
+        let state_view_cache = StateViewCache::new(&recording_view);
+        vm.dry_run_transaction_with_record(&state_view_cache.as_move_resolver(), txn, TRACE_OPTION)?
+    };
+    let read_set = recording_view.into_read_set()?;
+    let DryRunOutputView {
+        explained_status,
+        txn_output,
+        ..
+    } = explain_output(state_view, &payload, vm_status, output)?;
+    Ok(TransactionTraceView {
+        transaction_hash: None,
+        explained_status,
+        txn_output,
+        read_set: read_set.into_iter().map(Into::into).collect(),
+        call_trace: record.call_trace.map(Into::into),
+    })
+}
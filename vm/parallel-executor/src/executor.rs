@@ -11,6 +11,7 @@ use crate::{
 use num_cpus;
 use once_cell::sync::Lazy;
 use starcoin_infallible::Mutex;
+use starcoin_logger::tracer::Span;
 use starcoin_mvhashmap::MVHashMap;
 use std::{collections::HashSet, hash::Hash, marker::PhantomData, sync::Arc, thread::spawn};
 
@@ -299,9 +300,15 @@ where
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
 
+        let mut span = Span::enter("parallel_executor.execute_transactions")
+            .with("txn_count", num_txns)
+            .with("concurrency_level", self.concurrency_level);
+        let span_context = span.context();
         RAYON_EXEC_POOL.scope(|s| {
             for _ in 0..self.concurrency_level {
                 s.spawn(|_| {
+                    let _worker_span =
+                        Span::enter_with_parent("parallel_executor.worker", span_context);
                     self.work_task_with_scope(
                         &executor_initial_arguments,
                         &signature_verified_block,
@@ -340,7 +347,10 @@ where
         });
 
         match maybe_err {
-            Some(err) => Err(err),
+            Some(err) => {
+                span.set_error("aborted");
+                Err(err)
+            }
             None => {
                 final_results.resize_with(num_txns, E::Output::skip_output);
                 Ok(final_results)
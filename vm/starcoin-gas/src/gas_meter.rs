@@ -9,7 +9,7 @@ use move_core_types::gas_algebra::{
     AbstractMemorySize, InternalGasPerAbstractMemoryUnit, InternalGasPerArg, InternalGasPerByte,
     NumArgs,
 };
-use move_core_types::language_storage::ModuleId;
+use move_core_types::language_storage::{ModuleId, TypeTag};
 use move_core_types::{
     gas_algebra::{InternalGas, NumBytes},
     vm_status::StatusCode,
@@ -23,6 +23,8 @@ use starcoin_gas_algebra_ext::{
 use starcoin_logger::prelude::*;
 use std::collections::BTreeMap;
 
+use crate::profile::{GasKind, GasProfile, GasProfiler};
+use crate::trace::{operand_address, render_value, CallTrace, ExecutionTracer, ResourceAccessKind};
 use move_binary_format::file_format_common::Opcodes;
 use starcoin_gas_algebra_ext::InstructionGasParameters;
 use starcoin_gas_algebra_ext::TransactionGasParameters;
@@ -142,6 +144,8 @@ pub struct StarcoinGasMeter {
     gas_params: StarcoinGasParameters,
     balance: InternalGas,
     charge: bool,
+    tracer: Option<ExecutionTracer>,
+    profiler: Option<GasProfiler>,
 }
 
 impl StarcoinGasMeter {
@@ -151,6 +155,8 @@ impl StarcoinGasMeter {
             gas_params,
             balance,
             charge: true,
+            tracer: None,
+            profiler: None,
         }
     }
 
@@ -190,13 +196,109 @@ impl StarcoinGasMeter {
             "charge_intrinsic_gas cost InternalGasUnits({}) {}",
             cost, self.charge
         );
-        self.deduct_gas(cost)
+        self.charge(GasKind::Intrinsic, cost)
             .map_err(|e| e.finish(Location::Undefined))
     }
 
+    /// Charge the gas of the write set, it is recorded as a storage operation in the gas profile.
+    pub fn charge_write_set_gas(&mut self, amount: InternalGas) -> PartialVMResult<()> {
+        self.charge(GasKind::Storage("WRITE_SET"), amount)
+    }
+
+    fn charge(&mut self, kind: GasKind, amount: InternalGas) -> PartialVMResult<()> {
+        if let Some(profiler) = self.profiler() {
+            profiler.record(kind, u64::from(amount));
+        }
+        self.deduct_gas(amount)
+    }
+
     pub fn cal_write_set_gas(&self) -> InternalGas {
         self.gas_params.txn.cal_write_set_gas()
     }
+
+    /// Record the call tree of the metered execution, `root` is the entry of the transaction.
+    /// The unmetered prologue and epilogue are not traced.
+    pub fn enable_trace(&mut self, root: CallTrace) {
+        let balance = u64::from(self.balance());
+        self.tracer = Some(ExecutionTracer::new(root, balance));
+    }
+
+    /// Take the recorded call tree, return None if trace is not enabled.
+    pub fn take_trace(&mut self) -> Option<CallTrace> {
+        let balance = u64::from(self.balance());
+        self.tracer.take().map(|tracer| tracer.finish(balance))
+    }
+
+    /// Record the gas used by instructions, natives, storage operations and call stacks
+    /// of the metered execution, `root` is the name of the root frame.
+    pub fn enable_profile(&mut self, root: &str) {
+        self.profiler = Some(GasProfiler::new(root));
+    }
+
+    /// Take the recorded gas profile, return None if profile is not enabled.
+    pub fn take_profile(&mut self) -> Option<GasProfile> {
+        self.profiler.take().map(GasProfiler::finish)
+    }
+
+    fn profiler(&mut self) -> Option<&mut GasProfiler> {
+        if self.charge {
+            self.profiler.as_mut()
+        } else {
+            None
+        }
+    }
+
+    fn tracer(&mut self) -> Option<&mut ExecutionTracer> {
+        if self.charge {
+            self.tracer.as_mut()
+        } else {
+            None
+        }
+    }
+
+    fn trace_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: Vec<TypeTag>,
+        args: Vec<String>,
+    ) {
+        let balance = u64::from(self.balance());
+        if let Some(tracer) = self.tracer() {
+            tracer.enter(module_id, func_name, ty_args, args, balance);
+        }
+    }
+
+    fn profile_call(&mut self, module_id: &ModuleId, func_name: &str) {
+        if let Some(profiler) = self.profiler() {
+            profiler.enter(module_id, func_name);
+        }
+    }
+
+    fn trace_resource(&mut self, kind: ResourceAccessKind, ty: impl TypeView, success: bool) {
+        if let Some(tracer) = self.tracer() {
+            tracer.access_resource(kind, ty, success);
+        }
+    }
+
+    /// Keep the operand stack of the tracer in step with the interpreter, the `push` operands
+    /// produced by the instruction are not addresses.
+    fn trace_operands(&mut self, pop: usize, push: usize) {
+        if let Some(tracer) = self.tracer() {
+            tracer.pop_operands(pop);
+            tracer.push_operands(std::iter::repeat(None).take(push));
+        }
+    }
+
+    /// Push the values produced by the instruction to the operand stack of the tracer.
+    fn trace_push_values<'a, V: ValueView + 'a>(
+        &mut self,
+        values: impl IntoIterator<Item = &'a V>,
+    ) {
+        if let Some(tracer) = self.tracer() {
+            tracer.push_operands(values.into_iter().map(operand_address));
+        }
+    }
 }
 
 #[inline]
@@ -220,7 +322,59 @@ fn cal_instr_with_byte(per_arg: InternalGasPerByte, size: NumBytes) -> InternalG
     per_arg * size
 }
 
-#[allow(dead_code)]
+/// The number of operands popped and pushed by the instruction.
+/// `FreezeRef` changes the type of the reference on the top only, so it is treated as no effect.
+fn simple_instr_stack_effect(instr: SimpleInstruction) -> (usize, usize) {
+    match instr {
+        SimpleInstruction::Nop
+        | SimpleInstruction::Ret
+        | SimpleInstruction::Branch
+        | SimpleInstruction::FreezeRef => (0, 0),
+
+        SimpleInstruction::BrTrue | SimpleInstruction::BrFalse | SimpleInstruction::Abort => (1, 0),
+
+        SimpleInstruction::LdU8
+        | SimpleInstruction::LdU16
+        | SimpleInstruction::LdU32
+        | SimpleInstruction::LdU64
+        | SimpleInstruction::LdU128
+        | SimpleInstruction::LdU256
+        | SimpleInstruction::LdTrue
+        | SimpleInstruction::LdFalse
+        | SimpleInstruction::MutBorrowLoc
+        | SimpleInstruction::ImmBorrowLoc => (0, 1),
+
+        SimpleInstruction::ImmBorrowField
+        | SimpleInstruction::MutBorrowField
+        | SimpleInstruction::ImmBorrowFieldGeneric
+        | SimpleInstruction::MutBorrowFieldGeneric
+        | SimpleInstruction::CastU8
+        | SimpleInstruction::CastU16
+        | SimpleInstruction::CastU32
+        | SimpleInstruction::CastU64
+        | SimpleInstruction::CastU128
+        | SimpleInstruction::CastU256
+        | SimpleInstruction::Not => (1, 1),
+
+        SimpleInstruction::Add
+        | SimpleInstruction::Sub
+        | SimpleInstruction::Mul
+        | SimpleInstruction::Mod
+        | SimpleInstruction::Div
+        | SimpleInstruction::BitOr
+        | SimpleInstruction::BitAnd
+        | SimpleInstruction::Xor
+        | SimpleInstruction::Shl
+        | SimpleInstruction::Shr
+        | SimpleInstruction::Or
+        | SimpleInstruction::And
+        | SimpleInstruction::Lt
+        | SimpleInstruction::Gt
+        | SimpleInstruction::Le
+        | SimpleInstruction::Ge => (2, 1),
+    }
+}
+
 #[inline]
 fn simple_instr_to_opcode(instr: SimpleInstruction) -> Opcodes {
     match instr {
@@ -297,10 +451,28 @@ impl GasMeter for StarcoinGasMeter {
             cost,
             self.charge
         );
-        self.deduct_gas(cost)
+        let result = if self.profiler().is_some() {
+            let opcode = format!("{:?}", simple_instr_to_opcode(instr));
+            self.charge(GasKind::Instruction(opcode.into()), cost)
+        } else {
+            self.deduct_gas(cost)
+        };
+        let (pop, push) = simple_instr_stack_effect(instr);
+        self.trace_operands(pop, push);
+        if matches!(instr, SimpleInstruction::Ret) {
+            let balance = u64::from(self.balance());
+            if let Some(tracer) = self.tracer() {
+                tracer.exit(balance);
+            }
+            if let Some(profiler) = self.profiler() {
+                profiler.exit();
+            }
+        }
+        result
     }
 
     fn charge_pop(&mut self, _popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.trace_operands(1, 0);
         let params = &self.gas_params.instr;
         let cost = params.pop;
         #[cfg(testing)]
@@ -308,56 +480,70 @@ impl GasMeter for StarcoinGasMeter {
             "simple_instr pop cost InternalGasUnits({}) {}",
             cost, self.charge
         );
-        self.deduct_gas(cost)
+        self.charge(GasKind::Instruction("POP".into()), cost)
     }
 
     #[inline]
     fn charge_call(
         &mut self,
-        _module_id: &ModuleId,
-        _func_name: &str,
+        module_id: &ModuleId,
+        func_name: &str,
         args: impl ExactSizeIterator<Item = impl ValueView>,
         _num_locals: NumArgs,
     ) -> PartialVMResult<()> {
+        let num_args = args.len() as u64;
+        if self.tracer().is_some() {
+            let args = args.map(render_value).collect();
+            self.trace_call(module_id, func_name, vec![], args);
+        }
+        self.trace_operands(num_args as usize, 0);
         let params = &self.gas_params.instr;
         // Note args.len() may be zero, can't use args.len() + 1 directly
         let cost1 = cal_instr_with_arg(params.call_per_arg, NumArgs::new(1));
         #[cfg(testing)]
         info!("CALL cost InternalGasUnits({}) {}", cost1, self.charge);
-        let cost2 = cal_instr_with_arg(params.call_per_arg, NumArgs::new(args.len() as u64));
+        let cost2 = cal_instr_with_arg(params.call_per_arg, NumArgs::new(num_args));
         #[cfg(testing)]
         info!("CALL cost InternalGasUnits({}) {}", cost2, self.charge);
-        self.deduct_gas(cost1 + cost2)
+        let result = self.charge(GasKind::Instruction("CALL".into()), cost1 + cost2);
+        self.profile_call(module_id, func_name);
+        result
     }
 
     #[inline]
     fn charge_call_generic(
         &mut self,
-        _module_id: &ModuleId,
-        _func_name: &str,
+        module_id: &ModuleId,
+        func_name: &str,
         ty_args: impl ExactSizeIterator<Item = impl TypeView>,
         args: impl ExactSizeIterator<Item = impl ValueView>,
         _num_locals: NumArgs,
     ) -> PartialVMResult<()> {
+        let num_ty_args = ty_args.len() as u64;
+        let num_args = args.len() as u64;
+        if self.tracer().is_some() {
+            let ty_args = ty_args.map(|ty| ty.to_type_tag()).collect();
+            let args = args.map(render_value).collect();
+            self.trace_call(module_id, func_name, ty_args, args);
+        }
+        self.trace_operands(num_args as usize, 0);
         let params = &self.gas_params.instr;
         // Note args.len() may be zero, can't use ty_args.len() + args.len() + 1 directly
-        let cost1 = cal_instr_with_arg(
-            params.call_generic_per_arg,
-            NumArgs::new((ty_args.len() + 1) as u64),
-        );
+        let cost1 = cal_instr_with_arg(params.call_generic_per_arg, NumArgs::new(num_ty_args + 1));
         #[cfg(testing)]
         info!(
             "CALL_GENERIC cost InternalGasUnits({}) {}",
             cost1, self.charge
         );
-        let cost2 =
-            cal_instr_with_arg(params.call_generic_per_arg, NumArgs::new(args.len() as u64));
+        let cost2 = cal_instr_with_arg(params.call_generic_per_arg, NumArgs::new(num_args));
         #[cfg(testing)]
         info!(
             "CALL_GENERIC cost InternalGasUnits({}) {}",
             cost2, self.charge
         );
-        self.deduct_gas(cost1 + cost2)
+        let result = self.charge(GasKind::Instruction("CALL_GENERIC".into()), cost1 + cost2);
+        self.profile_call(module_id, func_name);
+        result
     }
 
     #[inline]
@@ -366,13 +552,14 @@ impl GasMeter for StarcoinGasMeter {
         let cost = cal_instr_with_byte(instr.ld_const_per_byte, size);
         #[cfg(testing)]
         info!("LD_CONST cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.charge(GasKind::Instruction("LD_CONST".into()), cost)
     }
 
     fn charge_ld_const_after_deserialization(
         &mut self,
-        _val: impl ValueView,
+        val: impl ValueView,
     ) -> PartialVMResult<()> {
+        self.trace_push_values([&val]);
         Ok(())
     }
 
@@ -385,7 +572,8 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("COPY_LOC cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_push_values([&val]);
+        self.charge(GasKind::Instruction("COPY_LOC".into()), cost)
     }
 
     #[inline]
@@ -397,7 +585,8 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("MOVE_LOC cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_push_values([&val]);
+        self.charge(GasKind::Instruction("MOVE_LOC".into()), cost)
     }
 
     #[inline]
@@ -409,7 +598,8 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("ST_LOC cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(1, 0);
+        self.charge(GasKind::Instruction("ST_LOC".into()), cost)
     }
 
     #[inline]
@@ -418,6 +608,7 @@ impl GasMeter for StarcoinGasMeter {
         is_generic: bool,
         args: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
+        self.trace_operands(args.len(), 1);
         let field_count = AbstractMemorySize::new(args.len() as u64);
         let params = &self.gas_params.instr;
         let size = args.fold(field_count, |acc, val| {
@@ -438,7 +629,11 @@ impl GasMeter for StarcoinGasMeter {
                 info!("PACK cost InternalGasUnits({}) {}", cost, self.charge);
             }
         }
-        self.deduct_gas(cost)
+        let opcode = match is_generic {
+            false => "PACK",
+            true => "PACK_GENERIC",
+        };
+        self.charge(GasKind::Instruction(opcode.into()), cost)
     }
 
     #[inline]
@@ -463,6 +658,9 @@ impl GasMeter for StarcoinGasMeter {
         };
         let field_count = AbstractMemorySize::new(args.len() as u64);
         let mut cost = cal_instr_with_size(param, field_count);
+        self.trace_operands(1, 0);
+        let tracing = self.tracer().is_some();
+        let mut fields = vec![];
         #[cfg(testing)]
         info!(
             "{:#?} cost InternalGasUnits({}) {}",
@@ -476,8 +674,18 @@ impl GasMeter for StarcoinGasMeter {
                 opcode, cost2, self.charge
             );
             cost += cost2;
+            if tracing {
+                fields.push(operand_address(&val));
+            }
         }
-        self.deduct_gas(cost)
+        if let Some(tracer) = self.tracer() {
+            tracer.push_operands(fields);
+        }
+        let opcode = match is_generic {
+            false => "UNPACK",
+            true => "UNPACK_GENERIC",
+        };
+        self.charge(GasKind::Instruction(opcode.into()), cost)
     }
 
     #[inline]
@@ -488,7 +696,9 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("READ_REF cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(1, 0);
+        self.trace_push_values([&val]);
+        self.charge(GasKind::Instruction("READ_REF".into()), cost)
     }
 
     #[inline]
@@ -503,7 +713,8 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("WRITE_REF cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(2, 0);
+        self.charge(GasKind::Instruction("WRITE_REF".into()), cost)
     }
 
     #[inline]
@@ -515,7 +726,8 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("EQ cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(2, 1);
+        self.charge(GasKind::Instruction("EQ".into()), cost)
     }
 
     #[inline]
@@ -527,17 +739,23 @@ impl GasMeter for StarcoinGasMeter {
         );
         #[cfg(testing)]
         info!("NEQ cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(2, 1);
+        self.charge(GasKind::Instruction("NEQ".into()), cost)
     }
 
     #[inline]
     fn charge_borrow_global(
         &mut self,
-        _is_mut: bool,
+        is_mut: bool,
         is_generic: bool,
-        _ty: impl TypeView,
+        ty: impl TypeView,
         is_success: bool,
     ) -> PartialVMResult<()> {
+        let kind = match is_mut {
+            false => ResourceAccessKind::BorrowGlobal,
+            true => ResourceAccessKind::BorrowGlobalMut,
+        };
+        self.trace_resource(kind, ty, is_success);
         let cost = if !is_success {
             0.into()
         } else {
@@ -559,16 +777,21 @@ impl GasMeter for StarcoinGasMeter {
             "{:#?} cost InternalGasUnits({}) {}",
             opcode, cost, self.charge
         );
-        self.deduct_gas(cost)
+        let operation = match is_generic {
+            false => "MUT_BORROW_GLOBAL",
+            true => "MUT_BORROW_GLOBAL_GENERIC",
+        };
+        self.charge(GasKind::Storage(operation), cost)
     }
 
     #[inline]
     fn charge_exists(
         &mut self,
         is_generic: bool,
-        _ty: impl TypeView,
+        ty: impl TypeView,
         exists: bool,
     ) -> PartialVMResult<()> {
+        self.trace_resource(ResourceAccessKind::Exists, ty, exists);
         let params = &self.gas_params.instr;
         let param = match is_generic {
             false => params.exists_per_abs_mem_unit,
@@ -589,16 +812,21 @@ impl GasMeter for StarcoinGasMeter {
             "{:#?} cost InternalGasUnits({}) {}",
             opcode, cost, self.charge
         );
-        self.deduct_gas(cost)
+        let operation = match is_generic {
+            false => "EXISTS",
+            true => "EXISTS_GENERIC",
+        };
+        self.charge(GasKind::Storage(operation), cost)
     }
 
     #[inline]
     fn charge_move_from(
         &mut self,
         is_generic: bool,
-        _ty: impl TypeView,
+        ty: impl TypeView,
         val: Option<impl ValueView>,
     ) -> PartialVMResult<()> {
+        self.trace_resource(ResourceAccessKind::MoveFrom, ty, val.is_some());
         if let Some(val) = val {
             let params = &self.gas_params.instr;
             let param = match is_generic {
@@ -616,7 +844,11 @@ impl GasMeter for StarcoinGasMeter {
                 "MOVE_FROM {:#?} cost InternalGasUnits({}) {}",
                 opcode, cost, self.charge
             );
-            return self.deduct_gas(cost);
+            let operation = match is_generic {
+                false => "MOVE_FROM",
+                true => "MOVE_FROM_GENERIC",
+            };
+            return self.charge(GasKind::Storage(operation), cost);
         }
         Ok(())
     }
@@ -625,10 +857,11 @@ impl GasMeter for StarcoinGasMeter {
     fn charge_move_to(
         &mut self,
         is_generic: bool,
-        _ty: impl TypeView,
+        ty: impl TypeView,
         val: impl ValueView,
         is_success: bool,
     ) -> PartialVMResult<()> {
+        self.trace_resource(ResourceAccessKind::MoveTo, ty, is_success);
         let cost = if !is_success {
             0.into()
         } else {
@@ -649,7 +882,11 @@ impl GasMeter for StarcoinGasMeter {
             "charge_MOVE_TO {:#?} cost InternalGasUnits({}) {}",
             opcode, cost, self.charge
         );
-        self.deduct_gas(cost)
+        let operation = match is_generic {
+            false => "MOVE_TO",
+            true => "MOVE_TO_GENERIC",
+        };
+        self.charge(GasKind::Storage(operation), cost)
     }
 
     #[inline]
@@ -658,12 +895,13 @@ impl GasMeter for StarcoinGasMeter {
         _ty: impl TypeView + 'a,
         args: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
+        self.trace_operands(args.len(), 1);
         let num_args = NumArgs::new(args.len() as u64);
         let params = &self.gas_params.instr;
         let cost = cal_instr_with_arg(params.vec_pack_per_elem, num_args);
         #[cfg(testing)]
         info!("VEC_PACK cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.charge(GasKind::Instruction("VEC_PACK".into()), cost)
     }
 
     #[inline]
@@ -671,7 +909,8 @@ impl GasMeter for StarcoinGasMeter {
         let cost = self.gas_params.instr.vec_len_base;
         #[cfg(testing)]
         info!("VEC_LEN cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(1, 1);
+        self.charge(GasKind::Instruction("VEC_LEN".into()), cost)
     }
 
     #[inline]
@@ -700,7 +939,12 @@ impl GasMeter for StarcoinGasMeter {
             "{:#?} cost InternalGasUnits({}) {}",
             opcode, cost, self.charge
         );
-        self.deduct_gas(cost)
+        self.trace_operands(2, 1);
+        let opcode = match is_mut {
+            false => "VEC_IMM_BORROW",
+            true => "VEC_MUT_BORROW",
+        };
+        self.charge(GasKind::Instruction(opcode.into()), cost)
     }
 
     #[inline]
@@ -718,22 +962,25 @@ impl GasMeter for StarcoinGasMeter {
             "VEC_PUSH_BACK cost InternalGasUnits({}) {}",
             cost, self.charge
         );
-        self.deduct_gas(cost)
+        self.trace_operands(2, 0);
+        self.charge(GasKind::Instruction("VEC_PUSH_BACK".into()), cost)
     }
 
     #[inline]
     fn charge_vec_pop_back(
         &mut self,
         _ty: impl TypeView,
-        _val: Option<impl ValueView>,
+        val: Option<impl ValueView>,
     ) -> PartialVMResult<()> {
+        self.trace_operands(1, 0);
+        self.trace_push_values(val.as_ref());
         let cost = self.gas_params.instr.vec_pop_back_base;
         #[cfg(testing)]
         info!(
             "VEC_POP_BACK cost InternalGasUnits({}) {}",
             cost, self.charge
         );
-        self.deduct_gas(cost)
+        self.charge(GasKind::Instruction("VEC_POP_BACK".into()), cost)
     }
 
     #[inline]
@@ -741,15 +988,19 @@ impl GasMeter for StarcoinGasMeter {
         &mut self,
         _ty: impl TypeView,
         expect_num_elements: NumArgs,
-        _elems: impl ExactSizeIterator<Item = impl ValueView>,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
+        self.trace_operands(1, 0);
+        if let Some(tracer) = self.tracer() {
+            tracer.push_operands(elems.map(|elem| operand_address(&elem)));
+        }
         let cost = cal_instr_with_arg(
             self.gas_params.instr.vec_unpack_per_expected_elem,
             expect_num_elements,
         );
         #[cfg(testing)]
         info!("VEC_UNPACK cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.charge(GasKind::Instruction("VEC_UNPACK".into()), cost)
     }
 
     #[inline]
@@ -757,7 +1008,8 @@ impl GasMeter for StarcoinGasMeter {
         let cost = self.gas_params.instr.vec_swap_base;
         #[cfg(testing)]
         info!("VEC_SWAP cost InternalGasUnits({}) {}", cost, self.charge);
-        self.deduct_gas(cost)
+        self.trace_operands(3, 0);
+        self.charge(GasKind::Instruction("VEC_SWAP".into()), cost)
     }
 
     #[inline]
@@ -772,21 +1024,40 @@ impl GasMeter for StarcoinGasMeter {
     fn charge_native_function(
         &mut self,
         amount: InternalGas,
-        _ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
     ) -> PartialVMResult<()> {
+        // the arguments of the native function are popped by `charge_call`.
+        let success = ret_vals.is_some();
+        if let (Some(ret_vals), Some(tracer)) = (ret_vals, self.tracer()) {
+            tracer.push_operands(ret_vals.map(|val| operand_address(&val)));
+        }
         #[cfg(testing)]
         info!(
             "NATIVE_FUNCTION cost InternalGasUnits({}) {}",
             amount, self.charge
         );
-        self.deduct_gas(amount)
+        let result = self.charge(GasKind::Native, amount);
+        let balance = u64::from(self.balance());
+        if let Some(tracer) = self.tracer() {
+            tracer.exit_native(balance, success);
+        }
+        if let Some(profiler) = self.profiler() {
+            profiler.exit_native();
+        }
+        result
     }
 
     fn charge_native_function_before_execution(
         &mut self,
-        _ty_args: impl ExactSizeIterator<Item = impl TypeView>,
-        _args: impl ExactSizeIterator<Item = impl ValueView>,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
+        if let Some(tracer) = self.tracer() {
+            tracer.enter_native(ty_args, args);
+        }
+        if let Some(profiler) = self.profiler() {
+            profiler.enter_native();
+        }
         Ok(())
     }
 
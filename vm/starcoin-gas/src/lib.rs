@@ -19,10 +19,14 @@
 //!     in the future.
 
 mod gas_meter;
+mod profile;
+mod trace;
 
 pub use gas_meter::{NativeGasParameters, StarcoinGasMeter, StarcoinGasParameters};
 pub use move_core_types::gas_algebra::{
     Arg, Byte, GasQuantity, InternalGas, InternalGasPerArg, InternalGasPerByte, InternalGasUnit,
     NumArgs, NumBytes, UnitDiv,
 };
+pub use profile::{GasProfile, GasStat};
 pub use starcoin_gas_algebra_ext::InstructionGasParameters;
+pub use trace::{CallTrace, EmittedEvent, ResourceAccess, ResourceAccessKind};
@@ -0,0 +1,189 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas profile of the metered execution, recorded by the gas meter when profiling is enabled.
+
+use move_core_types::language_storage::ModuleId;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Separator of the frames in a folded call stack.
+const FRAME_SEPARATOR: char = ';';
+
+/// What a piece of gas is charged for.
+#[derive(Clone, Debug)]
+pub(crate) enum GasKind {
+    Instruction(Cow<'static, str>),
+    Storage(&'static str),
+    Native,
+    Intrinsic,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GasStat {
+    /// Number of the charges.
+    pub count: u64,
+    pub gas: u64,
+}
+
+impl GasStat {
+    fn add(&mut self, count: u64, gas: u64) {
+        self.count = self.count.saturating_add(count);
+        self.gas = self.gas.saturating_add(gas);
+    }
+}
+
+/// Gas used by the metered execution, in internal gas units.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GasProfile {
+    /// Bytecode instructions by opcode, the global storage instructions are in `storage`.
+    pub instructions: BTreeMap<String, GasStat>,
+    /// Native functions by `address::module::function`.
+    pub natives: BTreeMap<String, GasStat>,
+    /// Global storage instructions, and the write set of the transaction.
+    pub storage: BTreeMap<String, GasStat>,
+    /// Intrinsic gas of the transaction, charged by the transaction size.
+    pub intrinsic: u64,
+    /// Self gas by call stack, the frames of a stack are separated by `;`.
+    pub frames: BTreeMap<String, u64>,
+}
+
+impl GasProfile {
+    /// Name of a function frame in the call stacks, such as `0x1::Account::deposit`.
+    pub fn frame_name(module_id: &ModuleId, func_name: &str) -> String {
+        format!(
+            "{}::{}::{}",
+            module_id.address().to_hex_literal(),
+            module_id.name(),
+            func_name
+        )
+    }
+
+    pub fn total_gas(&self) -> u64 {
+        self.frames
+            .values()
+            .fold(0u64, |total, gas| total.saturating_add(*gas))
+    }
+
+    /// Merge another profile into this one, for example the profiles of the transactions of a block.
+    pub fn merge(&mut self, other: GasProfile) {
+        for (target, source) in [
+            (&mut self.instructions, other.instructions),
+            (&mut self.natives, other.natives),
+            (&mut self.storage, other.storage),
+        ] {
+            for (key, stat) in source {
+                target.entry(key).or_default().add(stat.count, stat.gas);
+            }
+        }
+        self.intrinsic = self.intrinsic.saturating_add(other.intrinsic);
+        for (stack, gas) in other.frames {
+            let self_gas = self.frames.entry(stack).or_default();
+            *self_gas = self_gas.saturating_add(gas);
+        }
+    }
+
+    /// Render the profile in the folded stack format, one `stack gas` per line,
+    /// which can be rendered by flamegraph tools such as `inferno-flamegraph`.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut out = String::new();
+        for (stack, gas) in &self.frames {
+            if *gas > 0 {
+                let _ = writeln!(out, "{} {}", stack, gas);
+            }
+        }
+        out
+    }
+}
+
+/// Record the gas charges into a `GasProfile`.
+pub(crate) struct GasProfiler {
+    profile: GasProfile,
+    /// Open frames and the length of the folded stack before entering them, the root is never popped.
+    frames: Vec<(bool, usize)>,
+    stack: String,
+}
+
+impl GasProfiler {
+    pub(crate) fn new(root: &str) -> Self {
+        Self {
+            profile: GasProfile::default(),
+            frames: vec![(false, 0)],
+            stack: root.to_string(),
+        }
+    }
+
+    pub(crate) fn enter(&mut self, module_id: &ModuleId, func_name: &str) {
+        self.frames.push((false, self.stack.len()));
+        self.stack.push(FRAME_SEPARATOR);
+        self.stack
+            .push_str(GasProfile::frame_name(module_id, func_name).as_str());
+    }
+
+    /// Mark the current frame as a native function, natives do not execute `Ret`.
+    pub(crate) fn enter_native(&mut self) {
+        if let Some((is_native, _)) = self.frames.last_mut() {
+            *is_native = true;
+        }
+    }
+
+    pub(crate) fn exit(&mut self) {
+        if self.frames.len() > 1 {
+            if let Some((_, stack_len)) = self.frames.pop() {
+                self.stack.truncate(stack_len);
+            }
+        }
+    }
+
+    /// Exit the current frame if it is a native function.
+    pub(crate) fn exit_native(&mut self) {
+        if matches!(self.frames.last(), Some((true, _))) {
+            self.exit();
+        }
+    }
+
+    pub(crate) fn record(&mut self, kind: GasKind, gas: u64) {
+        let stat = match kind {
+            GasKind::Instruction(opcode) => Some(
+                self.profile
+                    .instructions
+                    .entry(opcode.into_owned())
+                    .or_default(),
+            ),
+            GasKind::Storage(operation) => Some(
+                self.profile
+                    .storage
+                    .entry(operation.to_string())
+                    .or_default(),
+            ),
+            GasKind::Native => {
+                // the current frame is the native function.
+                let name = self
+                    .stack
+                    .rsplit(FRAME_SEPARATOR)
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                Some(self.profile.natives.entry(name).or_default())
+            }
+            GasKind::Intrinsic => {
+                self.profile.intrinsic = self.profile.intrinsic.saturating_add(gas);
+                None
+            }
+        };
+        if let Some(stat) = stat {
+            stat.add(1, gas);
+        }
+        match self.profile.frames.get_mut(&self.stack) {
+            Some(self_gas) => *self_gas = self_gas.saturating_add(gas),
+            None => {
+                self.profile.frames.insert(self.stack.clone(), gas);
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> GasProfile {
+        self.profile
+    }
+}
@@ -0,0 +1,506 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structured execution trace, recorded by the gas meter when tracing is enabled.
+//! The gas meter sees every call, return and global storage operation of the Move VM,
+//! so the trace is built from the gas meter hooks without changing the interpreter.
+//! The hooks do not pass the address of a global storage operation, the tracer keeps a shadow
+//! of the operand stack from the stack effect of every hook to find the address operand.
+//! Events are recorded from the arguments of the native function which writes the event store.
+
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::{ModuleId, TypeTag, CORE_CODE_ADDRESS};
+use move_core_types::u256::U256;
+use move_vm_types::views::{TypeView, ValueView, ValueVisitor};
+
+/// Max nested depth of a rendered value, deeper values are rendered as `..`.
+const MAX_VALUE_DEPTH: usize = 8;
+/// Max length of a rendered value, longer values are truncated.
+const MAX_VALUE_LEN: usize = 512;
+/// The native function which emits an event: `write_to_event_store<T>(guid, count, msg: T)`.
+const EVENT_MODULE: &str = "Event";
+const EVENT_NATIVE_FUNCTION: &str = "write_to_event_store";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResourceAccessKind {
+    BorrowGlobal,
+    BorrowGlobalMut,
+    Exists,
+    MoveFrom,
+    MoveTo,
+}
+
+/// A global storage operation of a frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResourceAccess {
+    pub kind: ResourceAccessKind,
+    /// None if the address operand is not visible to the gas meter, such as a signer borrowed
+    /// from a local by `&account`.
+    pub address: Option<AccountAddress>,
+    pub type_tag: TypeTag,
+    /// false if the resource does not exist for borrow/exists/move_from, or exists for move_to.
+    pub success: bool,
+}
+
+/// An event emitted by a frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmittedEvent {
+    /// The bytes of the event key.
+    pub key: Vec<u8>,
+    pub seq_number: u64,
+    pub type_tag: TypeTag,
+    /// The rendered event data.
+    pub data: String,
+}
+
+/// A function call in the trace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallTrace {
+    /// None for the script of a script transaction.
+    pub module: Option<ModuleId>,
+    pub function: String,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<String>,
+    pub is_native: bool,
+    /// Gas used by the frame, including the sub calls.
+    pub gas_used: u64,
+    pub resources: Vec<ResourceAccess>,
+    /// The events emitted by the frame, by calling the event native function.
+    pub events: Vec<EmittedEvent>,
+    pub calls: Vec<CallTrace>,
+    /// The frame does not return, the execution aborts in it or in a sub call.
+    pub aborted: bool,
+}
+
+impl CallTrace {
+    pub fn new(
+        module: Option<ModuleId>,
+        function: String,
+        ty_args: Vec<TypeTag>,
+        args: Vec<String>,
+    ) -> Self {
+        Self {
+            module,
+            function,
+            ty_args,
+            args,
+            is_native: false,
+            gas_used: 0,
+            resources: vec![],
+            events: vec![],
+            calls: vec![],
+            aborted: false,
+        }
+    }
+}
+
+/// Build the call tree from the gas meter hooks.
+/// The root frame is the entry of the transaction, which is not charged as a call.
+pub(crate) struct ExecutionTracer {
+    /// The open frames and the gas balance when entering them, the first one is the root.
+    frames: Vec<(CallTrace, u64)>,
+    root: Option<CallTrace>,
+    /// The shadow of the interpreter operand stack, only the address operands are tracked.
+    operands: Vec<Option<AccountAddress>>,
+    /// The event of the running event native function, recorded when the native returns.
+    pending_event: Option<EmittedEvent>,
+}
+
+impl ExecutionTracer {
+    pub(crate) fn new(root: CallTrace, balance: u64) -> Self {
+        Self {
+            frames: vec![(root, balance)],
+            root: None,
+            operands: vec![],
+            pending_event: None,
+        }
+    }
+
+    pub(crate) fn enter(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: Vec<TypeTag>,
+        args: Vec<String>,
+        balance: u64,
+    ) {
+        if self.root.is_some() {
+            return;
+        }
+        self.frames.push((
+            CallTrace::new(
+                Some(module_id.clone()),
+                func_name.to_string(),
+                ty_args,
+                args,
+            ),
+            balance,
+        ));
+    }
+
+    /// Mark the current frame as a native function, natives do not execute `Ret`.
+    /// The event of the event native function is kept until the native returns.
+    pub(crate) fn enter_native<V: ValueView>(
+        &mut self,
+        mut ty_args: impl Iterator<Item = impl TypeView>,
+        args: impl Iterator<Item = V>,
+    ) {
+        if let Some((frame, _)) = self.frames.last_mut() {
+            frame.is_native = true;
+            let is_event_native = frame.function == EVENT_NATIVE_FUNCTION
+                && frame.module.as_ref().map_or(false, |module| {
+                    module.address() == &CORE_CODE_ADDRESS && module.name().as_str() == EVENT_MODULE
+                });
+            if is_event_native {
+                let args: Vec<V> = args.collect();
+                self.pending_event = match (ty_args.next(), args.as_slice()) {
+                    (Some(ty), [guid, count, msg]) => Some(EmittedEvent {
+                        key: operand_bytes(guid).unwrap_or_default(),
+                        seq_number: render_value(count).parse().unwrap_or_default(),
+                        type_tag: ty.to_type_tag(),
+                        data: render_value(msg),
+                    }),
+                    _ => None,
+                };
+            }
+        }
+    }
+
+    pub(crate) fn exit(&mut self, balance: u64) {
+        if let Some((mut frame, entry_balance)) = self.frames.pop() {
+            frame.gas_used = entry_balance.saturating_sub(balance);
+            match self.frames.last_mut() {
+                Some((parent, _)) => parent.calls.push(frame),
+                None => self.root = Some(frame),
+            }
+        }
+    }
+
+    /// Exit the current frame if it is a native function, the event of the native is recorded
+    /// on the caller frame if the native `success`.
+    pub(crate) fn exit_native(&mut self, balance: u64, success: bool) {
+        if matches!(self.frames.last(), Some((frame, _)) if frame.is_native) {
+            self.exit(balance);
+            if let (Some(event), true) = (self.pending_event.take(), success) {
+                if let Some((frame, _)) = self.frames.last_mut() {
+                    frame.events.push(event);
+                }
+            }
+        }
+    }
+
+    /// Pop the operands consumed by an instruction.
+    pub(crate) fn pop_operands(&mut self, n: usize) {
+        let len = self.operands.len().saturating_sub(n);
+        self.operands.truncate(len);
+    }
+
+    /// Push the operands produced by an instruction, None for a non-address operand.
+    pub(crate) fn push_operands(
+        &mut self,
+        addresses: impl IntoIterator<Item = Option<AccountAddress>>,
+    ) {
+        self.operands.extend(addresses);
+    }
+
+    pub(crate) fn access_resource(
+        &mut self,
+        kind: ResourceAccessKind,
+        ty: impl TypeView,
+        success: bool,
+    ) {
+        // move_to consumes the signer and the resource on the top, the others consume the
+        // address and produce a reference, a bool or the resource.
+        let address = match kind {
+            ResourceAccessKind::MoveTo => {
+                self.operands.pop();
+                self.operands.pop().flatten()
+            }
+            _ => {
+                let address = self.operands.pop().flatten();
+                self.operands.push(None);
+                address
+            }
+        };
+        if let Some((frame, _)) = self.frames.last_mut() {
+            frame.resources.push(ResourceAccess {
+                kind,
+                address,
+                type_tag: ty.to_type_tag(),
+                success,
+            });
+        }
+    }
+
+    /// Finish the trace, the frames still open are the frames the execution aborts in.
+    pub(crate) fn finish(mut self, balance: u64) -> CallTrace {
+        while !self.frames.is_empty() {
+            if let Some((frame, _)) = self.frames.last_mut() {
+                frame.aborted = true;
+            }
+            self.exit(balance);
+        }
+        self.root
+            .unwrap_or_else(|| CallTrace::new(None, String::new(), vec![], vec![]))
+    }
+}
+
+/// Render a Move value to a readable string, such as `{0x1, [1, 2], &true}`.
+pub(crate) fn render_value(value: impl ValueView) -> String {
+    let mut renderer = ValueRenderer::default();
+    value.visit(&mut renderer);
+    let mut out = renderer.out;
+    if out.len() > MAX_VALUE_LEN {
+        let mut end = MAX_VALUE_LEN;
+        while !out.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.truncate(end);
+        out.push_str("...");
+    }
+    out
+}
+
+/// Get the address of an operand which is an address, a signer or a reference to them.
+pub(crate) fn operand_address(value: &impl ValueView) -> Option<AccountAddress> {
+    let mut visitor = AddressVisitor::default();
+    value.visit(&mut visitor);
+    match visitor.other {
+        true => None,
+        false => visitor.address,
+    }
+}
+
+/// Get the bytes of an operand which is a `vector<u8>`.
+pub(crate) fn operand_bytes(value: &impl ValueView) -> Option<Vec<u8>> {
+    let mut visitor = BytesVisitor::default();
+    value.visit(&mut visitor);
+    match visitor.other {
+        true => None,
+        false => Some(visitor.bytes),
+    }
+}
+
+#[derive(Default)]
+struct BytesVisitor {
+    bytes: Vec<u8>,
+    /// A value other than a byte vector is visited.
+    other: bool,
+}
+
+impl ValueVisitor for BytesVisitor {
+    fn visit_u8(&mut self, depth: usize, val: u8) {
+        if depth == 1 {
+            self.bytes.push(val);
+        } else {
+            self.other = true;
+        }
+    }
+
+    fn visit_u16(&mut self, _depth: usize, _val: u16) {
+        self.other = true;
+    }
+
+    fn visit_u32(&mut self, _depth: usize, _val: u32) {
+        self.other = true;
+    }
+
+    fn visit_u64(&mut self, _depth: usize, _val: u64) {
+        self.other = true;
+    }
+
+    fn visit_u128(&mut self, _depth: usize, _val: u128) {
+        self.other = true;
+    }
+
+    fn visit_u256(&mut self, _depth: usize, _val: U256) {
+        self.other = true;
+    }
+
+    fn visit_bool(&mut self, _depth: usize, _val: bool) {
+        self.other = true;
+    }
+
+    fn visit_address(&mut self, _depth: usize, _val: AccountAddress) {
+        self.other = true;
+    }
+
+    fn visit_struct(&mut self, _depth: usize, _len: usize) -> bool {
+        self.other = true;
+        false
+    }
+
+    fn visit_vec(&mut self, depth: usize, _len: usize) -> bool {
+        if depth != 0 {
+            self.other = true;
+        }
+        !self.other
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        self.other = true;
+        false
+    }
+}
+
+#[derive(Default)]
+struct AddressVisitor {
+    address: Option<AccountAddress>,
+    /// A value other than a single address is visited.
+    other: bool,
+}
+
+impl ValueVisitor for AddressVisitor {
+    fn visit_u8(&mut self, _depth: usize, _val: u8) {
+        self.other = true;
+    }
+
+    fn visit_u16(&mut self, _depth: usize, _val: u16) {
+        self.other = true;
+    }
+
+    fn visit_u32(&mut self, _depth: usize, _val: u32) {
+        self.other = true;
+    }
+
+    fn visit_u64(&mut self, _depth: usize, _val: u64) {
+        self.other = true;
+    }
+
+    fn visit_u128(&mut self, _depth: usize, _val: u128) {
+        self.other = true;
+    }
+
+    fn visit_u256(&mut self, _depth: usize, _val: U256) {
+        self.other = true;
+    }
+
+    fn visit_bool(&mut self, _depth: usize, _val: bool) {
+        self.other = true;
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        if self.address.replace(val).is_some() {
+            self.other = true;
+        }
+    }
+
+    /// A signer is a struct with a single address field.
+    fn visit_struct(&mut self, _depth: usize, len: usize) -> bool {
+        if len != 1 {
+            self.other = true;
+        }
+        !self.other
+    }
+
+    fn visit_vec(&mut self, _depth: usize, _len: usize) -> bool {
+        self.other = true;
+        false
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        !self.other
+    }
+}
+
+#[derive(Default)]
+struct ValueRenderer {
+    out: String,
+    /// The remaining children and the close token of the open containers.
+    containers: Vec<(usize, &'static str)>,
+    need_separator: bool,
+}
+
+impl ValueRenderer {
+    fn separator(&mut self) {
+        if self.need_separator {
+            self.out.push_str(", ");
+        }
+    }
+
+    fn leaf(&mut self, value: impl std::fmt::Display) {
+        self.separator();
+        if self.out.len() <= MAX_VALUE_LEN {
+            self.out.push_str(value.to_string().as_str());
+        }
+        self.need_separator = true;
+        self.close();
+    }
+
+    fn open(&mut self, depth: usize, open: &str, close: &'static str, len: usize) -> bool {
+        if depth >= MAX_VALUE_DEPTH {
+            self.leaf(format!("{}..{}", open, close));
+            return false;
+        }
+        self.separator();
+        self.out.push_str(open);
+        if len == 0 {
+            self.out.push_str(close);
+            self.need_separator = true;
+            self.close();
+        } else {
+            self.containers.push((len, close));
+            self.need_separator = false;
+        }
+        true
+    }
+
+    /// A child is rendered, close the containers whose children are all rendered.
+    fn close(&mut self) {
+        while let Some((remaining, close)) = self.containers.last_mut() {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining > 0 {
+                break;
+            }
+            let close = *close;
+            self.containers.pop();
+            self.out.push_str(close);
+        }
+    }
+}
+
+impl ValueVisitor for ValueRenderer {
+    fn visit_u8(&mut self, _depth: usize, val: u8) {
+        self.leaf(val);
+    }
+
+    fn visit_u16(&mut self, _depth: usize, val: u16) {
+        self.leaf(val);
+    }
+
+    fn visit_u32(&mut self, _depth: usize, val: u32) {
+        self.leaf(val);
+    }
+
+    fn visit_u64(&mut self, _depth: usize, val: u64) {
+        self.leaf(val);
+    }
+
+    fn visit_u128(&mut self, _depth: usize, val: u128) {
+        self.leaf(val);
+    }
+
+    fn visit_u256(&mut self, _depth: usize, val: U256) {
+        self.leaf(val);
+    }
+
+    fn visit_bool(&mut self, _depth: usize, val: bool) {
+        self.leaf(val);
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        self.leaf(val.to_hex_literal());
+    }
+
+    fn visit_struct(&mut self, depth: usize, len: usize) -> bool {
+        self.open(depth, "{", "}", len)
+    }
+
+    fn visit_vec(&mut self, depth: usize, len: usize) -> bool {
+        self.open(depth, "[", "]", len)
+    }
+
+    fn visit_ref(&mut self, depth: usize, _is_global: bool) -> bool {
+        self.open(depth, "&", "", 1)
+    }
+}
@@ -708,6 +708,7 @@ impl<'a> StarcoinTestAdapter<'a> {
             function_id,
             type_args,
             args,
+            ..
         } = call;
         let rets = call_contract(
             &self.context.storage,
@@ -1387,6 +1388,9 @@ impl<'a> MoveTestAdapter<'a> for StarcoinTestAdapter<'a> {
                 function_id: name,
                 args,
                 type_args,
+                block_number: None,
+                block_hash: None,
+                state_root: None,
             }),
             StarcoinSubcommands::CallAPI { method, params } => self.handle_call_api(method, params),
             StarcoinSubcommands::Package {
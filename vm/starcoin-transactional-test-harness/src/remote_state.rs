@@ -239,7 +239,7 @@ impl RemoteRpcAsyncClient {
     ) -> VMResult<Option<BTreeMap<Identifier, Vec<u8>>>> {
         let state = self
             .state_client
-            .get_account_state_set(addr, Some(self.state_root))
+            .get_account_state_set(addr, Some(self.state_root), None, None)
             .await
             .map_err(|_| {
                 PartialVMError::new(StatusCode::STORAGE_ERROR).finish(Location::Undefined)
@@ -74,6 +74,24 @@ pub fn build_transfer_txn(
 pub fn build_batch_script_function(
     receivers: Vec<AccountAddress>,
     amounts: Vec<u128>,
+) -> ScriptFunction {
+    batch_script_function(receivers, amounts, stc_type_tag())
+}
+
+/// The batch transfer script function of the token, fails if the token code is not a struct tag.
+pub fn build_batch_script_function_by_token_code(
+    receivers: Vec<AccountAddress>,
+    amounts: Vec<u128>,
+    token_code: TokenCode,
+) -> Result<ScriptFunction> {
+    let token_type: TypeTag = token_code.try_into()?;
+    Ok(batch_script_function(receivers, amounts, token_type))
+}
+
+fn batch_script_function(
+    receivers: Vec<AccountAddress>,
+    amounts: Vec<u128>,
+    token_type: TypeTag,
 ) -> ScriptFunction {
     let addresses = MoveValue::vector_address(receivers);
     let amounts = MoveValue::Vector(amounts.into_iter().map(MoveValue::U128).collect());
@@ -83,7 +101,7 @@ pub fn build_batch_script_function(
             Identifier::new("TransferScripts").unwrap(),
         ),
         Identifier::new("batch_peer_to_peer_v2").unwrap(),
-        vec![stc_type_tag()],
+        vec![token_type],
         vec![
             bcs_ext::to_bytes(&addresses).unwrap(),
             bcs_ext::to_bytes(&amounts).unwrap(),
@@ -124,6 +142,33 @@ pub fn build_batch_transfer_txn(
     )
 }
 
+/// Transfer the `amounts` of the token to the `receivers` in a txn.
+pub fn build_batch_transfer_txn_by_token_type(
+    sender: AccountAddress,
+    receivers: Vec<AccountAddress>,
+    amounts: Vec<u128>,
+    seq_num: u64,
+    gas_price: u64,
+    max_gas: u64,
+    token_code: TokenCode,
+    expiration_timestamp_secs: u64,
+    chain_id: ChainId,
+) -> Result<RawUserTransaction> {
+    let payload = TransactionPayload::ScriptFunction(build_batch_script_function_by_token_code(
+        receivers, amounts, token_code,
+    )?);
+
+    Ok(RawUserTransaction::new_with_default_gas_token(
+        sender,
+        seq_num,
+        payload,
+        max_gas,
+        gas_price,
+        expiration_timestamp_secs,
+        chain_id,
+    ))
+}
+
 pub fn build_transfer_txn_by_token_type(
     sender: AccountAddress,
     receiver: AccountAddress,
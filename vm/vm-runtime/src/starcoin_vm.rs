@@ -19,7 +19,9 @@ use num_cpus;
 use once_cell::sync::OnceCell;
 use starcoin_config::genesis_config::G_LATEST_GAS_PARAMS;
 use starcoin_crypto::HashValue;
-use starcoin_gas::{NativeGasParameters, StarcoinGasMeter, StarcoinGasParameters};
+use starcoin_gas::{
+    CallTrace, GasProfile, NativeGasParameters, StarcoinGasMeter, StarcoinGasParameters,
+};
 use starcoin_gas_algebra_ext::{
     CostTable, FromOnChainGasSchedule, Gas, GasConstants, GasCost, InitialGasSchedule,
 };
@@ -906,10 +908,22 @@ impl StarcoinVM {
         storage: &S,
         txn: SignedUserTransaction,
     ) -> (VMStatus, TransactionOutput) {
+        let (status, output, _) =
+            self.execute_user_transaction_impl(storage, txn, ExecutionRecordOption::default());
+        (status, output)
+    }
+
+    fn execute_user_transaction_impl<S: MoveResolverExt + StateView>(
+        &self,
+        storage: &S,
+        txn: SignedUserTransaction,
+        record_option: ExecutionRecordOption,
+    ) -> (VMStatus, TransactionOutput, ExecutionRecord) {
         let txn_data = match TransactionMetadata::new(&txn) {
             Ok(txn_data) => txn_data,
             Err(e) => {
-                return discard_error_vm_status(e);
+                let (status, output) = discard_error_vm_status(e);
+                return (status, output, ExecutionRecord::default());
             }
         };
         let gas_params = match self.get_gas_parameters() {
@@ -918,7 +932,8 @@ impl StarcoinVM {
                 if storage.is_genesis() {
                     &G_LATEST_GAS_PARAMS
                 } else {
-                    return discard_error_vm_status(e);
+                    let (status, output) = discard_error_vm_status(e);
+                    return (status, output, ExecutionRecord::default());
                 }
             }
         };
@@ -929,13 +944,14 @@ impl StarcoinVM {
             .into();
         let mut gas_meter = StarcoinGasMeter::new(gas_params.clone(), txn_data.max_gas_amount());
         gas_meter.set_metering(false);
+        record_option.enable(&mut gas_meter, txn.payload());
         // check signature
         let signature_checked_txn = match txn.check_signature() {
             Ok(t) => Ok(t),
             Err(_) => Err(VMStatus::Error(StatusCode::INVALID_SIGNATURE)),
         };
 
-        match signature_checked_txn {
+        let (status, output) = match signature_checked_txn {
             Ok(txn) => {
                 let result = match txn.payload() {
                     payload @ TransactionPayload::Script(_)
@@ -972,7 +988,20 @@ impl StarcoinVM {
                 }
             }
             Err(e) => discard_error_vm_status(e),
-        }
+        };
+        (status, output, ExecutionRecord::take(&mut gas_meter))
+    }
+
+    /// Re-execute a user transaction with the call tree or the gas profile recorded,
+    /// `storage` should be the state before the transaction.
+    pub fn execute_user_transaction_with_record<S: MoveResolverExt + StateView>(
+        &mut self,
+        storage: &S,
+        txn: SignedUserTransaction,
+        record_option: ExecutionRecordOption,
+    ) -> Result<(VMStatus, TransactionOutput, ExecutionRecord)> {
+        self.load_configs(&storage)?;
+        Ok(self.execute_user_transaction_impl(storage, txn, record_option))
     }
 
     pub fn dry_run_transaction<S: MoveResolverExt + StateView>(
@@ -980,6 +1009,18 @@ impl StarcoinVM {
         storage: &S,
         txn: DryRunTransaction,
     ) -> Result<(VMStatus, TransactionOutput)> {
+        let (status, output, _) =
+            self.dry_run_transaction_with_record(storage, txn, ExecutionRecordOption::default())?;
+        Ok((status, output))
+    }
+
+    /// Dry run a transaction with the call tree or the gas profile recorded.
+    pub fn dry_run_transaction_with_record<S: MoveResolverExt + StateView>(
+        &mut self,
+        storage: &S,
+        txn: DryRunTransaction,
+        record_option: ExecutionRecordOption,
+    ) -> Result<(VMStatus, TransactionOutput, ExecutionRecord)> {
         // TODO load config by config change event.
         self.load_configs(&storage)?;
 
@@ -989,7 +1030,8 @@ impl StarcoinVM {
                 if storage.is_genesis() {
                     &G_LATEST_GAS_PARAMS
                 } else {
-                    return Ok(discard_error_vm_status(e));
+                    let (status, output) = discard_error_vm_status(e);
+                    return Ok((status, output, ExecutionRecord::default()));
                 }
             }
         };
@@ -998,7 +1040,10 @@ impl StarcoinVM {
             txn.public_key.authentication_key_preimage(),
         ) {
             Ok(txn_data) => txn_data,
-            Err(e) => return Ok(discard_error_vm_status(e)),
+            Err(e) => {
+                let (status, output) = discard_error_vm_status(e);
+                return Ok((status, output, ExecutionRecord::default()));
+            }
         };
         let session = self
             .move_vm
@@ -1006,6 +1051,7 @@ impl StarcoinVM {
             .into();
         let mut gas_meter = StarcoinGasMeter::new(gas_params.clone(), txn_data.max_gas_amount());
         gas_meter.set_metering(false);
+        record_option.enable(&mut gas_meter, txn.raw_txn.payload());
         let result = match txn.raw_txn.payload() {
             payload @ TransactionPayload::Script(_)
             | payload @ TransactionPayload::ScriptFunction(_) => {
@@ -1015,7 +1061,7 @@ impl StarcoinVM {
                 self.execute_package(session, &mut gas_meter, &txn_data, p, storage)
             }
         };
-        Ok(match result {
+        let (status, output) = match result {
             Ok(status_and_output) => status_and_output,
             Err(err) => {
                 let txn_status = TransactionStatus::from(err.clone());
@@ -1025,7 +1071,8 @@ impl StarcoinVM {
                     self.failed_transaction_cleanup(err, &mut gas_meter, &txn_data, storage)
                 }
             }
-        })
+        };
+        Ok((status, output, ExecutionRecord::take(&mut gas_meter)))
     }
 
     fn check_reconfigure<S: StateView>(
@@ -1411,6 +1458,82 @@ pub fn chunk_block_transactions(txns: Vec<Transaction>) -> Vec<TransactionBlock>
     blocks
 }
 
+/// What to record when executing a transaction, the unmetered prologue and epilogue are not recorded.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecutionRecordOption {
+    pub call_trace: bool,
+    pub gas_profile: bool,
+}
+
+impl ExecutionRecordOption {
+    fn enable(&self, gas_meter: &mut StarcoinGasMeter, payload: &TransactionPayload) {
+        if !self.call_trace && !self.gas_profile {
+            return;
+        }
+        let root = payload_call_trace(payload);
+        if self.gas_profile {
+            let root_name = match root.module.as_ref() {
+                Some(module_id) => GasProfile::frame_name(module_id, root.function.as_str()),
+                None => root.function.clone(),
+            };
+            gas_meter.enable_profile(root_name.as_str());
+        }
+        if self.call_trace {
+            gas_meter.enable_trace(root);
+        }
+    }
+}
+
+/// The records of a transaction execution, see `ExecutionRecordOption`.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionRecord {
+    pub call_trace: Option<CallTrace>,
+    pub gas_profile: Option<GasProfile>,
+}
+
+impl ExecutionRecord {
+    fn take(gas_meter: &mut StarcoinGasMeter) -> Self {
+        Self {
+            call_trace: gas_meter.take_trace(),
+            gas_profile: gas_meter.take_profile(),
+        }
+    }
+}
+
+/// The root frame of the call trace, the entry of the transaction is not charged as a call,
+/// so its arguments are the bcs encoded transaction arguments.
+fn payload_call_trace(payload: &TransactionPayload) -> CallTrace {
+    match payload {
+        TransactionPayload::Script(script) => CallTrace::new(
+            None,
+            "script".to_string(),
+            script.ty_args().to_vec(),
+            encode_args(script.args()),
+        ),
+        TransactionPayload::ScriptFunction(script_function) => CallTrace::new(
+            Some(script_function.module().clone()),
+            script_function.function().to_string(),
+            script_function.ty_args().to_vec(),
+            encode_args(script_function.args()),
+        ),
+        TransactionPayload::Package(package) => match package.init_script() {
+            Some(script_function) => CallTrace::new(
+                Some(script_function.module().clone()),
+                script_function.function().to_string(),
+                script_function.ty_args().to_vec(),
+                encode_args(script_function.args()),
+            ),
+            None => CallTrace::new(None, "package".to_string(), vec![], vec![]),
+        },
+    }
+}
+
+fn encode_args(args: &[Vec<u8>]) -> Vec<String> {
+    args.iter()
+        .map(|arg| format!("0x{}", hex::encode(arg)))
+        .collect()
+}
+
 pub(crate) fn charge_global_write_gas_usage<R: MoveResolverExt>(
     gas_meter: &mut StarcoinGasMeter,
     session: &SessionAdapter<R>,
@@ -1426,7 +1549,7 @@ pub(crate) fn charge_global_write_gas_usage<R: MoveResolverExt>(
         gas_meter.get_metering()
     );
     gas_meter
-        .deduct_gas(total_cost)
+        .charge_write_set_gas(total_cost)
         .map_err(|p_err| p_err.finish(Location::Undefined).into_vm_status())
 }
 